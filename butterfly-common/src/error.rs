@@ -29,6 +29,10 @@ pub enum Error {
     /// Network connectivity issues (timeout, connect failure). Generally
     /// retriable.
     NetworkError(String),
+
+    /// The destination filesystem can't hold the download. Both values
+    /// are in bytes.
+    InsufficientSpace { needed: u64, available: u64 },
 }
 
 impl Error {
@@ -51,6 +55,10 @@ impl fmt::Display for Error {
             Error::IoError(err) => write!(f, "I/O error: {err}"),
             Error::InvalidInput(msg) => write!(f, "Invalid input: {msg}"),
             Error::NetworkError(msg) => write!(f, "Network error: {msg}"),
+            Error::InsufficientSpace { needed, available } => write!(
+                f,
+                "Insufficient disk space: {needed} bytes needed, {available} bytes available"
+            ),
         }
     }
 }
@@ -94,5 +102,12 @@ mod tests {
         assert!(!Error::HttpError("500".into()).is_transient());
         assert!(!Error::SourceNotFound("zz".into()).is_transient());
         assert!(!Error::InvalidInput("bad".into()).is_transient());
+        assert!(
+            !Error::InsufficientSpace {
+                needed: 2,
+                available: 1
+            }
+            .is_transient()
+        );
     }
 }
@@ -0,0 +1,230 @@
+//! Credentials for Geofabrik's internal download server.
+//!
+//! `osm-internal.download.geofabrik.de` serves full-metadata extracts
+//! (user names, changeset ids) to logged-in OpenStreetMap users. Access
+//! is granted by a `gf_download_oauth` cookie that Geofabrik issues
+//! after an OSM OAuth login, e.g. via Geofabrik's
+//! `oauth_cookie_client.py`. butterfly-dl doesn't perform that login
+//! itself; it picks the cookie up from, in order:
+//!
+//! 1. `GEOFABRIK_COOKIE` — the cookie itself (`gf_download_oauth=…` or
+//!    just the value);
+//! 2. `GEOFABRIK_COOKIE_FILE` — a file holding it, such as the output
+//!    of `oauth_cookie_client.py`;
+//! 3. the config file `BUTTERFLY_DL_AUTH_CONFIG`, defaulting to
+//!    `$XDG_CONFIG_HOME/butterfly-dl/auth.toml` (or
+//!    `~/.config/butterfly-dl/auth.toml`):
+//!
+//! ```toml
+//! [geofabrik]
+//! cookie = "gf_download_oauth=login|2024-01-01|…"
+//! # or: cookie_file = "/home/me/.geofabrik-cookie.txt"
+//! ```
+//!
+//! The cookie is only ever sent to
+//! `SourceConfig::geofabrik_internal_base_url`.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use butterfly_common::{Error, Result};
+
+/// Name of the cookie Geofabrik's OAuth protector issues.
+pub const COOKIE_NAME: &str = "gf_download_oauth";
+
+/// Internal-server credentials: a `Cookie` header value.
+#[derive(Clone, PartialEq, Eq)]
+pub struct GeofabrikAuth {
+    cookie: String,
+}
+
+impl std::fmt::Debug for GeofabrikAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeofabrikAuth")
+            .field("cookie", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct AuthConfig {
+    geofabrik: Option<GeofabrikSection>,
+}
+
+#[derive(Deserialize)]
+struct GeofabrikSection {
+    cookie: Option<String>,
+    cookie_file: Option<PathBuf>,
+}
+
+impl GeofabrikAuth {
+    /// Build from a cookie string. Accepts a bare value, a
+    /// `name=value` pair, or a `Set-Cookie`-style line whose attributes
+    /// (`; expires=…; path=/`) are dropped.
+    pub fn from_cookie(cookie: &str) -> Result<Self> {
+        let pair = cookie.split(';').next().unwrap_or_default().trim();
+        if pair.is_empty() {
+            return Err(Error::InvalidInput("empty Geofabrik cookie".to_string()));
+        }
+        let cookie = if pair.contains('=') {
+            pair.to_string()
+        } else {
+            format!("{COOKIE_NAME}={pair}")
+        };
+        if reqwest::header::HeaderValue::from_str(&cookie).is_err() {
+            return Err(Error::InvalidInput(
+                "Geofabrik cookie contains characters not allowed in an HTTP header".to_string(),
+            ));
+        }
+        Ok(Self { cookie })
+    }
+
+    /// Read the cookie from a file: the first non-empty, non-comment
+    /// line is parsed with [`Self::from_cookie`].
+    pub fn from_cookie_file(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            Error::InvalidInput(format!("cannot read cookie file {}: {e}", path.display()))
+        })?;
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with('#'))
+            .ok_or_else(|| {
+                Error::InvalidInput(format!("cookie file {} is empty", path.display()))
+            })?;
+        Self::from_cookie(line)
+    }
+
+    /// Load credentials from a TOML config file (`[geofabrik]` with
+    /// `cookie` or `cookie_file`). `Ok(None)` when the file has no
+    /// `[geofabrik]` credentials.
+    pub fn from_config_file(path: &Path) -> Result<Option<Self>> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            Error::InvalidInput(format!("cannot read auth config {}: {e}", path.display()))
+        })?;
+        let config: AuthConfig = toml::from_str(&text).map_err(|e| {
+            Error::InvalidInput(format!("invalid auth config {}: {e}", path.display()))
+        })?;
+        match config.geofabrik {
+            Some(GeofabrikSection {
+                cookie: Some(cookie),
+                ..
+            }) => Self::from_cookie(&cookie).map(Some),
+            Some(GeofabrikSection {
+                cookie_file: Some(file),
+                ..
+            }) => {
+                // Relative cookie files are relative to the config file.
+                let file = match path.parent() {
+                    Some(dir) if file.is_relative() => dir.join(file),
+                    _ => file,
+                };
+                Self::from_cookie_file(&file).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Load credentials from the environment or the config file (see
+    /// the module docs for the lookup order). `Ok(None)` when nothing
+    /// is configured.
+    pub fn load() -> Result<Option<Self>> {
+        Self::load_with(|key| std::env::var(key).ok())
+    }
+
+    /// [`Self::load`] against an arbitrary variable lookup.
+    fn load_with(env: impl Fn(&str) -> Option<String>) -> Result<Option<Self>> {
+        if let Some(cookie) = env("GEOFABRIK_COOKIE") {
+            return Self::from_cookie(&cookie).map(Some);
+        }
+        if let Some(file) = env("GEOFABRIK_COOKIE_FILE") {
+            return Self::from_cookie_file(Path::new(&file)).map(Some);
+        }
+        let config = env("BUTTERFLY_DL_AUTH_CONFIG")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env("XDG_CONFIG_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| env("HOME").map(|home| Path::new(&home).join(".config")))
+                    .map(|dir| dir.join("butterfly-dl").join("auth.toml"))
+            });
+        match config {
+            Some(path) if path.exists() => Self::from_config_file(&path),
+            _ => Ok(None),
+        }
+    }
+
+    /// Value for the `Cookie` request header.
+    pub fn cookie_header(&self) -> &str {
+        &self.cookie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_cookie_forms() {
+        let bare = GeofabrikAuth::from_cookie("abc|def").unwrap();
+        assert_eq!(bare.cookie_header(), "gf_download_oauth=abc|def");
+
+        let set_cookie =
+            GeofabrikAuth::from_cookie("gf_download_oauth=abc; expires=Wed, 01 Jan 2031; path=/")
+                .unwrap();
+        assert_eq!(set_cookie.cookie_header(), "gf_download_oauth=abc");
+
+        assert!(GeofabrikAuth::from_cookie("  ").is_err());
+        assert!(GeofabrikAuth::from_cookie("a\nb").is_err());
+        assert!(!format!("{bare:?}").contains("abc"));
+    }
+
+    #[test]
+    fn test_load_lookup_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let cookie_file = dir.path().join("cookie.txt");
+        std::fs::write(
+            &cookie_file,
+            "# from oauth_cookie_client.py\ngf_download_oauth=file\n",
+        )
+        .unwrap();
+        let config_dir = dir.path().join("butterfly-dl");
+        std::fs::create_dir(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("auth.toml"),
+            "[geofabrik]\ncookie_file = \"../cookie.txt\"\n",
+        )
+        .unwrap();
+
+        let vars = |pairs: Vec<(&'static str, String)>| {
+            move |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.clone())
+            }
+        };
+        let xdg = dir.path().to_string_lossy().into_owned();
+
+        let auth = GeofabrikAuth::load_with(vars(vec![
+            ("GEOFABRIK_COOKIE", "env".into()),
+            ("XDG_CONFIG_HOME", xdg.clone()),
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(auth.cookie_header(), "gf_download_oauth=env");
+
+        let auth = GeofabrikAuth::load_with(vars(vec![("XDG_CONFIG_HOME", xdg)]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(auth.cookie_header(), "gf_download_oauth=file");
+
+        let empty = dir.path().join("nowhere").to_string_lossy().into_owned();
+        assert!(
+            GeofabrikAuth::load_with(vars(vec![("HOME", empty)]))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Machine-readable progress output for butterfly-dl
+//!
+//! Emits newline-delimited JSON events on stderr so CI systems and
+//! wrapper scripts can track a download without scraping the
+//! indicatif bar. One object per line, discriminated by `event`:
+//!
+//! ```text
+//! {"event":"start","source":"europe/monaco","dest":"monaco-latest.osm.pbf"}
+//! {"event":"progress","downloaded":1048576,"total":4194304,"elapsed_secs":1.0,"bytes_per_sec":1048576.0}
+//! {"event":"retry","attempt":1,"delay_ms":1000,"error":"..."}
+//! {"event":"done","bytes":4194304,"sha256":"ab12…","duration_secs":3.9}
+//! {"event":"error","message":"..."}
+//! ```
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// One NDJSON progress event.
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JsonEvent<'a> {
+    /// Download is about to start.
+    Start {
+        source: &'a str,
+        /// Destination path, or `"-"` for stdout.
+        dest: &'a str,
+    },
+    /// Periodic transfer status, throttled to the reporter interval.
+    Progress {
+        downloaded: u64,
+        total: u64,
+        elapsed_secs: f64,
+        bytes_per_sec: f64,
+    },
+    /// A network error is being retried after `delay_ms`.
+    Retry {
+        attempt: u32,
+        delay_ms: u64,
+        error: &'a str,
+    },
+    /// Download finished. `sha256` is `None` when the written file could
+    /// not be hashed.
+    Done {
+        bytes: u64,
+        sha256: Option<String>,
+        duration_secs: f64,
+    },
+    /// Download failed; the process exits non-zero right after.
+    Error { message: &'a str },
+}
+
+impl JsonEvent<'_> {
+    /// Serialize to a single line (no trailing newline).
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).expect("JsonEvent serialization is infallible")
+    }
+}
+
+/// Stateful reporter: owns the start instant and the throttle for
+/// `progress` events.
+pub struct JsonProgressReporter {
+    started: Instant,
+    interval: Duration,
+    last_emit: Mutex<Option<Instant>>,
+}
+
+impl JsonProgressReporter {
+    /// Create a reporter emitting `progress` at most once per `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            started: Instant::now(),
+            interval,
+            last_emit: Mutex::new(None),
+        }
+    }
+
+    /// Write one event to stderr.
+    pub fn emit(&self, event: &JsonEvent<'_>) {
+        eprintln!("{}", event.to_line());
+    }
+
+    /// Progress hook: emits a `progress` event when the interval has
+    /// elapsed since the last one. The terminal `downloaded == total`
+    /// call is left to [`Self::done`].
+    pub fn progress(&self, downloaded: u64, total: u64) {
+        if total > 0 && downloaded >= total {
+            return;
+        }
+        let now = Instant::now();
+        {
+            let mut last = self.last_emit.lock().expect("progress throttle poisoned");
+            if last.is_some_and(|t| now.duration_since(t) < self.interval) {
+                return;
+            }
+            *last = Some(now);
+        }
+        let elapsed = now.duration_since(self.started).as_secs_f64();
+        let bytes_per_sec = if elapsed > 0.0 {
+            downloaded as f64 / elapsed
+        } else {
+            0.0
+        };
+        self.emit(&JsonEvent::Progress {
+            downloaded,
+            total,
+            elapsed_secs: elapsed,
+            bytes_per_sec,
+        });
+    }
+
+    /// Emit the terminal `done` event.
+    pub fn done(&self, bytes: u64, sha256: Option<[u8; 32]>) {
+        self.emit(&JsonEvent::Done {
+            bytes,
+            sha256: sha256.map(hex::encode),
+            duration_secs: self.started.elapsed().as_secs_f64(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_lines_are_tagged() {
+        let line = JsonEvent::Start {
+            source: "europe/monaco",
+            dest: "-",
+        }
+        .to_line();
+        assert_eq!(
+            line,
+            r#"{"event":"start","source":"europe/monaco","dest":"-"}"#
+        );
+
+        let line = JsonEvent::Retry {
+            attempt: 2,
+            delay_ms: 2000,
+            error: "reset",
+        }
+        .to_line();
+        let v: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(v["event"], "retry");
+        assert_eq!(v["attempt"], 2);
+        assert_eq!(v["delay_ms"], 2000);
+
+        let line = JsonEvent::Done {
+            bytes: 10,
+            sha256: None,
+            duration_secs: 0.5,
+        }
+        .to_line();
+        let v: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(v["event"], "done");
+        assert!(v["sha256"].is_null());
+    }
+
+    #[test]
+    fn test_progress_throttle() {
+        let reporter = JsonProgressReporter::new(Duration::from_secs(3600));
+        reporter.progress(1, 100);
+        let first = *reporter.last_emit.lock().unwrap();
+        assert!(first.is_some());
+        // Second call inside the interval must not move the throttle.
+        reporter.progress(2, 100);
+        assert_eq!(*reporter.last_emit.lock().unwrap(), first);
+    }
+}
@@ -3,6 +3,8 @@
 //! This module contains code specific to the command-line interface,
 //! separate from the core library functionality.
 
+pub mod json_progress;
 pub mod progress;
 
+pub use json_progress::{JsonEvent, JsonProgressReporter};
 pub use progress::ProgressManager;
@@ -0,0 +1,1102 @@
+//! Object-storage destinations: `s3://bucket/key` and `gs://bucket/key`.
+//!
+//! Mirroring extracts straight into a bucket skips the local disk
+//! entirely: the source is streamed into an S3 multipart upload one
+//! part at a time, so memory use is bounded by the part size (16 MiB,
+//! grown for files that would exceed S3's 10 000-part limit).
+//!
+//! Google Cloud Storage is reached through its S3-compatible XML API,
+//! which supports the same multipart calls when authenticated with an
+//! HMAC key. Credentials and endpoints come from the environment:
+//!
+//! | | `s3://` | `gs://` |
+//! |---|---|---|
+//! | key id | `AWS_ACCESS_KEY_ID` | `GS_ACCESS_KEY_ID` |
+//! | secret | `AWS_SECRET_ACCESS_KEY` | `GS_SECRET_ACCESS_KEY` |
+//! | session token | `AWS_SESSION_TOKEN` | — |
+//! | region | `AWS_REGION` / `AWS_DEFAULT_REGION` (`us-east-1`) | `auto` |
+//! | endpoint | `AWS_ENDPOINT_URL_S3` / `AWS_ENDPOINT_URL` | `GS_ENDPOINT_URL` |
+//!
+//! Requests use path-style addressing (`<endpoint>/<bucket>/<key>`),
+//! which also works against MinIO and other S3-compatible stores.
+//!
+//! # Resume
+//!
+//! An interrupted transfer leaves its multipart upload open. With
+//! [`DownloadOptions::resume`] set, the next upload to the same key
+//! finds it, keeps the parts already stored and continues the source
+//! download from the matching byte offset with a Range request. An
+//! upload is only reused when the source's `Last-Modified` predates it;
+//! a newer source (Geofabrik republishes extracts daily) aborts the
+//! stale upload and starts over. With `resume` unset, a failed upload
+//! is aborted instead.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use crate::core::RemoteFile;
+use crate::core::downloader::{Downloader, confirm_overwrite, retry_on_network_error};
+use crate::{DownloadOptions, OverwriteBehavior};
+use butterfly_common::{Error, Result};
+
+/// Default multipart part size.
+pub const DEFAULT_PART_SIZE: u64 = 16 * 1024 * 1024;
+
+/// S3's limit on parts per multipart upload.
+const MAX_PARTS: u64 = 10_000;
+
+/// Consecutive source read failures tolerated (each reopens the
+/// source at the current offset) before giving up.
+const MAX_REOPENS: u32 = 5;
+
+/// Object-storage service behind a destination URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    /// Amazon S3 or an S3-compatible store (`s3://`).
+    S3,
+    /// Google Cloud Storage via its XML API (`gs://`).
+    Gcs,
+}
+
+/// A parsed `s3://` / `gs://` destination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectUrl {
+    /// Service the bucket lives in.
+    pub provider: Provider,
+    /// Bucket name.
+    pub bucket: String,
+    /// Object key (no leading `/`).
+    pub key: String,
+}
+
+/// Whether `dest` names an object-storage destination rather than a
+/// local path.
+pub fn is_object_url(dest: &str) -> bool {
+    dest.starts_with("s3://") || dest.starts_with("gs://")
+}
+
+impl ObjectUrl {
+    /// Parse `s3://bucket/key` or `gs://bucket/key`.
+    pub fn parse(dest: &str) -> Result<Self> {
+        let (provider, rest) = if let Some(rest) = dest.strip_prefix("s3://") {
+            (Provider::S3, rest)
+        } else if let Some(rest) = dest.strip_prefix("gs://") {
+            (Provider::Gcs, rest)
+        } else {
+            return Err(Error::InvalidInput(format!(
+                "not an object-storage URL: {dest}"
+            )));
+        };
+        match rest.split_once('/') {
+            Some((bucket, key)) if !bucket.is_empty() && !key.is_empty() => Ok(Self {
+                provider,
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+            }),
+            _ => Err(Error::InvalidInput(format!(
+                "object-storage URL needs a bucket and a key: {dest}"
+            ))),
+        }
+    }
+}
+
+/// HMAC access key pair.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// Access key id.
+    pub access_key_id: String,
+    /// Secret access key.
+    pub secret_access_key: String,
+    /// Temporary-credential session token (`x-amz-security-token`).
+    pub session_token: Option<String>,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"<redacted>")
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+/// One stored part of a multipart upload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Part {
+    number: u32,
+    etag: String,
+    size: u64,
+}
+
+/// An open multipart upload found for a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OpenUpload {
+    upload_id: String,
+    initiated: Option<DateTime<Utc>>,
+}
+
+/// S3 multipart-upload client for one endpoint.
+#[derive(Debug, Clone)]
+pub struct ObjectStore {
+    client: Client,
+    endpoint: String,
+    region: String,
+    credentials: Credentials,
+    /// Minimum part size; grown per upload to stay under 10 000 parts.
+    pub part_size: u64,
+}
+
+impl ObjectStore {
+    /// Client for `endpoint` (scheme, host and optional port) signing
+    /// for `region`.
+    pub fn new(endpoint: &str, region: &str, credentials: Credentials) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            region: region.to_string(),
+            credentials,
+            part_size: DEFAULT_PART_SIZE,
+        }
+    }
+
+    /// Client for `provider` configured from the environment (see the
+    /// module docs).
+    pub fn from_env(provider: Provider) -> Result<Self> {
+        Self::from_env_with(provider, |key| std::env::var(key).ok())
+    }
+
+    /// [`Self::from_env`] against an arbitrary variable lookup.
+    fn from_env_with(provider: Provider, env: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let (id_var, secret_var) = match provider {
+            Provider::S3 => ("AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY"),
+            Provider::Gcs => ("GS_ACCESS_KEY_ID", "GS_SECRET_ACCESS_KEY"),
+        };
+        let (Some(access_key_id), Some(secret_access_key)) = (env(id_var), env(secret_var)) else {
+            return Err(Error::InvalidInput(format!(
+                "object-storage upload needs {id_var} and {secret_var}"
+            )));
+        };
+        let credentials = Credentials {
+            access_key_id,
+            secret_access_key,
+            session_token: match provider {
+                Provider::S3 => env("AWS_SESSION_TOKEN"),
+                Provider::Gcs => None,
+            },
+        };
+        let (endpoint, region) = match provider {
+            Provider::S3 => {
+                let region = env("AWS_REGION")
+                    .or_else(|| env("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|| "us-east-1".to_string());
+                let endpoint = env("AWS_ENDPOINT_URL_S3")
+                    .or_else(|| env("AWS_ENDPOINT_URL"))
+                    .unwrap_or_else(|| format!("https://s3.{region}.amazonaws.com"));
+                (endpoint, region)
+            }
+            Provider::Gcs => (
+                env("GS_ENDPOINT_URL")
+                    .unwrap_or_else(|| "https://storage.googleapis.com".to_string()),
+                "auto".to_string(),
+            ),
+        };
+        Ok(Self::new(&endpoint, &region, credentials))
+    }
+
+    /// Part size for an object of `total_size` bytes.
+    fn part_size_for(&self, total_size: u64) -> u64 {
+        const MIB: u64 = 1024 * 1024;
+        let needed = total_size.div_ceil(MAX_PARTS);
+        if needed <= self.part_size {
+            self.part_size
+        } else {
+            needed.div_ceil(MIB) * MIB
+        }
+    }
+
+    /// Send a signed request for `bucket/key` (the bucket itself when
+    /// `key` is empty) and fail on non-2xx.
+    async fn send(
+        &self,
+        method: Method,
+        bucket: &str,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Bytes,
+    ) -> Result<reqwest::Response> {
+        let response = self
+            .send_raw(method.clone(), bucket, key, query, body)
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            let code = xml_text(&detail, "Code").unwrap_or_default();
+            return Err(Error::HttpError(format!(
+                "{method} {bucket}/{key} returned HTTP {status} {code}"
+            )));
+        }
+        Ok(response)
+    }
+
+    /// Sign and send a request, whatever its status.
+    async fn send_raw(
+        &self,
+        method: Method,
+        bucket: &str,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Bytes,
+    ) -> Result<reqwest::Response> {
+        let host_url = reqwest::Url::parse(&self.endpoint)
+            .map_err(|e| Error::InvalidInput(format!("bad endpoint {}: {e}", self.endpoint)))?;
+        let host = match (host_url.host_str(), host_url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "bad endpoint {}",
+                    self.endpoint
+                )));
+            }
+        };
+        let mut path = format!(
+            "{}/{}",
+            host_url.path().trim_end_matches('/'),
+            uri_encode(bucket, false)
+        );
+        if !key.is_empty() {
+            path.push('/');
+            path.push_str(&uri_encode(key, true));
+        }
+        let mut query: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, false), uri_encode(v, false)))
+            .collect();
+        query.sort();
+        let query = query
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let authorization = CanonicalRequest {
+            method: method.as_str(),
+            path: &path,
+            query: &query,
+            headers: &headers,
+            payload_hash: &payload_hash,
+        }
+        .authorization(&self.credentials, &amz_date, &self.region, "s3");
+
+        let origin = host_url.origin().ascii_serialization();
+        let url = if query.is_empty() {
+            format!("{origin}{path}")
+        } else {
+            format!("{origin}{path}?{query}")
+        };
+        let mut request = self
+            .client
+            .request(method.clone(), &url)
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .body(body);
+        // `host` is set by reqwest from the URL.
+        for (name, value) in &headers[1..] {
+            request = request.header(*name, value);
+        }
+        Ok(request.send().await?)
+    }
+
+    /// Whether `bucket/key` already exists.
+    async fn exists(&self, bucket: &str, key: &str) -> Result<bool> {
+        let response = self
+            .send_raw(Method::HEAD, bucket, key, &[], Bytes::new())
+            .await?;
+        match response.status() {
+            status if status.is_success() => Ok(true),
+            reqwest::StatusCode::NOT_FOUND => Ok(false),
+            status => Err(Error::HttpError(format!(
+                "HEAD {bucket}/{key} returned HTTP {status}"
+            ))),
+        }
+    }
+
+    async fn create_upload(&self, bucket: &str, key: &str) -> Result<String> {
+        let body = self
+            .send(Method::POST, bucket, key, &[("uploads", "")], Bytes::new())
+            .await?
+            .text()
+            .await?;
+        xml_text(&body, "UploadId").ok_or_else(|| {
+            Error::HttpError(format!(
+                "CreateMultipartUpload for {key} returned no UploadId"
+            ))
+        })
+    }
+
+    /// The most recently initiated open upload of exactly `key`.
+    async fn find_upload(&self, bucket: &str, key: &str) -> Result<Option<OpenUpload>> {
+        let body = self
+            .send(
+                Method::GET,
+                bucket,
+                "",
+                &[("uploads", ""), ("prefix", key)],
+                Bytes::new(),
+            )
+            .await?
+            .text()
+            .await?;
+        Ok(xml_records(&body, "Upload")
+            .into_iter()
+            .filter(|upload| upload.get("Key").map(String::as_str) == Some(key))
+            .filter_map(|mut upload| {
+                Some(OpenUpload {
+                    upload_id: upload.remove("UploadId")?,
+                    initiated: upload
+                        .get("Initiated")
+                        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                        .map(|t| t.with_timezone(&Utc)),
+                })
+            })
+            .max_by_key(|upload| upload.initiated))
+    }
+
+    async fn list_parts(&self, bucket: &str, key: &str, upload_id: &str) -> Result<Vec<Part>> {
+        let mut parts = Vec::new();
+        let mut marker = String::from("0");
+        loop {
+            let body = self
+                .send(
+                    Method::GET,
+                    bucket,
+                    key,
+                    &[("uploadId", upload_id), ("part-number-marker", &marker)],
+                    Bytes::new(),
+                )
+                .await?
+                .text()
+                .await?;
+            parts.extend(xml_records(&body, "Part").into_iter().filter_map(|part| {
+                Some(Part {
+                    number: part.get("PartNumber")?.parse().ok()?,
+                    etag: part.get("ETag")?.clone(),
+                    size: part.get("Size")?.parse().ok()?,
+                })
+            }));
+            match xml_text(&body, "NextPartNumberMarker") {
+                Some(next) if xml_text(&body, "IsTruncated").as_deref() == Some("true") => {
+                    marker = next
+                }
+                _ => break,
+            }
+        }
+        parts.sort_by_key(|p| p.number);
+        Ok(parts)
+    }
+
+    async fn upload_part(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        number: u32,
+        body: Bytes,
+    ) -> Result<String> {
+        let number = number.to_string();
+        let response = self
+            .send(
+                Method::PUT,
+                bucket,
+                key,
+                &[("partNumber", &number), ("uploadId", upload_id)],
+                body,
+            )
+            .await?;
+        response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned)
+            .ok_or_else(|| {
+                Error::HttpError(format!("UploadPart {number} of {key} returned no ETag"))
+            })
+    }
+
+    async fn complete(
+        &self,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &[Part],
+    ) -> Result<()> {
+        let mut xml = String::from("<CompleteMultipartUpload>");
+        for part in parts {
+            xml.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part.number,
+                quick_xml::escape::escape(part.etag.as_str())
+            ));
+        }
+        xml.push_str("</CompleteMultipartUpload>");
+        let body = self
+            .send(
+                Method::POST,
+                bucket,
+                key,
+                &[("uploadId", upload_id)],
+                Bytes::from(xml),
+            )
+            .await?
+            .text()
+            .await?;
+        // S3 may report a failed completion in a 200 response body.
+        if let Some(code) = xml_text(&body, "Code") {
+            return Err(Error::HttpError(format!(
+                "CompleteMultipartUpload for {key} failed: {code}"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn abort(&self, bucket: &str, key: &str, upload_id: &str) -> Result<()> {
+        self.send(
+            Method::DELETE,
+            bucket,
+            key,
+            &[("uploadId", upload_id)],
+            Bytes::new(),
+        )
+        .await
+        .map(drop)
+    }
+
+    /// Stream `source` into `bucket/key` as a multipart upload (see
+    /// the module docs for resume behaviour).
+    pub async fn upload(
+        &self,
+        downloader: &Downloader,
+        source: &str,
+        bucket: &str,
+        key: &str,
+        options: &DownloadOptions,
+    ) -> Result<RemoteFile> {
+        let (url, head) = downloader.resolve_remote(source, options).await?;
+        // Same rules as a local file; `Force` skips the HEAD so write-only
+        // credentials keep working.
+        if options.overwrite != OverwriteBehavior::Force && self.exists(bucket, key).await? {
+            confirm_overwrite("object", &format!("{bucket}/{key}"), &options.overwrite)?;
+        }
+
+        let mut part_size = self.part_size_for(head.size);
+        let mut parts = Vec::new();
+        let mut resumed = None;
+        if options.resume
+            && head.supports_ranges
+            && let Some(open) = self.find_upload(bucket, key).await?
+        {
+            let source_modified = head
+                .last_modified
+                .as_deref()
+                .and_then(|lm| DateTime::parse_from_rfc2822(lm).ok());
+            match (source_modified, open.initiated) {
+                (Some(modified), Some(initiated)) if modified <= initiated => {
+                    let stored = self.list_parts(bucket, key, &open.upload_id).await?;
+                    if let Some(first) = stored.first() {
+                        part_size = part_size.max(first.size);
+                    }
+                    parts = resumable_prefix(stored, part_size, head.size);
+                    resumed = Some(open.upload_id);
+                }
+                _ => {
+                    tracing::info!(upload_id = %open.upload_id, "aborting stale multipart upload");
+                    self.abort(bucket, key, &open.upload_id).await?;
+                }
+            }
+        }
+        let upload_id = match resumed {
+            Some(upload_id) => upload_id,
+            None => self.create_upload(bucket, key).await?,
+        };
+        let offset: u64 = parts.iter().map(|p| p.size).sum();
+        if offset > 0 {
+            tracing::info!(offset, "resuming multipart upload");
+            eprintln!(
+                "↩️  Resuming {bucket}/{key} at {offset} of {} bytes",
+                head.size
+            );
+        }
+
+        let result = self
+            .upload_parts(
+                downloader, &url, head.size, part_size, bucket, key, &upload_id, &mut parts,
+                options,
+            )
+            .await;
+        let result = match result {
+            Ok(()) => self.complete(bucket, key, &upload_id, &parts).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            if !options.resume
+                && let Err(abort_error) = self.abort(bucket, key, &upload_id).await
+            {
+                tracing::warn!(error = %abort_error, "failed to abort multipart upload");
+            }
+            return Err(e);
+        }
+
+        Ok(RemoteFile {
+            url,
+            etag: head.etag,
+            size: head.size,
+        })
+    }
+
+    /// Read the source from the end of `parts` on and upload the rest.
+    #[allow(clippy::too_many_arguments)]
+    async fn upload_parts(
+        &self,
+        downloader: &Downloader,
+        url: &str,
+        total_size: u64,
+        part_size: u64,
+        bucket: &str,
+        key: &str,
+        upload_id: &str,
+        parts: &mut Vec<Part>,
+        options: &DownloadOptions,
+    ) -> Result<()> {
+        let mut offset: u64 = parts.iter().map(|p| p.size).sum();
+        let mut stream = None;
+        let mut reopens = 0;
+        while offset < total_size || parts.is_empty() {
+            let want = part_size.min(total_size - offset) as usize;
+            let mut buffer = Vec::with_capacity(want);
+            while buffer.len() < want {
+                let reader = match &mut stream {
+                    Some(reader) => reader,
+                    None => stream.insert(
+                        downloader
+                            .open_at(url, offset + buffer.len() as u64)
+                            .await?,
+                    ),
+                };
+                let mut chunk = AsyncReadExt::take(reader, (want - buffer.len()) as u64);
+                match chunk.read_to_end(&mut buffer).await {
+                    Ok(0) => {
+                        return Err(Error::NetworkError(format!(
+                            "{url} ended at {} of {total_size} bytes",
+                            offset + buffer.len() as u64
+                        )));
+                    }
+                    Ok(_) => reopens = 0,
+                    Err(e) if reopens < MAX_REOPENS => {
+                        reopens += 1;
+                        tracing::warn!(error = %e, attempt = reopens, "source read failed, reopening");
+                        stream = None;
+                    }
+                    Err(e) => {
+                        return Err(Error::NetworkError(format!("Stream read error: {e}")));
+                    }
+                }
+            }
+
+            let number = parts.len() as u32 + 1;
+            let body = Bytes::from(buffer);
+            let size = body.len() as u64;
+            let etag = retry_on_network_error(options.retry.as_ref(), || {
+                self.upload_part(bucket, key, upload_id, number, body.clone())
+            })
+            .await?;
+            parts.push(Part { number, etag, size });
+            offset += size;
+            if let Some(progress) = &options.progress {
+                progress(offset, total_size);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stream `source` into the object at `dest`, configured from the
+/// environment. See the module docs.
+pub async fn upload(
+    downloader: &Downloader,
+    source: &str,
+    dest: &ObjectUrl,
+    options: &DownloadOptions,
+) -> Result<RemoteFile> {
+    ObjectStore::from_env(dest.provider)?
+        .upload(downloader, source, &dest.bucket, &dest.key, options)
+        .await
+}
+
+/// The leading parts of `stored` that line up with a fresh upload of
+/// `total_size` bytes in `part_size` parts: numbered 1, 2, … with
+/// every part full-size except possibly the very last part of the file.
+fn resumable_prefix(stored: Vec<Part>, part_size: u64, total_size: u64) -> Vec<Part> {
+    let mut offset = 0;
+    stored
+        .into_iter()
+        .enumerate()
+        .take_while(|(i, part)| {
+            let fits = part.number as usize == i + 1
+                && (part.size == part_size || offset + part.size == total_size)
+                && offset + part.size <= total_size;
+            offset += part.size;
+            fits
+        })
+        .map(|(_, part)| part)
+        .collect()
+}
+
+/// An AWS Signature Version 4 canonical request.
+struct CanonicalRequest<'a> {
+    method: &'a str,
+    /// URI-encoded path.
+    path: &'a str,
+    /// Sorted, URI-encoded query string.
+    query: &'a str,
+    /// Headers to sign, lowercase names, sorted by name.
+    headers: &'a [(&'a str, String)],
+    /// Hex SHA-256 of the body.
+    payload_hash: &'a str,
+}
+
+impl CanonicalRequest<'_> {
+    /// `Authorization` header value for this request at `amz_date`
+    /// (`YYYYMMDDTHHMMSSZ`).
+    fn authorization(
+        &self,
+        credentials: &Credentials,
+        amz_date: &str,
+        region: &str,
+        service: &str,
+    ) -> String {
+        let canonical_headers: String = self
+            .headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let signed_headers = self
+            .headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical = format!(
+            "{}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+            self.method, self.path, self.query, self.payload_hash
+        );
+
+        let date = &amz_date[..8];
+        let scope = format!("{date}/{region}/{service}/aws4_request");
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical.as_bytes()))
+        );
+        let key = [date, region, service, "aws4_request"].iter().fold(
+            format!("AWS4{}", credentials.secret_access_key).into_bytes(),
+            |key, part| hmac_sha256(&key, part.as_bytes()),
+        );
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id
+        )
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, key);
+    ring::hmac::sign(&key, data).as_ref().to_vec()
+}
+
+/// SigV4 URI encoding: everything but `A-Za-z0-9-_.~` (and `/` when
+/// `keep_slash`) is percent-encoded.
+fn uri_encode(s: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Text of every leaf element inside each `<parent>` element of `xml`,
+/// keyed by element name.
+fn xml_records(xml: &str, parent: &str) -> Vec<HashMap<String, String>> {
+    use quick_xml::events::Event;
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut records = Vec::new();
+    let mut record: Option<HashMap<String, String>> = None;
+    let mut field: Option<(String, String)> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).into_owned();
+                if name == parent {
+                    record = Some(HashMap::new());
+                } else if record.is_some() {
+                    field = Some((name, String::new()));
+                }
+            }
+            Ok(Event::Text(t)) => {
+                if let (Some((_, text)), Ok(decoded)) = (&mut field, t.decode()) {
+                    text.push_str(&decoded);
+                }
+            }
+            Ok(Event::GeneralRef(r)) => {
+                if let Some((_, text)) = &mut field {
+                    match r.resolve_char_ref() {
+                        Ok(Some(c)) => text.push(c),
+                        _ => {
+                            let name = r.decode().unwrap_or(Cow::Borrowed(""));
+                            text.push_str(
+                                quick_xml::escape::resolve_predefined_entity(&name)
+                                    .unwrap_or_default(),
+                            );
+                        }
+                    }
+                }
+            }
+            Ok(Event::End(e)) => {
+                let name = e.local_name();
+                if name.as_ref() == parent.as_bytes() {
+                    records.extend(record.take());
+                } else if let (Some(record), Some((name, text))) = (&mut record, field.take()) {
+                    record.insert(name, text);
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    records
+}
+
+/// Text of the first `<tag>` element anywhere in `xml`.
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    let wrapped = format!("<r>{xml}</r>");
+    xml_records(&wrapped, "r")
+        .into_iter()
+        .next()
+        .and_then(|mut fields| fields.remove(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_object_url() {
+        assert_eq!(
+            ObjectUrl::parse("s3://osm-mirror/europe/monaco.osm.pbf").unwrap(),
+            ObjectUrl {
+                provider: Provider::S3,
+                bucket: "osm-mirror".to_string(),
+                key: "europe/monaco.osm.pbf".to_string(),
+            }
+        );
+        assert_eq!(
+            ObjectUrl::parse("gs://b/k").unwrap().provider,
+            Provider::Gcs
+        );
+        assert!(ObjectUrl::parse("s3://bucket-only").is_err());
+        assert!(ObjectUrl::parse("s3:///key").is_err());
+        assert!(is_object_url("gs://b/k") && !is_object_url("out/s3.pbf"));
+    }
+
+    /// `get-vanilla` from the AWS SigV4 test suite.
+    #[test]
+    fn test_sigv4_test_vector() {
+        let credentials = Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let headers = [
+            ("host", "example.amazonaws.com".to_string()),
+            ("x-amz-date", "20150830T123600Z".to_string()),
+        ];
+        let authorization = CanonicalRequest {
+            method: "GET",
+            path: "/",
+            query: "",
+            headers: &headers,
+            payload_hash: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        }
+        .authorization(&credentials, "20150830T123600Z", "us-east-1", "service");
+        assert_eq!(
+            authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert!(!format!("{credentials:?}").contains("wJalr"));
+    }
+
+    #[test]
+    fn test_xml_records_unescapes_etags() {
+        let xml = r#"<ListPartsResult><IsTruncated>false</IsTruncated>
+            <Part><PartNumber>1</PartNumber><ETag>&quot;abc&quot;</ETag><Size>5</Size></Part>
+            <Part><PartNumber>2</PartNumber><ETag>"def"</ETag><Size>3</Size></Part>
+            </ListPartsResult>"#;
+        let parts = xml_records(xml, "Part");
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0]["ETag"], "\"abc\"");
+        assert_eq!(parts[1]["Size"], "3");
+        assert_eq!(xml_text(xml, "IsTruncated").as_deref(), Some("false"));
+    }
+
+    #[test]
+    fn test_part_sizing_and_resumable_prefix() {
+        let store = ObjectStore::new(
+            "http://localhost:9000",
+            "us-east-1",
+            Credentials {
+                access_key_id: "id".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            },
+        );
+        assert_eq!(store.part_size_for(1024), DEFAULT_PART_SIZE);
+        // ~80 GB planet: parts grow past 16 MiB to stay under 10 000.
+        let planet: u64 = 80 * 1024 * 1024 * 1024;
+        assert!(planet.div_ceil(store.part_size_for(planet)) <= MAX_PARTS);
+
+        let part = |number, size| Part {
+            number,
+            etag: format!("\"{number}\""),
+            size,
+        };
+        let mib = 1024 * 1024;
+        let total = 20 * mib;
+        // A gap at part 3 stops the prefix.
+        let stored = vec![part(1, 8 * mib), part(2, 8 * mib), part(4, 4 * mib)];
+        assert_eq!(resumable_prefix(stored, 8 * mib, total).len(), 2);
+        // A short part that isn't the file's tail stops it too.
+        let stored = vec![part(1, 8 * mib), part(2, mib)];
+        assert_eq!(resumable_prefix(stored, 8 * mib, total).len(), 1);
+        let stored = vec![part(1, 8 * mib), part(2, 8 * mib), part(3, 4 * mib)];
+        assert_eq!(resumable_prefix(stored, 8 * mib, total).len(), 3);
+    }
+
+    /// An open upload holding part 1 is resumed: the source is read
+    /// from the part boundary and the remaining parts complete it.
+    #[tokio::test]
+    async fn test_resumes_open_multipart_upload() {
+        use wiremock::matchers::{body_string_contains, method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let origin = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/monaco.pbf"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "10")
+                    .insert_header("accept-ranges", "bytes")
+                    .insert_header("last-modified", "Wed, 01 Jan 2025 00:00:00 GMT"),
+            )
+            .mount(&origin)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/monaco.pbf"))
+            .and(wiremock::matchers::header("range", "bytes=4-"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .set_body_raw(b"efghij".to_vec(), "application/octet-stream"),
+            )
+            .expect(1)
+            .mount(&origin)
+            .await;
+
+        let s3 = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/bucket"))
+            .and(query_param("uploads", ""))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<ListMultipartUploadsResult><Upload><Key>extracts/monaco.pbf</Key>\
+                 <UploadId>up1</UploadId><Initiated>2025-06-01T00:00:00.000Z</Initiated>\
+                 </Upload></ListMultipartUploadsResult>",
+            ))
+            .mount(&s3)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/bucket/extracts/monaco.pbf"))
+            .and(query_param("uploadId", "up1"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(
+                "<ListPartsResult><IsTruncated>false</IsTruncated><Part>\
+                 <PartNumber>1</PartNumber><ETag>&quot;p1&quot;</ETag><Size>4</Size>\
+                 </Part></ListPartsResult>",
+            ))
+            .mount(&s3)
+            .await;
+        for (number, body) in [("2", "efgh"), ("3", "ij")] {
+            Mock::given(method("PUT"))
+                .and(path("/bucket/extracts/monaco.pbf"))
+                .and(query_param("partNumber", number))
+                .and(query_param("uploadId", "up1"))
+                .and(wiremock::matchers::body_string(body))
+                .and(wiremock::matchers::header_exists("authorization"))
+                .respond_with(
+                    ResponseTemplate::new(200).insert_header("etag", format!("\"p{number}\"")),
+                )
+                .expect(1)
+                .mount(&s3)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/bucket/extracts/monaco.pbf"))
+            .and(query_param("uploadId", "up1"))
+            .and(body_string_contains(
+                "<Part><PartNumber>3</PartNumber><ETag>&quot;p3&quot;</ETag></Part>",
+            ))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_string("<CompleteMultipartUploadResult/>"),
+            )
+            .expect(1)
+            .mount(&s3)
+            .await;
+
+        let mut store = ObjectStore::new(
+            &s3.uri(),
+            "us-east-1",
+            Credentials {
+                access_key_id: "id".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            },
+        );
+        store.part_size = 4;
+        let remote = store
+            .upload(
+                &Downloader::new(),
+                &format!("{}/monaco.pbf", origin.uri()),
+                "bucket",
+                "extracts/monaco.pbf",
+                &DownloadOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(remote.size, 10);
+    }
+
+    /// An existing object is not replaced without `--force`: under
+    /// `NeverOverwrite` the upload fails before any multipart call.
+    #[tokio::test]
+    async fn test_existing_object_is_not_overwritten() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let origin = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/monaco.pbf"))
+            .respond_with(ResponseTemplate::new(200).insert_header("content-length", "10"))
+            .mount(&origin)
+            .await;
+
+        let s3 = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/bucket/monaco.pbf"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&s3)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&s3)
+            .await;
+
+        let store = ObjectStore::new(
+            &s3.uri(),
+            "us-east-1",
+            Credentials {
+                access_key_id: "id".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            },
+        );
+        let err = store
+            .upload(
+                &Downloader::new(),
+                &format!("{}/monaco.pbf", origin.uri()),
+                "bucket",
+                "monaco.pbf",
+                &DownloadOptions {
+                    overwrite: OverwriteBehavior::NeverOverwrite,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, Error::IoError(e) if e.kind() == std::io::ErrorKind::AlreadyExists),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_from_env() {
+        let env = |pairs: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert!(ObjectStore::from_env_with(Provider::S3, env(&[])).is_err());
+
+        let s3 = ObjectStore::from_env_with(
+            Provider::S3,
+            env(&[
+                ("AWS_ACCESS_KEY_ID", "id"),
+                ("AWS_SECRET_ACCESS_KEY", "secret"),
+                ("AWS_REGION", "eu-west-1"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(s3.endpoint, "https://s3.eu-west-1.amazonaws.com");
+        assert_eq!(s3.region, "eu-west-1");
+
+        let gcs = ObjectStore::from_env_with(
+            Provider::Gcs,
+            env(&[
+                ("GS_ACCESS_KEY_ID", "id"),
+                ("GS_SECRET_ACCESS_KEY", "secret"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(gcs.endpoint, "https://storage.googleapis.com");
+        assert_eq!(gcs.region, "auto");
+    }
+}
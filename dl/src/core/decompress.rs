@@ -0,0 +1,125 @@
+//! Transparent decompression for download streams
+//!
+//! An adapter over [`DownloadStream`] that sniffs the first bytes of
+//! the body and, when they carry a bzip2 (`BZh`) or gzip (`1f 8b`)
+//! magic, wraps the stream in the matching streaming decoder. Callers
+//! of `get_stream` then read raw OSM XML / PBF bytes regardless of how
+//! the artifact was compressed on the server.
+//!
+//! The decoders are pull-based `AsyncBufRead` adapters: nothing is read
+//! from the network until the consumer polls for decoded bytes, so the
+//! backpressure of the underlying HTTP stream is preserved end to end.
+//! Multi-member input (pbzip2-style planet `.osm.bz2`, concatenated
+//! gzip) is decoded as a single logical stream.
+
+use async_compression::tokio::bufread::{BzDecoder, GzipDecoder};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::core::stream::DownloadStream;
+
+/// Compression detected from the stream's leading bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// No recognised magic: bytes pass through untouched.
+    None,
+    /// bzip2 (`BZh` + block size digit).
+    Bzip2,
+    /// gzip (`1f 8b`).
+    Gzip,
+}
+
+impl Compression {
+    /// Detect compression from a stream prefix.
+    pub fn sniff(prefix: &[u8]) -> Self {
+        match prefix {
+            [b'B', b'Z', b'h', level, ..] if level.is_ascii_digit() => Compression::Bzip2,
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Buffer size for the peek/decoder reader; matches the default
+/// download buffer.
+const BUF_SIZE: usize = 64 * 1024;
+
+/// Wrap `stream` in a decoder when its body is bzip2 or gzip.
+///
+/// Peeks the first buffer of the body (one read from the network) to
+/// detect the format; the peeked bytes are not consumed, so the
+/// returned stream always starts at byte 0 of the (decoded) payload.
+pub async fn decompress_stream(stream: DownloadStream) -> std::io::Result<DownloadStream> {
+    let mut reader = BufReader::with_capacity(BUF_SIZE, stream);
+    let compression = Compression::sniff(reader.fill_buf().await?);
+    tracing::debug!(?compression, "stream compression detected");
+    Ok(match compression {
+        Compression::None => DownloadStream::Http(Box::new(reader)),
+        Compression::Bzip2 => {
+            let mut decoder = BzDecoder::new(reader);
+            decoder.multiple_members(true);
+            DownloadStream::Http(Box::new(decoder))
+        }
+        Compression::Gzip => {
+            let mut decoder = GzipDecoder::new(reader);
+            decoder.multiple_members(true);
+            DownloadStream::Http(Box::new(decoder))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_compression::tokio::write::{BzEncoder, GzipEncoder};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn stream_of(bytes: Vec<u8>) -> DownloadStream {
+        DownloadStream::Http(Box::new(std::io::Cursor::new(bytes)))
+    }
+
+    async fn read_all(stream: DownloadStream) -> Vec<u8> {
+        let mut out = Vec::new();
+        decompress_stream(stream)
+            .await
+            .unwrap()
+            .read_to_end(&mut out)
+            .await
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(Compression::sniff(b"BZh91AY&SY"), Compression::Bzip2);
+        assert_eq!(Compression::sniff(&[0x1f, 0x8b, 8, 0]), Compression::Gzip);
+        assert_eq!(Compression::sniff(b"<?xml"), Compression::None);
+        assert_eq!(Compression::sniff(b"BZ"), Compression::None);
+        assert_eq!(Compression::sniff(b""), Compression::None);
+    }
+
+    #[tokio::test]
+    async fn test_decompresses_bzip2_and_gzip() {
+        let xml = b"<?xml version='1.0'?><osm version=\"0.6\"></osm>".repeat(100);
+
+        let mut bz = BzEncoder::new(Vec::new());
+        bz.write_all(&xml).await.unwrap();
+        bz.shutdown().await.unwrap();
+        assert_eq!(read_all(stream_of(bz.into_inner())).await, xml);
+
+        // Two concatenated gzip members decode as one stream.
+        let mut gz_bytes = Vec::new();
+        for half in xml.chunks(xml.len() / 2) {
+            let mut gz = GzipEncoder::new(Vec::new());
+            gz.write_all(half).await.unwrap();
+            gz.shutdown().await.unwrap();
+            gz_bytes.extend(gz.into_inner());
+        }
+        assert_eq!(read_all(stream_of(gz_bytes)).await, xml);
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_keeps_peeked_bytes() {
+        let raw = b"\x00\x00\x00\x0dOSMHeader".to_vec();
+        assert_eq!(read_all(stream_of(raw.clone())).await, raw);
+    }
+}
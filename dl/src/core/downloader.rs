@@ -4,15 +4,28 @@
 
 use futures::StreamExt;
 use futures::TryStreamExt;
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::{Client, ClientBuilder};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
-
-use crate::core::source::{DownloadSource, SourceConfig};
-use crate::core::stream::{DownloadOptions, DownloadStream, OverwriteBehavior, create_http_stream};
+use tracing::Instrument;
+
+use crate::auth::GeofabrikAuth;
+use crate::cloud;
+use crate::core::mirrors::{self, MirrorProbe};
+use crate::core::politeness::{HostLimiter, HostPermit, PermitReader, Politeness};
+use crate::core::resume::{self, PartFileGuard, ResumeState, ResumeTracker};
+use crate::core::source::{
+    DownloadSource, FileFormat, SourceConfig, default_user_agent, resolve_output_filename,
+};
+use crate::core::space;
+use crate::core::stream::{
+    DownloadOptions, DownloadStream, OverwriteBehavior, RetryCallback, create_http_stream,
+};
+use crate::manifest::Manifest;
 use butterfly_common::{Error, Result};
 
 /// Supertrait combining [`AsyncWrite`](tokio::io::AsyncWrite) and
@@ -29,7 +42,7 @@ const MAX_RETRY_ATTEMPTS: u32 = 3;
 /// Base delay for exponential backoff (in milliseconds)
 const BASE_RETRY_DELAY_MS: u64 = 1000;
 
-/// Global HTTP client with optimisations.
+/// HTTP client settings shared by every downloader.
 ///
 /// Timeout policy (#137):
 /// - **Connect**: 30 s — generous for slow DNS / first-byte from busy mirrors.
@@ -39,20 +52,70 @@ const BASE_RETRY_DELAY_MS: u64 = 1000;
 ///   ~600 MB; on a 5 Mbit/s link the request is many minutes long but every
 ///   chunk arrives well within the read timeout. A wall-clock request timeout
 ///   would abort large downloads mid-stream regardless of connection health.
-static GLOBAL_CLIENT: Lazy<Client> = Lazy::new(|| {
+fn client_builder() -> ClientBuilder {
     ClientBuilder::new()
         .tcp_keepalive(Duration::from_secs(60))
         .pool_idle_timeout(Duration::from_secs(90))
         .pool_max_idle_per_host(20)
         .read_timeout(Duration::from_secs(60))
         .connect_timeout(Duration::from_secs(30))
-        .user_agent(format!("butterfly-dl/{}", env!("BUTTERFLY_VERSION")))
+}
+
+/// Global HTTP client with optimisations, used by every downloader
+/// running with the default `User-Agent` and no extra headers so they
+/// share one connection pool.
+static GLOBAL_CLIENT: Lazy<Client> = Lazy::new(|| {
+    client_builder()
+        .user_agent(default_user_agent())
         .build()
         .expect("Failed to create HTTP client")
 });
 
-/// Execute an operation with retry logic for network errors
-async fn retry_on_network_error<F, Fut, T>(operation: F) -> Result<T>
+/// Client carrying `config.user_agent` and `config.extra_headers` as
+/// default headers. Entries that aren't valid HTTP header names or
+/// values are skipped with a warning.
+fn client_for(config: &SourceConfig) -> Client {
+    if config.user_agent == default_user_agent() && config.extra_headers.is_empty() {
+        return GLOBAL_CLIENT.clone();
+    }
+    build_client(config, HeaderMap::new())
+}
+
+/// Build a client for `config` with `headers` added to its extra headers.
+fn build_client(config: &SourceConfig, mut headers: HeaderMap) -> Client {
+    for (name, value) in &config.extra_headers {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                headers.append(name, value);
+            }
+            _ => tracing::warn!(header = %name, "ignoring invalid extra header"),
+        }
+    }
+    client_builder()
+        .user_agent(config.user_agent.as_str())
+        .default_headers(headers)
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+/// Keep `permit` (a host connection slot) until `stream` is dropped.
+fn with_permit(stream: DownloadStream, permit: HostPermit) -> DownloadStream {
+    DownloadStream::Http(Box::new(PermitReader::new(stream, permit)))
+}
+
+/// Execute an operation with retry logic for network errors.
+///
+/// Each retry emits a `warn` event (with `attempt`, `delay_ms` and
+/// `error` fields) inside the caller's span. `on_retry` is notified
+/// before each backoff sleep; without one the retry is also reported
+/// on stderr.
+pub(crate) async fn retry_on_network_error<F, Fut, T>(
+    on_retry: Option<&RetryCallback>,
+    operation: F,
+) -> Result<T>
 where
     F: Fn() -> Fut,
     Fut: std::future::Future<Output = Result<T>>,
@@ -65,7 +128,13 @@ where
             Err(Error::NetworkError(msg)) if attempt < MAX_RETRY_ATTEMPTS => {
                 attempt += 1;
                 let delay = BASE_RETRY_DELAY_MS * (1 << (attempt - 1)); // Exponential backoff
-                eprintln!("⚠️  Network error (attempt {attempt}): {msg}. Retrying in {delay}ms...");
+                tracing::warn!(attempt, delay_ms = delay, error = %msg, "network error, retrying");
+                match on_retry {
+                    Some(cb) => cb(attempt, delay, &msg),
+                    None => eprintln!(
+                        "⚠️  Network error (attempt {attempt}): {msg}. Retrying in {delay}ms..."
+                    ),
+                }
                 tokio::time::sleep(Duration::from_millis(delay)).await;
             }
             Err(e) => return Err(e), // Non-network errors or max retries exceeded
@@ -79,18 +148,27 @@ async fn check_overwrite_permission(file_path: &str, behavior: &OverwriteBehavio
     if !std::path::Path::new(file_path).exists() {
         return Ok(true); // File doesn't exist, proceed
     }
+    confirm_overwrite("file", file_path, behavior)
+}
 
+/// Apply `behavior` to a destination that already exists: a local file
+/// or a bucket object, named by `kind` in the messages.
+pub(crate) fn confirm_overwrite(
+    kind: &str,
+    dest: &str,
+    behavior: &OverwriteBehavior,
+) -> Result<bool> {
     match behavior {
         OverwriteBehavior::Force => {
-            eprintln!("⚠️  Overwriting existing file: {file_path}");
+            eprintln!("⚠️  Overwriting existing {kind}: {dest}");
             Ok(true)
         }
         OverwriteBehavior::NeverOverwrite => Err(Error::IoError(std::io::Error::new(
             std::io::ErrorKind::AlreadyExists,
-            format!("File already exists: {file_path} (use --force to overwrite)"),
+            format!("{dest} already exists (use --force to overwrite)"),
         ))),
         OverwriteBehavior::Prompt => {
-            eprintln!("⚠️  File already exists: {file_path}");
+            eprintln!("⚠️  {dest} already exists");
             eprint!("Overwrite? [y/N]: ");
 
             // Flush stderr to ensure prompt is displayed
@@ -106,7 +184,7 @@ async fn check_overwrite_permission(file_path: &str, behavior: &OverwriteBehavio
             let response = input.trim().to_lowercase();
             match response.as_str() {
                 "y" | "yes" => {
-                    eprintln!("✅ Overwriting file");
+                    eprintln!("✅ Overwriting {kind}");
                     Ok(true)
                 }
                 _ => {
@@ -140,9 +218,42 @@ pub enum ConditionalOutcome {
     },
 }
 
+/// What a completed file download was fetched from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteFile {
+    /// Resolved source URL.
+    pub url: String,
+    /// Server `ETag` announced on the HEAD request, if any.
+    pub etag: Option<String>,
+    /// Size in bytes (`Content-Length`).
+    pub size: u64,
+}
+
+/// What a HEAD request announced about a remote file.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteHead {
+    /// `Content-Length`.
+    pub size: u64,
+    /// `Accept-Ranges: bytes`.
+    pub supports_ranges: bool,
+    /// `ETag`, if any.
+    pub etag: Option<String>,
+    /// `Last-Modified`, if any.
+    pub last_modified: Option<String>,
+}
+
 /// High-level downloader that handles all source types
 pub struct Downloader {
     config: SourceConfig,
+    client: Client,
+    /// Cached result of [`Self::benchmark_mirrors`], fastest first.
+    mirror_ranking: Option<Vec<MirrorProbe>>,
+    /// Client carrying the Geofabrik internal-server cookie, built on
+    /// first use so credentials are only looked up when needed.
+    internal_client: OnceCell<Client>,
+    /// Per-host connection cap / request spacing shared by every
+    /// transfer of this downloader.
+    limiter: Arc<HostLimiter>,
 }
 
 impl Default for Downloader {
@@ -156,84 +267,457 @@ impl Downloader {
     pub fn new() -> Self {
         Self {
             config: SourceConfig::default(),
+            client: GLOBAL_CLIENT.clone(),
+            mirror_ranking: None,
+            internal_client: OnceCell::new(),
+            limiter: Arc::new(HostLimiter::new(Politeness::default())),
         }
     }
 
-    /// Create a new downloader with custom configuration
+    /// Create a new downloader with custom configuration.
+    ///
+    /// `config.user_agent` and `config.extra_headers` are sent with
+    /// every request this downloader makes (HEAD probes, range
+    /// requests and parallel segments alike).
     pub fn with_config(config: SourceConfig) -> Self {
-        Self { config }
+        let client = client_for(&config);
+        let limiter = Arc::new(HostLimiter::new(config.politeness));
+        Self {
+            config,
+            client,
+            mirror_ranking: None,
+            internal_client: OnceCell::new(),
+            limiter,
+        }
+    }
+
+    /// HTTP client for `url`. Requests to
+    /// `geofabrik_internal_base_url` go through a client that carries
+    /// the internal-server cookie (from `config.auth`, else
+    /// [`GeofabrikAuth::load`]); everything else uses the shared one.
+    fn client_for_url(&self, url: &str) -> Result<&Client> {
+        let internal = self.config.geofabrik_internal_base_url.as_str();
+        if mirrors::rebase(url, internal, internal).is_none() {
+            return Ok(&self.client);
+        }
+        self.internal_client.get_or_try_init(|| {
+            let auth = match &self.config.auth {
+                Some(auth) => auth.clone(),
+                None => GeofabrikAuth::load()?.ok_or_else(|| {
+                    Error::InvalidInput(format!(
+                        "{internal} requires an OpenStreetMap login: set GEOFABRIK_COOKIE, \
+                         GEOFABRIK_COOKIE_FILE or ~/.config/butterfly-dl/auth.toml"
+                    ))
+                })?,
+            };
+            let mut headers = HeaderMap::new();
+            let cookie = HeaderValue::from_str(auth.cookie_header())
+                .map_err(|_| Error::InvalidInput("invalid Geofabrik cookie".to_string()))?;
+            headers.insert(reqwest::header::COOKIE, cookie);
+            Ok(build_client(&self.config, headers))
+        })
     }
 
-    /// Download to a file destination
+    /// Reuse a ranking from a previous [`Self::benchmark_mirrors`]
+    /// call: Geofabrik downloads go to its first entry without probing
+    /// again.
+    pub fn with_mirror_ranking(mut self, ranking: Vec<MirrorProbe>) -> Self {
+        self.mirror_ranking = Some(ranking);
+        self
+    }
+
+    /// Probe `geofabrik_base_url` and every configured mirror with a
+    /// small ranged read of `source` and return them fastest first.
+    ///
+    /// Mirrors that fail the probe are left out of the ranking (and
+    /// logged); an error is only returned when none respond. The
+    /// result can be cached and handed back through
+    /// [`Self::with_mirror_ranking`].
+    pub async fn benchmark_mirrors(
+        &self,
+        source: &str,
+        format: FileFormat,
+    ) -> Result<Vec<MirrorProbe>> {
+        let DownloadSource::Http { url } = self.resolve(source, format)?;
+        let base = self.config.geofabrik_base_url.as_str();
+        if mirrors::rebase(&url, base, base).is_none() {
+            return Err(Error::InvalidInput(format!(
+                "{url} is not served from {base}; mirrors only apply to Geofabrik sources"
+            )));
+        }
+
+        let candidates =
+            std::iter::once(base).chain(self.config.mirrors.iter().map(String::as_str));
+        let probes = futures::future::join_all(candidates.map(|mirror| {
+            let url = mirrors::rebase(&url, base, mirror).unwrap_or_else(|| url.clone());
+            async move {
+                let _permit = self.limiter.acquire(&url).await;
+                let result = mirrors::probe(&self.client, mirror, &url).await;
+                if let Err(e) = &result {
+                    tracing::warn!(mirror, error = %e, "mirror probe failed");
+                }
+                result.ok()
+            }
+        }))
+        .await;
+
+        let mut ranking: Vec<MirrorProbe> = probes.into_iter().flatten().collect();
+        if ranking.is_empty() {
+            return Err(Error::NetworkError(format!(
+                "no mirror answered the probe for {url}"
+            )));
+        }
+        mirrors::rank(&mut ranking);
+        for p in &ranking {
+            tracing::debug!(
+                mirror = %p.base_url,
+                latency_ms = p.latency.as_millis() as u64,
+                throughput = p.throughput,
+                "mirror probe"
+            );
+        }
+        Ok(ranking)
+    }
+
+    /// Point a resolved Geofabrik URL at the preferred mirror: the
+    /// cached ranking if there is one, otherwise a fresh probe when
+    /// `probe_mirrors` is enabled. Falls back to `url` unchanged.
+    async fn select_mirror(&self, source: &str, format: FileFormat, url: String) -> String {
+        let base = self.config.geofabrik_base_url.as_str();
+        if self.config.mirrors.is_empty() || mirrors::rebase(&url, base, base).is_none() {
+            return url;
+        }
+        let fastest = match &self.mirror_ranking {
+            Some(ranking) => ranking.first().map(|p| p.base_url.clone()),
+            None if self.config.probe_mirrors => match self.benchmark_mirrors(source, format).await
+            {
+                Ok(ranking) => ranking.into_iter().next().map(|p| p.base_url),
+                Err(e) => {
+                    tracing::warn!(error = %e, "mirror benchmark failed, using primary");
+                    None
+                }
+            },
+            None => None,
+        };
+        match fastest.and_then(|mirror| mirrors::rebase(&url, base, &mirror)) {
+            Some(mirrored) => {
+                tracing::info!(url = %mirrored, "using fastest mirror");
+                mirrored
+            }
+            None => url,
+        }
+    }
+
+    /// The configuration this downloader was built with.
+    pub fn config(&self) -> &SourceConfig {
+        &self.config
+    }
+
+    /// Download to a file destination, returning where the bytes came from
     pub async fn download_to_file(
         &self,
         source: &str,
         file_path: &str,
         options: &DownloadOptions,
-    ) -> Result<()> {
+    ) -> Result<RemoteFile> {
         // Check overwrite permission before starting download
         check_overwrite_permission(file_path, &options.overwrite).await?;
 
-        let download_source = crate::core::source::resolve_source(source, &self.config)?;
+        let download_source = self.resolve(source, options.format)?;
 
         match download_source {
             DownloadSource::Http { url } => {
+                let url = self.select_mirror(source, options.format, url).await;
                 self.download_http_to_file(&url, file_path, options).await
             }
         }
     }
 
+    /// [`crate::get_with_options`] through this downloader: clamped
+    /// progress contract, auto-generated filename when `dest` is
+    /// `None`, and the `.sha256` sidecar for recognised extensions.
+    pub async fn get_with_options(
+        &self,
+        source: &str,
+        dest: Option<&str>,
+        mut options: DownloadOptions,
+    ) -> Result<()> {
+        // Wrap any user-supplied progress callback so the documented
+        // contract (monotonic, clamped, single-terminal) is enforced
+        // by the library rather than left to caller discipline.
+        if let Some(cb) = options.progress.take() {
+            options.progress = Some(crate::clamp_progress_arc(cb));
+        }
+
+        if let Some(dest) = dest.filter(|d| cloud::is_object_url(d)) {
+            cloud::upload(self, source, &cloud::ObjectUrl::parse(dest)?, &options).await?;
+            return Ok(());
+        }
+
+        let file_path = match dest {
+            Some(path) => path.to_string(),
+            None => resolve_output_filename(source, options.format),
+        };
+
+        self.download_to_file(source, &file_path, &options).await?;
+        crate::maybe_write_sidecar(file_path).await;
+        Ok(())
+    }
+
+    /// [`crate::get_many`] through this downloader: every job runs
+    /// concurrently, sharing this downloader's per-host limits
+    /// ([`SourceConfig::politeness`]). Results are in job order.
+    pub async fn get_many(
+        &self,
+        jobs: &[(&str, Option<&str>)],
+        options: &DownloadOptions,
+    ) -> Vec<Result<()>> {
+        futures::future::join_all(
+            jobs.iter()
+                .map(|&(source, dest)| self.get_with_options(source, dest, options.clone())),
+        )
+        .await
+    }
+
+    /// [`crate::get_with_manifest`] through this downloader.
+    pub async fn get_with_manifest(
+        &self,
+        source: &str,
+        dest: Option<&str>,
+        mut options: DownloadOptions,
+    ) -> Result<Manifest> {
+        if let Some(cb) = options.progress.take() {
+            options.progress = Some(crate::clamp_progress_arc(cb));
+        }
+
+        if dest.is_some_and(cloud::is_object_url) {
+            return Err(Error::InvalidInput(
+                "manifests need a local destination, not an object-storage URL".to_string(),
+            ));
+        }
+
+        let file_path = match dest {
+            Some(path) => path.to_string(),
+            None => resolve_output_filename(source, options.format),
+        };
+
+        let remote = self.download_to_file(source, &file_path, &options).await?;
+
+        // One hash pass serves both the manifest and the `.sha256` sidecar.
+        tokio::task::spawn_blocking(move || -> Result<Manifest> {
+            let target = std::path::Path::new(&file_path);
+            let manifest = crate::manifest::build(target, &remote.url, remote.etag)?;
+            manifest.write(target)?;
+            if crate::verified::VerifiedOptions::for_extension(target).sha256_sidecar {
+                let mut sha = [0u8; 32];
+                if hex::decode_to_slice(&manifest.sha256, &mut sha).is_ok()
+                    && let Err(e) = crate::verified::write_sidecar(target, sha)
+                {
+                    eprintln!("⚠ failed to write .sha256 sidecar: {e}");
+                }
+            }
+            Ok(manifest)
+        })
+        .await
+        .map_err(|e| Error::DownloadFailed(format!("manifest task join error: {e}")))?
+    }
+
+    /// Resolve a source identifier inside a `resolve_source` span, so
+    /// the chosen URL is attached to the telemetry of the download.
+    fn resolve(&self, source: &str, format: FileFormat) -> Result<DownloadSource> {
+        let span = tracing::debug_span!("resolve_source", source, url = tracing::field::Empty);
+        let _enter = span.enter();
+        let resolved = crate::core::source::resolve_source(source, &self.config, format)?;
+        match &resolved {
+            DownloadSource::Http { url } => {
+                span.record("url", url.as_str());
+            }
+        }
+        Ok(resolved)
+    }
+
     /// Download and return a stream
     pub async fn download_stream(
         &self,
         source: &str,
         options: &DownloadOptions,
     ) -> Result<(DownloadStream, u64)> {
-        let download_source = crate::core::source::resolve_source(source, &self.config)?;
+        let download_source = self.resolve(source, options.format)?;
 
-        match download_source {
-            DownloadSource::Http { url } => self.create_http_stream(&url, options).await,
+        let (stream, total_size) = match download_source {
+            DownloadSource::Http { url } => {
+                let url = self.select_mirror(source, options.format, url).await;
+                self.create_http_stream(&url, options).await?
+            }
+        };
+        if options.decompress {
+            // `total_size` stays the compressed Content-Length: the
+            // decoded size is unknown until the stream is drained.
+            let stream = crate::core::decompress::decompress_stream(stream)
+                .await
+                .map_err(|e| Error::NetworkError(format!("Stream read error: {e}")))?;
+            return Ok((stream, total_size));
         }
+        Ok((stream, total_size))
     }
 
-    /// Download from HTTP to file
-    async fn download_http_to_file(
+    /// HEAD `url` (with retry) for its size, range support and
+    /// validators.
+    async fn head(
         &self,
+        client: &Client,
         url: &str,
-        file_path: &str,
         options: &DownloadOptions,
-    ) -> Result<()> {
-        let client = &*GLOBAL_CLIENT;
-
-        // Get file size and check range support with retry
-        let (total_size, supports_ranges) = retry_on_network_error(|| async {
+    ) -> Result<RemoteHead> {
+        retry_on_network_error(options.retry.as_ref(), || async {
+            let _permit = self.limiter.acquire(url).await;
             let head_response = client.head(url).send().await?;
             if !head_response.status().is_success() {
                 return Err(create_helpful_http_error(url, head_response.status()));
             }
+            let header = |name: reqwest::header::HeaderName| {
+                head_response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_owned)
+            };
 
-            let total_size = head_response
-                .headers()
-                .get("content-length")
-                .and_then(|v| v.to_str().ok())
+            let size = header(reqwest::header::CONTENT_LENGTH)
                 .and_then(|v| v.parse::<u64>().ok())
                 .ok_or_else(|| Error::HttpError("Could not determine file size".to_string()))?;
 
-            let supports_ranges = head_response
-                .headers()
-                .get("accept-ranges")
-                .is_some_and(|v| v.to_str().unwrap_or("") == "bytes");
-
-            Ok((total_size, supports_ranges))
+            Ok(RemoteHead {
+                size,
+                supports_ranges: header(reqwest::header::ACCEPT_RANGES).as_deref() == Some("bytes"),
+                etag: header(reqwest::header::ETAG),
+                last_modified: header(reqwest::header::LAST_MODIFIED),
+            })
         })
-        .await?;
+        .await
+    }
+
+    /// Resolve `source` (mirror selection included) and HEAD it.
+    /// Used by destinations that drive the transfer themselves, such
+    /// as [`crate::cloud`] uploads.
+    pub(crate) async fn resolve_remote(
+        &self,
+        source: &str,
+        options: &DownloadOptions,
+    ) -> Result<(String, RemoteHead)> {
+        let DownloadSource::Http { url } = self.resolve(source, options.format)?;
+        let url = self.select_mirror(source, options.format, url).await;
+        let head = self.head(self.client_for_url(&url)?, &url, options).await?;
+        Ok((url, head))
+    }
+
+    /// Streaming GET of `url` from byte `offset` on. A non-zero offset
+    /// must be honoured with `206 Partial Content`.
+    pub(crate) async fn open_at(&self, url: &str, offset: u64) -> Result<DownloadStream> {
+        let client = self.client_for_url(url)?;
+        let permit = self.limiter.acquire(url).await;
+        let mut request = client.get(url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(create_helpful_http_error(url, status));
+        }
+        if offset > 0 && status != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(Error::HttpError(format!(
+                "GET {url} ignored the range request (HTTP {status})"
+            )));
+        }
+        Ok(with_permit(create_http_stream(response), permit))
+    }
 
-        let file = create_optimized_file(file_path, Some(total_size)).await?;
+    /// Download from HTTP to file.
+    ///
+    /// Runs inside a `download` span carrying `url`, `file_path`,
+    /// `total_size` and the final `bytes_downloaded`; connection
+    /// establishment and each transferred segment get child spans.
+    #[tracing::instrument(
+        name = "download",
+        skip(self, options),
+        fields(total_size = tracing::field::Empty, bytes_downloaded = tracing::field::Empty)
+    )]
+    async fn download_http_to_file(
+        &self,
+        url: &str,
+        file_path: &str,
+        options: &DownloadOptions,
+    ) -> Result<RemoteFile> {
+        let client = self.client_for_url(url)?;
+
+        // Get file size and check range support with retry
+        let connect_span = tracing::info_span!(
+            "connect",
+            url,
+            total_size = tracing::field::Empty,
+            supports_ranges = tracing::field::Empty
+        );
+        let RemoteHead {
+            size: total_size,
+            supports_ranges,
+            etag,
+            ..
+        } = self
+            .head(client, url, options)
+            .instrument(connect_span.clone())
+            .await?;
+        connect_span.record("total_size", total_size);
+        connect_span.record("supports_ranges", supports_ranges);
+        tracing::Span::current().record("total_size", total_size);
+
+        // Pick up a previous interrupted run when the sidecar still
+        // describes the same remote object and the server can serve
+        // the remainder with Range requests.
+        let part_path = resume::part_path(file_path);
+        let on_disk = tokio::fs::metadata(&part_path)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0);
+        let previous = options
+            .resume
+            .then(|| ResumeState::load(file_path))
+            .flatten();
+        let mut state = match previous {
+            Some(mut prev) if supports_ranges && prev.matches(url, etag.as_deref(), total_size) => {
+                prev.truncate(on_disk);
+                prev
+            }
+            _ => ResumeState::new(url, etag.clone(), total_size),
+        };
+        if state.completed_bytes() >= total_size {
+            // A sidecar claiming a complete file means the final rename
+            // never happened; re-verify by downloading the last byte range.
+            state.truncate(total_size.saturating_sub(1));
+        }
+        let resume_from = state.completed_bytes();
+        if resume_from > 0 {
+            tracing::info!(resume_from, "resuming from .part file");
+            eprintln!(
+                "↩️  Resuming {} at {resume_from} of {total_size} bytes",
+                part_path.display()
+            );
+        }
+        if options.check_space {
+            space::ensure_space(std::path::Path::new(file_path), total_size - resume_from)?;
+        }
+        // Removes the `.part` file (unless resuming is enabled) if this
+        // future errors out or is dropped, e.g. by a Ctrl-C handler.
+        let guard = PartFileGuard::new(file_path, options.resume);
+        let mut tracker = ResumeTracker::new(state, file_path);
+        tracker.save();
+
+        let file = create_optimized_file(&part_path, resume_from).await?;
 
         let optimal_connections =
             calculate_optimal_connections(total_size, options.max_connections);
 
-        if !supports_ranges || optimal_connections == 1 {
+        let result = if !supports_ranges || optimal_connections == 1 {
             // Single connection download - resilient streaming
             self.download_single_resilient(
                 client,
@@ -241,43 +725,82 @@ impl Downloader {
                 Box::new(file),
                 total_size,
                 supports_ranges,
+                &mut tracker,
                 options,
             )
             .await
         } else {
             // Parallel download - resilient chunks
-            self.download_http_parallel_resilient(client, url, Box::new(file), total_size, options)
-                .await
+            self.download_http_parallel_resilient(
+                client,
+                url,
+                Box::new(file),
+                total_size,
+                &mut tracker,
+                options,
+            )
+            .await
+        };
+        if let Err(e) = result {
+            // Leave `.part` + sidecar in place for the next run (the
+            // guard removes them when resuming is disabled).
+            tracker.save();
+            return Err(e);
         }
+
+        // Only a fully written file ever reaches the destination name.
+        tokio::fs::rename(&part_path, file_path).await?;
+        guard.complete();
+        ResumeState::clear(file_path);
+        tracing::Span::current().record("bytes_downloaded", total_size);
+        tracing::info!(bytes = total_size, "download complete");
+        Ok(RemoteFile {
+            url: url.to_string(),
+            etag,
+            size: total_size,
+        })
     }
 
-    /// Create HTTP stream (single connection)
+    /// Create HTTP stream (single connection). Opening the connection
+    /// retries network errors through `options.retry`; once bytes flow
+    /// to the caller the stream cannot be restarted.
+    #[tracing::instrument(name = "connect", skip(self, options), fields(total_size = tracing::field::Empty))]
     async fn create_http_stream(
         &self,
         url: &str,
-        _options: &DownloadOptions,
+        options: &DownloadOptions,
     ) -> Result<(DownloadStream, u64)> {
-        let client = &*GLOBAL_CLIENT;
+        let client = self.client_for_url(url)?;
 
-        let head_response = client.head(url).send().await?;
-        if !head_response.status().is_success() {
-            return Err(create_helpful_http_error(url, head_response.status()));
-        }
-
-        let total_size = head_response
-            .headers()
-            .get("content-length")
-            .and_then(|v| v.to_str().ok())
-            .and_then(|v| v.parse::<u64>().ok())
-            .unwrap_or(0);
+        let (response, total_size, permit) =
+            retry_on_network_error(options.retry.as_ref(), || async {
+                let head_response = {
+                    let _permit = self.limiter.acquire(url).await;
+                    client.head(url).send().await?
+                };
+                if !head_response.status().is_success() {
+                    return Err(create_helpful_http_error(url, head_response.status()));
+                }
 
-        let response = client.get(url).send().await?;
-        if !response.status().is_success() {
-            let status = response.status();
-            return Err(Error::HttpError(format!("Failed to download: {status}")));
-        }
+                let total_size = head_response
+                    .headers()
+                    .get("content-length")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(0);
+
+                let permit = self.limiter.acquire(url).await;
+                let response = client.get(url).send().await?;
+                if !response.status().is_success() {
+                    let status = response.status();
+                    return Err(Error::HttpError(format!("Failed to download: {status}")));
+                }
+                Ok((response, total_size, permit))
+            })
+            .await?;
+        tracing::Span::current().record("total_size", total_size);
 
-        let stream = create_http_stream(response);
+        let stream = with_permit(create_http_stream(response), permit);
         Ok((stream, total_size))
     }
 
@@ -292,15 +815,16 @@ impl Downloader {
     /// requirement of [`Self::download_stream`] (which matches the
     /// OSM PBF workflow but trips on GTFS / NeTEx mirrors and on
     /// wiremock servers that don't register HEAD handlers). Same
-    /// `GLOBAL_CLIENT`, same TLS config, same user-agent — only the
-    /// HEAD prelude is skipped.
-    pub async fn stream_url_raw(url: &str) -> Result<(DownloadStream, Option<u64>)> {
+    /// client, same TLS config, same headers — only the HEAD prelude
+    /// is skipped.
+    pub async fn stream_url_raw(&self, url: &str) -> Result<(DownloadStream, Option<u64>)> {
         if !(url.starts_with("http://") || url.starts_with("https://")) {
             return Err(Error::InvalidInput(format!(
                 "stream_url_raw expects a raw http(s) URL, got: {url}"
             )));
         }
-        let client = &*GLOBAL_CLIENT;
+        let client = self.client_for_url(url)?;
+        let permit = self.limiter.acquire(url).await;
         let response = client.get(url).send().await?;
         if !response.status().is_success() {
             let status = response.status();
@@ -313,7 +837,7 @@ impl Downloader {
             .get("content-length")
             .and_then(|v| v.to_str().ok())
             .and_then(|v| v.parse::<u64>().ok());
-        let stream = create_http_stream(response);
+        let stream = with_permit(create_http_stream(response), permit);
         Ok((stream, total_size))
     }
 
@@ -327,9 +851,11 @@ impl Downloader {
     /// returned together with the response's own `ETag`/`Last-Modified` so the
     /// caller can cache them. With both validators `None` this is a plain GET
     /// that still surfaces the response validators (so a first download can
-    /// persist them for next time). Same shared client / TLS / user-agent as
+    /// persist them for next time). Same client / TLS / headers as
     /// [`Self::stream_url_raw`]; no HEAD prelude.
+    #[tracing::instrument(name = "connect", level = "debug", skip(self))]
     pub async fn stream_url_conditional(
+        &self,
         url: &str,
         etag: Option<&str>,
         last_modified: Option<&str>,
@@ -340,7 +866,7 @@ impl Downloader {
             )));
         }
         let sent_validator = etag.is_some() || last_modified.is_some();
-        let client = &*GLOBAL_CLIENT;
+        let client = self.client_for_url(url)?;
         let mut req = client.get(url);
         if let Some(tag) = etag {
             req = req.header(reqwest::header::IF_NONE_MATCH, tag);
@@ -348,6 +874,7 @@ impl Downloader {
         if let Some(lm) = last_modified {
             req = req.header(reqwest::header::IF_MODIFIED_SINCE, lm);
         }
+        let permit = self.limiter.acquire(url).await;
         let response = req.send().await?;
         let status = response.status();
 
@@ -383,7 +910,7 @@ impl Downloader {
             .and_then(|v| v.to_str().ok())
             .and_then(|v| v.parse::<u64>().ok());
 
-        let stream = create_http_stream(response);
+        let stream = with_permit(create_http_stream(response), permit);
         Ok(ConditionalOutcome::Body {
             stream,
             total_size,
@@ -393,6 +920,7 @@ impl Downloader {
     }
 
     /// Resilient single connection download with range resume capability
+    #[allow(clippy::too_many_arguments)]
     async fn download_single_resilient(
         &self,
         client: &Client,
@@ -400,14 +928,17 @@ impl Downloader {
         mut writer: Box<dyn AsyncWriteSeek + Send + Unpin>,
         total_size: u64,
         supports_ranges: bool,
+        tracker: &mut ResumeTracker,
         options: &DownloadOptions,
     ) -> Result<()> {
-        let mut downloaded = 0u64;
+        let mut downloaded = tracker.completed_bytes();
+        // One connection slot for the whole (possibly resumed) transfer.
+        let _permit = self.limiter.acquire(url).await;
 
         while downloaded < total_size {
             let result = if downloaded == 0 {
                 // Initial request — no range header needed
-                retry_on_network_error(|| async {
+                retry_on_network_error(options.retry.as_ref(), || async {
                     let response = client.get(url).send().await?;
                     let stream = create_http_stream(response);
                     Ok(stream)
@@ -415,7 +946,7 @@ impl Downloader {
                 .await
             } else if supports_ranges {
                 // Resume using range request
-                retry_on_network_error(|| async {
+                retry_on_network_error(options.retry.as_ref(), || async {
                     let range_header = format!("bytes={downloaded}-");
                     let response = client
                         .get(url)
@@ -448,18 +979,33 @@ impl Downloader {
             match result {
                 Ok(stream) => {
                     // Stream with resilient reading
-                    match self
+                    let start = downloaded;
+                    let segment_span = tracing::debug_span!(
+                        "segment",
+                        start,
+                        end = total_size.saturating_sub(1),
+                        bytes = tracing::field::Empty
+                    );
+                    let outcome = self
                         .stream_to_writer_resilient(
                             stream,
                             &mut writer,
                             total_size,
                             &mut downloaded,
+                            tracker,
                             options,
                         )
-                        .await
-                    {
+                        .instrument(segment_span.clone())
+                        .await;
+                    segment_span.record("bytes", downloaded - start);
+                    match outcome {
                         Ok(()) => break, // Download completed
-                        Err(Error::NetworkError(_)) => {
+                        Err(Error::NetworkError(msg)) => {
+                            tracing::warn!(
+                                downloaded,
+                                error = %msg,
+                                "stream interrupted, resuming"
+                            );
                             eprintln!("Stream interrupted at {downloaded} bytes, resuming...");
                             continue; // Retry from current position
                         }
@@ -477,14 +1023,16 @@ impl Downloader {
                         && let Error::HttpError(ref msg) = e
                         && msg.contains("200 instead of 206")
                     {
-                        log::warn!(
-                            "server ignored Range header ({msg}) — restarting download from byte 0"
+                        tracing::warn!(
+                            error = %msg,
+                            "server ignored Range header — restarting download from byte 0"
                         );
                         writer
                             .seek(std::io::SeekFrom::Start(0))
                             .await
                             .map_err(Error::IoError)?;
                         downloaded = 0;
+                        tracker.reset();
                         continue;
                     }
                     return Err(e);
@@ -493,32 +1041,57 @@ impl Downloader {
         }
 
         writer.flush().await?;
+        tracker.advance(downloaded, true);
         Ok(())
     }
 
-    /// Resilient streaming - if stream fails, propagate error for retry at higher level
+    /// Resilient streaming - if stream fails, propagate error for retry at higher level.
+    ///
+    /// Flushed progress is reported to `tracker` so an interrupted run
+    /// can resume from the last saved offset.
     async fn stream_to_writer_resilient(
         &self,
         mut stream: DownloadStream,
         writer: &mut Box<dyn AsyncWriteSeek + Send + Unpin>,
         total_size: u64,
         downloaded: &mut u64,
+        tracker: &mut ResumeTracker,
         options: &DownloadOptions,
     ) -> Result<()> {
         let mut buffer = vec![0u8; options.buffer_size];
+        let started_at = *downloaded;
 
         loop {
-            let bytes_read = stream
-                .read(&mut buffer)
-                .await
-                .map_err(|e| Error::NetworkError(format!("Stream read error: {e}")))?;
+            let bytes_read = match stream.read(&mut buffer).await {
+                Ok(n) => n,
+                Err(e) => {
+                    writer.flush().await?;
+                    tracker.advance(*downloaded, true);
+                    return Err(Error::NetworkError(format!("Stream read error: {e}")));
+                }
+            };
 
             if bytes_read == 0 {
+                if *downloaded < total_size {
+                    // Clean EOF before `Content-Length`: treat as an
+                    // interruption (resumable) as long as this attempt
+                    // made progress, so a truncated body is never
+                    // promoted to the destination name.
+                    writer.flush().await?;
+                    tracker.advance(*downloaded, true);
+                    let msg = format!("Stream ended at {downloaded} of {total_size} bytes");
+                    return Err(if *downloaded > started_at {
+                        Error::NetworkError(msg)
+                    } else {
+                        Error::DownloadFailed(msg)
+                    });
+                }
                 break;
             }
 
             writer.write_all(&buffer[..bytes_read]).await?;
             *downloaded += bytes_read as u64;
+            tracker.advance(*downloaded, false);
 
             if let Some(ref progress) = options.progress {
                 progress(*downloaded, total_size);
@@ -544,6 +1117,7 @@ impl Downloader {
         url: &str,
         mut writer: Box<dyn AsyncWrite + Send + Unpin>,
         total_size: u64,
+        tracker: &mut ResumeTracker,
         options: &DownloadOptions,
     ) -> Result<()> {
         /// Maximum size of a single in-flight chunk (16 MB).
@@ -556,18 +1130,23 @@ impl Downloader {
 
         let connections = calculate_optimal_connections(total_size, options.max_connections);
 
+        // Resume: the writer is already positioned at the end of the
+        // completed prefix; only the remainder is split into ranges.
+        let resume_from = tracker.completed_bytes();
+        let remaining = total_size - resume_from;
+
         // Cap chunk size so that each in-flight buffer stays within
         // MAX_CHUNK_SIZE. If the naive per-connection chunk would exceed that
         // limit, split into more (smaller) chunks and limit concurrency.
-        let naive_chunk_size = total_size / connections as u64;
+        let naive_chunk_size = (remaining / connections as u64).max(1);
         let chunk_size = naive_chunk_size.min(MAX_CHUNK_SIZE);
-        let num_chunks = total_size.div_ceil(chunk_size) as usize;
+        let num_chunks = remaining.div_ceil(chunk_size) as usize;
         let concurrency = connections.min(MAX_CONCURRENT_CHUNKS);
 
         // Generate ranges
         let ranges: Vec<(u64, u64)> = (0..num_chunks)
             .map(|i| {
-                let start = i as u64 * chunk_size;
+                let start = resume_from + i as u64 * chunk_size;
                 let end = if i == num_chunks - 1 {
                     total_size - 1
                 } else {
@@ -576,8 +1155,9 @@ impl Downloader {
                 (start, end)
             })
             .collect();
+        let chunk_ends: Vec<u64> = ranges.iter().map(|&(_, end)| end).collect();
 
-        let downloaded_bytes = Arc::new(AtomicU64::new(0));
+        let downloaded_bytes = Arc::new(AtomicU64::new(resume_from));
 
         // Progress tracking
         let progress_handle = if let Some(progress_fn) = options.progress.clone() {
@@ -602,12 +1182,17 @@ impl Downloader {
         let stream = futures::stream::iter(ranges.into_iter().enumerate())
             .map(|(idx, (start, end))| {
                 let client = client.clone();
+                let limiter = Arc::clone(&self.limiter);
                 let url = url.to_string();
                 let downloaded_bytes = Arc::clone(&downloaded_bytes);
+                let on_retry = options.retry.clone();
+                let segment_span =
+                    tracing::debug_span!("segment", idx, start, end, bytes = tracing::field::Empty);
 
                 async move {
                     // Retry entire chunk download on failure
-                    retry_on_network_error(|| async {
+                    retry_on_network_error(on_retry.as_ref(), || async {
+                        let _permit = limiter.acquire(&url).await;
                         let range_header = format!("bytes={start}-{end}");
                         let response = client
                             .get(&url)
@@ -631,10 +1216,12 @@ impl Downloader {
                             downloaded_bytes.fetch_add(bytes_chunk.len() as u64, Ordering::Relaxed);
                         }
 
+                        tracing::Span::current().record("bytes", chunk_data.len() as u64);
                         Ok::<(usize, Vec<u8>), Error>((idx, chunk_data))
                     })
                     .await
                 }
+                .instrument(segment_span)
             })
             .buffer_unordered(concurrency);
 
@@ -651,6 +1238,8 @@ impl Downloader {
             {
                 if let Some(chunk) = ring_buffer[next_chunk_to_write].take() {
                     writer.write_all(&chunk).await?;
+                    writer.flush().await?;
+                    tracker.advance(chunk_ends[next_chunk_to_write] + 1, true);
                 }
                 next_chunk_to_write += 1;
             }
@@ -685,7 +1274,8 @@ fn calculate_optimal_connections(file_size: u64, max_connections: usize) -> usiz
     )
 }
 
-/// Create a file for large downloads.
+/// Create the staging file for large downloads, positioned at
+/// `offset` (non-zero when resuming a `.part` file).
 ///
 /// **#231:** previously this opened large files with `O_DIRECT` on
 /// Linux, hoping to bypass the page cache for ~5% throughput gain on
@@ -700,8 +1290,10 @@ fn calculate_optimal_connections(file_size: u64, max_connections: usize) -> usiz
 /// downloads ~95% of O_DIRECT throughput through the page cache
 /// without any of the alignment hazards. Dropping the O_DIRECT path
 /// for the more robust standard I/O is the right tradeoff.
-async fn create_optimized_file(path: &str, _size_hint: Option<u64>) -> Result<tokio::fs::File> {
-    tokio::fs::File::create(path).await.map_err(Into::into)
+async fn create_optimized_file(path: &std::path::Path, offset: u64) -> Result<tokio::fs::File> {
+    resume::open_part_file(path, offset)
+        .await
+        .map_err(Into::into)
 }
 
 /// Create a helpful HTTP error with suggestions for common typos
@@ -713,10 +1305,14 @@ fn create_helpful_http_error(url: &str, status: reqwest::StatusCode) -> Error {
         let source = if url.contains("planet.openstreetmap.org") {
             Some("planet".to_string())
         } else if url.contains("download.geofabrik.de") {
-            // Extract the source from the URL pattern: https://download.geofabrik.de/{source}-latest.osm.pbf
+            // Extract the source from the URL pattern: https://download.geofabrik.de/{source}{suffix}
             url.split("download.geofabrik.de/")
                 .nth(1)
-                .and_then(|after_domain| after_domain.strip_suffix("-latest.osm.pbf"))
+                .and_then(|after_domain| {
+                    FileFormat::ALL
+                        .iter()
+                        .find_map(|f| after_domain.strip_suffix(f.suffix()))
+                })
                 .map(|s| s.to_string())
         } else {
             None
@@ -871,6 +1467,438 @@ mod tests {
         println!("✅ Basic download test passed! Made {head_calls} HEAD and {get_calls} GET calls");
     }
 
+    /// Library consumers installing a subscriber see the `download`,
+    /// `connect` and `segment` spans with byte counters recorded.
+    #[tokio::test]
+    async fn test_download_emits_tracing_spans() {
+        use std::sync::Mutex;
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::layer::{Context, SubscriberExt};
+
+        #[derive(Clone, Default)]
+        struct SpanLog(Arc<Mutex<Vec<(String, String, String)>>>);
+
+        struct FieldVisitor<'a>(&'a mut Vec<(String, String)>);
+        impl Visit for FieldVisitor<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .push((field.name().to_string(), format!("{value:?}")));
+            }
+        }
+
+        impl<S> tracing_subscriber::Layer<S> for SpanLog
+        where
+            S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+        {
+            fn on_record(
+                &self,
+                id: &tracing::span::Id,
+                values: &tracing::span::Record<'_>,
+                ctx: Context<'_, S>,
+            ) {
+                let name = ctx
+                    .span(id)
+                    .map(|s| s.name().to_string())
+                    .unwrap_or_default();
+                let mut fields = Vec::new();
+                values.record(&mut FieldVisitor(&mut fields));
+                let mut log = self.0.lock().unwrap();
+                for (k, v) in fields {
+                    log.push((name.clone(), k, v));
+                }
+            }
+        }
+
+        let mock_server = MockServer::start().await;
+        let test_data = b"B".repeat(2048);
+        let total_size = test_data.len() as u64;
+        Mock::given(method("HEAD"))
+            .and(path("/spans.pbf"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", total_size.to_string().as_str()),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/spans.pbf"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(test_data, "application/octet-stream"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let log = SpanLog::default();
+        let subscriber = tracing_subscriber::registry().with(log.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let temp_file = NamedTempFile::new().unwrap();
+        let url = format!("{}/spans.pbf", mock_server.uri());
+        Downloader::new()
+            .download_http_to_file(
+                &url,
+                temp_file.path().to_str().unwrap(),
+                &DownloadOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        let recorded = log.0.lock().unwrap().clone();
+        let has = |span: &str, field: &str, value: &str| {
+            recorded
+                .iter()
+                .any(|(s, f, v)| s == span && f == field && v == value)
+        };
+        assert!(has("connect", "total_size", "2048"), "{recorded:?}");
+        assert!(has("segment", "bytes", "2048"), "{recorded:?}");
+        assert!(has("download", "bytes_downloaded", "2048"), "{recorded:?}");
+    }
+
+    /// An interrupted run leaves `<dest>.part` + `<dest>.part.json`; the
+    /// next run with a matching ETag only fetches the missing tail and
+    /// renames the staging file onto the destination.
+    #[tokio::test]
+    async fn test_resume_from_part_file() {
+        use wiremock::matchers::header;
+
+        let mock_server = MockServer::start().await;
+        let test_data: Vec<u8> = (0..1024).map(|i| (i % 251) as u8).collect();
+        Mock::given(method("HEAD"))
+            .and(path("/resume.pbf"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "1024")
+                    .insert_header("accept-ranges", "bytes")
+                    .insert_header("etag", "\"v1\""),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/resume.pbf"))
+            .and(header("range", "bytes=512-"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .set_body_raw(test_data[512..].to_vec(), "application/octet-stream"),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("resume.pbf");
+        let dest = dest.to_str().unwrap();
+        let url = format!("{}/resume.pbf", mock_server.uri());
+
+        std::fs::write(resume::part_path(dest), &test_data[..512]).unwrap();
+        let mut state = ResumeState::new(&url, Some("\"v1\"".into()), 1024);
+        state.record(0, 511);
+        state.save(dest).unwrap();
+
+        Downloader::new()
+            .download_http_to_file(&url, dest, &DownloadOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(dest).unwrap(), test_data);
+        assert!(!resume::part_path(dest).exists());
+        assert!(!resume::state_path(dest).exists());
+    }
+
+    /// `SourceConfig::user_agent` and `extra_headers` reach the server
+    /// on both the HEAD probe and the GET.
+    #[tokio::test]
+    async fn test_custom_user_agent_and_headers() {
+        use wiremock::matchers::header;
+
+        let mock_server = MockServer::start().await;
+        for verb in ["HEAD", "GET"] {
+            Mock::given(method(verb))
+                .and(path("/ua.pbf"))
+                .and(header("user-agent", "acme-mirror/1.0 (ops@example.org)"))
+                .and(header("x-mirror-token", "abc"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-length", "4")
+                        .set_body_raw(b"PBF!".to_vec(), "application/octet-stream"),
+                )
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let downloader = Downloader::with_config(SourceConfig {
+            user_agent: "acme-mirror/1.0 (ops@example.org)".to_string(),
+            extra_headers: vec![("X-Mirror-Token".to_string(), "abc".to_string())],
+            ..Default::default()
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("ua.pbf");
+        let dest = dest.to_str().unwrap();
+        downloader
+            .download_http_to_file(
+                &format!("{}/ua.pbf", mock_server.uri()),
+                dest,
+                &DownloadOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(dest).unwrap(), b"PBF!");
+    }
+
+    /// `get_many` runs its jobs concurrently but, with one connection
+    /// per host, the HEAD/GET round trips of both jobs are serialized.
+    #[tokio::test]
+    async fn test_get_many_respects_host_connection_cap() {
+        let server = MockServer::start().await;
+        for verb in ["HEAD", "GET"] {
+            Mock::given(method(verb))
+                .and(path("/a.pbf"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_delay(Duration::from_millis(200))
+                        .insert_header("content-length", "4")
+                        .set_body_raw(b"PBF!".to_vec(), "application/octet-stream"),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        let downloader = Downloader::with_config(SourceConfig {
+            politeness: Politeness {
+                max_connections_per_host: 1,
+                request_delay: Duration::ZERO,
+            },
+            ..Default::default()
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("{}/a.pbf", server.uri());
+        let first = dir.path().join("first.pbf");
+        let second = dir.path().join("second.pbf");
+        let missing = format!("{}/missing.pbf", server.uri());
+
+        let started = std::time::Instant::now();
+        let results = downloader
+            .get_many(
+                &[
+                    (&url, Some(&first.to_string_lossy())),
+                    (&url, Some(&second.to_string_lossy())),
+                    (&missing, Some(&dir.path().join("m.pbf").to_string_lossy())),
+                ],
+                &DownloadOptions::default(),
+            )
+            .await;
+        assert!(started.elapsed() >= Duration::from_millis(800));
+
+        assert!(results[0].is_ok() && results[1].is_ok());
+        assert!(results[2].is_err());
+        assert_eq!(std::fs::read(&second).unwrap(), b"PBF!");
+    }
+
+    /// The probe ranks the faster mirror first and a download with
+    /// `probe_mirrors` fetches from it instead of the primary.
+    #[tokio::test]
+    async fn test_benchmark_mirrors_picks_fastest() {
+        let primary = MockServer::start().await;
+        let mirror = MockServer::start().await;
+        let body = vec![7u8; 1024];
+        Mock::given(method("GET"))
+            .and(path("/europe/monaco-latest.osm.pbf"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .set_delay(Duration::from_millis(300))
+                    .set_body_raw(body.clone(), "application/octet-stream"),
+            )
+            .expect(1)
+            .mount(&primary)
+            .await;
+        for verb in ["HEAD", "GET"] {
+            Mock::given(method(verb))
+                .and(path("/europe/monaco-latest.osm.pbf"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-length", "1024")
+                        .set_body_raw(body.clone(), "application/octet-stream"),
+                )
+                .mount(&mirror)
+                .await;
+        }
+
+        let downloader = Downloader::with_config(SourceConfig {
+            geofabrik_base_url: primary.uri(),
+            mirrors: vec![mirror.uri(), "http://127.0.0.1:9".to_string()],
+            probe_mirrors: true,
+            ..Default::default()
+        });
+        let ranking = downloader
+            .benchmark_mirrors("europe/monaco", FileFormat::Pbf)
+            .await
+            .unwrap();
+        let order: Vec<_> = ranking.iter().map(|p| p.base_url.clone()).collect();
+        assert_eq!(order, [mirror.uri(), primary.uri()]);
+
+        // Cached ranking: no second probe of the primary (`expect(1)`).
+        let downloader = downloader.with_mirror_ranking(ranking);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("monaco.pbf");
+        let remote = downloader
+            .download_to_file(
+                "europe/monaco",
+                dest.to_str().unwrap(),
+                &DownloadOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert!(remote.url.starts_with(&mirror.uri()));
+        assert_eq!(std::fs::read(&dest).unwrap(), body);
+    }
+
+    /// A file larger than the free space fails before anything is
+    /// written, unless the check is disabled.
+    #[tokio::test]
+    async fn test_insufficient_space_fails_early() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/huge.pbf"))
+            .respond_with(
+                ResponseTemplate::new(200).insert_header("content-length", u64::MAX.to_string()),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        if space::available_space(dir.path()).is_none() {
+            return;
+        }
+        let dest = dir.path().join("huge.pbf");
+        let dest = dest.to_str().unwrap();
+        let err = Downloader::new()
+            .download_http_to_file(
+                &format!("{}/huge.pbf", mock_server.uri()),
+                dest,
+                &DownloadOptions::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InsufficientSpace {
+                needed: u64::MAX,
+                ..
+            }
+        ));
+        assert!(!resume::part_path(dest).exists());
+    }
+
+    /// Internal-server downloads carry the Geofabrik cookie; public
+    /// downloads through the same downloader never see it.
+    #[tokio::test]
+    async fn test_internal_downloads_send_cookie() {
+        use wiremock::matchers::header;
+
+        let internal = MockServer::start().await;
+        let public = MockServer::start().await;
+        for verb in ["HEAD", "GET"] {
+            Mock::given(method(verb))
+                .and(path("/europe/monaco-latest-internal.osm.pbf"))
+                .and(header("cookie", "gf_download_oauth=secret"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-length", "4")
+                        .set_body_raw(b"FULL".to_vec(), "application/octet-stream"),
+                )
+                .expect(1)
+                .mount(&internal)
+                .await;
+            Mock::given(method(verb))
+                .and(path("/europe/monaco-latest.osm.pbf"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-length", "4")
+                        .set_body_raw(b"PBF!".to_vec(), "application/octet-stream"),
+                )
+                .mount(&public)
+                .await;
+        }
+
+        let downloader = Downloader::with_config(SourceConfig {
+            geofabrik_base_url: public.uri(),
+            geofabrik_internal_base_url: internal.uri(),
+            auth: Some(GeofabrikAuth::from_cookie("secret").unwrap()),
+            ..Default::default()
+        });
+        let dir = tempfile::tempdir().unwrap();
+        for (format, expected) in [
+            (FileFormat::InternalPbf, b"FULL"),
+            (FileFormat::Pbf, b"PBF!"),
+        ] {
+            let dest = dir.path().join(format.suffix().trim_start_matches('-'));
+            let options = DownloadOptions {
+                format,
+                ..Default::default()
+            };
+            downloader
+                .download_to_file("europe/monaco", dest.to_str().unwrap(), &options)
+                .await
+                .unwrap();
+            assert_eq!(&std::fs::read(&dest).unwrap(), expected);
+        }
+        for request in public.received_requests().await.unwrap() {
+            assert!(!request.headers.contains_key("cookie"));
+        }
+    }
+
+    /// A failed download never creates the destination file; the
+    /// partial bytes stay in `.part` with a sidecar describing them.
+    #[tokio::test]
+    async fn test_failed_download_keeps_part_file() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/broken.pbf"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-length", "1024")
+                    .insert_header("etag", "\"v1\""),
+            )
+            .mount(&mock_server)
+            .await;
+        // Short body without range support: the download cannot resume.
+        Mock::given(method("GET"))
+            .and(path("/broken.pbf"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(vec![7u8; 100], "application/octet-stream"),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("broken.pbf");
+        let dest = dest.to_str().unwrap();
+        let url = format!("{}/broken.pbf", mock_server.uri());
+
+        let result = Downloader::new()
+            .download_http_to_file(&url, dest, &DownloadOptions::default())
+            .await;
+        assert!(result.is_err());
+        assert!(!std::path::Path::new(dest).exists());
+        assert!(resume::part_path(dest).exists());
+        let state = ResumeState::load(dest).expect("sidecar written");
+        assert_eq!(state.url, url);
+        assert_eq!(state.completed_bytes(), 100);
+
+        // With resuming disabled the partial file is cleaned up instead.
+        let options = DownloadOptions {
+            resume: false,
+            ..Default::default()
+        };
+        let result = Downloader::new()
+            .download_http_to_file(&url, dest, &options)
+            .await;
+        assert!(result.is_err());
+        assert!(!std::path::Path::new(dest).exists());
+        assert!(!resume::part_path(dest).exists());
+        assert!(ResumeState::load(dest).is_none());
+    }
+
     #[tokio::test]
     async fn test_retry_exponential_backoff() {
         use std::time::Instant;
@@ -879,7 +1907,7 @@ mod tests {
         let start_time = Instant::now();
         let call_count = Arc::new(AtomicUsize::new(0));
 
-        let result = retry_on_network_error(|| {
+        let result = retry_on_network_error(None, || {
             let count_clone = Arc::clone(&call_count);
             async move {
                 let call_num = count_clone.fetch_add(1, Ordering::SeqCst) + 1;
@@ -911,6 +1939,34 @@ mod tests {
         println!("✅ Exponential backoff test passed! {calls} calls in {elapsed:?}");
     }
 
+    /// Opening a stream (the stdout path) reports every backoff through
+    /// `options.retry`, like file downloads do.
+    #[tokio::test(start_paused = true)]
+    async fn test_stream_open_retries_through_callback() {
+        // Bind and drop to get a local port that refuses connections.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let options = DownloadOptions {
+            retry: Some(Arc::new({
+                let attempts = Arc::clone(&attempts);
+                move |_, _, _| {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                }
+            })),
+            ..Default::default()
+        };
+
+        let result = Downloader::new()
+            .create_http_stream(&format!("http://127.0.0.1:{port}/a.pbf"), &options)
+            .await;
+        assert!(matches!(result, Err(Error::NetworkError(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), MAX_RETRY_ATTEMPTS as usize);
+    }
+
     #[tokio::test]
     async fn test_overwrite_behavior_force() {
         use crate::core::stream::OverwriteBehavior;
@@ -0,0 +1,135 @@
+//! Mirror benchmarking for butterfly-dl
+//!
+//! Each configured mirror is probed with a small ranged GET against
+//! the file about to be downloaded. Mirrors are ranked by throughput
+//! over the probe (time to headers included, so a high-latency mirror
+//! scores lower) with latency as the tie-breaker. Mirrors that fail
+//! the probe are dropped from the ranking.
+
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use tokio::io::AsyncReadExt;
+
+use crate::core::stream::create_http_stream;
+use butterfly_common::{Error, Result};
+
+/// Bytes requested from each mirror (`Range: bytes=0-262143`).
+pub const PROBE_BYTES: u64 = 256 * 1024;
+
+/// Result of probing one mirror.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirrorProbe {
+    /// Mirror base URL (same layout as `SourceConfig::geofabrik_base_url`).
+    pub base_url: String,
+    /// Time until the response headers arrived.
+    pub latency: Duration,
+    /// Probe bytes divided by total probe time, in bytes per second.
+    pub throughput: f64,
+}
+
+/// Rewrite `url` from the `from` base onto the `to` base. `None` when
+/// `url` doesn't live under `from`.
+pub fn rebase(url: &str, from: &str, to: &str) -> Option<String> {
+    let rest = url.strip_prefix(from.trim_end_matches('/'))?;
+    rest.starts_with('/')
+        .then(|| format!("{}{rest}", to.trim_end_matches('/')))
+}
+
+/// Probe `url` (served by the mirror at `base_url`) with a ranged read
+/// of at most [`PROBE_BYTES`].
+pub(crate) async fn probe(client: &Client, base_url: &str, url: &str) -> Result<MirrorProbe> {
+    let started = Instant::now();
+    let response = client
+        .get(url)
+        .header("Range", format!("bytes=0-{}", PROBE_BYTES - 1))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(Error::HttpError(format!(
+            "probe GET {url} returned HTTP {}",
+            response.status()
+        )));
+    }
+    let latency = started.elapsed();
+
+    // A server that ignores the Range header sends the whole file;
+    // stop reading after the probe size either way.
+    let mut body = create_http_stream(response).take(PROBE_BYTES);
+    let mut sink = Vec::with_capacity(PROBE_BYTES as usize);
+    let bytes = body
+        .read_to_end(&mut sink)
+        .await
+        .map_err(|e| Error::NetworkError(format!("probe read from {url} failed: {e}")))?;
+    let elapsed = started.elapsed().as_secs_f64().max(f64::EPSILON);
+
+    Ok(MirrorProbe {
+        base_url: base_url.to_string(),
+        latency,
+        throughput: bytes as f64 / elapsed,
+    })
+}
+
+/// Sort probes fastest first: higher throughput, then lower latency.
+pub(crate) fn rank(probes: &mut [MirrorProbe]) {
+    probes.sort_by(|a, b| {
+        b.throughput
+            .total_cmp(&a.throughput)
+            .then(a.latency.cmp(&b.latency))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe_result(base: &str, latency_ms: u64, throughput: f64) -> MirrorProbe {
+        MirrorProbe {
+            base_url: base.to_string(),
+            latency: Duration::from_millis(latency_ms),
+            throughput,
+        }
+    }
+
+    #[test]
+    fn test_rebase() {
+        assert_eq!(
+            rebase(
+                "https://download.geofabrik.de/europe/monaco-latest.osm.pbf",
+                "https://download.geofabrik.de",
+                "https://mirror.example.org/geofabrik/"
+            )
+            .as_deref(),
+            Some("https://mirror.example.org/geofabrik/europe/monaco-latest.osm.pbf")
+        );
+        assert_eq!(
+            rebase(
+                "https://planet.openstreetmap.org/pbf/planet-latest.osm.pbf",
+                "https://download.geofabrik.de",
+                "https://mirror.example.org"
+            ),
+            None
+        );
+        // Prefix match must end on a path boundary.
+        assert_eq!(
+            rebase(
+                "https://download.geofabrik.de.evil/x.pbf",
+                "https://download.geofabrik.de",
+                "https://mirror.example.org"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rank_prefers_throughput_then_latency() {
+        let mut probes = vec![
+            probe_result("slow", 10, 1_000.0),
+            probe_result("fast-far", 90, 50_000.0),
+            probe_result("fast-near", 20, 50_000.0),
+        ];
+        rank(&mut probes);
+        let order: Vec<_> = probes.iter().map(|p| p.base_url.as_str()).collect();
+        assert_eq!(order, ["fast-near", "fast-far", "slow"]);
+    }
+}
@@ -2,10 +2,17 @@
 //!
 //! This module contains the internal implementation details of the butterfly-dl library.
 
+pub mod decompress;
 pub mod downloader;
+pub mod mirrors;
+pub mod politeness;
+pub mod resume;
 pub mod source;
+pub mod space;
 pub mod stream;
 
 // Re-export main types for internal use
-pub use downloader::{ConditionalOutcome, Downloader};
-pub use source::{SourceConfig, resolve_output_filename};
+pub use downloader::{ConditionalOutcome, Downloader, RemoteFile};
+pub use mirrors::MirrorProbe;
+pub use politeness::Politeness;
+pub use source::{FileFormat, SourceConfig, resolve_output_filename};
@@ -0,0 +1,187 @@
+//! Per-host request politeness
+//!
+//! A [`HostLimiter`] is shared by every transfer of a [`Downloader`]
+//! (and so by every concurrent [`get_many`] / queue transfer using
+//! it). For each host it keeps a semaphore capping the number of open
+//! connections and the earliest instant the next request may start,
+//! so many extracts downloaded at once still arrive at Geofabrik as a
+//! bounded, evenly spaced trickle of requests.
+//!
+//! [`Downloader`]: crate::core::Downloader
+//! [`get_many`]: crate::get_many
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// Connection cap and request spacing per host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Politeness {
+    /// Maximum concurrent connections to one host. `0` means unlimited.
+    pub max_connections_per_host: usize,
+    /// Minimum delay between the starts of two requests to one host.
+    pub request_delay: Duration,
+}
+
+impl Politeness {
+    /// No limits: every request goes out immediately.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_connections_per_host == 0 && self.request_delay.is_zero()
+    }
+}
+
+struct HostSlot {
+    connections: Option<Arc<Semaphore>>,
+    next_request: Mutex<Instant>,
+}
+
+/// Shared per-host limiter.
+pub struct HostLimiter {
+    politeness: Politeness,
+    hosts: Mutex<HashMap<String, Arc<HostSlot>>>,
+}
+
+/// Held for the lifetime of one connection; releases the host's
+/// connection slot on drop.
+pub struct HostPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl HostLimiter {
+    /// Limiter enforcing `politeness`.
+    pub fn new(politeness: Politeness) -> Self {
+        Self {
+            politeness,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a connection slot on `url`'s host and for the request
+    /// delay since the previous request to that host to elapse.
+    pub async fn acquire(&self, url: &str) -> HostPermit {
+        if self.politeness.is_unlimited() {
+            return HostPermit { _permit: None };
+        }
+        let slot = self.slot(&host_key(url));
+
+        let permit = match &slot.connections {
+            Some(semaphore) => Some(
+                Arc::clone(semaphore)
+                    .acquire_owned()
+                    .await
+                    .expect("host semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        // Reserve the next start time under the lock, sleep outside it.
+        let start_at = {
+            let mut next = slot.next_request.lock().expect("host slot poisoned");
+            let start_at = (*next).max(Instant::now());
+            *next = start_at + self.politeness.request_delay;
+            start_at
+        };
+        tokio::time::sleep_until(start_at).await;
+
+        HostPermit { _permit: permit }
+    }
+
+    fn slot(&self, host: &str) -> Arc<HostSlot> {
+        let mut hosts = self.hosts.lock().expect("host map poisoned");
+        let cap = self.politeness.max_connections_per_host;
+        Arc::clone(hosts.entry(host.to_string()).or_insert_with(|| {
+            Arc::new(HostSlot {
+                connections: (cap > 0).then(|| Arc::new(Semaphore::new(cap))),
+                next_request: Mutex::new(Instant::now()),
+            })
+        }))
+    }
+}
+
+/// `host[:port]` of `url`; the whole string when it doesn't parse.
+fn host_key(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(u) => match (u.host_str(), u.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            _ => url.to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+/// A reader that keeps its host connection slot until dropped.
+pub struct PermitReader<R> {
+    inner: R,
+    _permit: HostPermit,
+}
+
+impl<R> PermitReader<R> {
+    /// Tie `permit` to `inner`'s lifetime.
+    pub fn new(inner: R, permit: HostPermit) -> Self {
+        Self {
+            inner,
+            _permit: permit,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for PermitReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_key() {
+        assert_eq!(
+            host_key("https://download.geofabrik.de/europe/a.pbf"),
+            "download.geofabrik.de"
+        );
+        assert_eq!(host_key("http://127.0.0.1:8080/a.pbf"), "127.0.0.1:8080");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_caps_connections_and_spaces_requests() {
+        let limiter = Arc::new(HostLimiter::new(Politeness {
+            max_connections_per_host: 2,
+            request_delay: Duration::from_millis(100),
+        }));
+        let started = Instant::now();
+
+        let a = limiter.acquire("https://h.example/a").await;
+        let b = limiter.acquire("https://h.example/b").await;
+        assert_eq!(started.elapsed(), Duration::from_millis(100));
+
+        // Other hosts are unaffected.
+        let _other = limiter.acquire("https://other.example/x").await;
+        assert_eq!(started.elapsed(), Duration::from_millis(100));
+
+        // Third connection waits for a slot.
+        let limiter2 = Arc::clone(&limiter);
+        let third = tokio::spawn(async move {
+            limiter2.acquire("https://h.example/c").await;
+            Instant::now()
+        });
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(!third.is_finished());
+        drop(a);
+        let got_at = third.await.unwrap();
+        assert!(got_at >= started + Duration::from_secs(5));
+        drop(b);
+    }
+}
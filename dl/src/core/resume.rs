@@ -0,0 +1,299 @@
+//! Persistent resume state for file downloads
+//!
+//! Downloads never write to the destination directly. Bytes land in
+//! `<dest>.part` and a small JSON sidecar `<dest>.part.json` records
+//! the source URL, the server `ETag`, the announced size and the byte
+//! ranges already flushed to the `.part` file. On success the `.part`
+//! file is atomically renamed onto `<dest>` and the sidecar removed, so
+//! an interrupted run can never leave a half-written final file.
+//!
+//! A later run against the same destination picks the state back up
+//! when the URL, size and `ETag` all still match (a changed `ETag`
+//! means the upstream file was republished and the partial bytes are
+//! stale). Without an `ETag` the partial file cannot be validated and
+//! the download restarts from byte 0.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Minimum number of newly completed bytes between two sidecar writes
+/// on the single-connection path (parallel downloads save after every
+/// chunk, which is already ≤ 16 MB).
+const SAVE_EVERY_BYTES: u64 = 16 * 1024 * 1024;
+
+/// `<dest>.part` — staging file the download writes into.
+pub fn part_path(dest: &str) -> PathBuf {
+    PathBuf::from(format!("{dest}.part"))
+}
+
+/// `<dest>.part.json` — resume sidecar next to the staging file.
+pub fn state_path(dest: &str) -> PathBuf {
+    PathBuf::from(format!("{dest}.part.json"))
+}
+
+/// On-disk resume sidecar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResumeState {
+    /// Source URL the partial bytes came from.
+    pub url: String,
+    /// Server `ETag` at the time the download started.
+    pub etag: Option<String>,
+    /// `Content-Length` announced by the server.
+    pub total_size: u64,
+    /// Completed, flushed byte ranges (`[start, end]` inclusive),
+    /// sorted and merged.
+    pub segments: Vec<(u64, u64)>,
+}
+
+impl ResumeState {
+    /// Fresh state with no completed segments.
+    pub fn new(url: &str, etag: Option<String>, total_size: u64) -> Self {
+        Self {
+            url: url.to_string(),
+            etag,
+            total_size,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Load the sidecar for `dest`, if present and parseable.
+    pub fn load(dest: &str) -> Option<Self> {
+        let bytes = std::fs::read(state_path(dest)).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Whether this state describes the same remote object. Requires
+    /// an `ETag` on both sides — without one the partial bytes can't
+    /// be trusted.
+    pub fn matches(&self, url: &str, etag: Option<&str>, total_size: u64) -> bool {
+        self.url == url
+            && self.total_size == total_size
+            && self.etag.is_some()
+            && self.etag.as_deref() == etag
+    }
+
+    /// Length of the contiguous completed prefix starting at byte 0.
+    pub fn completed_bytes(&self) -> u64 {
+        match self.segments.first() {
+            Some(&(0, end)) => end + 1,
+            _ => 0,
+        }
+    }
+
+    /// Mark `[start, end]` as completed, merging with adjacent or
+    /// overlapping segments.
+    pub fn record(&mut self, start: u64, end: u64) {
+        self.segments.push((start, end));
+        self.segments.sort_unstable();
+        let mut merged: Vec<(u64, u64)> = Vec::with_capacity(self.segments.len());
+        for &(s, e) in &self.segments {
+            match merged.last_mut() {
+                Some(last) if s <= last.1.saturating_add(1) => last.1 = last.1.max(e),
+                _ => merged.push((s, e)),
+            }
+        }
+        self.segments = merged;
+    }
+
+    /// Drop every completed segment past `len` (used when the `.part`
+    /// file on disk is shorter than the sidecar claims).
+    pub fn truncate(&mut self, len: u64) {
+        self.segments.retain(|&(s, _)| s < len);
+        if let Some(last) = self.segments.last_mut() {
+            last.1 = last.1.min(len.saturating_sub(1));
+        }
+        if len == 0 {
+            self.segments.clear();
+        }
+    }
+
+    /// Persist the sidecar via a temp file + rename so a crash mid-write
+    /// never leaves a truncated JSON behind.
+    pub fn save(&self, dest: &str) -> std::io::Result<()> {
+        let path = state_path(dest);
+        let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &path)
+    }
+
+    /// Remove the sidecar for `dest`. Missing files are not an error.
+    pub fn clear(dest: &str) {
+        let _ = std::fs::remove_file(state_path(dest));
+    }
+}
+
+/// Tracks contiguous progress of a sequential writer and saves the
+/// sidecar every [`SAVE_EVERY_BYTES`].
+pub struct ResumeTracker {
+    state: ResumeState,
+    dest: String,
+    last_saved: u64,
+}
+
+impl ResumeTracker {
+    /// Wrap `state` for `dest`.
+    pub fn new(state: ResumeState, dest: &str) -> Self {
+        let last_saved = state.completed_bytes();
+        Self {
+            state,
+            dest: dest.to_string(),
+            last_saved,
+        }
+    }
+
+    /// Bytes already on disk when the download (re)started.
+    pub fn completed_bytes(&self) -> u64 {
+        self.state.completed_bytes()
+    }
+
+    /// Record that `[0, completed)` is flushed to the `.part` file;
+    /// writes the sidecar when enough progress accumulated or `force`.
+    pub fn advance(&mut self, completed: u64, force: bool) {
+        if completed == 0 {
+            return;
+        }
+        self.state.record(0, completed - 1);
+        if force || completed - self.last_saved >= SAVE_EVERY_BYTES {
+            self.save();
+            self.last_saved = completed;
+        }
+    }
+
+    /// Forget all progress (server ignored a Range request and the
+    /// download restarts from byte 0).
+    pub fn reset(&mut self) {
+        self.state.segments.clear();
+        self.last_saved = 0;
+        self.save();
+    }
+
+    /// Write the sidecar now. Failures are logged, not fatal: the
+    /// worst case is a restart from byte 0 on the next run.
+    pub fn save(&self) {
+        if let Err(e) = self.state.save(&self.dest) {
+            tracing::warn!(dest = %self.dest, error = %e, "failed to write resume sidecar");
+        }
+    }
+}
+
+/// Drop guard for an in-flight download's `.part` file.
+///
+/// If the download future returns an error or is dropped before
+/// [`PartFileGuard::complete`] (a Ctrl-C handler cancelling it, a
+/// `select!` losing branch, …) the guard removes `<dest>.part` and its
+/// sidecar, unless `keep` is set — then both stay for the next run to
+/// resume from.
+pub struct PartFileGuard {
+    dest: String,
+    keep: bool,
+    completed: bool,
+}
+
+impl PartFileGuard {
+    /// Guard the `.part` file of `dest`.
+    pub fn new(dest: &str, keep: bool) -> Self {
+        Self {
+            dest: dest.to_string(),
+            keep,
+            completed: false,
+        }
+    }
+
+    /// The `.part` file was renamed onto `dest`; nothing to clean up.
+    pub fn complete(mut self) {
+        self.completed = true;
+    }
+}
+
+impl Drop for PartFileGuard {
+    fn drop(&mut self) {
+        if self.completed {
+            return;
+        }
+        if self.keep {
+            tracing::info!(dest = %self.dest, "download interrupted, keeping .part for resume");
+            return;
+        }
+        let _ = std::fs::remove_file(part_path(&self.dest));
+        ResumeState::clear(&self.dest);
+        tracing::info!(dest = %self.dest, "download interrupted, removed .part");
+    }
+}
+
+/// Open (or create) `<dest>.part` positioned at `offset`, truncating
+/// anything past it so stale tail bytes never survive a resume.
+pub async fn open_part_file(path: &Path, offset: u64) -> std::io::Result<tokio::fs::File> {
+    use tokio::io::AsyncSeekExt;
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path)
+        .await?;
+    file.set_len(offset).await?;
+    file.seek(std::io::SeekFrom::Start(offset)).await?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_merges_segments() {
+        let mut state = ResumeState::new("http://x/a.pbf", Some("\"e\"".into()), 100);
+        state.record(20, 29);
+        assert_eq!(state.completed_bytes(), 0);
+        state.record(0, 9);
+        state.record(10, 19);
+        assert_eq!(state.segments, vec![(0, 29)]);
+        assert_eq!(state.completed_bytes(), 30);
+        state.truncate(25);
+        assert_eq!(state.segments, vec![(0, 24)]);
+    }
+
+    #[test]
+    fn test_matches_requires_etag() {
+        let state = ResumeState::new("http://x/a.pbf", Some("\"v1\"".into()), 100);
+        assert!(state.matches("http://x/a.pbf", Some("\"v1\""), 100));
+        assert!(!state.matches("http://x/a.pbf", Some("\"v2\""), 100));
+        assert!(!state.matches("http://x/a.pbf", Some("\"v1\""), 101));
+        assert!(!state.matches("http://x/b.pbf", Some("\"v1\""), 100));
+
+        let no_etag = ResumeState::new("http://x/a.pbf", None, 100);
+        assert!(!no_etag.matches("http://x/a.pbf", None, 100));
+    }
+
+    #[test]
+    fn test_part_file_guard() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("a.pbf");
+        let dest = dest.to_str().unwrap();
+
+        std::fs::write(part_path(dest), b"partial").unwrap();
+        drop(PartFileGuard::new(dest, true));
+        assert!(part_path(dest).exists());
+
+        drop(PartFileGuard::new(dest, false));
+        assert!(!part_path(dest).exists());
+
+        std::fs::write(part_path(dest), b"partial").unwrap();
+        PartFileGuard::new(dest, false).complete();
+        assert!(part_path(dest).exists());
+    }
+
+    #[test]
+    fn test_save_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("a.pbf");
+        let dest = dest.to_str().unwrap();
+        let mut state = ResumeState::new("http://x/a.pbf", Some("\"v1\"".into()), 100);
+        state.record(0, 49);
+        state.save(dest).unwrap();
+        assert_eq!(ResumeState::load(dest), Some(state));
+        ResumeState::clear(dest);
+        assert!(ResumeState::load(dest).is_none());
+    }
+}
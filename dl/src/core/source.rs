@@ -3,6 +3,7 @@
 //! Handles HTTP source routing for OpenStreetMap data downloads.
 
 use butterfly_common::Result;
+use serde::{Deserialize, Serialize};
 
 /// Represents different download sources
 #[derive(Debug, Clone, PartialEq)]
@@ -11,13 +12,105 @@ pub enum DownloadSource {
     Http { url: String },
 }
 
+/// Artifact formats published by Geofabrik.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileFormat {
+    /// `<name>-latest.osm.pbf` (default)
+    #[default]
+    Pbf,
+    /// `<name>-latest.osm.bz2` — bzip2-compressed OSM XML
+    OsmBz2,
+    /// `<name>-latest-free.shp.zip` — Geofabrik's free shapefile bundle
+    Shp,
+    /// `<name>-latest-internal.osm.pbf` — full-metadata extract from
+    /// Geofabrik's internal server (OSM login required, see
+    /// [`crate::auth`])
+    InternalPbf,
+}
+
+impl FileFormat {
+    /// Filename suffix after the region name, shared by the remote
+    /// Geofabrik file and the auto-generated local filename.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            FileFormat::Pbf => "-latest.osm.pbf",
+            FileFormat::OsmBz2 => "-latest.osm.bz2",
+            FileFormat::Shp => "-latest-free.shp.zip",
+            FileFormat::InternalPbf => "-latest-internal.osm.pbf",
+        }
+    }
+
+    /// Every format, for suffix matching.
+    pub const ALL: [FileFormat; 4] = [
+        FileFormat::Pbf,
+        FileFormat::OsmBz2,
+        FileFormat::Shp,
+        FileFormat::InternalPbf,
+    ];
+}
+
+impl std::str::FromStr for FileFormat {
+    type Err = butterfly_common::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pbf" | "osm.pbf" => Ok(FileFormat::Pbf),
+            "osm.bz2" | "bz2" => Ok(FileFormat::OsmBz2),
+            "shp" | "shp.zip" => Ok(FileFormat::Shp),
+            "internal" | "internal.osm.pbf" => Ok(FileFormat::InternalPbf),
+            other => Err(butterfly_common::Error::InvalidInput(format!(
+                "unknown format '{other}' (expected pbf, osm.bz2, shp or internal)"
+            ))),
+        }
+    }
+}
+
 /// Configuration for download sources
+#[derive(Debug, Clone)]
 pub struct SourceConfig {
     /// HTTP URL for planet files
     pub planet_http_url: String,
 
+    /// HTTP URL for the bzip2 OSM XML planet
+    pub planet_bz2_http_url: String,
+
     /// Base URL for Geofabrik downloads
     pub geofabrik_base_url: String,
+
+    /// Base URL of Geofabrik's internal (OSM-login protected) server,
+    /// used for [`FileFormat::InternalPbf`]
+    pub geofabrik_internal_base_url: String,
+
+    /// Credentials for the internal server. When `None`, they are
+    /// loaded from the environment / config file on first use (see
+    /// [`crate::auth::GeofabrikAuth::load`]).
+    pub auth: Option<crate::auth::GeofabrikAuth>,
+
+    /// `User-Agent` sent with every request. Geofabrik asks bulk
+    /// users to identify themselves, e.g. `"acme-mirror/1.0 (ops@acme.org)"`.
+    pub user_agent: String,
+
+    /// Additional `(name, value)` headers sent with every request.
+    /// A `User-Agent` entry here overrides [`Self::user_agent`].
+    pub extra_headers: Vec<(String, String)>,
+
+    /// Alternative base URLs publishing the same tree as
+    /// [`Self::geofabrik_base_url`].
+    pub mirrors: Vec<String>,
+
+    /// Probe [`Self::mirrors`] before each Geofabrik download and fetch
+    /// from the fastest (see `Downloader::benchmark_mirrors`).
+    pub probe_mirrors: bool,
+
+    /// Per-host connection cap and inter-request delay, enforced across
+    /// every concurrent transfer of one `Downloader`. Unlimited by default.
+    pub politeness: crate::core::politeness::Politeness,
+}
+
+/// `butterfly-dl/<version>`, the default `User-Agent`.
+pub fn default_user_agent() -> String {
+    format!("butterfly-dl/{}", env!("BUTTERFLY_VERSION"))
 }
 
 impl Default for SourceConfig {
@@ -25,7 +118,16 @@ impl Default for SourceConfig {
         Self {
             planet_http_url: "https://planet.openstreetmap.org/pbf/planet-latest.osm.pbf"
                 .to_string(),
+            planet_bz2_http_url: "https://planet.openstreetmap.org/planet/planet-latest.osm.bz2"
+                .to_string(),
             geofabrik_base_url: "https://download.geofabrik.de".to_string(),
+            geofabrik_internal_base_url: "https://osm-internal.download.geofabrik.de".to_string(),
+            auth: None,
+            user_agent: default_user_agent(),
+            extra_headers: Vec::new(),
+            mirrors: Vec::new(),
+            probe_mirrors: false,
+            politeness: Default::default(),
         }
     }
 }
@@ -42,39 +144,66 @@ impl Default for SourceConfig {
 ///    expanded against `SourceConfig::geofabrik_base_url`.
 /// 4. **Bare continent** (`europe`, `africa`, …): expanded against
 ///    the same Geofabrik base URL.
-pub fn resolve_source(source: &str, config: &SourceConfig) -> Result<DownloadSource> {
+///
+/// `format` selects the Geofabrik artifact (filename suffix);
+/// [`FileFormat::InternalPbf`] presets expand against
+/// `SourceConfig::geofabrik_internal_base_url` instead. Raw URLs are
+/// passed through untouched regardless of format.
+pub fn resolve_source(
+    source: &str,
+    config: &SourceConfig,
+    format: FileFormat,
+) -> Result<DownloadSource> {
     if source.starts_with("http://") || source.starts_with("https://") {
         return Ok(DownloadSource::Http {
             url: source.to_string(),
         });
     }
+    let suffix = format.suffix();
+    let base = match format {
+        FileFormat::InternalPbf => &config.geofabrik_internal_base_url,
+        _ => &config.geofabrik_base_url,
+    };
     match source {
-        "planet" => resolve_planet_source(config),
+        "planet" => resolve_planet_source(config, format),
         path if path.contains('/') => Ok(DownloadSource::Http {
-            url: format!("{}/{}-latest.osm.pbf", config.geofabrik_base_url, path),
+            url: format!("{base}/{path}{suffix}"),
         }),
         continent => Ok(DownloadSource::Http {
-            url: format!("{}/{}-latest.osm.pbf", config.geofabrik_base_url, continent),
+            url: format!("{base}/{continent}{suffix}"),
         }),
     }
 }
 
 /// Resolves planet source to HTTP download
-fn resolve_planet_source(config: &SourceConfig) -> Result<DownloadSource> {
-    Ok(DownloadSource::Http {
-        url: config.planet_http_url.clone(),
-    })
+fn resolve_planet_source(config: &SourceConfig, format: FileFormat) -> Result<DownloadSource> {
+    let url = match format {
+        FileFormat::Pbf => config.planet_http_url.clone(),
+        FileFormat::OsmBz2 => config.planet_bz2_http_url.clone(),
+        FileFormat::Shp => {
+            return Err(butterfly_common::Error::InvalidInput(
+                "the planet is not published as a shapefile bundle".to_string(),
+            ));
+        }
+        FileFormat::InternalPbf => {
+            return Err(butterfly_common::Error::InvalidInput(
+                "the planet has no Geofabrik internal extract; planet PBFs already carry full metadata".to_string(),
+            ));
+        }
+    };
+    Ok(DownloadSource::Http { url })
 }
 
-/// Generates output filename from source
-pub fn resolve_output_filename(source: &str) -> String {
+/// Generates output filename from source for the given format
+pub fn resolve_output_filename(source: &str, format: FileFormat) -> String {
+    let suffix = format.suffix();
     match source {
-        "planet" => "planet-latest.osm.pbf".to_string(),
+        "planet" => format!("planet{suffix}"),
         path if path.contains('/') => {
             let name = path.split('/').next_back().unwrap_or(path);
-            format!("{name}-latest.osm.pbf")
+            format!("{name}{suffix}")
         }
-        continent => format!("{continent}-latest.osm.pbf"),
+        continent => format!("{continent}{suffix}"),
     }
 }
 
@@ -85,7 +214,7 @@ mod tests {
     #[test]
     fn test_resolve_planet_source() {
         let config = SourceConfig::default();
-        let source = resolve_source("planet", &config).unwrap();
+        let source = resolve_source("planet", &config, FileFormat::Pbf).unwrap();
 
         match source {
             DownloadSource::Http { url } => {
@@ -100,7 +229,7 @@ mod tests {
     #[test]
     fn test_resolve_continent_source() {
         let config = SourceConfig::default();
-        let source = resolve_source("europe", &config).unwrap();
+        let source = resolve_source("europe", &config, FileFormat::Pbf).unwrap();
 
         match source {
             DownloadSource::Http { url } => {
@@ -112,7 +241,7 @@ mod tests {
     #[test]
     fn test_resolve_country_source() {
         let config = SourceConfig::default();
-        let source = resolve_source("europe/belgium", &config).unwrap();
+        let source = resolve_source("europe/belgium", &config, FileFormat::Pbf).unwrap();
 
         match source {
             DownloadSource::Http { url } => {
@@ -124,12 +253,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolve_source_formats() {
+        let config = SourceConfig::default();
+        let url = |source, format| match resolve_source(source, &config, format).unwrap() {
+            DownloadSource::Http { url } => url,
+        };
+        assert_eq!(
+            url("europe/belgium", FileFormat::OsmBz2),
+            "https://download.geofabrik.de/europe/belgium-latest.osm.bz2"
+        );
+        assert_eq!(
+            url("europe/belgium", FileFormat::Shp),
+            "https://download.geofabrik.de/europe/belgium-latest-free.shp.zip"
+        );
+        assert_eq!(
+            url("planet", FileFormat::OsmBz2),
+            "https://planet.openstreetmap.org/planet/planet-latest.osm.bz2"
+        );
+        assert!(resolve_source("planet", &config, FileFormat::Shp).is_err());
+        assert_eq!(
+            url("europe/belgium", FileFormat::InternalPbf),
+            "https://osm-internal.download.geofabrik.de/europe/belgium-latest-internal.osm.pbf"
+        );
+        assert!(resolve_source("planet", &config, FileFormat::InternalPbf).is_err());
+
+        assert_eq!(
+            resolve_output_filename("europe/belgium", FileFormat::Shp),
+            "belgium-latest-free.shp.zip"
+        );
+        assert_eq!("osm.bz2".parse::<FileFormat>().unwrap(), FileFormat::OsmBz2);
+        assert!("gpkg".parse::<FileFormat>().is_err());
+    }
+
     #[test]
     fn test_resolve_output_filename() {
-        assert_eq!(resolve_output_filename("planet"), "planet-latest.osm.pbf");
-        assert_eq!(resolve_output_filename("europe"), "europe-latest.osm.pbf");
         assert_eq!(
-            resolve_output_filename("europe/belgium"),
+            resolve_output_filename("planet", FileFormat::Pbf),
+            "planet-latest.osm.pbf"
+        );
+        assert_eq!(
+            resolve_output_filename("europe", FileFormat::Pbf),
+            "europe-latest.osm.pbf"
+        );
+        assert_eq!(
+            resolve_output_filename("europe/belgium", FileFormat::Pbf),
             "belgium-latest.osm.pbf"
         );
     }
@@ -0,0 +1,62 @@
+//! Free-space preflight for file downloads
+//!
+//! Before the first byte is written, the announced `Content-Length`
+//! (minus whatever a resumed `.part` file already holds) is compared
+//! with the space available to unprivileged users on the destination
+//! filesystem, so an 80 GB planet download onto a 20 GB disk fails
+//! immediately instead of hours later.
+
+use std::path::Path;
+
+use butterfly_common::{Error, Result};
+
+/// Bytes available to unprivileged users on the filesystem holding
+/// `dir`. `None` when the platform or filesystem can't tell.
+#[cfg(unix)]
+pub fn available_space(dir: &Path) -> Option<u64> {
+    let stat = rustix::fs::statvfs(dir).ok()?;
+    Some(stat.f_bavail.saturating_mul(stat.f_frsize))
+}
+
+/// Bytes available on the filesystem holding `dir`. Unsupported on
+/// this platform.
+#[cfg(not(unix))]
+pub fn available_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+/// Fail with [`Error::InsufficientSpace`] when `needed` more bytes
+/// won't fit next to `file_path`. Unknown free space passes.
+pub fn ensure_space(file_path: &Path, needed: u64) -> Result<()> {
+    let dir = match file_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    match available_space(dir) {
+        Some(available) if available < needed => {
+            Err(Error::InsufficientSpace { needed, available })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("planet.osm.pbf");
+        assert!(ensure_space(&dest, 1).is_ok());
+        if available_space(dir.path()).is_some() {
+            assert!(matches!(
+                ensure_space(&dest, u64::MAX),
+                Err(Error::InsufficientSpace {
+                    needed: u64::MAX,
+                    ..
+                })
+            ));
+        }
+    }
+}
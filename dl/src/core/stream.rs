@@ -8,6 +8,8 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, ReadBuf};
 
+use crate::core::source::FileFormat;
+
 /// A unified stream for HTTP sources
 pub enum DownloadStream {
     /// HTTP stream using reqwest
@@ -29,6 +31,11 @@ impl AsyncRead for DownloadStream {
 /// Progress callback function type
 pub type ProgressCallback = Arc<dyn Fn(u64, u64) + Send + Sync>;
 
+/// Retry callback function type: `(attempt, delay_ms, error_message)`.
+///
+/// Invoked once per network-error retry, before the backoff sleep.
+pub type RetryCallback = Arc<dyn Fn(u32, u64, &str) + Send + Sync>;
+
 /// Overwrite behavior for existing files
 #[derive(Debug, Clone, Default, PartialEq)]
 pub enum OverwriteBehavior {
@@ -42,6 +49,7 @@ pub enum OverwriteBehavior {
 }
 
 /// Options for download operations
+#[derive(Clone)]
 pub struct DownloadOptions {
     /// Optional progress callback
     pub progress: Option<ProgressCallback>,
@@ -54,6 +62,28 @@ pub struct DownloadOptions {
 
     /// Behavior when destination file already exists
     pub overwrite: OverwriteBehavior,
+
+    /// Optional retry callback. When set, it replaces the default
+    /// stderr warning printed on each network-error retry.
+    pub retry: Option<RetryCallback>,
+
+    /// Geofabrik artifact to fetch for preset sources (`europe/belgium`,
+    /// `planet`, …). Raw URLs ignore it.
+    pub format: FileFormat,
+
+    /// Streaming downloads only: decode bzip2/gzip bodies on the fly so
+    /// the stream yields raw OSM XML / PBF bytes. Detection is by magic
+    /// bytes; uncompressed bodies pass through unchanged.
+    pub decompress: bool,
+
+    /// File downloads only: fail with `Error::InsufficientSpace` before
+    /// writing when the destination filesystem can't hold the file.
+    pub check_space: bool,
+
+    /// File downloads only: keep `<dest>.part` and its sidecar when a
+    /// download fails or is interrupted, and resume from them on the
+    /// next run. When `false` the partial file is removed instead.
+    pub resume: bool,
 }
 
 impl Default for DownloadOptions {
@@ -63,6 +93,11 @@ impl Default for DownloadOptions {
             buffer_size: 64 * 1024, // 64KB
             max_connections: 16,
             overwrite: OverwriteBehavior::default(),
+            retry: None,
+            format: FileFormat::default(),
+            decompress: false,
+            check_space: true,
+            resume: true,
         }
     }
 }
@@ -0,0 +1,349 @@
+//! Bounding-box → Geofabrik extract discovery.
+//!
+//! Geofabrik publishes `index-v1.json`, a GeoJSON `FeatureCollection`
+//! with one feature per extract: its id, parent, display name, the
+//! download URLs per format and the clipping polygon. Given a bounding
+//! box (or a single point), [`GeofabrikIndex::smallest_covering`] picks
+//! the extract with the smallest polygon that contains the box, so
+//! `butterfly-dl --bbox 4.2,50.7,4.5,50.9` fetches `belgium` rather
+//! than `europe`.
+//!
+//! Containment is tested on the four bbox corners against the extract
+//! polygon (holes respected). Geofabrik polygons are generous buffers
+//! around administrative borders, so a box whose corners are inside is
+//! covered in practice.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use tokio::io::AsyncReadExt;
+
+use crate::core::Downloader;
+use butterfly_common::{Error, Result};
+
+/// A lon/lat bounding box (WGS84 degrees).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BBox {
+    pub min_lon: f64,
+    pub min_lat: f64,
+    pub max_lon: f64,
+    pub max_lat: f64,
+}
+
+impl BBox {
+    /// Build a box, validating ranges and ordering.
+    pub fn new(min_lon: f64, min_lat: f64, max_lon: f64, max_lat: f64) -> Result<Self> {
+        let valid_lon = |v: f64| (-180.0..=180.0).contains(&v);
+        let valid_lat = |v: f64| (-90.0..=90.0).contains(&v);
+        if !(valid_lon(min_lon) && valid_lon(max_lon) && valid_lat(min_lat) && valid_lat(max_lat)) {
+            return Err(Error::InvalidInput(format!(
+                "bbox out of range: {min_lon},{min_lat},{max_lon},{max_lat}"
+            )));
+        }
+        if min_lon > max_lon || min_lat > max_lat {
+            return Err(Error::InvalidInput(format!(
+                "bbox must be min_lon,min_lat,max_lon,max_lat: {min_lon},{min_lat},{max_lon},{max_lat}"
+            )));
+        }
+        Ok(Self {
+            min_lon,
+            min_lat,
+            max_lon,
+            max_lat,
+        })
+    }
+
+    /// Parse `min_lon,min_lat,max_lon,max_lat`, or `lon,lat` for a point.
+    pub fn parse(s: &str) -> Result<Self> {
+        let parts: Vec<f64> = s
+            .split(',')
+            .map(|p| p.trim().parse::<f64>())
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| Error::InvalidInput(format!("invalid bbox '{s}': {e}")))?;
+        match parts.as_slice() {
+            [lon, lat] => Self::new(*lon, *lat, *lon, *lat),
+            [a, b, c, d] => Self::new(*a, *b, *c, *d),
+            _ => Err(Error::InvalidInput(format!(
+                "invalid bbox '{s}': expected 'min_lon,min_lat,max_lon,max_lat' or 'lon,lat'"
+            ))),
+        }
+    }
+
+    fn corners(&self) -> [[f64; 2]; 4] {
+        [
+            [self.min_lon, self.min_lat],
+            [self.max_lon, self.min_lat],
+            [self.max_lon, self.max_lat],
+            [self.min_lon, self.max_lat],
+        ]
+    }
+}
+
+/// One extract from the Geofabrik index.
+#[derive(Debug, Clone)]
+pub struct Extract {
+    /// Geofabrik id (e.g. `belgium`, `us/california`).
+    pub id: String,
+    /// Parent extract id (`europe` for `belgium`).
+    pub parent: Option<String>,
+    /// Human-readable name.
+    pub name: String,
+    /// Download URLs keyed by format (`pbf`, `bz2`, `shp`, …).
+    pub urls: BTreeMap<String, String>,
+    /// Polygons: outer ring first, then holes.
+    polygons: Vec<Vec<Vec<[f64; 2]>>>,
+}
+
+impl Extract {
+    /// URL of the `.osm.pbf` download, if published.
+    pub fn pbf_url(&self) -> Option<&str> {
+        self.urls.get("pbf").map(String::as_str)
+    }
+
+    /// Local filename matching the CLI's `<name>-latest.osm.pbf` scheme.
+    pub fn output_filename(&self) -> String {
+        let name = self.id.rsplit('/').next().unwrap_or(&self.id);
+        format!("{name}-latest.osm.pbf")
+    }
+
+    /// Point-in-polygon with holes (even-odd rule per polygon).
+    pub fn contains(&self, lon: f64, lat: f64) -> bool {
+        self.polygons.iter().any(|rings| {
+            let mut rings = rings.iter();
+            let Some(outer) = rings.next() else {
+                return false;
+            };
+            ring_contains(outer, lon, lat) && !rings.any(|hole| ring_contains(hole, lon, lat))
+        })
+    }
+
+    /// Whether all four corners of `bbox` lie inside the extract.
+    pub fn covers(&self, bbox: &BBox) -> bool {
+        bbox.corners()
+            .iter()
+            .all(|&[lon, lat]| self.contains(lon, lat))
+    }
+
+    /// Planar area in square degrees (outer rings minus holes). Only
+    /// used to rank candidates, so no projection is needed.
+    pub fn area(&self) -> f64 {
+        self.polygons
+            .iter()
+            .map(|rings| {
+                rings
+                    .iter()
+                    .enumerate()
+                    .map(|(i, r)| if i == 0 { ring_area(r) } else { -ring_area(r) })
+                    .sum::<f64>()
+            })
+            .sum()
+    }
+}
+
+/// Parsed Geofabrik `index-v1.json`.
+#[derive(Debug, Clone)]
+pub struct GeofabrikIndex {
+    pub extracts: Vec<Extract>,
+}
+
+#[derive(Deserialize)]
+struct RawIndex {
+    features: Vec<RawFeature>,
+}
+
+#[derive(Deserialize)]
+struct RawFeature {
+    properties: RawProperties,
+    geometry: Option<RawGeometry>,
+}
+
+#[derive(Deserialize)]
+struct RawProperties {
+    id: String,
+    #[serde(default)]
+    parent: Option<String>,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    urls: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum RawGeometry {
+    Polygon {
+        coordinates: Vec<Vec<[f64; 2]>>,
+    },
+    MultiPolygon {
+        coordinates: Vec<Vec<Vec<[f64; 2]>>>,
+    },
+}
+
+impl GeofabrikIndex {
+    /// Parse the bytes of `index-v1.json`.
+    pub fn parse(bytes: &[u8]) -> Result<Self> {
+        let raw: RawIndex = serde_json::from_slice(bytes)
+            .map_err(|e| Error::InvalidInput(format!("invalid Geofabrik index: {e}")))?;
+        let extracts = raw
+            .features
+            .into_iter()
+            .map(|f| Extract {
+                id: f.properties.id,
+                parent: f.properties.parent,
+                name: f.properties.name,
+                urls: f.properties.urls,
+                polygons: match f.geometry {
+                    Some(RawGeometry::Polygon { coordinates }) => vec![coordinates],
+                    Some(RawGeometry::MultiPolygon { coordinates }) => coordinates,
+                    None => Vec::new(),
+                },
+            })
+            .collect();
+        Ok(Self { extracts })
+    }
+
+    /// Fetch and parse `<geofabrik_base_url>/index-v1.json` through
+    /// `downloader` (its base URL, `User-Agent` and extra headers).
+    pub async fn fetch(downloader: &Downloader) -> Result<Self> {
+        let url = format!("{}/index-v1.json", downloader.config().geofabrik_base_url);
+        let (mut stream, size) = downloader.stream_url_raw(&url).await?;
+        let mut body = Vec::with_capacity(size.unwrap_or(0) as usize);
+        stream
+            .read_to_end(&mut body)
+            .await
+            .map_err(|e| Error::NetworkError(format!("reading {url}: {e}")))?;
+        Self::parse(&body)
+    }
+
+    /// The extract with the smallest area whose polygon covers `bbox`
+    /// and that publishes a PBF.
+    pub fn smallest_covering(&self, bbox: &BBox) -> Option<&Extract> {
+        self.extracts
+            .iter()
+            .filter(|e| e.pbf_url().is_some() && e.covers(bbox))
+            .min_by(|a, b| a.area().total_cmp(&b.area()))
+    }
+}
+
+/// Resolve the smallest Geofabrik extract covering a bounding box.
+///
+/// Pass the same value for min and max to look up a single point.
+/// Fetches the live Geofabrik index with the default
+/// [`SourceConfig`](crate::SourceConfig).
+pub async fn resolve_by_bbox(
+    min_lon: f64,
+    min_lat: f64,
+    max_lon: f64,
+    max_lat: f64,
+) -> Result<Extract> {
+    let bbox = BBox::new(min_lon, min_lat, max_lon, max_lat)?;
+    let index = GeofabrikIndex::fetch(&Downloader::new()).await?;
+    index.smallest_covering(&bbox).cloned().ok_or_else(|| {
+        Error::SourceNotFound(format!(
+            "no Geofabrik extract covers bbox {min_lon},{min_lat},{max_lon},{max_lat}"
+        ))
+    })
+}
+
+fn ring_contains(ring: &[[f64; 2]], lon: f64, lat: f64) -> bool {
+    let mut inside = false;
+    let n = ring.len();
+    if n < 3 {
+        return false;
+    }
+    let mut j = n - 1;
+    for i in 0..n {
+        let [xi, yi] = ring[i];
+        let [xj, yj] = ring[j];
+        if (yi > lat) != (yj > lat) && lon < (xj - xi) * (lat - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+fn ring_area(ring: &[[f64; 2]]) -> f64 {
+    let n = ring.len();
+    if n < 3 {
+        return 0.0;
+    }
+    let twice: f64 = (0..n)
+        .map(|i| {
+            let [x1, y1] = ring[i];
+            let [x2, y2] = ring[(i + 1) % n];
+            x1 * y2 - x2 * y1
+        })
+        .sum();
+    twice.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Europe as a big square, Belgium as a small one inside it, plus
+    /// an unrelated island with no PBF.
+    const INDEX: &str = r#"{
+      "type": "FeatureCollection",
+      "features": [
+        {"type": "Feature",
+         "properties": {"id": "europe", "name": "Europe",
+                        "urls": {"pbf": "https://download.geofabrik.de/europe-latest.osm.pbf"}},
+         "geometry": {"type": "Polygon",
+                      "coordinates": [[[-10,35],[30,35],[30,70],[-10,70],[-10,35]]]}},
+        {"type": "Feature",
+         "properties": {"id": "belgium", "parent": "europe", "name": "Belgium",
+                        "urls": {"pbf": "https://download.geofabrik.de/europe/belgium-latest.osm.pbf"}},
+         "geometry": {"type": "MultiPolygon",
+                      "coordinates": [[[[2.5,49.5],[6.5,49.5],[6.5,51.5],[2.5,51.5],[2.5,49.5]]]]}},
+        {"type": "Feature",
+         "properties": {"id": "nowhere", "name": "No PBF", "urls": {}},
+         "geometry": {"type": "Polygon",
+                      "coordinates": [[[4,50],[5,50],[5,51],[4,51],[4,50]]]}}
+      ]
+    }"#;
+
+    #[test]
+    fn test_bbox_parse() {
+        let b = BBox::parse("4.2,50.7,4.5,50.9").unwrap();
+        assert_eq!((b.min_lon, b.max_lat), (4.2, 50.9));
+        let p = BBox::parse("4.35, 50.85").unwrap();
+        assert_eq!(p.min_lon, p.max_lon);
+        assert!(BBox::parse("4.5,50.7,4.2,50.9").is_err());
+        assert!(BBox::parse("1,2,3").is_err());
+        assert!(BBox::parse("200,0").is_err());
+    }
+
+    #[test]
+    fn test_smallest_covering_picks_country() {
+        let index = GeofabrikIndex::parse(INDEX.as_bytes()).unwrap();
+        let brussels = BBox::parse("4.2,50.7,4.5,50.9").unwrap();
+        let hit = index.smallest_covering(&brussels).unwrap();
+        assert_eq!(hit.id, "belgium");
+        assert_eq!(hit.output_filename(), "belgium-latest.osm.pbf");
+
+        // Straddles the Belgian polygon → falls back to the continent.
+        let wide = BBox::parse("0,45,5,50").unwrap();
+        assert_eq!(index.smallest_covering(&wide).unwrap().id, "europe");
+
+        // Outside everything.
+        let pacific = BBox::parse("-150,0").unwrap();
+        assert!(index.smallest_covering(&pacific).is_none());
+    }
+
+    #[test]
+    fn test_polygon_holes_excluded() {
+        let index = GeofabrikIndex::parse(
+            br#"{"features":[{"properties":{"id":"ring","urls":{"pbf":"x"}},
+                 "geometry":{"type":"Polygon","coordinates":[
+                   [[0,0],[10,0],[10,10],[0,10],[0,0]],
+                   [[4,4],[6,4],[6,6],[4,6],[4,4]]]}}]}"#,
+        )
+        .unwrap();
+        let e = &index.extracts[0];
+        assert!(e.contains(1.0, 1.0));
+        assert!(!e.contains(5.0, 5.0));
+        assert!((e.area() - 96.0).abs() < 1e-9);
+    }
+}
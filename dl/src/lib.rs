@@ -55,7 +55,8 @@ use std::sync::Arc;
 use tokio::io::AsyncRead;
 
 // Re-export core types that users might need
-pub use crate::core::stream::{DownloadOptions, OverwriteBehavior};
+pub use crate::core::FileFormat;
+pub use crate::core::stream::{DownloadOptions, OverwriteBehavior, RetryCallback};
 pub use butterfly_common::{Error, Result};
 
 // Internal modules
@@ -93,6 +94,31 @@ fn clamp_progress_arc(
 /// [`verified::VerifiedOptions::for_extension`].
 pub mod verified;
 
+/// Bounding-box → smallest covering Geofabrik extract, backed by the
+/// Geofabrik `index-v1.json` polygons.
+pub mod discovery;
+
+pub use discovery::resolve_by_bbox;
+
+/// SHA-256 download manifests (`<dest>.sha256.json`) recording size,
+/// hash, source URL, ETag and completion time; see
+/// [`get_with_manifest`].
+pub mod manifest;
+
+pub use manifest::Manifest;
+
+/// Cookie credentials for Geofabrik's internal (OSM-login protected)
+/// server, loaded from the environment or a config file.
+pub mod auth;
+
+/// Persistent, prioritised download queue drained with bounded
+/// concurrency; backs `butterfly-dl queue add/status/run`.
+pub mod queue;
+
+/// `s3://` / `gs://` destinations: the download is streamed into a
+/// resumable multipart upload without touching local disk.
+pub mod cloud;
+
 /// Region-indexed parallel downloads (#100). One TOML per region
 /// (`dl/regions/<name>.toml`) enumerates every file the region's
 /// routing deployment needs; [`regions::fetch_region`] dispatches
@@ -123,7 +149,7 @@ pub async fn get(source: &str, dest: Option<&str>) -> Result<()> {
 
     let file_path = match dest {
         Some(path) => path.to_string(),
-        None => core::resolve_output_filename(source),
+        None => core::resolve_output_filename(source, options.format),
     };
 
     downloader
@@ -196,9 +222,41 @@ async fn maybe_write_sidecar(file_path: String) {
 /// # Ok(())
 /// # }
 /// ```
-pub async fn get_stream(source: &str) -> Result<impl AsyncRead + Send + Unpin> {
+pub async fn get_stream(source: &str) -> Result<impl AsyncRead + Send + Unpin + use<>> {
+    get_stream_with_options(source, DownloadOptions::default()).await
+}
+
+/// Download and return a stream with custom options
+///
+/// Like [`get_stream`], honouring `options.format` to pick the
+/// Geofabrik artifact (e.g. [`FileFormat::OsmBz2`]). With
+/// `options.decompress` set, bzip2/gzip bodies are decoded on the fly
+/// and the stream yields raw OSM XML / PBF bytes.
+///
+/// # Examples
+/// ```rust,no_run
+/// use butterfly_dl::{DownloadOptions, FileFormat};
+/// use tokio::io::AsyncReadExt;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let options = DownloadOptions {
+///     format: FileFormat::OsmBz2,
+///     decompress: true,
+///     ..Default::default()
+/// };
+/// let mut xml = butterfly_dl::get_stream_with_options("europe/monaco", options).await?;
+/// let mut head = [0u8; 5];
+/// xml.read_exact(&mut head).await?;
+/// assert_eq!(&head, b"<?xml");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn get_stream_with_options(
+    source: &str,
+    options: DownloadOptions,
+) -> Result<impl AsyncRead + Send + Unpin + use<>> {
     let downloader = core::Downloader::new();
-    let options = DownloadOptions::default();
 
     let (stream, _total_size) = downloader.download_stream(source, &options).await?;
     Ok(stream)
@@ -247,6 +305,13 @@ pub async fn get_stream(source: &str) -> Result<impl AsyncRead + Send + Unpin> {
 ///     progress: Some(Arc::new(|downloaded, total| {
 ///         println!("Downloaded: {} / {}", downloaded, total);
 ///     })),
+///     retry: Some(Arc::new(|attempt, delay_ms, error| {
+///         eprintln!("retry #{attempt} in {delay_ms}ms: {error}");
+///     })),
+///     format: butterfly_dl::FileFormat::Pbf,
+///     decompress: false,
+///     check_space: true,
+///     resume: true,
 /// };
 ///
 /// butterfly_dl::get_with_options("europe/belgium", None, options).await?;
@@ -256,27 +321,71 @@ pub async fn get_stream(source: &str) -> Result<impl AsyncRead + Send + Unpin> {
 pub async fn get_with_options(
     source: &str,
     dest: Option<&str>,
-    mut options: DownloadOptions,
+    options: DownloadOptions,
 ) -> Result<()> {
-    let downloader = core::Downloader::new();
-
-    // Wrap any user-supplied progress callback so the documented
-    // contract (monotonic, clamped, single-terminal) is enforced
-    // by the library rather than left to caller discipline.
-    if let Some(cb) = options.progress.take() {
-        options.progress = Some(clamp_progress_arc(cb));
-    }
+    core::Downloader::new()
+        .get_with_options(source, dest, options)
+        .await
+}
 
-    let file_path = match dest {
-        Some(path) => path.to_string(),
-        None => core::resolve_output_filename(source),
-    };
+/// Download with custom options and write a SHA-256 manifest
+///
+/// Same download as [`get_with_options`] (including the `.sha256`
+/// sidecar for recognised extensions), followed by a hash pass that
+/// writes `<dest>.sha256.json` and returns the same [`Manifest`].
+/// Build steps that pin their inputs (e.g. `butterfly-route` lock
+/// files) can record the returned struct directly.
+///
+/// # Examples
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let manifest = butterfly_dl::get_with_manifest(
+///     "europe/monaco",
+///     None,
+///     butterfly_dl::DownloadOptions::default(),
+/// )
+/// .await?;
+/// println!("{} {} bytes", manifest.sha256, manifest.size);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn get_with_manifest(
+    source: &str,
+    dest: Option<&str>,
+    options: DownloadOptions,
+) -> Result<Manifest> {
+    core::Downloader::new()
+        .get_with_manifest(source, dest, options)
+        .await
+}
 
-    downloader
-        .download_to_file(source, &file_path, &options)
-        .await?;
-    maybe_write_sidecar(file_path).await;
-    Ok(())
+/// Download several sources concurrently
+///
+/// Each `(source, dest)` job is fetched as by [`get_with_options`]
+/// with a clone of `options`; one failure doesn't cancel the others.
+/// All jobs share one [`Downloader`], so its
+/// [`SourceConfig::politeness`] caps connections per host across the
+/// whole batch — use [`Downloader::get_many`] to configure it.
+///
+/// # Examples
+/// ```rust,no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// let results = butterfly_dl::get_many(
+///     &[("europe/monaco", None), ("europe/andorra", None)],
+///     &butterfly_dl::DownloadOptions::default(),
+/// )
+/// .await;
+/// for result in results {
+///     if let Err(e) = result {
+///         eprintln!("download failed: {e}");
+///     }
+/// }
+/// # }
+/// ```
+pub async fn get_many(jobs: &[(&str, Option<&str>)], options: &DownloadOptions) -> Vec<Result<()>> {
+    core::Downloader::new().get_many(jobs, options).await
 }
 
 /// Advanced API: Create a downloader with custom configuration
@@ -285,21 +394,30 @@ pub async fn get_with_options(
 ///
 /// # Examples
 /// ```rust,no_run
-/// use butterfly_dl::{Downloader, SourceConfig};
+/// use butterfly_dl::{Downloader, Politeness, SourceConfig};
+/// use std::time::Duration;
 ///
 /// # #[tokio::main]
 /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let config = SourceConfig {
 ///     planet_http_url: "https://my-custom-mirror.org/planet.pbf".to_string(),
+///     politeness: Politeness {
+///         max_connections_per_host: 4,
+///         request_delay: Duration::from_millis(250),
+///     },
+///     user_agent: "acme-mirror/1.0 (ops@acme.org)".to_string(),
+///     extra_headers: vec![("X-Mirror-Token".to_string(), "secret".to_string())],
 ///     ..Default::default()
 /// };
 ///
 /// let downloader = Downloader::with_config(config);
-/// // Use downloader methods...
+/// downloader
+///     .get_with_options("planet", None, Default::default())
+///     .await?;
 /// # Ok(())
 /// # }
 /// ```
-pub use core::{Downloader, SourceConfig};
+pub use core::{Downloader, MirrorProbe, Politeness, RemoteFile, SourceConfig};
 
 #[cfg(test)]
 mod tests {
@@ -318,15 +436,15 @@ mod tests {
     #[test]
     fn test_resolve_output_filename() {
         assert_eq!(
-            core::resolve_output_filename("planet"),
+            core::resolve_output_filename("planet", FileFormat::Pbf),
             "planet-latest.osm.pbf"
         );
         assert_eq!(
-            core::resolve_output_filename("europe"),
+            core::resolve_output_filename("europe", FileFormat::Pbf),
             "europe-latest.osm.pbf"
         );
         assert_eq!(
-            core::resolve_output_filename("europe/belgium"),
+            core::resolve_output_filename("europe/belgium", FileFormat::Pbf),
             "belgium-latest.osm.pbf"
         );
     }
@@ -5,10 +5,14 @@
 
 use butterfly_dl::regions::{SectionFilter, fetch_region, shipped_regions};
 use butterfly_dl::verified::Outcome;
-use butterfly_dl::{DownloadOptions, OverwriteBehavior, Result};
-use clap::Parser;
-use log::error;
-use std::path::PathBuf;
+use butterfly_dl::{
+    DownloadOptions, Downloader, FileFormat, OverwriteBehavior, Politeness, Result, SourceConfig,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::error;
 
 mod cli;
 
@@ -24,22 +28,39 @@ mod cli;
   butterfly-dl europe              # Download Europe continent from HTTP
   butterfly-dl europe/belgium      # Download Belgium PBF from Geofabrik
   butterfly-dl europe/monaco -     # Stream Monaco to stdout
+  butterfly-dl --bbox 4.2,50.7,4.5,50.9  # Smallest Geofabrik extract covering a bbox
+  butterfly-dl europe/belgium --format shp  # Geofabrik shapefile bundle instead of PBF
 
 File Overwrite Behavior:
   By default, you'll be prompted if destination file exists
   --force                          # Overwrite without asking
-  --no-clobber                     # Never overwrite, fail if file exists"
+  --no-clobber                     # Never overwrite, fail if file exists
+
+Machine-readable progress:
+  --progress json                  # NDJSON events (start/progress/retry/done) on stderr
+
+Download queue (mirror maintenance):
+  butterfly-dl queue add europe/belgium europe/france --priority 5
+  butterfly-dl queue run --concurrency 4
+  butterfly-dl queue status"
 )]
 #[command(version = env!("BUTTERFLY_VERSION"))]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Source to download: a shipped region name (e.g. "belgium"),
     /// or a Geofabrik preset ("planet", "europe", "europe/belgium", …).
     /// Bare region names consult `dl/regions/<name>.toml` and fetch
     /// every file the region needs in parallel; path-shaped inputs
-    /// keep the single-PBF Geofabrik semantics.
-    source: String,
+    /// keep the single-PBF Geofabrik semantics. Omitted with `--bbox`.
+    #[arg(required_unless_present = "bbox")]
+    source: Option<String>,
 
-    /// Output file path, or "-" for stdout. Ignored when `source`
+    /// Output file path, "-" for stdout, or an `s3://bucket/key` /
+    /// `gs://bucket/key` object (multipart upload, no local disk;
+    /// credentials from the environment). Ignored when `source`
     /// is a bundled region (the region index determines target
     /// paths under `--to`).
     #[arg(default_value = "")]
@@ -73,6 +94,213 @@ struct Cli {
     /// Never overwrite existing files (fail if destination exists)
     #[arg(long)]
     no_clobber: bool,
+
+    /// Progress output format: `bar` (interactive indicatif bar) or
+    /// `json` (newline-delimited JSON events on stderr, for CI and
+    /// wrapper scripts).
+    #[arg(long, value_enum, default_value_t = ProgressFormat::Bar)]
+    progress: ProgressFormat,
+
+    /// Seconds between `progress` events in `--progress json` mode
+    #[arg(long, default_value_t = 1)]
+    progress_interval: u64,
+
+    /// Write `<dest>.sha256.json` (size, sha256, source URL, ETag,
+    /// timestamp) after a file download
+    #[arg(long)]
+    write_manifest: bool,
+
+    /// Download the smallest Geofabrik extract covering a bounding box
+    /// (`min_lon,min_lat,max_lon,max_lat`) or a point (`lon,lat`). The
+    /// only positional argument is then the optional output path.
+    #[arg(long, allow_hyphen_values = true)]
+    bbox: Option<String>,
+
+    /// Geofabrik artifact format for single-file downloads: `pbf`
+    /// (default), `osm.bz2` (bzip2 OSM XML), `shp` (free shapefile
+    /// bundle, `.shp.zip`) or `internal` (full-metadata PBF from the
+    /// internal server; needs GEOFABRIK_COOKIE or an auth config).
+    #[arg(long, default_value = "pbf", value_parser = parse_format)]
+    format: FileFormat,
+
+    /// When streaming to stdout ("-"), decode bzip2/gzip bodies on the
+    /// fly so the pipe carries raw OSM XML / PBF bytes
+    #[arg(long)]
+    decompress: bool,
+
+    /// `User-Agent` for every request (default `butterfly-dl/<version>`).
+    /// Geofabrik asks bulk users to identify themselves here.
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// Extra request header as `"Name: value"`; repeatable. Sent with
+    /// every request of a single-file download.
+    #[arg(long = "header", value_name = "NAME: VALUE", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+
+    /// Alternative Geofabrik mirror base URL; repeatable
+    #[arg(long = "mirror", value_name = "URL")]
+    mirrors: Vec<String>,
+
+    /// Probe every `--mirror` (and the primary) with a small ranged
+    /// read and download from the fastest
+    #[arg(long, requires = "mirrors")]
+    probe_mirrors: bool,
+
+    /// Skip the free-space check against the announced file size
+    #[arg(long)]
+    no_space_check: bool,
+
+    /// Delete the partial `.part` file when a download fails or is
+    /// interrupted instead of keeping it to resume from next time
+    #[arg(long)]
+    no_resume: bool,
+
+    /// Maximum concurrent connections to one host, shared by every
+    /// transfer (parallel segments, queue entries); 0 = unlimited
+    #[arg(long, default_value_t = 0)]
+    max_connections_per_host: usize,
+
+    /// Minimum delay between two requests to the same host, in
+    /// milliseconds
+    #[arg(long, default_value_t = 0)]
+    request_delay_ms: u64,
+}
+
+/// Subcommands
+#[derive(Subcommand)]
+enum Command {
+    /// Persistent download queue for maintaining many extracts
+    Queue {
+        /// Queue file
+        #[arg(long, default_value = butterfly_dl::queue::DEFAULT_QUEUE_FILE)]
+        queue_file: PathBuf,
+
+        #[command(subcommand)]
+        action: QueueAction,
+    },
+}
+
+/// `butterfly-dl queue` actions
+#[derive(Subcommand)]
+enum QueueAction {
+    /// Enqueue one or more sources
+    Add {
+        /// Sources ("europe/belgium", "planet", raw URL, …)
+        #[arg(required = true)]
+        sources: Vec<String>,
+
+        /// Higher priorities are downloaded first
+        #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+        priority: i32,
+
+        /// Directory the files are written to
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+
+        /// Geofabrik artifact format
+        #[arg(long, default_value = "pbf", value_parser = parse_format)]
+        format: FileFormat,
+    },
+    /// Show every entry and the per-state counts
+    Status,
+    /// Download every pending (and previously failed or interrupted) entry
+    Run {
+        /// Maximum concurrent transfers
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+
+        /// Keep running and poll the queue file for new entries every
+        /// SECS instead of exiting once nothing is pending
+        #[arg(long, value_name = "SECS")]
+        watch: Option<u64>,
+    },
+}
+
+/// clap value parser for `--format`
+fn parse_format(s: &str) -> std::result::Result<FileFormat, String> {
+    s.parse().map_err(|e: butterfly_dl::Error| e.to_string())
+}
+
+/// clap value parser for `--header "Name: value"`
+fn parse_header(s: &str) -> std::result::Result<(String, String), String> {
+    let (name, value) = s
+        .split_once(':')
+        .ok_or_else(|| format!("expected \"Name: value\", got '{s}'"))?;
+    let (name, value) = (name.trim(), value.trim());
+    reqwest::header::HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| format!("invalid header name '{name}'"))?;
+    reqwest::header::HeaderValue::from_str(value)
+        .map_err(|_| format!("invalid value for header '{name}'"))?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+impl Cli {
+    /// Downloader carrying `--user-agent`, `--header`, the mirror and
+    /// the per-host politeness flags.
+    fn downloader(&self) -> Downloader {
+        let mut config = SourceConfig {
+            extra_headers: self.headers.clone(),
+            mirrors: self.mirrors.clone(),
+            probe_mirrors: self.probe_mirrors,
+            politeness: Politeness {
+                max_connections_per_host: self.max_connections_per_host,
+                request_delay: Duration::from_millis(self.request_delay_ms),
+            },
+            ..Default::default()
+        };
+        if let Some(user_agent) = &self.user_agent {
+            config.user_agent = user_agent.clone();
+        }
+        Downloader::with_config(config)
+    }
+
+    /// Source identifier (set from the bbox lookup in `--bbox` mode).
+    fn source(&self) -> &str {
+        self.source.as_deref().unwrap_or_default()
+    }
+
+    /// `--bbox` mode: look up the covering extract and rewrite
+    /// `source`/`output` so the rest of the CLI downloads it like any
+    /// other single-file source. The positional argument, if given,
+    /// is the output path.
+    async fn resolve_bbox(&mut self, bbox: &str, downloader: &Downloader) -> Result<()> {
+        if self.source.is_some() && !self.output.is_empty() {
+            return Err(butterfly_dl::Error::InvalidInput(
+                "with --bbox, pass at most one positional argument (the output path)".into(),
+            ));
+        }
+        let bbox = butterfly_dl::discovery::BBox::parse(bbox)?;
+        let index = butterfly_dl::discovery::GeofabrikIndex::fetch(downloader).await?;
+        let extract = index.smallest_covering(&bbox).ok_or_else(|| {
+            butterfly_dl::Error::SourceNotFound(format!(
+                "no Geofabrik extract covers bbox {},{},{},{}",
+                bbox.min_lon, bbox.min_lat, bbox.max_lon, bbox.max_lat
+            ))
+        })?;
+        let url = extract
+            .pbf_url()
+            .expect("smallest_covering only returns extracts with a PBF")
+            .to_string();
+        eprintln!("🗺️  bbox covered by '{}' ({})", extract.id, extract.name);
+        if let Some(output) = self.source.take() {
+            self.output = output;
+        }
+        if self.output.is_empty() {
+            self.output = extract.output_filename();
+        }
+        self.source = Some(url);
+        Ok(())
+    }
+}
+
+/// Progress output formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ProgressFormat {
+    /// Interactive progress bar
+    Bar,
+    /// Newline-delimited JSON events on stderr
+    Json,
 }
 
 /// Output destination types
@@ -83,18 +311,19 @@ enum OutputDestination {
 }
 
 /// Resolve output destination from CLI arguments
-fn resolve_output(source: &str, output: &str) -> OutputDestination {
+fn resolve_output(source: &str, output: &str, format: FileFormat) -> OutputDestination {
     if output == "-" {
         OutputDestination::Stdout
     } else if output.is_empty() {
         // Auto-generate filename
+        let suffix = format.suffix();
         let filename = match source {
-            "planet" => "planet-latest.osm.pbf".to_string(),
+            "planet" => format!("planet{suffix}"),
             path if path.contains('/') => {
                 let name = path.split('/').next_back().unwrap_or(path);
-                format!("{name}-latest.osm.pbf")
+                format!("{name}{suffix}")
             }
-            continent => format!("{continent}-latest.osm.pbf"),
+            continent => format!("{continent}{suffix}"),
         };
         OutputDestination::File(filename)
     } else {
@@ -104,98 +333,299 @@ fn resolve_output(source: &str, output: &str) -> OutputDestination {
 
 #[tokio::main]
 async fn main() {
-    if let Err(e) = run().await {
+    // Racing the download against the signal drops the download future
+    // on Ctrl-C/SIGTERM, which runs the library's `.part` cleanup guard
+    // (kept for resume unless `--no-resume`) before the process exits.
+    let result = tokio::select! {
+        result = run() => result,
+        () = interrupted() => {
+            eprintln!("\n⏹️  Interrupted");
+            std::process::exit(130);
+        }
+    };
+    if let Err(e) = result {
         error!("❌ Error: {e}");
         std::process::exit(1);
     }
 }
 
+/// Resolves on SIGINT (Ctrl-C) or SIGTERM.
+async fn interrupted() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        signal(SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
 async fn run() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
 
-    // Initialize logging to stderr
-    env_logger::Builder::from_default_env()
-        .target(env_logger::Target::Stderr)
+    // Initialize logging to stderr. Library spans/events are filtered
+    // by RUST_LOG (e.g. `RUST_LOG=butterfly_dl=debug` for per-segment
+    // telemetry); errors only by default.
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("error")),
+        )
+        .with_writer(std::io::stderr)
         .init();
 
     if cli.verbose {
         eprintln!("🦋 Butterfly-dl v{} starting...", env!("BUTTERFLY_VERSION"));
     }
 
+    let downloader = cli.downloader();
+
+    if let Some(Command::Queue { queue_file, action }) = &cli.command {
+        return run_queue(queue_file, action, downloader).await;
+    }
+
+    if let Some(bbox) = cli.bbox.clone() {
+        cli.resolve_bbox(&bbox, &downloader).await?;
+    }
+
     // Region-indexed path: a bare region name (e.g. "belgium",
     // "france") matches a shipped TOML and dispatches every file
     // for that region in parallel. Path-shaped inputs (`europe/belgium`)
     // and special presets (`planet`, `europe`, …) fall through to
     // the single-file Geofabrik code path below.
-    if shipped_regions().contains(&cli.source.as_str()) {
+    if shipped_regions().contains(&cli.source()) {
         return run_region(&cli).await;
     }
 
     // Resolve output destination
-    let output = resolve_output(&cli.source, &cli.output);
+    let output = resolve_output(cli.source(), &cli.output, cli.format);
 
     if cli.dry_run {
-        let source = &cli.source;
+        let source = cli.source();
         eprintln!("🔍 [DRY RUN] Would download: {source} to {output:?}");
         return Ok(());
     }
 
+    if cli.decompress && !matches!(output, OutputDestination::Stdout) {
+        return Err(butterfly_dl::Error::InvalidInput(
+            "--decompress only applies when streaming to stdout (output \"-\")".into(),
+        ));
+    }
+
     // Validate conflicting flags
     if cli.force && cli.no_clobber {
         eprintln!("❌ Error: --force and --no-clobber cannot be used together");
         std::process::exit(1);
     }
 
+    if cli.progress == ProgressFormat::Json {
+        return run_json(&cli, &downloader, output).await;
+    }
+
     // Handle different output destinations
     match output {
         OutputDestination::File(file_path) => {
-            download_to_file(
-                &cli.source,
-                &file_path,
-                cli.verbose,
-                cli.force,
-                cli.no_clobber,
-            )
-            .await?;
+            download_to_file(&cli, &downloader, &file_path).await?;
         }
         OutputDestination::Stdout => {
-            download_to_stdout(&cli.source, cli.verbose).await?;
+            download_to_stdout(&cli, &downloader).await?;
         }
     }
 
     Ok(())
 }
 
-/// Download to a file with progress bar
-async fn download_to_file(
-    source: &str,
-    file_path: &str,
-    verbose: bool,
-    force: bool,
-    no_clobber: bool,
-) -> Result<()> {
-    if verbose {
-        // Show download source information
-        show_download_info(source);
-    }
-
-    eprintln!("📁 Saving to: {file_path}");
-
-    // Determine overwrite behavior from CLI flags
-    let overwrite = if force {
+/// Determine overwrite behavior from CLI flags
+fn overwrite_behavior(force: bool, no_clobber: bool) -> OverwriteBehavior {
+    if force {
         OverwriteBehavior::Force
     } else if no_clobber {
         OverwriteBehavior::NeverOverwrite
     } else {
         OverwriteBehavior::Prompt
+    }
+}
+
+/// `--progress json`: same downloads as the bar path, but every
+/// status line is an NDJSON event on stderr and the human-oriented
+/// messages are suppressed so wrappers can parse stderr line by line.
+async fn run_json(cli: &Cli, downloader: &Downloader, output: OutputDestination) -> Result<()> {
+    let reporter = Arc::new(cli::JsonProgressReporter::new(Duration::from_secs(
+        cli.progress_interval.max(1),
+    )));
+    let dest = match &output {
+        OutputDestination::File(path) => path.as_str(),
+        OutputDestination::Stdout => "-",
+    };
+    reporter.emit(&cli::JsonEvent::Start {
+        source: cli.source(),
+        dest,
+    });
+
+    let result = match &output {
+        OutputDestination::File(file_path) => {
+            download_to_file_json(cli, downloader, file_path, Arc::clone(&reporter)).await
+        }
+        OutputDestination::Stdout => {
+            download_to_stdout_json(cli, downloader, Arc::clone(&reporter)).await
+        }
+    };
+    if let Err(e) = &result {
+        reporter.emit(&cli::JsonEvent::Error {
+            message: &e.to_string(),
+        });
+    }
+    result
+}
+
+/// File download reporting through NDJSON events. The `done` event
+/// carries the SHA-256 from the `.sha256` sidecar when the library
+/// wrote one, otherwise a fresh hash of the file on disk.
+async fn download_to_file_json(
+    cli: &Cli,
+    downloader: &Downloader,
+    file_path: &str,
+    reporter: Arc<cli::JsonProgressReporter>,
+) -> Result<()> {
+    let options = DownloadOptions {
+        overwrite: overwrite_behavior(cli.force, cli.no_clobber),
+        format: cli.format,
+        check_space: !cli.no_space_check,
+        resume: !cli.no_resume,
+        progress: Some(Arc::new({
+            let reporter = Arc::clone(&reporter);
+            move |downloaded, total| reporter.progress(downloaded, total)
+        })),
+        retry: Some(retry_events(&reporter)),
+        ..Default::default()
     };
 
+    if cli.write_manifest {
+        let manifest = downloader
+            .get_with_manifest(cli.source(), Some(file_path), options)
+            .await?;
+        let mut sha = [0u8; 32];
+        let sha = hex::decode_to_slice(&manifest.sha256, &mut sha)
+            .ok()
+            .map(|()| sha);
+        reporter.done(manifest.size, sha);
+        return Ok(());
+    }
+
+    downloader
+        .get_with_options(cli.source(), Some(file_path), options)
+        .await?;
+
+    let path = PathBuf::from(file_path);
+    let (bytes, sha256) = tokio::task::spawn_blocking(move || {
+        let bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let sha = butterfly_dl::verified::read_sidecar(&path)
+            .or_else(|| butterfly_dl::verified::hash_file_if_exists(&path));
+        (bytes, sha)
+    })
+    .await
+    .map_err(|e| butterfly_dl::Error::DownloadFailed(format!("hash task failed: {e}")))?;
+    reporter.done(bytes, sha256);
+    Ok(())
+}
+
+/// Stdout streaming reporting through NDJSON events. Bytes are
+/// counted and hashed on their way to stdout, so `progress` and `done`
+/// carry the same fields as the file path; `done.sha256` is the hash
+/// of what was written (the decoded bytes with `--decompress`).
+async fn download_to_stdout_json(
+    cli: &Cli,
+    downloader: &Downloader,
+    reporter: Arc<cli::JsonProgressReporter>,
+) -> Result<()> {
+    use sha2::{Digest, Sha256};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let options = DownloadOptions {
+        format: cli.format,
+        decompress: cli.decompress,
+        retry: Some(retry_events(&reporter)),
+        ..Default::default()
+    };
+    let (mut stream, total_size) = downloader.download_stream(cli.source(), &options).await?;
+    // The decoded size is unknown until the stream is drained; 0 keeps
+    // `progress` events flowing instead of comparing against the
+    // compressed Content-Length.
+    let total = if cli.decompress { 0 } else { total_size };
+    let mut stdout = tokio::io::stdout();
+    let mut hasher = Sha256::new();
+    let mut bytes = 0u64;
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = stream
+            .read(&mut buf)
+            .await
+            .map_err(butterfly_dl::Error::IoError)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        stdout
+            .write_all(&buf[..n])
+            .await
+            .map_err(butterfly_dl::Error::IoError)?;
+        bytes += n as u64;
+        reporter.progress(bytes, total);
+    }
+    stdout.flush().await.map_err(butterfly_dl::Error::IoError)?;
+
+    let mut sha = [0u8; 32];
+    sha.copy_from_slice(hasher.finalize().as_slice());
+    reporter.done(bytes, Some(sha));
+    Ok(())
+}
+
+/// Retry hook emitting a `retry` event per backoff.
+fn retry_events(reporter: &Arc<cli::JsonProgressReporter>) -> butterfly_dl::RetryCallback {
+    let reporter = Arc::clone(reporter);
+    Arc::new(move |attempt, delay_ms, error| {
+        reporter.emit(&cli::JsonEvent::Retry {
+            attempt,
+            delay_ms,
+            error,
+        })
+    })
+}
+
+/// Download to a file with progress bar
+async fn download_to_file(cli: &Cli, downloader: &Downloader, file_path: &str) -> Result<()> {
+    let source = cli.source();
+    if cli.verbose {
+        // Show download source information
+        show_download_info(source, cli.format);
+    }
+
+    eprintln!("📁 Saving to: {file_path}");
+
     // Create progress bar manager
     let progress_manager = cli::ProgressManager::new(0, &format!("🌐 Downloading {source}"));
 
     // Create download options with overwrite behavior
     let options = DownloadOptions {
-        overwrite,
+        overwrite: overwrite_behavior(cli.force, cli.no_clobber),
+        format: cli.format,
+        check_space: !cli.no_space_check,
+        resume: !cli.no_resume,
         progress: Some(std::sync::Arc::new({
             let pb = progress_manager.pb.clone();
             move |downloaded, total| {
@@ -212,20 +642,38 @@ async fn download_to_file(
     };
 
     // Use library with custom options
-    butterfly_dl::get_with_options(source, Some(file_path), options).await?;
+    if cli.write_manifest {
+        let manifest = downloader
+            .get_with_manifest(source, Some(file_path), options)
+            .await?;
+        eprintln!(
+            "🧾 Manifest: {} (sha256 {})",
+            butterfly_dl::manifest::manifest_path(std::path::Path::new(file_path)).display(),
+            manifest.sha256
+        );
+    } else {
+        downloader
+            .get_with_options(source, Some(file_path), options)
+            .await?;
+    }
 
     Ok(())
 }
 
 /// Download to stdout (no progress bar)
-async fn download_to_stdout(source: &str, verbose: bool) -> Result<()> {
-    if verbose {
-        show_download_info(source);
+async fn download_to_stdout(cli: &Cli, downloader: &Downloader) -> Result<()> {
+    if cli.verbose {
+        show_download_info(cli.source(), cli.format);
         eprintln!("📡 Streaming to stdout");
     }
 
     // Get stream and pipe to stdout
-    let mut stream = butterfly_dl::get_stream(source).await?;
+    let options = DownloadOptions {
+        format: cli.format,
+        decompress: cli.decompress,
+        ..Default::default()
+    };
+    let (mut stream, _total_size) = downloader.download_stream(cli.source(), &options).await?;
     let mut stdout = tokio::io::stdout();
 
     tokio::io::copy(&mut stream, &mut stdout)
@@ -235,12 +683,76 @@ async fn download_to_stdout(source: &str, verbose: bool) -> Result<()> {
     Ok(())
 }
 
+/// `butterfly-dl queue add/status/run`
+async fn run_queue(queue_file: &Path, action: &QueueAction, downloader: Downloader) -> Result<()> {
+    use butterfly_dl::queue::{DownloadQueue, run};
+
+    match action {
+        QueueAction::Add {
+            sources,
+            priority,
+            dir,
+            format,
+        } => {
+            let ids = DownloadQueue::update(queue_file, |queue| {
+                sources
+                    .iter()
+                    .map(|source| queue.add(source, dir, *format, *priority))
+                    .collect::<Vec<_>>()
+            })?;
+            for (id, source) in ids.iter().zip(sources) {
+                eprintln!("➕ queued #{id}: {source} (priority {priority})");
+            }
+        }
+        QueueAction::Status => {
+            let queue = DownloadQueue::open(queue_file)?;
+            for e in queue.entries() {
+                let state = format!("{:?}", e.state).to_lowercase();
+                let error = e
+                    .error
+                    .as_deref()
+                    .map(|e| format!(" — {e}"))
+                    .unwrap_or_default();
+                println!(
+                    "#{:<4} {state:<8} p{:<3} {} → {}{error}",
+                    e.id,
+                    e.priority,
+                    e.source,
+                    e.dest.display()
+                );
+            }
+            let s = queue.status();
+            println!(
+                "pending {} · running {} · done {} · failed {}",
+                s.pending, s.running, s.done, s.failed
+            );
+        }
+        QueueAction::Run { concurrency, watch } => {
+            eprintln!(
+                "🦋 Draining {} with {concurrency} concurrent transfer(s)",
+                queue_file.display()
+            );
+            let poll = watch.map(|secs| std::time::Duration::from_secs(secs.max(1)));
+            let s = run(queue_file, Arc::new(downloader), *concurrency, poll).await?;
+            eprintln!("✅ done {} · ❌ failed {}", s.done, s.failed);
+            if s.failed > 0 {
+                return Err(butterfly_dl::Error::DownloadFailed(format!(
+                    "{} queue entr{} failed; see `butterfly-dl queue status`",
+                    s.failed,
+                    if s.failed == 1 { "y" } else { "ies" }
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Region-indexed parallel fetch. One-command provisioning: consults
 /// `dl/regions/<region>.toml`, dispatches every entry through
 /// `verified::download_verified` concurrently, prints a per-entry
 /// report.
 async fn run_region(cli: &Cli) -> Result<()> {
-    let region = cli.source.as_str();
+    let region = cli.source();
     let data_root = cli
         .to
         .clone()
@@ -338,22 +850,31 @@ async fn run_region(cli: &Cli) -> Result<()> {
 }
 
 /// Show information about the download source
-fn show_download_info(source: &str) {
+fn show_download_info(source: &str, format: FileFormat) {
+    let suffix = format.suffix();
+    let host = match format {
+        FileFormat::InternalPbf => "osm-internal.download.geofabrik.de",
+        _ => "download.geofabrik.de",
+    };
     match source {
+        url if url.starts_with("http://") || url.starts_with("https://") => {
+            eprintln!("🌐 Downloading from HTTP: {url}");
+        }
+        "planet" if format == FileFormat::OsmBz2 => {
+            eprintln!(
+                "🌐 Downloading from HTTP: https://planet.openstreetmap.org/planet/planet-latest.osm.bz2"
+            );
+        }
         "planet" => {
             eprintln!(
                 "🌐 Downloading from HTTP: https://planet.openstreetmap.org/pbf/planet-latest.osm.pbf"
             );
         }
         path if path.contains('/') => {
-            eprintln!(
-                "🌐 Downloading from HTTP: https://download.geofabrik.de/{path}-latest.osm.pbf"
-            );
+            eprintln!("🌐 Downloading from HTTP: https://{host}/{path}{suffix}");
         }
         continent => {
-            eprintln!(
-                "🌐 Downloading from HTTP: https://download.geofabrik.de/{continent}-latest.osm.pbf"
-            );
+            eprintln!("🌐 Downloading from HTTP: https://{host}/{continent}{suffix}");
         }
     }
 }
@@ -364,7 +885,7 @@ mod tests {
 
     #[test]
     fn test_resolve_output_auto() {
-        let output = resolve_output("europe/belgium", "");
+        let output = resolve_output("europe/belgium", "", FileFormat::Pbf);
         match output {
             OutputDestination::File(path) => {
                 assert_eq!(path, "belgium-latest.osm.pbf");
@@ -373,9 +894,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolve_output_per_format() {
+        match resolve_output("europe/belgium", "", FileFormat::OsmBz2) {
+            OutputDestination::File(path) => assert_eq!(path, "belgium-latest.osm.bz2"),
+            _ => panic!("Expected file output"),
+        }
+        let cli =
+            Cli::try_parse_from(["butterfly-dl", "europe/belgium", "--format", "shp"]).unwrap();
+        assert_eq!(cli.format, FileFormat::Shp);
+        assert!(Cli::try_parse_from(["butterfly-dl", "europe", "--format", "gpkg"]).is_err());
+    }
+
     #[test]
     fn test_resolve_output_stdout() {
-        let output = resolve_output("planet", "-");
+        let output = resolve_output("planet", "-", FileFormat::Pbf);
         match output {
             OutputDestination::Stdout => {}
             _ => panic!("Expected stdout output"),
@@ -384,7 +917,7 @@ mod tests {
 
     #[test]
     fn test_resolve_output_custom_file() {
-        let output = resolve_output("planet", "my-planet.pbf");
+        let output = resolve_output("planet", "my-planet.pbf", FileFormat::Pbf);
         match output {
             OutputDestination::File(path) => {
                 assert_eq!(path, "my-planet.pbf");
@@ -392,4 +925,131 @@ mod tests {
             _ => panic!("Expected file output"),
         }
     }
+
+    #[test]
+    fn test_progress_flag_parses_json() {
+        let cli =
+            Cli::try_parse_from(["butterfly-dl", "europe/monaco", "--progress", "json"]).unwrap();
+        assert_eq!(cli.progress, ProgressFormat::Json);
+        assert_eq!(cli.progress_interval, 1);
+
+        let cli = Cli::try_parse_from(["butterfly-dl", "europe/monaco"]).unwrap();
+        assert_eq!(cli.progress, ProgressFormat::Bar);
+    }
+
+    #[test]
+    fn test_bbox_makes_source_optional() {
+        let cli = Cli::try_parse_from(["butterfly-dl", "--bbox", "4.2,50.7,4.5,50.9"]).unwrap();
+        assert_eq!(cli.bbox.as_deref(), Some("4.2,50.7,4.5,50.9"));
+        assert!(cli.source.is_none());
+
+        let cli =
+            Cli::try_parse_from(["butterfly-dl", "--bbox", "-3.7,40.4", "madrid.pbf"]).unwrap();
+        assert_eq!(cli.bbox.as_deref(), Some("-3.7,40.4"));
+        assert_eq!(cli.source.as_deref(), Some("madrid.pbf"));
+
+        assert!(Cli::try_parse_from(["butterfly-dl"]).is_err());
+    }
+
+    #[test]
+    fn test_queue_subcommand_parses() {
+        let cli = Cli::try_parse_from([
+            "butterfly-dl",
+            "queue",
+            "add",
+            "europe/belgium",
+            "europe/france",
+            "--priority",
+            "-1",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Queue {
+                action:
+                    QueueAction::Add {
+                        sources, priority, ..
+                    },
+                ..
+            }) => {
+                assert_eq!(sources, ["europe/belgium", "europe/france"]);
+                assert_eq!(priority, -1);
+            }
+            _ => panic!("expected queue add"),
+        }
+
+        let cli =
+            Cli::try_parse_from(["butterfly-dl", "queue", "run", "--concurrency", "8"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Queue {
+                action: QueueAction::Run {
+                    concurrency: 8,
+                    watch: None
+                },
+                ..
+            })
+        ));
+        let cli = Cli::try_parse_from(["butterfly-dl", "queue", "run", "--watch", "30"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Queue {
+                action: QueueAction::Run {
+                    watch: Some(30),
+                    ..
+                },
+                ..
+            })
+        ));
+
+        // A plain source still parses without a subcommand.
+        let cli = Cli::try_parse_from(["butterfly-dl", "europe/monaco"]).unwrap();
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_header_flag_is_repeatable() {
+        let cli = Cli::try_parse_from([
+            "butterfly-dl",
+            "europe/monaco",
+            "--header",
+            "X-Mirror-Token: abc",
+            "--header",
+            "From:ops@example.org",
+            "--user-agent",
+            "acme-mirror/1.0",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.headers,
+            vec![
+                ("X-Mirror-Token".to_string(), "abc".to_string()),
+                ("From".to_string(), "ops@example.org".to_string()),
+            ]
+        );
+        assert_eq!(cli.user_agent.as_deref(), Some("acme-mirror/1.0"));
+
+        assert!(Cli::try_parse_from(["butterfly-dl", "x", "--header", "no-colon"]).is_err());
+        assert!(Cli::try_parse_from(["butterfly-dl", "x", "--header", "bad name: v"]).is_err());
+    }
+
+    #[test]
+    fn test_politeness_flags() {
+        let cli = Cli::try_parse_from([
+            "butterfly-dl",
+            "--max-connections-per-host",
+            "2",
+            "--request-delay-ms",
+            "500",
+            "queue",
+            "run",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.downloader().config().politeness,
+            Politeness {
+                max_connections_per_host: 2,
+                request_delay: Duration::from_millis(500),
+            }
+        );
+    }
 }
@@ -0,0 +1,102 @@
+//! SHA-256 download manifests.
+//!
+//! A manifest is a small JSON document written next to a downloaded
+//! file as `<dest>.sha256.json`. It pins everything a downstream build
+//! step (e.g. `butterfly-route`'s `step1.lock.json`) needs to record
+//! its input: size, SHA-256, source URL, the server `ETag` and when the
+//! download finished. Unlike the bare `.sha256` sidecar it also carries
+//! provenance, so a lock file can be regenerated without re-fetching.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use butterfly_common::{Error, Result};
+
+/// Contents of `<dest>.sha256.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// File size in bytes.
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the file contents.
+    pub sha256: String,
+    /// URL the file was downloaded from.
+    pub url: String,
+    /// Server `ETag` at download time, if the server sent one.
+    pub etag: Option<String>,
+    /// Completion time, Unix seconds (UTC).
+    pub timestamp: u64,
+}
+
+/// `<dest>.sha256.json`.
+pub fn manifest_path(dest: &Path) -> PathBuf {
+    let mut s = dest.as_os_str().to_owned();
+    s.push(".sha256.json");
+    PathBuf::from(s)
+}
+
+impl Manifest {
+    /// Read a manifest back from `<dest>.sha256.json`.
+    pub fn read(dest: &Path) -> Option<Self> {
+        let bytes = std::fs::read(manifest_path(dest)).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Write the manifest to `<dest>.sha256.json` (temp file + rename).
+    pub fn write(&self, dest: &Path) -> Result<()> {
+        let path = manifest_path(dest);
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|e| Error::InvalidInput(format!("manifest serialization failed: {e}")))?;
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
+
+/// Hash `dest` and build its manifest. Blocking (reads the whole
+/// file); call from `spawn_blocking` in async contexts.
+pub(crate) fn build(dest: &Path, url: &str, etag: Option<String>) -> Result<Manifest> {
+    let size = std::fs::metadata(dest)?.len();
+    let sha = crate::verified::hash_file_if_exists(dest).ok_or_else(|| {
+        Error::DownloadFailed(format!("cannot hash downloaded file {}", dest.display()))
+    })?;
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(Manifest {
+        size,
+        sha256: hex::encode(sha),
+        url: url.to_string(),
+        etag,
+        timestamp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_write_read_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("monaco-latest.osm.pbf");
+        std::fs::write(&dest, b"abc").unwrap();
+
+        let m = build(&dest, "https://example.org/m.pbf", Some("\"v1\"".into())).unwrap();
+        assert_eq!(m.size, 3);
+        assert_eq!(
+            m.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        m.write(&dest).unwrap();
+        assert_eq!(
+            manifest_path(&dest),
+            dir.path().join("monaco-latest.osm.pbf.sha256.json")
+        );
+        assert_eq!(Manifest::read(&dest), Some(m));
+    }
+}
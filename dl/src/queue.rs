@@ -0,0 +1,462 @@
+//! Persistent download queue.
+//!
+//! Operators maintaining a mirror of many extracts enqueue sources
+//! with a priority and let [`run`] drain the queue with a bounded
+//! number of concurrent transfers. The queue lives in a JSON file
+//! (`butterfly-queue.json` by default) that is rewritten after every
+//! state change, so `queue status` from another shell sees live
+//! progress and an interrupted run picks up where it stopped: entries
+//! left `running` go back to `pending` and resume from their `.part`
+//! files.
+//!
+//! Every change is a read-modify-write of the file under an exclusive
+//! lock on `<queue>.lock` ([`DownloadQueue::update`]), so `queue add`
+//! while a run is active lands in the file and the run picks the new
+//! entries up. A run additionally holds `<queue>.run.lock` for its
+//! whole lifetime: a second runner on the same queue is refused.
+//!
+//! Entries are taken highest priority first, then in insertion order.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+
+use crate::core::{Downloader, FileFormat, resolve_output_filename};
+use crate::{DownloadOptions, OverwriteBehavior};
+use butterfly_common::{Error, Result};
+
+/// Default queue file, relative to the working directory.
+pub const DEFAULT_QUEUE_FILE: &str = "butterfly-queue.json";
+
+/// Lifecycle of a queue entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryState {
+    /// Waiting for a transfer slot.
+    Pending,
+    /// Transfer in progress.
+    Running,
+    /// Downloaded successfully.
+    Done,
+    /// Last attempt failed; see [`QueueEntry::error`].
+    Failed,
+}
+
+/// One enqueued download.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueEntry {
+    /// Queue-unique id, assigned on [`DownloadQueue::add`].
+    pub id: u64,
+    /// Source identifier (`europe/belgium`, `planet`, raw URL, …).
+    pub source: String,
+    /// Destination file.
+    pub dest: PathBuf,
+    /// Geofabrik artifact to fetch.
+    pub format: FileFormat,
+    /// Higher runs first.
+    pub priority: i32,
+    /// Current state.
+    pub state: EntryState,
+    /// Error message of the last failed attempt.
+    pub error: Option<String>,
+}
+
+/// Per-state entry counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QueueStatus {
+    /// Waiting entries.
+    pub pending: usize,
+    /// Entries being transferred.
+    pub running: usize,
+    /// Completed entries.
+    pub done: usize,
+    /// Entries whose last attempt failed.
+    pub failed: usize,
+}
+
+/// The on-disk queue.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DownloadQueue {
+    next_id: u64,
+    entries: Vec<QueueEntry>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl DownloadQueue {
+    /// Open the queue at `path`; a missing file is an empty queue.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut queue: Self = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                Error::InvalidInput(format!("corrupt queue file {}: {e}", path.display()))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e.into()),
+        };
+        queue.path = path.to_path_buf();
+        Ok(queue)
+    }
+
+    /// Open the queue at `path` under its lock, apply `f` and save the
+    /// result. Concurrent writers (`queue add`, a running `queue run`)
+    /// serialize on the lock, so none of them overwrites the others'
+    /// changes.
+    pub fn update<T>(path: &Path, f: impl FnOnce(&mut Self) -> T) -> Result<T> {
+        let lock = lock_file(path, "lock")?;
+        lock.lock()?;
+        let mut queue = Self::open(path)?;
+        let out = f(&mut queue);
+        queue.save()?;
+        Ok(out)
+    }
+
+    /// Write the queue back to its file (temp file + rename).
+    pub fn save(&self) -> Result<()> {
+        let mut tmp = self.path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|e| Error::InvalidInput(format!("queue serialization failed: {e}")))?;
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Enqueue `source`, downloading into `dir` under its
+    /// auto-generated filename. Returns the new entry id.
+    pub fn add(&mut self, source: &str, dir: &Path, format: FileFormat, priority: i32) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(QueueEntry {
+            id,
+            source: source.to_string(),
+            dest: dir.join(resolve_output_filename(source, format)),
+            format,
+            priority,
+            state: EntryState::Pending,
+            error: None,
+        });
+        id
+    }
+
+    /// Every entry, in insertion order.
+    pub fn entries(&self) -> &[QueueEntry] {
+        &self.entries
+    }
+
+    /// Per-state counts.
+    pub fn status(&self) -> QueueStatus {
+        let mut status = QueueStatus::default();
+        for entry in &self.entries {
+            match entry.state {
+                EntryState::Pending => status.pending += 1,
+                EntryState::Running => status.running += 1,
+                EntryState::Done => status.done += 1,
+                EntryState::Failed => status.failed += 1,
+            }
+        }
+        status
+    }
+
+    /// Put entries left `running` (interrupted run) and `failed`
+    /// entries back to `pending`.
+    pub fn requeue_unfinished(&mut self) {
+        for entry in &mut self.entries {
+            if matches!(entry.state, EntryState::Running | EntryState::Failed) {
+                entry.state = EntryState::Pending;
+            }
+        }
+    }
+
+    /// Mark the next pending entry (highest priority, then oldest)
+    /// as running and return a copy of it.
+    fn start_next(&mut self) -> Option<QueueEntry> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .filter(|e| e.state == EntryState::Pending)
+            .min_by_key(|e| (std::cmp::Reverse(e.priority), e.id))?;
+        entry.state = EntryState::Running;
+        entry.error = None;
+        Some(entry.clone())
+    }
+
+    fn finish(&mut self, id: u64, result: Result<()>) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+            match result {
+                Ok(()) => entry.state = EntryState::Done,
+                Err(e) => {
+                    entry.state = EntryState::Failed;
+                    entry.error = Some(e.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Sibling lock file `<queue>.<suffix>`, created on first use.
+fn lock_file(path: &Path, suffix: &str) -> Result<File> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    Ok(OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(PathBuf::from(name))?)
+}
+
+/// Drain the queue at `path` with at most `concurrency` transfers in
+/// flight, persisting every state change. Existing destination files
+/// are overwritten: enqueuing a source is the operator's request to
+/// refresh it. Individual failures are recorded on their entries and
+/// don't stop the run; the returned status reports them.
+///
+/// The file is re-read before every change, so entries added while
+/// the run is active are downloaded too. With `poll` set the run is a
+/// daemon: instead of returning once nothing is pending, it checks
+/// the file for new entries every `poll` (also while transfers are in
+/// flight and a slot is free) and never returns on its own.
+///
+/// Fails with [`Error::InvalidInput`] when another run holds the queue.
+pub async fn run(
+    path: &Path,
+    downloader: Arc<Downloader>,
+    concurrency: usize,
+    poll: Option<Duration>,
+) -> Result<QueueStatus> {
+    let runner = lock_file(path, "run.lock")?;
+    match runner.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            return Err(Error::InvalidInput(format!(
+                "another `queue run` is active on {}",
+                path.display()
+            )));
+        }
+        Err(TryLockError::Error(e)) => return Err(e.into()),
+    }
+    DownloadQueue::update(path, DownloadQueue::requeue_unfinished)?;
+    let concurrency = concurrency.max(1);
+
+    let mut in_flight = JoinSet::new();
+    loop {
+        while in_flight.len() < concurrency {
+            let Some(entry) = DownloadQueue::update(path, DownloadQueue::start_next)? else {
+                break;
+            };
+
+            let downloader = Arc::clone(&downloader);
+            in_flight.spawn(async move {
+                tracing::info!(id = entry.id, source = %entry.source, "queue: starting");
+                (entry.id, transfer(&downloader, &entry).await)
+            });
+        }
+
+        let joined = match poll {
+            None => in_flight.join_next().await,
+            Some(interval) if in_flight.is_empty() => {
+                tokio::time::sleep(interval).await;
+                continue;
+            }
+            Some(interval) if in_flight.len() < concurrency => {
+                match tokio::time::timeout(interval, in_flight.join_next()).await {
+                    Ok(joined) => joined,
+                    Err(_) => continue,
+                }
+            }
+            Some(_) => in_flight.join_next().await,
+        };
+        let Some(joined) = joined else {
+            break;
+        };
+        let (id, result) =
+            joined.map_err(|e| Error::DownloadFailed(format!("queue task join error: {e}")))?;
+        match &result {
+            Ok(()) => tracing::info!(id, "queue: done"),
+            Err(e) => tracing::warn!(id, error = %e, "queue: failed"),
+        }
+        DownloadQueue::update(path, |queue| queue.finish(id, result))?;
+    }
+
+    Ok(DownloadQueue::open(path)?.status())
+}
+
+/// Download one entry, creating its destination directory first.
+async fn transfer(downloader: &Downloader, entry: &QueueEntry) -> Result<()> {
+    if let Some(parent) = entry.dest.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let options = DownloadOptions {
+        overwrite: OverwriteBehavior::Force,
+        format: entry.format,
+        ..Default::default()
+    };
+    downloader
+        .get_with_options(&entry.source, Some(&entry.dest.to_string_lossy()), options)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_priority_then_fifo_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = DownloadQueue::open(&dir.path().join("q.json")).unwrap();
+        queue.add("europe/monaco", dir.path(), FileFormat::Pbf, 0);
+        queue.add("europe/andorra", dir.path(), FileFormat::Pbf, 5);
+        queue.add("europe/malta", dir.path(), FileFormat::Pbf, 0);
+
+        let order: Vec<_> = std::iter::from_fn(|| queue.start_next())
+            .map(|e| e.source)
+            .collect();
+        assert_eq!(order, ["europe/andorra", "europe/monaco", "europe/malta"]);
+        assert_eq!(queue.status().running, 3);
+    }
+
+    #[test]
+    fn test_persists_and_requeues_interrupted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("q.json");
+        let mut queue = DownloadQueue::open(&path).unwrap();
+        queue.add("europe/monaco", dir.path(), FileFormat::OsmBz2, 1);
+        queue.start_next().unwrap();
+        queue.save().unwrap();
+
+        let mut reopened = DownloadQueue::open(&path).unwrap();
+        assert_eq!(reopened.entries(), queue.entries());
+        assert_eq!(
+            reopened.entries()[0].dest,
+            dir.path().join("monaco-latest.osm.bz2")
+        );
+        reopened.requeue_unfinished();
+        assert_eq!(reopened.status().pending, 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_drains_queue_and_records_failures() {
+        let server = MockServer::start().await;
+        for verb in ["HEAD", "GET"] {
+            Mock::given(method(verb))
+                .and(path("/ok.pbf"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-length", "4")
+                        .set_body_raw(b"PBF!".to_vec(), "application/octet-stream"),
+                )
+                .mount(&server)
+                .await;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("q.json");
+        let mut queue = DownloadQueue::open(&path).unwrap();
+        queue.add(
+            &format!("{}/ok.pbf", server.uri()),
+            dir.path(),
+            FileFormat::Pbf,
+            0,
+        );
+        queue.add(
+            &format!("{}/missing.pbf", server.uri()),
+            dir.path(),
+            FileFormat::Pbf,
+            0,
+        );
+        queue.save().unwrap();
+
+        let status = run(&path, Arc::new(Downloader::new()), 2, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            status,
+            QueueStatus {
+                done: 1,
+                failed: 1,
+                ..Default::default()
+            }
+        );
+        let queue = DownloadQueue::open(&path).unwrap();
+        assert_eq!(std::fs::read(&queue.entries()[0].dest).unwrap(), b"PBF!");
+        assert!(queue.entries()[1].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_add_during_daemon_run_is_picked_up() {
+        let server = MockServer::start().await;
+        for (file, delay_ms) in [("slow.pbf", 300), ("late.pbf", 0)] {
+            for verb in ["HEAD", "GET"] {
+                Mock::given(method(verb))
+                    .and(path(format!("/{file}")))
+                    .respond_with(
+                        ResponseTemplate::new(200)
+                            .insert_header("content-length", "4")
+                            .set_body_raw(b"PBF!".to_vec(), "application/octet-stream")
+                            .set_delay(Duration::from_millis(delay_ms)),
+                    )
+                    .mount(&server)
+                    .await;
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("q.json");
+        let slow = format!("{}/slow.pbf", server.uri());
+        DownloadQueue::update(&path, |q| q.add(&slow, dir.path(), FileFormat::Pbf, 0)).unwrap();
+
+        let runner = {
+            let path = path.clone();
+            tokio::spawn(async move {
+                run(
+                    &path,
+                    Arc::new(Downloader::new()),
+                    1,
+                    Some(Duration::from_millis(50)),
+                )
+                .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // A second runner on the same queue is refused.
+        let second = run(&path, Arc::new(Downloader::new()), 1, None).await;
+        assert!(matches!(second, Err(Error::InvalidInput(_))));
+
+        // `queue add` while the first transfer is in flight.
+        let late = format!("{}/late.pbf", server.uri());
+        let id =
+            DownloadQueue::update(&path, |q| q.add(&late, dir.path(), FileFormat::Pbf, 0)).unwrap();
+        assert_eq!(id, 1);
+
+        let mut status = QueueStatus::default();
+        for _ in 0..100 {
+            status = DownloadQueue::open(&path).unwrap().status();
+            if status.done == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(!runner.is_finished(), "daemon run must keep polling");
+        runner.abort();
+        assert_eq!(
+            status,
+            QueueStatus {
+                done: 2,
+                ..Default::default()
+            }
+        );
+        let queue = DownloadQueue::open(&path).unwrap();
+        assert_eq!(queue.entries().len(), 2);
+        assert_eq!(std::fs::read(&queue.entries()[1].dest).unwrap(), b"PBF!");
+    }
+}
@@ -261,7 +261,8 @@ async fn resolve_wildcard_url(url: &str) -> Result<String> {
         "only one '*' is supported in a feed url: {url}"
     );
     let index_url = format!("{parent}/");
-    let outcome = crate::core::Downloader::stream_url_conditional(&index_url, None, None)
+    let outcome = crate::core::Downloader::new()
+        .stream_url_conditional(&index_url, None, None)
         .await
         .with_context(|| format!("GET {index_url} (wildcard index for {url})"))?;
     let body = match outcome {
@@ -284,7 +285,7 @@ async fn resolve_wildcard_url(url: &str) -> Result<String> {
     let best = best_wildcard_match(&body, prefix, suffix).with_context(|| {
         format!("no file matching '{pattern}' found in index {index_url} (#498)")
     })?;
-    log::info!("{url}: wildcard resolved → {best} (#498)");
+    tracing::info!("{url}: wildcard resolved → {best} (#498)");
     Ok(format!("{parent}/{best}"))
 }
 
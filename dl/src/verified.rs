@@ -233,6 +233,7 @@ impl Outcome {
 /// See [`VerifiedOptions`] for the full knob list and
 /// [`VerifiedOptions::for_extension`] for the preset helper that
 /// drives the CLI path.
+#[tracing::instrument(name = "download_verified", skip(opts), fields(target = %target.display()))]
 pub async fn download_verified(
     url: &str,
     target: &Path,
@@ -280,24 +281,24 @@ pub async fn download_verified(
     // other butterfly-dl download, no HEAD prelude (mirrors that don't support
     // HEAD just work). With no validators it is a plain GET that still surfaces
     // the response ETag/Last-Modified so a first download can cache them.
-    let (stream, total_size_hint, resp_etag, resp_last_modified) =
-        match Downloader::stream_url_conditional(url, etag_req, lm_req)
-            .await
-            .with_context(|| format!("GET {url} (via butterfly-dl shared client)"))?
-        {
-            ConditionalOutcome::NotModified => {
-                // Upstream unchanged — transfer skipped entirely; the local file
-                // and both sidecars are left untouched.
-                log::info!("{url}: 304 Not Modified — skipped transfer (#418)");
-                return Ok(Outcome::Unchanged);
-            }
-            ConditionalOutcome::Body {
-                stream,
-                total_size,
-                etag,
-                last_modified,
-            } => (stream, total_size, etag, last_modified),
-        };
+    let (stream, total_size_hint, resp_etag, resp_last_modified) = match Downloader::new()
+        .stream_url_conditional(url, etag_req, lm_req)
+        .await
+        .with_context(|| format!("GET {url} (via butterfly-dl shared client)"))?
+    {
+        ConditionalOutcome::NotModified => {
+            // Upstream unchanged — transfer skipped entirely; the local file
+            // and both sidecars are left untouched.
+            tracing::info!("{url}: 304 Not Modified — skipped transfer (#418)");
+            return Ok(Outcome::Unchanged);
+        }
+        ConditionalOutcome::Body {
+            stream,
+            total_size,
+            etag,
+            last_modified,
+        } => (stream, total_size, etag, last_modified),
+    };
 
     // Note on content-type: the shared client's HEAD path already
     // enforced status + content-length. The content-type allowlist is
@@ -469,7 +470,7 @@ pub async fn download_verified(
             last_modified: resp_last_modified,
         };
         if let Err(e) = write_meta(target, &meta) {
-            log::warn!(
+            tracing::warn!(
                 "{}: failed to write validators sidecar: {e:#}",
                 target.display()
             );
@@ -3105,7 +3105,7 @@ fn run_bucket_m2m_bench(
     .ok_or_else(|| anyhow::anyhow!("Cannot find cch.w.{}.u32", mode))?;
 
     println!("Loading CCH topology from {:?}...", topo_path);
-    let topo = CchTopoFile::read(&topo_path)?;
+    let topo = CchTopoFile::read_mmap(&topo_path)?;
     let n_nodes = topo.n_nodes as usize;
     println!(
         "  ✓ {} nodes, {} up edges, {} down edges",
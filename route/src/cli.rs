@@ -7,13 +7,17 @@ use std::path::{Path, PathBuf};
 use crate::contraction;
 use crate::customization;
 use crate::ebg::{EbgConfig, build_ebg};
-use crate::ingest::{IngestConfig, run_ingest};
+use crate::gradient::GradientModel;
+use crate::ingest::update::{UpdateConfig, run_update};
+use crate::ingest::way_filter::WayFilter;
+use crate::ingest::{IngestConfig, IngestInput, run_ingest};
 use crate::nbg::{NbgConfig, build_nbg};
 use crate::ordering;
 use crate::ordering_lifted;
 use crate::profile::{ProfileConfig, run_profiling};
 use crate::profile_abi::Mode;
 use crate::server;
+use crate::server::elevation::ElevationData;
 use crate::validate::{
     Counts, LockFile, validate_step4, validate_step5, validate_step6, validate_step6_lifted,
     validate_step7, verify_lock_conditions,
@@ -235,21 +239,65 @@ pub struct Cli {
 pub enum Commands {
     /// Step 1: Ingest OSM PBF into immutable artifacts
     Step1Ingest {
-        /// Input OSM PBF file
-        #[arg(short, long)]
-        input: PathBuf,
+        /// Input OSM PBF file, or an http(s) URL streamed via butterfly-dl
+        #[arg(short, long, required_unless_present = "source")]
+        input: Option<String>,
+
+        /// butterfly-dl source to stream instead of --input (e.g. `europe/belgium`)
+        #[arg(long, conflicts_with = "input")]
+        source: Option<String>,
 
         /// Output directory for artifacts
         #[arg(short, long)]
         outdir: PathBuf,
 
-        /// Number of threads (currently unused, kept for future)
-        #[arg(short, long, default_value = "8")]
+        /// Number of PBF decoder threads (0 = all cores)
+        #[arg(short, long, default_value = "0")]
         threads: usize,
 
         /// Verify only (don't write, just check CRCs)
         #[arg(long)]
         verify_only: bool,
+
+        /// Memory budget (MiB) for sorting nodes and ways; larger inputs
+        /// spill sorted runs to disk and are merged (planet-scale ingest)
+        #[arg(long, default_value_t = crate::ingest::DEFAULT_MEMORY_BUDGET_MB)]
+        memory_budget_mb: usize,
+
+        /// Directory for sort spill files (default: `<outdir>/.step1-spill`)
+        #[arg(long)]
+        spill_dir: Option<PathBuf>,
+
+        /// Only keep nodes referenced by `highway=*` and `route=ferry` ways
+        /// (decodes ways first, then the input again for nodes; file input only)
+        #[arg(long)]
+        highway_nodes_only: bool,
+
+        /// Keep each way's and relation's OSM version and timestamp
+        /// (ways.raw / relations.raw v2) so step1-update can skip stale
+        /// changes
+        #[arg(long)]
+        keep_metadata: bool,
+
+        /// Ways to keep: comma-separated `key` or `key=value` rules, or
+        /// `all`; recorded in step1.lock.json
+        #[arg(long, default_value = crate::ingest::way_filter::DEFAULT_WAY_FILTER)]
+        way_filter: String,
+    },
+
+    /// Step 1: Apply OsmChange diffs (.osc / .osc.gz) to existing Step 1 artifacts
+    Step1Update {
+        /// Existing Step 1 output directory
+        #[arg(short, long)]
+        base: PathBuf,
+
+        /// Changefiles, applied in the given order
+        #[arg(short, long, required = true, num_args = 1..)]
+        changes: Vec<PathBuf>,
+
+        /// Output directory for the updated artifacts (must differ from --base)
+        #[arg(short, long)]
+        outdir: PathBuf,
     },
 
     /// Step 2: Generate per-mode attributes via routing profiles
@@ -272,6 +320,22 @@ pub enum Commands {
         #[arg(long, default_value = "osm-tag")]
         density_classifier: String,
 
+        /// Vehicle dimensions overriding a model's `vehicle` section, as
+        /// MODE:KEY=VALUE,... in metres / tonnes (e.g. --vehicle
+        /// truck:height=3.5,weight=12); repeat for several modes
+        #[arg(long = "vehicle", value_name = "MODE:DIMS")]
+        vehicle: Vec<String>,
+
+        /// Per-mode speed/access/surface/avoid/turn-penalty overlay
+        /// (default: `<models-dir>/profiles.toml` if present)
+        #[arg(long)]
+        profiles_config: Option<PathBuf>,
+
+        /// GeoJSON country boundaries (ISO 3166-1 codes) selecting each
+        /// model's `speed.country` defaults; needs nodes.sa beside --ways
+        #[arg(long)]
+        countries: Option<PathBuf>,
+
         /// Output directory for way_attrs.*.bin and turn_rules.*.bin
         #[arg(short, long)]
         outdir: PathBuf,
@@ -291,6 +355,29 @@ pub enum Commands {
         #[arg(long = "way-attrs", value_name = "MODE=PATH")]
         way_attrs: Vec<String>,
 
+        /// Path to node_barriers.bin from Step 1 (default: beside --nodes,
+        /// if present)
+        #[arg(long)]
+        node_barriers: Option<PathBuf>,
+
+        /// Memory budget (MiB) for counting node references; larger
+        /// inputs spill sorted runs to disk (planet-scale builds)
+        #[arg(long, default_value_t = crate::ingest::DEFAULT_MEMORY_BUDGET_MB)]
+        memory_budget_mb: usize,
+
+        /// Directory for sort spill files (default: `<outdir>/.step3-spill`)
+        #[arg(long)]
+        spill_dir: Option<PathBuf>,
+
+        /// Directory of SRTM .hgt tiles; samples per-edge ascent/descent
+        /// into nbg.elev
+        #[arg(long = "srtm-dir")]
+        srtm_dir: Option<PathBuf>,
+
+        /// Also store the elevation of every polyline point in nbg.elev
+        #[arg(long, requires = "srtm_dir")]
+        elevation_points: bool,
+
         /// Output directory for nbg.csr, nbg.geo, nbg.node_map
         #[arg(short, long)]
         outdir: PathBuf,
@@ -314,6 +401,10 @@ pub enum Commands {
         #[arg(long)]
         node_signals: Option<PathBuf>,
 
+        /// Path to node_barriers.bin from Step 1 (optional)
+        #[arg(long)]
+        node_barriers: Option<PathBuf>,
+
         /// Per-mode way_attrs paths as mode=path pairs (e.g. --way-attrs car=way_attrs.car.bin)
         #[arg(long = "way-attrs", value_name = "MODE=PATH")]
         way_attrs: Vec<String>,
@@ -355,6 +446,24 @@ pub enum Commands {
         #[arg(long = "way-attrs", value_name = "MODE=PATH")]
         way_attrs: Vec<String>,
 
+        /// Directory of SRTM .hgt tiles; enables gradient-aware travel
+        /// times (foot: tobler, bike: bike-power unless --gradient says otherwise)
+        #[arg(long = "srtm-dir")]
+        srtm_dir: Option<PathBuf>,
+
+        /// Per-mode gradient model as MODE=MODEL (tobler, bike-power, none)
+        #[arg(long = "gradient", value_name = "MODE=MODEL")]
+        gradient: Vec<String>,
+
+        /// Ignore elevation and emit flat-profile weights
+        #[arg(long = "no-gradient")]
+        no_gradient: bool,
+
+        /// Per-mode seconds added to every ferry edge as MODE=SECONDS
+        /// (default: 900 for motor vehicles, 300 otherwise)
+        #[arg(long = "ferry-penalty", value_name = "MODE=SECONDS")]
+        ferry_penalty: Vec<String>,
+
         /// Output directory for w.*.u32, t.*.u32, mask.*.bitset
         #[arg(short, long)]
         outdir: PathBuf,
@@ -389,6 +498,13 @@ pub enum Commands {
         /// Balance epsilon (default: 0.05)
         #[arg(long, default_value = "0.05")]
         balance_eps: f32,
+
+        /// Partitioner: "current" (principal-axis bisection, default) or
+        /// "nd" (inertial-flow nested dissection: minimum vertex cuts between
+        /// the extreme quarters of four coordinate projections). Recorded in
+        /// the step 6 lock file.
+        #[arg(long, default_value = "current")]
+        ordering: String,
     },
 
     /// Step 6 (Lifted): Generate CCH ordering via NBG ND + lift to EBG
@@ -526,6 +642,41 @@ pub enum Commands {
         /// Has no effect without `--traffic`.
         #[arg(long)]
         bake_as_base: bool,
+
+        /// OPTIONAL: live speed table (`osm_way_id,direction,speed_kmh`
+        /// CSV/TSV). Overrides the time weight of every listed way, writes
+        /// the regenerated node weights as `<outdir>/w.<mode>.u32` (preferred
+        /// over step5's by `serve` and `pack`) and re-customizes the base
+        /// `cch.w.<mode>.u32` on the existing hierarchy. Always reads the
+        /// freeflow `--weights`, so each refresh starts clean. Requires
+        /// `--nbg-geo`.
+        #[arg(long, conflicts_with = "traffic")]
+        speeds: Option<PathBuf>,
+
+        /// Store the time weights (`cch.w.<mode>.u32`) as quantized u16
+        /// with a per-block scale wherever they don't fit u16 losslessly.
+        /// Roughly halves their memory on planet-scale hierarchies at
+        /// ≤ 0.4 % duration error; the achieved error is printed. Distance
+        /// weights stay exact.
+        #[arg(long)]
+        quantize_weights: bool,
+
+        /// Also run perfect customization: make every time weight an exact
+        /// distance, drop arcs a witness triangle matches and write the
+        /// pruned weights as `cch.w.<mode>.perfect.u32` with the removal
+        /// statistics. The base `cch.w.<mode>.u32` is unchanged.
+        #[arg(long)]
+        perfect: bool,
+
+        /// OPTIONAL: incremental re-customization from a `changes.bin`
+        /// (per-EBG-node time weights; 0 closes an edge). Applies it to the
+        /// node weights behind the existing `<outdir>/cch.w.<mode>.u32` —
+        /// `<outdir>/w.<mode>.u32` when present, else `--weights` — and
+        /// recomputes only the triangles the changes reach. Writes the
+        /// updated `w.<mode>.u32`, `cch.w.<mode>.u32` and `cch.lat.<mode>.u32`
+        /// back to `--outdir`, so successive deltas accumulate.
+        #[arg(long, conflicts_with_all = ["traffic", "speeds", "quantize_weights", "perfect"])]
+        delta: Option<PathBuf>,
     },
 
     /// Download (refresh) GTFS transit feeds into `<data>/transit/gtfs/`.
@@ -940,6 +1091,41 @@ pub enum Commands {
         mode: String,
     },
 
+    /// Validate step 8 turn costs: bidirectional CCH vs turn-aware Dijkstra on the filtered EBG
+    ValidateTurns {
+        /// Path to cch.*.topo from Step 7
+        #[arg(long)]
+        cch_topo: PathBuf,
+
+        /// Path to cch.w.*.u32 from Step 8
+        #[arg(long)]
+        cch_weights: PathBuf,
+
+        /// Path to filtered.*.ebg from Step 5
+        #[arg(long)]
+        filtered_ebg: PathBuf,
+
+        /// Path to w.*.u32 the weights were customized from (Step 5, or Step 8 with --speeds)
+        #[arg(long)]
+        node_weights: PathBuf,
+
+        /// Path to t.*.u32 from Step 5
+        #[arg(long)]
+        turns: PathBuf,
+
+        /// Mode name (discovered from way_attrs.*.bin files in data dir)
+        #[arg(long)]
+        mode: String,
+
+        /// Number of random query pairs (default: 10000)
+        #[arg(long, default_value = "10000")]
+        n_pairs: usize,
+
+        /// Random seed (default: 42424242)
+        #[arg(long, default_value = "42424242")]
+        seed: u64,
+    },
+
     /// Validate graph/weight invariants for CCH correctness
     ValidateInvariants {
         /// Path to cch.*.topo from Step 7
@@ -1367,9 +1553,15 @@ impl Cli {
         match self.command {
             Commands::Step1Ingest {
                 input,
+                source,
                 outdir,
-                threads: _,
+                threads,
                 verify_only,
+                memory_budget_mb,
+                spill_dir,
+                highway_nodes_only,
+                keep_metadata,
+                way_filter,
             } => {
                 if verify_only {
                     // Verify mode: check existing files
@@ -1386,10 +1578,22 @@ impl Cli {
                     )?;
                 } else {
                     // Ingest mode: run the pipeline
+                    let input = match (input, source) {
+                        (_, Some(source)) => IngestInput::Download(source),
+                        (Some(input), None) => IngestInput::parse(&input),
+                        (None, None) => unreachable!("clap requires --input or --source"),
+                    };
                     let config = IngestConfig {
-                        input: input.clone(),
+                        input,
                         outdir: outdir.clone(),
+                        memory_budget: memory_budget_mb.saturating_mul(1024 * 1024),
+                        spill_dir,
+                        threads,
+                        highway_nodes_only,
+                        keep_metadata,
+                        way_filter: WayFilter::parse(&way_filter)?,
                     };
+                    let way_filter = config.way_filter.to_string();
 
                     let result = run_ingest(config)?;
 
@@ -1402,10 +1606,11 @@ impl Cli {
                         &result.relations_file,
                     )?;
 
-                    // Generate lock file
+                    // Generate lock file (the input was hashed while ingesting)
                     println!();
-                    let lock = LockFile::create(
-                        &input,
+                    println!("🔒 Generating lock file...");
+                    let mut lock = LockFile::with_input_sha256(
+                        hex::encode(result.input_sha256),
                         &result.nodes_sa_file,
                         &result.nodes_si_file,
                         &result.ways_file,
@@ -1416,6 +1621,7 @@ impl Cli {
                             relations: result.relations_count,
                         },
                     )?;
+                    lock.way_filter = Some(way_filter);
 
                     let lock_path = outdir.join("step1.lock.json");
                     lock.write(&lock_path)?;
@@ -1427,20 +1633,91 @@ impl Cli {
 
                 Ok(())
             }
+            Commands::Step1Update {
+                base,
+                changes,
+                outdir,
+            } => {
+                // Keep filtering changed ways the way the base was ingested;
+                // bases without a recorded filter kept every way.
+                let base_lock_path = base.join("step1.lock.json");
+                let way_filter = if base_lock_path.exists() {
+                    LockFile::read(&base_lock_path)?.way_filter
+                } else {
+                    None
+                };
+                let result = run_update(UpdateConfig {
+                    base,
+                    changes,
+                    outdir: outdir.clone(),
+                    way_filter: match &way_filter {
+                        Some(spec) => WayFilter::parse(spec)?,
+                        None => WayFilter::all(),
+                    },
+                })?;
+
+                println!();
+                verify_lock_conditions(
+                    &result.nodes_sa_file,
+                    &result.nodes_si_file,
+                    &result.ways_file,
+                    &result.relations_file,
+                )?;
+
+                println!();
+                println!("🔒 Generating lock file...");
+                let mut lock = LockFile::with_input_sha256(
+                    hex::encode(result.input_sha256),
+                    &result.nodes_sa_file,
+                    &result.nodes_si_file,
+                    &result.ways_file,
+                    &result.relations_file,
+                    Counts {
+                        nodes: result.nodes_count,
+                        ways: result.ways_count,
+                        relations: result.relations_count,
+                    },
+                )?;
+                lock.way_filter = way_filter;
+
+                let lock_path = outdir.join("step1.lock.json");
+                lock.write(&lock_path)?;
+
+                println!();
+                println!("🎉 Success! All lock conditions passed.");
+                println!("📋 Lock file: {}", lock_path.display());
+                Ok(())
+            }
             Commands::Step2Profile {
                 ways,
                 relations,
                 models_dir,
                 density_classifier,
+                vehicle,
+                profiles_config,
+                countries,
                 outdir,
             } => {
                 let classifier = crate::density::DensityClassifier::parse(&density_classifier)?;
+                let mut vehicle_overrides = std::collections::BTreeMap::new();
+                for spec in &vehicle {
+                    let (mode, dims) = spec.split_once(':').ok_or_else(|| {
+                        anyhow::anyhow!("--vehicle expects MODE:KEY=VALUE,..., got {spec:?}")
+                    })?;
+                    vehicle_overrides.insert(
+                        mode.to_string(),
+                        crate::model::schema::VehicleDimensions::parse_spec(dims)?,
+                    );
+                }
                 let config = ProfileConfig {
                     ways_path: ways,
                     relations_path: relations,
                     models_dir,
                     outdir,
                     density_classifier: classifier,
+                    vehicle_overrides,
+                    profiles_toml: profiles_config,
+                    countries_path: countries,
                 };
 
                 run_profiling(config)?;
@@ -1450,6 +1727,11 @@ impl Cli {
                 nodes,
                 ways,
                 way_attrs,
+                node_barriers,
+                memory_budget_mb,
+                spill_dir,
+                srtm_dir,
+                elevation_points,
                 outdir,
             } => {
                 let wa_parsed = parse_mode_path_pairs(&way_attrs, "way-attrs")?;
@@ -1458,21 +1740,39 @@ impl Cli {
                     .map(|(name, _, path)| (name, path))
                     .collect();
 
+                let nodes_sa_path = nodes.clone();
+                let node_barriers_path = node_barriers.or_else(|| {
+                    let path = nodes.with_file_name("node_barriers.bin");
+                    path.exists().then_some(path)
+                });
                 let config = NbgConfig {
                     nodes_sa_path: nodes,
                     ways_path: ways,
                     way_attrs_paths,
+                    node_barriers_path,
                     outdir: outdir.clone(),
+                    memory_budget: memory_budget_mb.saturating_mul(1024 * 1024),
+                    spill_dir,
+                    srtm_dir,
+                    elevation_points,
                 };
 
                 let result = build_nbg(config)?;
 
-                // Verify lock conditions
+                // Verify lock conditions, against the Step 1 bbox when the
+                // nodes come from a locked Step 1 directory.
                 println!();
+                let step1_lock_path = nodes_sa_path.with_file_name("step1.lock.json");
+                let step1_lock = if step1_lock_path.exists() {
+                    Some(LockFile::read(&step1_lock_path)?)
+                } else {
+                    None
+                };
                 crate::validate::verify_step3_lock_conditions(
                     &result.csr_path,
                     &result.geo_path,
                     &result.node_map_path,
+                    step1_lock.as_ref().map(|lock| &lock.bbox),
                 )?;
 
                 // Generate lock file
@@ -1481,7 +1781,7 @@ impl Cli {
 
                 let components = crate::validate::step3::compute_component_stats(&result.csr_path)?;
 
-                let lock = crate::validate::Step3LockFile::create(
+                let mut lock = crate::validate::Step3LockFile::create(
                     &result.csr_path,
                     &result.geo_path,
                     &result.node_map_path,
@@ -1490,6 +1790,9 @@ impl Cli {
                     components,
                     0, // RSS tracking would require build-time instrumentation
                 )?;
+                if let Some(elev_path) = &result.elev_path {
+                    lock = lock.with_elev(elev_path)?;
+                }
 
                 let lock_path = outdir.join("step3.lock.json");
                 lock.write(&lock_path)?;
@@ -1506,6 +1809,7 @@ impl Cli {
                 nbg_geo,
                 nbg_node_map,
                 node_signals,
+                node_barriers,
                 way_attrs,
                 turn_rules,
                 models_dir,
@@ -1544,6 +1848,12 @@ impl Cli {
                         .unwrap_or(Path::new("."))
                         .join("node_signals.bin")
                 });
+                let barriers_path = node_barriers.clone().unwrap_or_else(|| {
+                    nbg_csr
+                        .parent()
+                        .unwrap_or(Path::new("."))
+                        .join("node_barriers.bin")
+                });
 
                 // #332: mode indices MUST come from the global alphabetical
                 // ordering over every mode the step2 directory holds, NOT
@@ -1645,6 +1955,7 @@ impl Cli {
                     nbg_geo_path: nbg_geo.clone(),
                     nbg_node_map_path: nbg_node_map.clone(),
                     node_signals_path: signals_path,
+                    node_barriers_path: barriers_path,
                     modes: modes.clone(),
                     outdir: outdir.clone(),
                     models_dir: resolved_models_dir,
@@ -1693,6 +2004,10 @@ impl Cli {
                 turn_table,
                 nbg_geo,
                 way_attrs,
+                srtm_dir,
+                gradient,
+                no_gradient,
+                ferry_penalty,
                 outdir,
             } => {
                 // Parse mode=path pairs from CLI
@@ -1727,6 +2042,28 @@ impl Cli {
                     step2_dir.display()
                 );
 
+                let mut gradient_models = std::collections::HashMap::new();
+                for spec in &gradient {
+                    let (mode, model) = spec.split_once('=').ok_or_else(|| {
+                        anyhow::anyhow!("Invalid --gradient format '{}': expected MODE=MODEL", spec)
+                    })?;
+                    gradient_models.insert(mode.to_string(), GradientModel::parse(model)?);
+                }
+
+                let mut ferry_penalties = std::collections::HashMap::new();
+                for spec in &ferry_penalty {
+                    let (mode, seconds) = spec.split_once('=').ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Invalid --ferry-penalty format '{}': expected MODE=SECONDS",
+                            spec
+                        )
+                    })?;
+                    let seconds: u32 = seconds.parse().with_context(|| {
+                        format!("Invalid --ferry-penalty seconds in '{}'", spec)
+                    })?;
+                    ferry_penalties.insert(mode.to_string(), seconds);
+                }
+
                 // Build mode inputs with GLOBAL indices from discovery
                 let mode_inputs: Vec<weights::Step5ModeInput> = wa_raw
                     .iter()
@@ -1749,15 +2086,39 @@ impl Cli {
                             mode_name: name.clone(),
                             mode_index: global_idx,
                             way_attrs_path: path.clone(),
+                            gradient: gradient_models
+                                .get(name)
+                                .copied()
+                                .unwrap_or_else(|| GradientModel::default_for_mode(name)),
+                            ferry_penalty_s: ferry_penalties
+                                .get(name)
+                                .copied()
+                                .unwrap_or_else(|| weights::default_ferry_penalty_s(name)),
                         })
                     })
                     .collect::<Result<Vec<_>>>()?;
+
+                let elevation = match srtm_dir {
+                    Some(dir) if !no_gradient => {
+                        let elevation = ElevationData::load_from_dir(&dir).with_context(|| {
+                            format!("Failed to load SRTM tiles from {}", dir.display())
+                        })?;
+                        println!(
+                            "Loaded {} SRTM tiles from {}",
+                            elevation.tile_count(),
+                            dir.display()
+                        );
+                        Some(elevation)
+                    }
+                    _ => None,
+                };
                 let result = weights::generate_weights(
                     &ebg_nodes,
                     &ebg_csr,
                     &turn_table,
                     &nbg_geo,
                     &mode_inputs,
+                    elevation.as_ref(),
                     &outdir,
                 )?;
 
@@ -1794,7 +2155,10 @@ impl Cli {
                 outdir,
                 leaf_threshold,
                 balance_eps,
+                ordering: algorithm,
             } => {
+                let algorithm = ordering::OrderingAlgorithm::parse(&algorithm)?;
+
                 // Parse mode — discover from filtered_ebg's parent (step5 dir)
                 let mode_name = mode.to_lowercase();
                 let step5_dir = filtered_ebg.parent().unwrap_or(Path::new("."));
@@ -1809,6 +2173,7 @@ impl Cli {
                     outdir: outdir.clone(),
                     leaf_threshold,
                     balance_eps,
+                    algorithm,
                 };
 
                 let result = ordering::generate_ordering(config)?;
@@ -1929,6 +2294,10 @@ impl Cli {
                 nbg_geo,
                 skip_triangle_relax,
                 bake_as_base,
+                speeds,
+                quantize_weights,
+                perfect,
+                delta,
             } => {
                 // Parse mode — discover from filtered_ebg's parent (step5 dir)
                 let mode_name_str = mode.to_lowercase();
@@ -1940,7 +2309,7 @@ impl Cli {
                         let way_attrs_path = way_attrs.ok_or_else(|| {
                             anyhow::anyhow!("--traffic requires --way-attrs <PATH>")
                         })?;
-                        let nbg_geo_path = nbg_geo.ok_or_else(|| {
+                        let nbg_geo_path = nbg_geo.clone().ok_or_else(|| {
                             anyhow::anyhow!("--traffic requires --nbg-geo <PATH>")
                         })?;
                         let profile = crate::traffic::TrafficProfile::load(&traffic_path)?;
@@ -1995,6 +2364,17 @@ impl Cli {
                 if bake_as_base && traffic_cfg.is_none() {
                     anyhow::bail!("--bake-as-base requires --traffic <PROFILE>");
                 }
+                let speeds_cfg = match &speeds {
+                    Some(speeds_path) => {
+                        let nbg_geo_path = nbg_geo
+                            .ok_or_else(|| anyhow::anyhow!("--speeds requires --nbg-geo <PATH>"))?;
+                        Some(customization::SpeedsCustomization {
+                            overrides: crate::speeds::SpeedOverrides::load(speeds_path)?,
+                            nbg_geo_path,
+                        })
+                    }
+                    None => None,
+                };
                 let config = customization::Step8Config {
                     cch_topo_path: cch_topo,
                     filtered_ebg_path: filtered_ebg,
@@ -2007,6 +2387,10 @@ impl Cli {
                     outdir: outdir.clone(),
                     traffic: traffic_cfg,
                     bake_traffic_as_base: bake_as_base,
+                    speeds: speeds_cfg,
+                    quantize_weights,
+                    perfect,
+                    delta: delta.clone(),
                 };
 
                 let traffic_variant = config.traffic.as_ref().map(|t| t.profile.name.clone());
@@ -2025,6 +2409,15 @@ impl Cli {
                 let lock = serde_json::json!({
                     "mode": mode_name,
                     "traffic_variant": traffic_variant,
+                    "speeds": speeds.map(|p| p.display().to_string()),
+                    "quantize_weights": quantize_weights,
+                    "delta": delta.map(|p| p.display().to_string()),
+                    "perfect": result.perfect.as_ref().map(|(path, stats)| serde_json::json!({
+                        "output_path": path.display().to_string(),
+                        "removed_up": stats.removed_up,
+                        "removed_down": stats.removed_down,
+                        "removed_shortcuts": stats.removed_shortcuts,
+                    })),
                     "output_path": result.output_path.display().to_string(),
                     "distance_output_path": result.distance_output_path.display().to_string(),
                     "n_up_edges": result.n_up_edges,
@@ -2382,6 +2775,38 @@ impl Cli {
 
                 Ok(())
             }
+            Commands::ValidateTurns {
+                cch_topo,
+                cch_weights,
+                filtered_ebg,
+                node_weights,
+                turns,
+                mode,
+                n_pairs,
+                seed,
+            } => {
+                let mode_name = mode.to_lowercase();
+
+                let (result, _failures) = crate::validate::validate_turn_costs(
+                    &cch_topo,
+                    &cch_weights,
+                    &filtered_ebg,
+                    &node_weights,
+                    &turns,
+                    n_pairs,
+                    seed,
+                    &mode_name,
+                )?;
+
+                if result.mismatches > 0 {
+                    anyhow::bail!(
+                        "Turn validation failed with {} mismatches",
+                        result.mismatches
+                    );
+                }
+
+                Ok(())
+            }
             Commands::ValidateInvariants {
                 cch_topo,
                 cch_weights,
@@ -34,8 +34,8 @@ use anyhow::Result;
 use rayon::prelude::*;
 use std::fs::File;
 use std::io::{BufWriter, Write};
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 
 use crate::formats::{
     ArcCow, CchTopo, CchTopoFile, CchWeights, EbgNodes, EbgNodesFile, FilteredEbgFile,
@@ -71,6 +71,24 @@ pub struct Step8Config {
     /// legal-limit baseline, without introducing a separate variant
     /// mode name.
     pub bake_traffic_as_base: bool,
+    /// Optional live speed overrides. When `Some`, rewrites the node time
+    /// weights of overridden ways before customization and writes them as
+    /// `<outdir>/w.<mode>.u32` so the server bills the same durations.
+    pub speeds: Option<SpeedsCustomization>,
+    /// Store the time weights (`cch.w.*`) as quantized u16 with a per-block
+    /// scale wherever they don't fit u16 losslessly. Halves the weight
+    /// memory of a u32 file at ≤ 0.4 % duration error; distance and
+    /// length-along-time stay exact.
+    pub quantize_weights: bool,
+    /// Run the perfect-customization pass after the triangle relaxation and
+    /// write the pruned time weights as `cch.w.<mode>.perfect.u32`. The
+    /// base `cch.w.<mode>.u32` keeps every arc: the server's per-request
+    /// custom weights (exclude/avoid/shortest) reuse its arc set.
+    pub perfect: bool,
+    /// Incremental run: apply this `changes.bin` to the node weights behind
+    /// the existing `<outdir>/cch.w.<mode>.u32` and re-customize only the
+    /// affected triangles instead of the whole hierarchy.
+    pub delta: Option<PathBuf>,
 }
 
 /// Inputs needed to apply a traffic profile during step 8.
@@ -91,6 +109,25 @@ pub struct TrafficCustomization {
     pub skip_triangle_relax: bool,
 }
 
+/// Inputs needed to apply a speeds table during step 8.
+pub struct SpeedsCustomization {
+    pub overrides: crate::speeds::SpeedOverrides,
+    /// `nbg.geo` from step 3 — required to map EBG node → first OSM way id.
+    pub nbg_geo_path: PathBuf,
+}
+
+/// Node time weights in effect for `mode_name`: the speed-refreshed
+/// `step8/w.<mode>.u32` written by `step8-customize --speeds` when present,
+/// else the freeflow `step5/w.<mode>.u32`.
+pub fn node_weights_path(step5_dir: &Path, step8_dir: &Path, mode_name: &str) -> PathBuf {
+    let refreshed = step8_dir.join(format!("w.{}.u32", mode_name));
+    if refreshed.exists() {
+        refreshed
+    } else {
+        step5_dir.join(format!("w.{}.u32", mode_name))
+    }
+}
+
 /// Result of Step 8 customization
 #[derive(Debug)]
 pub struct Step8Result {
@@ -102,6 +139,9 @@ pub struct Step8Result {
     pub mode_name: String,
     pub n_up_edges: u64,
     pub n_down_edges: u64,
+    /// `Some` when `perfect` was requested: the pruned weight file and what
+    /// the witness search removed.
+    pub perfect: Option<(PathBuf, PerfectStats)>,
     pub customize_time_ms: u64,
 }
 
@@ -111,14 +151,29 @@ struct SortedFilteredEbgAdj {
     offsets: Vec<u64>,
     sorted_heads: Vec<u32>,        // Filtered node IDs (targets)
     sorted_orig_arc_idx: Vec<u32>, // Original arc indices for turn penalty lookup
+    n_penalized: usize,            // Arcs kept with a non-zero turn penalty
 }
 
 impl SortedFilteredEbgAdj {
-    /// Build sorted adjacency from FilteredEbg
-    fn build(filtered_ebg: &crate::formats::FilteredEbg) -> Self {
+    /// Build sorted adjacency from FilteredEbg.
+    ///
+    /// Parallel arcs u→v collapse to the one with the cheapest turn penalty,
+    /// so an original CCH edge costs exactly what a turn-aware Dijkstra on
+    /// the EBG would pay for that hop.
+    fn build(filtered_ebg: &crate::formats::FilteredEbg, turn_penalties: &[u32]) -> Result<Self> {
         let n_nodes = filtered_ebg.n_filtered_nodes as usize;
         let n_arcs = filtered_ebg.n_filtered_arcs as usize;
 
+        if let Some(&max_arc) = filtered_ebg.original_arc_idx.par_iter().max()
+            && max_arc as usize >= turn_penalties.len()
+        {
+            anyhow::bail!(
+                "turn penalties cover {} arcs but the filtered EBG references arc {}",
+                turn_penalties.len(),
+                max_arc
+            );
+        }
+
         let sorted_per_node: Vec<Vec<(u32, u32)>> = (0..n_nodes)
             .into_par_iter()
             .map(|u| {
@@ -127,7 +182,8 @@ impl SortedFilteredEbgAdj {
                 let mut edges: Vec<(u32, u32)> = (start..end)
                     .map(|i| (filtered_ebg.heads[i], filtered_ebg.original_arc_idx[i]))
                     .collect();
-                edges.sort_unstable_by_key(|(head, _)| *head);
+                edges.sort_unstable_by_key(|&(head, arc)| (head, turn_penalties[arc as usize]));
+                edges.dedup_by_key(|(head, _)| *head);
                 edges
             })
             .collect();
@@ -147,11 +203,17 @@ impl SortedFilteredEbgAdj {
         }
         offsets.push(offset);
 
-        Self {
+        let n_penalized = sorted_orig_arc_idx
+            .par_iter()
+            .filter(|&&arc| turn_penalties[arc as usize] > 0)
+            .count();
+
+        Ok(Self {
             offsets,
             sorted_heads,
             sorted_orig_arc_idx,
-        }
+            n_penalized,
+        })
     }
 
     #[inline]
@@ -175,8 +237,12 @@ impl SortedFilteredEbgAdj {
 /// Customize CCH for a specific mode (time + distance weights, parallelized).
 /// When `config.traffic` is `Some`, applies per-density-class speed factors
 /// to time weights and writes outputs as `cch.w.<mode>_<variant>.u32`. The
-/// distance metric is unaffected and not re-emitted.
+/// distance metric is unaffected and not re-emitted. When `config.delta` is
+/// `Some`, only the triangles reached by the listed changes are revisited.
 pub fn customize_cch(config: Step8Config) -> Result<Step8Result> {
+    if let Some(delta_path) = &config.delta {
+        return customize_cch_delta_from_files(&config, delta_path);
+    }
     let start_time = std::time::Instant::now();
     let mode_name = &config.mode_name;
     let traffic = config.traffic.as_ref();
@@ -263,10 +329,34 @@ pub fn customize_cch(config: Step8Config) -> Result<Step8Result> {
         false
     };
 
+    // Speed overrides replace the time weights of observed ways; the
+    // regenerated node weights are persisted next to the CCH weights so
+    // per-edge durations served from them match the customized metric.
+    if let Some(s) = &config.speeds {
+        let nbg_geo = NbgGeoFile::read(&s.nbg_geo_path)?;
+        let stats =
+            s.overrides
+                .apply_to_node_weights(weights.weights.to_mut(), &ebg_nodes, &nbg_geo)?;
+        println!(
+            "  ✓ Applied {} way speed overrides to {} EBG node weights ({} ways unmatched)",
+            s.overrides.len(),
+            stats.overridden,
+            stats.unmatched_ways
+        );
+        std::fs::create_dir_all(&config.outdir)?;
+        let p = config.outdir.join(format!("w.{}.u32", mode_name));
+        mod_weights::write(&p, &weights)?;
+        println!("  ✓ Written {}", p.display());
+    }
+
     // Build shared structures
     println!("\nBuilding sorted filtered EBG adjacency (parallel)...");
-    let sorted_ebg = SortedFilteredEbgAdj::build(&filtered_ebg);
-    println!("  ✓ Built sorted adjacency");
+    let sorted_ebg = SortedFilteredEbgAdj::build(&filtered_ebg, &turns.penalties)?;
+    println!(
+        "  ✓ Built sorted adjacency ({} of {} arcs carry a turn penalty)",
+        sorted_ebg.n_penalized,
+        sorted_ebg.sorted_heads.len()
+    );
 
     let rank_to_filtered = &topo.rank_to_filtered;
 
@@ -293,8 +383,8 @@ pub fn customize_cch(config: Step8Config) -> Result<Step8Result> {
     // ===================================================================
     // Bottom-up customization
     //
-    // INVARIANT: Each bottom-up pass is internally sequential (rank order).
-    // For traffic recustomization we only run TIME (distance is physical
+    // INVARIANT: Each bottom-up pass is level-synchronous — parallel within
+    // a level, levels in rank-dependency order. For traffic recustomization we only run TIME (distance is physical
     // and unchanged by traffic factors). For freeflow we run TIME + DIST
     // concurrently via rayon::join.
     // ===================================================================
@@ -472,6 +562,9 @@ pub fn customize_cch(config: Step8Config) -> Result<Step8Result> {
         Some(_) | None => mode_name.clone(),
     };
     let output_path = config.outdir.join(format!("cch.w.{}.u32", weight_suffix));
+    if config.quantize_weights {
+        report_quantization_error(&time_up, &time_down);
+    }
     println!("\nWriting time weights...");
     write_cch_weights(
         &output_path,
@@ -480,6 +573,7 @@ pub fn customize_cch(config: Step8Config) -> Result<Step8Result> {
         &time_up_mid,
         &time_down_mid,
         config.mode,
+        config.quantize_weights,
     )?;
     println!("  ✓ Written {}", output_path.display());
 
@@ -495,6 +589,7 @@ pub fn customize_cch(config: Step8Config) -> Result<Step8Result> {
             &topo_up_mid,
             &topo_down_mid,
             config.mode,
+            false,
         )?;
         println!("  ✓ Written {}", p.display());
         p
@@ -519,10 +614,55 @@ pub fn customize_cch(config: Step8Config) -> Result<Step8Result> {
             &time_up_mid,
             &time_down_mid,
             config.mode,
+            false,
         )?;
         println!("  ✓ Written {}", p.display());
     }
 
+    let perfect = if config.perfect {
+        println!("\n✂️  Perfect customization (witness search)...");
+        let pc_start = std::time::Instant::now();
+        let (perfect_up, perfect_down, stats) =
+            perfect_customize(&topo, &time_up, &time_down, &rev_down);
+        let finite = |w: &[u32]| w.iter().filter(|&&w| w != u32::MAX).count();
+        let (finite_up, finite_down) = (finite(&time_up), finite(&time_down));
+        let pct = |removed: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                100.0 * removed as f64 / total as f64
+            }
+        };
+        println!(
+            "  ✓ {:.2}s — removed {} of {} UP ({:.1}%), {} of {} DOWN ({:.1}%), {} shortcuts",
+            pc_start.elapsed().as_secs_f64(),
+            stats.removed_up,
+            finite_up,
+            pct(stats.removed_up, finite_up),
+            stats.removed_down,
+            finite_down,
+            pct(stats.removed_down, finite_down),
+            stats.removed_shortcuts
+        );
+
+        let p = config
+            .outdir
+            .join(format!("cch.w.{}.perfect.u32", weight_suffix));
+        write_cch_weights(
+            &p,
+            &perfect_up,
+            &perfect_down,
+            &time_up_mid,
+            &time_down_mid,
+            config.mode,
+            false,
+        )?;
+        println!("  ✓ Written {}", p.display());
+        Some((p, stats))
+    } else {
+        None
+    };
+
     // For traffic variants, also drop a sibling `.traffic.json` next to the
     // weight file for provenance — the server validates this on boot.
     if let Some(t) = traffic {
@@ -542,10 +682,131 @@ pub fn customize_cch(config: Step8Config) -> Result<Step8Result> {
         mode_name: config.mode_name.clone(),
         n_up_edges: n_up as u64,
         n_down_edges: n_down as u64,
+        perfect,
         customize_time_ms,
     })
 }
 
+/// `step8-customize --delta`: apply a `changes.bin` to the node weights the
+/// current `<outdir>/cch.w.<mode>.u32` was customized from and re-customize
+/// only the triangles the changes reach ([`customize_cch_delta`]).
+///
+/// Those node weights are `<outdir>/w.<mode>.u32` when an earlier `--speeds`
+/// or `--delta` run left one, else `--weights`; the updated copy is written
+/// back to `<outdir>/w.<mode>.u32`, so successive deltas accumulate.
+/// `cch.lat.<mode>.u32` is recomputed from the new middles when present;
+/// distance weights do not depend on time and stay untouched.
+fn customize_cch_delta_from_files(config: &Step8Config, delta_path: &Path) -> Result<Step8Result> {
+    let start_time = std::time::Instant::now();
+    let mode_name = &config.mode_name;
+    println!(
+        "\n🩹 Step 8: Delta re-customization for {} from {}...\n",
+        mode_name,
+        delta_path.display()
+    );
+
+    let delta = crate::formats::weight_delta::read_all(delta_path)?;
+    anyhow::ensure!(
+        delta.mode == config.mode,
+        "{} was written for mode {:?}, not {}",
+        delta_path.display(),
+        delta.mode,
+        mode_name
+    );
+    println!("  ✓ {} node weight changes", delta.changes.len());
+
+    println!("Loading CCH topology...");
+    let topo = CchTopoFile::read(&config.cch_topo_path)?;
+    println!("Loading filtered EBG...");
+    let filtered_ebg = FilteredEbgFile::read(&config.filtered_ebg_path)?;
+    println!("Loading turn penalties ({})...", mode_name);
+    let turns = mod_turns::read_all(&config.turns_path)?;
+
+    let refreshed_path = config.outdir.join(format!("w.{}.u32", mode_name));
+    let weights_path = if refreshed_path.exists() {
+        &refreshed_path
+    } else {
+        &config.weights_path
+    };
+    println!("Loading weights from {}...", weights_path.display());
+    let mut weights = mod_weights::read_all(weights_path)?;
+
+    let output_path = config.outdir.join(format!("cch.w.{}.u32", mode_name));
+    println!(
+        "Loading current time weights from {}...",
+        output_path.display()
+    );
+    let prior = crate::formats::CchWeightsFile::read(&output_path)?;
+
+    let changed = delta.apply(weights.weights.to_mut())?;
+    let delta_start = std::time::Instant::now();
+    let (time, stats) = customize_cch_delta(
+        &topo,
+        &filtered_ebg,
+        &weights.weights,
+        &turns.penalties,
+        &prior,
+        &changed,
+    )?;
+    println!(
+        "\n  ✓ {:.3}s — {} changed nodes seeded {} arcs; {} arcs recomputed, {} updated (of {})",
+        delta_start.elapsed().as_secs_f64(),
+        stats.changed_nodes,
+        stats.seeded_arcs,
+        stats.recomputed_arcs,
+        stats.updated_arcs,
+        topo.up_targets.len() + topo.down_targets.len()
+    );
+
+    println!("\nWriting weights...");
+    mod_weights::write(&refreshed_path, &weights)?;
+    println!("  ✓ Written {}", refreshed_path.display());
+    write_cch_weights(
+        &output_path,
+        &time.up.to_vec_u32(),
+        &time.down.to_vec_u32(),
+        &time.up_middle,
+        &time.down_middle,
+        config.mode,
+        false,
+    )?;
+    println!("  ✓ Written {}", output_path.display());
+
+    let lat_path = config.outdir.join(format!("cch.lat.{}.u32", mode_name));
+    if lat_path.exists() {
+        let ebg_nodes = EbgNodesFile::read(&config.ebg_nodes_path)?;
+        let lat = recompute_len_along_time_from_middles(
+            &topo,
+            &filtered_ebg.filtered_to_original,
+            &ebg_nodes,
+            &weights.weights,
+            &time.up_middle,
+            &time.down_middle,
+        );
+        write_cch_weights(
+            &lat_path,
+            &lat.up.to_vec_u32(),
+            &lat.down.to_vec_u32(),
+            &time.up_middle,
+            &time.down_middle,
+            config.mode,
+            false,
+        )?;
+        println!("  ✓ Written {}", lat_path.display());
+    }
+
+    Ok(Step8Result {
+        output_path,
+        distance_output_path: config.outdir.join(format!("cch.d.{}.u32", mode_name)),
+        mode: config.mode,
+        mode_name: mode_name.clone(),
+        n_up_edges: topo.up_targets.len() as u64,
+        n_down_edges: topo.down_targets.len() as u64,
+        perfect: None,
+        customize_time_ms: start_time.elapsed().as_millis() as u64,
+    })
+}
+
 /// Serve-boot TIME-only CCH recustomization, fully in memory.
 ///
 /// Mirrors the TIME path of [`customize_cch`] exactly — same bottom-up +
@@ -596,7 +857,7 @@ pub fn customize_cch_time_in_memory(
     }
 
     // Shared structures — identical construction to the CLI TIME path.
-    let sorted_ebg = SortedFilteredEbgAdj::build(filtered_ebg);
+    let sorted_ebg = SortedFilteredEbgAdj::build(filtered_ebg, turn_penalties)?;
     let rank_to_filtered = &topo.rank_to_filtered;
     let sorted_down_indices: Vec<Vec<usize>> = (0..n_nodes)
         .into_par_iter()
@@ -870,32 +1131,6 @@ fn build_reverse_down_adj_for_relax(topo: &CchTopo) -> ReverseDownAdj {
     }
 }
 
-/// Generic bottom-up CCH customization.
-///
-/// INVARIANT: Processes ranks in ascending order (sequential, NOT parallel).
-/// For each rank u:
-///   1. DOWN edges sorted by target rank (ensures u→m done before u→v when rank(m) < rank(v))
-///   2. UP edges after DOWN (UP shortcuts need down_weights[u→m])
-///
-/// `orig_weight_fn(u_rank, v_rank) -> u32` provides original edge weight.
-/// Shortcuts always use: weight(u→m) + weight(m→v) via stored middle node.
-/// Bottom-up customize using EXTERNAL middles (e.g. the post-triangle-
-/// relax time-optimal middles), to compute "length along the time-
-/// shortest path" per shortcut for #371/#372.
-///
-/// For non-shortcut edges, `orig_weight_fn(u, v)` returns the physical
-/// edge length (mode-independent). For shortcut edges, the value is
-/// recursively `w[u→m] + w[m→v]` where `m` is the supplied external
-/// middle for that shortcut (the time-optimal apex, NOT
-/// `topo.{up,down}_middle` which holds the contraction-time middle
-/// pre-relax).
-///
-/// Iteration order (rank ascending, DOWN then UP within each rank)
-/// matches `bottom_up_customize`, so the recursive dependency holds:
-/// when we visit a shortcut at rank u, all sub-edges `u→m` (down within
-/// rank u, processed first by target-rank sort) and `m→v` (up from m,
-/// `m < u` so processed in an earlier outer iteration) already have
-/// their length-along-time computed.
 /// #528: recompute the length-along-time-shortest weights for a set of TIME
 /// middles produced by an in-memory (re)customization. The base build-time
 /// `cch.lat.<mode>.u32` describes the CLEAN time-shortest paths; after boot
@@ -950,85 +1185,128 @@ pub fn recompute_len_along_time_from_middles(
     }
 }
 
+/// Bottom-up customize using EXTERNAL middles (e.g. the post-triangle-
+/// relax time-optimal middles), to compute "length along the time-
+/// shortest path" per shortcut for #371/#372.
+///
+/// For non-shortcut edges, `orig_weight_fn(u, v)` returns the physical
+/// edge length (mode-independent). For shortcut edges, the value is
+/// recursively `w[u→m] + w[m→v]` where `m` is the supplied external
+/// middle for that shortcut (the time-optimal apex, NOT
+/// `topo.{up,down}_middle` which holds the contraction-time middle
+/// pre-relax).
+///
+/// Same level-synchronous pass as `bottom_up_customize`; external middles
+/// are lower triangle apexes too, so the level dependency still holds.
 pub fn bottom_up_with_external_middles(
     topo: &CchTopo,
     sorted_down_indices: &[Vec<usize>],
     external_up_mid: &[u32],
     external_down_mid: &[u32],
-    orig_weight_fn: impl Fn(usize, usize) -> u32,
+    orig_weight_fn: impl Fn(usize, usize) -> u32 + Sync,
 ) -> (Vec<u32>, Vec<u32>) {
-    let n_nodes = topo.n_nodes as usize;
-    let n_up = topo.up_targets.len();
-    let n_down = topo.down_targets.len();
+    assert_eq!(external_up_mid.len(), topo.up_targets.len());
+    assert_eq!(external_down_mid.len(), topo.down_targets.len());
 
-    assert_eq!(external_up_mid.len(), n_up);
-    assert_eq!(external_down_mid.len(), n_down);
-
-    let mut up_weights = vec![u32::MAX; n_up];
-    let mut down_weights = vec![u32::MAX; n_down];
-
-    for rank in 0..n_nodes {
-        let u = rank;
+    bottom_up_parallel(
+        topo,
+        sorted_down_indices,
+        |i| external_up_mid[i],
+        |i| external_down_mid[i],
+        orig_weight_fn,
+    )
+}
 
-        for &i in &sorted_down_indices[u] {
-            let v = topo.down_targets[i] as usize;
-            if !topo.down_is_shortcut.bit(i) {
-                down_weights[i] = orig_weight_fn(u, v);
-            } else {
-                let m = external_down_mid[i] as usize;
-                let w_um =
-                    find_edge_weight(u, m, &topo.down_offsets, &topo.down_targets, &down_weights);
-                let w_mv = find_edge_weight(m, v, &topo.up_offsets, &topo.up_targets, &up_weights);
-                down_weights[i] = w_um.saturating_add(w_mv);
-            }
-        }
+/// Generic bottom-up CCH customization.
+///
+/// For each rank u:
+///   1. DOWN edges sorted by target rank (ensures u→m done before u→v when rank(m) < rank(v))
+///   2. UP edges after DOWN (UP shortcuts need down_weights[u→m])
+///
+/// Ranks run level by level, in parallel within a level (see
+/// [`bottom_up_parallel`]).
+///
+/// `orig_weight_fn(u_rank, v_rank) -> u32` provides original edge weight.
+/// Shortcuts always use: weight(u→m) + weight(m→v) via stored middle node.
+fn bottom_up_customize(
+    topo: &CchTopo,
+    sorted_down_indices: &[Vec<usize>],
+    orig_weight_fn: impl Fn(usize, usize) -> u32 + Sync,
+) -> (Vec<u32>, Vec<u32>) {
+    bottom_up_parallel(
+        topo,
+        sorted_down_indices,
+        |i| topo.up_middle.get(i),
+        |i| topo.down_middle.get(i),
+        orig_weight_fn,
+    )
+}
 
-        let up_start = topo.up_offsets[u] as usize;
-        let up_end = topo.up_offsets[u + 1] as usize;
-        for i in up_start..up_end {
-            let v = topo.up_targets[i] as usize;
-            if !topo.up_is_shortcut.bit(i) {
-                up_weights[i] = orig_weight_fn(u, v);
-            } else {
-                let m = external_up_mid[i] as usize;
-                let w_um =
-                    find_edge_weight(u, m, &topo.down_offsets, &topo.down_targets, &down_weights);
-                let w_mv = find_edge_weight(m, v, &topo.up_offsets, &topo.up_targets, &up_weights);
-                up_weights[i] = w_um.saturating_add(w_mv);
-            }
-        }
+/// Group ranks into customization levels. A node's level is one more than
+/// the highest level among its DOWN neighbours (level 0 without any), so
+/// every row a node reads during bottom-up — `u→m` in its own DOWN row and
+/// `m→v` in the UP row of a lower neighbour `m` — is either its own or
+/// belongs to a strictly lower level. Nodes of one level therefore write
+/// disjoint rows and read only finished ones.
+fn customization_levels(topo: &CchTopo) -> Vec<Vec<u32>> {
+    let n_nodes = topo.n_nodes as usize;
+    let mut level = vec![0u32; n_nodes];
+    let mut n_levels = 0usize;
+    for u in 0..n_nodes {
+        let start = topo.down_offsets[u] as usize;
+        let end = topo.down_offsets[u + 1] as usize;
+        let l = topo.down_targets[start..end]
+            .iter()
+            .map(|&m| level[m as usize] + 1)
+            .max()
+            .unwrap_or(0);
+        level[u] = l;
+        n_levels = n_levels.max(l as usize + 1);
     }
 
-    (up_weights, down_weights)
+    let mut levels = vec![Vec::new(); n_levels];
+    for (u, &l) in level.iter().enumerate() {
+        levels[l as usize].push(u as u32);
+    }
+    levels
 }
 
-fn bottom_up_customize(
+/// Level-synchronous parallel bottom-up customization.
+///
+/// Levels run in order; the nodes of one level run in parallel (see
+/// [`customization_levels`]). Within a node the sequential order is kept:
+/// DOWN edges by increasing target rank, then UP edges. Weights live in
+/// `AtomicU32` slots purely so threads can share the arrays — each slot is
+/// written once by its owning node, and rayon's join at the end of a level
+/// orders those writes before any read from a higher level, so `Relaxed`
+/// suffices. The result is identical to a sequential rank-order pass.
+fn bottom_up_parallel(
     topo: &CchTopo,
     sorted_down_indices: &[Vec<usize>],
-    orig_weight_fn: impl Fn(usize, usize) -> u32,
+    up_mid: impl Fn(usize) -> u32 + Sync,
+    down_mid: impl Fn(usize) -> u32 + Sync,
+    orig_weight_fn: impl Fn(usize, usize) -> u32 + Sync,
 ) -> (Vec<u32>, Vec<u32>) {
-    let n_nodes = topo.n_nodes as usize;
     let n_up = topo.up_targets.len();
     let n_down = topo.down_targets.len();
 
-    let mut up_weights = vec![u32::MAX; n_up];
-    let mut down_weights = vec![u32::MAX; n_down];
-
-    for rank in 0..n_nodes {
-        let u = rank;
+    let up_weights: Vec<AtomicU32> = (0..n_up).map(|_| AtomicU32::new(u32::MAX)).collect();
+    let down_weights: Vec<AtomicU32> = (0..n_down).map(|_| AtomicU32::new(u32::MAX)).collect();
 
+    let customize_node = |u: usize| {
         // PHASE 1: DOWN edges (sorted by target rank for correct dependency order)
         for &i in &sorted_down_indices[u] {
             let v = topo.down_targets[i] as usize;
-            if !topo.down_is_shortcut.bit(i) {
-                down_weights[i] = orig_weight_fn(u, v);
+            let w = if !topo.down_is_shortcut.bit(i) {
+                orig_weight_fn(u, v)
             } else {
-                let m = topo.down_middle.get(i) as usize;
+                let m = down_mid(i) as usize;
                 let w_um =
-                    find_edge_weight(u, m, &topo.down_offsets, &topo.down_targets, &down_weights);
-                let w_mv = find_edge_weight(m, v, &topo.up_offsets, &topo.up_targets, &up_weights);
-                down_weights[i] = w_um.saturating_add(w_mv);
-            }
+                    load_edge_weight(u, m, &topo.down_offsets, &topo.down_targets, &down_weights);
+                let w_mv = load_edge_weight(m, v, &topo.up_offsets, &topo.up_targets, &up_weights);
+                w_um.saturating_add(w_mv)
+            };
+            down_weights[i].store(w, Ordering::Relaxed);
         }
 
         // PHASE 2: UP edges (all down_weights[u→*] are now computed)
@@ -1036,19 +1314,33 @@ fn bottom_up_customize(
         let up_end = topo.up_offsets[u + 1] as usize;
         for i in up_start..up_end {
             let v = topo.up_targets[i] as usize;
-            if !topo.up_is_shortcut.bit(i) {
-                up_weights[i] = orig_weight_fn(u, v);
+            let w = if !topo.up_is_shortcut.bit(i) {
+                orig_weight_fn(u, v)
             } else {
-                let m = topo.up_middle.get(i) as usize;
+                let m = up_mid(i) as usize;
                 let w_um =
-                    find_edge_weight(u, m, &topo.down_offsets, &topo.down_targets, &down_weights);
-                let w_mv = find_edge_weight(m, v, &topo.up_offsets, &topo.up_targets, &up_weights);
-                up_weights[i] = w_um.saturating_add(w_mv);
-            }
+                    load_edge_weight(u, m, &topo.down_offsets, &topo.down_targets, &down_weights);
+                let w_mv = load_edge_weight(m, v, &topo.up_offsets, &topo.up_targets, &up_weights);
+                w_um.saturating_add(w_mv)
+            };
+            up_weights[i].store(w, Ordering::Relaxed);
         }
+    };
+
+    for level in customization_levels(topo) {
+        level
+            .par_iter()
+            .with_min_len(256)
+            .for_each(|&u| customize_node(u as usize));
     }
 
-    (up_weights, down_weights)
+    (
+        up_weights.into_iter().map(AtomicU32::into_inner).collect(),
+        down_weights
+            .into_iter()
+            .map(AtomicU32::into_inner)
+            .collect(),
+    )
 }
 
 /// Pack (weight, middle_rank) into a single u64 for atomic fetch_min.
@@ -1433,6 +1725,480 @@ fn triangle_relax_lex_parallel(
     (up, down, up_mid, down_mid, total_relaxations, pass)
 }
 
+// ===================================================================
+// Perfect customization
+// ===================================================================
+
+/// Arcs dropped by [`perfect_customize`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PerfectStats {
+    pub removed_up: usize,
+    pub removed_down: usize,
+    /// Removed arcs (UP + DOWN) that are shortcuts rather than original arcs.
+    pub removed_shortcuts: usize,
+}
+
+/// Group ranks for the top-down perfect pass. A node's level is one more
+/// than the highest level among its UPPER neighbours — UP targets and the
+/// sources of DOWN arcs into it — (level 0 without any). Every arc between
+/// two upper neighbours of `x` belongs to the lower-ranked of the two, which
+/// sits on a strictly lower level than `x`, so levels can run in order and
+/// nodes of one level in parallel.
+fn perfect_levels(topo: &CchTopo, rev_down: &ReverseDownAdj) -> Vec<Vec<u32>> {
+    let n_nodes = topo.n_nodes as usize;
+    let mut level = vec![0u32; n_nodes];
+    let mut n_levels = 0usize;
+    for x in (0..n_nodes).rev() {
+        let up = &topo.up_targets[topo.up_offsets[x] as usize..topo.up_offsets[x + 1] as usize];
+        let into =
+            &rev_down.sources[rev_down.offsets[x] as usize..rev_down.offsets[x + 1] as usize];
+        let l = up
+            .iter()
+            .chain(into)
+            .map(|&y| level[y as usize] + 1)
+            .max()
+            .unwrap_or(0);
+        level[x] = l;
+        n_levels = n_levels.max(l as usize + 1);
+    }
+
+    let mut levels = vec![Vec::new(); n_levels];
+    for (x, &l) in level.iter().enumerate() {
+        levels[l as usize].push(x as u32);
+    }
+    levels
+}
+
+/// Weight of the arc `z→y` between two distinct ranks, whichever row holds it.
+#[inline]
+fn load_arc_weight(
+    topo: &CchTopo,
+    z: usize,
+    y: usize,
+    up_weights: &[AtomicU32],
+    down_weights: &[AtomicU32],
+) -> u32 {
+    if z < y {
+        load_edge_weight(z, y, &topo.up_offsets, &topo.up_targets, up_weights)
+    } else {
+        load_edge_weight(z, y, &topo.down_offsets, &topo.down_targets, down_weights)
+    }
+}
+
+/// Perfect customization (Dibbelt, Strasser & Wagner) on top of relaxed
+/// basic weights.
+///
+/// Pass 1 runs top-down over [`perfect_levels`] and turns every weight into
+/// the exact shortest-path distance between its endpoints: a path leaving
+/// `x` first reaches a higher node `z` over a single CCH arc whose basic
+/// weight already covers the detour below `x`, so
+/// `w(x→y) = min(w(x→y), w(x→z) + w(z→y))` over the upper neighbours `z`
+/// of `x`, with `w(z→y)` already perfect (and symmetrically for DOWN arcs
+/// `y→x`). Pass 2 drops every arc whose perfect weight is matched by an
+/// intermediate or upper triangle `x→z→y` — the query reaches `y` through
+/// `z` at the same cost, so the arc never improves a search. Weights are
+/// positive (every EBG node costs at least 1 s), so two arcs can never
+/// witness each other's removal.
+///
+/// Removed arcs come back as `u32::MAX`, the same sentinel as an unusable
+/// arc, so query-time adjacency builders skip them. Middles are untouched:
+/// a kept shortcut still unpacks through its lower triangle.
+fn perfect_customize(
+    topo: &CchTopo,
+    up_weights: &[u32],
+    down_weights: &[u32],
+    rev_down: &ReverseDownAdj,
+) -> (Vec<u32>, Vec<u32>, PerfectStats) {
+    let perfect_up: Vec<AtomicU32> = up_weights.iter().map(|&w| AtomicU32::new(w)).collect();
+    let perfect_down: Vec<AtomicU32> = down_weights.iter().map(|&w| AtomicU32::new(w)).collect();
+
+    // Pass 1: each node writes only its own UP row and the DOWN arcs into
+    // it, reading its own basic weights plus finished arcs between upper
+    // neighbours (see `bottom_up_parallel` for why `Relaxed` suffices).
+    let perfect_node = |x: usize| {
+        let up_range = topo.up_offsets[x] as usize..topo.up_offsets[x + 1] as usize;
+        let into_range = rev_down.offsets[x] as usize..rev_down.offsets[x + 1] as usize;
+
+        for i in up_range.clone() {
+            let y = topo.up_targets[i] as usize;
+            let mut best = up_weights[i];
+            for j in up_range.clone() {
+                let z = topo.up_targets[j] as usize;
+                if z == y || up_weights[j] == u32::MAX {
+                    continue;
+                }
+                let w_zy = load_arc_weight(topo, z, y, &perfect_up, &perfect_down);
+                best = best.min(up_weights[j].saturating_add(w_zy));
+            }
+            perfect_up[i].store(best, Ordering::Relaxed);
+        }
+
+        for i_rev in into_range.clone() {
+            let y = rev_down.sources[i_rev] as usize;
+            let i = rev_down.edge_idx[i_rev];
+            let mut best = down_weights[i];
+            for j_rev in into_range.clone() {
+                let z = rev_down.sources[j_rev] as usize;
+                let w_zx = down_weights[rev_down.edge_idx[j_rev]];
+                if z == y || w_zx == u32::MAX {
+                    continue;
+                }
+                let w_yz = load_arc_weight(topo, y, z, &perfect_up, &perfect_down);
+                best = best.min(w_yz.saturating_add(w_zx));
+            }
+            perfect_down[i].store(best, Ordering::Relaxed);
+        }
+    };
+
+    for level in perfect_levels(topo, rev_down) {
+        level
+            .par_iter()
+            .with_min_len(256)
+            .for_each(|&x| perfect_node(x as usize));
+    }
+
+    let perfect_up: Vec<u32> = perfect_up.into_iter().map(AtomicU32::into_inner).collect();
+    let perfect_down: Vec<u32> = perfect_down
+        .into_iter()
+        .map(AtomicU32::into_inner)
+        .collect();
+
+    // Pass 2: witness search over the final weights. Decisions read only
+    // `perfect_*` and each node again writes only its own arcs.
+    let arc_weight = |z: usize, y: usize| {
+        if z < y {
+            find_edge_index(z, y, &topo.up_offsets, &topo.up_targets)
+                .map_or(u32::MAX, |k| perfect_up[k])
+        } else {
+            find_edge_index(z, y, &topo.down_offsets, &topo.down_targets)
+                .map_or(u32::MAX, |k| perfect_down[k])
+        }
+    };
+    let witnessed = |w: u32, w_a: u32, w_b: u32| {
+        w != u32::MAX && w_a != u32::MAX && w_b != u32::MAX && w_a.saturating_add(w_b) <= w
+    };
+
+    let up_removed: Vec<AtomicBool> = (0..perfect_up.len())
+        .map(|_| AtomicBool::new(false))
+        .collect();
+    let down_removed: Vec<AtomicBool> = (0..perfect_down.len())
+        .map(|_| AtomicBool::new(false))
+        .collect();
+    (0..topo.n_nodes as usize).into_par_iter().for_each(|x| {
+        let up_range = topo.up_offsets[x] as usize..topo.up_offsets[x + 1] as usize;
+        let into_range = rev_down.offsets[x] as usize..rev_down.offsets[x + 1] as usize;
+
+        for i in up_range.clone() {
+            let y = topo.up_targets[i] as usize;
+            let removed = up_range.clone().any(|j| {
+                let z = topo.up_targets[j] as usize;
+                z != y && witnessed(perfect_up[i], perfect_up[j], arc_weight(z, y))
+            });
+            up_removed[i].store(removed, Ordering::Relaxed);
+        }
+
+        for i_rev in into_range.clone() {
+            let y = rev_down.sources[i_rev] as usize;
+            let i = rev_down.edge_idx[i_rev];
+            let removed = into_range.clone().any(|j_rev| {
+                let z = rev_down.sources[j_rev] as usize;
+                let w_zx = perfect_down[rev_down.edge_idx[j_rev]];
+                z != y && witnessed(perfect_down[i], arc_weight(y, z), w_zx)
+            });
+            down_removed[i].store(removed, Ordering::Relaxed);
+        }
+    });
+
+    let mut stats = PerfectStats::default();
+    let mut up = perfect_up;
+    for (i, removed) in up_removed.into_iter().enumerate() {
+        if removed.into_inner() {
+            up[i] = u32::MAX;
+            stats.removed_up += 1;
+            stats.removed_shortcuts += usize::from(topo.up_is_shortcut.bit(i));
+        }
+    }
+    let mut down = perfect_down;
+    for (i, removed) in down_removed.into_iter().enumerate() {
+        if removed.into_inner() {
+            down[i] = u32::MAX;
+            stats.removed_down += 1;
+            stats.removed_shortcuts += usize::from(topo.down_is_shortcut.bit(i));
+        }
+    }
+
+    (up, down, stats)
+}
+
+// ===================================================================
+// Delta customization
+// ===================================================================
+
+/// Outcome of [`customize_cch_delta`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DeltaStats {
+    /// EBG nodes whose time weight changed.
+    pub changed_nodes: usize,
+    /// Original CCH arcs entering a changed node (the propagation seeds).
+    pub seeded_arcs: usize,
+    /// Arcs whose (weight, middle) was recomputed.
+    pub recomputed_arcs: usize,
+    /// Arcs whose weight actually changed.
+    pub updated_arcs: usize,
+}
+
+/// A CCH arc queued for recomputation, ordered by its lower endpoint: every
+/// triangle of `tail→head` runs through an apex below both endpoints, so
+/// arcs are final once all arcs with a smaller lower endpoint are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct DeltaArc {
+    lower: u32,
+    down: bool,
+    tail: u32,
+    idx: usize,
+}
+
+impl DeltaArc {
+    fn new(topo: &CchTopo, tail: usize, head: usize) -> Option<Self> {
+        let (down, idx) = if tail < head {
+            (
+                false,
+                find_edge_index(tail, head, &topo.up_offsets, &topo.up_targets)?,
+            )
+        } else {
+            (
+                true,
+                find_edge_index(tail, head, &topo.down_offsets, &topo.down_targets)?,
+            )
+        };
+        Some(Self {
+            lower: tail.min(head) as u32,
+            down,
+            tail: tail as u32,
+            idx,
+        })
+    }
+}
+
+/// Incremental counterpart of bottom-up + triangle relaxation.
+///
+/// The relaxed weight of an arc `x→y` is the lexicographic minimum of
+/// `(weight, middle)` over its own start value — the original arc cost, or
+/// unusable for a shortcut, with the topology middle — and every lower
+/// triangle `x→m→y`. That is exactly the fixpoint [`triangle_relax_parallel`]
+/// converges to, so recomputing the seeded arcs and then every arc with a
+/// triangle leg whose weight changed, in increasing lower-endpoint order,
+/// reproduces a full customization of the new metric while touching only
+/// the affected triangles.
+fn delta_customize(
+    topo: &CchTopo,
+    rev_down: &ReverseDownAdj,
+    weights: &mut CchWeights,
+    seeds: Vec<DeltaArc>,
+    orig_weight_fn: impl Fn(usize, usize) -> u32,
+) -> DeltaStats {
+    let up = weights.up.to_mut_vec();
+    let down = weights.down.to_mut_vec();
+    let up_mid = weights.up_middle.to_mut();
+    let down_mid = weights.down_middle.to_mut();
+    let mut stats = DeltaStats {
+        seeded_arcs: seeds.len(),
+        ..Default::default()
+    };
+    let mut queue: std::collections::BinaryHeap<std::cmp::Reverse<DeltaArc>> =
+        seeds.into_iter().map(std::cmp::Reverse).collect();
+    let mut last: Option<DeltaArc> = None;
+
+    while let Some(std::cmp::Reverse(arc)) = queue.pop() {
+        if last == Some(arc) {
+            continue;
+        }
+        last = Some(arc);
+
+        let x = arc.tail as usize;
+        let (y, is_shortcut, topo_mid) = if arc.down {
+            (
+                topo.down_targets[arc.idx] as usize,
+                topo.down_is_shortcut.bit(arc.idx),
+                topo.down_middle.get(arc.idx),
+            )
+        } else {
+            (
+                topo.up_targets[arc.idx] as usize,
+                topo.up_is_shortcut.bit(arc.idx),
+                topo.up_middle.get(arc.idx),
+            )
+        };
+
+        let start = if is_shortcut {
+            u32::MAX
+        } else {
+            orig_weight_fn(x, y)
+        };
+        let mut best = pack_wm(start, topo_mid);
+        let lower = topo.down_offsets[x] as usize..topo.down_offsets[x + 1] as usize;
+        for (&m, &w_xm) in topo.down_targets[lower.clone()].iter().zip(&down[lower]) {
+            let m = m as usize;
+            if m >= y || w_xm == u32::MAX {
+                continue;
+            }
+            let Some(k) = find_edge_index(m, y, &topo.up_offsets, &topo.up_targets) else {
+                continue;
+            };
+            if up[k] == u32::MAX {
+                continue;
+            }
+            best = best.min(pack_wm(w_xm.saturating_add(up[k]), m as u32));
+        }
+        stats.recomputed_arcs += 1;
+
+        let (w_slot, mid_slot) = if arc.down {
+            (&mut down[arc.idx], &mut down_mid[arc.idx])
+        } else {
+            (&mut up[arc.idx], &mut up_mid[arc.idx])
+        };
+        *mid_slot = unpack_middle(best);
+        if *w_slot == unpack_weight(best) {
+            continue;
+        }
+        *w_slot = unpack_weight(best);
+        stats.updated_arcs += 1;
+
+        // Re-queue every arc that uses this one as a triangle leg.
+        if arc.down {
+            // x→m (m = y): triangles x→m→z for the UP targets z of m.
+            for i in topo.up_offsets[y] as usize..topo.up_offsets[y + 1] as usize {
+                let z = topo.up_targets[i] as usize;
+                if z != x
+                    && let Some(next) = DeltaArc::new(topo, x, z)
+                {
+                    queue.push(std::cmp::Reverse(next));
+                }
+            }
+        } else {
+            // m→y (m = x): triangles z→m→y for the DOWN arcs z→m.
+            for i_rev in rev_down.offsets[x] as usize..rev_down.offsets[x + 1] as usize {
+                let z = rev_down.sources[i_rev] as usize;
+                if z != y
+                    && let Some(next) = DeltaArc::new(topo, z, y)
+                {
+                    queue.push(std::cmp::Reverse(next));
+                }
+            }
+        }
+    }
+
+    stats
+}
+
+/// Re-customize TIME weights after a small set of node weights changed.
+///
+/// `prior` must be the customization of the node weights before the change
+/// (as written by [`customize_cch`] or returned by
+/// [`customize_cch_time_in_memory`]); `node_weights_time` are the weights
+/// after it and `changed_nodes` the original EBG ids that differ. Only the
+/// original arcs entering a changed node and the triangles above them are
+/// revisited — see [`delta_customize`]. Middles of recomputed arcs are
+/// elected on time alone, as in [`customize_cch_time_in_memory`].
+///
+/// A changed node must already belong to the mode's filtered graph: a delta
+/// can close an edge (weight 0) or reopen it, never add one to the graph.
+pub fn customize_cch_delta(
+    topo: &CchTopo,
+    filtered_ebg: &crate::formats::FilteredEbg,
+    node_weights_time: &[u32],
+    turn_penalties: &[u32],
+    prior: &CchWeights,
+    changed_nodes: &[u32],
+) -> Result<(CchWeights, DeltaStats)> {
+    let n_nodes = topo.n_nodes as usize;
+    anyhow::ensure!(
+        prior.up.len() == topo.up_targets.len() && prior.down.len() == topo.down_targets.len(),
+        "prior weights ({} up, {} down) do not match the topology ({} up, {} down)",
+        prior.up.len(),
+        prior.down.len(),
+        topo.up_targets.len(),
+        topo.down_targets.len()
+    );
+    anyhow::ensure!(
+        !prior.up.is_quantized() && !prior.down.is_quantized(),
+        "delta customization needs exact prior weights, not quantized ones"
+    );
+
+    let mut filtered_to_rank = vec![u32::MAX; filtered_ebg.n_filtered_nodes as usize];
+    for (rank, &f) in topo.rank_to_filtered.iter().enumerate() {
+        filtered_to_rank[f as usize] = rank as u32;
+    }
+    let mut changed_rank = vec![false; n_nodes];
+    for &orig in changed_nodes {
+        let f = filtered_ebg
+            .original_to_filtered
+            .get(orig as usize)
+            .copied()
+            .unwrap_or(u32::MAX);
+        anyhow::ensure!(
+            f != u32::MAX,
+            "EBG node {} is not part of the {:?} graph; adding it needs a full rebuild",
+            orig,
+            filtered_ebg.mode
+        );
+        changed_rank[filtered_to_rank[f as usize] as usize] = true;
+    }
+
+    let sorted_ebg = SortedFilteredEbgAdj::build(filtered_ebg, turn_penalties)?;
+    let rev_down = build_reverse_down_adj_for_relax(topo);
+
+    // Seeds: original arcs whose head is a changed node.
+    let seeds: Vec<DeltaArc> = (0..n_nodes)
+        .into_par_iter()
+        .flat_map_iter(|x| {
+            let up = (topo.up_offsets[x] as usize..topo.up_offsets[x + 1] as usize)
+                .filter(|&i| {
+                    !topo.up_is_shortcut.bit(i) && changed_rank[topo.up_targets[i] as usize]
+                })
+                .map(move |idx| DeltaArc {
+                    lower: x as u32,
+                    down: false,
+                    tail: x as u32,
+                    idx,
+                });
+            let down = (topo.down_offsets[x] as usize..topo.down_offsets[x + 1] as usize)
+                .filter(|&i| {
+                    !topo.down_is_shortcut.bit(i) && changed_rank[topo.down_targets[i] as usize]
+                })
+                .map(move |idx| DeltaArc {
+                    lower: topo.down_targets[idx],
+                    down: true,
+                    tail: x as u32,
+                    idx,
+                });
+            up.chain(down)
+        })
+        .collect();
+
+    let mut weights = prior.clone();
+    if weights.up_middle.is_empty() {
+        weights.up_middle = ArcCow::from_vec(topo.up_middle.to_vec_u32());
+    }
+    if weights.down_middle.is_empty() {
+        weights.down_middle = ArcCow::from_vec(topo.down_middle.to_vec_u32());
+    }
+    let mut stats = delta_customize(topo, &rev_down, &mut weights, seeds, |u_rank, v_rank| {
+        compute_original_weight_rank_aligned(
+            u_rank,
+            v_rank,
+            node_weights_time,
+            turn_penalties,
+            &sorted_ebg,
+            &filtered_ebg.filtered_to_original,
+            &topo.rank_to_filtered,
+        )
+    });
+    stats.changed_nodes = changed_nodes.len();
+
+    Ok((weights, stats))
+}
+
 // ===================================================================
 // Original edge weight functions
 // ===================================================================
@@ -1491,16 +2257,14 @@ fn compute_distance_weight_rank_aligned(
 // ===================================================================
 
 #[inline]
-fn find_edge_weight(u: usize, v: usize, offsets: &[u64], targets: &[u32], weights: &[u32]) -> u32 {
-    let start = offsets[u] as usize;
-    let end = offsets[u + 1] as usize;
-    if start >= end {
-        return u32::MAX;
-    }
-    match targets[start..end].binary_search(&(v as u32)) {
-        Ok(idx) => weights[start + idx],
-        Err(_) => u32::MAX,
-    }
+fn load_edge_weight(
+    u: usize,
+    v: usize,
+    offsets: &[u64],
+    targets: &[u32],
+    weights: &[AtomicU32],
+) -> u32 {
+    find_edge_index(u, v, offsets, targets).map_or(u32::MAX, |i| weights[i].load(Ordering::Relaxed))
 }
 
 #[inline]
@@ -1622,6 +2386,33 @@ fn sanity_check_weights(
     Ok(())
 }
 
+/// Print the error `--quantize-weights` introduces on the time weights:
+/// the worst single edge and the aggregate (sum of absolute errors over
+/// sum of weights), a proxy for the relative error of route durations.
+fn report_quantization_error(up_weights: &[u32], down_weights: &[u32]) {
+    let mut max_abs = 0u32;
+    let mut abs_sum = 0u64;
+    let mut total = 0u64;
+    for weights in [up_weights, down_weights] {
+        let quantized = crate::formats::WeightArray::quantize(weights);
+        for (w, q) in weights.iter().zip(quantized.iter()) {
+            if *w == u32::MAX {
+                continue;
+            }
+            let err = w.abs_diff(q);
+            max_abs = max_abs.max(err);
+            abs_sum += err as u64;
+            total += *w as u64;
+        }
+    }
+    println!("\n📉 Quantization (Q16) error:");
+    println!("  Max per edge: {} s", max_abs);
+    println!(
+        "  Aggregate: {:.4}%",
+        abs_sum as f64 / total.max(1) as f64 * 100.0
+    );
+}
+
 fn sanity_check_weights_simple(
     up_weights: &[u32],
     down_weights: &[u32],
@@ -1651,8 +2442,10 @@ fn write_cch_weights(
     up_middle: &[u32],
     down_middle: &[u32],
     mode: Mode,
+    quantize: bool,
 ) -> Result<()> {
     use crate::formats::WeightWidth;
+    use crate::formats::cch_weights::{q16_padded_body_bytes, quantize_q16, quantized_flag_bits};
     use crate::formats::crc::Digest;
 
     const MAGIC: u32 = 0x43434857; // "CCHW"
@@ -1667,6 +2460,22 @@ fn write_cch_weights(
     let up_width = WeightWidth::choose(up_weights);
     let down_width = WeightWidth::choose(down_weights);
 
+    // `--quantize-weights`: a direction that needs more than
+    // u16 losslessly is stored as a Q16 stream instead (u16 code + per
+    // block scale). Directions that already fit u16 stay exact.
+    let up_quantized = quantize && up_width != WeightWidth::U16;
+    let down_quantized = quantize && down_width != WeightWidth::U16;
+    let up_width = if up_quantized {
+        WeightWidth::U16
+    } else {
+        up_width
+    };
+    let down_width = if down_quantized {
+        WeightWidth::U16
+    } else {
+        down_width
+    };
+
     // Per-direction 2-bit width code in header byte 7 (#306 PR 3):
     //   00 = u32
     //   01 = u16
@@ -1678,7 +2487,9 @@ fn write_cch_weights(
             WeightWidth::U24 => 2,
         }
     };
-    let width_flags = width_code(up_width) | (width_code(down_width) << 2);
+    let width_flags = width_code(up_width)
+        | (width_code(down_width) << 2)
+        | quantized_flag_bits(up_quantized, down_quantized);
 
     let mut writer = BufWriter::new(File::create(path)?);
     let mut crc_digest = Digest::new();
@@ -1711,10 +2522,21 @@ fn write_cch_weights(
     // sentinel mapping reconstructs `u32::MAX` losslessly.
     // Each u16 body is padded to a 4-byte boundary so the following
     // arrays (the other direction's body + u32 middles) stay aligned.
-    write_weights_body(&mut writer, &mut crc_digest, up_weights, up_width)?;
-    write_padding(&mut writer, &mut crc_digest, up_width, up_weights.len())?;
-    write_weights_body(&mut writer, &mut crc_digest, down_weights, down_width)?;
-    write_padding(&mut writer, &mut crc_digest, down_width, down_weights.len())?;
+    for (weights, width, quantized) in [
+        (up_weights, up_width, up_quantized),
+        (down_weights, down_width, down_quantized),
+    ] {
+        if quantized {
+            let stream = quantize_q16(weights);
+            let mut body: Vec<u8> = stream.iter().flat_map(|v| v.to_le_bytes()).collect();
+            body.resize(q16_padded_body_bytes(weights.len()), 0);
+            writer.write_all(&body)?;
+            crc_digest.update(&body);
+        } else {
+            write_weights_body(&mut writer, &mut crc_digest, weights, width)?;
+            write_padding(&mut writer, &mut crc_digest, width, weights.len())?;
+        }
+    }
 
     // Write relaxed middle arrays — stay at u32 (middle node ids
     // address `n_filtered_nodes` which planet-scale exceeds 65 535).
@@ -1922,7 +2744,7 @@ pub fn customize_cch_hybrid(config: Step8HybridConfig) -> Result<Step8Result> {
         .collect();
     println!("  ✓ Pre-sorted down edges");
 
-    // Bottom-up customization (sequential, single metric for hybrid)
+    // Bottom-up customization (level-parallel, single metric for hybrid)
     println!("\nCustomizing weights (bottom-up)...");
     let (up_weights, down_weights) =
         bottom_up_customize(&topo, &sorted_down_indices, |u_rank, v_rank| {
@@ -1963,6 +2785,7 @@ pub fn customize_cch_hybrid(config: Step8HybridConfig) -> Result<Step8Result> {
         &topo_up_mid,
         &topo_down_mid,
         config.mode,
+        false,
     )?;
     println!("  ✓ Written {}", output_path.display());
 
@@ -1980,6 +2803,7 @@ pub fn customize_cch_hybrid(config: Step8HybridConfig) -> Result<Step8Result> {
         mode_name: config.mode_name.clone(),
         n_up_edges: n_up as u64,
         n_down_edges: n_down as u64,
+        perfect: None,
         customize_time_ms,
     })
 }
@@ -2349,6 +3173,21 @@ mod len_along_time_middle_tests {
         );
     }
 
+    #[test]
+    fn levels_put_every_node_above_its_down_neighbours() {
+        let topo = topo_4node();
+        // Node 2 reads the UP rows of its DOWN neighbours 0 and 1.
+        assert_eq!(customization_levels(&topo), vec![vec![0u32, 1, 3], vec![2]]);
+
+        // The level-parallel pass expands the shortcut through the topology
+        // middle (apex 0) exactly as a rank-order pass would.
+        let sorted_down_indices: Vec<Vec<usize>> =
+            vec![Vec::new(), Vec::new(), vec![0usize, 1], Vec::new()];
+        let (up, down) = bottom_up_customize(&topo, &sorted_down_indices, leaf_len);
+        assert_eq!(up, vec![10, 100, 13]);
+        assert_eq!(down, vec![3, 5]);
+    }
+
     #[test]
     fn pack_wm_is_a_bijection_and_orders_by_weight_then_middle() {
         // pack_wm must (1) round-trip both fields exactly and (2) put the
@@ -2485,3 +3324,206 @@ mod len_along_time_middle_tests {
         assert_eq!(a_mid, b_mid, "elected middles must be reproducible");
     }
 }
+
+#[cfg(test)]
+mod quantized_weights_tests {
+    use super::*;
+    use crate::formats::CchWeightsFile;
+
+    /// `--quantize-weights` round-trip through both the owned and the mmap
+    /// reader: a direction that fits u16 stays exact, the other decodes
+    /// within its block scale and keeps the sentinel.
+    #[test]
+    fn quantized_file_round_trips() {
+        let up: Vec<u32> = vec![5, u32::MAX, 65_534, 0];
+        let down: Vec<u32> = (0..300u32)
+            .map(|i| {
+                if i == 7 {
+                    u32::MAX
+                } else {
+                    100_000 + i * 1_000
+                }
+            })
+            .collect();
+        let up_mid = vec![u32::MAX; up.len()];
+        let down_mid = vec![u32::MAX; down.len()];
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cch.w.car.u32");
+        write_cch_weights(&path, &up, &down, &up_mid, &down_mid, Mode(0), true).unwrap();
+
+        let owned = CchWeightsFile::read(&path).unwrap();
+        let len = std::fs::metadata(&path).unwrap().len() as usize;
+        let mmap = crate::formats::mmap::map_readonly(&path).unwrap();
+        let mapped = CchWeightsFile::read_from_mmap_unverified(mmap, 0, len).unwrap();
+
+        for w in [&owned, &mapped] {
+            assert!(!w.up.is_quantized(), "u16-exact direction stays lossless");
+            assert_eq!(w.up.to_vec_u32(), up);
+            assert!(w.down.is_quantized());
+            assert_eq!(w.down.len(), down.len());
+            for (i, &orig) in down.iter().enumerate() {
+                let got = w.down.get(i);
+                if orig == u32::MAX {
+                    assert_eq!(got, u32::MAX);
+                } else {
+                    // Block maxima ≤ 399 000 → scale ≤ 7 → error ≤ 3.
+                    assert!(got.abs_diff(orig) <= 3, "{orig} -> {got}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod perfect_customization_tests {
+    use super::*;
+    use crate::formats::BitsetField;
+
+    /// Triangle 0-1-2 with the lowest rank as the only non-apex:
+    ///
+    /// ```text
+    ///   UP edges  : 0→1 (1), 0→2 (5), 1→2 (1, SHORTCUT)
+    ///   DOWN edges: 1→0 (1), 2→0 (3), 2→1 (unusable, SHORTCUT)
+    /// ```
+    fn topo_triangle() -> CchTopo {
+        CchTopo {
+            n_nodes: 3,
+            n_shortcuts: 2,
+            n_original_arcs: 4,
+            inputs_sha: [0u8; 32],
+            up_offsets: ArcCow::from_vec(vec![0u64, 2, 3, 3]),
+            up_targets: ArcCow::from_vec(vec![1u32, 2, 2]),
+            up_is_shortcut: BitsetField::from_bools(&[false, false, true]),
+            up_middle: WeightArray::from_vec_u32(vec![u32::MAX, u32::MAX, 0]),
+            down_offsets: ArcCow::from_vec(vec![0u64, 0, 1, 3]),
+            down_targets: ArcCow::from_vec(vec![0u32, 0, 1]),
+            down_is_shortcut: BitsetField::from_bools(&[false, false, true]),
+            down_middle: WeightArray::from_vec_u32(vec![u32::MAX, u32::MAX, 0]),
+            rank_to_filtered: ArcCow::from_vec(vec![0u32, 1, 2]),
+        }
+    }
+
+    #[test]
+    fn levels_put_every_node_below_its_upper_neighbours() {
+        let topo = topo_triangle();
+        let rev_down = build_reverse_down_adj_for_relax(&topo);
+        assert_eq!(
+            perfect_levels(&topo, &rev_down),
+            vec![vec![2u32], vec![1], vec![0]]
+        );
+    }
+
+    #[test]
+    fn removes_arcs_matched_by_an_upper_witness() {
+        let topo = topo_triangle();
+        let rev_down = build_reverse_down_adj_for_relax(&topo);
+        let (up, down, stats) = perfect_customize(&topo, &[1, 5, 1], &[1, 3, u32::MAX], &rev_down);
+
+        // 0→2 costs 1 + 1 through 0→1→2, so the direct arc goes. 0→1 and
+        // 2→0 would need the unusable 2→1; 1→0 is cheaper than 1→2→0.
+        assert_eq!(up, vec![1, u32::MAX, 1]);
+        assert_eq!(down, vec![1, 3, u32::MAX]);
+        assert_eq!(
+            stats,
+            PerfectStats {
+                removed_up: 1,
+                removed_down: 0,
+                removed_shortcuts: 0,
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod delta_customization_tests {
+    use super::*;
+    use crate::formats::{BitsetField, FilteredEbg};
+
+    /// Same shape as `len_along_time_middle_tests::topo_4node`: shortcut 2→3
+    /// can be expanded through apex 0 or apex 1.
+    ///
+    /// ```text
+    ///   UP edges  : 0→3, 1→3, 2→3 (SHORTCUT)
+    ///   DOWN edges: 2→0, 2→1
+    /// ```
+    fn topo_4node() -> CchTopo {
+        CchTopo {
+            n_nodes: 4,
+            n_shortcuts: 1,
+            n_original_arcs: 4,
+            inputs_sha: [0u8; 32],
+            up_offsets: ArcCow::from_vec(vec![0u64, 1, 2, 3, 3]),
+            up_targets: ArcCow::from_vec(vec![3u32, 3, 3]),
+            up_is_shortcut: BitsetField::from_bools(&[false, false, true]),
+            up_middle: WeightArray::from_vec_u32(vec![u32::MAX, u32::MAX, 0]),
+            down_offsets: ArcCow::from_vec(vec![0u64, 0, 0, 2, 2]),
+            down_targets: ArcCow::from_vec(vec![0u32, 1]),
+            down_is_shortcut: BitsetField::from_bools(&[false, false]),
+            down_middle: WeightArray::from_vec_u32(vec![u32::MAX, u32::MAX]),
+            rank_to_filtered: ArcCow::from_vec(vec![0u32, 1, 2, 3]),
+        }
+    }
+
+    /// Filtered EBG with identity rank/filtered/original ids and one arc per
+    /// CCH original edge; arc `i` carries turn penalty `i`.
+    fn filtered_ebg() -> FilteredEbg {
+        FilteredEbg {
+            mode: Mode(0),
+            n_filtered_nodes: 4,
+            n_filtered_arcs: 4,
+            n_original_nodes: 4,
+            inputs_sha: [0u8; 32],
+            offsets: ArcCow::from_vec(vec![0u64, 1, 2, 4, 4]),
+            heads: ArcCow::from_vec(vec![3u32, 3, 0, 1]),
+            original_arc_idx: ArcCow::from_vec(vec![0u32, 1, 2, 3]),
+            filtered_to_original: ArcCow::from_vec(vec![0u32, 1, 2, 3]),
+            original_to_filtered: ArcCow::from_vec(vec![0u32, 1, 2, 3]),
+        }
+    }
+
+    fn full(topo: &CchTopo, ebg: &FilteredEbg, weights: &[u32], turns: &[u32]) -> CchWeights {
+        let ebg_nodes = EbgNodes {
+            n_nodes: 0,
+            created_unix: 0,
+            inputs_sha: [0; 32],
+            nodes: ArcCow::from_vec(Vec::new()),
+        };
+        customize_cch_time_in_memory(topo, ebg, weights, turns, &ebg_nodes, None)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn delta_matches_full_customization() {
+        let topo = topo_4node();
+        let ebg = filtered_ebg();
+        let turns = [0u32, 1, 2, 3];
+        let mut weights = vec![3u32, 5, 1, 10];
+        let mut current = full(&topo, &ebg, &weights, &turns);
+        // via 0: (3 + 2) + (10 + 0) = 15, via 1: (5 + 3) + (10 + 1) = 19.
+        assert_eq!(current.up.get(2), 15);
+        assert_eq!(current.up_middle[2], 0);
+
+        // Slow node 0 (middle flips to 1), close node 1 (back to 0, slower),
+        // then speed node 0 up again.
+        for (node, weight, expected) in [(0u32, 9u32, 19u32), (1, 0, 21), (0, 2, 14)] {
+            let changed = crate::formats::WeightDelta::new(Mode(0), vec![(node, weight)])
+                .unwrap()
+                .apply(&mut weights)
+                .unwrap();
+            let (delta, stats) =
+                customize_cch_delta(&topo, &ebg, &weights, &turns, &current, &changed).unwrap();
+            let reference = full(&topo, &ebg, &weights, &turns);
+
+            assert_eq!(delta.up.to_vec_u32(), reference.up.to_vec_u32());
+            assert_eq!(delta.down.to_vec_u32(), reference.down.to_vec_u32());
+            assert_eq!(delta.up_middle.to_vec(), reference.up_middle.to_vec());
+            assert_eq!(delta.down_middle.to_vec(), reference.down_middle.to_vec());
+            assert_eq!(delta.up.get(2), expected);
+            assert_eq!(stats.changed_nodes, 1);
+            assert_eq!(stats.seeded_arcs, 1);
+            current = delta;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Per-mode barrier policy for Step 4
+//!
+//! Step 3 splits edges at every barrier node, so a barrier is always an
+//! EBG via node. Arcs that pass through it are dropped for modes whose
+//! model denies the barrier kind (turning around stays allowed), and the
+//! model's delay is added to the turn penalty of every other mode.
+
+use anyhow::Result;
+
+use super::turn_penalty::read_model_schema;
+use crate::formats::BarrierKind;
+use crate::model::schema::BarrierSchema;
+
+const N_KINDS: usize = BarrierKind::ALL.len();
+
+#[derive(Debug, Clone, Default)]
+pub struct BarrierPolicy {
+    deny: [bool; N_KINDS],
+    delay_s: [u32; N_KINDS],
+}
+
+impl BarrierPolicy {
+    /// Policy from a model's `barriers` section; unknown kinds are an error.
+    pub fn from_schema(schema: &BarrierSchema) -> Result<Self> {
+        let kind = |name: &str| {
+            BarrierKind::from_name(name)
+                .ok_or_else(|| anyhow::anyhow!("unknown barrier kind '{name}' in model"))
+        };
+        let mut policy = Self::default();
+        for name in &schema.deny {
+            policy.deny[kind(name)? as usize] = true;
+        }
+        for (name, &delay) in &schema.delay_s {
+            policy.delay_s[kind(name)? as usize] = delay;
+        }
+        Ok(policy)
+    }
+
+    /// Load the policy for an active mode from `<models_dir>/<mode>.model.json`.
+    pub fn from_models_dir(models_dir: &std::path::Path, mode_name: &str) -> Result<Self> {
+        Self::from_schema(&read_model_schema(models_dir, mode_name)?.barriers)
+    }
+
+    pub fn denies(&self, kind: BarrierKind) -> bool {
+        self.deny[kind as usize]
+    }
+
+    pub fn delay_s(&self, kind: BarrierKind) -> u32 {
+        self.delay_s[kind as usize]
+    }
+
+    /// Number of denied kinds (for the build log).
+    pub fn n_denied(&self) -> usize {
+        self.deny.iter().filter(|&&d| d).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_schema() {
+        let schema: BarrierSchema =
+            serde_json::from_str(r#"{"deny": ["bollard", "other"], "delay_s": {"lift_gate": 10}}"#)
+                .unwrap();
+        let policy = BarrierPolicy::from_schema(&schema).unwrap();
+        assert!(policy.denies(BarrierKind::Bollard));
+        assert!(policy.denies(BarrierKind::Other));
+        assert!(!policy.denies(BarrierKind::LiftGate));
+        assert_eq!(policy.delay_s(BarrierKind::LiftGate), 10);
+        assert_eq!(policy.n_denied(), 2);
+
+        let bad: BarrierSchema = serde_json::from_str(r#"{"deny": ["moat"]}"#).unwrap();
+        assert!(BarrierPolicy::from_schema(&bad).is_err());
+    }
+
+    #[test]
+    fn test_shipped_models_parse() {
+        let dir = std::path::PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../models"));
+        let car = BarrierPolicy::from_models_dir(&dir, "car").unwrap();
+        assert!(car.denies(BarrierKind::Bollard));
+        let foot = BarrierPolicy::from_models_dir(&dir, "foot").unwrap();
+        assert!(!foot.denies(BarrierKind::Bollard));
+    }
+}
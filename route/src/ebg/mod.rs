@@ -12,9 +12,11 @@ use std::path::{Path, PathBuf};
 use crate::formats::*;
 use crate::profile_abi::{MAX_MODES, Mode};
 
+pub mod barriers;
 pub mod turn_penalty;
 pub mod turn_processor;
 
+use barriers::BarrierPolicy;
 use turn_penalty::{TurnGeometry, TurnPenaltyConfig, compute_turn_penalty};
 
 /// Per-mode input paths for EBG construction
@@ -32,6 +34,8 @@ pub struct EbgConfig {
     pub nbg_geo_path: PathBuf,
     pub nbg_node_map_path: PathBuf,
     pub node_signals_path: PathBuf,
+    /// node_barriers.bin from Step 1; barriers are ignored when absent.
+    pub node_barriers_path: PathBuf,
     pub modes: Vec<EbgModeConfig>,
     pub outdir: PathBuf,
     /// Runtime-resolved models directory (#491) — turn penalties per active
@@ -107,17 +111,23 @@ pub fn build_ebg(config: EbgConfig) -> Result<EbgResult> {
         NodeSignals::new(vec![])
     };
 
+    // 1c. Load barrier nodes
+    let node_barriers = if config.node_barriers_path.exists() {
+        let barriers = NodeBarriersFile::read(&config.node_barriers_path)?;
+        println!("  ✓ Loaded {} barrier nodes", barriers.len());
+        barriers
+    } else {
+        println!("  ⚠ No node_barriers.bin found, barriers disabled");
+        NodeBarriers::new(vec![])
+    };
+
     // 2. Load way attributes per mode (dynamic list)
     println!("Loading way attributes...");
-    let mut way_attrs_by_mode: Vec<HashMap<i64, WayAttr>> = Vec::with_capacity(MAX_MODES);
-    // Pre-fill with empty maps for all slots
-    for _ in 0..MAX_MODES {
-        way_attrs_by_mode.push(HashMap::new());
-    }
+    let mut way_attrs_by_mode: [Option<way_attrs::Index>; MAX_MODES] = Default::default();
     for mc in &config.modes {
-        let attrs = load_way_attrs(&mc.way_attrs_path)?;
+        let attrs = way_attrs::Index::open(&mc.way_attrs_path)?;
         println!("  ✓ {}: {} ways", mc.mode_name, attrs.len());
-        way_attrs_by_mode[mc.mode_index as usize] = attrs;
+        way_attrs_by_mode[mc.mode_index as usize] = Some(attrs);
     }
 
     // Compute active mode mask (which bits are present)
@@ -166,6 +176,16 @@ pub fn build_ebg(config: EbgConfig) -> Result<EbgResult> {
         );
         penalty_configs[mc.mode_index as usize] = tp;
     }
+    let mut barrier_policies: [BarrierPolicy; MAX_MODES] = Default::default();
+    for mc in &config.modes {
+        let policy = BarrierPolicy::from_models_dir(&config.models_dir, &mc.mode_name)?;
+        println!(
+            "  ✓ barriers {}: {} kinds denied",
+            mc.mode_name,
+            policy.n_denied()
+        );
+        barrier_policies[mc.mode_index as usize] = policy;
+    }
 
     // Determine which mode (if any) to use for highway class lookup in turn geometry.
     // Use the first available mode's way_attrs for highway class info.
@@ -180,11 +200,13 @@ pub fn build_ebg(config: EbgConfig) -> Result<EbgResult> {
         &nbg_geo,
         &nbg_node_map,
         &node_signals,
+        &node_barriers,
         &ebg_nodes,
         &canonical_rules,
         &way_attrs_by_mode,
         active_mode_mask,
         &penalty_configs,
+        &barrier_policies,
         highway_class_mode_idx,
         &config.modes,
     )?;
@@ -307,11 +329,13 @@ fn build_adjacency(
     nbg_geo: &NbgGeo,
     nbg_node_map: &NbgNodeMap,
     node_signals: &NodeSignals,
+    node_barriers: &NodeBarriers,
     ebg_nodes: &[EbgNode],
     canonical_rules: &HashMap<TurnRuleKey, CanonicalTurnRule>,
-    way_attrs_by_mode: &[HashMap<i64, WayAttr>],
+    way_attrs_by_mode: &[Option<way_attrs::Index>],
     active_mode_mask: u8,
     penalty_configs: &[TurnPenaltyConfig; MAX_MODES],
+    barrier_policies: &[BarrierPolicy; MAX_MODES],
     highway_class_mode_idx: usize,
     modes: &[EbgModeConfig],
 ) -> Result<(HashMap<u32, Vec<(u32, u32)>>, Vec<TurnEntry>)> {
@@ -362,6 +386,17 @@ fn build_adjacency(
         let via_node_osm_for_signal = nbg_node_to_osm_id(nbg_node, nbg_node_map);
         let via_has_signal = node_signals.has_signal(via_node_osm_for_signal);
 
+        // Barrier at the via node: modes that cannot pass it
+        let via_barrier = node_barriers.get(via_node_osm_for_signal);
+        let mut barrier_blocked_mask = 0u8;
+        if let Some(kind) = via_barrier {
+            for mc in modes {
+                if barrier_policies[mc.mode_index as usize].denies(kind) {
+                    barrier_blocked_mask |= Mode(mc.mode_index).bit();
+                }
+            }
+        }
+
         // For each incoming EBG edge (a = u→nbg_node)
         for &a_id in &incoming {
             let a_node = &ebg_nodes[a_id as usize];
@@ -377,8 +412,11 @@ fn build_adjacency(
                     continue;
                 }
 
-                // Handle U-turns with mode-specific policy
-                let is_uturn = a_node.tail_nbg == b_node.head_nbg;
+                // Handle U-turns with mode-specific policy. A U-turn goes
+                // back along the same NBG edge: a parallel edge back to
+                // where we came from is a separate road, and a self-loop
+                // may be driven round again.
+                let is_uturn = a_node.geom_idx == b_node.geom_idx && a_id != b_id;
                 let is_dead_end = outgoing.len() == 1;
 
                 // Determine mode accessibility
@@ -417,9 +455,15 @@ fn build_adjacency(
                 mode_mask &= get_way_mode_mask(from_way_id, way_attrs_by_mode, active_mode_mask);
                 mode_mask &= get_way_mode_mask(to_way_id, way_attrs_by_mode, active_mode_mask);
 
-                // Apply U-turn policy: restrict u-turn-restricted modes at non-dead-ends
+                // Apply U-turn policy: restrict u-turn-restricted modes at non-dead-ends.
+                // A barrier a mode cannot pass is a dead end for that mode.
                 if is_uturn && !is_dead_end {
-                    mode_mask &= !uturn_restricted_mask;
+                    mode_mask &= !(uturn_restricted_mask & !barrier_blocked_mask);
+                }
+
+                // Barriers block passing through, not turning around
+                if !is_uturn {
+                    mode_mask &= !barrier_blocked_mask;
                 }
 
                 // If no modes can use this turn, skip it
@@ -428,7 +472,7 @@ fn build_adjacency(
                 }
 
                 // === COMPUTE TURN GEOMETRY AND PENALTIES ===
-                let from_bearing = if a_node.tail_nbg == from_edge.u_node {
+                let from_bearing = if EbgNode::is_forward(a_id) {
                     from_edge.bearing_deci_deg
                 } else if from_edge.bearing_deci_deg == 65535 {
                     65535
@@ -436,7 +480,7 @@ fn build_adjacency(
                     (from_edge.bearing_deci_deg + 1800) % 3600
                 };
 
-                let to_bearing = if b_node.tail_nbg == to_edge.u_node {
+                let to_bearing = if EbgNode::is_forward(b_id) {
                     to_edge.bearing_deci_deg
                 } else if to_edge.bearing_deci_deg == 65535 {
                     65535
@@ -445,14 +489,15 @@ fn build_adjacency(
                 };
 
                 // Get highway classes for road class transition penalty
-                let from_highway_class = way_attrs_by_mode[highway_class_mode_idx]
-                    .get(&from_way_id)
-                    .map(|a| a.output.highway_class)
-                    .unwrap_or(0);
-                let to_highway_class = way_attrs_by_mode[highway_class_mode_idx]
-                    .get(&to_way_id)
-                    .map(|a| a.output.highway_class)
-                    .unwrap_or(0);
+                let highway_class = |way_id| {
+                    way_attrs_by_mode[highway_class_mode_idx]
+                        .as_ref()
+                        .and_then(|attrs| attrs.get(way_id))
+                        .map(|a| a.output.highway_class)
+                        .unwrap_or(0)
+                };
+                let from_highway_class = highway_class(from_way_id);
+                let to_highway_class = highway_class(to_way_id);
 
                 // Compute turn geometry
                 let geom = TurnGeometry::compute(
@@ -483,6 +528,19 @@ fn build_adjacency(
                     }
                 }
 
+                // Delay for passing a barrier (gate to open, toll booth).
+                if let Some(kind) = via_barrier
+                    && !is_uturn
+                {
+                    for mc in modes {
+                        let idx = mc.mode_index as usize;
+                        if (mode_mask & Mode(mc.mode_index).bit()) != 0 {
+                            penalty_s[idx] =
+                                penalty_s[idx].saturating_add(barrier_policies[idx].delay_s(kind));
+                        }
+                    }
+                }
+
                 // Statistics
                 total_arcs += 1;
                 let first_penalty = modes
@@ -598,7 +656,7 @@ fn materialize_csr(
 /// Checks all active modes dynamically.
 fn get_way_mode_mask(
     way_id: i64,
-    way_attrs_by_mode: &[HashMap<i64, WayAttr>],
+    way_attrs_by_mode: &[Option<way_attrs::Index>],
     active_mode_mask: u8,
 ) -> u8 {
     let mut mask = 0u8;
@@ -608,7 +666,8 @@ fn get_way_mode_mask(
             continue; // Mode not active
         }
         if attrs
-            .get(&way_id)
+            .as_ref()
+            .and_then(|attrs| attrs.get(way_id))
             .map(|a| a.output.access_fwd || a.output.access_rev)
             .unwrap_or(false)
         {
@@ -627,16 +686,6 @@ fn nbg_node_to_osm_id(compact_id: u32, node_map: &NbgNodeMap) -> i64 {
         .unwrap_or(0)
 }
 
-/// Helper: Load way attributes into HashMap
-fn load_way_attrs(path: &Path) -> Result<HashMap<i64, WayAttr>> {
-    let attrs = way_attrs::read_all(path)?;
-    let mut map = HashMap::with_capacity(attrs.len());
-    for attr in attrs {
-        map.insert(attr.way_id, attr);
-    }
-    Ok(map)
-}
-
 /// Compute combined SHA-256 of all inputs
 fn compute_inputs_sha(config: &EbgConfig) -> Result<[u8; 32]> {
     use sha2::{Digest, Sha256};
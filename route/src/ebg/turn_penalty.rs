@@ -144,17 +144,7 @@ impl TurnPenaltyConfig {
     /// artifacts with ZERO turn penalties. Identity is reserved for genuinely
     /// inactive mode slots, which never reach this call.
     pub fn from_models_dir(models_dir: &std::path::Path, mode_name: &str) -> anyhow::Result<Self> {
-        use anyhow::Context;
-        let model_path = models_dir.join(format!("{}.model.json", mode_name));
-        let content = std::fs::read_to_string(&model_path).with_context(|| {
-            format!(
-                "cannot read model file for active mode '{}': {} (#491 — refusing to build with zero turn penalties)",
-                mode_name,
-                model_path.display()
-            )
-        })?;
-        let schema: ModelSchema = serde_json::from_str(&content)
-            .with_context(|| format!("unparseable model file: {}", model_path.display()))?;
+        let schema = read_model_schema(models_dir, mode_name)?;
         Ok(Self::from_model_schema(&schema.turn_penalties))
     }
 
@@ -217,6 +207,25 @@ impl TurnPenaltyConfig {
     }
 }
 
+/// Read `<models_dir>/<mode>.model.json` for an active mode (#491: a
+/// missing or unparseable file is a hard error).
+pub(crate) fn read_model_schema(
+    models_dir: &std::path::Path,
+    mode_name: &str,
+) -> anyhow::Result<ModelSchema> {
+    use anyhow::Context;
+    let model_path = models_dir.join(format!("{}.model.json", mode_name));
+    let content = std::fs::read_to_string(&model_path).with_context(|| {
+        format!(
+            "cannot read model file for active mode '{}': {} (#491 — refusing to build with zero turn penalties)",
+            mode_name,
+            model_path.display()
+        )
+    })?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("unparseable model file: {}", model_path.display()))
+}
+
 /// Compute turn penalty using OSRM's sigmoid formula
 ///
 /// OSRM formula from car.lua:
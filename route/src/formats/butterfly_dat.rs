@@ -107,6 +107,9 @@ pub enum SectionKind {
     WayAttrs = 0x0002_0001,
     /// `step2/turn_rules.<mode>.bin` — per-mode turn rules.
     TurnRules = 0x0002_0002,
+    /// `step2/way_conditionals.<mode>.bin` — per-mode time-dependent
+    /// way restrictions.
+    WayConditionals = 0x0002_0003,
 
     /// `step3/nbg.csr` — node-based graph CSR.
     NbgCsr = 0x0003_0001,
@@ -288,6 +291,7 @@ impl SectionKind {
 
             0x0002_0001 => Self::WayAttrs,
             0x0002_0002 => Self::TurnRules,
+            0x0002_0003 => Self::WayConditionals,
 
             0x0003_0001 => Self::NbgCsr,
             0x0003_0002 => Self::NbgGeo,
@@ -353,6 +357,7 @@ impl SectionKind {
             Self::NodeSignals => "step1/node_signals.bin",
             Self::WayAttrs => "step2/way_attrs",
             Self::TurnRules => "step2/turn_rules",
+            Self::WayConditionals => "step2/way_conditionals",
             Self::NbgCsr => "step3/nbg.csr",
             Self::NbgGeo => "step3/nbg.geo",
             Self::NbgNodeMap => "step3/nbg.node_map",
@@ -77,7 +77,7 @@ use std::sync::Arc;
 use super::bitset::BitsetField;
 use super::cch_weights::{WeightArray, WeightWidth};
 use super::crc;
-use super::mmap::ArcCow;
+use super::mmap::{ArcCow, map_readonly};
 
 const MAGIC: u32 = 0x43434854; // "CCHT"
 const VERSION: u32 = 5; // Version 5 (#352): u16/u24/u32 width-picked middles via WeightArray
@@ -566,6 +566,33 @@ impl CchTopoFile {
         Self::read_from_reader(BufReader::new(File::open(path)?))
     }
 
+    /// Memory-map `path` and return a `CchTopo` whose body arrays are
+    /// zero-copy views into the mapping (see
+    /// [`Self::read_from_mmap_unverified`]). The CRC is checked over the
+    /// mapped bytes first, so the file is paged in once but never
+    /// copied onto the heap. Use this instead of [`Self::read`] for
+    /// long-lived loaders (unpacked serve, range/matrix tools).
+    pub fn read_mmap<P: AsRef<Path>>(path: P) -> Result<CchTopo> {
+        let path = path.as_ref();
+        let mmap = map_readonly(path)?;
+        let len = mmap.len();
+        anyhow::ensure!(
+            len >= HEADER_LEN + FOOTER_LEN,
+            "cch.topo too short for header+footer: {len} bytes"
+        );
+        let body_end = len - FOOTER_LEN;
+        let mut digest = crc::Digest::new();
+        digest.update(&mmap[..body_end]);
+        let computed_crc = digest.finalize();
+        let stored_crc = u64::from_le_bytes(mmap[body_end..body_end + 8].try_into()?);
+        anyhow::ensure!(
+            computed_crc == stored_crc,
+            "CRC64 mismatch in {}: computed 0x{computed_crc:016X}, stored 0x{stored_crc:016X}",
+            path.display()
+        );
+        Self::read_from_mmap_unverified(mmap, 0, len)
+    }
+
     /// Read directly from an in-memory byte slice (e.g. an mmap-backed
     /// section of a `butterfly.dat` container). Same byte format as the
     /// path API; CRC is checked here too.
@@ -1315,6 +1342,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_read_mmap_matches_read() -> Result<()> {
+        let data = make_test_topo();
+        let tmp = NamedTempFile::new()?;
+        CchTopoFile::write(tmp.path(), &data)?;
+        let owned = CchTopoFile::read(tmp.path())?;
+        let mapped = CchTopoFile::read_mmap(tmp.path())?;
+
+        assert!(matches!(mapped.up_targets, ArcCow::Mmap { .. }));
+        assert_eq!(&mapped.up_offsets[..], &owned.up_offsets[..]);
+        assert_eq!(&mapped.up_targets[..], &owned.up_targets[..]);
+        assert_eq!(&mapped.down_offsets[..], &owned.down_offsets[..]);
+        assert_eq!(&mapped.down_targets[..], &owned.down_targets[..]);
+        assert_eq!(&mapped.rank_to_filtered[..], &owned.rank_to_filtered[..]);
+        assert!(mapped.up_is_shortcut.bit(2));
+        assert_eq!(mapped.down_middle.get(2), 1);
+
+        // The CRC still guards the mapped path.
+        {
+            let mut file = std::fs::OpenOptions::new().write(true).open(tmp.path())?;
+            file.seek(SeekFrom::Start(88))?;
+            file.write_all(&[0xFF])?;
+        }
+        let err = CchTopoFile::read_mmap(tmp.path()).expect_err("must reject");
+        assert!(err.to_string().contains("CRC64 mismatch"), "{err}");
+        Ok(())
+    }
+
     #[test]
     fn test_bitset_pack_unpack_roundtrip() {
         // Empty
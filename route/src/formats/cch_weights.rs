@@ -52,14 +52,103 @@ const VERSION: u16 = 4;
 ///   11 = reserved
 ///
 /// Bits 0..2 are the up-direction width, bits 2..4 are the
-/// down-direction width. Bits 4..8 are reserved.
+/// down-direction width. Bits 4 and 5 flag a quantized up / down body
+/// (see [`WeightArray::Q16`]); the matching width code must be u16.
+/// Bits 6..8 are reserved.
 const UP_WIDTH_MASK: u8 = 0b0000_0011;
 const DOWN_WIDTH_SHIFT: u8 = 2;
 const DOWN_WIDTH_MASK: u8 = 0b0000_1100;
+const UP_QUANTIZED_BIT: u8 = 0b0001_0000;
+const DOWN_QUANTIZED_BIT: u8 = 0b0010_0000;
+const KNOWN_FLAG_BITS: u8 = 0b0011_1111;
 const WIDTH_CODE_U32: u8 = 0;
 const WIDTH_CODE_U16: u8 = 1;
 const WIDTH_CODE_U24: u8 = 2;
 
+/// Entries per quantization block of a [`WeightArray::Q16`] stream.
+pub const Q16_BLOCK: usize = 256;
+
+/// Largest stored code; `u16::MAX` stays the "no edge" sentinel.
+const Q16_MAX_CODE: u64 = u16::MAX as u64 - 1;
+
+/// Header flag bits for a quantized body in each direction.
+#[inline]
+pub(crate) fn quantized_flag_bits(up: bool, down: bool) -> u8 {
+    (if up { UP_QUANTIZED_BIT } else { 0 }) | (if down { DOWN_QUANTIZED_BIT } else { 0 })
+}
+
+/// Number of `u16` words in a Q16 stream holding `n` entries: every
+/// block of up to [`Q16_BLOCK`] entries is preceded by its `u32` scale
+/// (two words, low half first).
+#[inline]
+pub fn q16_stream_len(n: usize) -> usize {
+    n + 2 * n.div_ceil(Q16_BLOCK)
+}
+
+/// On-disk bytes of a Q16 body of `n` entries, padded to a 4-byte
+/// boundary like [`WeightWidth::padded_body_bytes`].
+#[inline]
+pub fn q16_padded_body_bytes(n: usize) -> usize {
+    (2 * q16_stream_len(n) + 3) & !3
+}
+
+/// Quantize `weights` into a Q16 stream.
+///
+/// Each block's scale is the smallest integer that maps its largest
+/// finite weight into `1..=65 534`; entries are stored as
+/// `round(w / scale)`, saturating at 65 534 and never rounding a
+/// non-zero weight down to 0. `u32::MAX` becomes the `u16::MAX`
+/// sentinel. A block whose weights all fit u16 gets scale 1 and
+/// round-trips exactly; otherwise the per-entry error is at most
+/// `scale / 2`, i.e. under 1/131 068 of the block maximum (weights below
+/// `scale / 2` are rounded up to `scale`, keeping every edge non-free).
+pub fn quantize_q16(weights: &[u32]) -> Vec<u16> {
+    let mut stream = Vec::with_capacity(q16_stream_len(weights.len()));
+    for block in weights.chunks(Q16_BLOCK) {
+        let max_finite = block
+            .iter()
+            .filter(|&&w| w != u32::MAX)
+            .max()
+            .copied()
+            .unwrap_or(0) as u64;
+        let scale = max_finite.div_ceil(Q16_MAX_CODE).max(1);
+        stream.push(scale as u16);
+        stream.push((scale >> 16) as u16);
+        for &w in block {
+            let code = if w == u32::MAX {
+                u16::MAX
+            } else {
+                let w = w as u64;
+                let q = ((w + scale / 2) / scale).min(Q16_MAX_CODE);
+                (if w > 0 { q.max(1) } else { 0 }) as u16
+            };
+            stream.push(code);
+        }
+    }
+    stream
+}
+
+/// Entry count of a Q16 stream of `stream_len` words (inverse of
+/// [`q16_stream_len`]).
+#[inline]
+fn q16_entries(stream_len: usize) -> usize {
+    stream_len - 2 * stream_len.div_ceil(Q16_BLOCK + 2)
+}
+
+/// Decode entry `i` of a Q16 stream.
+#[inline(always)]
+fn q16_get(stream: &[u16], i: usize) -> u32 {
+    let base = (i / Q16_BLOCK) * (Q16_BLOCK + 2);
+    let code = stream[base + 2 + i % Q16_BLOCK];
+    if code == u16::MAX {
+        u32::MAX
+    } else {
+        let scale = u32::from(stream[base]) | (u32::from(stream[base + 1]) << 16);
+        // ≤ 65 534 × 65 538 < u32::MAX, so a finite entry never aliases the sentinel.
+        u32::from(code) * scale
+    }
+}
+
 /// Per-direction body width for a CCH weights file.
 ///
 /// - **U16** (#306 PR 2): each entry stored in 2 bytes. `u16::MAX`
@@ -161,6 +250,12 @@ pub enum WeightArray {
     U24(ArcCow<u8>),
     /// Native `u32` storage. Used when even u24 overflows.
     U32(ArcCow<u32>),
+    /// Lossy quantized storage, opted into at customization
+    /// time with `step8-customize --quantize-weights`. A single `u16`
+    /// stream: each block of [`Q16_BLOCK`] codes is preceded by its
+    /// `u32` scale, so `get` touches one contiguous region and the enum
+    /// stays the size of the other variants. See [`quantize_q16`].
+    Q16(ArcCow<u16>),
 }
 
 impl WeightArray {
@@ -183,6 +278,7 @@ impl WeightArray {
                 if v == U24_SENTINEL { u32::MAX } else { v }
             }
             Self::U32(arr) => arr.as_slice()[i],
+            Self::Q16(stream) => q16_get(stream.as_slice(), i),
         }
     }
 
@@ -193,6 +289,7 @@ impl WeightArray {
             Self::U16(arr) => arr.len(),
             Self::U24(bytes) => bytes.len() / 3,
             Self::U32(arr) => arr.len(),
+            Self::Q16(stream) => q16_entries(stream.len()),
         }
     }
 
@@ -219,22 +316,32 @@ impl WeightArray {
     }
 
     /// Get the storage width — useful for size reporting and tests.
+    ///
+    /// Quantized arrays report `U32`: their decoded values are not
+    /// bounded by any narrower lossless width, so re-encoders (flat
+    /// writers) must widen through `get` / `iter`.
     #[inline]
     pub fn width(&self) -> WeightWidth {
         match self {
             Self::U16(_) => WeightWidth::U16,
             Self::U24(_) => WeightWidth::U24,
-            Self::U32(_) => WeightWidth::U32,
+            Self::U32(_) | Self::Q16(_) => WeightWidth::U32,
         }
     }
 
+    /// `true` for the lossy [`Self::Q16`] representation.
+    #[inline]
+    pub fn is_quantized(&self) -> bool {
+        matches!(self, Self::Q16(_))
+    }
+
     /// Upgrade to an owned `Vec<u32>` for mutation. Compact variants
     /// are widened in place; subsequent reads then decode through the
     /// U32 path. After calling, the returned `&mut Vec<u32>` is the
     /// same storage `get` / `iter` reads from.
     pub fn to_mut_vec(&mut self) -> &mut Vec<u32> {
         match self {
-            Self::U16(_) | Self::U24(_) => {
+            Self::U16(_) | Self::U24(_) | Self::Q16(_) => {
                 let widened: Vec<u32> = (0..self.len()).map(|i| self.get(i)).collect();
                 *self = Self::U32(ArcCow::from_vec(widened));
             }
@@ -303,6 +410,12 @@ impl WeightArray {
         Self::U24(ArcCow::from_vec(bytes))
     }
 
+    /// Quantize `weights` into an owned [`Self::Q16`] array.
+    #[inline]
+    pub fn quantize(weights: &[u32]) -> Self {
+        Self::Q16(ArcCow::from_vec(quantize_q16(weights)))
+    }
+
     /// Empty array (U32 width).
     #[inline]
     pub fn empty() -> Self {
@@ -434,13 +547,15 @@ impl CchWeightsFile {
             "cch.weights section too short for header+footer: {byte_len} bytes",
         );
 
-        let header = &mmap[byte_offset..byte_offset + HEADER_LEN];
+        let header = parse_header(&mmap[byte_offset..byte_offset + HEADER_LEN])?;
         let CchWeightsHeader {
             n_up,
             n_down,
             up_width,
             down_width,
-        } = parse_header(header)?;
+            up_quantized,
+            down_quantized,
+        } = header;
 
         // Layout v4 (with optional middle arrays):
         //   header(32) | up(width_up·n_up [+0-3 pad]) | down(width_down·n_down [+0-3 pad])
@@ -452,8 +567,9 @@ impl CchWeightsFile {
         // `n * 3` up to a 4-byte boundary. The pad keeps the following
         // arrays (the other direction's body and the u32 middles)
         // 4-byte aligned for `bytemuck::cast_slice` / `ArcCow::<u32>::from_mmap`.
-        let up_bytes = up_width.padded_body_bytes(n_up);
-        let down_bytes = down_width.padded_body_bytes(n_down);
+        // Quantized bodies pad their u16 stream the same way.
+        let up_bytes = header.up_body_bytes();
+        let down_bytes = header.down_body_bytes();
         let body_no_middle = up_bytes
             .checked_add(down_bytes)
             .ok_or_else(|| anyhow::anyhow!("cch.weights body size overflow"))?;
@@ -484,8 +600,20 @@ impl CchWeightsFile {
         let up_off = byte_offset + HEADER_LEN;
         let down_off = up_off + up_bytes;
 
-        let up = decode_weight_array_mmap(&mmap, up_off, n_up, up_width)?;
-        let down = decode_weight_array_mmap(&mmap, down_off, n_down, down_width)?;
+        let decode =
+            |off: usize, n: usize, width: WeightWidth, quantized: bool| -> Result<WeightArray> {
+                if quantized {
+                    Ok(WeightArray::Q16(ArcCow::<u16>::from_mmap(
+                        Arc::clone(&mmap),
+                        off,
+                        q16_stream_len(n),
+                    )?))
+                } else {
+                    decode_weight_array_mmap(&mmap, off, n, width)
+                }
+            };
+        let up = decode(up_off, n_up, up_width, up_quantized)?;
+        let down = decode(down_off, n_down, down_width, down_quantized)?;
 
         let (up_middle, down_middle) = if has_middles {
             let upm_off = down_off + down_bytes;
@@ -519,21 +647,23 @@ impl CchWeightsFile {
             "cch.weights section must start 4-byte aligned (got addr 0x{:x})",
             bytes.as_ptr() as usize
         );
-        let header = &bytes[..HEADER_LEN];
+        let header = parse_header(&bytes[..HEADER_LEN])?;
         let CchWeightsHeader {
             n_up,
             n_down,
             up_width,
             down_width,
-        } = parse_header(header)?;
+            up_quantized,
+            down_quantized,
+        } = header;
 
         // v4 layout (with optional middles, per-direction body width):
         //   header(32) | up(width_up·n_up [+0-3 pad]) | down(width_down·n_down [+0-3 pad])
         //              | [up_middle(4·n_up) | down_middle(4·n_down)]
         //              | footer(16)
-        // u16/u24 bodies pad up to 4-byte boundary — see `padded_body_bytes`.
-        let up_bytes = up_width.padded_body_bytes(n_up);
-        let down_bytes = down_width.padded_body_bytes(n_down);
+        // u16/u24/Q16 bodies pad up to 4-byte boundary — see `padded_body_bytes`.
+        let up_bytes = header.up_body_bytes();
+        let down_bytes = header.down_body_bytes();
         let no_middle_len = HEADER_LEN + up_bytes + down_bytes + FOOTER_LEN;
         let with_middle_len = no_middle_len + 4 * (n_up + n_down);
         let has_middles = match bytes.len() {
@@ -553,16 +683,18 @@ impl CchWeightsFile {
         let down_end = down_off + down_bytes;
 
         // Read only the actual data bytes (skip any 0-2 byte tail pad).
-        let up_data = up_width.bytes_per_entry() * n_up;
-        let down_data = down_width.bytes_per_entry() * n_down;
+        let up_data = body_data_bytes(up_width, up_quantized, n_up);
+        let down_data = body_data_bytes(down_width, down_quantized, n_down);
         let up_body = &bytes[up_off..up_off + up_data];
         let down_body = &bytes[down_off..down_off + down_data];
         let up_vec: Vec<u32> = match up_width {
+            _ if up_quantized => decode_q16_to_u32_vec(up_body),
             WeightWidth::U16 => decode_u16_to_u32_vec(up_body),
             WeightWidth::U24 => decode_u24_to_u32_vec(up_body),
             WeightWidth::U32 => bytemuck::cast_slice::<u8, u32>(up_body).to_vec(),
         };
         let down_vec: Vec<u32> = match down_width {
+            _ if down_quantized => decode_q16_to_u32_vec(down_body),
             WeightWidth::U16 => decode_u16_to_u32_vec(down_body),
             WeightWidth::U24 => decode_u24_to_u32_vec(down_body),
             WeightWidth::U32 => bytemuck::cast_slice::<u8, u32>(down_body).to_vec(),
@@ -613,43 +745,32 @@ impl CchWeightsFile {
         let mut header = [0u8; HEADER_LEN];
         reader.read_exact(&mut header)?;
         crc_digest.update(&header);
+        let header = parse_header(&header)?;
         let CchWeightsHeader {
             n_up,
             n_down,
             up_width,
             down_width,
-        } = parse_header(&header)?;
+            up_quantized,
+            down_quantized,
+        } = header;
 
         // Read up weights body (padded to next 4-byte boundary).
-        let up_padded_count = up_width.padded_body_bytes(n_up);
-        let up_data_count = up_width.bytes_per_entry() * n_up;
+        let up_padded_count = header.up_body_bytes();
+        let up_data_count = body_data_bytes(up_width, up_quantized, n_up);
         let mut up_body = vec![0u8; up_padded_count];
         reader.read_exact(&mut up_body)?;
         crc_digest.update(&up_body);
         // Decode only the actual data bytes (skip the 0-2 byte tail pad).
-        let up_vec: Vec<u32> = match up_width {
-            WeightWidth::U16 => decode_u16_to_u32_vec(&up_body[..up_data_count]),
-            WeightWidth::U24 => decode_u24_to_u32_vec(&up_body[..up_data_count]),
-            WeightWidth::U32 => up_body[..up_data_count]
-                .chunks_exact(4)
-                .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
-                .collect(),
-        };
+        let up = decode_owned_body(&up_body[..up_data_count], up_width, up_quantized);
 
         // Read down weights body (padded).
-        let down_padded_count = down_width.padded_body_bytes(n_down);
-        let down_data_count = down_width.bytes_per_entry() * n_down;
+        let down_padded_count = header.down_body_bytes();
+        let down_data_count = body_data_bytes(down_width, down_quantized, n_down);
         let mut down_body = vec![0u8; down_padded_count];
         reader.read_exact(&mut down_body)?;
         crc_digest.update(&down_body);
-        let down_vec: Vec<u32> = match down_width {
-            WeightWidth::U16 => decode_u16_to_u32_vec(&down_body[..down_data_count]),
-            WeightWidth::U24 => decode_u24_to_u32_vec(&down_body[..down_data_count]),
-            WeightWidth::U32 => down_body[..down_data_count]
-                .chunks_exact(4)
-                .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
-                .collect(),
-        };
+        let down = decode_owned_body(&down_body[..down_data_count], down_width, down_quantized);
 
         let no_middle_len = HEADER_LEN + up_padded_count + down_padded_count + FOOTER_LEN;
         let with_middle_len = no_middle_len + 4 * (n_up + n_down);
@@ -693,14 +814,46 @@ impl CchWeightsFile {
         );
 
         Ok(CchWeights {
-            up: WeightArray::from_vec_u32(up_vec),
-            down: WeightArray::from_vec_u32(down_vec),
+            up,
+            down,
             up_middle: ArcCow::from_vec(up_middle),
             down_middle: ArcCow::from_vec(down_middle),
         })
     }
 }
 
+/// Decode one direction's body for the owned reader. Lossless widths
+/// widen to `u32`; a quantized body stays a compact [`WeightArray::Q16`]
+/// since widening it would give back the memory it was chosen to save.
+fn decode_owned_body(body: &[u8], width: WeightWidth, quantized: bool) -> WeightArray {
+    if quantized {
+        return WeightArray::Q16(ArcCow::from_vec(
+            body.chunks_exact(2)
+                .map(|c| u16::from_le_bytes(c.try_into().unwrap()))
+                .collect(),
+        ));
+    }
+    WeightArray::from_vec_u32(match width {
+        WeightWidth::U16 => decode_u16_to_u32_vec(body),
+        WeightWidth::U24 => decode_u24_to_u32_vec(body),
+        WeightWidth::U32 => body
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect(),
+    })
+}
+
+/// Decode a Q16 stream body (see [`quantize_q16`]) into a `Vec<u32>`.
+fn decode_q16_to_u32_vec(bytes: &[u8]) -> Vec<u32> {
+    let stream: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    (0..q16_entries(stream.len()))
+        .map(|i| q16_get(&stream, i))
+        .collect()
+}
+
 /// Decode a u16-compact body into a `Vec<u32>`, mapping the
 /// `u16::MAX` sentinel back to `u32::MAX`.
 ///
@@ -779,6 +932,38 @@ pub(crate) struct CchWeightsHeader {
     pub n_down: usize,
     pub up_width: WeightWidth,
     pub down_width: WeightWidth,
+    pub up_quantized: bool,
+    pub down_quantized: bool,
+}
+
+impl CchWeightsHeader {
+    /// Padded on-disk size of the up body.
+    fn up_body_bytes(&self) -> usize {
+        body_bytes(self.up_width, self.up_quantized, self.n_up)
+    }
+
+    /// Padded on-disk size of the down body.
+    fn down_body_bytes(&self) -> usize {
+        body_bytes(self.down_width, self.down_quantized, self.n_down)
+    }
+}
+
+/// Padded on-disk size of one direction's body.
+fn body_bytes(width: WeightWidth, quantized: bool, n: usize) -> usize {
+    if quantized {
+        q16_padded_body_bytes(n)
+    } else {
+        width.padded_body_bytes(n)
+    }
+}
+
+/// Unpadded data bytes of one direction's body.
+fn body_data_bytes(width: WeightWidth, quantized: bool, n: usize) -> usize {
+    if quantized {
+        2 * q16_stream_len(n)
+    } else {
+        width.bytes_per_entry() * n
+    }
 }
 
 /// Parse the 32-byte CCH weights header.
@@ -787,7 +972,9 @@ pub(crate) struct CchWeightsHeader {
 /// code:
 ///   bits 0..2: up_width   { 00=u32, 01=u16, 10=u24, 11=reserved }
 ///   bits 2..4: down_width { same encoding }
-///   bits 4..8: reserved
+///   bit  4   : up body quantized (Q16, width code must be u16)
+///   bit  5   : down body quantized (same)
+///   bits 6..8: reserved
 ///
 /// Anything other than v4 is rejected — re-run step 8 to regenerate.
 /// Shared by the owned, zero-copy, and mmap-backed readers.
@@ -812,6 +999,11 @@ fn parse_header(header: &[u8]) -> Result<CchWeightsHeader> {
         VERSION,
     );
     let flags = header[7];
+    anyhow::ensure!(
+        flags & !KNOWN_FLAG_BITS == 0,
+        "cch.weights: unknown flag bits in header byte 7 = 0x{:02X}",
+        flags
+    );
     let up_code = flags & UP_WIDTH_MASK;
     let down_code = (flags & DOWN_WIDTH_MASK) >> DOWN_WIDTH_SHIFT;
     let decode_width = |c: u8, dir: &str| -> Result<WeightWidth> {
@@ -829,6 +1021,14 @@ fn parse_header(header: &[u8]) -> Result<CchWeightsHeader> {
     };
     let up_width = decode_width(up_code, "up")?;
     let down_width = decode_width(down_code, "down")?;
+    let up_quantized = flags & UP_QUANTIZED_BIT != 0;
+    let down_quantized = flags & DOWN_QUANTIZED_BIT != 0;
+    anyhow::ensure!(
+        (!up_quantized || up_width == WeightWidth::U16)
+            && (!down_quantized || down_width == WeightWidth::U16),
+        "cch.weights: quantized body must carry the u16 width code (header byte 7 = 0x{:02X})",
+        flags
+    );
     let n_up = u64::from_le_bytes(header[8..16].try_into().unwrap()) as usize;
     let n_down = u64::from_le_bytes(header[16..24].try_into().unwrap()) as usize;
     Ok(CchWeightsHeader {
@@ -836,6 +1036,8 @@ fn parse_header(header: &[u8]) -> Result<CchWeightsHeader> {
         n_down,
         up_width,
         down_width,
+        up_quantized,
+        down_quantized,
     })
 }
 
@@ -945,6 +1147,49 @@ mod tests {
         assert_eq!(vals, vec![1, 2, u32::MAX, 65_534]);
     }
 
+    #[test]
+    fn q16_is_exact_when_values_fit_u16() {
+        let w = vec![0u32, 1, 65_534, u32::MAX, 1234];
+        let arr = WeightArray::quantize(&w);
+        assert!(arr.is_quantized());
+        assert_eq!(arr.len(), w.len());
+        assert_eq!(arr.to_vec_u32(), w);
+    }
+
+    #[test]
+    fn q16_error_is_bounded_by_half_the_block_scale() {
+        // 600 entries = two full blocks plus a partial one.
+        let w: Vec<u32> = (0..600u32)
+            .map(|i| match i % 7 {
+                0 => u32::MAX,
+                1 => 1,
+                _ => i * 4_999 + 70_000,
+            })
+            .collect();
+        let arr = WeightArray::quantize(&w);
+        assert_eq!(arr.len(), 600);
+        assert_eq!(q16_padded_body_bytes(600), (2 * (600 + 2 * 3) + 3) & !3);
+        for (block_idx, block) in w.chunks(Q16_BLOCK).enumerate() {
+            let max = block.iter().filter(|&&v| v != u32::MAX).max().unwrap();
+            let scale = max.div_ceil(65_534);
+            for (j, &orig) in block.iter().enumerate() {
+                let got = arr.get(block_idx * Q16_BLOCK + j);
+                if orig == u32::MAX {
+                    assert_eq!(got, u32::MAX);
+                } else {
+                    assert!(got > 0, "non-zero weight rounded to 0");
+                    assert!(
+                        got.abs_diff(orig) <= scale / 2 || (orig < scale && got == scale),
+                        "{orig} -> {got} (scale {scale})"
+                    );
+                }
+            }
+        }
+        // Largest finite weight stays finite and never aliases the sentinel.
+        let arr = WeightArray::quantize(&[u32::MAX - 1, 3]);
+        assert!(arr.get(0) < u32::MAX && arr.get(0) >= u32::MAX - 65_538);
+    }
+
     #[test]
     fn weight_array_to_mut_widens_u16_to_u32() {
         let mut arr = WeightArray::U16(ArcCow::from_vec(vec![1u16, u16::MAX, 100]));
@@ -13,13 +13,13 @@
 //! cast each slice with `bytemuck::cast_slice`.
 
 use anyhow::Result;
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{Read, Write};
 use std::path::Path;
 use std::sync::Arc;
 
 use super::crc;
 use super::mmap::ArcCow;
+use super::zstd_compress::{create_artifact, open_artifact};
 
 const MAGIC: u32 = 0x45424743; // "EBGC"
 const VERSION: u16 = 1;
@@ -48,7 +48,7 @@ pub struct EbgCsrFile;
 impl EbgCsrFile {
     /// Write EBG CSR to file
     pub fn write<P: AsRef<Path>>(path: P, data: &EbgCsr) -> Result<()> {
-        let mut writer = BufWriter::new(File::create(path)?);
+        let mut writer = create_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         // Header (64 bytes)
@@ -104,14 +104,14 @@ impl EbgCsrFile {
         let file_crc = body_crc;
         writer.write_all(&body_crc.to_le_bytes())?;
         writer.write_all(&file_crc.to_le_bytes())?;
-        writer.flush()?;
+        writer.finish()?;
 
         Ok(())
     }
 
     /// Read EBG CSR from file
     pub fn read<P: AsRef<Path>>(path: P) -> Result<EbgCsr> {
-        Self::read_from_reader(BufReader::new(File::open(path)?))
+        Self::read_from_reader(open_artifact(path.as_ref())?)
     }
 
     pub fn read_from_bytes(bytes: &[u8]) -> Result<EbgCsr> {
@@ -11,13 +11,13 @@
 //! mmap with no heap copy.
 
 use anyhow::Result;
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{Read, Write};
 use std::path::Path;
 use std::sync::Arc;
 
 use super::crc;
 use super::mmap::ArcCow;
+use super::zstd_compress::{create_artifact, open_artifact};
 
 const MAGIC: u32 = 0x4542474E; // "EBGN"
 /// Current on-disk version. v2 stores `length_m` (meters) instead of v1's
@@ -41,6 +41,16 @@ pub struct EbgNode {
     pub primary_way: u32, // lower 32 bits of first_osm_way_id
 }
 
+impl EbgNode {
+    /// Whether EBG node `ebg_id` runs u→v along its NBG edge. Step 4 emits
+    /// edge `g` as `2g` (u→v) and `2g + 1` (v→u); on a self-loop tail and
+    /// head coincide, so the id is the only way to tell the two apart.
+    #[inline]
+    pub fn is_forward(ebg_id: u32) -> bool {
+        ebg_id.is_multiple_of(2)
+    }
+}
+
 const _: () = assert!(std::mem::size_of::<EbgNode>() == NODE_RECORD_LEN);
 const _: () = assert!(std::mem::align_of::<EbgNode>() == 4);
 
@@ -61,7 +71,7 @@ pub struct EbgNodesFile;
 impl EbgNodesFile {
     /// Write EBG nodes to file
     pub fn write<P: AsRef<Path>>(path: P, data: &EbgNodes) -> Result<()> {
-        let mut writer = BufWriter::new(File::create(path)?);
+        let mut writer = create_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         // Header (64 bytes)
@@ -117,14 +127,14 @@ impl EbgNodesFile {
         let file_crc = body_crc;
         writer.write_all(&body_crc.to_le_bytes())?;
         writer.write_all(&file_crc.to_le_bytes())?;
-        writer.flush()?;
+        writer.finish()?;
 
         Ok(())
     }
 
     /// Read EBG nodes from file
     pub fn read<P: AsRef<Path>>(path: P) -> Result<EbgNodes> {
-        Self::read_from_reader(BufReader::new(File::open(path)?))
+        Self::read_from_reader(open_artifact(path.as_ref())?)
     }
 
     pub fn read_from_bytes(bytes: &[u8]) -> Result<EbgNodes> {
@@ -1,11 +1,11 @@
 //! ebg.turn_table format - Deduplicated turn table with mode masks
 
 use anyhow::Result;
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{Read, Write};
 use std::path::Path;
 
 use super::crc;
+use super::zstd_compress::{create_artifact, open_artifact};
 use crate::profile_abi::MAX_MODES;
 
 const MAGIC: u32 = 0x45424754; // "EBGT"
@@ -57,7 +57,7 @@ const RECORD_SIZE: usize = 4 + 4 * MAX_MODES + 4; // = 40 bytes
 impl TurnTableFile {
     /// Write turn table to file (v2 format with dynamic penalty arrays)
     pub fn write<P: AsRef<Path>>(path: P, data: &TurnTable) -> Result<()> {
-        let mut writer = BufWriter::new(File::create(path)?);
+        let mut writer = create_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         // Header (44 bytes): magic(4) + version(2) + reserved(2) + n_entries(4) + inputs_sha(32)
@@ -104,14 +104,14 @@ impl TurnTableFile {
         let body_crc = crc_digest.finalize();
         writer.write_all(&body_crc.to_le_bytes())?;
         writer.write_all(&body_crc.to_le_bytes())?;
-        writer.flush()?;
+        writer.finish()?;
 
         Ok(())
     }
 
     /// Read turn table from file (v2 format with dynamic penalty arrays)
     pub fn read<P: AsRef<Path>>(path: P) -> Result<TurnTable> {
-        let mut reader = BufReader::new(File::open(path)?);
+        let mut reader = open_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         let mut header = vec![0u8; 44]; // magic(4) + version(2) + reserved(2) + n_entries(4) + inputs_sha(32)
@@ -6,6 +6,7 @@ pub mod butterfly_dat;
 pub mod crc;
 pub mod lazy_verify;
 pub mod mmap;
+pub mod node_barriers;
 pub mod node_signals;
 pub mod nodes_sa;
 pub mod nodes_si;
@@ -15,9 +16,11 @@ pub mod ways;
 // Step 2 formats
 pub mod turn_rules;
 pub mod way_attrs;
+pub mod way_conditionals;
 
 // Step 3 formats
 pub mod nbg_csr;
+pub mod nbg_elev;
 pub mod nbg_geo;
 pub mod nbg_node_map;
 
@@ -58,6 +61,7 @@ pub mod cch_topo;
 
 // Step 8 formats
 pub mod cch_weights;
+pub mod weight_delta;
 
 // Transparent zstd compression for cold container sections (#347)
 pub mod zstd_compress;
@@ -78,8 +82,13 @@ pub use mod_turns::ModTurns;
 pub use mod_weights::ModWeights;
 pub use mode_index::{ModeIndex, ModeIndexFile, ModeIndexKind};
 pub use nbg_csr::{NbgCsr, NbgCsrFile};
-pub use nbg_geo::{NbgEdge, NbgGeo, NbgGeoFile, PolyLine};
+pub use nbg_elev::{EdgeElevation, NbgElev, NbgElevFile};
+pub use nbg_geo::{
+    EDGE_FLAG_BRIDGE, EDGE_FLAG_FERRY, EDGE_FLAG_FORD, EDGE_FLAG_ROUNDABOUT, EDGE_FLAG_SELF_LOOP,
+    EDGE_FLAG_TUNNEL, NbgEdge, NbgGeo, NbgGeoFile, PolyLine,
+};
 pub use nbg_node_map::{NbgNodeMap, NbgNodeMapFile, NodeMapping};
+pub use node_barriers::{BarrierKind, NodeBarriers, NodeBarriersFile};
 pub use node_signals::{NodeSignals, NodeSignalsFile};
 pub use order_ebg::{OrderEbg, OrderEbgFile};
 pub use region_tiles::{
@@ -92,4 +101,6 @@ pub use snap_index::{
 };
 pub use turn_rules::TurnRule;
 pub use way_attrs::WayAttr;
-pub use ways::{Way, WaysFile};
+pub use way_conditionals::WayConditional;
+pub use ways::{ElementMeta, Way, WaysFile, WaysSummary};
+pub use weight_delta::WeightDelta;
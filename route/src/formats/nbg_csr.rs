@@ -1,11 +1,11 @@
 //! nbg.csr format - Compact CSR graph for undirected NBG topology
 
 use anyhow::Result;
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::Write;
 use std::path::Path;
 
 use super::crc;
+use super::zstd_compress::{create_artifact, open_artifact};
 
 const MAGIC: u32 = 0x4E424743; // "NBGC"
 const VERSION: u16 = 1;
@@ -26,7 +26,7 @@ pub struct NbgCsrFile;
 impl NbgCsrFile {
     /// Write NBG CSR to file
     pub fn write<P: AsRef<Path>>(path: P, csr: &NbgCsr) -> Result<()> {
-        let mut writer = BufWriter::new(File::create(path)?);
+        let mut writer = create_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         // Header
@@ -83,16 +83,16 @@ impl NbgCsrFile {
         let file_crc = body_crc; // Simple approach for now
         writer.write_all(&body_crc.to_le_bytes())?;
         writer.write_all(&file_crc.to_le_bytes())?;
-        writer.flush()?;
+        writer.finish()?;
 
         Ok(())
     }
 
     /// Read NBG CSR from file
     pub fn read<P: AsRef<Path>>(path: P) -> Result<NbgCsr> {
-        use std::io::{BufReader, Read};
+        use std::io::Read;
 
-        let mut reader = BufReader::new(std::fs::File::open(path)?);
+        let mut reader = open_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         let mut header = vec![0u8; 64];
@@ -0,0 +1,250 @@
+//! nbg.elev format - Per-edge elevation sampled onto NBG geometry
+//!
+//! Format: nbg.elev (little-endian)
+//!
+//! Header (64 bytes):
+//!   magic:     u32 = 0x4E424745  // "NBGE"
+//!   version:   u16 = 1
+//!   flags:     u16               // bit0: per-point z present
+//!   n_edges:   u64               // == nbg.geo n_edges_und
+//!   n_points:  u64               // z values; 0 without bit0
+//!   reserved:  [40]u8
+//!
+//! Body:
+//!   n_edges records, in nbg.geo edge order:
+//!     ascent_dm:  u32   // climb along u → v, decimetres
+//!     descent_dm: u32   // drop along u → v, decimetres
+//!   n_points z values (bit0 only), parallel to the nbg.geo polylines:
+//!     z_m: i16          // metres above sea level, NO_Z if void
+//!
+//! Footer (16 bytes):
+//!   body_crc64: u64
+//!   file_crc64: u64
+//!
+//! Travelling v → u swaps ascent and descent. Edges without elevation
+//! coverage store `NO_ELEVATION` in both fields.
+
+use anyhow::{Context, Result, bail};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use super::crc::Digest;
+use super::nbg_geo::NbgEdge;
+use super::zstd_compress::{create_artifact, open_artifact};
+
+const MAGIC: u32 = 0x4E424745; // "NBGE"
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 64;
+const FLAG_POINTS: u16 = 1 << 0;
+
+/// Ascent/descent of an edge without elevation coverage.
+pub const NO_ELEVATION: u32 = u32::MAX;
+/// Per-point z where the DEM has a void.
+pub const NO_Z: i16 = i16::MIN;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeElevation {
+    pub ascent_dm: u32,
+    pub descent_dm: u32,
+}
+
+impl EdgeElevation {
+    pub const NONE: Self = Self {
+        ascent_dm: NO_ELEVATION,
+        descent_dm: NO_ELEVATION,
+    };
+
+    pub fn has_data(&self) -> bool {
+        self.ascent_dm != NO_ELEVATION
+    }
+
+    /// `(ascent_dm, descent_dm)` travelling the edge in the given direction.
+    pub fn directed(&self, forward: bool) -> (u32, u32) {
+        if forward {
+            (self.ascent_dm, self.descent_dm)
+        } else {
+            (self.descent_dm, self.ascent_dm)
+        }
+    }
+}
+
+pub struct NbgElev {
+    /// One entry per nbg.geo edge
+    pub edges: Vec<EdgeElevation>,
+    /// Per-polyline-point z, laid out like the nbg.geo polyline blob
+    pub z: Option<Vec<i16>>,
+}
+
+impl NbgElev {
+    /// Per-point z of an nbg.geo edge's polyline, if points were stored.
+    pub fn edge_z(&self, edge: &NbgEdge) -> Option<&[i16]> {
+        // poly_off is a byte offset into the polyline blob (8 bytes per point).
+        let start = (edge.poly_off / 8) as usize;
+        self.z
+            .as_ref()?
+            .get(start..start + edge.n_poly_pts as usize)
+    }
+}
+
+pub struct NbgElevFile;
+
+impl NbgElevFile {
+    /// Write NBG elevation to file
+    pub fn write<P: AsRef<Path>>(path: P, elev: &NbgElev) -> Result<()> {
+        let mut writer = create_artifact(path.as_ref())
+            .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
+
+        let flags = if elev.z.is_some() { FLAG_POINTS } else { 0 };
+        let n_points = elev.z.as_ref().map_or(0, |z| z.len() as u64);
+
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(&MAGIC.to_le_bytes());
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&flags.to_le_bytes());
+        header.extend_from_slice(&(elev.edges.len() as u64).to_le_bytes());
+        header.extend_from_slice(&n_points.to_le_bytes());
+        header.resize(HEADER_SIZE, 0);
+        writer.write_all(&header)?;
+
+        let mut body_digest = Digest::new();
+        let mut file_digest = Digest::new();
+        file_digest.update(&header);
+        let mut body = Vec::with_capacity(elev.edges.len() * 8 + n_points as usize * 2);
+        for edge in &elev.edges {
+            body.extend_from_slice(&edge.ascent_dm.to_le_bytes());
+            body.extend_from_slice(&edge.descent_dm.to_le_bytes());
+        }
+        for &z in elev.z.iter().flatten() {
+            body.extend_from_slice(&z.to_le_bytes());
+        }
+        body_digest.update(&body);
+        file_digest.update(&body);
+        writer.write_all(&body)?;
+
+        writer.write_all(&body_digest.finalize().to_le_bytes())?;
+        writer.write_all(&file_digest.finalize().to_le_bytes())?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Read NBG elevation from file
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<NbgElev> {
+        let mut reader = open_artifact(path.as_ref())
+            .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
+
+        let mut header = [0u8; HEADER_SIZE];
+        reader
+            .read_exact(&mut header)
+            .context("Failed to read header")?;
+
+        let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let version = u16::from_le_bytes(header[4..6].try_into().unwrap());
+        if magic != MAGIC {
+            bail!(
+                "Invalid magic: expected 0x{:08X}, got 0x{:08X}",
+                MAGIC,
+                magic
+            );
+        }
+        if version != VERSION {
+            bail!("Unsupported version: expected {}, got {}", VERSION, version);
+        }
+        let flags = u16::from_le_bytes(header[6..8].try_into().unwrap());
+        let n_edges = u64::from_le_bytes(header[8..16].try_into().unwrap()) as usize;
+        let n_points = u64::from_le_bytes(header[16..24].try_into().unwrap()) as usize;
+
+        let mut body_digest = Digest::new();
+        let mut records = vec![0u8; n_edges * 8];
+        reader
+            .read_exact(&mut records)
+            .context("Failed to read edge records")?;
+        body_digest.update(&records);
+        let edges = records
+            .chunks_exact(8)
+            .map(|r| EdgeElevation {
+                ascent_dm: u32::from_le_bytes(r[0..4].try_into().unwrap()),
+                descent_dm: u32::from_le_bytes(r[4..8].try_into().unwrap()),
+            })
+            .collect();
+
+        let z = if flags & FLAG_POINTS != 0 {
+            let mut bytes = vec![0u8; n_points * 2];
+            reader
+                .read_exact(&mut bytes)
+                .context("Failed to read point elevations")?;
+            body_digest.update(&bytes);
+            Some(
+                bytes
+                    .chunks_exact(2)
+                    .map(|b| i16::from_le_bytes([b[0], b[1]]))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let mut footer = [0u8; 16];
+        reader
+            .read_exact(&mut footer)
+            .context("Failed to read footer")?;
+        let expected_body_crc = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+        let actual_body_crc = body_digest.finalize();
+        if expected_body_crc != actual_body_crc {
+            bail!(
+                "Body CRC mismatch: expected 0x{:016X}, got 0x{:016X}",
+                expected_body_crc,
+                actual_body_crc
+            );
+        }
+
+        Ok(NbgElev { edges, z })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let edges = vec![
+            EdgeElevation {
+                ascent_dm: 120,
+                descent_dm: 35,
+            },
+            EdgeElevation::NONE,
+        ];
+        let tmp = NamedTempFile::new()?;
+
+        let elev = NbgElev {
+            edges: edges.clone(),
+            z: Some(vec![10, 22, NO_Z, 7]),
+        };
+        NbgElevFile::write(tmp.path(), &elev)?;
+        let loaded = NbgElevFile::read(tmp.path())?;
+        assert_eq!(loaded.edges, edges);
+        assert_eq!(loaded.z, elev.z);
+        assert!(!loaded.edges[1].has_data());
+        assert_eq!(loaded.edges[0].directed(false), (35, 120));
+
+        let edge = NbgEdge {
+            u_node: 0,
+            v_node: 1,
+            length_mm: 0,
+            bearing_deci_deg: 0,
+            n_poly_pts: 2,
+            poly_off: 16,
+            first_osm_way_id: 0,
+            flags: 0,
+        };
+        assert_eq!(loaded.edge_z(&edge), Some(&[NO_Z, 7][..]));
+
+        let flat = NbgElev { edges, z: None };
+        NbgElevFile::write(tmp.path(), &flat)?;
+        let loaded = NbgElevFile::read(tmp.path())?;
+        assert!(loaded.z.is_none());
+        assert_eq!(loaded.edge_z(&edge), None);
+        Ok(())
+    }
+}
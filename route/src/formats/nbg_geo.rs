@@ -1,15 +1,34 @@
 //! nbg.geo format - Edge geometry and metrics for NBG
 
 use anyhow::Result;
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::Write;
 use std::path::Path;
 
 use super::crc;
+use super::zstd_compress::{create_artifact, open_artifact};
 
 const MAGIC: u32 = 0x4E424747; // "NBGG"
 const VERSION: u16 = 1;
 
+/// [`NbgEdge::flags`] bits Step 3 derives from way tags. They are copied
+/// into `EbgNode::class_bits` for both directions of the edge.
+/// `route=ferry`
+pub const EDGE_FLAG_FERRY: u32 = 1 << 0;
+/// `bridge=*` other than `no`
+pub const EDGE_FLAG_BRIDGE: u32 = 1 << 1;
+/// `tunnel=*` other than `no`
+pub const EDGE_FLAG_TUNNEL: u32 = 1 << 2;
+/// `junction=roundabout` or `junction=circular`
+pub const EDGE_FLAG_ROUNDABOUT: u32 = 1 << 3;
+/// `ford=yes`
+pub const EDGE_FLAG_FORD: u32 = 1 << 4;
+
+/// [`NbgEdge::flags`] bit set on self-loops (`u_node == v_node`), e.g. a
+/// closed way whose only decision node is where it starts and ends. Both
+/// directions of such an edge have the same tail and head, so consumers
+/// must tell them apart by EBG id, not by endpoints.
+pub const EDGE_FLAG_SELF_LOOP: u32 = 1 << 6;
+
 #[derive(Debug, Clone)]
 pub struct NbgEdge {
     pub u_node: u32,
@@ -19,7 +38,7 @@ pub struct NbgEdge {
     pub n_poly_pts: u16,
     pub poly_off: u64,
     pub first_osm_way_id: i64,
-    pub flags: u32, // bit0=ferry, bit1=bridge, bit2=tunnel, bit3=roundabout, bit4=ford, bit5=layer_boundary
+    pub flags: u32, // bit0=ferry, bit1=bridge, bit2=tunnel, bit3=roundabout, bit4=ford, bit5=layer_boundary, bit6=self_loop
 }
 
 #[derive(Debug, Clone)]
@@ -39,7 +58,7 @@ pub struct NbgGeoFile;
 impl NbgGeoFile {
     /// Write NBG geo to file
     pub fn write<P: AsRef<Path>>(path: P, geo: &NbgGeo) -> Result<()> {
-        let mut writer = BufWriter::new(File::create(path)?);
+        let mut writer = create_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         // Calculate poly_bytes
@@ -119,15 +138,14 @@ impl NbgGeoFile {
         let file_crc = body_crc;
         writer.write_all(&body_crc.to_le_bytes())?;
         writer.write_all(&file_crc.to_le_bytes())?;
-        writer.flush()?;
+        writer.finish()?;
 
         Ok(())
     }
 
     /// Read NBG geo from file
     pub fn read<P: AsRef<Path>>(path: P) -> Result<NbgGeo> {
-        use std::io::BufReader;
-        Self::read_from_reader(BufReader::new(std::fs::File::open(path)?))
+        Self::read_from_reader(open_artifact(path.as_ref())?)
     }
 
     pub fn read_from_bytes(bytes: &[u8]) -> Result<NbgGeo> {
@@ -2,11 +2,11 @@
 
 use anyhow::Result;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufWriter, Read, Write};
+use std::io::{Read, Write};
 use std::path::Path;
 
 use super::crc;
+use super::zstd_compress::{create_artifact, open_artifact};
 
 const MAGIC: u32 = 0x4E42474D; // "NBGM"
 const VERSION: u16 = 1;
@@ -26,7 +26,7 @@ pub struct NbgNodeMapFile;
 impl NbgNodeMapFile {
     /// Write node map to file
     pub fn write<P: AsRef<Path>>(path: P, node_map: &NbgNodeMap) -> Result<()> {
-        let mut writer = BufWriter::new(File::create(path)?);
+        let mut writer = create_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         // Header
@@ -63,14 +63,14 @@ impl NbgNodeMapFile {
         let file_crc = body_crc;
         writer.write_all(&body_crc.to_le_bytes())?;
         writer.write_all(&file_crc.to_le_bytes())?;
-        writer.flush()?;
+        writer.finish()?;
 
         Ok(())
     }
 
     /// Read node map from file and build lookup HashMap
     pub fn read<P: AsRef<Path>>(path: P) -> Result<HashMap<i64, u32>> {
-        let mut file = File::open(path)?;
+        let mut file = open_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         let mut header = [0u8; 16];
@@ -110,7 +110,7 @@ impl NbgNodeMapFile {
 
     /// Read node map from file as NbgNodeMap struct
     pub fn read_map<P: AsRef<Path>>(path: P) -> Result<NbgNodeMap> {
-        Self::read_map_from_reader(File::open(path)?)
+        Self::read_map_from_reader(open_artifact(path.as_ref())?)
     }
 
     /// Read node map from an in-memory byte slice (mmap-backed bundle).
@@ -0,0 +1,317 @@
+//! OSM nodes tagged `barrier=*`, with their barrier kind
+//!
+//! Format: node_barriers.bin (little-endian)
+//!
+//! Header (64 bytes):
+//!   magic:        u32 = 0x42415253  // "BARS"
+//!   version:      u16 = 1
+//!   reserved:     u16 = 0
+//!   count:        u64
+//!   created_unix: u64
+//!   input_sha256: [32]u8
+//!   reserved2:    [8]u8
+//!
+//! Body (count records, sorted strictly ascending by id):
+//!   osm_node_id: i64
+//!   kind:        u8   // BarrierKind
+//!
+//! Footer (16 bytes):
+//!   body_crc64: u64
+//!   file_crc64: u64
+//!
+//! Step 1 only records the kind; whether a mode may pass (and how long it
+//! waits) is decided per model in Step 4 (see `ModelSchema::barriers`).
+
+use anyhow::{Context, Result, bail};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use super::crc::Digest;
+use super::zstd_compress::{create_artifact, open_artifact};
+
+const MAGIC: u32 = 0x42415253; // "BARS"
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 64;
+const RECORD_SIZE: usize = 9;
+
+/// `barrier=*` values Step 1 keeps. Anything else on a node becomes
+/// [`BarrierKind::Other`]. Line barriers (walls, fences) are not kept: where
+/// one shares a node with a way it marks a passage through the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u8)]
+pub enum BarrierKind {
+    Other = 0,
+    Gate = 1,
+    LiftGate = 2,
+    SwingGate = 3,
+    SlidingGate = 4,
+    KissingGate = 5,
+    Bollard = 6,
+    Block = 7,
+    Chain = 8,
+    CycleBarrier = 9,
+    MotorcycleBarrier = 10,
+    Stile = 11,
+    Turnstile = 12,
+    FullHeightTurnstile = 13,
+    CattleGrid = 14,
+    TollBooth = 15,
+    BorderControl = 16,
+    BusTrap = 17,
+    HeightRestrictor = 18,
+    JerseyBarrier = 19,
+    Log = 20,
+    Entrance = 21,
+}
+
+impl BarrierKind {
+    pub const ALL: [BarrierKind; 22] = [
+        BarrierKind::Other,
+        BarrierKind::Gate,
+        BarrierKind::LiftGate,
+        BarrierKind::SwingGate,
+        BarrierKind::SlidingGate,
+        BarrierKind::KissingGate,
+        BarrierKind::Bollard,
+        BarrierKind::Block,
+        BarrierKind::Chain,
+        BarrierKind::CycleBarrier,
+        BarrierKind::MotorcycleBarrier,
+        BarrierKind::Stile,
+        BarrierKind::Turnstile,
+        BarrierKind::FullHeightTurnstile,
+        BarrierKind::CattleGrid,
+        BarrierKind::TollBooth,
+        BarrierKind::BorderControl,
+        BarrierKind::BusTrap,
+        BarrierKind::HeightRestrictor,
+        BarrierKind::JerseyBarrier,
+        BarrierKind::Log,
+        BarrierKind::Entrance,
+    ];
+
+    /// The `barrier=*` value (`other` for [`BarrierKind::Other`]).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BarrierKind::Other => "other",
+            BarrierKind::Gate => "gate",
+            BarrierKind::LiftGate => "lift_gate",
+            BarrierKind::SwingGate => "swing_gate",
+            BarrierKind::SlidingGate => "sliding_gate",
+            BarrierKind::KissingGate => "kissing_gate",
+            BarrierKind::Bollard => "bollard",
+            BarrierKind::Block => "block",
+            BarrierKind::Chain => "chain",
+            BarrierKind::CycleBarrier => "cycle_barrier",
+            BarrierKind::MotorcycleBarrier => "motorcycle_barrier",
+            BarrierKind::Stile => "stile",
+            BarrierKind::Turnstile => "turnstile",
+            BarrierKind::FullHeightTurnstile => "full-height_turnstile",
+            BarrierKind::CattleGrid => "cattle_grid",
+            BarrierKind::TollBooth => "toll_booth",
+            BarrierKind::BorderControl => "border_control",
+            BarrierKind::BusTrap => "bus_trap",
+            BarrierKind::HeightRestrictor => "height_restrictor",
+            BarrierKind::JerseyBarrier => "jersey_barrier",
+            BarrierKind::Log => "log",
+            BarrierKind::Entrance => "entrance",
+        }
+    }
+
+    /// Kind for a node's `barrier=*` value; `None` for values that do not
+    /// block a way (`no`, line barriers).
+    pub fn from_tag(value: &str) -> Option<Self> {
+        match value {
+            "no" | "wall" | "fence" | "hedge" | "retaining_wall" | "ditch" => None,
+            _ => Some(
+                Self::ALL
+                    .into_iter()
+                    .find(|k| k.as_str() == value)
+                    .unwrap_or(BarrierKind::Other),
+            ),
+        }
+    }
+
+    /// Kind named `name` in a model's `barriers` section (`other` included).
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.as_str() == name)
+    }
+
+    pub fn from_u8(v: u8) -> Option<Self> {
+        Self::ALL.get(v as usize).copied()
+    }
+}
+
+/// Barrier nodes - sorted by OSM node ID
+pub struct NodeBarriers {
+    /// `(osm_node_id, kind)`, sorted by id
+    pub nodes: Vec<(i64, BarrierKind)>,
+}
+
+impl NodeBarriers {
+    /// Create from unsorted `(id, kind)` pairs; the first kind seen for a
+    /// duplicated id wins.
+    pub fn new(mut nodes: Vec<(i64, BarrierKind)>) -> Self {
+        nodes.sort_by_key(|&(id, _)| id);
+        nodes.dedup_by_key(|&mut (id, _)| id);
+        Self { nodes }
+    }
+
+    /// Barrier kind at a node, if any (O(log n))
+    pub fn get(&self, osm_node_id: i64) -> Option<BarrierKind> {
+        self.nodes
+            .binary_search_by_key(&osm_node_id, |&(id, _)| id)
+            .ok()
+            .map(|i| self.nodes[i].1)
+    }
+
+    /// Number of barrier nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if empty
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// File reader/writer for NodeBarriers
+pub struct NodeBarriersFile;
+
+impl NodeBarriersFile {
+    /// Write barrier nodes to file
+    pub fn write<P: AsRef<Path>>(
+        path: P,
+        barriers: &NodeBarriers,
+        input_sha256: &[u8; 32],
+    ) -> Result<()> {
+        let mut writer = create_artifact(path.as_ref())
+            .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
+
+        // Deterministic for byte-reproducible builds (field never read).
+        let created_unix: u64 = 0;
+
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(&MAGIC.to_le_bytes());
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // reserved
+        header.extend_from_slice(&(barriers.nodes.len() as u64).to_le_bytes());
+        header.extend_from_slice(&created_unix.to_le_bytes());
+        header.extend_from_slice(input_sha256);
+        header.resize(HEADER_SIZE, 0);
+
+        writer.write_all(&header)?;
+
+        let mut body_digest = Digest::new();
+        let mut file_digest = Digest::new();
+        file_digest.update(&header);
+        for &(node_id, kind) in &barriers.nodes {
+            let mut record = [0u8; RECORD_SIZE];
+            record[0..8].copy_from_slice(&node_id.to_le_bytes());
+            record[8] = kind as u8;
+            body_digest.update(&record);
+            file_digest.update(&record);
+            writer.write_all(&record)?;
+        }
+
+        writer.write_all(&body_digest.finalize().to_le_bytes())?;
+        writer.write_all(&file_digest.finalize().to_le_bytes())?;
+
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Read barrier nodes from file
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<NodeBarriers> {
+        let mut reader = open_artifact(path.as_ref())
+            .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
+
+        let mut header = [0u8; HEADER_SIZE];
+        reader
+            .read_exact(&mut header)
+            .context("Failed to read header")?;
+
+        let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+        let version = u16::from_le_bytes(header[4..6].try_into().unwrap());
+        if magic != MAGIC {
+            bail!(
+                "Invalid magic: expected 0x{:08X}, got 0x{:08X}",
+                MAGIC,
+                magic
+            );
+        }
+        if version != VERSION {
+            bail!("Unsupported version: expected {}, got {}", VERSION, version);
+        }
+
+        let count = u64::from_le_bytes(header[8..16].try_into().unwrap()) as usize;
+
+        let mut nodes = Vec::with_capacity(count);
+        let mut body_digest = Digest::new();
+        for _ in 0..count {
+            let mut record = [0u8; RECORD_SIZE];
+            reader
+                .read_exact(&mut record)
+                .context("Failed to read barrier record")?;
+            body_digest.update(&record);
+            let node_id = i64::from_le_bytes(record[0..8].try_into().unwrap());
+            let kind = BarrierKind::from_u8(record[8])
+                .ok_or_else(|| anyhow::anyhow!("Invalid barrier kind: {}", record[8]))?;
+            nodes.push((node_id, kind));
+        }
+
+        let mut footer = [0u8; 16];
+        reader
+            .read_exact(&mut footer)
+            .context("Failed to read footer")?;
+
+        let expected_body_crc = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+        let actual_body_crc = body_digest.finalize();
+        if expected_body_crc != actual_body_crc {
+            bail!(
+                "Body CRC mismatch: expected 0x{:016X}, got 0x{:016X}",
+                expected_body_crc,
+                actual_body_crc
+            );
+        }
+
+        Ok(NodeBarriers { nodes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_kind_names() {
+        for kind in BarrierKind::ALL {
+            assert_eq!(BarrierKind::from_u8(kind as u8), Some(kind));
+            assert_eq!(BarrierKind::from_name(kind.as_str()), Some(kind));
+        }
+        assert_eq!(BarrierKind::from_tag("bollard"), Some(BarrierKind::Bollard));
+        assert_eq!(BarrierKind::from_tag("yes"), Some(BarrierKind::Other));
+        assert_eq!(BarrierKind::from_tag("fence"), None);
+        assert_eq!(BarrierKind::from_name("yes"), None);
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let barriers = NodeBarriers::new(vec![
+            (300, BarrierKind::Gate),
+            (100, BarrierKind::Bollard),
+            (300, BarrierKind::Gate),
+        ]);
+        assert_eq!(barriers.len(), 2);
+        assert_eq!(barriers.get(100), Some(BarrierKind::Bollard));
+        assert_eq!(barriers.get(200), None);
+
+        let tmp = NamedTempFile::new()?;
+        NodeBarriersFile::write(tmp.path(), &barriers, &[0u8; 32])?;
+        let loaded = NodeBarriersFile::read(tmp.path())?;
+        assert_eq!(loaded.nodes, barriers.nodes);
+        Ok(())
+    }
+}
@@ -21,11 +21,11 @@
 //! Lookup: O(log n) binary search to check if a node has a traffic signal
 
 use anyhow::{Context, Result, bail};
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{Read, Write};
 use std::path::Path;
 
 use super::crc::Digest;
+use super::zstd_compress::{create_artifact, open_artifact};
 
 const MAGIC: u32 = 0x53494753; // "SIGS"
 const VERSION: u16 = 1;
@@ -71,9 +71,8 @@ impl NodeSignalsFile {
         signals: &NodeSignals,
         input_sha256: &[u8; 32],
     ) -> Result<()> {
-        let file = File::create(path.as_ref())
+        let mut writer = create_artifact(path.as_ref())
             .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
-        let mut writer = BufWriter::new(file);
 
         // #419: deterministic for byte-reproducible builds (field never read).
         let created_unix: u64 = 0;
@@ -111,15 +110,14 @@ impl NodeSignalsFile {
         writer.write_all(&body_crc64.to_le_bytes())?;
         writer.write_all(&file_crc64.to_le_bytes())?;
 
-        writer.flush()?;
+        writer.finish()?;
         Ok(())
     }
 
     /// Read signal nodes from file
     pub fn read<P: AsRef<Path>>(path: P) -> Result<NodeSignals> {
-        let file = File::open(path.as_ref())
+        let mut reader = open_artifact(path.as_ref())
             .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
-        let mut reader = BufReader::new(file);
 
         // Read header
         let mut header = [0u8; HEADER_SIZE];
@@ -26,11 +26,12 @@
 //!   file_crc64: u64
 
 use anyhow::{Context, Result};
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{Read, Write};
 use std::path::Path;
 
 use super::crc::Digest;
+use super::mmap::{ArcCow, map_readonly};
+use super::zstd_compress::{ArtifactReader, ArtifactWriter, create_artifact, open_artifact};
 
 const MAGIC: u32 = 0x4E4F4453; // "NODS"
 const VERSION: u16 = 1;
@@ -44,10 +45,6 @@ pub fn write<P: AsRef<Path>>(
     nodes: &[(i64, f64, f64)],
     input_sha256: &[u8; 32],
 ) -> Result<()> {
-    let file = File::create(path.as_ref())
-        .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
-    let mut writer = BufWriter::new(file);
-
     // #421: step1 already sorts nodes by id, so the previous unconditional
     // `nodes.to_vec()` + re-sort was a redundant ~1.6 GB clone + O(n log n).
     // Borrow when already sorted (the build path); only own+sort the unsorted
@@ -61,94 +58,341 @@ pub fn write<P: AsRef<Path>>(
             std::borrow::Cow::Owned(v)
         };
 
-    // Calculate bounding box in fixed-point
-    let (bbox_min_lat, bbox_min_lon, bbox_max_lat, bbox_max_lon) = calculate_bbox(&sorted_nodes);
-
-    // #419: deterministic for byte-reproducible builds. created_unix is never
-    // read for logic; build provenance lives in the lock files + artifact-info.
-    let created_unix: u64 = 0;
-
-    // Write header (we'll calculate CRCs and update later)
-    let mut header = Vec::with_capacity(HEADER_SIZE);
-    header.extend_from_slice(&MAGIC.to_le_bytes());
-    header.extend_from_slice(&VERSION.to_le_bytes());
-    header.extend_from_slice(&0u16.to_le_bytes()); // reserved
-    header.extend_from_slice(&(sorted_nodes.len() as u64).to_le_bytes());
-    header.extend_from_slice(&SCALE.to_le_bytes());
-    header.extend_from_slice(&bbox_min_lat.to_le_bytes());
-    header.extend_from_slice(&bbox_min_lon.to_le_bytes());
-    header.extend_from_slice(&bbox_max_lat.to_le_bytes());
-    header.extend_from_slice(&bbox_max_lon.to_le_bytes());
-    header.extend_from_slice(&created_unix.to_le_bytes());
-    header.extend_from_slice(input_sha256);
-    header.resize(HEADER_SIZE, 0); // Fill reserved2
-
-    writer.write_all(&header)?;
-
-    // Write body and calculate CRC
-    let mut body_digest = Digest::new();
-    for (id, lat, lon) in sorted_nodes.iter() {
-        let lat_fxp = (lat * SCALE as f64).round() as i32;
-        let lon_fxp = (lon * SCALE as f64).round() as i32;
-
-        let mut record = Vec::with_capacity(RECORD_SIZE);
-        record.extend_from_slice(&id.to_le_bytes());
-        record.extend_from_slice(&lat_fxp.to_le_bytes());
-        record.extend_from_slice(&lon_fxp.to_le_bytes());
-
-        body_digest.update(&record);
-        writer.write_all(&record)?;
-    }
-
-    let body_crc64 = body_digest.finalize();
-
-    // Calculate file CRC (header + body)
-    let mut file_digest = Digest::new();
-    file_digest.update(&header);
-    for (id, lat, lon) in sorted_nodes.iter() {
-        let lat_fxp = (lat * SCALE as f64).round() as i32;
-        let lon_fxp = (lon * SCALE as f64).round() as i32;
-
-        let mut record = Vec::with_capacity(RECORD_SIZE);
-        record.extend_from_slice(&id.to_le_bytes());
-        record.extend_from_slice(&lat_fxp.to_le_bytes());
-        record.extend_from_slice(&lon_fxp.to_le_bytes());
-
-        file_digest.update(&record);
-    }
-    let file_crc64 = file_digest.finalize();
-
-    // Write footer
-    writer.write_all(&body_crc64.to_le_bytes())?;
-    writer.write_all(&file_crc64.to_le_bytes())?;
-
-    writer.flush()?;
-    Ok(())
+    let mut writer = Writer::create(
+        path,
+        sorted_nodes.len() as u64,
+        calculate_bbox(&sorted_nodes),
+        input_sha256,
+    )?;
+    for &(id, lat, lon) in sorted_nodes.iter() {
+        writer.push(id, lat, lon)?;
+    }
+    writer.finish()
 }
 
-fn calculate_bbox(nodes: &[(i64, f64, f64)]) -> (i32, i32, i32, i32) {
-    if nodes.is_empty() {
-        return (0, 0, 0, 0);
+/// Streaming nodes.sa writer for inputs too large to hold in memory
+/// (planet Step 1). The header needs the record count and bbox up
+/// front; records must then arrive sorted by id.
+pub struct Writer {
+    writer: ArtifactWriter,
+    body_digest: Digest,
+    file_digest: Digest,
+    count: u64,
+    written: u64,
+}
+
+impl Writer {
+    /// Create `path` and write the header for `count` records inside
+    /// `bbox` (fixed-point, see [`Bounds::to_fxp`]).
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        count: u64,
+        bbox: (i32, i32, i32, i32),
+        input_sha256: &[u8; 32],
+    ) -> Result<Self> {
+        let mut writer = create_artifact(path.as_ref())
+            .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
+        let (bbox_min_lat, bbox_min_lon, bbox_max_lat, bbox_max_lon) = bbox;
+
+        // #419: deterministic for byte-reproducible builds. created_unix is never
+        // read for logic; build provenance lives in the lock files + artifact-info.
+        let created_unix: u64 = 0;
+
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(&MAGIC.to_le_bytes());
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // reserved
+        header.extend_from_slice(&count.to_le_bytes());
+        header.extend_from_slice(&SCALE.to_le_bytes());
+        header.extend_from_slice(&bbox_min_lat.to_le_bytes());
+        header.extend_from_slice(&bbox_min_lon.to_le_bytes());
+        header.extend_from_slice(&bbox_max_lat.to_le_bytes());
+        header.extend_from_slice(&bbox_max_lon.to_le_bytes());
+        header.extend_from_slice(&created_unix.to_le_bytes());
+        header.extend_from_slice(input_sha256);
+        header.resize(HEADER_SIZE, 0); // Fill reserved2
+
+        writer.write_all(&header)?;
+        let mut file_digest = Digest::new();
+        file_digest.update(&header);
+
+        Ok(Self {
+            writer,
+            body_digest: Digest::new(),
+            file_digest,
+            count,
+            written: 0,
+        })
+    }
+
+    /// Append one record.
+    pub fn push(&mut self, id: i64, lat: f64, lon: f64) -> Result<()> {
+        self.push_fxp(id, to_fxp(lat), to_fxp(lon))
     }
 
-    let mut min_lat = f64::MAX;
-    let mut min_lon = f64::MAX;
-    let mut max_lat = f64::MIN;
-    let mut max_lon = f64::MIN;
+    /// Append one record already in fixed-point (e.g. copied from an
+    /// existing nodes.sa by [`Reader`]).
+    pub fn push_fxp(&mut self, id: i64, lat_fxp: i32, lon_fxp: i32) -> Result<()> {
+        let mut record = [0u8; RECORD_SIZE];
+        record[..8].copy_from_slice(&id.to_le_bytes());
+        record[8..12].copy_from_slice(&lat_fxp.to_le_bytes());
+        record[12..].copy_from_slice(&lon_fxp.to_le_bytes());
+
+        self.body_digest.update(&record);
+        self.file_digest.update(&record);
+        self.writer.write_all(&record)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Write the CRC footer. Fails if the record count doesn't match
+    /// the header.
+    pub fn finish(mut self) -> Result<()> {
+        anyhow::ensure!(
+            self.written == self.count,
+            "nodes.sa header announced {} records, {} written",
+            self.count,
+            self.written
+        );
+        let body_crc64 = self.body_digest.finalize();
+        let file_crc64 = self.file_digest.finalize();
+        self.writer.write_all(&body_crc64.to_le_bytes())?;
+        self.writer.write_all(&file_crc64.to_le_bytes())?;
+        self.writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Degrees to nodes.sa fixed-point.
+pub fn to_fxp(deg: f64) -> i32 {
+    (deg * SCALE as f64).round() as i32
+}
+
+/// Streaming nodes.sa reader yielding `(id, lat_fxp, lon_fxp)` in file
+/// (ascending id) order. Checksums are not verified; see
+/// `validate::verify_lock_conditions`.
+pub struct Reader {
+    reader: ArtifactReader,
+    remaining: u64,
+    bbox: (i32, i32, i32, i32),
+    input_sha256: [u8; 32],
+}
+
+impl Reader {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut reader = open_artifact(path.as_ref())
+            .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
+        let mut header = [0u8; HEADER_SIZE];
+        reader
+            .read_exact(&mut header)
+            .context("Failed to read nodes.sa header")?;
+
+        let magic = u32::from_le_bytes(header[0..4].try_into()?);
+        anyhow::ensure!(
+            magic == MAGIC,
+            "Invalid nodes.sa magic: expected 0x{MAGIC:08X}, got 0x{magic:08X}"
+        );
+        let version = u16::from_le_bytes(header[4..6].try_into()?);
+        anyhow::ensure!(
+            version == VERSION,
+            "Unsupported nodes.sa version: {version}"
+        );
+
+        Ok(Self {
+            reader,
+            remaining: u64::from_le_bytes(header[8..16].try_into()?),
+            bbox: (
+                i32::from_le_bytes(header[20..24].try_into()?),
+                i32::from_le_bytes(header[24..28].try_into()?),
+                i32::from_le_bytes(header[28..32].try_into()?),
+                i32::from_le_bytes(header[32..36].try_into()?),
+            ),
+            input_sha256: header[44..76].try_into()?,
+        })
+    }
+
+    /// Records left to read.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// `(min_lat, min_lon, max_lat, max_lon)` fixed-point bbox from the
+    /// header; all zero for an empty file.
+    pub fn bbox(&self) -> (i32, i32, i32, i32) {
+        self.bbox
+    }
+
+    /// SHA-256 of the input the file was built from.
+    pub fn input_sha256(&self) -> &[u8; 32] {
+        &self.input_sha256
+    }
+
+    /// Whether the file is zstd-framed (and so can't be mapped).
+    pub fn is_compressed(&self) -> bool {
+        self.reader.is_compressed()
+    }
+}
+
+/// One body record, laid out exactly as on disk (little-endian hosts).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct NodeRecord {
+    pub id: i64,
+    pub lat_fxp: i32,
+    pub lon_fxp: i32,
+}
+
+/// nodes.sa body as an id-indexed table. Raw files are memory-mapped
+/// and the records are a zero-copy view at offset 128 (the header is a
+/// multiple of the record alignment); zstd-framed files are decoded
+/// into an owned copy. Checksums are not verified, as with [`Reader`].
+pub struct Table {
+    records: ArcCow<NodeRecord>,
+}
+
+impl Table {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let reader = Reader::open(path)?;
+        let count = reader.remaining() as usize;
+
+        let records = if reader.is_compressed() {
+            let mut records = Vec::with_capacity(count);
+            for record in reader {
+                let (id, lat_fxp, lon_fxp) = record?;
+                records.push(NodeRecord {
+                    id,
+                    lat_fxp,
+                    lon_fxp,
+                });
+            }
+            ArcCow::from_vec(records)
+        } else {
+            drop(reader);
+            let mmap = map_readonly(path)?;
+            let expected = HEADER_SIZE + count * RECORD_SIZE + 16;
+            anyhow::ensure!(
+                mmap.len() == expected,
+                "Size mismatch in {}: expected {expected} bytes, got {}",
+                path.display(),
+                mmap.len()
+            );
+            ArcCow::from_mmap(mmap, HEADER_SIZE, count)?
+        };
 
+        // nodes.sa is strictly ascending by id (format invariant) → binary search OK.
+        debug_assert!(records.windows(2).all(|w| w[0].id < w[1].id));
+        Ok(Self { records })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn records(&self) -> &[NodeRecord] {
+        &self.records
+    }
+
+    /// Fixed-point `(lat, lon)` of node `id`, if present.
+    #[inline]
+    pub fn get(&self, id: i64) -> Option<(i32, i32)> {
+        let records = self.records.as_slice();
+        records
+            .binary_search_by_key(&id, |r| r.id)
+            .ok()
+            .map(|i| (records[i].lat_fxp, records[i].lon_fxp))
+    }
+}
+
+impl Iterator for Reader {
+    type Item = Result<(i64, i32, i32)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let mut record = [0u8; RECORD_SIZE];
+        if let Err(e) = self.reader.read_exact(&mut record) {
+            self.remaining = 0;
+            return Some(Err(
+                anyhow::Error::new(e).context("nodes.sa: truncated body")
+            ));
+        }
+        Some(Ok((
+            i64::from_le_bytes(record[..8].try_into().unwrap()),
+            i32::from_le_bytes(record[8..12].try_into().unwrap()),
+            i32::from_le_bytes(record[12..].try_into().unwrap()),
+        )))
+    }
+}
+
+/// Running lat/lon bounding box, for callers that stream nodes.
+#[derive(Debug, Clone, Copy)]
+pub struct Bounds {
+    min_lat: f64,
+    min_lon: f64,
+    max_lat: f64,
+    max_lon: f64,
+    empty: bool,
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Self {
+            min_lat: f64::MAX,
+            min_lon: f64::MAX,
+            max_lat: f64::MIN,
+            max_lon: f64::MIN,
+            empty: true,
+        }
+    }
+}
+
+impl Bounds {
+    /// Grow the box to include `(lat, lon)`.
+    pub fn add(&mut self, lat: f64, lon: f64) {
+        self.min_lat = self.min_lat.min(lat);
+        self.min_lon = self.min_lon.min(lon);
+        self.max_lat = self.max_lat.max(lat);
+        self.max_lon = self.max_lon.max(lon);
+        self.empty = false;
+    }
+
+    /// Merge another box into this one.
+    pub fn merge(&mut self, other: &Bounds) {
+        if !other.empty {
+            self.add(other.min_lat, other.min_lon);
+            self.add(other.max_lat, other.max_lon);
+        }
+    }
+
+    /// `(min_lat, min_lon, max_lat, max_lon)` in nodes.sa fixed-point;
+    /// all zero when no node was added.
+    pub fn to_fxp(&self) -> (i32, i32, i32, i32) {
+        if self.empty {
+            return (0, 0, 0, 0);
+        }
+        (
+            to_fxp(self.min_lat),
+            to_fxp(self.min_lon),
+            to_fxp(self.max_lat),
+            to_fxp(self.max_lon),
+        )
+    }
+}
+
+fn calculate_bbox(nodes: &[(i64, f64, f64)]) -> (i32, i32, i32, i32) {
+    let mut bounds = Bounds::default();
     for (_, lat, lon) in nodes {
-        min_lat = min_lat.min(*lat);
-        min_lon = min_lon.min(*lon);
-        max_lat = max_lat.max(*lat);
-        max_lon = max_lon.max(*lon);
-    }
-
-    (
-        (min_lat * SCALE as f64).round() as i32,
-        (min_lon * SCALE as f64).round() as i32,
-        (max_lat * SCALE as f64).round() as i32,
-        (max_lon * SCALE as f64).round() as i32,
-    )
+        bounds.add(*lat, *lon);
+    }
+    bounds.to_fxp()
 }
 
 #[cfg(test)]
@@ -171,4 +415,59 @@ mod tests {
         assert!(min_lon <= 39520000);
         assert!(max_lon >= 44025000);
     }
+
+    #[test]
+    fn test_reader_round_trip() {
+        let nodes = vec![(1, 50.8503, 4.3517), (7, -33.9, -70.6)];
+        let sha = [7u8; 32];
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        write(tmp.path(), &nodes, &sha).unwrap();
+
+        let reader = Reader::open(tmp.path()).unwrap();
+        assert_eq!(reader.remaining(), 2);
+        assert_eq!(reader.input_sha256(), &sha);
+        assert_eq!(
+            reader.bbox(),
+            (
+                to_fxp(-33.9),
+                to_fxp(-70.6),
+                to_fxp(50.8503),
+                to_fxp(4.3517)
+            )
+        );
+        let records: Vec<_> = reader.map(Result::unwrap).collect();
+        assert_eq!(
+            records,
+            vec![
+                (1, to_fxp(50.8503), to_fxp(4.3517)),
+                (7, to_fxp(-33.9), to_fxp(-70.6))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_lookup_mapped_and_compressed() {
+        let nodes = vec![(1, 50.8503, 4.3517), (7, -33.9, -70.6), (9, 0.5, 0.25)];
+        let dir = tempfile::tempdir().unwrap();
+
+        let raw = dir.path().join("nodes.sa");
+        write(&raw, &nodes, &[0u8; 32]).unwrap();
+        let table = Table::open(&raw).unwrap();
+        assert!(matches!(table.records, ArcCow::Mmap { .. }));
+
+        // Same records in a zstd frame: decoded into an owned table.
+        let zst = dir.path().join("nodes.sa.zst");
+        let mut w = super::super::zstd_compress::create_artifact_with(&zst, true).unwrap();
+        w.write_all(&std::fs::read(&raw).unwrap()).unwrap();
+        w.finish().unwrap();
+        let owned = Table::open(&zst).unwrap();
+        assert!(matches!(owned.records, ArcCow::Owned(_)));
+
+        for t in [&table, &owned] {
+            assert_eq!(t.len(), 3);
+            assert_eq!(t.get(7), Some((to_fxp(-33.9), to_fxp(-70.6))));
+            assert_eq!(t.get(9), Some((to_fxp(0.5), to_fxp(0.25))));
+            assert_eq!(t.get(2), None);
+        }
+    }
 }
@@ -21,10 +21,11 @@
 //!     rec_index:  u64  // = j*block_size
 
 use anyhow::{Context, Result};
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::Write;
 use std::path::Path;
 
+use super::zstd_compress::create_artifact;
+
 const MAGIC: u32 = 0x4E4F4458; // "NODX"
 const VERSION: u16 = 1;
 const BLOCK_SIZE: u32 = 2048;
@@ -40,10 +41,6 @@ struct Level2Sample {
 
 /// Write two-level sparse index for nodes
 pub fn write<P: AsRef<Path>>(path: P, nodes: &[(i64, f64, f64)]) -> Result<()> {
-    let file = File::create(path.as_ref())
-        .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
-    let mut writer = BufWriter::new(file);
-
     // #421: borrow when already sorted (step1 pre-sorts) instead of an
     // unconditional ~1.6 GB clone + re-sort; own+sort only the unsorted
     // (test-only) case. Byte-identical output.
@@ -56,71 +53,94 @@ pub fn write<P: AsRef<Path>>(path: P, nodes: &[(i64, f64, f64)]) -> Result<()> {
             std::borrow::Cow::Owned(v)
         };
 
-    // Build Level 2 samples (one per block_size records)
-    let mut level2: Vec<Level2Sample> = Vec::new();
-    for (j, chunk) in sorted_nodes.chunks(BLOCK_SIZE as usize).enumerate() {
-        if let Some((id, _, _)) = chunk.first() {
-            level2.push(Level2Sample {
-                id_sample: *id,
-                rec_index: (j as u64) * (BLOCK_SIZE as u64),
+    let mut builder = IndexBuilder::default();
+    for (id, _, _) in sorted_nodes.iter() {
+        builder.push(*id);
+    }
+    builder.write(path)
+}
+
+/// Builds nodes.si from node ids streamed in nodes.sa order, keeping
+/// only the Level 2 samples (one per `BLOCK_SIZE` records) in memory.
+#[derive(Debug, Default)]
+pub struct IndexBuilder {
+    level2: Vec<Level2Sample>,
+    seen: u64,
+}
+
+impl IndexBuilder {
+    /// Record the next node id (ids must arrive sorted).
+    pub fn push(&mut self, id: i64) {
+        if self.seen.is_multiple_of(BLOCK_SIZE as u64) {
+            self.level2.push(Level2Sample {
+                id_sample: id,
+                rec_index: self.seen,
             });
         }
+        self.seen += 1;
     }
 
-    // Build Level 1 buckets by partitioning Level 2 samples
-    let mut level1: Vec<(u64, u64)> = vec![(0, 0); NUM_BUCKETS];
-
-    // Group Level 2 samples by high bits
-    for (sample_idx, sample) in level2.iter().enumerate() {
-        let bucket = compute_bucket(sample.id_sample);
-        let (start, end) = &mut level1[bucket];
+    /// Write the index to `path`.
+    pub fn write<P: AsRef<Path>>(self, path: P) -> Result<()> {
+        let mut writer = create_artifact(path.as_ref())
+            .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
+        let level2 = self.level2;
+
+        // Build Level 1 buckets by partitioning Level 2 samples
+        let mut level1: Vec<(u64, u64)> = vec![(0, 0); NUM_BUCKETS];
+
+        // Group Level 2 samples by high bits
+        for (sample_idx, sample) in level2.iter().enumerate() {
+            let bucket = compute_bucket(sample.id_sample);
+            let (start, end) = &mut level1[bucket];
+
+            if *start == 0 && *end == 0 {
+                // First sample in this bucket
+                *start = sample_idx as u64;
+                *end = (sample_idx + 1) as u64;
+            } else {
+                // Extend bucket
+                *end = (sample_idx + 1) as u64;
+            }
+        }
 
-        if *start == 0 && *end == 0 {
-            // First sample in this bucket
-            *start = sample_idx as u64;
-            *end = (sample_idx + 1) as u64;
-        } else {
-            // Extend bucket
-            *end = (sample_idx + 1) as u64;
+        // Fix empty buckets: ensure start_idx points to correct position
+        let mut last_end = 0u64;
+        for (start, end) in level1.iter_mut() {
+            if *start == 0 && *end == 0 {
+                // Empty bucket - point to where next bucket would start
+                *start = last_end;
+                *end = last_end;
+            }
+            last_end = *end;
         }
-    }
 
-    // Fix empty buckets: ensure start_idx points to correct position
-    let mut last_end = 0u64;
-    for (start, end) in level1.iter_mut() {
-        if *start == 0 && *end == 0 {
-            // Empty bucket - point to where next bucket would start
-            *start = last_end;
-            *end = last_end;
+        // Write header
+        let mut header = Vec::with_capacity(HEADER_SIZE);
+        header.extend_from_slice(&MAGIC.to_le_bytes());
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes()); // reserved
+        header.extend_from_slice(&BLOCK_SIZE.to_le_bytes());
+        header.push(TOP_BITS);
+        header.resize(HEADER_SIZE, 0); // Fill reserved2
+
+        writer.write_all(&header)?;
+
+        // Write Level 1
+        for (start_idx, end_idx) in level1.iter() {
+            writer.write_all(&start_idx.to_le_bytes())?;
+            writer.write_all(&end_idx.to_le_bytes())?;
         }
-        last_end = *end;
-    }
 
-    // Write header
-    let mut header = Vec::with_capacity(HEADER_SIZE);
-    header.extend_from_slice(&MAGIC.to_le_bytes());
-    header.extend_from_slice(&VERSION.to_le_bytes());
-    header.extend_from_slice(&0u16.to_le_bytes()); // reserved
-    header.extend_from_slice(&BLOCK_SIZE.to_le_bytes());
-    header.push(TOP_BITS);
-    header.resize(HEADER_SIZE, 0); // Fill reserved2
-
-    writer.write_all(&header)?;
-
-    // Write Level 1
-    for (start_idx, end_idx) in level1.iter() {
-        writer.write_all(&start_idx.to_le_bytes())?;
-        writer.write_all(&end_idx.to_le_bytes())?;
-    }
+        // Write Level 2
+        for sample in level2.iter() {
+            writer.write_all(&sample.id_sample.to_le_bytes())?;
+            writer.write_all(&sample.rec_index.to_le_bytes())?;
+        }
 
-    // Write Level 2
-    for sample in level2.iter() {
-        writer.write_all(&sample.id_sample.to_le_bytes())?;
-        writer.write_all(&sample.rec_index.to_le_bytes())?;
+        writer.finish()?;
+        Ok(())
     }
-
-    writer.flush()?;
-    Ok(())
 }
 
 /// Compute bucket from node ID using high bits
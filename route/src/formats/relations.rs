@@ -1,15 +1,21 @@
 //! relations.raw format - turn restrictions and relevant relations
+//!
+//! Version 2 (written only when relations carry [`ElementMeta`]) inserts
+//! `version: i32 | timestamp: i64` right after `rel_id`, as in ways.raw.
 
 use anyhow::Result;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufWriter, Read, Write};
+use std::io::Write;
 use std::path::Path;
 
 use super::crc;
+use super::ways::ElementMeta;
+use super::zstd_compress::{create_artifact, read_artifact};
 
 const MAGIC: u32 = 0x52454C53; // "RELS"
 const VERSION: u16 = 1;
+const VERSION_META: u16 = 2;
+const META_SIZE: u64 = 12;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MemberKind {
@@ -41,6 +47,7 @@ pub struct Relation {
     pub id: i64,
     pub members: Vec<Member>,
     pub tags: Vec<(String, String)>,
+    pub meta: Option<ElementMeta>,
 }
 
 pub struct RelationsFile;
@@ -51,8 +58,7 @@ impl RelationsFile {
         if relations.is_empty() {
             // Empty is OK for relations (some regions may have no restrictions)
             // Write empty file with just header and footer
-            let file = File::create(path.as_ref())?;
-            let mut writer = BufWriter::new(file);
+            let mut writer = create_artifact(path.as_ref())?;
 
             let mut header_bytes = Vec::new();
             header_bytes.extend_from_slice(&MAGIC.to_le_bytes());
@@ -66,7 +72,7 @@ impl RelationsFile {
             let crc = crc::checksum(&header_bytes);
             writer.write_all(&0u64.to_le_bytes())?; // rels_crc
             writer.write_all(&crc.to_le_bytes())?; // file_crc
-            writer.flush()?;
+            writer.finish()?;
             return Ok(());
         }
 
@@ -117,6 +123,13 @@ impl RelationsFile {
             body_size += 8 * rel.tags.len() as u64; // tags (k_id + v_id)
         }
 
+        // Any relation with metadata switches the whole file to v2.
+        let with_meta = sorted_rels.iter().any(|r| r.meta.is_some());
+        let version = if with_meta { VERSION_META } else { VERSION };
+        if with_meta {
+            body_size += META_SIZE * sorted_rels.len() as u64;
+        }
+
         let kdict_off = header_size + body_size;
 
         let mut kdict_size = 0u64;
@@ -129,14 +142,13 @@ impl RelationsFile {
         let vdict_off = kdict_off + kdict_size;
 
         // Now write everything with correct offsets from the start
-        let file = File::create(path.as_ref())?;
-        let mut writer = BufWriter::new(file);
+        let mut writer = create_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         // Write header with CORRECT offsets
         let mut header_bytes = Vec::new();
         header_bytes.extend_from_slice(&MAGIC.to_le_bytes());
-        header_bytes.extend_from_slice(&VERSION.to_le_bytes());
+        header_bytes.extend_from_slice(&version.to_le_bytes());
         header_bytes.extend_from_slice(&0u16.to_le_bytes()); // reserved
         header_bytes.extend_from_slice(&(sorted_rels.len() as u64).to_le_bytes());
         header_bytes.extend_from_slice(&kdict_off.to_le_bytes());
@@ -154,6 +166,14 @@ impl RelationsFile {
             rels_digest.update(&id_bytes);
             crc_digest.update(&id_bytes);
 
+            // version + timestamp (v2)
+            if with_meta {
+                let meta_bytes = ElementMeta::encode(rel.meta);
+                writer.write_all(&meta_bytes)?;
+                rels_digest.update(&meta_bytes);
+                crc_digest.update(&meta_bytes);
+            }
+
             // n_members
             let n_members = rel.members.len() as u16;
             let n_members_bytes = n_members.to_le_bytes();
@@ -235,23 +255,21 @@ impl RelationsFile {
         let file_crc = crc_digest.finalize();
         writer.write_all(&rels_crc.to_le_bytes())?;
         writer.write_all(&file_crc.to_le_bytes())?;
-        writer.flush()?;
+        writer.finish()?;
 
         Ok(())
     }
 
     /// Read relations.raw file and return relations with tags resolved from dictionaries
     pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Relation>> {
-        let mut file = File::open(path)?;
-        let file_len = file.metadata()?.len();
+        let all_bytes = read_artifact(path.as_ref())?;
 
-        if file_len < 32 + 16 {
+        if all_bytes.len() < 32 + 16 {
             anyhow::bail!("File too short");
         }
 
         // Read header (32 bytes)
-        let mut header = [0u8; 32];
-        file.read_exact(&mut header)?;
+        let header = &all_bytes[..32];
 
         let magic = u32::from_le_bytes(header[0..4].try_into()?);
         if magic != MAGIC {
@@ -263,19 +281,15 @@ impl RelationsFile {
         }
 
         let version = u16::from_le_bytes(header[4..6].try_into()?);
-        if version != VERSION {
+        if version != VERSION && version != VERSION_META {
             anyhow::bail!("Unsupported version: {}", version);
         }
+        let with_meta = version == VERSION_META;
 
         let count = u64::from_le_bytes(header[8..16].try_into()?);
         let kdict_off = u64::from_le_bytes(header[16..24].try_into()?);
         let vdict_off = u64::from_le_bytes(header[24..32].try_into()?);
 
-        // Read entire file
-        let mut all_bytes = vec![0u8; file_len as usize];
-        all_bytes[..32].copy_from_slice(&header);
-        file.read_exact(&mut all_bytes[32..])?;
-
         // Read key dictionary
         let key_dict = Self::read_dict(&all_bytes, kdict_off as usize, vdict_off as usize)?;
 
@@ -298,6 +312,15 @@ impl RelationsFile {
             let rel_id = i64::from_le_bytes(all_bytes[pos..pos + 8].try_into()?);
             pos += 8;
 
+            // version + timestamp (v2)
+            let meta = if with_meta {
+                let meta = ElementMeta::decode(&all_bytes[pos..pos + META_SIZE as usize]);
+                pos += META_SIZE as usize;
+                meta
+            } else {
+                None
+            };
+
             // n_members
             let n_members = u16::from_le_bytes(all_bytes[pos..pos + 2].try_into()?) as usize;
             pos += 2;
@@ -347,6 +370,7 @@ impl RelationsFile {
                 id: rel_id,
                 members,
                 tags,
+                meta,
             });
         }
 
@@ -363,25 +387,18 @@ impl RelationsFile {
         [u8; 32],
         [u8; 32],
     )> {
-        let mut file = File::open(path)?;
-        let file_len = file.metadata()?.len();
+        let all_bytes = read_artifact(path.as_ref())?;
 
-        if file_len < 32 + 16 {
+        if all_bytes.len() < 32 + 16 {
             anyhow::bail!("File too short");
         }
 
         // Read header (32 bytes)
-        let mut header = [0u8; 32];
-        file.read_exact(&mut header)?;
+        let header = &all_bytes[..32];
 
         let kdict_off = u64::from_le_bytes(header[16..24].try_into()?);
         let vdict_off = u64::from_le_bytes(header[24..32].try_into()?);
 
-        // Read entire file
-        let mut all_bytes = vec![0u8; file_len as usize];
-        all_bytes[..32].copy_from_slice(&header);
-        file.read_exact(&mut all_bytes[32..])?;
-
         // Read key dictionary
         let key_dict = Self::read_dict(&all_bytes, kdict_off as usize, vdict_off as usize)?;
 
@@ -443,26 +460,18 @@ impl RelationsFile {
 
     /// Verify checksums in relations.raw file
     pub fn verify<P: AsRef<Path>>(path: P) -> Result<()> {
-        let mut file = File::open(path)?;
+        let bytes = read_artifact(path.as_ref())?;
 
-        // Read entire file except footer
-        let file_len = file.metadata()?.len();
-        if file_len < 28 + 16 {
+        // Split off the footer
+        if bytes.len() < 28 + 16 {
             anyhow::bail!("File too short");
         }
-
-        let content_len = file_len - 16;
-        let mut content = vec![0u8; content_len as usize];
-        file.read_exact(&mut content)?;
-
-        // Read footer
-        let mut footer = [0u8; 16];
-        file.read_exact(&mut footer)?;
+        let (content, footer) = bytes.split_at(bytes.len() - 16);
 
         let stored_file_crc = u64::from_le_bytes(footer[8..16].try_into()?);
 
         // Verify file CRC
-        let computed_file_crc = crc::checksum(&content);
+        let computed_file_crc = crc::checksum(content);
         if computed_file_crc != stored_file_crc {
             anyhow::bail!(
                 "File CRC mismatch: expected {:016x}, got {:016x}",
@@ -506,10 +515,42 @@ mod tests {
                 ("type".to_string(), "restriction".to_string()),
                 ("restriction".to_string(), "no_left_turn".to_string()),
             ],
+            meta: None,
         }];
 
         let tmpfile = NamedTempFile::new().unwrap();
         RelationsFile::write(tmpfile.path(), &relations).unwrap();
         RelationsFile::verify(tmpfile.path()).unwrap();
     }
+
+    #[test]
+    fn test_relations_v2_metadata_round_trip() {
+        let meta = ElementMeta {
+            version: 3,
+            timestamp: 1_600_000_000,
+        };
+        let relation = |id, meta| Relation {
+            id,
+            members: vec![Member {
+                role: "via".to_string(),
+                kind: MemberKind::Node,
+                ref_id: 2,
+            }],
+            tags: vec![("type".to_string(), "restriction".to_string())],
+            meta,
+        };
+
+        let tmpfile = NamedTempFile::new().unwrap();
+        RelationsFile::write(
+            tmpfile.path(),
+            &[relation(9, Some(meta)), relation(4, None)],
+        )
+        .unwrap();
+        RelationsFile::verify(tmpfile.path()).unwrap();
+
+        let read = RelationsFile::read(tmpfile.path()).unwrap();
+        let metas: Vec<_> = read.iter().map(|r| (r.id, r.meta)).collect();
+        assert_eq!(metas, vec![(4, None), (9, Some(meta))]);
+        assert_eq!(read[1].members[0].role, "via");
+    }
 }
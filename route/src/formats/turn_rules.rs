@@ -25,11 +25,11 @@
 //!   file_crc64:    u64
 
 use anyhow::{Context, Result};
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::Write;
 use std::path::Path;
 
 use super::crc::Digest;
+use super::zstd_compress::{artifact_len, create_artifact, open_artifact};
 use crate::profile_abi::{Mode, TurnRuleKind};
 
 const MAGIC: u32 = 0x5455524E; // "TURN"
@@ -55,9 +55,8 @@ pub fn write<P: AsRef<Path>>(
     rel_dict_k_sha256: &[u8; 32],
     rel_dict_v_sha256: &[u8; 32],
 ) -> Result<()> {
-    let file = File::create(path.as_ref())
+    let mut writer = create_artifact(path.as_ref())
         .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
-    let mut writer = BufWriter::new(file);
 
     // Sort rules by (via_node_id, from_way_id, to_way_id)
     let mut sorted_rules = rules.to_vec();
@@ -99,7 +98,7 @@ pub fn write<P: AsRef<Path>>(
     writer.write_all(&body_crc64.to_le_bytes())?;
     writer.write_all(&file_crc64.to_le_bytes())?;
 
-    writer.flush()?;
+    writer.finish()?;
     Ok(())
 }
 
@@ -160,7 +159,7 @@ fn decode_record(record: &[u8]) -> Result<TurnRule> {
 pub fn read_all<P: AsRef<Path>>(path: P) -> Result<Vec<TurnRule>> {
     use std::io::Read;
 
-    let mut file = File::open(path.as_ref())
+    let mut file = open_artifact(path.as_ref())
         .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
 
     // Read header
@@ -226,9 +225,9 @@ pub fn read_all<P: AsRef<Path>>(path: P) -> Result<Vec<TurnRule>> {
 
 /// Verify turn_rules file structure and checksums
 pub fn verify<P: AsRef<Path>>(path: P) -> Result<()> {
-    use std::io::{Read, Seek, SeekFrom};
+    use std::io::Read;
 
-    let mut file = File::open(path.as_ref())
+    let mut file = open_artifact(path.as_ref())
         .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
 
     // Read and verify header
@@ -252,7 +251,7 @@ pub fn verify<P: AsRef<Path>>(path: P) -> Result<()> {
 
     // Verify file size
     let expected_size = HEADER_SIZE as u64 + (count * RECORD_SIZE as u64) + 16;
-    let actual_size = file.seek(SeekFrom::End(0))?;
+    let actual_size = artifact_len(path.as_ref())?;
 
     if actual_size != expected_size {
         anyhow::bail!(
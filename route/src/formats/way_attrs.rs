@@ -27,11 +27,12 @@
 //!   file_crc64:  u64
 
 use anyhow::{Context, Result};
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::Write;
 use std::path::Path;
 
 use super::crc::Digest;
+use super::mmap::{ArcCow, map_readonly};
+use super::zstd_compress::{ArtifactReader, artifact_len, create_artifact, open_artifact};
 use crate::profile_abi::{Mode, WayOutput};
 
 const MAGIC: u32 = 0x57415941; // "WAYA"
@@ -56,9 +57,8 @@ pub fn write<P: AsRef<Path>>(
     dict_k_sha256: &[u8; 32],
     dict_v_sha256: &[u8; 32],
 ) -> Result<()> {
-    let file = File::create(path.as_ref())
+    let mut writer = create_artifact(path.as_ref())
         .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
-    let mut writer = BufWriter::new(file);
 
     // Ensure attrs are sorted by way_id
     let mut sorted_attrs = attrs.to_vec();
@@ -95,7 +95,7 @@ pub fn write<P: AsRef<Path>>(
     writer.write_all(&body_crc64.to_le_bytes())?;
     writer.write_all(&file_crc64.to_le_bytes())?;
 
-    writer.flush()?;
+    writer.finish()?;
     Ok(())
 }
 
@@ -168,7 +168,7 @@ fn decode_record(record: &[u8], way_id: i64, version: u16) -> Result<WayAttr> {
 
 /// Read all way_attrs from file
 pub fn read_all<P: AsRef<Path>>(path: P) -> Result<Vec<WayAttr>> {
-    let file = File::open(path.as_ref())
+    let file = open_artifact(path.as_ref())
         .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
     read_all_from_reader(file).with_context(|| format!("reading {}", path.as_ref().display()))
 }
@@ -244,11 +244,143 @@ fn read_all_from_reader<R: std::io::Read>(mut file: R) -> Result<Vec<WayAttr>> {
     Ok(attrs)
 }
 
+/// Records of a way_attrs file, decoded one at a time in way_id order so
+/// callers can merge-join them against ways.raw without holding the file.
+/// CRCs are not checked here; [`verify`] does that.
+pub struct Stream {
+    file: ArtifactReader,
+    version: u16,
+    remaining: u64,
+}
+
+/// Open `path` for streaming.
+pub fn stream<P: AsRef<Path>>(path: P) -> Result<Stream> {
+    use std::io::Read;
+
+    let mut file = open_artifact(path.as_ref())
+        .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
+    let mut header = [0u8; HEADER_SIZE];
+    file.read_exact(&mut header)?;
+
+    let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    anyhow::ensure!(
+        magic == MAGIC,
+        "Bad magic in way_attrs: 0x{:08X} (expected 0x{:08X})",
+        magic,
+        MAGIC
+    );
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    anyhow::ensure!(
+        (VERSION_MIN..=VERSION).contains(&version),
+        "Unsupported way_attrs version {} (supported: {}..={})",
+        version,
+        VERSION_MIN,
+        VERSION
+    );
+    let remaining = u64::from_le_bytes(header[8..16].try_into().unwrap());
+
+    Ok(Stream {
+        file,
+        version,
+        remaining,
+    })
+}
+
+impl Iterator for Stream {
+    type Item = Result<WayAttr>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use std::io::Read;
+
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let mut record = [0u8; RECORD_SIZE];
+        if let Err(e) = self.file.read_exact(&mut record) {
+            self.remaining = 0;
+            return Some(Err(e.into()));
+        }
+        let way_id = i64::from_le_bytes(record[0..8].try_into().unwrap());
+        Some(decode_record(&record, way_id, self.version))
+    }
+}
+
+/// way_attrs body as a way_id-keyed lookup table. Raw files are
+/// memory-mapped and searched in place (records stay encoded until a hit
+/// is decoded); zstd-framed files are decoded into an owned copy of the
+/// body. CRCs are not checked here; [`verify`] does that.
+pub struct Index {
+    records: ArcCow<[u8; RECORD_SIZE]>,
+    version: u16,
+}
+
+impl Index {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        use std::io::Read;
+
+        let path = path.as_ref();
+        let mut stream = stream(path)?;
+        let count = stream.remaining as usize;
+        let version = stream.version;
+
+        let records = if stream.file.is_compressed() {
+            let mut records = vec![[0u8; RECORD_SIZE]; count];
+            for record in &mut records {
+                stream
+                    .file
+                    .read_exact(record)
+                    .with_context(|| format!("reading {}", path.display()))?;
+            }
+            ArcCow::from_vec(records)
+        } else {
+            drop(stream);
+            let mmap = map_readonly(path)?;
+            let expected = HEADER_SIZE + count * RECORD_SIZE + 16;
+            anyhow::ensure!(
+                mmap.len() == expected,
+                "Size mismatch in {}: expected {expected} bytes, got {}",
+                path.display(),
+                mmap.len()
+            );
+            ArcCow::from_mmap(mmap, HEADER_SIZE, count)?
+        };
+
+        // way_attrs is sorted by way_id (format invariant) → binary search OK.
+        debug_assert!(
+            records
+                .windows(2)
+                .all(|w| record_way_id(&w[0]) < record_way_id(&w[1]))
+        );
+        Ok(Self { records, version })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Attributes of `way_id`, if the way is in the file.
+    pub fn get(&self, way_id: i64) -> Option<WayAttr> {
+        let records = self.records.as_slice();
+        let i = records.binary_search_by_key(&way_id, record_way_id).ok()?;
+        decode_record(&records[i], way_id, self.version).ok()
+    }
+}
+
+#[inline]
+fn record_way_id(record: &[u8; RECORD_SIZE]) -> i64 {
+    i64::from_le_bytes(record[0..8].try_into().unwrap())
+}
+
 /// Verify way_attrs file structure and checksums
 pub fn verify<P: AsRef<Path>>(path: P) -> Result<()> {
-    use std::io::{Read, Seek, SeekFrom};
+    use std::io::Read;
 
-    let mut file = File::open(path.as_ref())
+    let mut file = open_artifact(path.as_ref())
         .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
 
     // Read and verify header
@@ -272,7 +404,7 @@ pub fn verify<P: AsRef<Path>>(path: P) -> Result<()> {
 
     // Verify file size
     let expected_size = HEADER_SIZE as u64 + (count * RECORD_SIZE as u64) + 16;
-    let actual_size = file.seek(SeekFrom::End(0))?;
+    let actual_size = artifact_len(path.as_ref())?;
 
     if actual_size != expected_size {
         anyhow::bail!(
@@ -393,5 +525,22 @@ mod tests {
         assert_eq!(read_back.len(), 2);
         assert_eq!(read_back[0].output.density_class, 4);
         assert_eq!(read_back[1].output.density_class, 0);
+
+        let streamed: Vec<_> = stream(tmp.path()).unwrap().map(Result::unwrap).collect();
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed[1].way_id, 2);
+        assert!(streamed[1].output.access_rev);
+
+        // Index: mmap'd raw file and the zstd-framed copy answer alike.
+        let zst = NamedTempFile::new().unwrap();
+        let raw = std::fs::read(tmp.path()).unwrap();
+        std::fs::write(zst.path(), zstd::encode_all(&raw[..], 3).unwrap()).unwrap();
+        for path in [tmp.path(), zst.path()] {
+            let index = Index::open(path).unwrap();
+            assert_eq!(index.len(), 2);
+            assert_eq!(index.get(1).unwrap().output.density_class, 4);
+            assert!(index.get(2).unwrap().output.access_rev);
+            assert!(index.get(3).is_none());
+        }
     }
 }
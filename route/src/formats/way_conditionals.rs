@@ -0,0 +1,219 @@
+//! way_conditionals.<mode>.bin format - Per-mode time-dependent way restrictions
+//!
+//! Companion to way_attrs.<mode>.bin: the static attributes stay as they
+//! are, and each record here says what changes for a way during a weekly
+//! schedule (see [`crate::model::conditional`]).
+//!
+//! Format (little-endian):
+//!
+//! Header (80 bytes):
+//!   magic:       u32 = 0x57415943  // "WAYC"
+//!   version:     u16 = 1
+//!   mode:        u8  = {0,1,2,...} (alphabetical mode index)
+//!   reserved:    u8  = 0
+//!   count:       u64
+//!   dict_k_sha:  [32]u8
+//!   dict_v_sha:  [32]u8
+//!
+//! Body (count records, sorted by way_id, kind):
+//!   way_id:      i64
+//!   kind:        u8   // 0=Deny,1=Allow,2=OnewayFwd,3=OnewayRev
+//!   reserved:    [7]u8
+//!   schedule:    [11]u64  // 15-minute slots, bit 0 = Monday 00:00
+//!
+//! Footer (16 bytes):
+//!   body_crc64:  u64
+//!   file_crc64:  u64
+
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use super::crc::Digest;
+use super::zstd_compress::{create_artifact, open_artifact};
+use crate::model::conditional::{ConditionalKind, SCHEDULE_WORDS, WeekSchedule};
+use crate::profile_abi::Mode;
+
+const MAGIC: u32 = 0x57415943; // "WAYC"
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 80; // 4 + 2 + 1 + 1 + 8 + 32 + 32
+const RECORD_SIZE: usize = 16 + SCHEDULE_WORDS * 8; // 8 + 1 + 7 + 88
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WayConditional {
+    pub way_id: i64,
+    pub kind: ConditionalKind,
+    pub schedule: WeekSchedule,
+}
+
+/// Write way_conditionals.<mode>.bin file
+pub fn write<P: AsRef<Path>>(
+    path: P,
+    mode: Mode,
+    conditionals: &[WayConditional],
+    dict_k_sha256: &[u8; 32],
+    dict_v_sha256: &[u8; 32],
+) -> Result<()> {
+    let mut writer = create_artifact(path.as_ref())
+        .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
+
+    let mut sorted = conditionals.to_vec();
+    sorted.sort_by_key(|c| (c.way_id, c.kind));
+
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(&MAGIC.to_le_bytes());
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.push(mode.0);
+    header.push(0); // reserved
+    header.extend_from_slice(&(sorted.len() as u64).to_le_bytes());
+    header.extend_from_slice(dict_k_sha256);
+    header.extend_from_slice(dict_v_sha256);
+    assert_eq!(header.len(), HEADER_SIZE);
+
+    writer.write_all(&header)?;
+
+    let mut body_digest = Digest::new();
+    let mut file_digest = Digest::new();
+    file_digest.update(&header);
+    for conditional in &sorted {
+        let record = encode_record(conditional);
+        body_digest.update(&record);
+        file_digest.update(&record);
+        writer.write_all(&record)?;
+    }
+
+    writer.write_all(&body_digest.finalize().to_le_bytes())?;
+    writer.write_all(&file_digest.finalize().to_le_bytes())?;
+
+    writer.finish()?;
+    Ok(())
+}
+
+fn encode_record(conditional: &WayConditional) -> Vec<u8> {
+    let mut record = Vec::with_capacity(RECORD_SIZE);
+    record.extend_from_slice(&conditional.way_id.to_le_bytes());
+    record.push(conditional.kind as u8);
+    record.extend_from_slice(&[0u8; 7]); // reserved
+    for word in conditional.schedule.words {
+        record.extend_from_slice(&word.to_le_bytes());
+    }
+    assert_eq!(record.len(), RECORD_SIZE);
+    record
+}
+
+fn decode_record(record: &[u8]) -> Result<WayConditional> {
+    anyhow::ensure!(record.len() >= RECORD_SIZE, "Record too small");
+
+    let way_id = i64::from_le_bytes(record[0..8].try_into().unwrap());
+    let kind = ConditionalKind::from_u8(record[8])
+        .ok_or_else(|| anyhow::anyhow!("Invalid conditional kind: {}", record[8]))?;
+    let mut schedule = WeekSchedule::default();
+    for (i, word) in schedule.words.iter_mut().enumerate() {
+        let at = 16 + i * 8;
+        *word = u64::from_le_bytes(record[at..at + 8].try_into().unwrap());
+    }
+
+    Ok(WayConditional {
+        way_id,
+        kind,
+        schedule,
+    })
+}
+
+/// Read all way conditionals from file
+pub fn read_all<P: AsRef<Path>>(path: P) -> Result<Vec<WayConditional>> {
+    let file = open_artifact(path.as_ref())
+        .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
+    read_all_from_reader(file).with_context(|| format!("reading {}", path.as_ref().display()))
+}
+
+/// Read way conditionals from an in-memory byte slice (mmap-backed bundle).
+pub fn read_all_from_bytes(bytes: &[u8]) -> Result<Vec<WayConditional>> {
+    read_all_from_reader(std::io::Cursor::new(bytes))
+}
+
+fn read_all_from_reader<R: Read>(mut file: R) -> Result<Vec<WayConditional>> {
+    let mut header = vec![0u8; HEADER_SIZE];
+    file.read_exact(&mut header)?;
+
+    let magic = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    anyhow::ensure!(
+        magic == MAGIC,
+        "Bad magic in way_conditionals: 0x{:08X} (expected 0x{:08X})",
+        magic,
+        MAGIC
+    );
+
+    let version = u16::from_le_bytes([header[4], header[5]]);
+    anyhow::ensure!(
+        version == VERSION,
+        "Unsupported way_conditionals version {} (expected {})",
+        version,
+        VERSION
+    );
+
+    let count = u64::from_le_bytes(header[8..16].try_into().unwrap());
+
+    let mut body_digest = Digest::new();
+    let mut file_digest = Digest::new();
+    file_digest.update(&header);
+
+    let mut conditionals = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let mut record = vec![0u8; RECORD_SIZE];
+        file.read_exact(&mut record)?;
+        body_digest.update(&record);
+        file_digest.update(&record);
+        conditionals.push(decode_record(&record)?);
+    }
+
+    let computed_body_crc = body_digest.finalize();
+    let computed_file_crc = file_digest.finalize();
+
+    let mut footer = [0u8; 16];
+    file.read_exact(&mut footer)?;
+    let stored_body_crc = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+    let stored_file_crc = u64::from_le_bytes(footer[8..16].try_into().unwrap());
+    anyhow::ensure!(
+        computed_body_crc == stored_body_crc && computed_file_crc == stored_file_crc,
+        "CRC64 mismatch in way_conditionals: body 0x{:016X}/0x{:016X}, file 0x{:016X}/0x{:016X}",
+        computed_body_crc,
+        stored_body_crc,
+        computed_file_crc,
+        stored_file_crc
+    );
+
+    Ok(conditionals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::conditional::parse_opening_hours;
+
+    #[test]
+    fn test_round_trip() {
+        use tempfile::NamedTempFile;
+        let tmp = NamedTempFile::new().unwrap();
+
+        let conditionals = vec![
+            WayConditional {
+                way_id: 9,
+                kind: ConditionalKind::OnewayRev,
+                schedule: parse_opening_hours("Su 00:00-24:00").unwrap(),
+            },
+            WayConditional {
+                way_id: 3,
+                kind: ConditionalKind::Deny,
+                schedule: parse_opening_hours("Mo-Fr 07:00-09:00").unwrap(),
+            },
+        ];
+        write(tmp.path(), Mode(0), &conditionals, &[0; 32], &[0; 32]).unwrap();
+
+        let read_back = read_all(tmp.path()).unwrap();
+        assert_eq!(read_back.len(), 2);
+        assert_eq!(read_back[0], conditionals[1]);
+        assert_eq!(read_back[1], conditionals[0]);
+        assert!(read_back[1].schedule.contains(6, 23 * 60 + 45));
+    }
+}
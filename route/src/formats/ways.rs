@@ -1,80 +1,159 @@
 //! ways.raw format - way geometry and tags with dictionary encoding
+//!
+//! Version 1 records are `way_id | n_nodes | nodes | n_tags | tags`.
+//! Version 2 (written only when ways carry [`ElementMeta`]) inserts
+//! `version: i32 | timestamp: i64` right after `way_id`; version 0
+//! marks an element whose metadata is unknown.
 
 use anyhow::Result;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::collections::{BTreeSet, HashMap};
+use std::io::{BufReader, Read, Write};
 use std::path::Path;
 
 use super::crc;
+use super::zstd_compress::{
+    artifact_len, create_artifact, open_artifact, read_artifact, read_artifact_tail,
+};
 
 const MAGIC: u32 = 0x57415953; // "WAYS"
 const VERSION: u16 = 1;
+const VERSION_META: u16 = 2;
+/// Bytes a v2 record spends on [`ElementMeta`].
+const META_SIZE: u64 = 12;
+
+/// OSM version and timestamp (unix seconds) of a way or relation,
+/// kept by `step1-ingest --keep-metadata` so updates can tell stale
+/// changes apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ElementMeta {
+    pub version: i32,
+    pub timestamp: i64,
+}
+
+impl ElementMeta {
+    pub(crate) fn encode(meta: Option<Self>) -> [u8; META_SIZE as usize] {
+        let meta = meta.unwrap_or(Self {
+            version: 0,
+            timestamp: 0,
+        });
+        let mut bytes = [0u8; META_SIZE as usize];
+        bytes[..4].copy_from_slice(&meta.version.to_le_bytes());
+        bytes[4..].copy_from_slice(&meta.timestamp.to_le_bytes());
+        bytes
+    }
+
+    pub(crate) fn decode(bytes: &[u8]) -> Option<Self> {
+        let version = i32::from_le_bytes(bytes[..4].try_into().unwrap());
+        (version != 0).then(|| Self {
+            version,
+            timestamp: i64::from_le_bytes(bytes[4..12].try_into().unwrap()),
+        })
+    }
+}
 
 #[derive(Clone)]
 pub struct Way {
     pub id: i64,
     pub nodes: Vec<i64>,
     pub tags: Vec<(String, String)>,
+    pub meta: Option<ElementMeta>,
 }
 
 pub struct WaysFile;
 
+/// What the ways.raw header and tag dictionaries need, accumulated one
+/// way at a time so [`WaysFile::write_sorted`] can stream the body.
+#[derive(Debug, Default)]
+pub struct WaysSummary {
+    count: u64,
+    body_size: u64,
+    with_meta: bool,
+    keys: BTreeSet<String>,
+    values: BTreeSet<String>,
+}
+
+impl WaysSummary {
+    /// Account for one way.
+    pub fn add(&mut self, way: &Way) {
+        self.count += 1;
+        self.body_size += 8; // way_id
+        self.body_size += 4; // n_nodes
+        self.body_size += 8 * way.nodes.len() as u64; // nodes
+        self.body_size += 2; // n_tags
+        self.body_size += 8 * way.tags.len() as u64; // tags (k_id + v_id)
+        self.with_meta |= way.meta.is_some();
+        for (k, v) in &way.tags {
+            if !self.keys.contains(k) {
+                self.keys.insert(k.clone());
+            }
+            if !self.values.contains(v) {
+                self.values.insert(v.clone());
+            }
+        }
+    }
+
+    /// Ways accounted for.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
 impl WaysFile {
     /// Write ways.raw file
     pub fn write<P: AsRef<Path>>(path: P, ways: &[Way]) -> Result<()> {
-        if ways.is_empty() {
-            anyhow::bail!("Cannot write empty ways file");
+        let mut summary = WaysSummary::default();
+        for way in ways {
+            summary.add(way);
         }
 
-        // Build dictionaries
-        let mut keys = HashMap::new();
-        let mut values = HashMap::new();
+        // Sort ways by ID
+        let mut sorted_ways = ways.to_vec();
+        sorted_ways.sort_by_key(|w| w.id);
+
+        Self::write_sorted(path, summary, sorted_ways.into_iter().map(Ok))
+    }
 
-        for way in ways {
-            for (k, v) in &way.tags {
-                keys.insert(k.clone(), 0u32);
-                values.insert(v.clone(), 0u32);
-            }
+    /// Streaming variant of [`Self::write`] for inputs too large to
+    /// hold in memory (planet Step 1): `summary` must cover exactly the
+    /// ways `ways` yields, in ascending id order.
+    pub fn write_sorted<P: AsRef<Path>>(
+        path: P,
+        summary: WaysSummary,
+        ways: impl Iterator<Item = Result<Way>>,
+    ) -> Result<()> {
+        if summary.count == 0 {
+            anyhow::bail!("Cannot write empty ways file");
         }
 
-        // Sort dictionaries lexicographically
-        let mut sorted_keys: Vec<_> = keys.keys().cloned().collect();
-        sorted_keys.sort();
-        let key_dict: HashMap<String, u32> = sorted_keys
+        // BTreeSet iteration is the lexicographic dictionary order.
+        let key_dict: HashMap<&str, u32> = summary
+            .keys
             .iter()
             .enumerate()
-            .map(|(i, k)| (k.clone(), i as u32))
+            .map(|(i, k)| (k.as_str(), i as u32))
             .collect();
-
-        let mut sorted_values: Vec<_> = values.keys().cloned().collect();
-        sorted_values.sort();
-        let value_dict: HashMap<String, u32> = sorted_values
+        let value_dict: HashMap<&str, u32> = summary
+            .values
             .iter()
             .enumerate()
-            .map(|(i, v)| (v.clone(), i as u32))
+            .map(|(i, v)| (v.as_str(), i as u32))
             .collect();
 
-        // Sort ways by ID
-        let mut sorted_ways = ways.to_vec();
-        sorted_ways.sort_by_key(|w| w.id);
-
         // Calculate offsets BEFORE writing anything
         let header_size = 32u64; // magic(4) + version(2) + reserved(2) + count(8) + kdict_off(8) + vdict_off(8)
 
-        let mut body_size = 0u64;
-        for way in &sorted_ways {
-            body_size += 8; // way_id
-            body_size += 4; // n_nodes
-            body_size += 8 * way.nodes.len() as u64; // nodes
-            body_size += 2; // n_tags
-            body_size += 8 * way.tags.len() as u64; // tags (k_id + v_id)
-        }
-
+        // Any way with metadata switches the whole file to v2.
+        let with_meta = summary.with_meta;
+        let version = if with_meta { VERSION_META } else { VERSION };
+        let body_size = if with_meta {
+            summary.body_size + META_SIZE * summary.count
+        } else {
+            summary.body_size
+        };
         let kdict_off = header_size + body_size;
 
         let mut kdict_size = 0u64;
-        for key in &sorted_keys {
+        for key in &summary.keys {
             kdict_size += 4; // k_id
             kdict_size += 2; // len
             kdict_size += key.len() as u64; // bytes
@@ -83,16 +162,15 @@ impl WaysFile {
         let vdict_off = kdict_off + kdict_size;
 
         // Now write everything with correct offsets from the start
-        let file = File::create(path.as_ref())?;
-        let mut writer = BufWriter::new(file);
+        let mut writer = create_artifact(path.as_ref())?;
         let mut crc_digest = crc::Digest::new();
 
         // Write header with CORRECT offsets
         let mut header_bytes = Vec::new();
         header_bytes.extend_from_slice(&MAGIC.to_le_bytes());
-        header_bytes.extend_from_slice(&VERSION.to_le_bytes());
+        header_bytes.extend_from_slice(&version.to_le_bytes());
         header_bytes.extend_from_slice(&0u16.to_le_bytes()); // reserved
-        header_bytes.extend_from_slice(&(sorted_ways.len() as u64).to_le_bytes());
+        header_bytes.extend_from_slice(&summary.count.to_le_bytes());
         header_bytes.extend_from_slice(&kdict_off.to_le_bytes());
         header_bytes.extend_from_slice(&vdict_off.to_le_bytes());
 
@@ -101,13 +179,25 @@ impl WaysFile {
 
         // Write ways
         let mut ways_digest = crc::Digest::new();
-        for way in &sorted_ways {
+        let mut written = 0u64;
+        for way in ways {
+            let way = way?;
+            written += 1;
+
             // way_id
             let id_bytes = way.id.to_le_bytes();
             writer.write_all(&id_bytes)?;
             ways_digest.update(&id_bytes);
             crc_digest.update(&id_bytes);
 
+            // version + timestamp (v2)
+            if with_meta {
+                let meta_bytes = ElementMeta::encode(way.meta);
+                writer.write_all(&meta_bytes)?;
+                ways_digest.update(&meta_bytes);
+                crc_digest.update(&meta_bytes);
+            }
+
             // n_nodes
             let n_nodes = way.nodes.len() as u32;
             let n_nodes_bytes = n_nodes.to_le_bytes();
@@ -132,8 +222,8 @@ impl WaysFile {
 
             // tags
             for (k, v) in &way.tags {
-                let k_id = key_dict[k];
-                let v_id = value_dict[v];
+                let k_id = key_dict[k.as_str()];
+                let v_id = value_dict[v.as_str()];
                 let k_bytes = k_id.to_le_bytes();
                 let v_bytes = v_id.to_le_bytes();
                 writer.write_all(&k_bytes)?;
@@ -144,10 +234,15 @@ impl WaysFile {
                 crc_digest.update(&v_bytes);
             }
         }
+        anyhow::ensure!(
+            written == summary.count,
+            "ways.raw summary covers {} ways, {written} written",
+            summary.count
+        );
 
         // Write key dictionary
-        for key in &sorted_keys {
-            let k_id = key_dict[key];
+        for key in &summary.keys {
+            let k_id = key_dict[key.as_str()];
             let len = key.len() as u16;
             writer.write_all(&k_id.to_le_bytes())?;
             writer.write_all(&len.to_le_bytes())?;
@@ -158,8 +253,8 @@ impl WaysFile {
         }
 
         // Write value dictionary
-        for value in &sorted_values {
-            let v_id = value_dict[value];
+        for value in &summary.values {
+            let v_id = value_dict[value.as_str()];
             let len = value.len() as u16;
             writer.write_all(&v_id.to_le_bytes())?;
             writer.write_all(&len.to_le_bytes())?;
@@ -174,23 +269,21 @@ impl WaysFile {
         let file_crc = crc_digest.finalize();
         writer.write_all(&ways_crc.to_le_bytes())?;
         writer.write_all(&file_crc.to_le_bytes())?;
-        writer.flush()?;
+        writer.finish()?;
 
         Ok(())
     }
 
     /// Read ways.raw file and return ways with tags resolved from dictionaries
     pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Way>> {
-        let mut file = File::open(path)?;
-        let file_len = file.metadata()?.len();
+        let all_bytes = read_artifact(path.as_ref())?;
 
-        if file_len < 32 + 16 {
+        if all_bytes.len() < 32 + 16 {
             anyhow::bail!("File too short");
         }
 
         // Read header (32 bytes)
-        let mut header = [0u8; 32];
-        file.read_exact(&mut header)?;
+        let header = &all_bytes[..32];
 
         let magic = u32::from_le_bytes(header[0..4].try_into()?);
         if magic != MAGIC {
@@ -202,19 +295,15 @@ impl WaysFile {
         }
 
         let version = u16::from_le_bytes(header[4..6].try_into()?);
-        if version != VERSION {
+        if version != VERSION && version != VERSION_META {
             anyhow::bail!("Unsupported version: {}", version);
         }
+        let with_meta = version == VERSION_META;
 
         let count = u64::from_le_bytes(header[8..16].try_into()?);
         let kdict_off = u64::from_le_bytes(header[16..24].try_into()?);
         let vdict_off = u64::from_le_bytes(header[24..32].try_into()?);
 
-        // Read entire file
-        let mut all_bytes = vec![0u8; file_len as usize];
-        all_bytes[..32].copy_from_slice(&header);
-        file.read_exact(&mut all_bytes[32..])?;
-
         // Read key dictionary
         let key_dict = Self::read_dict(&all_bytes, kdict_off as usize, vdict_off as usize)?;
 
@@ -237,6 +326,15 @@ impl WaysFile {
             let way_id = i64::from_le_bytes(all_bytes[pos..pos + 8].try_into()?);
             pos += 8;
 
+            // version + timestamp (v2)
+            let meta = if with_meta {
+                let meta = ElementMeta::decode(&all_bytes[pos..pos + META_SIZE as usize]);
+                pos += META_SIZE as usize;
+                meta
+            } else {
+                None
+            };
+
             // n_nodes
             let n_nodes = u32::from_le_bytes(all_bytes[pos..pos + 4].try_into()?) as usize;
             pos += 4;
@@ -275,13 +373,15 @@ impl WaysFile {
                 id: way_id,
                 nodes,
                 tags,
+                meta,
             });
         }
 
         Ok(ways)
     }
 
-    /// Read dictionaries from ways.raw and return them with their SHA-256 hashes
+    /// Read dictionaries from ways.raw and return them with their SHA-256 hashes.
+    /// Only the header and the dictionary tail are read, not the ways.
     #[allow(clippy::type_complexity)]
     pub fn read_dictionaries<P: AsRef<Path>>(
         path: P,
@@ -291,8 +391,19 @@ impl WaysFile {
         [u8; 32],
         [u8; 32],
     )> {
-        let bytes = std::fs::read(path)?;
-        Self::read_dictionaries_from_bytes(&bytes)
+        let path = path.as_ref();
+        let mut header = [0u8; 32];
+        open_artifact(path)?.read_exact(&mut header)?;
+        let kdict_off = u64::from_le_bytes(header[16..24].try_into()?);
+        let len = artifact_len(path)?;
+        anyhow::ensure!(
+            (32..=len.saturating_sub(16)).contains(&kdict_off),
+            "Dictionary offset {} out of bounds in {}",
+            kdict_off,
+            path.display()
+        );
+        let tail = read_artifact_tail(path, (len - kdict_off) as usize)?;
+        Self::parse_dictionaries(&header, &tail, kdict_off)
     }
 
     /// Same as `read_dictionaries` but on an in-memory slice.
@@ -311,13 +422,39 @@ impl WaysFile {
 
         let header = &all_bytes[..32];
         let kdict_off = u64::from_le_bytes(header[16..24].try_into()?);
+        anyhow::ensure!(
+            (32..=all_bytes.len() as u64 - 16).contains(&kdict_off),
+            "Dictionary offset {} out of bounds",
+            kdict_off
+        );
+        Self::parse_dictionaries(header, &all_bytes[kdict_off as usize..], kdict_off)
+    }
+
+    /// Parse both dictionaries from `dicts`, the file's bytes from
+    /// `kdict_off` to the end (footer included).
+    #[allow(clippy::type_complexity)]
+    fn parse_dictionaries(
+        header: &[u8],
+        dicts: &[u8],
+        kdict_off: u64,
+    ) -> Result<(
+        HashMap<u32, String>,
+        HashMap<u32, String>,
+        [u8; 32],
+        [u8; 32],
+    )> {
         let vdict_off = u64::from_le_bytes(header[24..32].try_into()?);
+        let vdict_start = vdict_off
+            .checked_sub(kdict_off)
+            .filter(|&start| start as usize <= dicts.len() - 16)
+            .ok_or_else(|| anyhow::anyhow!("Value dictionary offset {} out of bounds", vdict_off))?
+            as usize;
 
         // Read key dictionary
-        let key_dict = Self::read_dict(all_bytes, kdict_off as usize, vdict_off as usize)?;
+        let key_dict = Self::read_dict(dicts, 0, vdict_start)?;
 
         // Read value dictionary
-        let val_dict = Self::read_dict(all_bytes, vdict_off as usize, all_bytes.len() - 16)?;
+        let val_dict = Self::read_dict(dicts, vdict_start, dicts.len() - 16)?;
 
         // Compute SHA-256 of dictionaries
         let key_sha256 = Self::compute_dict_sha256(&key_dict);
@@ -359,30 +496,66 @@ impl WaysFile {
     pub fn stream_ways<P: AsRef<Path>>(
         path: P,
     ) -> Result<impl Iterator<Item = Result<(i64, Vec<u32>, Vec<u32>, Vec<i64>)>>> {
-        use std::io::{BufReader, Seek, SeekFrom};
-
-        let mut file = File::open(path)?;
-        let _file_len = file.metadata()?.len();
+        let mut file = open_artifact(path.as_ref())?;
 
         // Read header
         let mut header = [0u8; 32];
         file.read_exact(&mut header)?;
 
+        let with_meta = Self::header_with_meta(&header)?;
         let count = u64::from_le_bytes(header[8..16].try_into()?);
         let kdict_off = u64::from_le_bytes(header[16..24].try_into()?);
 
-        // Position at start of ways data
-        file.seek(SeekFrom::Start(32))?;
-
+        // The reader now sits at the start of ways data (offset 32).
         let reader = BufReader::with_capacity(1024 * 1024, file); // 1MB buffer
 
         Ok(WayStreamIterator {
             reader,
             remaining: count,
+            with_meta,
             _end_offset: kdict_off,
         })
     }
 
+    /// Same as `stream_ways`, but also yields each way's [`ElementMeta`]
+    /// (`None` for v1 files or ways ingested without metadata).
+    #[allow(clippy::type_complexity)]
+    pub fn stream_ways_with_meta<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<
+        impl Iterator<Item = Result<((i64, Vec<u32>, Vec<u32>, Vec<i64>), Option<ElementMeta>)>>,
+    > {
+        let mut file = open_artifact(path.as_ref())?;
+        let mut header = [0u8; 32];
+        file.read_exact(&mut header)?;
+
+        let with_meta = Self::header_with_meta(&header)?;
+        let count = u64::from_le_bytes(header[8..16].try_into()?);
+        let kdict_off = u64::from_le_bytes(header[16..24].try_into()?);
+
+        let mut iter = WayStreamIterator {
+            reader: BufReader::with_capacity(1024 * 1024, file),
+            remaining: count,
+            with_meta,
+            _end_offset: kdict_off,
+        };
+        Ok(std::iter::from_fn(move || iter.next_with_meta()))
+    }
+
+    /// Validate the header magic/version and report whether records carry
+    /// [`ElementMeta`].
+    fn header_with_meta(header: &[u8]) -> Result<bool> {
+        let magic = u32::from_le_bytes(header[0..4].try_into()?);
+        if magic != MAGIC {
+            anyhow::bail!("Invalid magic number");
+        }
+        match u16::from_le_bytes(header[4..6].try_into()?) {
+            VERSION => Ok(false),
+            VERSION_META => Ok(true),
+            version => anyhow::bail!("Unsupported version: {}", version),
+        }
+    }
+
     /// Same as `stream_ways` but reads from an in-memory byte slice. The
     /// returned iterator borrows from `bytes`; the caller must keep the
     /// underlying buffer (e.g. mmap) alive for the iterator's lifetime.
@@ -394,6 +567,7 @@ impl WaysFile {
             anyhow::bail!("ways.raw byte slice too short");
         }
         let header = &bytes[..32];
+        let with_meta = Self::header_with_meta(header)?;
         let count = u64::from_le_bytes(header[8..16].try_into()?);
         let kdict_off = u64::from_le_bytes(header[16..24].try_into()?);
 
@@ -409,6 +583,7 @@ impl WaysFile {
         Ok(WayStreamIterator {
             reader,
             remaining: count,
+            with_meta,
             _end_offset: kdict_off,
         })
     }
@@ -434,26 +609,18 @@ impl WaysFile {
 
     /// Verify checksums in ways.raw file
     pub fn verify<P: AsRef<Path>>(path: P) -> Result<()> {
-        let mut file = File::open(path)?;
+        let bytes = read_artifact(path.as_ref())?;
 
-        // Read entire file except footer
-        let file_len = file.metadata()?.len();
-        if file_len < 28 + 16 {
+        // Split off the footer
+        if bytes.len() < 28 + 16 {
             anyhow::bail!("File too short");
         }
-
-        let content_len = file_len - 16;
-        let mut content = vec![0u8; content_len as usize];
-        file.read_exact(&mut content)?;
-
-        // Read footer
-        let mut footer = [0u8; 16];
-        file.read_exact(&mut footer)?;
+        let (content, footer) = bytes.split_at(bytes.len() - 16);
 
         let stored_file_crc = u64::from_le_bytes(footer[8..16].try_into()?);
 
         // Verify file CRC
-        let computed_file_crc = crc::checksum(&content);
+        let computed_file_crc = crc::checksum(content);
         if computed_file_crc != stored_file_crc {
             anyhow::bail!(
                 "File CRC mismatch: expected {:016x}, got {:016x}",
@@ -471,13 +638,15 @@ impl WaysFile {
 pub struct WayStreamIterator<R: Read> {
     reader: BufReader<R>,
     remaining: u64,
+    with_meta: bool,
     _end_offset: u64,
 }
 
-impl<R: Read> Iterator for WayStreamIterator<R> {
-    type Item = Result<(i64, Vec<u32>, Vec<u32>, Vec<i64>)>;
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl<R: Read> WayStreamIterator<R> {
+    #[allow(clippy::type_complexity)]
+    fn next_with_meta(
+        &mut self,
+    ) -> Option<Result<((i64, Vec<u32>, Vec<u32>, Vec<i64>), Option<ElementMeta>)>> {
         if self.remaining == 0 {
             return None;
         }
@@ -491,6 +660,17 @@ impl<R: Read> Iterator for WayStreamIterator<R> {
         }
         let way_id = i64::from_le_bytes(buf8);
 
+        // Read version + timestamp (v2)
+        let meta = if self.with_meta {
+            let mut buf = [0u8; META_SIZE as usize];
+            if let Err(e) = self.reader.read_exact(&mut buf) {
+                return Some(Err(e.into()));
+            }
+            ElementMeta::decode(&buf)
+        } else {
+            None
+        };
+
         // Read n_nodes
         let mut buf4 = [0u8; 4];
         if let Err(e) = self.reader.read_exact(&mut buf4) {
@@ -532,7 +712,16 @@ impl<R: Read> Iterator for WayStreamIterator<R> {
             vals.push(v_id);
         }
 
-        Some(Ok((way_id, keys, vals, nodes)))
+        Some(Ok(((way_id, keys, vals, nodes), meta)))
+    }
+}
+
+impl<R: Read> Iterator for WayStreamIterator<R> {
+    type Item = Result<(i64, Vec<u32>, Vec<u32>, Vec<i64>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_meta()
+            .map(|record| record.map(|(way, _)| way))
     }
 }
 
@@ -551,16 +740,79 @@ mod tests {
                     ("highway".to_string(), "residential".to_string()),
                     ("name".to_string(), "Main St".to_string()),
                 ],
+                meta: None,
             },
             Way {
                 id: 50,
                 nodes: vec![4, 5],
                 tags: vec![("highway".to_string(), "primary".to_string())],
+                meta: None,
             },
         ];
 
         let tmpfile = NamedTempFile::new().unwrap();
         WaysFile::write(tmpfile.path(), &ways).unwrap();
         WaysFile::verify(tmpfile.path()).unwrap();
+
+        let (keys, vals, key_sha, val_sha) = WaysFile::read_dictionaries(tmpfile.path()).unwrap();
+        let bytes = std::fs::read(tmpfile.path()).unwrap();
+        let from_bytes = WaysFile::read_dictionaries_from_bytes(&bytes).unwrap();
+        assert_eq!((&keys, &vals), (&from_bytes.0, &from_bytes.1));
+        assert_eq!((key_sha, val_sha), (from_bytes.2, from_bytes.3));
+        let mut names: Vec<_> = keys.values().map(String::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["highway", "name"]);
+        assert_eq!(vals.len(), 3);
+    }
+
+    #[test]
+    fn test_ways_v2_metadata_round_trip() {
+        let meta = ElementMeta {
+            version: 7,
+            timestamp: 1_700_000_000,
+        };
+        let ways = vec![
+            Way {
+                id: 2,
+                nodes: vec![1, 2],
+                tags: vec![("highway".to_string(), "primary".to_string())],
+                meta: Some(meta),
+            },
+            Way {
+                id: 1,
+                nodes: vec![3, 4],
+                tags: vec![("highway".to_string(), "service".to_string())],
+                meta: None,
+            },
+        ];
+
+        let tmpfile = NamedTempFile::new().unwrap();
+        WaysFile::write(tmpfile.path(), &ways).unwrap();
+        WaysFile::verify(tmpfile.path()).unwrap();
+        assert_eq!(
+            std::fs::read(tmpfile.path()).unwrap()[4],
+            VERSION_META as u8
+        );
+
+        let read = WaysFile::read(tmpfile.path()).unwrap();
+        let metas: Vec<_> = read.iter().map(|w| (w.id, w.meta)).collect();
+        assert_eq!(metas, vec![(1, None), (2, Some(meta))]);
+
+        let streamed: Vec<_> = WaysFile::stream_ways_with_meta(tmpfile.path())
+            .unwrap()
+            .map(|r| {
+                r.map(|((id, _, _, nodes), meta)| (id, nodes, meta))
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            streamed,
+            vec![(1, vec![3, 4], None), (2, vec![1, 2], Some(meta))]
+        );
+        let plain: Vec<_> = WaysFile::stream_ways(tmpfile.path())
+            .unwrap()
+            .map(|r| r.unwrap().0)
+            .collect();
+        assert_eq!(plain, vec![1, 2]);
     }
 }
@@ -0,0 +1,227 @@
+//! changes.bin format - Per-EBG-node time weight changes for one mode
+//!
+//! Input to `step8-customize --delta` and the server's delta hook: only the
+//! listed nodes get a new weight, everything else keeps the weights the
+//! current `cch.w.<mode>.u32` was customized from.
+//!
+//! Format (little-endian):
+//!
+//! Header (16 bytes):
+//!   magic:       u32 = 0x57444C54  // "WDLT"
+//!   version:     u16 = 1
+//!   mode:        u8  = {0=car,1=bike,2=foot,...}
+//!   reserved:    u8  = 0
+//!   count:       u32 = n_changes
+//!   reserved:    u32 = 0
+//!
+//! Body (count * 8 bytes, ascending unique `ebg_node`):
+//!   u32 ebg_node   // original EBG node id
+//!   u32 weight_s   // new weight in seconds (0 = closed)
+//!
+//! Footer (16 bytes):
+//!   body_crc64:  u64
+//!   file_crc64:  u64
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::crc::Digest;
+use crate::profile_abi::Mode;
+
+const MAGIC: u32 = 0x57444C54; // "WDLT"
+const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeightDelta {
+    pub mode: Mode,
+    /// `(ebg_node, weight_s)`, sorted by node id without duplicates.
+    pub changes: Vec<(u32, u32)>,
+}
+
+impl WeightDelta {
+    /// Build a delta, sorting the changes and rejecting duplicate nodes.
+    pub fn new(mode: Mode, mut changes: Vec<(u32, u32)>) -> Result<Self> {
+        changes.sort_unstable_by_key(|&(node, _)| node);
+        if let Some(w) = changes.windows(2).find(|w| w[0].0 == w[1].0) {
+            anyhow::bail!("duplicate change for EBG node {}", w[0].0);
+        }
+        Ok(Self { mode, changes })
+    }
+
+    /// Write the new weights into `weights` (indexed by EBG node) and return
+    /// the nodes whose weight actually changed.
+    pub fn apply(&self, weights: &mut [u32]) -> Result<Vec<u32>> {
+        let mut changed = Vec::with_capacity(self.changes.len());
+        let n_weights = weights.len();
+        for &(node, weight) in &self.changes {
+            let slot = weights.get_mut(node as usize).with_context(|| {
+                format!(
+                    "EBG node {} out of range ({} node weights)",
+                    node, n_weights
+                )
+            })?;
+            if *slot != weight {
+                *slot = weight;
+                changed.push(node);
+            }
+        }
+        Ok(changed)
+    }
+}
+
+fn header_bytes(data: &WeightDelta) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_SIZE);
+    header.extend_from_slice(&MAGIC.to_le_bytes());
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.push(data.mode.0);
+    header.push(0); // reserved
+    header.extend_from_slice(&(data.changes.len() as u32).to_le_bytes());
+    header.extend_from_slice(&[0u8; 4]); // reserved
+    header
+}
+
+/// Write changes.bin file
+pub fn write<P: AsRef<Path>>(path: P, data: &WeightDelta) -> Result<()> {
+    let file = File::create(path.as_ref())
+        .with_context(|| format!("Failed to create {}", path.as_ref().display()))?;
+    let mut writer = BufWriter::new(file);
+
+    let header = header_bytes(data);
+    writer.write_all(&header)?;
+
+    let mut body_digest = Digest::new();
+    let mut file_digest = Digest::new();
+    file_digest.update(&header);
+    for &(node, weight) in &data.changes {
+        for bytes in [node.to_le_bytes(), weight.to_le_bytes()] {
+            body_digest.update(&bytes);
+            file_digest.update(&bytes);
+            writer.write_all(&bytes)?;
+        }
+    }
+
+    writer.write_all(&body_digest.finalize().to_le_bytes())?;
+    writer.write_all(&file_digest.finalize().to_le_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read changes.bin file
+pub fn read_all<P: AsRef<Path>>(path: P) -> Result<WeightDelta> {
+    let file = File::open(path.as_ref())
+        .with_context(|| format!("Failed to open {}", path.as_ref().display()))?;
+    read_all_from_reader(file).with_context(|| format!("reading {}", path.as_ref().display()))
+}
+
+/// Read changes.bin from an in-memory byte slice (e.g. a request body).
+pub fn read_all_from_bytes(bytes: &[u8]) -> Result<WeightDelta> {
+    read_all_from_reader(std::io::Cursor::new(bytes))
+}
+
+fn read_all_from_reader<R: std::io::Read>(mut file: R) -> Result<WeightDelta> {
+    let mut header = [0u8; HEADER_SIZE];
+    file.read_exact(&mut header)
+        .context("changes.bin shorter than its header")?;
+
+    let magic = u32::from_le_bytes(header[0..4].try_into().unwrap());
+    anyhow::ensure!(
+        magic == MAGIC,
+        "Invalid magic: expected 0x{:08x}, got 0x{:08x}",
+        MAGIC,
+        magic
+    );
+
+    let version = u16::from_le_bytes(header[4..6].try_into().unwrap());
+    anyhow::ensure!(
+        version == VERSION,
+        "Unsupported changes.bin version: {} (expected {})",
+        version,
+        VERSION
+    );
+
+    let mode_byte = header[6];
+    anyhow::ensure!(
+        (mode_byte as usize) < crate::profile_abi::MAX_MODES,
+        "Invalid mode: {}",
+        mode_byte
+    );
+
+    let count = u32::from_le_bytes(header[8..12].try_into().unwrap());
+
+    let mut body_digest = Digest::new();
+    let mut file_digest = Digest::new();
+    file_digest.update(&header);
+    let mut changes = Vec::new();
+    for _ in 0..count {
+        let mut bytes = [0u8; 8];
+        file.read_exact(&mut bytes)
+            .context("changes.bin body truncated")?;
+        body_digest.update(&bytes);
+        file_digest.update(&bytes);
+        changes.push((
+            u32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+            u32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+        ));
+    }
+
+    let mut footer = [0u8; 16];
+    file.read_exact(&mut footer)
+        .context("changes.bin footer truncated")?;
+    let stored_body_crc = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+    let stored_file_crc = u64::from_le_bytes(footer[8..16].try_into().unwrap());
+    let computed_body_crc = body_digest.finalize();
+    let computed_file_crc = file_digest.finalize();
+    anyhow::ensure!(
+        computed_body_crc == stored_body_crc && computed_file_crc == stored_file_crc,
+        "CRC64 mismatch in changes.bin: body 0x{:016X}/0x{:016X}, file 0x{:016X}/0x{:016X}",
+        computed_body_crc,
+        stored_body_crc,
+        computed_file_crc,
+        stored_file_crc
+    );
+
+    WeightDelta::new(Mode(mode_byte), changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_sorts_changes() {
+        let delta = WeightDelta::new(Mode(1), vec![(7, 0), (2, 45), (9, 12)]).unwrap();
+        assert_eq!(delta.changes, vec![(2, 45), (7, 0), (9, 12)]);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("changes.bin");
+        write(&path, &delta).unwrap();
+        assert_eq!(read_all(&path).unwrap(), delta);
+    }
+
+    #[test]
+    fn rejects_duplicates_and_corruption() {
+        assert!(WeightDelta::new(Mode(0), vec![(3, 1), (3, 2)]).is_err());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("changes.bin");
+        write(&path, &WeightDelta::new(Mode(0), vec![(3, 10)]).unwrap()).unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[HEADER_SIZE + 4] ^= 1;
+        let err = read_all_from_bytes(&bytes).unwrap_err();
+        assert!(err.to_string().contains("CRC64"), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn apply_reports_only_real_changes() {
+        let delta = WeightDelta::new(Mode(0), vec![(0, 5), (2, 0)]).unwrap();
+        let mut weights = vec![5, 8, 9];
+        assert_eq!(delta.apply(&mut weights).unwrap(), vec![2]);
+        assert_eq!(weights, vec![5, 8, 0]);
+
+        let out_of_range = WeightDelta::new(Mode(0), vec![(3, 1)]).unwrap();
+        assert!(out_of_range.apply(&mut weights).is_err());
+    }
+}
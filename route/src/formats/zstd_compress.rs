@@ -31,9 +31,23 @@
 //! went through [`decompress_if_zstd`] will see no magic and parse the
 //! raw payload. The minimum-savings guard avoids hurting payloads that
 //! are already compact (e.g. tightly packed bitsets).
+//!
+//! # Step artifacts
+//!
+//! The same magic sniff applies to whole Step 1–4 files (nodes.sa,
+//! ways.raw, nbg.geo, …), which for planet are hundreds of GB raw.
+//! [`create_artifact`] wraps the file in a single zstd frame when
+//! [`COMPRESS_ARTIFACTS_ENV`] selects it; [`open_artifact`] decodes
+//! such files as a stream. The format header inside the frame is
+//! unchanged — the frame magic is the "compressed" version marker —
+//! so raw artifacts stay byte-identical to older builds and every
+//! reader accepts both.
 
 use anyhow::Result;
 use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
 
 /// First four bytes of every zstd-compressed stream — RFC 8478 §3.1.1
 /// ("Zstandard frames"). Reader uses this as a sniff hint: if the
@@ -101,6 +115,182 @@ pub fn decompress_if_zstd(bytes: &[u8]) -> Result<Cow<'_, [u8]>> {
 /// inputs claiming planet-scale sizes.
 const MAX_DECOMPRESSED: usize = 16 * 1024 * 1024 * 1024;
 
+/// Comma-separated artifact file names (`nodes.sa,ways.raw`) that
+/// Step 1–4 writers compress, or `all`. Unset means every artifact is
+/// written raw.
+pub const COMPRESS_ARTIFACTS_ENV: &str = "BUTTERFLY_COMPRESS_ARTIFACTS";
+
+/// Whether [`COMPRESS_ARTIFACTS_ENV`] selects the artifact at `path`.
+pub fn compress_artifact(path: &Path) -> bool {
+    let Ok(list) = std::env::var(COMPRESS_ARTIFACTS_ENV) else {
+        return false;
+    };
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    list.split(',')
+        .map(str::trim)
+        .any(|entry| entry == "all" || entry == name)
+}
+
+/// Buffered writer for a Step 1–4 artifact, raw or zstd-framed.
+/// [`ArtifactWriter::finish`] must be called: dropping a compressed
+/// writer leaves a truncated frame.
+pub enum ArtifactWriter {
+    Raw(BufWriter<File>),
+    Zstd(zstd::stream::write::Encoder<'static, BufWriter<File>>),
+}
+
+impl ArtifactWriter {
+    /// Flush buffered data and, when compressed, close the frame.
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Raw(mut w) => w.flush(),
+            Self::Zstd(e) => e.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for ArtifactWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Raw(w) => w.write(buf),
+            Self::Zstd(e) => e.write(buf),
+        }
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Raw(w) => w.write_all(buf),
+            Self::Zstd(e) => e.write_all(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Raw(w) => w.flush(),
+            Self::Zstd(e) => e.flush(),
+        }
+    }
+}
+
+/// Create `path`, compressed if [`compress_artifact`] selects it.
+pub fn create_artifact(path: &Path) -> io::Result<ArtifactWriter> {
+    create_artifact_with(path, compress_artifact(path))
+}
+
+/// Create `path`, compressed or raw regardless of the environment.
+pub fn create_artifact_with(path: &Path, compress: bool) -> io::Result<ArtifactWriter> {
+    let file = BufWriter::with_capacity(1 << 20, File::create(path)?);
+    if !compress {
+        return Ok(ArtifactWriter::Raw(file));
+    }
+    let mut encoder = zstd::stream::write::Encoder::new(file, COMPRESS_LEVEL)?;
+    encoder.include_checksum(true)?;
+    Ok(ArtifactWriter::Zstd(encoder))
+}
+
+/// Sequential reader over an artifact's decoded bytes.
+pub enum ArtifactReader {
+    Raw(BufReader<File>),
+    Zstd(BufReader<zstd::stream::read::Decoder<'static, BufReader<File>>>),
+}
+
+impl ArtifactReader {
+    pub fn is_compressed(&self) -> bool {
+        matches!(self, Self::Zstd(_))
+    }
+}
+
+impl Read for ArtifactReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Raw(r) => r.read(buf),
+            Self::Zstd(r) => r.read(buf),
+        }
+    }
+}
+
+impl BufRead for ArtifactReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Self::Raw(r) => r.fill_buf(),
+            Self::Zstd(r) => r.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Self::Raw(r) => r.consume(amt),
+            Self::Zstd(r) => r.consume(amt),
+        }
+    }
+}
+
+/// Open an artifact written by [`create_artifact`], decoding it on the
+/// fly when it starts with the zstd magic.
+pub fn open_artifact(path: &Path) -> io::Result<ArtifactReader> {
+    let mut file = BufReader::with_capacity(1 << 20, File::open(path)?);
+    if !file.fill_buf()?.starts_with(&ZSTD_MAGIC) {
+        return Ok(ArtifactReader::Raw(file));
+    }
+    let decoder = zstd::stream::read::Decoder::with_buffer(file)?;
+    Ok(ArtifactReader::Zstd(BufReader::with_capacity(
+        1 << 20,
+        decoder,
+    )))
+}
+
+/// `std::fs::read` for artifacts: the whole decoded contents.
+pub fn read_artifact(path: &Path) -> io::Result<Vec<u8>> {
+    let mut reader = open_artifact(path)?;
+    let mut bytes = match &reader {
+        ArtifactReader::Raw(r) => Vec::with_capacity(r.get_ref().metadata()?.len() as usize),
+        ArtifactReader::Zstd(_) => Vec::new(),
+    };
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Decoded size of an artifact. Free for raw files; compressed ones
+/// are streamed through once.
+pub fn artifact_len(path: &Path) -> io::Result<u64> {
+    match open_artifact(path)? {
+        ArtifactReader::Raw(r) => Ok(r.get_ref().metadata()?.len()),
+        mut reader => io::copy(&mut reader, &mut io::sink()),
+    }
+}
+
+/// Last `n` decoded bytes of an artifact (e.g. a CRC footer). Raw
+/// files seek; compressed ones are streamed through once.
+pub fn read_artifact_tail(path: &Path, n: usize) -> io::Result<Vec<u8>> {
+    let mut tail = Vec::with_capacity(2 * n);
+    match open_artifact(path)? {
+        ArtifactReader::Raw(mut r) => {
+            use std::io::{Seek, SeekFrom};
+            r.seek(SeekFrom::End(-(n as i64)))?;
+            r.read_to_end(&mut tail)?;
+        }
+        mut reader => loop {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            let len = chunk.len();
+            tail.extend_from_slice(&chunk[len.saturating_sub(n)..]);
+            tail.drain(..tail.len().saturating_sub(n));
+            reader.consume(len);
+        },
+    }
+    if tail.len() < n {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("{} is shorter than {n} bytes", path.display()),
+        ));
+    }
+    Ok(tail)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +365,44 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn artifact_round_trip_raw_and_compressed() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let body: Vec<u8> = (0..200_000u32)
+            .flat_map(|i| (i / 7).to_le_bytes())
+            .collect();
+        for compress in [false, true] {
+            let path = dir.path().join("nodes.sa");
+            let mut w = create_artifact_with(&path, compress)?;
+            w.write_all(&body)?;
+            w.finish()?;
+
+            let on_disk = std::fs::read(&path)?;
+            assert_eq!(on_disk.starts_with(&ZSTD_MAGIC), compress);
+            if !compress {
+                assert_eq!(on_disk, body, "raw artifacts are written verbatim");
+            } else {
+                assert!(on_disk.len() < body.len());
+            }
+            assert_eq!(open_artifact(&path)?.is_compressed(), compress);
+            assert_eq!(read_artifact(&path)?, body);
+            assert_eq!(artifact_len(&path)?, body.len() as u64);
+            assert_eq!(read_artifact_tail(&path, 8)?, &body[body.len() - 8..]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn artifact_rejects_truncated_frame() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("ways.raw");
+        let mut w = create_artifact_with(&path, true)?;
+        w.write_all(&vec![1u8; 1 << 16])?;
+        w.finish()?;
+        let bytes = std::fs::read(&path)?;
+        std::fs::write(&path, &bytes[..bytes.len() - 8])?;
+        assert!(read_artifact(&path).is_err());
+        Ok(())
+    }
 }
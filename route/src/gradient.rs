@@ -0,0 +1,234 @@
+//! Gradient-aware travel times for Step 5.
+//!
+//! Given SRTM elevation, each NBG edge is sampled along its polyline and
+//! every sampled segment's grade is turned into a travel-time multiplier
+//! by a per-mode [`GradientModel`]. The length-weighted mean multiplier
+//! for each travel direction scales the edge's travel time in step 5
+//! (penalties are not scaled).
+//!
+//! Multipliers are stored in permille (1000 = flat) so step 5 and its
+//! lock validation compute identical integer weights. Edges without
+//! elevation coverage stay at 1000, and a build without `--srtm-dir` (or
+//! with `--no-gradient`) is byte-identical to a flat-profile build.
+//!
+//! ## Models
+//!
+//! - [`GradientModel::Tobler`] — Tobler's hiking function,
+//!   `v = 6·exp(-3.5·|g + 0.05|)` km/h, normalized to its flat speed.
+//!   Gentle descents are slightly faster, steep ones slow down again.
+//! - [`GradientModel::BikePower`] — constant-power cyclist (rolling
+//!   resistance + gravity + aerodynamic drag) holding the power needed for
+//!   18 km/h on the flat; speed is capped at 45 km/h downhill and floored
+//!   at walking pace uphill.
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::formats::{EbgNode, EbgNodes, NbgGeo};
+use crate::server::elevation::ElevationData;
+
+/// Multiplier for a flat edge.
+pub const FLAT_PERMILLE: u16 = 1000;
+
+/// Distance between elevation samples along an edge (SRTM is ~30-90 m).
+const SAMPLE_INTERVAL_M: f64 = 50.0;
+/// Grades beyond this are DEM artifacts (bridges, cliffs), not roads.
+const MAX_GRADE: f64 = 0.3;
+/// Largest multiplier we emit (keeps weights far from u32 saturation).
+const MAX_FACTOR: f64 = 10.0;
+
+// Bike power model constants (rider + bike).
+const BIKE_MASS_KG: f64 = 90.0;
+const BIKE_CRR: f64 = 0.005;
+const BIKE_CDA_M2: f64 = 0.45;
+const AIR_DENSITY: f64 = 1.225;
+const GRAVITY: f64 = 9.81;
+const BIKE_FLAT_MPS: f64 = 5.0;
+const BIKE_MAX_MPS: f64 = 12.5;
+const BIKE_MIN_MPS: f64 = 1.2;
+
+/// How a mode's speed reacts to the terrain grade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientModel {
+    Tobler,
+    BikePower,
+}
+
+impl GradientModel {
+    /// Parse a model name; `none` yields `Ok(None)`.
+    pub fn parse(s: &str) -> Result<Option<Self>> {
+        match s.to_ascii_lowercase().as_str() {
+            "tobler" => Ok(Some(GradientModel::Tobler)),
+            "bike-power" | "bike_power" => Ok(Some(GradientModel::BikePower)),
+            "none" | "flat" => Ok(None),
+            other => anyhow::bail!(
+                "unknown gradient model '{}': supported: tobler, bike-power, none",
+                other
+            ),
+        }
+    }
+
+    /// Model applied to a mode when none is given on the command line.
+    pub fn default_for_mode(mode_name: &str) -> Option<Self> {
+        match mode_name {
+            "foot" => Some(GradientModel::Tobler),
+            "bike" => Some(GradientModel::BikePower),
+            _ => None,
+        }
+    }
+
+    /// Travel-time multiplier relative to the flat for a rise/run `grade`.
+    pub fn time_factor(self, grade: f64) -> f64 {
+        let grade = grade.clamp(-MAX_GRADE, MAX_GRADE);
+        let factor = match self {
+            // v(g)/v(0) = exp(-3.5·(|g + 0.05| - 0.05))
+            GradientModel::Tobler => (3.5 * ((grade + 0.05).abs() - 0.05)).exp(),
+            GradientModel::BikePower => BIKE_FLAT_MPS / bike_speed_mps(grade),
+        };
+        factor.clamp(1.0 / MAX_FACTOR, MAX_FACTOR)
+    }
+}
+
+/// Power (W) needed to ride at `v` m/s on `grade`.
+fn bike_power_w(v: f64, grade: f64) -> f64 {
+    v * BIKE_MASS_KG * GRAVITY * (BIKE_CRR + grade) + 0.5 * AIR_DENSITY * BIKE_CDA_M2 * v * v * v
+}
+
+/// Speed at which the flat-cruising power is held on `grade`. Power is
+/// negative then increasing in `v`, so the positive root is unique.
+fn bike_speed_mps(grade: f64) -> f64 {
+    let target = bike_power_w(BIKE_FLAT_MPS, 0.0);
+    if bike_power_w(BIKE_MAX_MPS, grade) <= target {
+        return BIKE_MAX_MPS;
+    }
+    let (mut lo, mut hi) = (0.0, BIKE_MAX_MPS);
+    for _ in 0..50 {
+        let mid = 0.5 * (lo + hi);
+        if bike_power_w(mid, grade) < target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    hi.max(BIKE_MIN_MPS)
+}
+
+/// `(forward, reverse)` permille multipliers for one polyline; flat when
+/// fewer than two points have elevation.
+fn polyline_factors(
+    lat_fxp: &[i32],
+    lon_fxp: &[i32],
+    elevation: &ElevationData,
+    model: GradientModel,
+) -> (u16, u16) {
+    let path: Vec<[f64; 2]> = lat_fxp
+        .iter()
+        .zip(lon_fxp)
+        .map(|(&lat, &lon)| [lat as f64 * 1e-7, lon as f64 * 1e-7])
+        .collect();
+    let profile = elevation.elevation_profile(&path, SAMPLE_INTERVAL_M);
+
+    let (mut total_m, mut fwd, mut rev) = (0.0, 0.0, 0.0);
+    for pair in profile.windows(2) {
+        let run = pair[1].distance_m - pair[0].distance_m;
+        if run < 1.0 {
+            continue;
+        }
+        let grade = (pair[1].elevation - pair[0].elevation) / run;
+        total_m += run;
+        fwd += run * model.time_factor(grade);
+        rev += run * model.time_factor(-grade);
+    }
+    if total_m == 0.0 {
+        return (FLAT_PERMILLE, FLAT_PERMILLE);
+    }
+    let permille = |sum: f64| (sum / total_m * 1000.0).round() as u16;
+    (permille(fwd), permille(rev))
+}
+
+/// Per-EBG-node travel-time multipliers (permille) for one mode.
+pub fn ebg_time_factors(
+    ebg_nodes: &EbgNodes,
+    nbg_geo: &NbgGeo,
+    elevation: &ElevationData,
+    model: GradientModel,
+) -> Vec<u16> {
+    let per_edge: Vec<(u16, u16)> = nbg_geo
+        .polylines
+        .par_iter()
+        .map(|poly| polyline_factors(&poly.lat_fxp, &poly.lon_fxp, elevation, model))
+        .collect();
+
+    ebg_nodes
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(ebg_id, node)| {
+            let (fwd, rev) = per_edge[node.geom_idx as usize];
+            if EbgNode::is_forward(ebg_id as u32) {
+                fwd
+            } else {
+                rev
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::elevation::SrtmTile;
+
+    #[test]
+    fn test_tobler_factor() {
+        let m = GradientModel::Tobler;
+        assert!((m.time_factor(0.0) - 1.0).abs() < 1e-9);
+        // Gentle descent is the fastest point of the curve.
+        assert!(m.time_factor(-0.05) < 1.0);
+        assert!(m.time_factor(0.1) > m.time_factor(-0.1));
+        assert!(m.time_factor(-0.25) > 1.0);
+    }
+
+    #[test]
+    fn test_bike_power_factor() {
+        let m = GradientModel::BikePower;
+        assert!((m.time_factor(0.0) - 1.0).abs() < 1e-6);
+        assert!(m.time_factor(0.05) > 2.0);
+        assert!(m.time_factor(-0.03) < 1.0);
+        // Capped descent speed and walking-pace floor.
+        assert!((m.time_factor(-0.2) - BIKE_FLAT_MPS / BIKE_MAX_MPS).abs() < 1e-9);
+        assert!((m.time_factor(0.3) - BIKE_FLAT_MPS / BIKE_MIN_MPS).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            GradientModel::parse("Tobler").unwrap(),
+            Some(GradientModel::Tobler)
+        );
+        assert_eq!(GradientModel::parse("none").unwrap(), None);
+        assert!(GradientModel::parse("steep").is_err());
+        assert_eq!(GradientModel::default_for_mode("car"), None);
+    }
+
+    #[test]
+    fn test_polyline_factors() {
+        // 3x3 tile over lat/lon [0, 1] with elevation rising 1000 m per
+        // degree of latitude (~0.9% grade northwards; row 0 is north).
+        let data = vec![1000, 1000, 1000, 500, 500, 500, 0, 0, 0];
+        let elevation = ElevationData::from_tiles(vec![SrtmTile::new(0, 0, 3, data)]);
+        let (fwd, rev) = polyline_factors(
+            &[1_000_000, 2_000_000],
+            &[5_000_000, 5_000_000],
+            &elevation,
+            GradientModel::BikePower,
+        );
+        assert!(fwd > FLAT_PERMILLE && rev < FLAT_PERMILLE, "{fwd} {rev}");
+
+        let none = ElevationData::empty();
+        assert_eq!(
+            polyline_factors(&[0, 1], &[0, 1], &none, GradientModel::Tobler),
+            (FLAT_PERMILLE, FLAT_PERMILLE)
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! External merge sort for Step 1 (also counts node references in Step 3).
+//!
+//! Planet has ~9 billion nodes and ~1 billion ways; sorting them in RAM
+//! needs well over 100 GB. [`ExternalSorter`] buffers records up to a
+//! memory budget, sorts each full buffer and spills it to a run file,
+//! then k-way merges the runs while the caller streams the output
+//! artifact. Inputs that fit the budget never touch the disk and come
+//! out exactly as the in-memory sort produced them.
+
+use anyhow::{Context, Result};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::formats::{ElementMeta, Way};
+
+/// Read buffer per run during the merge.
+const RUN_READ_BUFFER: usize = 1 << 20;
+
+/// A record that can be sorted by key and round-tripped through a
+/// spill file.
+pub trait SpillRecord: Sized {
+    /// Sort key. Ties keep push order only within one run.
+    type Key: Ord + Copy;
+
+    fn key(&self) -> Self::Key;
+
+    /// Approximate in-memory footprint, heap allocations included.
+    fn mem_bytes(&self) -> usize;
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()>;
+
+    /// Next record, or `None` at a clean end of file.
+    fn read_from<R: Read>(r: &mut R) -> io::Result<Option<Self>>;
+}
+
+/// Budgeted sort buffer spilling sorted runs to `spill_dir`.
+pub struct ExternalSorter<T: SpillRecord> {
+    name: &'static str,
+    spill_dir: PathBuf,
+    budget: usize,
+    buffer: Vec<T>,
+    buffered_bytes: usize,
+    runs: Vec<PathBuf>,
+    len: u64,
+}
+
+impl<T: SpillRecord> ExternalSorter<T> {
+    /// Sorter holding at most ~`budget` bytes of records in memory.
+    /// Run files are named `<name>-<n>.run` inside `spill_dir`, which
+    /// is created on the first spill.
+    pub fn new(name: &'static str, spill_dir: &Path, budget: usize) -> Self {
+        Self {
+            name,
+            spill_dir: spill_dir.to_path_buf(),
+            budget,
+            buffer: Vec::new(),
+            buffered_bytes: 0,
+            runs: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, record: T) -> Result<()> {
+        self.buffered_bytes += record.mem_bytes();
+        self.buffer.push(record);
+        self.len += 1;
+        if self.buffered_bytes >= self.budget {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Records pushed so far.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Sort the buffer and write it out as a new run.
+    fn spill(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.spill_dir).with_context(|| {
+            format!(
+                "Failed to create spill directory {}",
+                self.spill_dir.display()
+            )
+        })?;
+        let path = self
+            .spill_dir
+            .join(format!("{}-{}.run", self.name, self.runs.len()));
+        self.buffer.sort_by_key(T::key);
+
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create spill file {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        for record in self.buffer.drain(..) {
+            record.write_to(&mut writer)?;
+        }
+        writer.flush()?;
+        self.buffered_bytes = 0;
+        self.runs.push(path);
+        Ok(())
+    }
+
+    /// Number of runs spilled to disk so far.
+    pub fn spilled_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// All records in key order. Run files are deleted once the
+    /// returned iterator is dropped.
+    pub fn finish(mut self) -> Result<Sorted<T>> {
+        if self.runs.is_empty() {
+            let mut buffer = std::mem::take(&mut self.buffer);
+            buffer.sort_by_key(T::key);
+            return Ok(Sorted::Memory(buffer.into_iter()));
+        }
+        self.spill()?;
+        let runs = std::mem::take(&mut self.runs);
+        Ok(Sorted::Merge(Merge::open(runs)?))
+    }
+}
+
+impl<T: SpillRecord> Drop for ExternalSorter<T> {
+    fn drop(&mut self) {
+        // Only non-empty when `finish` was never reached (error path).
+        for path in &self.runs {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Sorted output of an [`ExternalSorter`].
+pub enum Sorted<T: SpillRecord> {
+    /// Everything fit the budget.
+    Memory(std::vec::IntoIter<T>),
+    /// K-way merge over spilled runs.
+    Merge(Merge<T>),
+}
+
+impl<T: SpillRecord> Iterator for Sorted<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Sorted::Memory(records) => records.next().map(Ok),
+            Sorted::Merge(merge) => merge.next(),
+        }
+    }
+}
+
+/// K-way merge over sorted run files.
+pub struct Merge<T: SpillRecord> {
+    paths: Vec<PathBuf>,
+    readers: Vec<BufReader<File>>,
+    heads: Vec<Option<T>>,
+    /// (key, run) of every run's current head; the run index breaks
+    /// ties so equal keys come out in spill order.
+    heap: BinaryHeap<Reverse<(T::Key, usize)>>,
+}
+
+impl<T: SpillRecord> Merge<T> {
+    fn open(paths: Vec<PathBuf>) -> Result<Self> {
+        let mut merge = Self {
+            readers: Vec::with_capacity(paths.len()),
+            heads: Vec::with_capacity(paths.len()),
+            heap: BinaryHeap::with_capacity(paths.len()),
+            paths,
+        };
+        for (run, path) in merge.paths.iter().enumerate() {
+            let file = File::open(path)
+                .with_context(|| format!("Failed to open spill file {}", path.display()))?;
+            let mut reader = BufReader::with_capacity(RUN_READ_BUFFER, file);
+            let head = T::read_from(&mut reader)?;
+            if let Some(record) = &head {
+                merge.heap.push(Reverse((record.key(), run)));
+            }
+            merge.readers.push(reader);
+            merge.heads.push(head);
+        }
+        Ok(merge)
+    }
+}
+
+impl<T: SpillRecord> Iterator for Merge<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((_, run)) = self.heap.pop()?;
+        let record = self.heads[run].take()?;
+        match T::read_from(&mut self.readers[run]) {
+            Ok(Some(next)) => {
+                self.heap.push(Reverse((next.key(), run)));
+                self.heads[run] = Some(next);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                return Some(Err(anyhow::Error::new(e).context(format!(
+                    "Failed to read spill file {}",
+                    self.paths[run].display()
+                ))));
+            }
+        }
+        Some(Ok(record))
+    }
+}
+
+impl<T: SpillRecord> Drop for Merge<T> {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// `read_exact` that reports a clean EOF before the first byte as `false`.
+fn read_first<R: Read>(r: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match r.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_string<R: Read>(r: &mut R) -> io::Result<String> {
+    let mut bytes = vec![0u8; read_u32(r)? as usize];
+    r.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write_string<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    w.write_all(&(s.len() as u32).to_le_bytes())?;
+    w.write_all(s.as_bytes())
+}
+
+/// `(id, lat, lon)` node records, sorted by id.
+/// Bare ids (referenced-node collection).
+impl SpillRecord for i64 {
+    type Key = i64;
+
+    fn key(&self) -> i64 {
+        *self
+    }
+
+    fn mem_bytes(&self) -> usize {
+        size_of::<Self>()
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.to_le_bytes())
+    }
+
+    fn read_from<R: Read>(r: &mut R) -> io::Result<Option<Self>> {
+        let mut buf = [0u8; 8];
+        Ok(read_first(r, &mut buf)?.then(|| i64::from_le_bytes(buf)))
+    }
+}
+
+impl SpillRecord for (i64, f64, f64) {
+    type Key = i64;
+
+    fn key(&self) -> i64 {
+        self.0
+    }
+
+    fn mem_bytes(&self) -> usize {
+        size_of::<Self>()
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.0.to_le_bytes())?;
+        w.write_all(&self.1.to_le_bytes())?;
+        w.write_all(&self.2.to_le_bytes())
+    }
+
+    fn read_from<R: Read>(r: &mut R) -> io::Result<Option<Self>> {
+        let mut buf = [0u8; 24];
+        if !read_first(r, &mut buf)? {
+            return Ok(None);
+        }
+        let field = |i: usize| <[u8; 8]>::try_from(&buf[i * 8..(i + 1) * 8]).unwrap();
+        Ok(Some((
+            i64::from_le_bytes(field(0)),
+            f64::from_le_bytes(field(1)),
+            f64::from_le_bytes(field(2)),
+        )))
+    }
+}
+
+/// Ways, sorted by id.
+impl SpillRecord for Way {
+    type Key = i64;
+
+    fn key(&self) -> i64 {
+        self.id
+    }
+
+    fn mem_bytes(&self) -> usize {
+        size_of::<Way>()
+            + self.nodes.capacity() * size_of::<i64>()
+            + self
+                .tags
+                .iter()
+                .map(|(k, v)| size_of::<(String, String)>() + k.capacity() + v.capacity())
+                .sum::<usize>()
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.id.to_le_bytes())?;
+        w.write_all(&ElementMeta::encode(self.meta))?;
+        w.write_all(&(self.nodes.len() as u32).to_le_bytes())?;
+        for node in &self.nodes {
+            w.write_all(&node.to_le_bytes())?;
+        }
+        w.write_all(&(self.tags.len() as u32).to_le_bytes())?;
+        for (k, v) in &self.tags {
+            write_string(w, k)?;
+            write_string(w, v)?;
+        }
+        Ok(())
+    }
+
+    fn read_from<R: Read>(r: &mut R) -> io::Result<Option<Self>> {
+        let mut id = [0u8; 8];
+        if !read_first(r, &mut id)? {
+            return Ok(None);
+        }
+        let mut meta = [0u8; 12];
+        r.read_exact(&mut meta)?;
+        let n_nodes = read_u32(r)? as usize;
+        let mut node_bytes = vec![0u8; n_nodes * 8];
+        r.read_exact(&mut node_bytes)?;
+        let nodes = node_bytes
+            .chunks_exact(8)
+            .map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        let n_tags = read_u32(r)? as usize;
+        let mut tags = Vec::with_capacity(n_tags);
+        for _ in 0..n_tags {
+            tags.push((read_string(r)?, read_string(r)?));
+        }
+        Ok(Some(Way {
+            id: i64::from_le_bytes(id),
+            nodes,
+            tags,
+            meta: ElementMeta::decode(&meta),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn way(id: i64) -> Way {
+        Way {
+            id,
+            nodes: vec![id * 10, id * 10 + 1],
+            tags: vec![("highway".to_string(), format!("v{id}"))],
+            meta: None,
+        }
+    }
+
+    #[test]
+    fn test_spills_and_merges_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let spill_dir = dir.path().join("spill");
+        // Budget of ~3 node records: 1000 nodes -> hundreds of runs.
+        let mut sorter = ExternalSorter::new("nodes", &spill_dir, 3 * 24);
+        let ids: Vec<i64> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        for &id in &ids {
+            sorter.push((id, id as f64 * 0.5, -(id as f64))).unwrap();
+        }
+        assert_eq!(sorter.len(), 1000);
+        assert!(sorter.spilled_runs() > 100);
+
+        let sorted: Vec<_> = sorter.finish().unwrap().map(Result::unwrap).collect();
+        let expected: Vec<_> = (0..1000)
+            .map(|id| (id, id as f64 * 0.5, -(id as f64)))
+            .collect();
+        assert_eq!(sorted, expected);
+        // Run files are removed with the merge.
+        assert_eq!(std::fs::read_dir(&spill_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_within_budget_stays_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sorter = ExternalSorter::new("ways", dir.path(), usize::MAX);
+        for id in [3, 1, 2] {
+            sorter.push(way(id)).unwrap();
+        }
+        assert_eq!(sorter.spilled_runs(), 0);
+        let ids: Vec<_> = sorter.finish().unwrap().map(|w| w.unwrap().id).collect();
+        assert_eq!(ids, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_way_round_trip() {
+        let mut bytes = Vec::new();
+        way(42).write_to(&mut bytes).unwrap();
+        way(7).write_to(&mut bytes).unwrap();
+        let mut reader = bytes.as_slice();
+        let first = Way::read_from(&mut reader).unwrap().unwrap();
+        assert_eq!(
+            (first.id, first.nodes, first.tags),
+            (42, way(42).nodes, way(42).tags)
+        );
+        assert_eq!(Way::read_from(&mut reader).unwrap().unwrap().id, 7);
+        assert!(Way::read_from(&mut reader).unwrap().is_none());
+
+        // A truncated record is an error, not a silent end of run.
+        let mut truncated = &bytes[..10];
+        assert!(Way::read_from(&mut truncated).is_err());
+    }
+}
@@ -1,87 +1,251 @@
 //! PBF ingestion pipeline - Step 1
 
 use anyhow::{Context, Result};
-use osmpbf::{Element, ElementReader};
+use osmpbf::Element;
 use sha2::{Digest as Sha2Digest, Sha256};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
-use crate::formats::{Member, MemberKind, Relation, RelationsFile, Way, WaysFile};
-use crate::formats::{NodeSignals, NodeSignalsFile};
+use crate::formats::mmap::{ArcCow, map_readonly};
+use crate::formats::{BarrierKind, NodeBarriers, NodeBarriersFile, NodeSignals, NodeSignalsFile};
+use crate::formats::{
+    ElementMeta, Member, MemberKind, Relation, RelationsFile, Way, WaysFile, WaysSummary,
+};
 use crate::formats::{nodes_sa, nodes_si};
 
-/// (nodes, signal_node_ids) accumulated from one PBF blob during the parallel
-/// node pass (#421). Aliased to keep the rayon closure return type within
-/// clippy's type-complexity budget.
-type NodeBlob = (Vec<(i64, f64, f64)>, Vec<i64>);
+pub mod external_sort;
+pub mod osc;
+pub mod update;
+pub mod way_filter;
+
+use external_sort::ExternalSorter;
+use way_filter::WayFilter;
+
+/// Default Step 1 sort budget, shared by the node and way sorts.
+/// Extracts that fit are sorted in memory; larger inputs (planet)
+/// spill sorted runs to disk and are k-way merged.
+pub const DEFAULT_MEMORY_BUDGET_MB: usize = 4096;
+
+/// Where Step 1 reads the PBF from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IngestInput {
+    /// Local PBF file.
+    File(PathBuf),
+    /// URL or butterfly-dl source (`europe/belgium`), streamed straight
+    /// into the decoder: no download step, no temp file.
+    Download(String),
+}
+
+impl IngestInput {
+    /// `http(s)://` URLs download; anything else is a local path.
+    pub fn parse(input: &str) -> Self {
+        if input.starts_with("http://") || input.starts_with("https://") {
+            Self::Download(input.to_string())
+        } else {
+            Self::File(PathBuf::from(input))
+        }
+    }
+}
+
+impl std::fmt::Display for IngestInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Download(source) => write!(f, "{source} (butterfly-dl)"),
+        }
+    }
+}
 
 pub struct IngestConfig {
-    pub input: PathBuf,
+    pub input: IngestInput,
     pub outdir: PathBuf,
+    /// Bytes of node + way records held in memory before spilling
+    /// sorted runs; split evenly between the two sorts.
+    pub memory_budget: usize,
+    /// Directory for spill runs; defaults to `<outdir>/.step1-spill`.
+    pub spill_dir: Option<PathBuf>,
+    /// Blob decoder threads; 0 uses every core.
+    pub threads: usize,
+    /// Keep only nodes referenced by `highway=*` and `route=ferry` ways
+    /// (the only ways any profile can route on). Costs a second pass over
+    /// the input.
+    pub highway_nodes_only: bool,
+    /// Keep each way's and relation's OSM version and timestamp
+    /// (ways.raw / relations.raw v2), so `step1-update` can skip stale
+    /// changes.
+    pub keep_metadata: bool,
+    /// Ways to keep; the rest are dropped while decoding.
+    pub way_filter: WayFilter,
 }
 
 pub struct IngestResult {
     pub nodes_count: u64,
     pub signal_nodes_count: u64,
+    pub barrier_nodes_count: u64,
     pub ways_count: u64,
     pub relations_count: u64,
+    /// Provenance recorded in the artifact headers: the PBF's SHA-256,
+    /// or for `step1-update` the chain of base and changefiles.
+    pub input_sha256: [u8; 32],
     pub nodes_sa_file: PathBuf,
     pub nodes_si_file: PathBuf,
     pub node_signals_file: PathBuf,
+    pub node_barriers_file: PathBuf,
     pub ways_file: PathBuf,
     pub relations_file: PathBuf,
 }
 
-/// Run the complete 3-pass ingestion pipeline
+/// Run the Step 1 ingestion pipeline.
+///
+/// By default the PBF is decoded in a single pass (nodes, ways and
+/// restriction relations together) so it can be streamed from a
+/// download, and hashed on the way through instead of being read a
+/// second time. `highway_nodes_only` decodes ways first, then only the
+/// nodes they reference.
 pub fn run_ingest(config: IngestConfig) -> Result<IngestResult> {
     println!("🦋 Starting Step 1: PBF Ingest");
-    println!("📂 Input: {}", config.input.display());
+    println!("📂 Input: {}", config.input);
     println!("📂 Output: {}", config.outdir.display());
     println!();
 
     // Create output directory
     std::fs::create_dir_all(&config.outdir).context("Failed to create output directory")?;
-
-    // Calculate input file SHA-256
-    println!("Computing input file SHA-256...");
-    let input_sha256 = compute_file_sha256(&config.input)?;
+    let spill_dir = config
+        .spill_dir
+        .clone()
+        .unwrap_or_else(|| config.outdir.join(".step1-spill"));
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.threads)
+        .build()
+        .context("Failed to build decoder thread pool")?;
+    println!("🧵 Decoder threads: {}", pool.current_num_threads());
+    println!("🛣️  Way filter: {}", config.way_filter);
+    let way_options = WayOptions {
+        filter: &config.way_filter,
+        keep_metadata: config.keep_metadata,
+    };
+
+    let budget = config.memory_budget;
+    let (input_sha256, ways_pass, nodes_pass) = if config.highway_nodes_only {
+        let IngestInput::File(path) = &config.input else {
+            anyhow::bail!("--highway-nodes-only reads the input twice; download it first");
+        };
+
+        println!("Pass 1/2: Decoding ways and relations...");
+        let mut reader = HashingReader::new(open_input(&config.input)?);
+        let mut ways_pass = Extracted::new(&spill_dir, 0, budget / 2, budget / 2);
+        extract(
+            &pool,
+            &mut reader,
+            &mut ways_pass,
+            Pass::WaysAndRelations,
+            way_options,
+        )?;
+        let input_sha256 = reader.finish()?;
+        let referenced = ReferencedNodes::build(
+            std::mem::replace(
+                &mut ways_pass.referenced,
+                ExternalSorter::new("referenced", &spill_dir, 0),
+            ),
+            &spill_dir,
+        )?;
+        println!(
+            "  ✓ {} distinct nodes referenced by highway and ferry ways",
+            referenced.len()
+        );
+
+        println!("Pass 2/2: Decoding referenced nodes...");
+        let mut nodes_pass = Extracted::new(&spill_dir, budget, 0, 0);
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        extract(
+            &pool,
+            file,
+            &mut nodes_pass,
+            Pass::Nodes(Some(&referenced)),
+            way_options,
+        )?;
+        println!(
+            "  ✓ Skipped {} unreferenced nodes",
+            nodes_pass.skipped_nodes
+        );
+        (input_sha256, ways_pass, nodes_pass)
+    } else {
+        println!("Decoding PBF...");
+        let mut reader = HashingReader::new(open_input(&config.input)?);
+        let mut all = Extracted::new(&spill_dir, budget / 2, budget / 2, 0);
+        extract(&pool, &mut reader, &mut all, Pass::All, way_options)?;
+        let input_sha256 = reader.finish()?;
+        let (ways_pass, nodes_pass) = all.split();
+        (input_sha256, ways_pass, nodes_pass)
+    };
     println!("  ✓ SHA-256: {}", hex::encode(input_sha256));
 
-    // Pass 1: Extract nodes (including traffic signals)
-    println!("Pass 1/3: Processing nodes...");
-    let node_result = extract_nodes(&config.input)?;
-    println!("  ✓ Found {} nodes", node_result.nodes.len());
-    println!(
-        "  ✓ Found {} traffic signal nodes",
-        node_result.signal_node_ids.len()
-    );
+    let Extracted {
+        nodes,
+        bounds,
+        signal_node_ids,
+        barrier_nodes,
+        ..
+    } = nodes_pass;
+    let Extracted {
+        ways,
+        ways_summary,
+        skipped_ways,
+        relations,
+        ..
+    } = ways_pass;
+    let nodes_count = nodes.len();
+    let ways_count = ways_summary.count();
+    println!("  ✓ Found {nodes_count} nodes");
+    println!("  ✓ Found {} traffic signal nodes", signal_node_ids.len());
+    println!("  ✓ Found {} barrier nodes", barrier_nodes.len());
+    println!("  ✓ Found {ways_count} ways ({skipped_ways} filtered out)");
+    println!("  ✓ Found {} relations (restrictions)", relations.len());
+    let spilled = nodes.spilled_runs() + ways.spilled_runs();
+    if spilled > 0 {
+        println!(
+            "  ✓ Spilled {spilled} sorted runs to {}",
+            spill_dir.display()
+        );
+    }
 
+    println!("Writing artifacts...");
     let nodes_sa_file = config.outdir.join("nodes.sa");
     let nodes_si_file = config.outdir.join("nodes.si");
     let node_signals_file = config.outdir.join("node_signals.bin");
-
-    nodes_sa::write(&nodes_sa_file, &node_result.nodes, &input_sha256)?;
+    let node_barriers_file = config.outdir.join("node_barriers.bin");
+
+    let mut nodes_sa_writer =
+        nodes_sa::Writer::create(&nodes_sa_file, nodes_count, bounds.to_fxp(), &input_sha256)?;
+    let mut nodes_si_builder = nodes_si::IndexBuilder::default();
+    for node in nodes.finish()? {
+        let (id, lat, lon) = node?;
+        nodes_sa_writer.push(id, lat, lon)?;
+        nodes_si_builder.push(id);
+    }
+    nodes_sa_writer.finish()?;
     println!("  ✓ Wrote {}", nodes_sa_file.display());
 
-    nodes_si::write(&nodes_si_file, &node_result.nodes)?;
+    nodes_si_builder.write(&nodes_si_file)?;
     println!("  ✓ Wrote {}", nodes_si_file.display());
 
-    let signals = NodeSignals::new(node_result.signal_node_ids.clone());
+    let signal_nodes_count = signal_node_ids.len() as u64;
+    let signals = NodeSignals::new(signal_node_ids);
     NodeSignalsFile::write(&node_signals_file, &signals, &input_sha256)?;
     println!("  ✓ Wrote {}", node_signals_file.display());
 
-    // Pass 2: Extract ways
-    println!("Pass 2/3: Processing ways...");
-    let ways = extract_ways(&config.input)?;
-    println!("  ✓ Found {} ways", ways.len());
+    let barrier_nodes_count = barrier_nodes.len() as u64;
+    let barriers = NodeBarriers::new(barrier_nodes);
+    NodeBarriersFile::write(&node_barriers_file, &barriers, &input_sha256)?;
+    println!("  ✓ Wrote {}", node_barriers_file.display());
 
     let ways_file = config.outdir.join("ways.raw");
-    WaysFile::write(&ways_file, &ways)?;
+    WaysFile::write_sorted(&ways_file, ways_summary, ways.finish()?)?;
     println!("  ✓ Wrote {}", ways_file.display());
-
-    // Pass 3: Extract relations (filtered for restrictions)
-    println!("Pass 3/3: Processing relations...");
-    let relations = extract_relations(&config.input)?;
-    println!("  ✓ Found {} relations (restrictions)", relations.len());
+    // Best effort: only succeeds once every run has been merged away.
+    let _ = std::fs::remove_dir(&spill_dir);
 
     let relations_file = config.outdir.join("relations.raw");
     RelationsFile::write(&relations_file, &relations)?;
@@ -91,18 +255,90 @@ pub fn run_ingest(config: IngestConfig) -> Result<IngestResult> {
     println!("✅ Ingestion complete!");
 
     Ok(IngestResult {
-        nodes_count: node_result.nodes.len() as u64,
-        signal_nodes_count: node_result.signal_node_ids.len() as u64,
-        ways_count: ways.len() as u64,
+        nodes_count,
+        signal_nodes_count,
+        barrier_nodes_count,
+        ways_count,
         relations_count: relations.len() as u64,
+        input_sha256,
         nodes_sa_file,
         nodes_si_file,
         node_signals_file,
+        node_barriers_file,
         ways_file,
         relations_file,
     })
 }
 
+/// Open the PBF byte stream for `input`.
+fn open_input(input: &IngestInput) -> Result<Box<dyn Read + Send>> {
+    match input {
+        IngestInput::File(path) => {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            Ok(Box::new(file))
+        }
+        IngestInput::Download(source) => Ok(Box::new(DownloadReader::open(source)?)),
+    }
+}
+
+/// Blocking reader over a butterfly-dl stream, for osmpbf's sync
+/// `BlobReader`. Owns the runtime driving the download.
+struct DownloadReader {
+    // Declared first so it drops before the runtime it blocks on.
+    stream: tokio_util::io::SyncIoBridge<Box<dyn tokio::io::AsyncRead + Send + Unpin>>,
+    _runtime: tokio::runtime::Runtime,
+}
+
+impl DownloadReader {
+    fn open(source: &str) -> Result<Self> {
+        let runtime = tokio::runtime::Runtime::new()?;
+        let stream = runtime
+            .block_on(butterfly_dl::get_stream(source))
+            .with_context(|| format!("Failed to start download of {source}"))?;
+        let stream: Box<dyn tokio::io::AsyncRead + Send + Unpin> = Box::new(stream);
+        Ok(Self {
+            stream: tokio_util::io::SyncIoBridge::new_with_handle(stream, runtime.handle().clone()),
+            _runtime: runtime,
+        })
+    }
+}
+
+impl Read for DownloadReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+/// Reader that SHA-256s everything read through it.
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Hash of the whole input, draining anything the decoder left.
+    fn finish(mut self) -> Result<[u8; 32]> {
+        std::io::copy(&mut self, &mut std::io::sink())?;
+        Ok(self.hasher.finalize().into())
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
 /// Compute SHA-256 hash of a file
 fn compute_file_sha256<P: AsRef<Path>>(path: P) -> Result<[u8; 32]> {
     use std::io::Read;
@@ -127,203 +363,421 @@ fn compute_file_sha256<P: AsRef<Path>>(path: P) -> Result<[u8; 32]> {
     Ok(hash)
 }
 
-/// Result of node extraction including traffic signals
-struct NodeExtractionResult {
-    nodes: Vec<(i64, f64, f64)>,
-    signal_node_ids: Vec<i64>,
+/// `highway=traffic_signals`; with `barrier=*`, the only node tags Step 1
+/// keeps.
+pub(crate) fn is_traffic_signal<'a>(mut tags: impl Iterator<Item = (&'a str, &'a str)>) -> bool {
+    tags.any(|(k, v)| k == "highway" && v == "traffic_signals")
 }
 
-/// Extract all nodes from PBF, also collecting traffic signal node IDs.
+/// The node's `barrier=*` kind, if it blocks a way.
+pub(crate) fn barrier_kind<'a>(
+    mut tags: impl Iterator<Item = (&'a str, &'a str)>,
+) -> Option<BarrierKind> {
+    tags.find(|&(k, _)| k == "barrier")
+        .and_then(|(_, v)| BarrierKind::from_tag(v))
+}
+
+/// Relations Step 1 keeps: type=restriction or restriction-related tags.
+pub(crate) fn is_restriction(tags: &[(String, String)]) -> bool {
+    tags.iter().any(|(k, v)| {
+        (k == "type" && v == "restriction") || k.starts_with("restriction") || k == "except"
+    })
+}
+
+/// Decode the PBF's data blobs on `pool` with `decode` and feed each
+/// blob's output to `sink` on the calling thread.
 ///
-/// #421: decode PBF blobs in parallel (osmpbf blobs are independent). Each blob
-/// accumulates into LOCAL Vecs — no per-element allocation, no lock contention —
-/// then per-blob results are merged append-smaller-into-larger to bound copying.
-/// par_bridge yields blobs in arbitrary order, so the result is sorted to a
-/// deterministic total order afterwards (node ids are unique in OSM, so the
-/// lat/lon tiebreak never triggers and the output matches the serial id-sorted
-/// baseline byte-for-byte). Peak RSS stays below the (ways-pass) build peak.
-fn extract_nodes<P: AsRef<Path>>(path: P) -> Result<NodeExtractionResult> {
+/// #421 decodes blobs in parallel (osmpbf blobs are independent), each
+/// into its own buffer, so nothing is shared between decoders. The
+/// bounded channel keeps decoders from running ahead of a sink that is
+/// busy spilling a sorted run, so memory stays within the sort budget.
+/// Blob order is arbitrary: sinks feed an order-independent sort.
+fn for_each_blob<T: Send, R: Read + Send>(
+    pool: &rayon::ThreadPool,
+    reader: R,
+    decode: impl Fn(&osmpbf::PrimitiveBlock) -> T + Sync,
+    mut sink: impl FnMut(T) -> Result<()>,
+) -> Result<()> {
     use osmpbf::{BlobDecode, BlobReader};
     use rayon::prelude::*;
-
-    let reader = BlobReader::from_path(path)?;
-
-    let (mut nodes, mut signal_node_ids) = reader
-        .par_bridge()
-        .map(|blob| -> Result<NodeBlob> {
-            let mut nodes = Vec::new();
-            let mut signals = Vec::new();
-            if let BlobDecode::OsmData(block) = blob?.decode()? {
-                for element in block.elements() {
-                    match element {
-                        Element::Node(node) => {
-                            nodes.push((node.id(), node.lat(), node.lon()));
-                            for (key, value) in node.tags() {
-                                if key == "highway" && value == "traffic_signals" {
-                                    signals.push(node.id());
-                                    break;
-                                }
-                            }
-                        }
-                        Element::DenseNode(node) => {
-                            nodes.push((node.id(), node.lat(), node.lon()));
-                            for (key, value) in node.tags() {
-                                if key == "highway" && value == "traffic_signals" {
-                                    signals.push(node.id());
-                                    break;
-                                }
-                            }
-                        }
-                        _ => {}
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let reader = BlobReader::new(reader);
+    let (tx, rx) = std::sync::mpsc::sync_channel::<Result<T>>(pool.current_num_threads() * 2);
+    let stop = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            pool.install(|| {
+                reader.par_bridge().for_each_with(tx, |tx, blob| {
+                    if stop.load(Ordering::Relaxed) {
+                        return;
                     }
-                }
-            }
-            Ok((nodes, signals))
-        })
-        .reduce(
-            || Ok((Vec::new(), Vec::new())),
-            |a, b| {
-                let (mut an, mut asig) = a?;
-                let (mut bn, mut bsig) = b?;
-                if bn.len() > an.len() {
-                    std::mem::swap(&mut an, &mut bn);
-                }
-                an.extend(bn);
-                if bsig.len() > asig.len() {
-                    std::mem::swap(&mut asig, &mut bsig);
-                }
-                asig.extend(bsig);
-                Ok((an, asig))
-            },
-        )
-        .context("Failed to read nodes")?;
-
-    // Deterministic total order. id is unique in OSM so the lat/lon tiebreak is
-    // pure insurance; the sorted sequence is identical to the serial baseline.
-    nodes.sort_unstable_by(|a, b| {
-        a.0.cmp(&b.0)
-            .then_with(|| a.1.total_cmp(&b.1))
-            .then_with(|| a.2.total_cmp(&b.2))
-    });
-    signal_node_ids.sort_unstable();
-    signal_node_ids.dedup();
-
-    Ok(NodeExtractionResult {
-        nodes,
-        signal_node_ids,
+                    let out = match blob.and_then(|blob| match blob.decode()? {
+                        BlobDecode::OsmData(block) => Ok(Some(decode(&block))),
+                        _ => Ok(None),
+                    }) {
+                        Ok(Some(out)) => Ok(out),
+                        Ok(None) => return,
+                        Err(e) => Err(e.into()),
+                    };
+                    if tx.send(out).is_err() {
+                        stop.store(true, Ordering::Relaxed);
+                    }
+                })
+            });
+        });
+
+        let result = rx.iter().try_for_each(|out| sink(out?));
+        // Stop the decoders early if the sink failed.
+        stop.store(true, Ordering::Relaxed);
+        drop(rx);
+        result
     })
 }
 
-/// Extract all ways from PBF
-fn extract_ways<P: AsRef<Path>>(path: P) -> Result<Vec<Way>> {
-    use std::sync::Mutex;
+/// Everything Step 1 keeps from one PBF.
+struct Extracted {
+    nodes: ExternalSorter<(i64, f64, f64)>,
+    bounds: nodes_sa::Bounds,
+    signal_node_ids: Vec<i64>,
+    barrier_nodes: Vec<(i64, BarrierKind)>,
+    skipped_nodes: u64,
+    ways: ExternalSorter<Way>,
+    ways_summary: WaysSummary,
+    /// Ways the [`WayFilter`] dropped.
+    skipped_ways: u64,
+    /// Node ids of highway ways (with repeats), for `highway_nodes_only`.
+    referenced: ExternalSorter<i64>,
+    relations: Vec<Relation>,
+}
+
+impl Extracted {
+    /// Empty result whose sorts spill past the given byte budgets.
+    fn new(spill_dir: &Path, nodes_budget: usize, ways_budget: usize, refs_budget: usize) -> Self {
+        Self {
+            nodes: ExternalSorter::new("nodes", spill_dir, nodes_budget),
+            bounds: nodes_sa::Bounds::default(),
+            signal_node_ids: Vec::new(),
+            barrier_nodes: Vec::new(),
+            skipped_nodes: 0,
+            ways: ExternalSorter::new("ways", spill_dir, ways_budget),
+            ways_summary: WaysSummary::default(),
+            skipped_ways: 0,
+            referenced: ExternalSorter::new("referenced", spill_dir, refs_budget),
+            relations: Vec::new(),
+        }
+    }
+
+    /// Split a single-pass result into its (ways, nodes) halves.
+    fn split(self) -> (Self, Self) {
+        let spill_dir = Path::new("");
+        let mut nodes = Self::new(spill_dir, 0, 0, 0);
+        let mut ways = self;
+        std::mem::swap(&mut nodes.nodes, &mut ways.nodes);
+        std::mem::swap(&mut nodes.bounds, &mut ways.bounds);
+        std::mem::swap(&mut nodes.signal_node_ids, &mut ways.signal_node_ids);
+        std::mem::swap(&mut nodes.barrier_nodes, &mut ways.barrier_nodes);
+        (ways, nodes)
+    }
+}
 
-    let reader = ElementReader::from_path(path)?;
-    let ways = Mutex::new(Vec::new());
+/// How ways are decoded.
+#[derive(Clone, Copy)]
+struct WayOptions<'a> {
+    filter: &'a WayFilter,
+    /// Keep each way's and relation's [`ElementMeta`].
+    keep_metadata: bool,
+}
+
+/// What a decoding pass keeps.
+#[derive(Clone, Copy)]
+enum Pass<'a> {
+    /// Nodes, ways and relations in one go.
+    All,
+    /// Ways and relations, collecting the nodes highway ways reference.
+    WaysAndRelations,
+    /// Nodes only, optionally restricted to a referenced set.
+    Nodes(Option<&'a ReferencedNodes>),
+}
+
+/// One blob's worth of [`Extracted`], decoded off the main thread.
+#[derive(Default)]
+struct BlobElements {
+    nodes: Vec<(i64, f64, f64)>,
+    signal_node_ids: Vec<i64>,
+    barrier_nodes: Vec<(i64, BarrierKind)>,
+    skipped_nodes: u64,
+    ways: Vec<Way>,
+    skipped_ways: u64,
+    referenced: Vec<i64>,
+    relations: Vec<Relation>,
+}
 
-    reader
-        .for_each(|element| {
-            if let Element::Way(way) = element {
-                let id = way.id();
-                let node_ids: Vec<i64> = way.refs().collect();
+/// Decode what `pass` asks for from `reader` into `out`, keeping the
+/// ways `way_options` selects.
+///
+/// Nodes and ways go through [`ExternalSorter`]s; the caller drains
+/// them in id order (ids are unique in OSM, so the order matches the
+/// in-memory baseline byte-for-byte). The bbox and ways summary are
+/// accumulated here because the nodes.sa and ways.raw headers need them
+/// before the sorted bodies stream out.
+fn extract<R: Read + Send>(
+    pool: &rayon::ThreadPool,
+    reader: R,
+    out: &mut Extracted,
+    pass: Pass<'_>,
+    way_options: WayOptions<'_>,
+) -> Result<()> {
+    for_each_blob(
+        pool,
+        reader,
+        |block| decode_block(block, pass, way_options),
+        |blob| {
+            for node in blob.nodes {
+                out.bounds.add(node.1, node.2);
+                out.nodes.push(node)?;
+            }
+            out.signal_node_ids.extend(blob.signal_node_ids);
+            out.barrier_nodes.extend(blob.barrier_nodes);
+            out.skipped_nodes += blob.skipped_nodes;
+            out.skipped_ways += blob.skipped_ways;
+            for way in blob.ways {
+                out.ways_summary.add(&way);
+                out.ways.push(way)?;
+            }
+            for id in blob.referenced {
+                out.referenced.push(id)?;
+            }
+            out.relations.extend(blob.relations);
+            Ok(())
+        },
+    )
+    .context("Failed to read PBF")?;
+
+    out.signal_node_ids.sort_unstable();
+    out.signal_node_ids.dedup();
+    out.barrier_nodes.sort_unstable();
+    // Sort by unique ID for determinism.
+    out.relations.sort_by_key(|r| r.id);
+    Ok(())
+}
 
-                let tags: Vec<(String, String)> = way
+fn decode_block(
+    block: &osmpbf::PrimitiveBlock,
+    pass: Pass<'_>,
+    way_options: WayOptions<'_>,
+) -> BlobElements {
+    let keep_metadata = way_options.keep_metadata;
+    let (nodes, ways, filter) = match pass {
+        Pass::All => (true, true, None),
+        Pass::WaysAndRelations => (false, true, None),
+        Pass::Nodes(filter) => (true, false, filter),
+    };
+    let collect_refs = matches!(pass, Pass::WaysAndRelations);
+
+    let mut out = BlobElements::default();
+    let mut push_node =
+        |id: i64, lat: f64, lon: f64, is_signal: bool, barrier: Option<BarrierKind>| {
+            if filter.is_some_and(|filter| !filter.contains(id)) {
+                out.skipped_nodes += 1;
+                return;
+            }
+            out.nodes.push((id, lat, lon));
+            if is_signal {
+                out.signal_node_ids.push(id);
+            }
+            if let Some(kind) = barrier {
+                out.barrier_nodes.push((id, kind));
+            }
+        };
+    for element in block.elements() {
+        match element {
+            Element::Node(node) if nodes => push_node(
+                node.id(),
+                node.lat(),
+                node.lon(),
+                is_traffic_signal(node.tags()),
+                barrier_kind(node.tags()),
+            ),
+            Element::DenseNode(node) if nodes => push_node(
+                node.id(),
+                node.lat(),
+                node.lon(),
+                is_traffic_signal(node.tags()),
+                barrier_kind(node.tags()),
+            ),
+            Element::Way(way) if ways => {
+                if !way_options.filter.matches(way.tags()) {
+                    out.skipped_ways += 1;
+                    continue;
+                }
+                let way = Way {
+                    id: way.id(),
+                    nodes: way.refs().collect(),
+                    tags: way
+                        .tags()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                    meta: keep_metadata.then(|| element_meta(&way.info())).flatten(),
+                };
+                if collect_refs
+                    && way
+                        .tags
+                        .iter()
+                        .any(|(k, v)| k == "highway" || (k == "route" && v == "ferry"))
+                {
+                    out.referenced.extend_from_slice(&way.nodes);
+                }
+                out.ways.push(way);
+            }
+            Element::Relation(relation) if ways => {
+                let tags: Vec<(String, String)> = relation
                     .tags()
                     .map(|(k, v)| (k.to_string(), v.to_string()))
                     .collect();
+                if !is_restriction(&tags) {
+                    continue;
+                }
+
+                let members: Vec<Member> = relation
+                    .members()
+                    .filter_map(|member| {
+                        let kind = match member.member_type {
+                            osmpbf::RelMemberType::Node => MemberKind::Node,
+                            osmpbf::RelMemberType::Way => MemberKind::Way,
+                            osmpbf::RelMemberType::Relation => return None,
+                        };
+                        Some(Member {
+                            role: member.role().unwrap_or("").to_string(),
+                            kind,
+                            ref_id: member.member_id,
+                        })
+                    })
+                    .collect();
 
-                ways.lock().unwrap().push(Way {
-                    id,
-                    nodes: node_ids,
+                out.relations.push(Relation {
+                    id: relation.id(),
+                    members,
                     tags,
+                    meta: keep_metadata
+                        .then(|| element_meta(&relation.info()))
+                        .flatten(),
                 });
             }
-        })
-        .context("Failed to read ways")?;
-
-    let mut ways = ways.into_inner().unwrap();
+            _ => {}
+        }
+    }
+    out
+}
 
-    // Sort by ID for determinism
-    ways.sort_by_key(|w| w.id);
+/// Version and timestamp from a PBF element's info block, if present.
+fn element_meta(info: &osmpbf::Info<'_>) -> Option<ElementMeta> {
+    Some(ElementMeta {
+        version: info.version()?,
+        timestamp: info.milli_timestamp()? / 1000,
+    })
+}
 
-    Ok(ways)
+/// Sorted, deduplicated ids of the nodes highway ways reference.
+///
+/// Planet highways reference a few billion nodes, more than the sort
+/// budget: once the id sort has spilled, the merged ids are written to
+/// a sorted id file and memory-mapped instead of held on the heap.
+struct ReferencedNodes {
+    ids: ArcCow<i64>,
 }
 
-/// Extract relations from PBF, filtering for turn restrictions
-fn extract_relations<P: AsRef<Path>>(path: P) -> Result<Vec<Relation>> {
-    use osmpbf::{BlobDecode, BlobReader};
-    use rayon::prelude::*;
+impl ReferencedNodes {
+    fn build(ids: ExternalSorter<i64>, spill_dir: &Path) -> Result<Self> {
+        let spilled = ids.spilled_runs() > 0;
+        let mut sorted = ids.finish()?;
+        if !spilled {
+            let mut ids = sorted.collect::<Result<Vec<_>>>()?;
+            ids.dedup();
+            return Ok(Self {
+                ids: ArcCow::from_vec(ids),
+            });
+        }
 
-    let reader = BlobReader::from_path(path)?;
-
-    // #421: parallel blob decode (relations are a tiny fraction of elements;
-    // the cost is the full-file decode, which parallelises). Per-blob local
-    // Vecs, merged append-into-larger; sorted by unique id afterwards so the
-    // output is identical to the serial baseline.
-    let mut relations = reader
-        .par_bridge()
-        .map(|blob| -> Result<Vec<Relation>> {
-            let mut out = Vec::new();
-            if let BlobDecode::OsmData(block) = blob?.decode()? {
-                for element in block.elements() {
-                    if let Element::Relation(relation) = element {
-                        let tags: Vec<(String, String)> = relation
-                            .tags()
-                            .map(|(k, v)| (k.to_string(), v.to_string()))
-                            .collect();
-
-                        // Filter: type=restriction or restriction-related tags.
-                        let is_restriction = tags.iter().any(|(k, v)| {
-                            (k == "type" && v == "restriction")
-                                || k.starts_with("restriction")
-                                || k == "except"
-                        });
-                        if !is_restriction {
-                            continue;
-                        }
-
-                        let members: Vec<Member> = relation
-                            .members()
-                            .filter_map(|member| {
-                                let kind = match member.member_type {
-                                    osmpbf::RelMemberType::Node => MemberKind::Node,
-                                    osmpbf::RelMemberType::Way => MemberKind::Way,
-                                    osmpbf::RelMemberType::Relation => return None,
-                                };
-                                Some(Member {
-                                    role: member.role().unwrap_or("").to_string(),
-                                    kind,
-                                    ref_id: member.member_id,
-                                })
-                            })
-                            .collect();
-
-                        out.push(Relation {
-                            id: relation.id(),
-                            members,
-                            tags,
-                        });
-                    }
-                }
+        let path = spill_dir.join("referenced-nodes.ids");
+        let mut writer = std::io::BufWriter::new(
+            std::fs::File::create(&path)
+                .with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+        let mut count = 0usize;
+        let mut last = None;
+        while let Some(id) = sorted.next().transpose()? {
+            if last != Some(id) {
+                writer.write_all(&id.to_le_bytes())?;
+                count += 1;
+                last = Some(id);
             }
-            Ok(out)
-        })
-        .reduce(
-            || Ok(Vec::new()),
-            |a, b| {
-                let mut av = a?;
-                let mut bv = b?;
-                if bv.len() > av.len() {
-                    std::mem::swap(&mut av, &mut bv);
-                }
-                av.extend(bv);
-                Ok(av)
-            },
-        )
-        .context("Failed to read relations")?;
+        }
+        writer.into_inner().map_err(|e| e.into_error())?;
+        drop(sorted);
+
+        let ids = if count == 0 {
+            ArcCow::from_vec(Vec::new())
+        } else {
+            ArcCow::from_mmap(map_readonly(&path)?, 0, count)?
+        };
+        // The mapping stays valid after unlinking.
+        let _ = std::fs::remove_file(&path);
+        Ok(Self { ids })
+    }
 
-    // Sort by unique ID for determinism.
-    relations.sort_by_key(|r| r.id);
+    fn contains(&self, id: i64) -> bool {
+        self.ids.as_slice().binary_search(&id).is_ok()
+    }
+
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+}
 
-    Ok(relations)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ingest_input_parse() {
+        assert_eq!(
+            IngestInput::parse("https://download.geofabrik.de/europe/belgium-latest.osm.pbf"),
+            IngestInput::Download(
+                "https://download.geofabrik.de/europe/belgium-latest.osm.pbf".to_string()
+            )
+        );
+        assert_eq!(
+            IngestInput::parse("data/belgium.pbf"),
+            IngestInput::File(PathBuf::from("data/belgium.pbf"))
+        );
+    }
+
+    #[test]
+    fn test_referenced_nodes_dedup_in_memory_and_spilled() {
+        let dir = tempfile::tempdir().unwrap();
+        for budget in [usize::MAX, 0] {
+            let mut ids = ExternalSorter::new("referenced", dir.path(), budget);
+            for id in [7, 3, 7, 1, 3, 9] {
+                ids.push(id).unwrap();
+            }
+            let referenced = ReferencedNodes::build(ids, dir.path()).unwrap();
+            assert_eq!(referenced.ids.as_slice(), &[1, 3, 7, 9]);
+            assert!(referenced.contains(7));
+            assert!(!referenced.contains(2));
+        }
+        // Runs and the id file are cleaned up.
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_hashing_reader_drains_input() {
+        let data = vec![42u8; 10_000];
+        let mut reader = HashingReader::new(data.as_slice());
+        let mut head = [0u8; 100];
+        reader.read_exact(&mut head).unwrap();
+        let expected: [u8; 32] = Sha256::digest(&data).into();
+        assert_eq!(reader.finish().unwrap(), expected);
+    }
 }
@@ -0,0 +1,389 @@
+//! OsmChange (`.osc`, `.osc.gz`) parser for Step 1 updates.
+//!
+//! A changefile lists elements inside `<create>`, `<modify>` and
+//! `<delete>` blocks; each element carries its full new state, so
+//! create and modify are the same operation here. [`ChangeSet`] folds
+//! any number of changefiles into the net effect per element id —
+//! later files (and later entries within a file) win.
+
+use anyhow::{Context, Result, bail};
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use crate::formats::{BarrierKind, ElementMeta, Member, MemberKind, Relation, Way};
+
+/// New state of a created/modified node. Only what Step 1 keeps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedNode {
+    pub lat: f64,
+    pub lon: f64,
+    pub traffic_signal: bool,
+    pub barrier: Option<BarrierKind>,
+}
+
+/// Net effect of one or more changefiles: the latest state of every
+/// touched element, `None` when it ended up deleted.
+#[derive(Default)]
+pub struct ChangeSet {
+    pub nodes: BTreeMap<i64, Option<ChangedNode>>,
+    pub ways: BTreeMap<i64, Option<Way>>,
+    pub relations: BTreeMap<i64, Option<Relation>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Action {
+    Upsert,
+    Delete,
+}
+
+enum Pending {
+    Node {
+        id: i64,
+        coords: Option<(f64, f64)>,
+        tags: Vec<(String, String)>,
+    },
+    Way(Way),
+    Relation(Relation),
+}
+
+impl ChangeSet {
+    /// Apply a changefile on top of what was read so far. Gzip input is
+    /// detected from its magic bytes, not the file name.
+    pub fn read_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut reader = BufReader::with_capacity(1 << 20, file);
+        let gzip = reader.fill_buf()?.starts_with(&[0x1f, 0x8b]);
+        let result = if gzip {
+            let decoder = flate2::read::MultiGzDecoder::new(reader);
+            self.read(BufReader::with_capacity(1 << 20, decoder))
+        } else {
+            self.read(reader)
+        };
+        result.with_context(|| format!("Failed to parse changefile {}", path.display()))
+    }
+
+    /// Apply an OsmChange XML document.
+    pub fn read<R: BufRead>(&mut self, reader: R) -> Result<()> {
+        let mut xml = Reader::from_reader(reader);
+        xml.config_mut().trim_text(true);
+
+        let mut buf = Vec::with_capacity(4096);
+        let mut action = None;
+        let mut pending = None;
+
+        loop {
+            let event = xml.read_event_into(&mut buf)?;
+            let (element, empty) = match &event {
+                Event::Start(e) => (e, false),
+                Event::Empty(e) => (e, true),
+                Event::End(e) => {
+                    match e.local_name().as_ref() {
+                        b"create" | b"modify" | b"delete" => action = None,
+                        b"node" | b"way" | b"relation" => {
+                            if let (Some(action), Some(element)) = (action, pending.take()) {
+                                self.commit(action, element)?;
+                            }
+                        }
+                        _ => {}
+                    }
+                    buf.clear();
+                    continue;
+                }
+                Event::Eof => break,
+                _ => {
+                    buf.clear();
+                    continue;
+                }
+            };
+
+            match element.local_name().as_ref() {
+                b"create" | b"modify" => action = Some(Action::Upsert),
+                b"delete" => action = Some(Action::Delete),
+                name @ (b"node" | b"way" | b"relation") => {
+                    let Some(action) = action else {
+                        bail!(
+                            "<{}> outside <create>/<modify>/<delete>",
+                            String::from_utf8_lossy(name)
+                        );
+                    };
+                    let id = parse_attr(element, b"id")?;
+                    let element = match name {
+                        b"node" => Pending::Node {
+                            id,
+                            coords: match (attr(element, b"lat")?, attr(element, b"lon")?) {
+                                (Some(lat), Some(lon)) => Some((parse(&lat)?, parse(&lon)?)),
+                                _ => None,
+                            },
+                            tags: Vec::new(),
+                        },
+                        b"way" => Pending::Way(Way {
+                            id,
+                            nodes: Vec::new(),
+                            tags: Vec::new(),
+                            meta: element_meta(element)?,
+                        }),
+                        _ => Pending::Relation(Relation {
+                            id,
+                            members: Vec::new(),
+                            tags: Vec::new(),
+                            meta: element_meta(element)?,
+                        }),
+                    };
+                    if empty {
+                        self.commit(action, element)?;
+                    } else {
+                        pending = Some(element);
+                    }
+                }
+                b"tag" => {
+                    let tag = (required_attr(element, b"k")?, required_attr(element, b"v")?);
+                    match &mut pending {
+                        Some(Pending::Node { tags, .. }) => tags.push(tag),
+                        Some(Pending::Way(way)) => way.tags.push(tag),
+                        Some(Pending::Relation(relation)) => relation.tags.push(tag),
+                        None => {}
+                    }
+                }
+                b"nd" => {
+                    if let Some(Pending::Way(way)) = &mut pending {
+                        way.nodes.push(parse_attr(element, b"ref")?);
+                    }
+                }
+                b"member" => {
+                    if let Some(Pending::Relation(relation)) = &mut pending {
+                        // Same as ingest: relation members are dropped.
+                        let kind = match required_attr(element, b"type")?.as_str() {
+                            "node" => Some(MemberKind::Node),
+                            "way" => Some(MemberKind::Way),
+                            _ => None,
+                        };
+                        if let Some(kind) = kind {
+                            relation.members.push(Member {
+                                role: attr(element, b"role")?.unwrap_or_default(),
+                                kind,
+                                ref_id: parse_attr(element, b"ref")?,
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(())
+    }
+
+    fn commit(&mut self, action: Action, element: Pending) -> Result<()> {
+        let upsert = action == Action::Upsert;
+        match element {
+            Pending::Node { id, coords, tags } => {
+                let node = match (upsert, coords) {
+                    (false, _) => None,
+                    (true, Some((lat, lon))) => Some(ChangedNode {
+                        lat,
+                        lon,
+                        traffic_signal: super::is_traffic_signal(
+                            tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                        ),
+                        barrier: super::barrier_kind(
+                            tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                        ),
+                    }),
+                    (true, None) => bail!("node {id} has no lat/lon"),
+                };
+                self.nodes.insert(id, node);
+            }
+            Pending::Way(way) => {
+                self.ways.insert(way.id, upsert.then_some(way));
+            }
+            Pending::Relation(relation) => {
+                self.relations
+                    .insert(relation.id, upsert.then_some(relation));
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of touched elements.
+    pub fn len(&self) -> usize {
+        self.nodes.len() + self.ways.len() + self.relations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn attr(e: &BytesStart<'_>, key: &[u8]) -> Result<Option<String>> {
+    for a in e.attributes() {
+        let a = a?;
+        if a.key.as_ref() == key {
+            return Ok(Some(a.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn required_attr(e: &BytesStart<'_>, key: &[u8]) -> Result<String> {
+    attr(e, key)?.with_context(|| {
+        format!(
+            "<{}> without {}=",
+            String::from_utf8_lossy(e.local_name().as_ref()),
+            String::from_utf8_lossy(key)
+        )
+    })
+}
+
+fn parse_attr<T: std::str::FromStr>(e: &BytesStart<'_>, key: &[u8]) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    parse(&required_attr(e, key)?)
+}
+
+/// `version=` and `timestamp=` (ISO 8601) of an element, when the
+/// changefile carries both.
+fn element_meta(e: &BytesStart<'_>) -> Result<Option<ElementMeta>> {
+    let (Some(version), Some(timestamp)) = (attr(e, b"version")?, attr(e, b"timestamp")?) else {
+        return Ok(None);
+    };
+    let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp)
+        .with_context(|| format!("Invalid timestamp {timestamp:?}"))?;
+    Ok(Some(ElementMeta {
+        version: parse(&version)?,
+        timestamp: timestamp.timestamp(),
+    }))
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .parse()
+        .with_context(|| format!("Invalid number {value:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn parse_str(xml: &str) -> Result<ChangeSet> {
+        let mut changes = ChangeSet::default();
+        changes.read(xml.as_bytes())?;
+        Ok(changes)
+    }
+
+    const OSC: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osmChange version="0.6" generator="test">
+  <create>
+    <node id="10" version="1" lat="50.1" lon="4.2">
+      <tag k="highway" v="traffic_signals"/>
+    </node>
+    <way id="5" version="1">
+      <nd ref="1"/><nd ref="10"/>
+      <tag k="name" v="Rue &amp; Co"/>
+    </way>
+  </create>
+  <modify>
+    <node id="2" version="3" lat="-1.5" lon="2.5">
+      <tag k="barrier" v="lift_gate"/>
+    </node>
+    <relation id="7" version="2">
+      <member type="way" ref="5" role="from"/>
+      <member type="relation" ref="9" role=""/>
+      <member type="node" ref="10" role="via"/>
+      <tag k="type" v="restriction"/>
+    </relation>
+  </modify>
+  <delete>
+    <node id="3" version="4"/>
+    <way id="6" version="2" visible="false"/>
+  </delete>
+</osmChange>
+"#;
+
+    #[test]
+    fn test_parse_create_modify_delete() {
+        let changes = parse_str(OSC).unwrap();
+        assert_eq!(changes.len(), 6);
+        assert_eq!(
+            changes.nodes[&10],
+            Some(ChangedNode {
+                lat: 50.1,
+                lon: 4.2,
+                traffic_signal: true,
+                barrier: None,
+            })
+        );
+        assert_eq!(
+            changes.nodes[&2],
+            Some(ChangedNode {
+                lat: -1.5,
+                lon: 2.5,
+                traffic_signal: false,
+                barrier: Some(BarrierKind::LiftGate),
+            })
+        );
+        assert_eq!(changes.nodes[&3], None);
+
+        let way = changes.ways[&5].as_ref().unwrap();
+        assert_eq!(way.nodes, vec![1, 10]);
+        assert_eq!(way.tags, vec![("name".into(), "Rue & Co".into())]);
+        assert!(changes.ways[&6].is_none());
+
+        let relation = changes.relations[&7].as_ref().unwrap();
+        let members: Vec<_> = relation
+            .members
+            .iter()
+            .map(|m| (m.kind, m.ref_id, m.role.as_str()))
+            .collect();
+        assert_eq!(
+            members,
+            vec![(MemberKind::Way, 5, "from"), (MemberKind::Node, 10, "via")]
+        );
+    }
+
+    #[test]
+    fn test_later_changes_win() {
+        let mut changes = parse_str(OSC).unwrap();
+        changes
+            .read(
+                r#"<osmChange><delete><node id="10"/></delete>
+                   <create><node id="3" lat="1" lon="1"/></create></osmChange>"#
+                    .as_bytes(),
+            )
+            .unwrap();
+        assert_eq!(changes.nodes[&10], None);
+        assert!(changes.nodes[&3].is_some());
+    }
+
+    #[test]
+    fn test_read_gzip_file() {
+        let mut tmp = tempfile::NamedTempFile::new().unwrap();
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        gz.write_all(OSC.as_bytes()).unwrap();
+        tmp.write_all(&gz.finish().unwrap()).unwrap();
+
+        let mut changes = ChangeSet::default();
+        changes.read_file(tmp.path()).unwrap();
+        assert_eq!(changes.len(), 6);
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(parse_str(r#"<osmChange><node id="1" lat="0" lon="0"/></osmChange>"#).is_err());
+        assert!(parse_str(r#"<osmChange><modify><node id="1"/></modify></osmChange>"#).is_err());
+        assert!(
+            parse_str(r#"<osmChange><create><node id="x" lat="0" lon="0"/></create></osmChange>"#)
+                .is_err()
+        );
+    }
+}
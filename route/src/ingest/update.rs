@@ -0,0 +1,545 @@
+//! Apply OsmChange diffs to existing Step 1 artifacts.
+//!
+//! Daily diffs touch a tiny fraction of the planet, so instead of
+//! re-ingesting the PBF the changes are held in memory ([`ChangeSet`])
+//! and overlaid on the id-sorted base artifacts while they stream into
+//! the output directory. Node and way bases are never loaded whole.
+//!
+//! The result has the same layout a fresh ingest of the updated PBF
+//! would produce. Its `input_sha256` chains the base's with every
+//! changefile's, so two updates agree iff they used the same inputs.
+//!
+//! When the base was ingested with `--keep-metadata`, a created or
+//! modified way/relation whose version is not newer than the base's is
+//! stale (e.g. a diff replayed out of order) and the base is kept.
+//! Deletes carry no state to compare and are always applied.
+
+use anyhow::{Context, Result, bail};
+use sha2::{Digest as Sha2Digest, Sha256};
+use std::iter::Peekable;
+use std::path::PathBuf;
+
+use super::osc::ChangeSet;
+use super::way_filter::WayFilter;
+use super::{IngestResult, compute_file_sha256, is_restriction};
+use crate::formats::{
+    ElementMeta, NodeBarriers, NodeBarriersFile, NodeSignals, NodeSignalsFile, Relation,
+    RelationsFile, Way, WaysFile, WaysSummary, nodes_sa, nodes_si,
+};
+
+pub struct UpdateConfig {
+    /// Existing Step 1 output directory.
+    pub base: PathBuf,
+    /// Changefiles, applied in order.
+    pub changes: Vec<PathBuf>,
+    /// Output directory; must differ from `base`.
+    pub outdir: PathBuf,
+    /// The filter the base was ingested with; changed ways that fail it
+    /// are dropped like they would be by a fresh ingest.
+    pub way_filter: WayFilter,
+}
+
+/// Apply `config.changes` to the artifacts in `config.base`
+pub fn run_update(config: UpdateConfig) -> Result<IngestResult> {
+    println!("🦋 Starting Step 1: apply changefiles");
+    println!("📂 Base: {}", config.base.display());
+    println!("📂 Output: {}", config.outdir.display());
+    println!();
+
+    if config.changes.is_empty() {
+        bail!("No changefiles given");
+    }
+    std::fs::create_dir_all(&config.outdir).context("Failed to create output directory")?;
+    if std::fs::canonicalize(&config.base)? == std::fs::canonicalize(&config.outdir)? {
+        bail!("Output directory must differ from the base (artifacts are streamed from it)");
+    }
+
+    let base_nodes = config.base.join("nodes.sa");
+    let base_ways = config.base.join("ways.raw");
+
+    let mut hasher = Sha256::new();
+    hasher.update(nodes_sa::Reader::open(&base_nodes)?.input_sha256());
+    let mut changes = ChangeSet::default();
+    for path in &config.changes {
+        hasher.update(compute_file_sha256(path)?);
+        changes.read_file(path)?;
+        println!("  ✓ Read {}", path.display());
+    }
+    let input_sha256: [u8; 32] = hasher.finalize().into();
+    println!(
+        "  ✓ {} nodes, {} ways, {} relations changed",
+        changes.nodes.len(),
+        changes.ways.len(),
+        changes.relations.len()
+    );
+
+    // Nodes: one pass for the header's count and bbox, one to write.
+    println!("Updating nodes...");
+    let node_changes = || {
+        changes.nodes.iter().map(|(&id, node)| {
+            let node = node
+                .as_ref()
+                .map(|n| (nodes_sa::to_fxp(n.lat), nodes_sa::to_fxp(n.lon)));
+            (id, node)
+        })
+    };
+    let nodes = || -> Result<_> {
+        let base =
+            nodes_sa::Reader::open(&base_nodes)?.map(|r| r.map(|(id, lat, lon)| (id, (lat, lon))));
+        Ok(Overlay::new(base, node_changes()))
+    };
+
+    let mut nodes_count = 0u64;
+    let mut bbox: Option<(i32, i32, i32, i32)> = None;
+    for node in nodes()? {
+        let (_, (lat, lon)) = node?;
+        nodes_count += 1;
+        bbox = Some(match bbox {
+            None => (lat, lon, lat, lon),
+            Some((a, b, c, d)) => (a.min(lat), b.min(lon), c.max(lat), d.max(lon)),
+        });
+    }
+
+    let nodes_sa_file = config.outdir.join("nodes.sa");
+    let nodes_si_file = config.outdir.join("nodes.si");
+    let mut nodes_sa_writer = nodes_sa::Writer::create(
+        &nodes_sa_file,
+        nodes_count,
+        bbox.unwrap_or_default(),
+        &input_sha256,
+    )?;
+    let mut nodes_si_builder = nodes_si::IndexBuilder::default();
+    for node in nodes()? {
+        let (id, (lat, lon)) = node?;
+        nodes_sa_writer.push_fxp(id, lat, lon)?;
+        nodes_si_builder.push(id);
+    }
+    nodes_sa_writer.finish()?;
+    nodes_si_builder.write(&nodes_si_file)?;
+    println!(
+        "  ✓ Wrote {} ({nodes_count} nodes)",
+        nodes_sa_file.display()
+    );
+    println!("  ✓ Wrote {}", nodes_si_file.display());
+
+    // Signals: drop every touched node, re-add the ones still tagged.
+    let node_signals_file = config.outdir.join("node_signals.bin");
+    let mut signal_ids = NodeSignalsFile::read(config.base.join("node_signals.bin"))?.node_ids;
+    signal_ids.retain(|id| !changes.nodes.contains_key(id));
+    signal_ids.extend(
+        changes
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.as_ref().is_some_and(|n| n.traffic_signal))
+            .map(|(&id, _)| id),
+    );
+    let signals = NodeSignals::new(signal_ids);
+    let signal_nodes_count = signals.len() as u64;
+    NodeSignalsFile::write(&node_signals_file, &signals, &input_sha256)?;
+    println!(
+        "  ✓ Wrote {} ({signal_nodes_count} signals)",
+        node_signals_file.display()
+    );
+
+    // Barriers: same, from an empty set for bases that predate them.
+    let node_barriers_file = config.outdir.join("node_barriers.bin");
+    let base_barriers = config.base.join("node_barriers.bin");
+    let mut barrier_nodes = if base_barriers.exists() {
+        NodeBarriersFile::read(&base_barriers)?.nodes
+    } else {
+        Vec::new()
+    };
+    barrier_nodes.retain(|(id, _)| !changes.nodes.contains_key(id));
+    barrier_nodes.extend(
+        changes
+            .nodes
+            .iter()
+            .filter_map(|(&id, node)| node.as_ref().and_then(|n| n.barrier).map(|kind| (id, kind))),
+    );
+    let barriers = NodeBarriers::new(barrier_nodes);
+    let barrier_nodes_count = barriers.len() as u64;
+    NodeBarriersFile::write(&node_barriers_file, &barriers, &input_sha256)?;
+    println!(
+        "  ✓ Wrote {} ({barrier_nodes_count} barriers)",
+        node_barriers_file.display()
+    );
+
+    // Ways: one pass for the dictionaries, one to write.
+    println!("Updating ways...");
+    let (key_dict, val_dict, _, _) = WaysFile::read_dictionaries(&base_ways)?;
+    let ways = || -> Result<_> {
+        let base = WaysFile::stream_ways_with_meta(&base_ways)?.map(|way| {
+            let ((id, keys, vals, nodes), meta) = way?;
+            let tags = keys
+                .iter()
+                .zip(&vals)
+                .map(|(k, v)| {
+                    let key = key_dict
+                        .get(k)
+                        .with_context(|| format!("Key ID {k} not in dictionary"))?;
+                    let val = val_dict
+                        .get(v)
+                        .with_context(|| format!("Value ID {v} not in dictionary"))?;
+                    Ok((key.clone(), val.clone()))
+                })
+                .collect::<Result<_>>()?;
+            Ok((
+                id,
+                Way {
+                    id,
+                    nodes,
+                    tags,
+                    meta,
+                },
+            ))
+        });
+        let changed = changes.ways.iter().map(|(&id, way)| (id, way.clone()));
+        Ok(Overlay::new(base, changed)
+            .skip_stale(|base, change| is_stale(base.meta, change.meta))
+            .filter(|way| {
+                way.as_ref().map_or(true, |(_, way)| {
+                    config
+                        .way_filter
+                        .matches(way.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+                })
+            })
+            .map(|way| way.map(|(_, way)| way)))
+    };
+
+    let mut summary = WaysSummary::default();
+    for way in ways()? {
+        summary.add(&way?);
+    }
+    let ways_count = summary.count();
+    let ways_file = config.outdir.join("ways.raw");
+    WaysFile::write_sorted(&ways_file, summary, ways()?)?;
+    println!("  ✓ Wrote {} ({ways_count} ways)", ways_file.display());
+
+    // Relations: restrictions only, small enough to hold.
+    println!("Updating relations...");
+    let base = RelationsFile::read(config.base.join("relations.raw"))?;
+    let changed = changes.relations.iter().map(|(&id, relation)| {
+        let relation = relation
+            .as_ref()
+            .filter(|r| is_restriction(&r.tags))
+            .cloned();
+        (id, relation)
+    });
+    let relations: Vec<Relation> = Overlay::new(base.into_iter().map(|r| Ok((r.id, r))), changed)
+        .skip_stale(|base, change| is_stale(base.meta, change.meta))
+        .map(|relation| relation.map(|(_, relation)| relation))
+        .collect::<Result<_>>()?;
+    let relations_file = config.outdir.join("relations.raw");
+    RelationsFile::write(&relations_file, &relations)?;
+    println!(
+        "  ✓ Wrote {} ({} relations)",
+        relations_file.display(),
+        relations.len()
+    );
+
+    println!();
+    println!("✅ Update complete!");
+
+    Ok(IngestResult {
+        nodes_count,
+        signal_nodes_count,
+        barrier_nodes_count,
+        ways_count,
+        relations_count: relations.len() as u64,
+        input_sha256,
+        nodes_sa_file,
+        nodes_si_file,
+        node_signals_file,
+        node_barriers_file,
+        ways_file,
+        relations_file,
+    })
+}
+
+/// A change is stale when both sides know their version and the
+/// change's is not newer.
+fn is_stale(base: Option<ElementMeta>, change: Option<ElementMeta>) -> bool {
+    matches!((base, change), (Some(base), Some(change)) if change.version <= base.version)
+}
+
+/// Overlays id-sorted `changes` on an id-sorted `base` stream: a
+/// changed id takes the change's value (or disappears when deleted)
+/// and new ids are spliced in at their sorted position.
+struct Overlay<T, B: Iterator, C: Iterator> {
+    base: Peekable<B>,
+    changes: Peekable<C>,
+    /// `(base, change) -> true` keeps the base value over the change.
+    stale: fn(&T, &T) -> bool,
+}
+
+impl<T, B, C> Overlay<T, B, C>
+where
+    B: Iterator<Item = Result<(i64, T)>>,
+    C: Iterator<Item = (i64, Option<T>)>,
+{
+    fn new(base: B, changes: C) -> Self {
+        Self {
+            base: base.peekable(),
+            changes: changes.peekable(),
+            stale: |_, _| false,
+        }
+    }
+
+    /// Keep the base value wherever `stale(base, change)` holds.
+    fn skip_stale(self, stale: fn(&T, &T) -> bool) -> Self {
+        Self { stale, ..self }
+    }
+}
+
+impl<T, B, C> Iterator for Overlay<T, B, C>
+where
+    B: Iterator<Item = Result<(i64, T)>>,
+    C: Iterator<Item = (i64, Option<T>)>,
+{
+    type Item = Result<(i64, T)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let base_id = match self.base.peek() {
+                Some(Ok((id, _))) => Some(*id),
+                Some(Err(_)) => return self.base.next(),
+                None => None,
+            };
+            let change_id = self.changes.peek().map(|(id, _)| *id);
+
+            match (base_id, change_id) {
+                (None, None) => return None,
+                (Some(base_id), change_id) if change_id.is_none_or(|c| base_id < c) => {
+                    return self.base.next();
+                }
+                (base_id, _) => {
+                    let (id, change) = self.changes.next()?;
+                    if base_id == Some(id) {
+                        let base = self.base.next()?;
+                        if let (Ok((_, base_value)), Some(change)) = (&base, &change)
+                            && (self.stale)(base_value, change)
+                        {
+                            return Some(base);
+                        }
+                    }
+                    if let Some(value) = change {
+                        return Some(Ok((id, value)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{BarrierKind, Member, MemberKind};
+
+    #[test]
+    fn test_overlay_merges_in_id_order() {
+        let base = vec![(1, 'a'), (3, 'c'), (5, 'e')];
+        let changes = vec![
+            (0, Some('z')),
+            (3, None),
+            (4, Some('d')),
+            (5, Some('E')),
+            (9, None),
+        ];
+        let merged: Vec<_> = Overlay::new(base.into_iter().map(Ok), changes.into_iter())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(merged, vec![(0, 'z'), (1, 'a'), (4, 'd'), (5, 'E')]);
+    }
+
+    #[test]
+    fn test_update_applies_changefile() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base");
+        std::fs::create_dir_all(&base).unwrap();
+        let sha = [1u8; 32];
+
+        let nodes = vec![(1, 50.0, 4.0), (2, 50.1, 4.1), (3, 50.2, 4.2)];
+        nodes_sa::write(base.join("nodes.sa"), &nodes, &sha).unwrap();
+        nodes_si::write(base.join("nodes.si"), &nodes).unwrap();
+        NodeSignalsFile::write(
+            base.join("node_signals.bin"),
+            &NodeSignals::new(vec![2]),
+            &sha,
+        )
+        .unwrap();
+        let way = |id, nodes: Vec<i64>, highway: &str| Way {
+            id,
+            nodes,
+            tags: vec![("highway".into(), highway.into())],
+            meta: None,
+        };
+        WaysFile::write(
+            base.join("ways.raw"),
+            &[
+                way(10, vec![1, 2], "primary"),
+                way(11, vec![2, 3], "service"),
+            ],
+        )
+        .unwrap();
+        let restriction = Relation {
+            id: 20,
+            members: vec![Member {
+                role: "via".into(),
+                kind: MemberKind::Node,
+                ref_id: 2,
+            }],
+            tags: vec![("type".into(), "restriction".into())],
+            meta: None,
+        };
+        RelationsFile::write(base.join("relations.raw"), &[restriction]).unwrap();
+
+        let osc = dir.path().join("day.osc");
+        std::fs::write(
+            &osc,
+            r#"<osmChange version="0.6">
+  <create>
+    <node id="4" lat="51.0" lon="3.0"><tag k="highway" v="traffic_signals"/></node>
+    <way id="12"><nd ref="3"/><nd ref="4"/><tag k="highway" v="residential"/></way>
+    <way id="13"><nd ref="3"/><nd ref="4"/><tag k="building" v="yes"/></way>
+  </create>
+  <modify>
+    <node id="2" lat="50.15" lon="4.15"><tag k="barrier" v="bollard"/></node>
+    <relation id="20"><member type="way" ref="10" role="from"/><tag k="type" v="multipolygon"/></relation>
+  </modify>
+  <delete><node id="1"/><way id="10"/></delete>
+</osmChange>"#,
+        )
+        .unwrap();
+
+        let out = dir.path().join("out");
+        let result = run_update(UpdateConfig {
+            base: base.clone(),
+            changes: vec![osc],
+            outdir: out.clone(),
+            way_filter: WayFilter::default(),
+        })
+        .unwrap();
+
+        assert_eq!(result.nodes_count, 3);
+        let records: Vec<_> = nodes_sa::Reader::open(&result.nodes_sa_file)
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                (2, nodes_sa::to_fxp(50.15), nodes_sa::to_fxp(4.15)),
+                (3, nodes_sa::to_fxp(50.2), nodes_sa::to_fxp(4.2)),
+                (4, nodes_sa::to_fxp(51.0), nodes_sa::to_fxp(3.0)),
+            ]
+        );
+        assert_ne!(
+            nodes_sa::Reader::open(&result.nodes_sa_file)
+                .unwrap()
+                .input_sha256(),
+            &sha
+        );
+
+        // Node 2 lost its signal tag; node 4 gained one.
+        let signals = NodeSignalsFile::read(&result.node_signals_file).unwrap();
+        assert_eq!(signals.node_ids, vec![4]);
+        // The base had no node_barriers.bin; node 2 became a bollard.
+        let barriers = NodeBarriersFile::read(&result.node_barriers_file).unwrap();
+        assert_eq!(barriers.nodes, vec![(2, BarrierKind::Bollard)]);
+
+        let ways = WaysFile::read(&result.ways_file).unwrap();
+        let ids: Vec<_> = ways.iter().map(|w| w.id).collect();
+        // Way 13 fails the default way filter.
+        assert_eq!(ids, vec![11, 12]);
+        assert_eq!(ways[1].nodes, vec![3, 4]);
+        assert_eq!(ways[1].tags, vec![("highway".into(), "residential".into())]);
+
+        // Relation 20 is no longer a restriction.
+        assert_eq!(result.relations_count, 0);
+
+        crate::validate::verify_lock_conditions(
+            &result.nodes_sa_file,
+            &result.nodes_si_file,
+            &result.ways_file,
+            &result.relations_file,
+        )
+        .unwrap();
+
+        // Updating in place would truncate the inputs mid-stream.
+        assert!(
+            run_update(UpdateConfig {
+                base: base.clone(),
+                changes: vec![dir.path().join("day.osc")],
+                outdir: base,
+                way_filter: WayFilter::default(),
+            })
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_update_skips_stale_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("base");
+        std::fs::create_dir_all(&base).unwrap();
+        let sha = [1u8; 32];
+
+        let nodes = vec![(1, 50.0, 4.0), (2, 50.1, 4.1), (3, 50.2, 4.2)];
+        nodes_sa::write(base.join("nodes.sa"), &nodes, &sha).unwrap();
+        nodes_si::write(base.join("nodes.si"), &nodes).unwrap();
+        NodeSignalsFile::write(
+            base.join("node_signals.bin"),
+            &NodeSignals::new(vec![]),
+            &sha,
+        )
+        .unwrap();
+        let way = |id, highway: &str, version| Way {
+            id,
+            nodes: vec![1, 2],
+            tags: vec![("highway".into(), highway.into())],
+            meta: Some(ElementMeta {
+                version,
+                timestamp: 1_600_000_000,
+            }),
+        };
+        WaysFile::write(
+            base.join("ways.raw"),
+            &[way(10, "primary", 3), way(11, "service", 2)],
+        )
+        .unwrap();
+        RelationsFile::write(base.join("relations.raw"), &[]).unwrap();
+
+        // Way 10's change predates the base; way 11's is newer.
+        let osc = dir.path().join("day.osc");
+        std::fs::write(
+            &osc,
+            r#"<osmChange version="0.6">
+  <modify>
+    <way id="10" version="2" timestamp="2019-01-01T00:00:00Z"><nd ref="1"/><tag k="highway" v="track"/></way>
+    <way id="11" version="3" timestamp="2024-05-01T12:00:00Z"><nd ref="2"/><nd ref="3"/><tag k="highway" v="residential"/></way>
+  </modify>
+</osmChange>"#,
+        )
+        .unwrap();
+
+        let result = run_update(UpdateConfig {
+            base,
+            changes: vec![osc],
+            outdir: dir.path().join("out"),
+            way_filter: WayFilter::default(),
+        })
+        .unwrap();
+
+        let ways = WaysFile::read(&result.ways_file).unwrap();
+        let summary: Vec<_> = ways
+            .iter()
+            .map(|w| (w.id, w.tags[0].1.as_str(), w.meta.map(|m| m.version)))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(10, "primary", Some(3)), (11, "residential", Some(3))]
+        );
+        assert_eq!(ways[1].meta.unwrap().timestamp, 1_714_564_800);
+    }
+}
@@ -0,0 +1,124 @@
+//! Step 1 way pre-filter.
+//!
+//! Profiles only ever route on a handful of way kinds, so Step 1 drops
+//! everything else (buildings, landuse, waterways...) while decoding
+//! instead of carrying it through ways.raw and every later step. The
+//! filter is a comma-separated list of `key` (any value) or `key=value`
+//! rules; a way is kept when any rule matches one of its tags. The spec
+//! is recorded in `step1.lock.json` so `step1-update` applies the same
+//! filter to changed ways.
+
+use anyhow::{Result, bail};
+
+/// Ways Step 1 keeps unless told otherwise.
+pub const DEFAULT_WAY_FILTER: &str = "highway,route=ferry,junction";
+
+/// Spec that keeps every way.
+pub const ALL_WAYS: &str = "all";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WayFilter {
+    /// `(key, value)`; `None` matches any value. Empty keeps every way.
+    rules: Vec<(String, Option<String>)>,
+}
+
+impl WayFilter {
+    /// Keep every way.
+    pub fn all() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Parse a filter spec: [`ALL_WAYS`] or `key[=value],...`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec == ALL_WAYS {
+            return Ok(Self::all());
+        }
+        let mut rules = Vec::new();
+        for rule in spec.split(',').map(str::trim) {
+            let (key, value) = match rule.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim())),
+                None => (rule, None),
+            };
+            if key.is_empty() || value.is_some_and(str::is_empty) {
+                bail!("Invalid way filter rule {rule:?} in {spec:?}");
+            }
+            rules.push((key.to_string(), value.map(str::to_string)));
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn keeps_all(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether a way with `tags` passes the filter.
+    pub fn matches<'a>(&self, tags: impl IntoIterator<Item = (&'a str, &'a str)>) -> bool {
+        if self.keeps_all() {
+            return true;
+        }
+        tags.into_iter().any(|(k, v)| {
+            self.rules
+                .iter()
+                .any(|(key, value)| key == k && value.as_deref().is_none_or(|value| value == v))
+        })
+    }
+}
+
+impl Default for WayFilter {
+    fn default() -> Self {
+        Self::parse(DEFAULT_WAY_FILTER).expect("default way filter parses")
+    }
+}
+
+impl std::fmt::Display for WayFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.keeps_all() {
+            return f.write_str(ALL_WAYS);
+        }
+        for (i, (key, value)) in self.rules.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            match value {
+                Some(value) => write!(f, "{key}={value}")?,
+                None => f.write_str(key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_filter() {
+        let filter = WayFilter::default();
+        assert_eq!(filter.to_string(), DEFAULT_WAY_FILTER);
+        assert!(filter.matches([("highway", "residential")]));
+        assert!(filter.matches([("name", "Ferry"), ("route", "ferry")]));
+        assert!(filter.matches([("junction", "roundabout")]));
+        assert!(!filter.matches([("route", "bus")]));
+        assert!(!filter.matches([("building", "yes")]));
+        assert!(!filter.matches([]));
+    }
+
+    #[test]
+    fn test_parse_specs() {
+        let all = WayFilter::parse("all").unwrap();
+        assert!(all.keeps_all());
+        assert!(all.matches([("building", "yes")]));
+        assert_eq!(all.to_string(), "all");
+
+        let filter = WayFilter::parse(" highway , railway=platform").unwrap();
+        assert_eq!(filter.to_string(), "highway,railway=platform");
+        assert!(filter.matches([("railway", "platform")]));
+        assert!(!filter.matches([("railway", "rail")]));
+
+        assert!(WayFilter::parse("").is_err());
+        assert!(WayFilter::parse("highway,,route").is_err());
+        assert!(WayFilter::parse("route=").is_err());
+    }
+}
@@ -20,6 +20,7 @@ pub mod customization;
 pub mod density;
 pub mod ebg;
 pub mod formats;
+pub mod gradient;
 pub mod ingest;
 pub mod matrix;
 pub mod model;
@@ -32,6 +33,7 @@ pub mod profile;
 pub mod profile_abi;
 pub mod range;
 pub mod server;
+pub mod speeds;
 pub mod traffic;
 pub mod transit;
 pub mod validate;
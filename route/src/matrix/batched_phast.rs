@@ -105,7 +105,7 @@ impl BatchedPhastEngine {
         weights_path: &std::path::Path,
         _order_path: &std::path::Path, // Unused with rank-aligned CCH
     ) -> anyhow::Result<Self> {
-        let topo = CchTopoFile::read(topo_path)?;
+        let topo = CchTopoFile::read_mmap(topo_path)?;
         let weights = CchWeightsFile::read(weights_path)?;
 
         Ok(Self::new(topo, weights))
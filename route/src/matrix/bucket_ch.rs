@@ -1598,6 +1598,13 @@ fn write_adj_flat_body_and_footer(
             debug_assert_eq!(slice.len(), 3 * n_edges);
             out.extend_from_slice(slice);
         }
+        WeightArray::Q16(_) => {
+            // Flats are built at a lossless width, but a quantized
+            // array reports `U32` (see `WeightArray::width`) — widen.
+            for v in weights.iter() {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
     }
     // Trailing pad so the next array starts 4-B aligned. u32 widths
     // never pad; u16 pads 0 or 2; u24 pads 0/1/2/3.
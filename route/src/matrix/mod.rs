@@ -19,8 +19,12 @@
 //!
 //! ## Strategy Selection
 //!
-//! - **N×M ≤ 10,000**: Use bucket many-to-many (latency mode)
-//! - **N×M > 10,000**: Use tiled PHAST streaming (throughput mode)
+//! - **Per matrix**: `bucket_ch::table_seeded_bounded_routed` picks bucket
+//!   many-to-many (balanced shapes) or one seeded PHAST field per source
+//!   (lopsided shapes, few sources × many targets) from measured costs
+//! - **/table above 4M cells**: the server runs the matrix in blocks of
+//!   source rows, each a lopsided shape for the router, and writes every
+//!   block out before the next (`server::table::TABLE_TILE_CELLS`)
 //! - **Isochrones**: Always use PHAST (need all reachable nodes)
 
 pub mod arrow_stream;
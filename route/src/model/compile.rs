@@ -97,6 +97,13 @@ pub struct CompiledModel {
     // Highway key_id for fast lookup
     pub highway_key_id: Option<u32>,
 
+    // Ferry: `route=ferry` ways without a highway tag are routable at
+    // this speed (0 = not routable); `duration` overrides it in Step 2.
+    pub ferry_speed_mmps: u32,
+    pub route_key_id: Option<u32>,
+    pub ferry_value_id: Option<u32>,
+    pub duration_key_id: Option<u32>,
+
     // Turn restrictions
     pub respect_turn_restrictions: bool,
     pub restriction_key_id: Option<u32>,
@@ -129,6 +136,10 @@ impl CompiledModel {
             highway_class_table: vec![],
             class_bit_rules: vec![],
             highway_key_id: None,
+            ferry_speed_mmps: 0,
+            route_key_id: None,
+            ferry_value_id: None,
+            duration_key_id: None,
             respect_turn_restrictions: false,
             restriction_key_id: None,
             mode_restriction_key_id: None,
@@ -191,7 +202,7 @@ pub fn compile_model(
     }
 
     // Deny rules
-    let deny_rules: Vec<CompiledDenyRule> = schema
+    let mut deny_rules: Vec<CompiledDenyRule> = schema
         .access
         .deny_if
         .iter()
@@ -221,6 +232,13 @@ pub fn compile_model(
         })
         .collect();
 
+    // Vehicle dimensions: a `max*` limit below the vehicle is one more deny.
+    if let Some(vehicle) = &schema.vehicle {
+        deny_rules.extend(compile_dimension_limits(
+            vehicle, &rev_key, val_dict, table_len,
+        ));
+    }
+
     // --- Hard-deny table (#470: unconditional legal class bans) ---
     let mut hard_deny_table = vec![false; table_len];
     for highway_type in &schema.access.hard_deny_highways {
@@ -309,6 +327,12 @@ pub fn compile_model(
         })
         .collect();
 
+    // --- Ferry ---
+    let ferry_speed_mmps = schema
+        .ferry
+        .as_ref()
+        .map_or(0, |f| kmh_to_mmps(f.speed_kmh, speed_cap_mmps));
+
     // --- Turn restrictions ---
     let restriction_key_id = rev_key
         .get(schema.turn_restrictions.restriction_tag.as_str())
@@ -354,6 +378,11 @@ pub fn compile_model(
         class_bit_rules,
         highway_key_id,
 
+        ferry_speed_mmps,
+        route_key_id: rev_key.get("route").copied(),
+        ferry_value_id: rev_val.get("ferry").copied(),
+        duration_key_id: rev_key.get("duration").copied(),
+
         respect_turn_restrictions: schema.turn_restrictions.respect,
         restriction_key_id,
         mode_restriction_key_id,
@@ -415,6 +444,101 @@ fn compile_class_bit_rule(
     }
 }
 
+/// `(tag, vehicle dimension, value parser)`.
+type DimensionTag = (
+    &'static str,
+    fn(&VehicleDimensions) -> Option<f64>,
+    fn(&str) -> Option<f64>,
+);
+
+/// Way tags limiting each vehicle dimension.
+const DIMENSION_TAGS: [DimensionTag; 7] = [
+    ("maxheight", |v| v.height_m, parse_length_m),
+    ("maxheight:physical", |v| v.height_m, parse_length_m),
+    ("maxwidth", |v| v.width_m, parse_length_m),
+    ("maxwidth:physical", |v| v.width_m, parse_length_m),
+    ("maxlength", |v| v.length_m, parse_length_m),
+    ("maxweight", |v| v.weight_t, parse_weight_t),
+    ("maxweight:hgv", |v| v.weight_t, parse_weight_t),
+];
+
+/// One deny rule per `max*` key present in the dictionary, denying the
+/// values whose limit is below the vehicle's dimension. Values that do
+/// not parse (`none`, `default`, `below_default`, ...) never deny.
+fn compile_dimension_limits(
+    vehicle: &VehicleDimensions,
+    rev_key: &HashMap<&str, u32>,
+    val_dict: &HashMap<u32, String>,
+    table_len: usize,
+) -> Vec<CompiledDenyRule> {
+    DIMENSION_TAGS
+        .iter()
+        .filter_map(|&(tag, dimension, parse)| {
+            let key_id = *rev_key.get(tag)?;
+            let dimension = dimension(vehicle)?;
+            let mut denied_values = vec![false; table_len];
+            for (&vid, value) in val_dict {
+                if parse(value).is_some_and(|limit| limit < dimension) {
+                    denied_values[vid as usize] = true;
+                }
+            }
+            Some(CompiledDenyRule {
+                key_id,
+                denied_values,
+                unless: None,
+            })
+        })
+        .collect()
+}
+
+/// Parse an OSM length (`4`, `4.2 m`, `3.8m`, `12'6"`) into metres.
+pub fn parse_length_m(value: &str) -> Option<f64> {
+    let value = value.trim();
+    if let Some((feet, inches)) = value.split_once('\'') {
+        let feet: f64 = feet.trim().parse().ok()?;
+        let inches = inches.trim().trim_end_matches('"').trim();
+        let inches: f64 = if inches.is_empty() {
+            0.0
+        } else {
+            inches.parse().ok()?
+        };
+        return Some(feet * 0.3048 + inches * 0.0254);
+    }
+    let number = value.strip_suffix('m').unwrap_or(value).trim();
+    number
+        .parse()
+        .ok()
+        .filter(|m: &f64| m.is_finite() && *m > 0.0)
+}
+
+/// Parse an OSM weight (`7.5`, `7.5 t`, `3500 kg`) into tonnes.
+pub fn parse_weight_t(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let (number, scale) = if let Some(kg) = value.strip_suffix("kg") {
+        (kg, 0.001)
+    } else {
+        (value.strip_suffix('t').unwrap_or(value), 1.0)
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    (number.is_finite() && number > 0.0).then_some(number * scale)
+}
+
+/// Parse an OSM ferry `duration` (`1:30`, `01:30:00`, `45` minutes)
+/// into seconds.
+pub fn parse_duration_s(value: &str) -> Option<u32> {
+    let parts: Vec<&str> = value.trim().split(':').map(str::trim).collect();
+    let field = |s: &str| s.parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0);
+    let seconds = match parts.as_slice() {
+        [minutes] => field(minutes)? * 60.0,
+        [hours, minutes] => field(hours)? * 3600.0 + field(minutes)? * 60.0,
+        [hours, minutes, seconds] => {
+            field(hours)? * 3600.0 + field(minutes)? * 60.0 + field(seconds)?
+        }
+        _ => return None,
+    };
+    (seconds >= 1.0 && seconds < u32::MAX as f64).then_some(seconds.round() as u32)
+}
+
 /// Compile tag conditions from a HashMap<String, Value> into (key_id, value_id) pairs
 fn compile_tag_conditions(
     conditions: &HashMap<String, serde_json::Value>,
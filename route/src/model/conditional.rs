@@ -0,0 +1,373 @@
+//! Conditional restrictions (`*:conditional`) and their weekly schedules
+//!
+//! `access:conditional=no @ (Mo-Fr 07:00-09:00)` and
+//! `oneway:conditional=yes @ (Mo-Fr 16:00-19:00)` only hold at certain
+//! times. Step 2 turns them into per-mode [`WeekSchedule`]s (15-minute
+//! slots over a Monday-first week) written to
+//! `way_conditionals.<mode>.bin`, next to the static way_attrs.
+//!
+//! Only the time-of-week subset of `opening_hours` is understood:
+//! weekday lists/ranges, `HH:MM-HH:MM` ranges (wrapping past midnight)
+//! and `24/7`. Clauses with dates, holidays or non-time conditions
+//! (`wet`, `weight>7.5`) are skipped, leaving the static tag in force.
+
+use std::collections::HashMap;
+
+use super::schema::ModelSchema;
+
+pub const SLOT_MINUTES: u32 = 15;
+pub const SLOTS_PER_DAY: usize = (24 * 60 / SLOT_MINUTES) as usize;
+pub const SLOTS_PER_WEEK: usize = 7 * SLOTS_PER_DAY;
+/// u64 words backing one schedule.
+pub const SCHEDULE_WORDS: usize = SLOTS_PER_WEEK.div_ceil(64);
+
+/// Set of 15-minute slots in a week; slot 0 is Monday 00:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WeekSchedule {
+    pub words: [u64; SCHEDULE_WORDS],
+}
+
+impl WeekSchedule {
+    pub fn always() -> Self {
+        let mut schedule = Self::default();
+        schedule.set_range(0, SLOTS_PER_WEEK);
+        schedule
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Whether the schedule covers `weekday` (0 = Monday) at
+    /// `minute_of_day`.
+    pub fn contains(&self, weekday: u32, minute_of_day: u32) -> bool {
+        let slot = weekday as usize * SLOTS_PER_DAY + (minute_of_day / SLOT_MINUTES) as usize;
+        slot < SLOTS_PER_WEEK && self.words[slot / 64] & (1 << (slot % 64)) != 0
+    }
+
+    pub fn union(&mut self, other: &Self) {
+        for (w, o) in self.words.iter_mut().zip(other.words) {
+            *w |= o;
+        }
+    }
+
+    /// Set slots `start..end`, wrapping from Sunday into Monday.
+    fn set_range(&mut self, start: usize, end: usize) {
+        for slot in start..end {
+            let slot = slot % SLOTS_PER_WEEK;
+            self.words[slot / 64] |= 1 << (slot % 64);
+        }
+    }
+}
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+fn parse_weekday(s: &str) -> Option<usize> {
+    WEEKDAYS.iter().position(|&d| d == s)
+}
+
+/// `Mo-Fr,Su` -> per-day flags.
+fn parse_weekdays(s: &str) -> Option<[bool; 7]> {
+    let mut days = [false; 7];
+    for part in s.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_weekday(from)?, parse_weekday(to)?);
+                let mut day = from;
+                loop {
+                    days[day] = true;
+                    if day == to {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+            None => days[parse_weekday(part)?] = true,
+        }
+    }
+    Some(days)
+}
+
+/// `HH:MM` -> slot of the day (24:00 allowed as an end).
+fn parse_time(s: &str) -> Option<usize> {
+    let (h, m) = s.split_once(':')?;
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    if m >= 60 || h > 24 || (h == 24 && m > 0) {
+        return None;
+    }
+    Some(((h * 60 + m) / SLOT_MINUTES) as usize)
+}
+
+/// `07:00-09:00,16:00-18:00` -> slot ranges within a day (end may pass
+/// midnight).
+fn parse_times(s: &str) -> Option<Vec<(usize, usize)>> {
+    s.split(',')
+        .map(|range| {
+            let (from, to) = range.split_once('-')?;
+            let (from, to) = (parse_time(from)?, parse_time(to)?);
+            Some(if to > from {
+                (from, to)
+            } else {
+                (from, to + SLOTS_PER_DAY)
+            })
+        })
+        .collect()
+}
+
+/// Parse the time-of-week subset of `opening_hours` (see module docs).
+pub fn parse_opening_hours(s: &str) -> Option<WeekSchedule> {
+    let mut schedule = WeekSchedule::default();
+    for rule in s.split(';').map(str::trim).filter(|r| !r.is_empty()) {
+        if rule == "24/7" {
+            return Some(WeekSchedule::always());
+        }
+        let mut parts = rule.split_whitespace();
+        let first = parts.next()?;
+        let (days, times) = match parse_weekdays(first) {
+            Some(days) => (days, parts.next()),
+            None => ([true; 7], Some(first)),
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        let times = match times {
+            Some(times) => parse_times(times)?,
+            None => vec![(0, SLOTS_PER_DAY)],
+        };
+        for (day, _) in days.iter().enumerate().filter(|(_, on)| **on) {
+            for &(from, to) in &times {
+                let base = day * SLOTS_PER_DAY;
+                schedule.set_range(base + from, base + to);
+            }
+        }
+    }
+    (!schedule.is_empty()).then_some(schedule)
+}
+
+/// Split a `*:conditional` value into `(value, schedule)` clauses,
+/// dropping clauses whose condition is not a time of week.
+pub fn parse_conditional(s: &str) -> Vec<(String, WeekSchedule)> {
+    let mut clauses = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ';' if depth == 0 => {
+                clauses.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    clauses.push(&s[start..]);
+
+    clauses
+        .into_iter()
+        .filter_map(|clause| {
+            let (value, condition) = clause.split_once('@')?;
+            let condition = condition.trim();
+            let condition = condition
+                .strip_prefix('(')
+                .and_then(|c| c.strip_suffix(')'))
+                .unwrap_or(condition);
+            Some((value.trim().to_string(), parse_opening_hours(condition)?))
+        })
+        .collect()
+}
+
+/// What a conditional does to a way while its schedule holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum ConditionalKind {
+    /// Not accessible.
+    Deny = 0,
+    /// Accessible although the static tags deny it.
+    Allow = 1,
+    /// One-way in way direction.
+    OnewayFwd = 2,
+    /// One-way against way direction.
+    OnewayRev = 3,
+}
+
+impl ConditionalKind {
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(Self::Deny),
+            1 => Some(Self::Allow),
+            2 => Some(Self::OnewayFwd),
+            3 => Some(Self::OnewayRev),
+            _ => None,
+        }
+    }
+}
+
+/// One `*:conditional` key a mode listens to.
+#[derive(Debug, Clone)]
+struct ConditionalKey {
+    key_id: u32,
+    /// Clause values and what they mean for the mode.
+    values: HashMap<String, ConditionalKind>,
+}
+
+/// A mode's conditional keys, compiled against the way dictionaries:
+/// `<tag>:conditional` for every `deny_if` tag of the model and
+/// `<oneway tag>:conditional` when it respects oneways.
+#[derive(Debug, Clone, Default)]
+pub struct CompiledConditionals {
+    keys: Vec<ConditionalKey>,
+}
+
+impl CompiledConditionals {
+    pub fn compile(schema: &ModelSchema, key_dict: &HashMap<u32, String>) -> Self {
+        let rev_key: HashMap<&str, u32> =
+            key_dict.iter().map(|(id, s)| (s.as_str(), *id)).collect();
+        let key_id = |tag: &str| rev_key.get(format!("{tag}:conditional").as_str()).copied();
+
+        let mut keys = Vec::new();
+        for rule in &schema.access.deny_if {
+            let Some(key_id) = key_id(&rule.tag) else {
+                continue;
+            };
+            let mut values: HashMap<String, ConditionalKind> = ["yes", "permissive", "designated"]
+                .into_iter()
+                .map(|v| (v.to_string(), ConditionalKind::Allow))
+                .collect();
+            for v in &rule.values {
+                values.insert(v.clone(), ConditionalKind::Deny);
+            }
+            keys.push(ConditionalKey { key_id, values });
+        }
+        if schema.oneway.respect
+            && let Some(key_id) = key_id(&schema.oneway.tag)
+        {
+            let values = (schema.oneway.forward_values.iter())
+                .map(|v| (v.clone(), ConditionalKind::OnewayFwd))
+                .chain(
+                    (schema.oneway.reverse_values.iter())
+                        .map(|v| (v.clone(), ConditionalKind::OnewayRev)),
+                )
+                .collect();
+            keys.push(ConditionalKey { key_id, values });
+        }
+        Self { keys }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Schedules per kind for one way's tags, merged across keys and
+    /// clauses; empty when the way has no applicable conditional.
+    pub fn evaluate(
+        &self,
+        kv_keys: &[u32],
+        kv_vals: &[u32],
+        val_dict: &HashMap<u32, String>,
+    ) -> Vec<(ConditionalKind, WeekSchedule)> {
+        let mut out: Vec<(ConditionalKind, WeekSchedule)> = Vec::new();
+        for key in &self.keys {
+            let Some(pos) = kv_keys.iter().position(|&k| k == key.key_id) else {
+                continue;
+            };
+            let Some(value) = val_dict.get(&kv_vals[pos]) else {
+                continue;
+            };
+            for (clause_value, schedule) in parse_conditional(value) {
+                let Some(&kind) = key.values.get(&clause_value) else {
+                    continue;
+                };
+                match out.iter_mut().find(|(k, _)| *k == kind) {
+                    Some((_, merged)) => merged.union(&schedule),
+                    None => out.push((kind, schedule)),
+                }
+            }
+        }
+        out.sort_by_key(|(kind, _)| *kind);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_opening_hours() {
+        let s = parse_opening_hours("Mo-Fr 07:00-09:30").unwrap();
+        assert!(s.contains(0, 7 * 60));
+        assert!(s.contains(4, 9 * 60 + 15));
+        assert!(!s.contains(4, 9 * 60 + 30));
+        assert!(!s.contains(5, 8 * 60));
+
+        // Time only = every day; wrapping ranges continue the next day.
+        let s = parse_opening_hours("22:00-06:00").unwrap();
+        assert!(s.contains(6, 23 * 60));
+        assert!(s.contains(0, 5 * 60)); // Sunday night wraps into Monday
+        assert!(!s.contains(2, 12 * 60));
+
+        let s = parse_opening_hours("Sa,Su; Mo 08:00-10:00,16:00-18:00").unwrap();
+        assert!(s.contains(5, 0) && s.contains(6, 23 * 60 + 45));
+        assert!(s.contains(0, 17 * 60) && !s.contains(0, 12 * 60));
+
+        assert_eq!(parse_opening_hours("24/7"), Some(WeekSchedule::always()));
+        assert_eq!(
+            parse_opening_hours("Fr-Mo").map(|s| s.contains(6, 0)),
+            Some(true)
+        );
+
+        for unsupported in ["PH off", "Jan-Mar", "sunrise-sunset", "Mo 25:00-26:00", ""] {
+            assert_eq!(parse_opening_hours(unsupported), None, "{unsupported}");
+        }
+    }
+
+    #[test]
+    fn test_parse_conditional() {
+        let clauses = parse_conditional("no @ (Mo-Fr 07:00-09:00; Sa 10:00-12:00); yes @ wet");
+        assert_eq!(clauses.len(), 1);
+        assert_eq!(clauses[0].0, "no");
+        assert!(clauses[0].1.contains(5, 11 * 60));
+
+        let clauses = parse_conditional("delivery @ 05:00-11:00");
+        assert_eq!(clauses[0].0, "delivery");
+        assert!(parse_conditional("no").is_empty());
+    }
+
+    #[test]
+    fn test_compiled_conditionals() {
+        let json = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../models/car.model.json"
+        ))
+        .unwrap();
+        let schema: ModelSchema = serde_json::from_str(&json).unwrap();
+        let key_dict: HashMap<u32, String> = [
+            (1, "highway"),
+            (2, "access:conditional"),
+            (3, "oneway:conditional"),
+        ]
+        .into_iter()
+        .map(|(id, s)| (id, s.to_string()))
+        .collect();
+        let val_dict: HashMap<u32, String> = [
+            (1, "residential"),
+            (2, "no @ (Mo-Fr 07:00-09:00)"),
+            (3, "-1 @ (16:00-18:00)"),
+        ]
+        .into_iter()
+        .map(|(id, s)| (id, s.to_string()))
+        .collect();
+
+        let compiled = CompiledConditionals::compile(&schema, &key_dict);
+        let out = compiled.evaluate(&[1, 2, 3], &[1, 2, 3], &val_dict);
+        let kinds: Vec<_> = out.iter().map(|(k, _)| *k).collect();
+        assert_eq!(
+            kinds,
+            vec![ConditionalKind::Deny, ConditionalKind::OnewayRev]
+        );
+        assert!(out[0].1.contains(1, 8 * 60));
+        assert!(out[1].1.contains(6, 17 * 60));
+        assert!(compiled.evaluate(&[1], &[1], &val_dict).is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+//! Country lookup for per-country default speeds
+//!
+//! Implied speed limits differ by country (rural roads: 80 km/h in FR,
+//! 100 km/h in DE), so a model may carry `speed.country` tables keyed by
+//! ISO 3166-1 alpha-2 code. Step 2 locates each way by its first node
+//! and, when it falls inside a boundary from `--countries`, evaluates it
+//! against the model compiled with that country's speeds.
+//!
+//! The boundary file is a GeoJSON FeatureCollection of `Polygon` /
+//! `MultiPolygon` features (e.g. an `admin_level=2` extract). The code is
+//! read from the first of the `ISO3166-1`, `ISO3166-1:alpha2`, `iso_a2`
+//! or `code` properties. Overlapping boundaries resolve to the first
+//! feature in file order.
+
+use anyhow::{Context, Result};
+use geo::{BoundingRect, Contains, Coord, LineString, MultiPolygon, Point, Polygon, Rect};
+use std::path::Path;
+
+const CODE_PROPERTIES: [&str; 4] = ["ISO3166-1", "ISO3166-1:alpha2", "iso_a2", "code"];
+
+struct Country {
+    code: String,
+    shape: MultiPolygon<f64>,
+    bbox: Rect<f64>,
+}
+
+/// Country boundaries loaded for Step 2.
+pub struct CountryBoundaries {
+    countries: Vec<Country>,
+}
+
+impl CountryBoundaries {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_geojson(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn from_geojson(content: &str) -> Result<Self> {
+        let root: serde_json::Value = serde_json::from_str(content)?;
+        let features = root["features"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Expected a GeoJSON FeatureCollection"))?;
+
+        let mut countries = Vec::with_capacity(features.len());
+        for (i, feature) in features.iter().enumerate() {
+            let code = CODE_PROPERTIES
+                .iter()
+                .find_map(|p| feature["properties"][p].as_str())
+                .ok_or_else(|| anyhow::anyhow!("features[{i}] has no country code property"))?
+                .to_ascii_uppercase();
+            let geometry = &feature["geometry"];
+            let coords = &geometry["coordinates"];
+            let polygons = match geometry["type"].as_str() {
+                Some("Polygon") => vec![parse_polygon(coords)?],
+                Some("MultiPolygon") => coords
+                    .as_array()
+                    .ok_or_else(|| anyhow::anyhow!("features[{i}]: bad MultiPolygon"))?
+                    .iter()
+                    .map(parse_polygon)
+                    .collect::<Result<_>>()?,
+                other => anyhow::bail!("features[{i}] ({code}): unsupported geometry {other:?}"),
+            };
+            let shape = MultiPolygon::new(polygons);
+            let bbox = shape
+                .bounding_rect()
+                .ok_or_else(|| anyhow::anyhow!("features[{i}] ({code}) is empty"))?;
+            countries.push(Country { code, shape, bbox });
+        }
+        Ok(Self { countries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.countries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.countries.is_empty()
+    }
+
+    /// Code of boundary `index`.
+    pub fn code(&self, index: usize) -> &str {
+        &self.countries[index].code
+    }
+
+    /// Index of the boundary containing the point, if any.
+    pub fn lookup(&self, lat: f64, lon: f64) -> Option<usize> {
+        let point = Point::new(lon, lat);
+        self.countries.iter().position(|c| {
+            let (min, max) = (c.bbox.min(), c.bbox.max());
+            lon >= min.x && lon <= max.x && lat >= min.y && lat <= max.y && c.shape.contains(&point)
+        })
+    }
+}
+
+/// `[[ [lon, lat], ... ], hole, ...]` -> polygon.
+fn parse_polygon(value: &serde_json::Value) -> Result<Polygon<f64>> {
+    let rings = value
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("Polygon must be an array of rings"))?;
+    let mut rings = rings.iter().map(|ring| {
+        ring.as_array()
+            .ok_or_else(|| anyhow::anyhow!("Ring must be an array of positions"))?
+            .iter()
+            .map(|pos| match (pos[0].as_f64(), pos[1].as_f64()) {
+                (Some(x), Some(y)) => Ok(Coord { x, y }),
+                _ => anyhow::bail!("Position must be [lon, lat]"),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(LineString::from)
+    });
+    let exterior = rings
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Polygon has no exterior ring"))??;
+    Ok(Polygon::new(exterior, rings.collect::<Result<_>>()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GEOJSON: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {"type": "Feature", "properties": {"ISO3166-1": "fr"},
+             "geometry": {"type": "Polygon", "coordinates": [
+                [[0, 0], [10, 0], [10, 10], [0, 10], [0, 0]],
+                [[4, 4], [6, 4], [6, 6], [4, 6], [4, 4]]]}},
+            {"type": "Feature", "properties": {"iso_a2": "DE"},
+             "geometry": {"type": "MultiPolygon", "coordinates": [
+                [[[10, 0], [20, 0], [20, 10], [10, 10], [10, 0]]],
+                [[[5, 5], [5.5, 5], [5.5, 5.5], [5, 5.5], [5, 5]]]]}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_lookup() {
+        let countries = CountryBoundaries::from_geojson(GEOJSON).unwrap();
+        assert_eq!(countries.len(), 2);
+        let code = |lat, lon| countries.lookup(lat, lon).map(|i| countries.code(i));
+        assert_eq!(code(2.0, 2.0), Some("FR"));
+        assert_eq!(code(5.0, 15.0), Some("DE"));
+        // Hole in FR with a DE exclave inside it.
+        assert_eq!(code(5.2, 5.2), Some("DE"));
+        assert_eq!(code(4.5, 4.5), None);
+        assert_eq!(code(-1.0, 2.0), None);
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(CountryBoundaries::from_geojson("{}").is_err());
+        let no_code = r#"{"features": [{"properties": {},
+            "geometry": {"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1]]]}}]}"#;
+        assert!(CountryBoundaries::from_geojson(no_code).is_err());
+    }
+}
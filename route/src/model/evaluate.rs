@@ -26,7 +26,8 @@ pub fn evaluate_way(
 
     let highway_val_id = match find_value_for_key(kv_keys, kv_vals, highway_key_id) {
         Some(vid) => vid,
-        None => return output, // No highway tag = not routable
+        // No highway tag = not routable, unless it is a ferry route
+        None => return evaluate_ferry(model, kv_keys, kv_vals, val_dict),
     };
 
     let hw_idx = highway_val_id as usize;
@@ -59,22 +60,8 @@ pub fn evaluate_way(
     }
 
     // Check deny rules (explicit access tag overrides)
-    for deny in &model.deny_rules {
-        if let Some(val_id) = find_value_for_key(kv_keys, kv_vals, deny.key_id) {
-            let vidx = val_id as usize;
-            if vidx < deny.denied_values.len() && deny.denied_values[vidx] {
-                // #478: OSM specific-over-generic — e.g. `access=no` must
-                // not deny a car when `motor_vehicle=yes` is present.
-                if let Some((ukey, uvals)) = &deny.unless
-                    && let Some(uval) = find_value_for_key(kv_keys, kv_vals, *ukey)
-                    && (uval as usize) < uvals.len()
-                    && uvals[uval as usize]
-                {
-                    continue; // rescued by the more-specific tag
-                }
-                return output; // Explicitly denied
-            }
-        }
+    if is_denied(model, kv_keys, kv_vals) {
+        return output; // Explicitly denied
     }
 
     // Set access flags
@@ -82,33 +69,7 @@ pub fn evaluate_way(
     output.access_rev = true;
 
     // Handle oneway
-    if model.respect_oneway {
-        if let Some(oneway_key_id) = model.oneway_key_id
-            && let Some(oneway_val_id) = find_value_for_key(kv_keys, kv_vals, oneway_key_id)
-        {
-            if model.forward_value_ids.contains(&oneway_val_id) {
-                output.access_rev = false;
-                output.oneway = 1;
-            } else if model.reverse_value_ids.contains(&oneway_val_id) {
-                output.access_fwd = false;
-                output.oneway = 2;
-            }
-        }
-
-        // Default oneways (e.g., motorways)
-        if output.oneway == 0 && model.default_oneway_highway_ids.contains(&highway_val_id) {
-            output.access_rev = false;
-            output.oneway = 1;
-        }
-    } else if let Some(oneway_key_id) = model.oneway_key_id {
-        // Mode doesn't respect car oneways but may have its own oneway tag (e.g., oneway:bicycle)
-        if let Some(oneway_val_id) = find_value_for_key(kv_keys, kv_vals, oneway_key_id)
-            && model.forward_value_ids.contains(&oneway_val_id)
-        {
-            output.access_rev = false;
-            output.oneway = 1;
-        }
-    }
+    apply_oneway(model, kv_keys, kv_vals, Some(highway_val_id), &mut output);
 
     // Speed (skip if already set by allow_if rule)
     if output.base_speed_mmps == 0 {
@@ -135,11 +96,7 @@ pub fn evaluate_way(
     };
 
     // Class bits
-    for &(bit_pos, ref rule) in &model.class_bit_rules {
-        if check_class_bit_rule(rule, kv_keys, kv_vals, highway_val_id, val_dict) {
-            output.class_bits |= 1 << bit_pos;
-        }
-    }
+    output.class_bits = class_bits(model, kv_keys, kv_vals, Some(highway_val_id), val_dict);
 
     // Priority rules (compute per_km_penalty_ds)
     for rule in &model.priority_rules {
@@ -168,6 +125,137 @@ pub fn evaluate_way(
     output
 }
 
+/// Evaluate a way without a highway tag. Only ferry routes (`route=ferry`)
+/// are routable, at the model's ferry speed, and only for models with a
+/// `ferry` section. Deny rules and oneway tags apply as on roads, so
+/// `motor_vehicle=no` ferries stay closed to cars and `avoid=ferry` works.
+fn evaluate_ferry(
+    model: &CompiledModel,
+    kv_keys: &[u32],
+    kv_vals: &[u32],
+    val_dict: &std::collections::HashMap<u32, String>,
+) -> WayOutput {
+    let mut output = WayOutput::default();
+    if model.ferry_speed_mmps == 0
+        || !is_ferry(model, kv_keys, kv_vals)
+        || is_denied(model, kv_keys, kv_vals)
+    {
+        return output;
+    }
+
+    output.access_fwd = true;
+    output.access_rev = true;
+    apply_oneway(model, kv_keys, kv_vals, None, &mut output);
+    output.base_speed_mmps = model.ferry_speed_mmps;
+    output.class_bits = class_bits(model, kv_keys, kv_vals, None, val_dict);
+    output
+}
+
+/// Crossing time in seconds from a ferry route's `duration` tag. `None`
+/// for other ways and for missing or unparseable durations.
+pub fn ferry_duration_s(
+    model: &CompiledModel,
+    kv_keys: &[u32],
+    kv_vals: &[u32],
+    val_dict: &std::collections::HashMap<u32, String>,
+) -> Option<u32> {
+    if !is_ferry(model, kv_keys, kv_vals) {
+        return None;
+    }
+    let val_id = find_value_for_key(kv_keys, kv_vals, model.duration_key_id?)?;
+    super::compile::parse_duration_s(val_dict.get(&val_id)?)
+}
+
+/// Whether the way is tagged `route=ferry`.
+#[inline]
+fn is_ferry(model: &CompiledModel, kv_keys: &[u32], kv_vals: &[u32]) -> bool {
+    match (model.route_key_id, model.ferry_value_id) {
+        (Some(key_id), Some(value_id)) => {
+            find_value_for_key(kv_keys, kv_vals, key_id) == Some(value_id)
+        }
+        _ => false,
+    }
+}
+
+/// Check deny rules (explicit access tag overrides)
+fn is_denied(model: &CompiledModel, kv_keys: &[u32], kv_vals: &[u32]) -> bool {
+    for deny in &model.deny_rules {
+        if let Some(val_id) = find_value_for_key(kv_keys, kv_vals, deny.key_id) {
+            let vidx = val_id as usize;
+            if vidx < deny.denied_values.len() && deny.denied_values[vidx] {
+                // #478: OSM specific-over-generic — e.g. `access=no` must
+                // not deny a car when `motor_vehicle=yes` is present.
+                if let Some((ukey, uvals)) = &deny.unless
+                    && let Some(uval) = find_value_for_key(kv_keys, kv_vals, *ukey)
+                    && (uval as usize) < uvals.len()
+                    && uvals[uval as usize]
+                {
+                    continue; // rescued by the more-specific tag
+                }
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Restrict access by oneway tags; `highway_val_id` selects default
+/// oneways (e.g. motorways) and is `None` for ferries.
+fn apply_oneway(
+    model: &CompiledModel,
+    kv_keys: &[u32],
+    kv_vals: &[u32],
+    highway_val_id: Option<u32>,
+    output: &mut WayOutput,
+) {
+    if model.respect_oneway {
+        if let Some(oneway_key_id) = model.oneway_key_id
+            && let Some(oneway_val_id) = find_value_for_key(kv_keys, kv_vals, oneway_key_id)
+        {
+            if model.forward_value_ids.contains(&oneway_val_id) {
+                output.access_rev = false;
+                output.oneway = 1;
+            } else if model.reverse_value_ids.contains(&oneway_val_id) {
+                output.access_fwd = false;
+                output.oneway = 2;
+            }
+        }
+
+        // Default oneways (e.g., motorways)
+        if output.oneway == 0
+            && highway_val_id.is_some_and(|hw| model.default_oneway_highway_ids.contains(&hw))
+        {
+            output.access_rev = false;
+            output.oneway = 1;
+        }
+    } else if let Some(oneway_key_id) = model.oneway_key_id {
+        // Mode doesn't respect car oneways but may have its own oneway tag (e.g., oneway:bicycle)
+        if let Some(oneway_val_id) = find_value_for_key(kv_keys, kv_vals, oneway_key_id)
+            && model.forward_value_ids.contains(&oneway_val_id)
+        {
+            output.access_rev = false;
+            output.oneway = 1;
+        }
+    }
+}
+
+/// Feature bits set by the model's class bit rules.
+fn class_bits(
+    model: &CompiledModel,
+    kv_keys: &[u32],
+    kv_vals: &[u32],
+    highway_val_id: Option<u32>,
+    val_dict: &std::collections::HashMap<u32, String>,
+) -> u32 {
+    let mut bits = 0;
+    for &(bit_pos, ref rule) in &model.class_bit_rules {
+        if check_class_bit_rule(rule, kv_keys, kv_vals, highway_val_id, val_dict) {
+            bits |= 1 << bit_pos;
+        }
+    }
+    bits
+}
+
 /// Determine if a mode is a motor vehicle mode (applies generic turn restrictions)
 fn is_motor_vehicle_mode(mode_name: &str) -> bool {
     matches!(mode_name, "car" | "truck" | "bus" | "taxi" | "motorcycle")
@@ -218,22 +306,24 @@ fn check_class_bit_rule(
     rule: &CompiledClassBitRule,
     kv_keys: &[u32],
     kv_vals: &[u32],
-    highway_val_id: u32,
+    highway_val_id: Option<u32>,
     val_dict: &std::collections::HashMap<u32, String>,
 ) -> bool {
     match rule {
         CompiledClassBitRule::TagValue { key_id, value_id } => {
             find_value_for_key(kv_keys, kv_vals, *key_id) == Some(*value_id)
         }
-        CompiledClassBitRule::Highway { value_id } => highway_val_id == *value_id,
+        CompiledClassBitRule::Highway { value_id } => highway_val_id == Some(*value_id),
         CompiledClassBitRule::HighwaySuffix { suffix } => {
-            if let Some(hw_str) = val_dict.get(&highway_val_id) {
+            if let Some(hw_str) = highway_val_id.and_then(|hw| val_dict.get(&hw)) {
                 hw_str.ends_with(suffix.as_str())
             } else {
                 false
             }
         }
-        CompiledClassBitRule::HighwayAny { value_ids } => value_ids.contains(&highway_val_id),
+        CompiledClassBitRule::HighwayAny { value_ids } => {
+            highway_val_id.is_some_and(|hw| value_ids.contains(&hw))
+        }
     }
 }
 
@@ -329,6 +419,11 @@ mod tests {
     const K_SIDEWALK: u32 = 4;
     const K_ACCESS: u32 = 5;
     const K_MOTOR_VEHICLE: u32 = 6;
+    const K_MAXHEIGHT: u32 = 7;
+    const K_MAXWEIGHT: u32 = 8;
+    const K_HGV: u32 = 9;
+    const K_ROUTE: u32 = 10;
+    const K_DURATION: u32 = 11;
 
     const V_MOTORWAY: u32 = 1;
     const V_MOTORWAY_LINK: u32 = 2;
@@ -338,6 +433,14 @@ mod tests {
     const V_NO: u32 = 6;
     const V_PRIVATE: u32 = 7;
     const V_TRUNK: u32 = 8;
+    const V_3_5: u32 = 9;
+    const V_4_2_M: u32 = 10;
+    const V_12_FT_6: u32 = 11;
+    const V_7_5_T: u32 = 12;
+    const V_NONE: u32 = 13;
+    const V_DESIGNATED: u32 = 14;
+    const V_FERRY: u32 = 15;
+    const V_1_30: u32 = 16;
 
     fn dicts() -> (HashMap<u32, String>, HashMap<u32, String>) {
         let key_dict: HashMap<u32, String> = [
@@ -347,6 +450,11 @@ mod tests {
             (K_SIDEWALK, "sidewalk"),
             (K_ACCESS, "access"),
             (K_MOTOR_VEHICLE, "motor_vehicle"),
+            (K_MAXHEIGHT, "maxheight"),
+            (K_MAXWEIGHT, "maxweight"),
+            (K_HGV, "hgv"),
+            (K_ROUTE, "route"),
+            (K_DURATION, "duration"),
         ]
         .into_iter()
         .map(|(id, s)| (id, s.to_string()))
@@ -360,6 +468,14 @@ mod tests {
             (V_NO, "no"),
             (V_PRIVATE, "private"),
             (V_TRUNK, "trunk"),
+            (V_3_5, "3.5"),
+            (V_4_2_M, "4.2 m"),
+            (V_12_FT_6, "12'6\""),
+            (V_7_5_T, "7.5 t"),
+            (V_NONE, "none"),
+            (V_DESIGNATED, "designated"),
+            (V_FERRY, "ferry"),
+            (V_1_30, "1:30"),
         ]
         .into_iter()
         .map(|(id, s)| (id, s.to_string()))
@@ -545,4 +661,110 @@ mod tests {
         );
         assert!(out.access_fwd);
     }
+
+    /// Truck dimensions: a `maxheight`/`maxweight` below the vehicle
+    /// denies the way, a higher or unparseable limit does not.
+    #[test]
+    fn truck_dimension_limits() {
+        let (model, val_dict) = compile_shipped("truck");
+        let eval = |keys: &[u32], vals: &[u32]| evaluate_way(&model, keys, vals, &val_dict);
+
+        // Shipped truck is 4 m / 40 t.
+        assert_no_access(&eval(&[K_HIGHWAY, K_MAXHEIGHT], &[V_TRUNK, V_3_5]));
+        assert_no_access(&eval(&[K_HIGHWAY, K_MAXHEIGHT], &[V_TRUNK, V_12_FT_6]));
+        assert_no_access(&eval(&[K_HIGHWAY, K_MAXWEIGHT], &[V_TRUNK, V_7_5_T]));
+        assert!(eval(&[K_HIGHWAY, K_MAXHEIGHT], &[V_TRUNK, V_4_2_M]).access_fwd);
+        assert!(eval(&[K_HIGHWAY, K_MAXHEIGHT], &[V_TRUNK, V_NONE]).access_fwd);
+
+        // Car ignores the limits: it has no vehicle section.
+        let (car, val_dict) = compile_shipped("car");
+        let out = evaluate_way(
+            &car,
+            &[K_HIGHWAY, K_MAXHEIGHT],
+            &[V_TRUNK, V_3_5],
+            &val_dict,
+        );
+        assert!(out.access_fwd);
+    }
+
+    /// A smaller truck supplied at Step 2 passes the 3.5 m / 7.5 t limits.
+    #[test]
+    fn truck_vehicle_override() {
+        let path = format!("{}/../models/truck.model.json", env!("CARGO_MANIFEST_DIR"));
+        let mut schema: ModelSchema =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        schema.vehicle.as_mut().unwrap().merge(
+            &crate::model::schema::VehicleDimensions::parse_spec("height=3.4,weight=7").unwrap(),
+        );
+        let (key_dict, val_dict) = dicts();
+        let model = compile_model(&schema, 0, [0u8; 32], &key_dict, &val_dict);
+        let out = evaluate_way(
+            &model,
+            &[K_HIGHWAY, K_MAXHEIGHT, K_MAXWEIGHT],
+            &[V_TRUNK, V_3_5, V_7_5_T],
+            &val_dict,
+        );
+        assert!(out.access_fwd);
+    }
+
+    /// `access=no` + `hgv=designated` stays routable for truck only.
+    #[test]
+    fn truck_hgv_designated_overrides_access_no() {
+        let (truck, val_dict) = compile_shipped("truck");
+        let keys = [K_HIGHWAY, K_ACCESS, K_HGV];
+        let vals = [V_TRUNK, V_NO, V_DESIGNATED];
+        assert!(evaluate_way(&truck, &keys, &vals, &val_dict).access_fwd);
+        let (car, val_dict) = compile_shipped("car");
+        assert_no_access(&evaluate_way(&car, &keys, &vals, &val_dict));
+    }
+
+    /// A `route=ferry` way without a highway tag is routable at the
+    /// model's ferry speed and carries the ferry class bit where the
+    /// model declares one (car does, foot only has `footway`).
+    #[test]
+    fn ferry_without_highway_routable() {
+        for name in ["car", "foot"] {
+            let (model, val_dict) = compile_shipped(name);
+            let out = evaluate_way(&model, &[K_ROUTE], &[V_FERRY], &val_dict);
+            assert!(
+                out.access_fwd && out.access_rev,
+                "{name}: ferry must be routable"
+            );
+            assert_eq!(out.base_speed_mmps, model.ferry_speed_mmps);
+            let ferry_bit = out.class_bits & (1 << crate::profile_abi::class_bits::FERRY);
+            assert_eq!(ferry_bit != 0, name == "car", "{name}: ferry class bit");
+        }
+
+        // Deny rules still apply: no cars on a foot ferry.
+        let (car, val_dict) = compile_shipped("car");
+        assert_no_access(&evaluate_way(
+            &car,
+            &[K_ROUTE, K_MOTOR_VEHICLE],
+            &[V_FERRY, V_NO],
+            &val_dict,
+        ));
+
+        // Models without a ferry section keep ferries unroutable.
+        let mut plain = car.clone();
+        plain.ferry_speed_mmps = 0;
+        assert_no_access(&evaluate_way(&plain, &[K_ROUTE], &[V_FERRY], &val_dict));
+    }
+
+    #[test]
+    fn ferry_duration() {
+        let (model, val_dict) = compile_shipped("car");
+        let duration = |keys: &[u32], vals: &[u32]| ferry_duration_s(&model, keys, vals, &val_dict);
+        assert_eq!(
+            duration(&[K_ROUTE, K_DURATION], &[V_FERRY, V_1_30]),
+            Some(5400)
+        );
+        assert_eq!(duration(&[K_ROUTE], &[V_FERRY]), None);
+        assert_eq!(duration(&[K_HIGHWAY, K_DURATION], &[V_TRUNK, V_1_30]), None);
+
+        use crate::model::compile::parse_duration_s;
+        assert_eq!(parse_duration_s("45"), Some(2700));
+        assert_eq!(parse_duration_s("01:05:30"), Some(3930));
+        assert_eq!(parse_duration_s("0:00"), None);
+        assert_eq!(parse_duration_s("PT1H"), None);
+    }
 }
@@ -11,13 +11,16 @@
 //! 4. **ModeInfo** — dynamic mode discovery with deterministic alphabetical indexing
 
 pub mod compile;
+pub mod conditional;
+pub mod country;
 pub mod evaluate;
+pub mod params;
 pub mod profiling;
 pub mod schema;
 pub mod types;
 
 pub use compile::{CompiledModel, compile_model};
-pub use evaluate::{evaluate_turn_full, evaluate_way};
+pub use evaluate::{evaluate_turn_full, evaluate_way, ferry_duration_s};
 pub use schema::ModelSchema;
 
 use anyhow::{Context, Result};
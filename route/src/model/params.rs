@@ -0,0 +1,293 @@
+//! `profiles.toml` — operator-tunable parameters layered over the models
+//!
+//! The `*.model.json` files define a mode; `profiles.toml` tweaks the
+//! numbers operators most often want to change without editing them.
+//! One table per mode name, every field optional:
+//!
+//! ```toml
+//! [car]
+//! speed_cap_kmh = 130
+//! avoid = ["toll"]
+//!
+//! [car.speed]          # km/h per highway type
+//! motorway = 110
+//!
+//! [car.access]         # highway type -> accessible
+//! track = true
+//!
+//! [car.surface]        # speed multiplier in (0, 1] per surface value
+//! gravel = 0.6
+//!
+//! [bike.smoothness]    # same, per smoothness / tracktype value
+//! bad = 0.5
+//! [bike.tracktype]
+//! grade3 = 0.6
+//!
+//! [car.turn_penalties] # seconds
+//! u_turn_penalty_s = 60
+//! ```
+//!
+//! Step 2 applies the overlay before compiling and folds it into the
+//! model's hash, so `profile_meta.json` records the effective config.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::schema::{DenyRule, ModelSchema, PriorityRule};
+
+/// File name Step 2 picks up from the models directory.
+pub const PROFILES_TOML: &str = "profiles.toml";
+
+/// Features a mode can be told to avoid. Avoided features are removed
+/// from the mode at build time; `exclude=` is the per-request variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Avoid {
+    Toll,
+    Ferry,
+    Motorway,
+    Tunnel,
+    Unpaved,
+}
+
+/// Surface values [`Avoid::Unpaved`] denies.
+const UNPAVED_SURFACES: [&str; 9] = [
+    "unpaved",
+    "compacted",
+    "gravel",
+    "fine_gravel",
+    "dirt",
+    "earth",
+    "ground",
+    "grass",
+    "sand",
+];
+
+/// Tracktype values [`Avoid::Unpaved`] denies (grade1 is paved).
+const UNPAVED_TRACKTYPES: [&str; 4] = ["grade2", "grade3", "grade4", "grade5"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TurnPenaltyParams {
+    pub turn_penalty_s: Option<u32>,
+    pub u_turn_penalty_s: Option<u32>,
+    pub signal_delay_s: Option<u32>,
+}
+
+/// One mode's section of `profiles.toml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileParams {
+    #[serde(default)]
+    pub speed: BTreeMap<String, f64>,
+    pub speed_cap_kmh: Option<f64>,
+    #[serde(default)]
+    pub access: BTreeMap<String, bool>,
+    #[serde(default)]
+    pub surface: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub smoothness: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub tracktype: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub avoid: Vec<Avoid>,
+    #[serde(default)]
+    pub turn_penalties: TurnPenaltyParams,
+}
+
+impl ProfileParams {
+    /// `(tag, value -> multiplier)` for each multiplier table.
+    fn multipliers(&self) -> [(&'static str, &BTreeMap<String, f64>); 3] {
+        [
+            ("surface", &self.surface),
+            ("smoothness", &self.smoothness),
+            ("tracktype", &self.tracktype),
+        ]
+    }
+
+    /// Overlay these parameters on `schema`.
+    pub fn apply(&self, schema: &mut ModelSchema) {
+        for (highway, &kmh) in &self.speed {
+            schema.speed.highway.insert(highway.clone(), kmh);
+        }
+        if let Some(cap) = self.speed_cap_kmh {
+            schema.speed.speed_cap_kmh = cap;
+        }
+        for (highway, &accessible) in &self.access {
+            schema.access.highway.insert(highway.clone(), accessible);
+        }
+        for (tag, table) in self.multipliers() {
+            for (value, &factor) in table {
+                schema.priority.push(PriorityRule {
+                    condition: [(tag.to_string(), value.as_str().into())].into(),
+                    multiply_by: factor,
+                });
+            }
+        }
+        for avoid in &self.avoid {
+            let deny = |tag: &str, values: &[&str]| DenyRule {
+                tag: tag.to_string(),
+                values: values.iter().map(|v| v.to_string()).collect(),
+                unless: None,
+            };
+            match avoid {
+                Avoid::Toll => schema.access.deny_if.push(deny("toll", &["yes"])),
+                Avoid::Ferry => schema.access.deny_if.push(deny("route", &["ferry"])),
+                Avoid::Tunnel => schema.access.deny_if.push(deny("tunnel", &["yes"])),
+                Avoid::Unpaved => {
+                    schema
+                        .access
+                        .deny_if
+                        .push(deny("surface", &UNPAVED_SURFACES));
+                    schema
+                        .access
+                        .deny_if
+                        .push(deny("tracktype", &UNPAVED_TRACKTYPES));
+                }
+                Avoid::Motorway => {
+                    for highway in ["motorway", "motorway_link"] {
+                        schema.access.highway.insert(highway.to_string(), false);
+                    }
+                }
+            }
+        }
+        let turns = &mut schema.turn_penalties;
+        if let Some(s) = self.turn_penalties.turn_penalty_s {
+            turns.turn_penalty_s = s;
+        }
+        if let Some(s) = self.turn_penalties.u_turn_penalty_s {
+            turns.u_turn_penalty_s = s;
+        }
+        if let Some(s) = self.turn_penalties.signal_delay_s {
+            turns.signal_delay_s = s;
+        }
+    }
+
+    fn validate(&self, mode: &str) -> Result<()> {
+        for (highway, &kmh) in &self.speed {
+            anyhow::ensure!(
+                kmh > 0.0,
+                "{mode}.speed.{highway} must be positive, got {kmh}"
+            );
+        }
+        for (tag, table) in self.multipliers() {
+            for (value, &factor) in table {
+                anyhow::ensure!(
+                    factor > 0.0 && factor <= 1.0,
+                    "{mode}.{tag}.{value} must be in (0, 1], got {factor}"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse `profiles.toml` into per-mode parameters.
+pub fn parse_profiles_toml(content: &str) -> Result<BTreeMap<String, ProfileParams>> {
+    let params: BTreeMap<String, ProfileParams> = toml::from_str(content)?;
+    for (mode, p) in &params {
+        p.validate(mode)?;
+    }
+    Ok(params)
+}
+
+/// Load `profiles.toml` from `path`.
+pub fn load_profiles_toml(path: &Path) -> Result<BTreeMap<String, ProfileParams>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_profiles_toml(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn truck() -> ModelSchema {
+        let json = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../models/truck.model.json"
+        ))
+        .unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_apply_overlay() {
+        let params = parse_profiles_toml(
+            r#"
+            [truck]
+            speed_cap_kmh = 80
+            avoid = ["toll", "motorway"]
+            [truck.speed]
+            primary = 55
+            [truck.access]
+            track = true
+            [truck.surface]
+            gravel = 0.5
+            [truck.turn_penalties]
+            u_turn_penalty_s = 90
+            "#,
+        )
+        .unwrap();
+
+        let mut schema = truck();
+        let priority_rules = schema.priority.len();
+        let deny_rules = schema.access.deny_if.len();
+        params["truck"].apply(&mut schema);
+
+        assert_eq!(schema.speed.speed_cap_kmh, 80.0);
+        assert_eq!(schema.speed.highway["primary"], 55.0);
+        assert!(schema.access.highway["track"]);
+        assert!(!schema.access.highway["motorway"]);
+        assert!(!schema.access.highway["motorway_link"]);
+        assert_eq!(schema.priority.len(), priority_rules + 1);
+        assert_eq!(schema.access.deny_if.len(), deny_rules + 1);
+        assert_eq!(schema.access.deny_if.last().unwrap().tag, "toll");
+        assert_eq!(schema.turn_penalties.u_turn_penalty_s, 90);
+        // Untouched fields keep the model's values.
+        assert_eq!(schema.turn_penalties.turn_penalty_s, 10);
+    }
+
+    #[test]
+    fn test_smoothness_and_unpaved() {
+        let params = parse_profiles_toml(
+            r#"
+            [truck]
+            avoid = ["unpaved"]
+            [truck.smoothness]
+            bad = 0.5
+            [truck.tracktype]
+            grade1 = 0.9
+            "#,
+        )
+        .unwrap();
+
+        let mut schema = truck();
+        let priority_rules = schema.priority.len();
+        params["truck"].apply(&mut schema);
+
+        let added = &schema.priority[priority_rules..];
+        assert_eq!(added.len(), 2);
+        assert!(added[0].condition.contains_key("smoothness"));
+        assert_eq!(added[1].multiply_by, 0.9);
+        let denied: Vec<_> = schema
+            .access
+            .deny_if
+            .iter()
+            .map(|r| r.tag.as_str())
+            .collect();
+        assert!(denied.ends_with(&["surface", "tracktype"]));
+    }
+
+    #[test]
+    fn test_rejects_bad_params() {
+        assert!(parse_profiles_toml("[car]\nspeeed = 1").is_err());
+        assert!(parse_profiles_toml("[car]\navoid = [\"hills\"]").is_err());
+        assert!(parse_profiles_toml("[car.surface]\ngravel = 1.5").is_err());
+        assert!(parse_profiles_toml("[bike.tracktype]\ngrade5 = 0").is_err());
+        assert!(parse_profiles_toml("[car.speed]\nprimary = 0").is_err());
+        assert!(parse_profiles_toml("").unwrap().is_empty());
+    }
+}
@@ -8,9 +8,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
-use super::{CompiledModel, compile_model, evaluate_turn_full, evaluate_way};
+use super::conditional::{CompiledConditionals, ConditionalKind, WeekSchedule};
+use super::country::CountryBoundaries;
+use super::params::{PROFILES_TOML, ProfileParams, load_profiles_toml};
+use super::schema::{ModelSchema, VehicleDimensions};
+use super::{CompiledModel, compile_model, evaluate_turn_full, evaluate_way, ferry_duration_s};
 use crate::density::{DensityClassifier, WayTagsView};
-use crate::formats::{TurnRule, WayAttr, turn_rules, way_attrs};
+use crate::formats::{TurnRule, WayAttr, WayConditional, turn_rules, way_attrs, way_conditionals};
 use crate::profile_abi::{Mode, TurnRuleKind, WayOutput};
 
 pub struct ProfileConfig {
@@ -20,6 +24,15 @@ pub struct ProfileConfig {
     pub outdir: PathBuf,
     /// Strategy used to assign `DensityClass` per way. Defaults to OsmTag.
     pub density_classifier: DensityClassifier,
+    /// Per-mode vehicle dimensions overriding the model's `vehicle`
+    /// section (e.g. a 3.5 m rigid truck instead of the 4 m default).
+    pub vehicle_overrides: BTreeMap<String, VehicleDimensions>,
+    /// `profiles.toml` overlay; defaults to `<models_dir>/profiles.toml`
+    /// when that file exists.
+    pub profiles_toml: Option<PathBuf>,
+    /// GeoJSON country boundaries selecting each model's `speed.country`
+    /// table; ways are located via `nodes.sa` next to `ways_path`.
+    pub countries_path: Option<PathBuf>,
 }
 
 impl Default for ProfileConfig {
@@ -30,6 +43,9 @@ impl Default for ProfileConfig {
             models_dir: PathBuf::new(),
             outdir: PathBuf::new(),
             density_classifier: DensityClassifier::OsmTag,
+            vehicle_overrides: BTreeMap::new(),
+            profiles_toml: None,
+            countries_path: None,
         }
     }
 }
@@ -40,6 +56,7 @@ pub struct ModeProfileOutput {
     pub mode_name: String,
     pub mode_index: u8,
     pub way_attrs_path: PathBuf,
+    pub way_conditionals_path: PathBuf,
     pub turn_rules_path: PathBuf,
 }
 
@@ -80,6 +97,16 @@ pub struct ProfileMeta {
     pub relations_sha256: String,
     pub way_attrs_sha256: BTreeMap<String, String>,
     pub turn_rules_sha256: BTreeMap<String, String>,
+    /// Hash of each mode's effective model: the JSON file plus any
+    /// `profiles.toml` and `--vehicle` overlay.
+    #[serde(default)]
+    pub model_sha256: BTreeMap<String, String>,
+    /// `way_conditionals.<mode>.bin` hashes (absent in older builds).
+    #[serde(default)]
+    pub way_conditionals_sha256: BTreeMap<String, String>,
+    /// Hash of the `--countries` boundary file, when one was used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub countries_sha256: Option<String>,
 }
 
 pub fn build_highway_classes() -> HashMap<u16, String> {
@@ -164,6 +191,101 @@ fn compute_file_sha256<P: AsRef<Path>>(path: P) -> Result<String> {
     Ok(hex::encode(hasher.finalize()))
 }
 
+/// Length of a way in metres; `None` if a node is missing from `nodes.sa`
+/// or the way has no length.
+fn way_length_m(nodes: &crate::formats::nodes_sa::Table, way_nodes: &[i64]) -> Option<f64> {
+    let mut coords = Vec::with_capacity(way_nodes.len());
+    for &id in way_nodes {
+        let (lat, lon) = nodes.get(id)?;
+        coords.push((lat as f64 * 1e-7, lon as f64 * 1e-7));
+    }
+    let length_m: f64 = coords
+        .windows(2)
+        .map(|w| crate::nbg::haversine_distance(w[0].0, w[0].1, w[1].0, w[1].1))
+        .sum();
+    (length_m > 0.0).then_some(length_m)
+}
+
+/// Step 2 overlays on top of the model files.
+#[derive(Default)]
+struct ModelOverlays {
+    params: BTreeMap<String, ProfileParams>,
+    vehicles: BTreeMap<String, VehicleDimensions>,
+}
+
+impl ModelOverlays {
+    fn load(config: &ProfileConfig, modes: &[super::ModeInfo]) -> Result<Self> {
+        let default_toml = config.models_dir.join(PROFILES_TOML);
+        let toml_path = match &config.profiles_toml {
+            Some(path) => Some(path.clone()),
+            None => default_toml.exists().then_some(default_toml),
+        };
+        let params = match &toml_path {
+            Some(path) => {
+                println!("  Profile parameters: {}", path.display());
+                load_profiles_toml(path)?
+            }
+            None => BTreeMap::new(),
+        };
+        for name in params.keys().chain(config.vehicle_overrides.keys()) {
+            anyhow::ensure!(
+                modes.iter().any(|m| &m.name == name),
+                "Profile override for unknown mode '{name}'"
+            );
+        }
+        Ok(Self {
+            params,
+            vehicles: config.vehicle_overrides.clone(),
+        })
+    }
+}
+
+/// One mode's models compiled against the way dictionaries.
+struct CompiledMode {
+    model: CompiledModel,
+    conditionals: CompiledConditionals,
+    /// `speed.country` variants, indexed like the loaded boundaries.
+    by_country: Vec<Option<CompiledModel>>,
+}
+
+impl CompiledMode {
+    fn model_for(&self, country: Option<usize>) -> &CompiledModel {
+        country
+            .and_then(|c| self.by_country[c].as_ref())
+            .unwrap_or(&self.model)
+    }
+}
+
+/// Load a mode's model with its Step 2 overlays applied. They change the
+/// profile, so each one is folded into the model hash.
+fn load_mode_model(
+    config: &ProfileConfig,
+    overlays: &ModelOverlays,
+    name: &str,
+) -> Result<(ModelSchema, [u8; 32])> {
+    use sha2::{Digest, Sha256};
+
+    let model_path = super::model_file_path(&config.models_dir, name);
+    let mut schema = super::load_model_schema(&model_path)?;
+    let mut sha256 = super::compute_model_sha256(&model_path)?;
+    if let Some(params) = overlays.params.get(name) {
+        params.apply(&mut schema);
+        let mut hasher = Sha256::new();
+        hasher.update(sha256);
+        hasher.update(serde_json::to_vec(params)?);
+        sha256 = hasher.finalize().into();
+    }
+    if let Some(dims) = overlays.vehicles.get(name) {
+        let vehicle = schema.vehicle.get_or_insert_with(Default::default);
+        vehicle.merge(dims);
+        let mut hasher = Sha256::new();
+        hasher.update(sha256);
+        hasher.update(serde_json::to_vec(vehicle)?);
+        sha256 = hasher.finalize().into();
+    }
+    Ok((schema, sha256))
+}
+
 /// Run Step 2 profiling pipeline using auto-discovered JSON model files
 pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
     println!("Starting Step 2: Modal Profiling (declarative models)");
@@ -190,29 +312,70 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
     println!("  key dictionary: {} entries", key_dict.len());
     println!("  value dictionary: {} entries", val_dict.len());
 
-    // Compile all models against the way dictionaries
-    let compiled_models: Vec<CompiledModel> = modes
+    let overlays = ModelOverlays::load(&config, &modes)?;
+
+    // Node coordinates locate ways in countries and measure ferry routes;
+    // they are required only with country boundaries.
+    let nodes_path = config
+        .ways_path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("ways_path has no parent directory"))?
+        .join("nodes.sa");
+    let nodes = if config.countries_path.is_some() || nodes_path.exists() {
+        Some(crate::formats::nodes_sa::Table::open(&nodes_path)?)
+    } else {
+        None
+    };
+    let countries = match &config.countries_path {
+        Some(path) => {
+            let countries = CountryBoundaries::load(path)?;
+            println!(
+                "  country boundaries: {} ({} features)",
+                path.display(),
+                countries.len()
+            );
+            Some(countries)
+        }
+        None => None,
+    };
+
+    // Compile all models (and their conditional keys) against the way dictionaries
+    let compiled_modes: Vec<CompiledMode> = modes
         .iter()
         .map(|mode_info| {
-            let model_path = super::model_file_path(&config.models_dir, &mode_info.name);
-            let schema = super::load_model_schema(&model_path)?;
-            let sha256 = super::compute_model_sha256(&model_path)?;
-            Ok(compile_model(
-                &schema,
-                mode_info.index,
-                sha256,
-                &key_dict,
-                &val_dict,
-            ))
+            let (schema, sha256) = load_mode_model(&config, &overlays, &mode_info.name)?;
+            if let Some(vehicle) = &schema.vehicle {
+                println!("  {}: vehicle {:?}", mode_info.name, vehicle);
+            }
+            let compile = |schema: &ModelSchema| {
+                compile_model(schema, mode_info.index, sha256, &key_dict, &val_dict)
+            };
+            let by_country = match &countries {
+                Some(countries) => (0..countries.len())
+                    .map(|c| {
+                        let speeds = schema.speed.country.get(countries.code(c))?;
+                        let mut variant = schema.clone();
+                        variant.speed.highway.extend(speeds.clone());
+                        Some(compile(&variant))
+                    })
+                    .collect(),
+                None => Vec::new(),
+            };
+            Ok(CompiledMode {
+                model: compile(&schema),
+                conditionals: CompiledConditionals::compile(&schema, &key_dict),
+                by_country,
+            })
         })
         .collect::<Result<Vec<_>>>()?;
-    println!("  compiled {} models", compiled_models.len());
+    println!("  compiled {} models", compiled_modes.len());
 
     // Stream and process ways through all compiled models
     println!();
     println!("Streaming and processing ways...");
     let n_modes = modes.len();
     let mut way_attrs_per_mode: Vec<Vec<WayAttr>> = vec![Vec::new(); n_modes];
+    let mut way_conditionals_per_mode: Vec<Vec<WayConditional>> = vec![Vec::new(); n_modes];
 
     // Density classifier: same call for every mode (density is mode-agnostic).
     if config.density_classifier == DensityClassifier::ExternalParquet {
@@ -242,14 +405,16 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
     let mut count = 0u64;
     let mut next_progress = 1_000_000u64;
     let mut density_hist: [u64; 5] = [0; 5];
-    let mut chunk: Vec<(i64, Vec<u32>, Vec<u32>)> = Vec::with_capacity(CHUNK_WAYS);
+    // (way_id, keys, vals, nodes) as decoded by `stream_ways`.
+    type DecodedWay = (i64, Vec<u32>, Vec<u32>, Vec<i64>);
+    let mut chunk: Vec<DecodedWay> = Vec::with_capacity(CHUNK_WAYS);
 
     loop {
         // Fill one bounded chunk from the (serial) decode stream.
         chunk.clear();
         for result in way_stream.by_ref() {
-            let (way_id, keys, vals, _nodes) = result?;
-            chunk.push((way_id, keys, vals));
+            let (way_id, keys, vals, nodes) = result?;
+            chunk.push((way_id, keys, vals, nodes));
             if chunk.len() >= CHUNK_WAYS {
                 break;
             }
@@ -259,13 +424,14 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
         }
 
         // Evaluate the chunk in parallel; collect() preserves chunk index order.
-        let results: Vec<(i64, u8, Vec<WayOutput>)> = chunk
+        type ModeResult = (WayOutput, Vec<(ConditionalKind, WeekSchedule)>);
+        let results: Vec<(i64, u8, Vec<ModeResult>)> = chunk
             .par_iter()
-            .map(|(way_id, keys, vals)| {
+            .map(|(way_id, keys, vals, way_nodes)| {
                 // Density class is mode-agnostic — compute once per way (one
                 // extra eval just to resolve the highway tag; any model works
                 // since they share dictionaries).
-                let out0 = evaluate_way(&compiled_models[0], keys, vals, &val_dict);
+                let out0 = evaluate_way(&compiled_modes[0].model, keys, vals, &val_dict);
                 let highway_name = highway_classes
                     .get(&out0.highway_class)
                     .map(|s| s.as_str())
@@ -279,12 +445,35 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
                 let dclass =
                     crate::density::classify_osm_tag(density_classifier, highway_name, &view)
                         .to_u8();
-                let outputs: Vec<WayOutput> = compiled_models
+                let country =
+                    countries
+                        .as_ref()
+                        .zip(nodes.as_ref())
+                        .and_then(|(countries, nodes)| {
+                            let (lat, lon) = nodes.get(*way_nodes.first()?)?;
+                            countries.lookup(lat as f64 * 1e-7, lon as f64 * 1e-7)
+                        });
+                let outputs: Vec<ModeResult> = compiled_modes
                     .iter()
                     .map(|compiled| {
-                        let mut output = evaluate_way(compiled, keys, vals, &val_dict);
+                        let model = compiled.model_for(country);
+                        let mut output = evaluate_way(model, keys, vals, &val_dict);
                         output.density_class = dclass;
-                        output
+                        // A ferry's `duration` sets the crossing time: pick
+                        // the speed that covers the route's length in it.
+                        if (output.access_fwd || output.access_rev)
+                            && let Some(duration_s) = ferry_duration_s(model, keys, vals, &val_dict)
+                            && let Some(length_m) =
+                                nodes.as_ref().and_then(|n| way_length_m(n, way_nodes))
+                        {
+                            output.base_speed_mmps =
+                                ((length_m * 1000.0 / duration_s as f64).round() as u32)
+                                    .clamp(1, model.speed_cap_mmps);
+                        }
+                        (
+                            output,
+                            compiled.conditionals.evaluate(keys, vals, &val_dict),
+                        )
                     })
                     .collect();
                 (*way_id, dclass, outputs)
@@ -294,8 +483,15 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
         // Accumulate serially (deterministic).
         for (way_id, dclass, outputs) in results {
             density_hist[dclass as usize] += 1;
-            for (i, output) in outputs.into_iter().enumerate() {
+            for (i, (output, conditionals)) in outputs.into_iter().enumerate() {
                 way_attrs_per_mode[i].push(WayAttr { way_id, output });
+                way_conditionals_per_mode[i].extend(conditionals.into_iter().map(
+                    |(kind, schedule)| WayConditional {
+                        way_id,
+                        kind,
+                        schedule,
+                    },
+                ));
             }
         }
 
@@ -335,16 +531,33 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
             way_attrs_per_mode[i].len()
         );
 
+        let cond_filename = format!("way_conditionals.{}.bin", mode_info.name);
+        let cond_path = config.outdir.join(&cond_filename);
+        way_conditionals::write(
+            &cond_path,
+            mode,
+            &way_conditionals_per_mode[i],
+            &dict_k_sha256,
+            &dict_v_sha256,
+        )?;
+        println!(
+            "  wrote {} ({} conditional restrictions)",
+            cond_filename,
+            way_conditionals_per_mode[i].len()
+        );
+
         mode_outputs.push(ModeProfileOutput {
             mode_name: mode_info.name.clone(),
             mode_index: mode_info.index,
             way_attrs_path: path,
+            way_conditionals_path: cond_path,
             turn_rules_path: PathBuf::new(), // filled below
         });
     }
 
     // Drop way attrs to free memory before processing relations
     drop(way_attrs_per_mode);
+    drop(way_conditionals_per_mode);
 
     // Load dictionaries from relations.raw
     println!();
@@ -358,9 +571,7 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
     let compiled_turn_models: Vec<CompiledModel> = modes
         .iter()
         .map(|mode_info| {
-            let model_path = super::model_file_path(&config.models_dir, &mode_info.name);
-            let schema = super::load_model_schema(&model_path)?;
-            let sha256 = super::compute_model_sha256(&model_path)?;
+            let (schema, sha256) = load_mode_model(&config, &overlays, &mode_info.name)?;
             Ok(compile_model(
                 &schema,
                 mode_info.index,
@@ -464,11 +675,17 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
     let profile_meta_path = config.outdir.join("profile_meta.json");
 
     let ways_sha256 = compute_file_sha256(&config.ways_path)?;
+    let countries_sha256 = match &config.countries_path {
+        Some(path) => Some(compute_file_sha256(path)?),
+        None => None,
+    };
     let relations_sha256 = compute_file_sha256(&config.relations_path)?;
 
     let mut way_attrs_sha256 = BTreeMap::new();
     let mut turn_rules_sha256 = BTreeMap::new();
+    let mut way_conditionals_sha256 = BTreeMap::new();
     let mut profile_versions = BTreeMap::new();
+    let mut model_sha256 = BTreeMap::new();
 
     for out in &mode_outputs {
         way_attrs_sha256.insert(
@@ -479,9 +696,13 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
             out.mode_name.clone(),
             compute_file_sha256(&out.turn_rules_path)?,
         );
-        let model_path = super::model_file_path(&config.models_dir, &out.mode_name);
-        let schema = super::load_model_schema(&model_path)?;
+        way_conditionals_sha256.insert(
+            out.mode_name.clone(),
+            compute_file_sha256(&out.way_conditionals_path)?,
+        );
+        let (schema, sha256) = load_mode_model(&config, &overlays, &out.mode_name)?;
         profile_versions.insert(out.mode_name.clone(), schema.version);
+        model_sha256.insert(out.mode_name.clone(), hex::encode(sha256));
     }
 
     let meta = ProfileMeta {
@@ -494,6 +715,9 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
         relations_sha256,
         way_attrs_sha256,
         turn_rules_sha256,
+        model_sha256,
+        way_conditionals_sha256,
+        countries_sha256,
     };
 
     let meta_json = serde_json::to_string_pretty(&meta)?;
@@ -507,8 +731,9 @@ pub fn run_profiling(config: ProfileConfig) -> Result<ProfileResult> {
         modes: modes
             .iter()
             .map(|m| {
-                let model_path = super::model_file_path(&config.models_dir, &m.name);
-                let sha256 = super::compute_model_sha256(&model_path).unwrap_or([0u8; 32]);
+                let sha256 = load_mode_model(&config, &overlays, &m.name)
+                    .map(|(_, sha256)| sha256)
+                    .unwrap_or([0u8; 32]);
                 super::ManifestMode {
                     name: m.name.clone(),
                     index: m.index,
@@ -1,7 +1,7 @@
 //! JSON model schema - serde structs for declarative routing profiles
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Root schema for a model JSON file
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +17,97 @@ pub struct ModelSchema {
     pub class_bits: HashMap<String, ClassBitRule>,
     pub turn_penalties: TurnPenaltySchema,
     pub turn_restrictions: TurnRestrictionConfig,
+    /// Vehicle dimensions checked against `max*` way tags; `None` for
+    /// modes that are not dimension-restricted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vehicle: Option<VehicleDimensions>,
+    /// Barrier nodes (`barrier=*`) the mode cannot pass or is delayed at,
+    /// applied in Step 4.
+    #[serde(default)]
+    pub barriers: BarrierSchema,
+    /// Ferry routes (`route=ferry`) without a highway tag; `None` keeps
+    /// them unroutable for the mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ferry: Option<FerrySchema>,
+}
+
+/// How a mode travels on ferries. The way's `duration` tag, when present,
+/// sets the crossing time and takes precedence over `speed_kmh`. Ways that
+/// also carry a highway tag keep the highway's access and speed rules.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FerrySchema {
+    /// Crossing speed for ferries without a usable `duration` tag.
+    pub speed_kmh: f64,
+}
+
+/// Barrier policy, keyed by `barrier=*` value (see
+/// [`crate::formats::BarrierKind`]; `other` covers every value not
+/// listed there). Barriers that are neither denied nor delayed are free
+/// to pass.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BarrierSchema {
+    /// Barriers the mode cannot pass; it may still turn around at them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+    /// Seconds added for passing a barrier (gates to open, toll booths).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub delay_s: HashMap<String, u32>,
+}
+
+/// Vehicle size and mass. A way is denied when it carries a legal limit
+/// below the matching dimension: `maxheight`/`maxheight:physical`,
+/// `maxwidth`/`maxwidth:physical`, `maxlength`, `maxweight`/`maxweight:hgv`.
+/// Unset dimensions are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VehicleDimensions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length_m: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight_t: Option<f64>,
+}
+
+impl VehicleDimensions {
+    /// Parse a Step 2 override such as `height=4.2,weight=44` (metres and
+    /// tonnes).
+    pub fn parse_spec(spec: &str) -> anyhow::Result<Self> {
+        let mut dims = Self::default();
+        for part in spec.split(',').map(str::trim) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Expected KEY=VALUE, got {part:?}"))?;
+            let value: f64 = value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid vehicle {key} {value:?}"))?;
+            anyhow::ensure!(value > 0.0, "Vehicle {key} must be positive");
+            let slot = match key.trim() {
+                "height" => &mut dims.height_m,
+                "width" => &mut dims.width_m,
+                "length" => &mut dims.length_m,
+                "weight" => &mut dims.weight_t,
+                other => anyhow::bail!(
+                    "Unknown vehicle dimension {other:?} (expected height, width, length or weight)"
+                ),
+            };
+            *slot = Some(value);
+        }
+        Ok(dims)
+    }
+
+    /// Overlay the dimensions set in `other`.
+    pub fn merge(&mut self, other: &Self) {
+        self.height_m = other.height_m.or(self.height_m);
+        self.width_m = other.width_m.or(self.width_m);
+        self.length_m = other.length_m.or(self.length_m);
+        self.weight_t = other.weight_t.or(self.weight_t);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +118,11 @@ pub struct SpeedConfig {
     pub speed_cap_kmh: f64,
     #[serde(default)]
     pub overrides: Vec<SpeedOverride>,
+    /// Per-country highway speeds keyed by ISO 3166-1 alpha-2 code,
+    /// replacing `highway` entries for ways inside that country (see
+    /// [`super::country`]).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub country: BTreeMap<String, HashMap<String, f64>>,
 }
 
 fn default_speed_cap() -> f64 {
@@ -259,5 +355,31 @@ mod tests {
             model.turn_restrictions.mode_specific_tag,
             Some("restriction:hgv".to_string())
         );
+        let vehicle = model
+            .vehicle
+            .expect("truck model carries vehicle dimensions");
+        assert!(vehicle.height_m.is_some() && vehicle.weight_t.is_some());
+    }
+
+    #[test]
+    fn test_vehicle_spec_overrides() {
+        let mut dims = VehicleDimensions {
+            height_m: Some(4.0),
+            weight_t: Some(40.0),
+            ..Default::default()
+        };
+        dims.merge(&VehicleDimensions::parse_spec("weight=7.5, length=8").unwrap());
+        assert_eq!(
+            dims,
+            VehicleDimensions {
+                height_m: Some(4.0),
+                width_m: None,
+                length_m: Some(8.0),
+                weight_t: Some(7.5),
+            }
+        );
+        assert!(VehicleDimensions::parse_spec("axles=3").is_err());
+        assert!(VehicleDimensions::parse_spec("height").is_err());
+        assert!(VehicleDimensions::parse_spec("height=-1").is_err());
     }
 }
@@ -0,0 +1,128 @@
+//! Optional Step 3 pass: sample SRTM elevation along every NBG edge.
+//!
+//! Each polyline is sampled at [`SAMPLE_INTERVAL_M`] with the same
+//! [`ElevationData::elevation_profile`] the `/elevation` endpoints use, and
+//! the positive and negative height differences between consecutive
+//! samples are summed into the edge's ascent and descent. Per-point z (the
+//! DEM height at each polyline vertex) is stored only on request, since it
+//! roughly doubles the artifact for little gain over the profile.
+
+use rayon::prelude::*;
+
+use crate::formats::nbg_elev::{EdgeElevation, NO_Z, NbgElev};
+use crate::formats::{NbgGeo, PolyLine};
+use crate::server::elevation::ElevationData;
+
+/// Distance between elevation samples along an edge (SRTM is ~30-90 m).
+const SAMPLE_INTERVAL_M: f64 = 50.0;
+/// Largest stored climb; keeps `NO_ELEVATION` unambiguous.
+const MAX_DM: u32 = u32::MAX - 1;
+
+fn path(poly: &PolyLine) -> Vec<[f64; 2]> {
+    poly.lat_fxp
+        .iter()
+        .zip(&poly.lon_fxp)
+        .map(|(&lat, &lon)| [lat as f64 * 1e-7, lon as f64 * 1e-7])
+        .collect()
+}
+
+/// Ascent and descent along one polyline; `NONE` when fewer than two
+/// samples have elevation.
+fn edge_elevation(poly: &PolyLine, elevation: &ElevationData) -> EdgeElevation {
+    let profile = elevation.elevation_profile(&path(poly), SAMPLE_INTERVAL_M);
+    if profile.len() < 2 {
+        return EdgeElevation::NONE;
+    }
+    let (mut ascent_m, mut descent_m) = (0.0, 0.0);
+    for pair in profile.windows(2) {
+        let rise = pair[1].elevation - pair[0].elevation;
+        if rise > 0.0 {
+            ascent_m += rise;
+        } else {
+            descent_m -= rise;
+        }
+    }
+    let dm = |m: f64| ((m * 10.0).round() as u32).min(MAX_DM);
+    EdgeElevation {
+        ascent_dm: dm(ascent_m),
+        descent_dm: dm(descent_m),
+    }
+}
+
+/// Sample every edge of `geo`; `with_points` also stores the DEM height
+/// at each polyline vertex.
+pub fn sample_nbg_elevation(geo: &NbgGeo, elevation: &ElevationData, with_points: bool) -> NbgElev {
+    let edges = geo
+        .polylines
+        .par_iter()
+        .map(|poly| edge_elevation(poly, elevation))
+        .collect();
+
+    let z = with_points.then(|| {
+        geo.polylines
+            .par_iter()
+            .flat_map_iter(|poly| {
+                path(poly).into_iter().map(|[lat, lon]| {
+                    elevation
+                        .elevation_at(lat, lon)
+                        .map_or(NO_Z, |z| z.round().clamp(-32767.0, 32767.0) as i16)
+                })
+            })
+            .collect()
+    });
+
+    NbgElev { edges, z }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::NbgEdge;
+    use crate::server::elevation::SrtmTile;
+
+    #[test]
+    fn test_sample_nbg_elevation() {
+        // 3x3 tile over lat/lon [0, 1] rising 1000 m per degree of
+        // latitude northwards (row 0 is north).
+        let data = vec![1000, 1000, 1000, 500, 500, 500, 0, 0, 0];
+        let elevation = ElevationData::from_tiles(vec![SrtmTile::new(0, 0, 3, data)]);
+
+        let edge = |n_poly_pts: u16, poly_off: u64| NbgEdge {
+            u_node: 0,
+            v_node: 1,
+            length_mm: 0,
+            bearing_deci_deg: 0,
+            n_poly_pts,
+            poly_off,
+            first_osm_way_id: 0,
+            flags: 0,
+        };
+        let geo = NbgGeo {
+            n_edges_und: 2,
+            edges: vec![edge(3, 0), edge(2, 24)],
+            polylines: vec![
+                // North 0.1°, then back south 0.05°: +100 m, -50 m.
+                PolyLine {
+                    lat_fxp: vec![2_000_000, 3_000_000, 2_500_000],
+                    lon_fxp: vec![5_000_000; 3],
+                },
+                // Outside the tile.
+                PolyLine {
+                    lat_fxp: vec![20_000_000, 21_000_000],
+                    lon_fxp: vec![5_000_000; 2],
+                },
+            ],
+        };
+
+        let elev = sample_nbg_elevation(&geo, &elevation, true);
+        let climb = elev.edges[0];
+        assert!((climb.ascent_dm as i64 - 1000).abs() <= 5, "{climb:?}");
+        assert!((climb.descent_dm as i64 - 500).abs() <= 5, "{climb:?}");
+        assert_eq!(elev.edges[1], EdgeElevation::NONE);
+
+        assert_eq!(elev.edge_z(&geo.edges[0]), Some(&[200, 300, 250][..]));
+        assert_eq!(elev.edge_z(&geo.edges[1]), Some(&[NO_Z, NO_Z][..]));
+
+        assert!(sample_nbg_elevation(&geo, &elevation, false).z.is_none());
+    }
+}
@@ -1,26 +1,53 @@
 //! Step 3: Node-based graph (NBG) construction
-
-use anyhow::Result;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+//!
+//! Memory stays bounded for planet inputs: ways.raw and every
+//! way_attrs.<mode>.bin are sorted by way id and merge-joined as they
+//! stream, node coordinates are read from the memory-mapped nodes.sa, and
+//! node references are counted through an external sort. What remains in
+//! memory is the output itself (decision nodes and edges).
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
 
 use crate::formats::{
-    NbgCsr, NbgCsrFile, NbgEdge, NbgGeo, NbgGeoFile, NbgNodeMap, NbgNodeMapFile, NodeMapping,
-    PolyLine, WaysFile,
+    EDGE_FLAG_BRIDGE, EDGE_FLAG_FERRY, EDGE_FLAG_FORD, EDGE_FLAG_ROUNDABOUT, EDGE_FLAG_SELF_LOOP,
+    EDGE_FLAG_TUNNEL, NbgCsr, NbgCsrFile, NbgEdge, NbgElevFile, NbgGeo, NbgGeoFile, NbgNodeMap,
+    NbgNodeMapFile, NodeBarriers, NodeBarriersFile, NodeMapping, PolyLine, WaysFile, nodes_sa,
+    way_attrs,
 };
+use crate::ingest::external_sort::ExternalSorter;
+use crate::server::elevation::ElevationData;
+
+pub mod elevation;
 
 pub struct NbgConfig {
     pub nodes_sa_path: PathBuf,
     pub ways_path: PathBuf,
     /// Per-mode way_attrs paths, keyed by mode name, in alphabetical order
     pub way_attrs_paths: Vec<(String, PathBuf)>,
+    /// node_barriers.bin from Step 1: barrier nodes become graph nodes so
+    /// Step 4 can block or delay passing them per mode.
+    pub node_barriers_path: Option<PathBuf>,
     pub outdir: PathBuf,
+    /// Bytes of node references held in memory before spilling sorted
+    /// runs.
+    pub memory_budget: usize,
+    /// Directory for spill runs; defaults to `<outdir>/.step3-spill`.
+    pub spill_dir: Option<PathBuf>,
+    /// SRTM .hgt tiles to sample edge elevation from into nbg.elev.
+    pub srtm_dir: Option<PathBuf>,
+    /// Also store the elevation of every polyline point in nbg.elev.
+    pub elevation_points: bool,
 }
 
 pub struct NbgResult {
     pub csr_path: PathBuf,
     pub geo_path: PathBuf,
     pub node_map_path: PathBuf,
+    /// nbg.elev, when elevation was sampled
+    pub elev_path: Option<PathBuf>,
     pub n_nodes: u32,
     pub n_edges_und: u64,
 }
@@ -56,18 +83,6 @@ pub fn compute_bearing(lat1_deg: f64, lon1_deg: f64, lat2_deg: f64, lon2_deg: f6
     deci_deg.min(3599)
 }
 
-/// Check if a way has access in any mode
-fn has_any_access(records: &[&[u8]]) -> bool {
-    for rec in records {
-        let fwd = rec.get(8).copied().unwrap_or(0) != 0;
-        let rev = rec.get(9).copied().unwrap_or(0) != 0;
-        if fwd || rev {
-            return true;
-        }
-    }
-    false
-}
-
 /// Build NBG from Step 1 and Step 2 outputs
 pub fn build_nbg(config: NbgConfig) -> Result<NbgResult> {
     use std::time::Instant;
@@ -81,69 +96,100 @@ pub fn build_nbg(config: NbgConfig) -> Result<NbgResult> {
     println!();
 
     std::fs::create_dir_all(&config.outdir)?;
+    let spill_dir = config
+        .spill_dir
+        .clone()
+        .unwrap_or_else(|| config.outdir.join(".step3-spill"));
 
-    // Step 1: Load way_attrs to determine included ways
-    println!("Loading way_attrs to determine included ways...");
-    let way_attrs_by_mode: Vec<HashMap<i64, Vec<u8>>> = config
-        .way_attrs_paths
-        .iter()
-        .map(|(name, path)| {
-            println!("  Loading way_attrs for '{}'...", name);
-            load_way_attrs_index(path)
-        })
-        .collect::<Result<Vec<_>>>()?;
-    println!("  ✓ Loaded way_attrs for {} modes", way_attrs_by_mode.len());
+    // Step 1: way_attrs are merge-joined while streaming ways
+    println!(
+        "Joining ways against way_attrs for {} modes",
+        config.way_attrs_paths.len()
+    );
 
     // Step 2: Load nodes.sa for coordinate lookup
     println!("Loading nodes.sa...");
     let node_coords = load_node_coordinates(&config.nodes_sa_path)?;
     println!("  ✓ Loaded {} node coordinates", node_coords.len());
 
+    let barriers = match &config.node_barriers_path {
+        Some(path) => {
+            let barriers = NodeBarriersFile::read(path)?;
+            println!("  ✓ Loaded {} barrier nodes", barriers.len());
+            barriers
+        }
+        None => NodeBarriers::new(vec![]),
+    };
+
     // Step 3: Stream ways and collect decision nodes
     println!("Streaming ways to collect decision nodes...");
-    let (decision_nodes, included_ways) =
-        collect_decision_nodes(&config.ways_path, &way_attrs_by_mode)?;
+    let (decision_nodes, included_ways) = collect_decision_nodes(
+        &config.ways_path,
+        &config.way_attrs_paths,
+        &barriers,
+        &spill_dir,
+        config.memory_budget,
+    )?;
     println!("  ✓ Found {} decision nodes", decision_nodes.len());
-    println!("  ✓ Found {} included ways", included_ways.len());
+    println!("  ✓ Found {} included ways", included_ways);
 
-    // Step 4: Build node map (OSM ID -> compact ID)
-    println!("Building node map...");
-    let node_map = build_node_map(&decision_nodes)?;
-    println!("  ✓ Assigned {} compact node IDs", node_map.mappings.len());
-
-    // Create lookup for compact IDs
-    let osm_to_compact: HashMap<i64, u32> = node_map
-        .mappings
-        .iter()
-        .map(|m| (m.osm_node_id, m.compact_id))
-        .collect();
-
-    // Step 5: Emit edges
+    // Step 4: Emit edges
     println!("Emitting edges...");
-    let (edges, adjacency) = emit_edges(
+    let class_tags = EdgeClassTags::load(&config.ways_path)?;
+    let (edges, stats) = emit_edges(
         &config.ways_path,
-        &included_ways,
-        &osm_to_compact,
+        &config.way_attrs_paths,
+        &decision_nodes,
         &node_coords,
+        &class_tags,
     )?;
-    println!("  ✓ Emitted {} undirected edges", edges.len());
+    println!(
+        "  ✓ Emitted {} undirected edges ({} self-loops, {} zero-length)",
+        edges.len(),
+        stats.self_loops,
+        stats.zero_length
+    );
+    println!(
+        "  ✓ Flagged {} ferry, {} bridge, {} tunnel, {} roundabout, {} ford edges",
+        stats.flagged[0], stats.flagged[1], stats.flagged[2], stats.flagged[3], stats.flagged[4]
+    );
+    if stats.dropped_missing_coords + stats.dropped_degenerate_loops + stats.skipped_vertices > 0 {
+        println!(
+            "  ⚠ Dropped {} segments without coordinates and {} degenerate loops; skipped {} vertices without coordinates",
+            stats.dropped_missing_coords, stats.dropped_degenerate_loops, stats.skipped_vertices
+        );
+    }
+
+    // Step 5: Build node map (OSM ID -> compact ID = index in the sorted list)
+    println!("Building node map...");
+    let node_map = build_node_map(decision_nodes);
+    println!("  ✓ Assigned {} compact node IDs", node_map.mappings.len());
 
     // Step 6: Assemble CSR
     println!("Assembling CSR...");
-    let mut csr = assemble_csr(
-        &adjacency,
-        node_map.mappings.len() as u32,
-        edges.len() as u64,
-    )?;
+    let mut csr = assemble_csr(&edges, node_map.mappings.len() as u32);
     // Hash every input file the CSR was derived from so downstream
-    // steps can detect when an upstream artefact has changed.
+    // steps can detect when an upstream artefact has changed. Files are
+    // streamed through the hasher (ways.raw alone is ~100 GB on planet).
     csr.inputs_sha = {
         use sha2::{Digest, Sha256};
+        use std::io::Read;
         let mut hasher = Sha256::new();
-        hasher.update(std::fs::read(&config.nodes_sa_path)?);
-        hasher.update(std::fs::read(&config.ways_path)?);
-        for (_name, path) in &config.way_attrs_paths {
-            hasher.update(std::fs::read(path)?);
+        let mut buffer = vec![0u8; 1 << 20];
+        let inputs = [&config.nodes_sa_path, &config.ways_path]
+            .into_iter()
+            .chain(config.way_attrs_paths.iter().map(|(_name, path)| path))
+            .chain(&config.node_barriers_path);
+        for path in inputs {
+            let mut file = std::fs::File::open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            loop {
+                let n = file.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..n]);
+            }
         }
         let result = hasher.finalize();
         let mut sha = [0u8; 32];
@@ -207,6 +253,25 @@ pub fn build_nbg(config: NbgConfig) -> Result<NbgResult> {
     NbgGeoFile::write(&geo_path, &geo)?;
     println!("  ✓ Wrote {}", geo_path.display());
 
+    let elev_path = match &config.srtm_dir {
+        Some(dir) => {
+            let elevation = ElevationData::load_from_dir(dir)
+                .with_context(|| format!("Failed to load SRTM tiles from {}", dir.display()))?;
+            println!(
+                "Sampling elevation from {} SRTM tiles...",
+                elevation.tile_count()
+            );
+            let elev = elevation::sample_nbg_elevation(&geo, &elevation, config.elevation_points);
+            let covered = elev.edges.iter().filter(|e| e.has_data()).count();
+            println!("  ✓ {} of {} edges covered", covered, elev.edges.len());
+            let path = config.outdir.join("nbg.elev");
+            NbgElevFile::write(&path, &elev)?;
+            println!("  ✓ Wrote {}", path.display());
+            Some(path)
+        }
+        None => None,
+    };
+
     let csr_path = config.outdir.join("nbg.csr");
     NbgCsrFile::write(&csr_path, &csr)?;
     println!("  ✓ Wrote {}", csr_path.display());
@@ -223,148 +288,155 @@ pub fn build_nbg(config: NbgConfig) -> Result<NbgResult> {
         csr_path,
         geo_path,
         node_map_path,
+        elev_path,
         n_nodes: csr.n_nodes,
         n_edges_und: csr.n_edges_und,
     })
 }
 
-fn load_way_attrs_index(path: &PathBuf) -> Result<HashMap<i64, Vec<u8>>> {
-    use std::fs::File;
-    use std::io::Read;
-
-    let mut file = File::open(path)?;
-    let mut header = vec![0u8; 80];
-    file.read_exact(&mut header)?;
-
-    let count = u64::from_le_bytes(header[8..16].try_into()?);
-    let mut index = HashMap::new();
-
-    for _ in 0..count {
-        let mut record = vec![0u8; 32];
-        file.read_exact(&mut record)?;
+/// Merge-join of ways.raw against every mode's way_attrs, both sorted by
+/// way id: answers "does any mode route on this way" while streaming.
+struct AccessJoin {
+    modes: Vec<Peekable<way_attrs::Stream>>,
+    last_way_id: Option<i64>,
+}
 
-        let way_id = i64::from_le_bytes(record[0..8].try_into()?);
-        index.insert(way_id, record);
+impl AccessJoin {
+    fn open(way_attrs_paths: &[(String, PathBuf)]) -> Result<Self> {
+        let modes = way_attrs_paths
+            .iter()
+            .map(|(_name, path)| Ok(way_attrs::stream(path)?.peekable()))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            modes,
+            last_way_id: None,
+        })
     }
 
-    Ok(index)
+    /// Whether any mode has access to `way_id` in either direction. Ids
+    /// must come in ascending order.
+    fn has_any_access(&mut self, way_id: i64) -> Result<bool> {
+        if let Some(last) = self.last_way_id {
+            anyhow::ensure!(
+                way_id > last,
+                "ways.raw is not sorted by way id ({way_id} after {last})"
+            );
+        }
+        self.last_way_id = Some(way_id);
+
+        let mut any = false;
+        for attrs in &mut self.modes {
+            while let Some(attr) =
+                attrs.next_if(|attr| attr.as_ref().map_or(true, |a| a.way_id <= way_id))
+            {
+                let attr = attr?;
+                if attr.way_id == way_id && (attr.output.access_fwd || attr.output.access_rev) {
+                    any = true;
+                }
+            }
+        }
+        Ok(any)
+    }
 }
 
 /// Node coordinate table loaded from nodes.sa. (#422)
 ///
 /// Replaces the prior `HashMap<i64,(f64,f64)>` (~3.3 GB on Belgium: 8B key + 16B
-/// value + hash overhead over 69M nodes) with a sorted 16-bytes-per-node
-/// `Vec<(i64,i32,i32)>` (~1.1 GB) + binary search. nodes.sa records are stored
-/// strictly ascending by id, so no sort is needed. `get()` decodes lat/lon with
-/// the EXACT same expression the loader used, so geometry stays byte-identical.
+/// value + hash overhead over 69M nodes) with the nodes.sa body itself: 16-byte
+/// records strictly ascending by id, memory-mapped rather than copied, so load
+/// is O(1) and only the pages the ways touch become resident. `get()` decodes
+/// lat/lon with the EXACT same expression the loader used, so geometry stays
+/// byte-identical.
 struct NodeCoords {
-    /// (node_id, lat_fxp, lon_fxp) ascending by node_id.
-    entries: Vec<(i64, i32, i32)>,
+    table: nodes_sa::Table,
 }
 
 impl NodeCoords {
     fn len(&self) -> usize {
-        self.entries.len()
+        self.table.len()
     }
 
     /// Look up a node's (lat, lon) in degrees; None if absent.
     #[inline]
     fn get(&self, id: i64) -> Option<(f64, f64)> {
-        match self.entries.binary_search_by_key(&id, |&(nid, _, _)| nid) {
-            Ok(i) => {
-                let (_, lat_fxp, lon_fxp) = self.entries[i];
-                Some((lat_fxp as f64 * 1e-7, lon_fxp as f64 * 1e-7))
-            }
-            Err(_) => None,
-        }
+        self.table
+            .get(id)
+            .map(|(lat_fxp, lon_fxp)| (lat_fxp as f64 * 1e-7, lon_fxp as f64 * 1e-7))
     }
 }
 
-fn load_node_coordinates(path: &PathBuf) -> Result<NodeCoords> {
-    use std::fs::File;
-    use std::io::{BufReader, Read};
-
-    // Buffered read: the body is `count` × 16-byte records; the previous
-    // read_exact(16) per record was one syscall per node (~69M). A 1 MiB
-    // BufReader collapses that to ~1k syscalls — a load-time win that offsets
-    // the binary-search lookup cost downstream.
-    let mut file = BufReader::with_capacity(1 << 20, File::open(path)?);
-    let mut header = [0u8; 128];
-    file.read_exact(&mut header)?;
-
-    let count = u64::from_le_bytes(header[8..16].try_into()?) as usize;
-    let mut entries = Vec::with_capacity(count);
-
-    let mut record = [0u8; 16];
-    for _ in 0..count {
-        file.read_exact(&mut record)?;
-        let node_id = i64::from_le_bytes(record[0..8].try_into()?);
-        let lat_lon = u64::from_le_bytes(record[8..16].try_into()?);
-        // Little-endian: lower 32 bits = lat_fxp (bytes 8-11), upper = lon_fxp.
-        let lat_fxp = (lat_lon & 0xFFFFFFFF) as i32;
-        let lon_fxp = (lat_lon >> 32) as i32;
-        entries.push((node_id, lat_fxp, lon_fxp));
-    }
-
-    // nodes.sa is strictly ascending by id (format invariant) → binary search OK.
-    debug_assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
-
-    Ok(NodeCoords { entries })
+fn load_node_coordinates(path: &Path) -> Result<NodeCoords> {
+    Ok(NodeCoords {
+        table: nodes_sa::Table::open(path)?,
+    })
 }
 
+/// Sorted decision nodes (way endpoints, nodes shared by two or more
+/// way references, barriers) and the number of included ways.
+///
+/// Node references of included ways go through an external sort so the
+/// counting runs in `memory_budget`; endpoints are pushed once more so
+/// they always reach two references.
 fn collect_decision_nodes(
-    ways_path: &PathBuf,
-    way_attrs_by_mode: &[HashMap<i64, Vec<u8>>],
-) -> Result<(HashSet<i64>, HashSet<i64>)> {
-    let mut node_usage: HashMap<i64, usize> = HashMap::new();
-    let mut decision_nodes = HashSet::new();
-    let mut included_ways = HashSet::new();
-
-    // Stream ways and count node usage
-    let way_stream = WaysFile::stream_ways(ways_path)?;
-
-    for result in way_stream {
+    ways_path: &Path,
+    way_attrs_paths: &[(String, PathBuf)],
+    barriers: &NodeBarriers,
+    spill_dir: &Path,
+    memory_budget: usize,
+) -> Result<(Vec<i64>, u64)> {
+    let mut refs = ExternalSorter::new("nbg-refs", spill_dir, memory_budget);
+    let mut access = AccessJoin::open(way_attrs_paths)?;
+    let mut included_ways = 0u64;
+
+    for result in WaysFile::stream_ways(ways_path)? {
         let (way_id, _keys, _vals, nodes) = result?;
 
         // Check if way is included (has access in any mode)
-        let records: Vec<&[u8]> = way_attrs_by_mode
-            .iter()
-            .filter_map(|attrs| attrs.get(&way_id).map(|v| v.as_slice()))
-            .collect();
-
-        if !records.is_empty() && has_any_access(&records) {
-            included_ways.insert(way_id);
-
-            // Mark endpoints as decision nodes
-            if let Some(&first) = nodes.first() {
-                decision_nodes.insert(first);
-            }
-            if let Some(&last) = nodes.last() {
-                decision_nodes.insert(last);
-            }
+        if !access.has_any_access(way_id)? {
+            continue;
+        }
+        included_ways += 1;
 
-            // Count node usage for intersection detection
-            for &node_id in &nodes {
-                *node_usage.entry(node_id).or_insert(0) += 1;
-            }
+        for &node_id in &nodes {
+            refs.push(node_id)?;
+        }
+        if let (Some(&first), Some(&last)) = (nodes.first(), nodes.last()) {
+            refs.push(first)?;
+            refs.push(last)?;
         }
     }
 
-    // Add intersections (nodes used by >= 2 ways) as decision nodes
-    for (node_id, count) in node_usage {
-        if count >= 2 {
-            decision_nodes.insert(node_id);
+    let spilled = refs.spilled_runs();
+    let mut decision_nodes = Vec::new();
+    let mut sorted = refs.finish()?.peekable();
+    while let Some(node_id) = sorted.next().transpose()? {
+        let mut count = 1;
+        while sorted
+            .next_if(|next| matches!(next, Ok(id) if *id == node_id))
+            .is_some()
+        {
+            count += 1;
+        }
+        if count >= 2 || barriers.get(node_id).is_some() {
+            decision_nodes.push(node_id);
         }
     }
+    drop(sorted);
+    if spilled > 0 {
+        println!(
+            "  ✓ Spilled {spilled} sorted runs to {}",
+            spill_dir.display()
+        );
+        // Best effort: the runs themselves are already gone.
+        let _ = std::fs::remove_dir(spill_dir);
+    }
 
     Ok((decision_nodes, included_ways))
 }
 
-fn build_node_map(decision_nodes: &HashSet<i64>) -> Result<NbgNodeMap> {
-    let mut nodes: Vec<i64> = decision_nodes.iter().copied().collect();
-    nodes.sort_unstable();
-
-    let mappings: Vec<NodeMapping> = nodes
+/// `decision_nodes` must be sorted and unique.
+fn build_node_map(decision_nodes: Vec<i64>) -> NbgNodeMap {
+    let mappings: Vec<NodeMapping> = decision_nodes
         .into_iter()
         .enumerate()
         .map(|(idx, osm_id)| NodeMapping {
@@ -373,7 +445,13 @@ fn build_node_map(decision_nodes: &HashSet<i64>) -> Result<NbgNodeMap> {
         })
         .collect();
 
-    Ok(NbgNodeMap { mappings })
+    NbgNodeMap { mappings }
+}
+
+/// Compact id of a decision node: its index in the sorted list.
+#[inline]
+fn compact_id(decision_nodes: &[i64], osm_id: i64) -> Option<u32> {
+    decision_nodes.binary_search(&osm_id).ok().map(|i| i as u32)
 }
 
 #[derive(Debug, Clone)]
@@ -393,24 +471,104 @@ struct EdgeInfo {
     flags: u32,
 }
 
-#[allow(clippy::type_complexity)]
+/// What [`emit_edges`] did with the way segments between decision nodes,
+/// so nothing is dropped without showing up in the build log.
+#[derive(Debug, Default)]
+struct EmitStats {
+    self_loops: u64,
+    /// Kept with the 1 m floor: distinct decision nodes at one position.
+    zero_length: u64,
+    /// Dropped: fewer than two vertices with coordinates.
+    dropped_missing_coords: u64,
+    /// Dropped: a loop back to its start that never leaves its position.
+    dropped_degenerate_loops: u64,
+    /// Polyline vertices skipped for missing coordinates on kept edges.
+    skipped_vertices: u64,
+    /// Edges per tag-derived flag bit (ferry, bridge, tunnel, roundabout, ford).
+    flagged: [u64; 5],
+}
+
+/// Dictionary ids of the way tags behind the `EDGE_FLAG_*` bits, resolved
+/// once so Step 3 does not keep the (planet-sized) dictionaries around.
+#[derive(Debug, Default)]
+struct EdgeClassTags {
+    route: Option<u32>,
+    bridge: Option<u32>,
+    tunnel: Option<u32>,
+    junction: Option<u32>,
+    ford: Option<u32>,
+    ferry: Option<u32>,
+    no: Option<u32>,
+    roundabout: Option<u32>,
+    circular: Option<u32>,
+    yes: Option<u32>,
+}
+
+impl EdgeClassTags {
+    fn load(ways_path: &Path) -> Result<Self> {
+        let (keys, vals, _, _) = WaysFile::read_dictionaries(ways_path)?;
+        Ok(Self::from_dictionaries(&keys, &vals))
+    }
+
+    fn from_dictionaries(keys: &HashMap<u32, String>, vals: &HashMap<u32, String>) -> Self {
+        let id = |dict: &HashMap<u32, String>, s: &str| {
+            dict.iter().find(|(_, v)| *v == s).map(|(&id, _)| id)
+        };
+        Self {
+            route: id(keys, "route"),
+            bridge: id(keys, "bridge"),
+            tunnel: id(keys, "tunnel"),
+            junction: id(keys, "junction"),
+            ford: id(keys, "ford"),
+            ferry: id(vals, "ferry"),
+            no: id(vals, "no"),
+            roundabout: id(vals, "roundabout"),
+            circular: id(vals, "circular"),
+            yes: id(vals, "yes"),
+        }
+    }
+
+    /// `EDGE_FLAG_*` bits for a way's tags.
+    fn flags(&self, keys: &[u32], vals: &[u32]) -> u32 {
+        let mut flags = 0;
+        for (&k, &v) in keys.iter().zip(vals) {
+            let (k, v) = (Some(k), Some(v));
+            if k == self.route && v == self.ferry {
+                flags |= EDGE_FLAG_FERRY;
+            } else if k == self.bridge && v != self.no {
+                flags |= EDGE_FLAG_BRIDGE;
+            } else if k == self.tunnel && v != self.no {
+                flags |= EDGE_FLAG_TUNNEL;
+            } else if k == self.junction && (v == self.roundabout || v == self.circular) {
+                flags |= EDGE_FLAG_ROUNDABOUT;
+            } else if k == self.ford && v == self.yes {
+                flags |= EDGE_FLAG_FORD;
+            }
+        }
+        flags
+    }
+}
+
 fn emit_edges(
-    ways_path: &PathBuf,
-    included_ways: &HashSet<i64>,
-    osm_to_compact: &HashMap<i64, u32>,
+    ways_path: &Path,
+    way_attrs_paths: &[(String, PathBuf)],
+    decision_nodes: &[i64],
     node_coords: &NodeCoords,
-) -> Result<(Vec<EdgeInfo>, HashMap<u32, Vec<(u32, u64)>>)> {
+    class_tags: &EdgeClassTags,
+) -> Result<(Vec<EdgeInfo>, EmitStats)> {
     let mut edges = Vec::new();
-    let mut adjacency: HashMap<u32, Vec<(u32, u64)>> = HashMap::new();
+    let mut stats = EmitStats::default();
+    let mut access = AccessJoin::open(way_attrs_paths)?;
 
     let way_stream = WaysFile::stream_ways(ways_path)?;
 
     for result in way_stream {
-        let (way_id, _keys, _vals, nodes) = result?;
+        let (way_id, keys, vals, nodes) = result?;
 
-        if !included_ways.contains(&way_id) {
+        if !access.has_any_access(way_id)? {
             continue;
         }
+        let way_flags = class_tags.flags(&keys, &vals);
 
         // Walk the way and emit edges between decision nodes
         let mut seg_start_idx = 0;
@@ -419,14 +577,15 @@ fn emit_edges(
             let node_id = nodes[i];
 
             // Check if this is a decision node
-            if osm_to_compact.contains_key(&node_id) {
+            if compact_id(decision_nodes, node_id).is_some() {
                 // Emit edge from seg_start_idx to i
                 let start_osm = nodes[seg_start_idx];
                 let end_osm = node_id;
 
-                if let (Some(&u_compact), Some(&v_compact)) =
-                    (osm_to_compact.get(&start_osm), osm_to_compact.get(&end_osm))
-                {
+                if let (Some(u_compact), Some(v_compact)) = (
+                    compact_id(decision_nodes, start_osm),
+                    compact_id(decision_nodes, end_osm),
+                ) {
                     // Collect polyline + the 1:1 OSM id chain (#460). The
                     // id push sits INSIDE the coord guard so ids stay
                     // exactly parallel to the vertices when a node's
@@ -452,18 +611,44 @@ fn emit_edges(
                         }
                     }
 
-                    if lat_fxp.len() >= 2 && length_m > 0.0 {
+                    // Parallel edges (two ways between the same decision
+                    // nodes) are distinct edges; nothing here is keyed by
+                    // (u, v). A self-loop is kept as long as it goes
+                    // somewhere, and a zero-length link between distinct
+                    // nodes is kept so they stay connected.
+                    let is_self_loop = u_compact == v_compact;
+                    if lat_fxp.len() < 2 {
+                        stats.dropped_missing_coords += 1;
+                    } else if is_self_loop && length_m == 0.0 {
+                        stats.dropped_degenerate_loops += 1;
+                    } else {
+                        stats.skipped_vertices += (i + 1 - seg_start_idx - lat_fxp.len()) as u64;
+                        if length_m == 0.0 {
+                            stats.zero_length += 1;
+                        }
                         let length_mm = (length_m * 1000.0).round() as u32;
                         // Saturate to minimum 1m as per spec (1m ≤ length_mm ≤ 500km)
                         let length_mm = length_mm.max(1000);
 
-                        // Compute bearing
-                        let (start_lat, start_lon) =
-                            node_coords.get(start_osm).unwrap_or((0.0, 0.0));
-                        let (end_lat, end_lon) = node_coords.get(end_osm).unwrap_or((0.0, 0.0));
-                        let bearing = compute_bearing(start_lat, start_lon, end_lat, end_lon);
+                        // Bearing from start to end; NA when they coincide
+                        // (always for a self-loop).
+                        let start = node_coords.get(start_osm).unwrap_or((0.0, 0.0));
+                        let end = node_coords.get(end_osm).unwrap_or((0.0, 0.0));
+                        let bearing = if is_self_loop || start == end {
+                            65535
+                        } else {
+                            compute_bearing(start.0, start.1, end.0, end.1)
+                        };
+
+                        let mut flags = way_flags;
+                        for (bit, count) in stats.flagged.iter_mut().enumerate() {
+                            *count += u64::from((flags >> bit) & 1);
+                        }
+                        if is_self_loop {
+                            flags |= EDGE_FLAG_SELF_LOOP;
+                            stats.self_loops += 1;
+                        }
 
-                        let edge_idx = edges.len() as u64;
                         let edge = EdgeInfo {
                             u_node: u_compact,
                             v_node: v_compact,
@@ -472,20 +657,10 @@ fn emit_edges(
                             polyline: PolyLine { lat_fxp, lon_fxp },
                             osm_ids,
                             first_osm_way_id: way_id,
-                            flags: 0, // Reserved for future use (roundabout, ferry, tunnel, bridge); see NbgEdge definition in formats/nbg_geo.rs
+                            flags,
                         };
 
                         edges.push(edge);
-
-                        // Add both directions to adjacency
-                        adjacency
-                            .entry(u_compact)
-                            .or_default()
-                            .push((v_compact, edge_idx));
-                        adjacency
-                            .entry(v_compact)
-                            .or_default()
-                            .push((u_compact, edge_idx));
                     }
                 }
 
@@ -494,30 +669,35 @@ fn emit_edges(
         }
     }
 
-    Ok((edges, adjacency))
+    Ok((edges, stats))
 }
 
-fn assemble_csr(
-    adjacency: &HashMap<u32, Vec<(u32, u64)>>,
-    n_nodes: u32,
-    n_edges_und: u64,
-) -> Result<NbgCsr> {
+/// CSR over both directions of every edge. A counting sort by tail node,
+/// visiting edges in emission order, so each node lists its neighbours
+/// in the order the edges were emitted (u→v before v→u).
+fn assemble_csr(edges: &[EdgeInfo], n_nodes: u32) -> NbgCsr {
+    let n_edges_und = edges.len() as u64;
     let mut offsets = vec![0u64; (n_nodes + 1) as usize];
-    let mut heads = Vec::new();
-    let mut edge_idx = Vec::new();
-
-    // Build CSR
-    for node_id in 0..n_nodes {
-        offsets[node_id as usize] = heads.len() as u64;
+    for edge in edges {
+        offsets[edge.u_node as usize + 1] += 1;
+        offsets[edge.v_node as usize + 1] += 1;
+    }
+    for i in 0..n_nodes as usize {
+        offsets[i + 1] += offsets[i];
+    }
 
-        if let Some(neighbors) = adjacency.get(&node_id) {
-            for &(neighbor, edge_id) in neighbors {
-                heads.push(neighbor);
-                edge_idx.push(edge_id);
-            }
+    let n_arcs = offsets[n_nodes as usize] as usize;
+    let mut heads = vec![0u32; n_arcs];
+    let mut edge_idx = vec![0u64; n_arcs];
+    let mut next = offsets[..n_nodes as usize].to_vec();
+    for (idx, edge) in edges.iter().enumerate() {
+        for (tail, head) in [(edge.u_node, edge.v_node), (edge.v_node, edge.u_node)] {
+            let slot = &mut next[tail as usize];
+            heads[*slot as usize] = head;
+            edge_idx[*slot as usize] = idx as u64;
+            *slot += 1;
         }
     }
-    offsets[n_nodes as usize] = heads.len() as u64;
 
     // #419: deterministic for byte-reproducible builds (field never read).
     let created_unix: u64 = 0;
@@ -527,7 +707,7 @@ fn assemble_csr(
     // ways.raw, every way_attrs.*) before writing to disk. Leaving it
     // zero here keeps `assemble_csr` pure (no I/O); the stamp lives at
     // the orchestration layer where the input paths are known.
-    Ok(NbgCsr {
+    NbgCsr {
         n_nodes,
         n_edges_und,
         created_unix,
@@ -535,7 +715,7 @@ fn assemble_csr(
         offsets,
         heads,
         edge_idx,
-    })
+    }
 }
 
 fn build_geo_structure(edges: Vec<EdgeInfo>) -> Result<NbgGeo> {
@@ -544,8 +724,14 @@ fn build_geo_structure(edges: Vec<EdgeInfo>) -> Result<NbgGeo> {
     let mut polylines = Vec::new();
     let mut poly_off = 0u64;
 
-    for edge in edges {
-        let n_poly_pts = edge.polyline.lat_fxp.len() as u16;
+    for (idx, edge) in edges.into_iter().enumerate() {
+        let n_poly_pts = u16::try_from(edge.polyline.lat_fxp.len()).with_context(|| {
+            format!(
+                "edge {idx} (way {}) has {} vertices, more than nbg.geo can store",
+                edge.first_osm_way_id,
+                edge.polyline.lat_fxp.len()
+            )
+        })?;
         let poly_bytes = (n_poly_pts as u64) * 4 * 2; // lat + lon, 4 bytes each
 
         nbg_edges.push(NbgEdge {
@@ -569,3 +755,64 @@ fn build_geo_structure(edges: Vec<EdgeInfo>) -> Result<NbgGeo> {
         polylines,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(u_node: u32, v_node: u32) -> EdgeInfo {
+        EdgeInfo {
+            u_node,
+            v_node,
+            length_mm: 1000,
+            bearing_deci_deg: 0,
+            polyline: PolyLine {
+                lat_fxp: vec![0, 1],
+                lon_fxp: vec![0, 1],
+            },
+            osm_ids: vec![0, 1],
+            first_osm_way_id: 1,
+            flags: 0,
+        }
+    }
+
+    #[test]
+    fn test_assemble_csr_keeps_emission_order() {
+        // 0-1, 2-0, 1-1 (self-loop), 0-2 (parallel to the second edge)
+        let edges = [edge(0, 1), edge(2, 0), edge(1, 1), edge(0, 2)];
+        let csr = assemble_csr(&edges, 4);
+        assert_eq!(csr.n_edges_und, 4);
+        assert_eq!(csr.offsets, vec![0, 3, 6, 8, 8]);
+        assert_eq!(csr.heads, vec![1, 2, 2, 0, 1, 1, 0, 0]);
+        assert_eq!(csr.edge_idx, vec![0, 1, 3, 0, 2, 2, 1, 3]);
+    }
+
+    #[test]
+    fn test_edge_class_tags() {
+        let dict = |entries: &[(u32, &str)]| {
+            entries
+                .iter()
+                .map(|&(id, s)| (id, s.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+        let keys = dict(&[(0, "bridge"), (1, "junction"), (2, "route"), (3, "highway")]);
+        let vals = dict(&[(0, "no"), (1, "roundabout"), (2, "ferry"), (3, "viaduct")]);
+        let tags = EdgeClassTags::from_dictionaries(&keys, &vals);
+
+        assert_eq!(tags.flags(&[2], &[2]), EDGE_FLAG_FERRY);
+        assert_eq!(
+            tags.flags(&[0, 1], &[3, 1]),
+            EDGE_FLAG_BRIDGE | EDGE_FLAG_ROUNDABOUT
+        );
+        assert_eq!(tags.flags(&[0, 3], &[0, 1]), 0); // bridge=no
+        // No tunnel/ford keys in the dictionary: nothing to match.
+        assert_eq!(tags.flags(&[], &[]), 0);
+    }
+
+    #[test]
+    fn test_compact_id() {
+        let nodes = [3, 8, 20];
+        assert_eq!(compact_id(&nodes, 8), Some(1));
+        assert_eq!(compact_id(&nodes, 9), None);
+    }
+}
@@ -175,6 +175,36 @@ fn minimum_degree_order_generic<G: CsrAdjacency>(graph: &G, nodes: &[u32]) -> Ve
     ordered
 }
 
+/// Partitioner used by the nested dissection in [`generate_ordering`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderingAlgorithm {
+    /// Principal-axis bisection at the coordinate median, separator taken
+    /// as a greedy vertex cover of the crossing edges.
+    #[default]
+    Current,
+    /// Inertial flow: for several projection directions, the first and last
+    /// quarter of the nodes become source and sink, and the separator is a
+    /// minimum vertex cut between them (unit-capacity max-flow).
+    Nd,
+}
+
+impl OrderingAlgorithm {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "current" => Ok(Self::Current),
+            "nd" => Ok(Self::Nd),
+            other => anyhow::bail!("unknown ordering '{}' (expected nd or current)", other),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Current => "current",
+            Self::Nd => "nd",
+        }
+    }
+}
+
 /// Configuration for Step 6
 pub struct Step6Config {
     pub filtered_ebg_path: PathBuf,
@@ -185,6 +215,7 @@ pub struct Step6Config {
     pub outdir: PathBuf,
     pub leaf_threshold: usize,
     pub balance_eps: f32,
+    pub algorithm: OrderingAlgorithm,
 }
 
 /// Result of Step 6 ordering
@@ -193,6 +224,7 @@ pub struct Step6Result {
     pub order_path: PathBuf,
     pub mode: Mode,
     pub mode_name: String,
+    pub algorithm: OrderingAlgorithm,
     pub n_nodes: u32,
     pub n_components: usize,
     pub tree_depth: usize,
@@ -245,12 +277,19 @@ pub fn generate_ordering(config: Step6Config) -> Result<Step6Result> {
     }
 
     // Build ordering via nested dissection on filtered space
-    println!("\nBuilding nested dissection ordering...");
+    println!(
+        "\nBuilding nested dissection ordering ({})...",
+        match config.algorithm {
+            OrderingAlgorithm::Current => "inertial bisection",
+            OrderingAlgorithm::Nd => "inertial flow",
+        }
+    );
     let mut builder = NdBuilder::new(
         filtered_ebg.n_filtered_nodes as usize,
         config.leaf_threshold,
         config.balance_eps,
     );
+    builder.algorithm = config.algorithm;
 
     let mut max_depth = 0;
     for (comp_idx, component) in components.iter().enumerate() {
@@ -295,6 +334,7 @@ pub fn generate_ordering(config: Step6Config) -> Result<Step6Result> {
         order_path,
         mode: config.mode,
         mode_name: config.mode_name.clone(),
+        algorithm: config.algorithm,
         n_nodes: filtered_ebg.n_filtered_nodes,
         n_components,
         tree_depth: max_depth,
@@ -428,6 +468,7 @@ struct NdBuilder {
     inv_perm: Vec<u32>,
     next_rank: u32,
     leaf_threshold: usize,
+    algorithm: OrderingAlgorithm,
 }
 
 impl NdBuilder {
@@ -441,6 +482,7 @@ impl NdBuilder {
             inv_perm: vec![u32::MAX; n_nodes],
             next_rank: 0,
             leaf_threshold,
+            algorithm: OrderingAlgorithm::Current,
         }
     }
 
@@ -491,8 +533,12 @@ impl NdBuilder {
             return Ok(NdResult { ordering, depth });
         }
 
-        let (part_a, part_b, separator) =
-            self.inertial_partition_filtered(filtered_ebg, coords, nodes)?;
+        let (part_a, part_b, separator) = match self.algorithm {
+            OrderingAlgorithm::Current => {
+                self.inertial_partition_filtered(filtered_ebg, coords, nodes)?
+            }
+            OrderingAlgorithm::Nd => inertial_flow_partition_filtered(filtered_ebg, coords, nodes),
+        };
 
         let balance = part_a.len() as f32 / (part_a.len() + part_b.len()).max(1) as f32;
 
@@ -773,6 +819,303 @@ fn histogram_partition(projections: &[(f64, u32)]) -> (Vec<u32>, Vec<u32>) {
     (part_a, part_b)
 }
 
+// ---------------------------------------------------------------------------
+// Inertial flow partitioning (`--ordering nd`)
+// ---------------------------------------------------------------------------
+
+/// Share of the projected nodes pinned to the source (and to the sink) side.
+const INERTIAL_FLOW_TERMINAL_RATIO: f64 = 0.25;
+
+/// Projection directions tried by inertial flow: horizontal, vertical and
+/// both diagonals.
+const INERTIAL_FLOW_DIRECTIONS: [(f64, f64); 4] = [
+    (1.0, 0.0),
+    (0.0, 1.0),
+    (
+        std::f64::consts::FRAC_1_SQRT_2,
+        std::f64::consts::FRAC_1_SQRT_2,
+    ),
+    (
+        std::f64::consts::FRAC_1_SQRT_2,
+        -std::f64::consts::FRAC_1_SQRT_2,
+    ),
+];
+
+/// Inertial flow partition of `nodes` on the symmetrized filtered EBG.
+fn inertial_flow_partition_filtered(
+    filtered_ebg: &FilteredEbg,
+    coords: &[(f64, f64)],
+    nodes: &[u32],
+) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
+    let local: HashMap<u32, u32> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, &node)| (node, i as u32))
+        .collect();
+
+    let mut edges: Vec<(u32, u32)> = Vec::new();
+    for (i, &node) in nodes.iter().enumerate() {
+        let start = filtered_ebg.offsets[node as usize] as usize;
+        let end = filtered_ebg.offsets[node as usize + 1] as usize;
+        for &head in &filtered_ebg.heads[start..end] {
+            if let Some(&j) = local.get(&head) {
+                let i = i as u32;
+                if i != j {
+                    edges.push((i.min(j), i.max(j)));
+                }
+            }
+        }
+    }
+    edges.sort_unstable();
+    edges.dedup();
+
+    let local_coords: Vec<(f64, f64)> = nodes.iter().map(|&n| coords[n as usize]).collect();
+    let (part_a, part_b, separator) = inertial_flow_cut(&local_coords, &edges);
+
+    let to_global = |part: Vec<u32>| part.into_iter().map(|i| nodes[i as usize]).collect();
+    (to_global(part_a), to_global(part_b), to_global(separator))
+}
+
+/// Inertial flow on a local undirected graph (`edges` as `(lo, hi)` pairs).
+///
+/// For every direction in [`INERTIAL_FLOW_DIRECTIONS`] the nodes are sorted
+/// by projection; the first and last [`INERTIAL_FLOW_TERMINAL_RATIO`] become
+/// source and sink, and a minimum vertex cut between them is the candidate
+/// separator. The smallest separator wins, ties broken by balance, then by
+/// direction order. Returns `(part_a, part_b, separator)` in local ids, each
+/// sorted; no edge joins `part_a` and `part_b`.
+fn inertial_flow_cut(
+    coords: &[(f64, f64)],
+    edges: &[(u32, u32)],
+) -> (Vec<u32>, Vec<u32>, Vec<u32>) {
+    let n = coords.len();
+    if n <= 2 {
+        return (vec![], vec![], (0..n as u32).collect());
+    }
+    let n_terminals = ((n as f64 * INERTIAL_FLOW_TERMINAL_RATIO) as usize).clamp(1, n / 2);
+
+    let mut best: Option<(usize, usize, Vec<u8>)> = None;
+    for (dir_x, dir_y) in INERTIAL_FLOW_DIRECTIONS {
+        let mut by_proj: Vec<u32> = (0..n as u32).collect();
+        by_proj.sort_by(|&a, &b| {
+            let (ax, ay) = coords[a as usize];
+            let (bx, by) = coords[b as usize];
+            (ax * dir_x + ay * dir_y)
+                .total_cmp(&(bx * dir_x + by * dir_y))
+                .then(a.cmp(&b))
+        });
+        let sources = &by_proj[..n_terminals];
+        let sinks = &by_proj[n - n_terminals..];
+
+        let side = min_vertex_cut(n, edges, sources, sinks);
+        let sep_len = side.iter().filter(|&&s| s == SIDE_SEPARATOR).count();
+        let a_len = side.iter().filter(|&&s| s == SIDE_A).count();
+        let imbalance = a_len.abs_diff(n - sep_len - a_len);
+        if best
+            .as_ref()
+            .is_none_or(|(b_sep, b_imb, _)| (sep_len, imbalance) < (*b_sep, *b_imb))
+        {
+            best = Some((sep_len, imbalance, side));
+        }
+    }
+
+    let (_, _, side) = best.expect("at least one direction");
+    let mut part_a = Vec::new();
+    let mut part_b = Vec::new();
+    let mut separator = Vec::new();
+    for (i, s) in side.into_iter().enumerate() {
+        match s {
+            SIDE_A => part_a.push(i as u32),
+            SIDE_B => part_b.push(i as u32),
+            _ => separator.push(i as u32),
+        }
+    }
+    (part_a, part_b, separator)
+}
+
+const SIDE_A: u8 = 0;
+const SIDE_B: u8 = 1;
+const SIDE_SEPARATOR: u8 = 2;
+
+/// Minimum vertex cut between `sources` and `sinks` on an undirected graph.
+///
+/// Standard node splitting: local node `i` becomes `in = 2i` and
+/// `out = 2i + 1` joined by a unit-capacity arc, every edge becomes two
+/// unbounded arcs `out → in`. Terminals hang off a super source / sink with
+/// unbounded arcs, so a terminal may itself land in the separator. After
+/// max-flow, nodes whose `out` is reachable in the residual graph form side
+/// A, nodes whose `in` is reachable but not their `out` the separator.
+fn min_vertex_cut(n: usize, edges: &[(u32, u32)], sources: &[u32], sinks: &[u32]) -> Vec<u8> {
+    const UNBOUNDED: u32 = u32::MAX / 2;
+    let s = 2 * n as u32;
+    let t = s + 1;
+
+    let mut net =
+        FlowNetwork::with_capacity(2 * n + 2, 2 * (n + 2 * edges.len() + 2 * sources.len()));
+    for i in 0..n as u32 {
+        net.add_arc(2 * i, 2 * i + 1, 1);
+    }
+    for &(u, v) in edges {
+        net.add_arc(2 * u + 1, 2 * v, UNBOUNDED);
+        net.add_arc(2 * v + 1, 2 * u, UNBOUNDED);
+    }
+    for &src in sources {
+        net.add_arc(s, 2 * src, UNBOUNDED);
+    }
+    for &snk in sinks {
+        net.add_arc(2 * snk + 1, t, UNBOUNDED);
+    }
+    net.max_flow(s, t);
+
+    let reachable = net.residual_reachable(s);
+    (0..n)
+        .map(|i| match (reachable[2 * i], reachable[2 * i + 1]) {
+            (_, true) => SIDE_A,
+            (true, false) => SIDE_SEPARATOR,
+            (false, _) => SIDE_B,
+        })
+        .collect()
+}
+
+/// Dinic max-flow on a CSR arc list. Arc `a` and its residual twin are
+/// `a` and `a ^ 1`.
+struct FlowNetwork {
+    n_nodes: usize,
+    tails: Vec<u32>,
+    heads: Vec<u32>,
+    caps: Vec<u32>,
+    first_out: Vec<u32>,
+    out_arcs: Vec<u32>,
+}
+
+impl FlowNetwork {
+    fn with_capacity(n_nodes: usize, n_arcs: usize) -> Self {
+        Self {
+            n_nodes,
+            tails: Vec::with_capacity(n_arcs),
+            heads: Vec::with_capacity(n_arcs),
+            caps: Vec::with_capacity(n_arcs),
+            first_out: Vec::new(),
+            out_arcs: Vec::new(),
+        }
+    }
+
+    fn add_arc(&mut self, from: u32, to: u32, cap: u32) {
+        self.tails.extend([from, to]);
+        self.heads.extend([to, from]);
+        self.caps.extend([cap, 0]);
+    }
+
+    fn build_csr(&mut self) {
+        let mut first_out = vec![0u32; self.n_nodes + 1];
+        for &tail in &self.tails {
+            first_out[tail as usize + 1] += 1;
+        }
+        for i in 0..self.n_nodes {
+            first_out[i + 1] += first_out[i];
+        }
+        let mut fill = first_out.clone();
+        let mut out_arcs = vec![0u32; self.tails.len()];
+        for (arc, &tail) in self.tails.iter().enumerate() {
+            out_arcs[fill[tail as usize] as usize] = arc as u32;
+            fill[tail as usize] += 1;
+        }
+        self.first_out = first_out;
+        self.out_arcs = out_arcs;
+    }
+
+    fn max_flow(&mut self, s: u32, t: u32) -> u64 {
+        self.build_csr();
+        let mut total = 0u64;
+        let mut level = vec![u32::MAX; self.n_nodes];
+        let mut next = vec![0u32; self.n_nodes];
+        let mut path: Vec<u32> = Vec::new();
+        let mut queue = VecDeque::new();
+
+        loop {
+            // BFS: level graph
+            level.fill(u32::MAX);
+            level[s as usize] = 0;
+            queue.push_back(s);
+            while let Some(u) = queue.pop_front() {
+                let u = u as usize;
+                for &arc in
+                    &self.out_arcs[self.first_out[u] as usize..self.first_out[u + 1] as usize]
+                {
+                    let v = self.heads[arc as usize] as usize;
+                    if self.caps[arc as usize] > 0 && level[v] == u32::MAX {
+                        level[v] = level[u] + 1;
+                        queue.push_back(v as u32);
+                    }
+                }
+            }
+            if level[t as usize] == u32::MAX {
+                return total;
+            }
+
+            // Blocking flow: iterative DFS with per-node arc cursors
+            next.copy_from_slice(&self.first_out[..self.n_nodes]);
+            let mut u = s as usize;
+            loop {
+                if u == t as usize {
+                    let push = path
+                        .iter()
+                        .map(|&a| self.caps[a as usize])
+                        .min()
+                        .unwrap_or(0);
+                    for &a in &path {
+                        self.caps[a as usize] -= push;
+                        self.caps[a as usize ^ 1] += push;
+                    }
+                    total += push as u64;
+                    path.clear();
+                    u = s as usize;
+                    continue;
+                }
+                let end = self.first_out[u + 1];
+                while next[u] < end {
+                    let arc = self.out_arcs[next[u] as usize] as usize;
+                    let v = self.heads[arc] as usize;
+                    if self.caps[arc] > 0 && level[v] == level[u] + 1 {
+                        break;
+                    }
+                    next[u] += 1;
+                }
+                if next[u] < end {
+                    let arc = self.out_arcs[next[u] as usize];
+                    path.push(arc);
+                    u = self.heads[arc as usize] as usize;
+                } else if u == s as usize {
+                    break;
+                } else {
+                    // Dead end: retreat and skip the arc that led here
+                    level[u] = u32::MAX;
+                    let arc = path.pop().expect("non-source node is on the path");
+                    u = self.tails[arc as usize] as usize;
+                    next[u] += 1;
+                }
+            }
+        }
+    }
+
+    fn residual_reachable(&self, s: u32) -> Vec<bool> {
+        let mut seen = vec![false; self.n_nodes];
+        seen[s as usize] = true;
+        let mut stack = vec![s];
+        while let Some(u) = stack.pop() {
+            let u = u as usize;
+            for &arc in &self.out_arcs[self.first_out[u] as usize..self.first_out[u + 1] as usize] {
+                let v = self.heads[arc as usize] as usize;
+                if self.caps[arc as usize] > 0 && !seen[v] {
+                    seen[v] = true;
+                    stack.push(v as u32);
+                }
+            }
+        }
+        seen
+    }
+}
+
 fn compute_inputs_sha(
     ebg_csr_path: &Path,
     ebg_nodes_path: &Path,
@@ -971,6 +1314,7 @@ pub fn generate_ordering_hybrid(config: Step6HybridConfig) -> Result<Step6Result
         order_path,
         mode: config.mode,
         mode_name: config.mode_name.clone(),
+        algorithm: OrderingAlgorithm::Current,
         n_nodes: hybrid.n_states,
         n_components,
         tree_depth: max_depth,
@@ -1612,3 +1956,55 @@ impl NdBuilder {
         minimum_degree_order_generic(hybrid, nodes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inertial_flow_cut_separates_a_grid() {
+        // 8x6 grid, 4-neighbour edges.
+        let (w, h) = (8u32, 6u32);
+        let coords: Vec<(f64, f64)> = (0..w * h)
+            .map(|i| ((i % w) as f64, (i / w) as f64))
+            .collect();
+        let mut edges = Vec::new();
+        for i in 0..w * h {
+            if i % w + 1 < w {
+                edges.push((i, i + 1));
+            }
+            if i + w < w * h {
+                edges.push((i, i + w));
+            }
+        }
+
+        let (part_a, part_b, separator) = inertial_flow_cut(&coords, &edges);
+        assert_eq!(
+            part_a.len() + part_b.len() + separator.len(),
+            (w * h) as usize
+        );
+        assert!(!part_a.is_empty() && !part_b.is_empty());
+        // Never worse than a straight cut across the short side.
+        assert!(separator.len() <= h as usize, "{separator:?}");
+        let in_a: HashSet<u32> = part_a.iter().copied().collect();
+        let in_b: HashSet<u32> = part_b.iter().copied().collect();
+        for &(u, v) in &edges {
+            assert!(
+                !(in_a.contains(&u) && in_b.contains(&v) || in_b.contains(&u) && in_a.contains(&v)),
+                "edge ({u}, {v}) crosses the separator"
+            );
+        }
+    }
+
+    #[test]
+    fn ordering_algorithm_round_trips() {
+        for alg in [OrderingAlgorithm::Current, OrderingAlgorithm::Nd] {
+            assert_eq!(OrderingAlgorithm::parse(alg.as_str()).unwrap(), alg);
+        }
+        assert_eq!(
+            OrderingAlgorithm::parse("ND").unwrap(),
+            OrderingAlgorithm::Nd
+        );
+        assert!(OrderingAlgorithm::parse("metis").is_err());
+    }
+}
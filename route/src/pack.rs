@@ -80,7 +80,11 @@ fn maybe_append(
         name,
         path.display()
     );
-    w.append_file(kind, name, path)
+    // Sections are served from mmap, so zstd-framed step artifacts are
+    // decoded on the way in (see `zstd_compress::open_artifact`).
+    let mut reader = crate::formats::zstd_compress::open_artifact(path)
+        .with_context(|| format!("opening section source {}", path.display()))?;
+    w.append_from_reader(kind, name, &mut reader)
         .with_context(|| format!("packing {} from {}", name, path.display()))?;
     Ok(true)
 }
@@ -497,6 +501,13 @@ pub fn pack_with_options(
             &format!("mode/{}/turn_rules", mode),
             &turn_rules,
         )?;
+        let way_conditionals = step2.join(format!("way_conditionals.{}.bin", mode));
+        maybe_append(
+            &mut w,
+            SectionKind::WayConditionals,
+            &format!("mode/{}/way_conditionals", mode),
+            &way_conditionals,
+        )?;
         // step5: filtered EBG, weights, mask.
         let filtered = step5.join(format!("filtered.{}.ebg", mode));
         maybe_append(
@@ -505,7 +516,8 @@ pub fn pack_with_options(
             &format!("mode/{}/filtered_ebg", mode),
             &filtered,
         )?;
-        let weights_time = step5.join(format!("w.{}.u32", mode));
+        // Speed-refreshed node weights (step8-customize --speeds) win.
+        let weights_time = crate::customization::node_weights_path(&step5, &step8, mode);
         maybe_append(
             &mut w,
             SectionKind::NodeWeightsTime,
@@ -32,8 +32,8 @@ pub use contour::{ContourResult, ContourStats, export_contour_geojson};
 
 pub mod sparse_contour;
 pub use sparse_contour::{
-    SparseContourConfig, SparseContourResult, SparseContourStats, generate_sparse_contour,
-    generate_sparse_contour_anchored,
+    ContourGeneralization, SparseContourConfig, SparseContourResult, SparseContourStats,
+    generate_sparse_contour, generate_sparse_contour_anchored,
 };
 
 pub mod batched_isochrone;
@@ -115,7 +115,7 @@ impl RangeEngine {
         _order_path: &Path, // Unused with rank-aligned CCH
         _mode: Mode,
     ) -> Result<Self> {
-        let topo = CchTopoFile::read(topo_path)?;
+        let topo = CchTopoFile::read_mmap(topo_path)?;
         let weights = CchWeightsFile::read(weights_path)?;
 
         let n_nodes = topo.n_nodes as usize;
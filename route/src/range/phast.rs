@@ -30,7 +30,8 @@ use crate::matrix::bucket_ch::{DownReverseAdjFlat, UpAdjFlat};
 pub struct PhastEngine {
     /// CCH topology (rank-aligned)
     topo: CchTopo,
-    /// CCH weights
+    /// CCH weights. Read through `WeightArray::get`, so lossless and
+    /// quantized (`--quantize-weights`) files are both served as-is.
     weights: CchWeights,
     /// Number of nodes
     n_nodes: usize,
@@ -104,7 +105,7 @@ impl PhastEngine {
     ) -> anyhow::Result<Self> {
         use crate::formats::{CchTopoFile, CchWeightsFile};
 
-        let topo = CchTopoFile::read(topo_path)?;
+        let topo = CchTopoFile::read_mmap(topo_path)?;
         let weights = CchWeightsFile::read(weights_path)?;
 
         Ok(Self::new(topo, weights))
@@ -608,6 +608,33 @@ impl SparseContourConfig {
             simplify_tolerance_m: 0.0,
         }
     }
+
+    /// Apply per-request generalization on top of this config. Smoothing
+    /// and buffer are rounded to whole cells of this config's size.
+    pub fn generalized(mut self, g: &ContourGeneralization) -> Self {
+        let rounds = |m: f64| (m / self.cell_size_m).round() as usize;
+        let smoothing = rounds(g.smoothing_m);
+        let buffer = rounds(g.buffer_m);
+        self.dilation_rounds += smoothing + buffer;
+        self.erosion_rounds += smoothing;
+        if let Some(tolerance) = g.simplify_m {
+            self.simplify_tolerance_m = tolerance;
+        }
+        self
+    }
+}
+
+/// Per-request contour generalization, in meters
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ContourGeneralization {
+    /// Douglas-Peucker tolerance replacing the tier default (0 keeps every
+    /// traced vertex)
+    pub simplify_m: Option<f64>,
+    /// Extra balanced closing: bridges gaps and fills notches up to about
+    /// twice this width without growing the polygon
+    pub smoothing_m: f64,
+    /// Net outward growth of the polygon
+    pub buffer_m: f64,
 }
 
 /// Statistics from sparse contour generation
@@ -626,12 +653,31 @@ pub struct SparseContourStats {
 }
 
 /// Result of sparse contour generation
+///
+/// Outer rings run counter-clockwise and holes clockwise, as traced.
 pub struct SparseContourResult {
     pub outer_ring: Vec<(f64, f64)>, // WGS84 (lon, lat) pairs
+    /// Unreachable enclaves inside `outer_ring`
     pub holes: Vec<Vec<(f64, f64)>>,
+    /// Other disconnected components as `[outer, holes...]`, largest first
+    pub parts: Vec<Vec<Vec<(f64, f64)>>>,
     pub stats: SparseContourStats,
 }
 
+impl SparseContourResult {
+    /// All polygons as `[outer, holes...]`, the primary one first (empty
+    /// when there is no contour).
+    pub fn polygons(&self) -> Vec<Vec<Vec<(f64, f64)>>> {
+        if self.outer_ring.is_empty() {
+            return vec![];
+        }
+        let primary = std::iter::once(self.outer_ring.clone()).chain(self.holes.iter().cloned());
+        std::iter::once(primary.collect())
+            .chain(self.parts.iter().cloned())
+            .collect()
+    }
+}
+
 /// Generate contour using sparse tile-based approach
 pub fn generate_sparse_contour(
     segments: &[ReachableSegment],
@@ -661,6 +707,7 @@ pub fn generate_sparse_contour_anchored(
         return Ok(SparseContourResult {
             outer_ring: vec![],
             holes: vec![],
+            parts: vec![],
             stats,
         });
     }
@@ -767,14 +814,15 @@ pub fn generate_sparse_contour_anchored(
 
     // Step 4: Extract contour using marching squares on sparse tiles
     let contour_start = std::time::Instant::now();
-    let contour = extract_contour_sparse(&closed, anchor_cell);
-    stats.contour_vertices_before_simplify = contour.len();
+    let polygons = extract_polygons_sparse(&closed, anchor_cell);
+    stats.contour_vertices_before_simplify = polygons.first().map_or(0, |p| p[0].len());
     stats.contour_time_us = contour_start.elapsed().as_micros() as u64;
 
-    if contour.is_empty() {
+    if polygons.is_empty() {
         return Ok(SparseContourResult {
             outer_ring: vec![],
             holes: vec![],
+            parts: vec![],
             stats,
         });
     }
@@ -782,17 +830,32 @@ pub fn generate_sparse_contour_anchored(
     // Step 5: Convert to WGS84 and simplify
     let simplify_start = std::time::Instant::now();
 
-    let mut wgs84_contour: Vec<(f64, f64)> = contour
-        .iter()
-        .map(|&(col, row)| {
-            let x = min_x + col * cell_size_merc;
-            let y = min_y + row * cell_size_merc;
-            from_mercator(x, y)
-        })
-        .collect();
-
     let tolerance_deg = config.simplify_tolerance_m / 111000.0;
-    wgs84_contour = douglas_peucker(&wgs84_contour, tolerance_deg);
+    let to_wgs84 = |ring: &[(f64, f64)]| -> Vec<(f64, f64)> {
+        let ring: Vec<(f64, f64)> = ring
+            .iter()
+            .map(|&(col, row)| {
+                let x = min_x + col * cell_size_merc;
+                let y = min_y + row * cell_size_merc;
+                from_mercator(x, y)
+            })
+            .collect();
+        douglas_peucker(&ring, tolerance_deg)
+    };
+
+    // Simplification can collapse a small hole (or a whole small part) to
+    // fewer than 3 vertices: drop it rather than emit a degenerate ring.
+    let mut polygons = polygons.into_iter().map(|rings| {
+        let mut rings = rings.iter().map(|r| to_wgs84(r));
+        let outer = rings.next().unwrap_or_default();
+        let holes: Vec<Vec<(f64, f64)>> = rings.filter(|h| h.len() >= 3).collect();
+        (outer, holes)
+    });
+    let (wgs84_contour, holes) = polygons.next().unwrap_or_default();
+    let parts: Vec<Vec<Vec<(f64, f64)>>> = polygons
+        .filter(|(outer, _)| outer.len() >= 3)
+        .map(|(outer, holes)| std::iter::once(outer).chain(holes).collect())
+        .collect();
     stats.contour_vertices_after_simplify = wgs84_contour.len();
     stats.simplify_time_us = simplify_start.elapsed().as_micros() as u64;
 
@@ -815,27 +878,57 @@ pub fn generate_sparse_contour_anchored(
 
     Ok(SparseContourResult {
         outer_ring: wgs84_contour,
-        holes: vec![],
+        holes,
+        parts,
         stats,
     })
 }
 
-/// Extract contour from sparse tile map using Moore-neighbor boundary tracing
+/// Boundary ring traced in tile coordinates (cell centers).
+struct TracedRing {
+    ring: Vec<(f64, f64)>,
+    /// Twice the signed (shoelace) area: positive for the outer boundary of
+    /// a component, negative for the boundary of an enclave in it.
+    area2: f64,
+    /// Center of the empty cell across the ring's start edge: a point
+    /// inside the enclave a hole ring bounds.
+    outside: (f64, f64),
+}
+
+/// Extract the primary outer ring only (see [`extract_polygons_sparse`]).
+#[cfg(test)]
+fn extract_contour_sparse(map: &SparseTileMap, anchor_cell: Option<(f64, f64)>) -> Vec<(f64, f64)> {
+    extract_polygons_sparse(map, anchor_cell)
+        .into_iter()
+        .next()
+        .map(|mut rings| rings.swap_remove(0))
+        .unwrap_or_default()
+}
+
+/// Extract contour polygons from sparse tile map using Moore-neighbor
+/// boundary tracing
 ///
 /// This is O(perimeter), not O(area) - no densification needed.
 /// We trace the boundary between filled and empty cells directly on the sparse tile map.
 ///
-/// For maps with multiple disconnected components, we trace ALL boundaries and return
-/// the LARGEST one (by vertex count). This handles cases where roads reach far-away
-/// areas without connecting to intermediate regions.
-fn extract_contour_sparse(map: &SparseTileMap, anchor_cell: Option<(f64, f64)>) -> Vec<(f64, f64)> {
+/// For maps with multiple disconnected components, we trace ALL boundaries:
+/// each component's outer ring becomes a polygon, and every enclave ring
+/// (walked in the opposite direction) becomes a hole of the smallest outer
+/// ring around it. Polygons are `[outer, holes...]`, the primary one first:
+/// the component containing the anchor, else the LARGEST one (by vertex
+/// count); the rest follow by vertex count. This handles cases where roads
+/// reach far-away areas without connecting to intermediate regions.
+fn extract_polygons_sparse(
+    map: &SparseTileMap,
+    anchor_cell: Option<(f64, f64)>,
+) -> Vec<Vec<Vec<(f64, f64)>>> {
     if map.tiles.is_empty() {
         return vec![];
     }
 
     // Track which cells have been visited (as part of a boundary)
     let mut visited_edges: HashSet<(i32, i32, u8)> = HashSet::new();
-    let mut all_contours: Vec<Vec<(f64, f64)>> = Vec::new();
+    let mut all_rings: Vec<TracedRing> = Vec::new();
 
     // Find all boundary starts and trace each component.
     //
@@ -844,17 +937,23 @@ fn extract_contour_sparse(map: &SparseTileMap, anchor_cell: Option<(f64, f64)>)
     // (its rotation) feeds Douglas-Peucker — which pins the first/last
     // vertex — so an unsorted start list made the simplified polygon vary
     // across identical runs.
+    //
+    // One start per cell finds every component's outer ring, but an enclave
+    // whose boundary cells all start on another edge would be missed: a
+    // second pass traces whatever boundary edges are still unvisited.
     let mut boundary_starts = find_all_boundary_starts(map);
     boundary_starts.sort_unstable();
+    let mut remaining_edges = find_all_boundary_edges(map);
+    remaining_edges.sort_unstable();
 
-    for (start_col, start_row, start_edge) in boundary_starts {
+    for (start_col, start_row, start_edge) in boundary_starts.into_iter().chain(remaining_edges) {
         // Skip if this edge was already traced
         if visited_edges.contains(&(start_col, start_row, start_edge)) {
             continue;
         }
 
         // Trace this boundary
-        let contour = trace_boundary_edges_with_visited(
+        let ring = trace_boundary_edges_with_visited(
             map,
             start_col,
             start_row,
@@ -862,36 +961,91 @@ fn extract_contour_sparse(map: &SparseTileMap, anchor_cell: Option<(f64, f64)>)
             &mut visited_edges,
         );
 
-        if contour.len() >= 3 {
-            all_contours.push(contour);
+        if ring.len() >= 3 {
+            let (col, row) = edge_neighbor(start_col, start_row, start_edge);
+            all_rings.push(TracedRing {
+                area2: ring_area2(&ring),
+                outside: (col as f64 + 0.5, row as f64 + 0.5),
+                ring,
+            });
         }
     }
 
+    // Zero-area rings (1-cell-wide corridors) have no enclave to bound:
+    // they are outer rings.
+    let (holes, outers): (Vec<TracedRing>, Vec<TracedRing>) =
+        all_rings.into_iter().partition(|r| r.area2 < 0.0);
+    if outers.is_empty() {
+        return vec![];
+    }
+
     // #497: prefer the contour CONTAINING the anchor (the snapped query
     // origin) — an isochrone must include its own origin, however small its
     // component. Fall back to the largest contour (by vertex count) when no
     // anchor is given or no ring contains it.
+    let mut primary: Option<usize> = None;
     if let Some(anchor) = anchor_cell {
-        let mut containing: Option<&Vec<(f64, f64)>> = None;
-        for c in &all_contours {
+        for (i, c) in outers.iter().enumerate() {
             // "Contains" must tolerate thin components: ring vertices are
             // emitted at CELL CENTERS (#431), so a 1-cell-wide corridor traces
             // a zero-area ring and its own origin lies ON it, never strictly
             // inside. Accept strictly-inside OR within one cell of the ring.
-            if (point_in_ring(anchor, c) || ring_near(anchor, c, 1.0))
-                && containing.is_none_or(|b| c.len() > b.len())
+            if (point_in_ring(anchor, &c.ring) || ring_near(anchor, &c.ring, 1.0))
+                && primary.is_none_or(|b| c.ring.len() > outers[b].ring.len())
             {
-                containing = Some(c);
+                primary = Some(i);
             }
         }
-        if let Some(c) = containing {
-            return c.clone();
+    }
+    let primary = primary.unwrap_or_else(|| {
+        (0..outers.len())
+            .max_by_key(|&i| outers[i].ring.len())
+            .unwrap_or_default()
+    });
+    let mut order: Vec<usize> = (0..outers.len()).filter(|&i| i != primary).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(outers[i].ring.len()));
+    order.insert(0, primary);
+
+    // A hole belongs to the innermost outer ring around its enclave (an
+    // island inside a lake inside a component is its own polygon).
+    let mut polygons: Vec<Vec<Vec<(f64, f64)>>> = order
+        .iter()
+        .map(|&i| vec![outers[i].ring.clone()])
+        .collect();
+    for hole in holes {
+        let owner = order
+            .iter()
+            .enumerate()
+            .filter(|&(_, &i)| point_in_ring(hole.outside, &outers[i].ring))
+            .min_by(|a, b| outers[*a.1].area2.total_cmp(&outers[*b.1].area2))
+            .map(|(slot, _)| slot);
+        if let Some(slot) = owner {
+            polygons[slot].push(hole.ring);
         }
     }
-    all_contours
-        .into_iter()
-        .max_by_key(|c| c.len())
-        .unwrap_or_default()
+    polygons
+}
+
+/// Cell across `edge` of `(col, row)`.
+fn edge_neighbor(col: i32, row: i32, edge: u8) -> (i32, i32) {
+    match edge {
+        0 => (col, row - 1),
+        1 => (col + 1, row),
+        2 => (col, row + 1),
+        _ => (col - 1, row),
+    }
+}
+
+/// Twice the signed shoelace area of an open ring (positive = CCW).
+fn ring_area2(ring: &[(f64, f64)]) -> f64 {
+    let n = ring.len();
+    (0..n)
+        .map(|i| {
+            let (x1, y1) = ring[i];
+            let (x2, y2) = ring[(i + 1) % n];
+            x1 * y2 - x2 * y1
+        })
+        .sum()
 }
 
 /// True when `pt` is within `tol` (cell units) of any ring segment.
@@ -972,6 +1126,40 @@ fn find_all_boundary_starts(map: &SparseTileMap) -> Vec<(i32, i32, u8)> {
     starts
 }
 
+/// Every boundary edge of every filled cell, as (col, row, edge)
+fn find_all_boundary_edges(map: &SparseTileMap) -> Vec<(i32, i32, u8)> {
+    let mut edges = Vec::new();
+
+    for (&coord, tile) in &map.tiles {
+        let base_col = coord.tx * TILE_SIZE as i32;
+        let base_row = coord.ty * TILE_SIZE as i32;
+
+        for local_row in 0..TILE_SIZE {
+            let row_bits = tile.bits[local_row];
+            if row_bits == 0 {
+                continue;
+            }
+
+            for local_col in 0..TILE_SIZE {
+                if (row_bits >> local_col) & 1 == 0 {
+                    continue;
+                }
+
+                let col = base_col + local_col as i32;
+                let row = base_row + local_row as i32;
+                for edge in 0..4u8 {
+                    let (ncol, nrow) = edge_neighbor(col, row, edge);
+                    if !map.get_cell(ncol, nrow) {
+                        edges.push((col, row, edge));
+                    }
+                }
+            }
+        }
+    }
+
+    edges
+}
+
 /// Trace boundary edges, marking visited edges to avoid re-tracing the same component
 fn trace_boundary_edges_with_visited(
     map: &SparseTileMap,
@@ -1371,6 +1559,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generalized_rounds_to_cells() {
+        let base = SparseContourConfig::for_car();
+        assert_eq!(
+            base.clone()
+                .generalized(&ContourGeneralization::default())
+                .simplify_tolerance_m,
+            base.simplify_tolerance_m
+        );
+        let g = ContourGeneralization {
+            simplify_m: Some(100.0),
+            smoothing_m: 60.0,
+            buffer_m: 100.0,
+        };
+        let config = base.clone().generalized(&g);
+        assert_eq!(config.simplify_tolerance_m, 100.0);
+        // 60 m smoothing = 2 cells closed both ways; 100 m buffer = 3 cells
+        // of net dilation.
+        assert_eq!(config.erosion_rounds, base.erosion_rounds + 2);
+        assert_eq!(config.dilation_rounds, base.dilation_rounds + 5);
+    }
+
     #[test]
     fn test_adaptive_boundary_at_exactly_600s() {
         // 600 s = 10 min → last tier using 1x multiplier.
@@ -1570,6 +1780,89 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_enclave_becomes_clockwise_hole() {
+        // 5x5 block with an empty 1-cell enclave at (2, 2): one polygon,
+        // CCW outer ring plus a CW hole around the enclave.
+        let cells: Vec<(i32, i32)> = (0..5)
+            .flat_map(|row| (0..5).map(move |col| (col, row)))
+            .filter(|&cell| cell != (2, 2))
+            .collect();
+        let polygons = extract_polygons_sparse(&map_with_cells(&cells), None);
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].len(), 2, "outer + one hole: {polygons:?}");
+        let (outer, hole) = (&polygons[0][0], &polygons[0][1]);
+        assert!(ring_area2(outer) > 0.0, "outer ring must be CCW");
+        assert!(ring_area2(hole) < 0.0, "hole ring must be CW");
+        assert!(point_in_ring((2.5, 2.5), hole));
+    }
+
+    #[test]
+    fn test_disjoint_components_and_island_in_hole() {
+        // 9x9 block with a 5x5 empty lake holding a 2x2 island, plus a far
+        // 3x3 block: three polygons (main + hole, far block, island), the
+        // lake's hole attached to the main block only.
+        let mut cells: Vec<(i32, i32)> = (0..9)
+            .flat_map(|row| (0..9).map(move |col| (col, row)))
+            .filter(|&(c, r)| {
+                let lake = (2..7).contains(&c) && (2..7).contains(&r);
+                let island = (4..6).contains(&c) && (4..6).contains(&r);
+                !lake || island
+            })
+            .collect();
+        cells.extend((100..103).flat_map(|row| (100..103).map(move |col| (col, row))));
+
+        let polygons = extract_polygons_sparse(&map_with_cells(&cells), None);
+        assert_eq!(polygons.len(), 3, "{polygons:?}");
+        assert_eq!(polygons[0].len(), 2, "main block keeps its lake hole");
+        assert!(polygons[1..].iter().all(|p| p.len() == 1));
+        for rings in &polygons {
+            assert!(ring_area2(&rings[0]) >= 0.0);
+        }
+        assert!(
+            polygons
+                .iter()
+                .any(|p| p[0].iter().all(|&(x, y)| x > 99.0 && y > 99.0)),
+            "far block is its own polygon"
+        );
+        // The primary outer ring is what the single-ring extraction returns.
+        assert_eq!(
+            half_cells(&polygons[0][0]),
+            half_cells(&extract_contour_sparse(&map_with_cells(&cells), None))
+        );
+    }
+
+    #[test]
+    fn test_generate_returns_holes_and_parts() {
+        // Two parallel east-west roads joined at both ends enclose an
+        // unreached block; a far road is a separate part.
+        let (lat, lon) = (50.85, 4.35);
+        let d = 0.004;
+        let segments = vec![
+            segment(lat, lon, lat, lon + d),
+            segment(lat + d, lon, lat + d, lon + d),
+            segment(lat, lon, lat + d, lon),
+            segment(lat, lon + d, lat + d, lon + d),
+            segment(lat + 0.05, lon + 0.05, lat + 0.05, lon + 0.055),
+        ];
+        let config = SparseContourConfig::no_morphology(30.0);
+        let result =
+            generate_sparse_contour_anchored(&segments, &config, Some(to_e7(lat, lon))).unwrap();
+        assert_eq!(result.holes.len(), 1, "the enclosed block is a hole");
+        assert!(point_in_ring(
+            (lon + d / 2.0, lat + d / 2.0),
+            &result.holes[0]
+        ));
+        assert_eq!(result.parts.len(), 1, "the far road is its own part");
+        let polygons = result.polygons();
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[0][0], result.outer_ring);
+    }
+
+    fn to_e7(lat: f64, lon: f64) -> (i32, i32) {
+        ((lat * 1e7) as i32, (lon * 1e7) as i32)
+    }
+
     // ==================================================================
     // #431 rank 3: cos(lat) ground-meter cell sizing
     // ==================================================================
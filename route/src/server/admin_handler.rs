@@ -0,0 +1,91 @@
+//! /admin handlers — operator hooks that mutate the loaded weights
+//!
+//! Only registered when `BUTTERFLY_ADMIN_API=on` (see [`super::api::build_router`]);
+//! the server has no authentication, so these must stay behind a trusted
+//! network boundary.
+
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use super::regions::RegionsState;
+use super::types::ErrorResponse;
+
+#[derive(Debug, Deserialize)]
+pub struct WeightDeltaQuery {
+    /// Base mode the delta applies to (traffic variants are rejected).
+    mode: String,
+    /// Region id; defaults to the primary region.
+    region: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WeightDeltaResponse {
+    pub region: String,
+    pub mode: String,
+    pub changed_nodes: usize,
+    pub seeded_arcs: usize,
+    pub recomputed_arcs: usize,
+    pub updated_arcs: usize,
+    pub elapsed_ms: u64,
+}
+
+fn bad_request(error: String) -> axum::response::Response {
+    (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response()
+}
+
+/// `POST /admin/weights/delta?mode=car[&region=ID]` with a changes.bin body.
+///
+/// Re-customizes only the CCH arcs affected by the changed node weights and
+/// hot-swaps the mode, like the boot-time traffic recustomization does.
+pub async fn weight_delta_handler(
+    State(regions): State<Arc<RegionsState>>,
+    Query(req): Query<WeightDeltaQuery>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let delta = match crate::formats::weight_delta::read_all_from_bytes(&body) {
+        Ok(d) => d,
+        Err(e) => return bad_request(format!("invalid changes.bin: {:#}", e)),
+    };
+
+    let (region, state) = match &req.region {
+        Some(id) => match regions.get(id) {
+            Some(entry) => (entry.id.clone(), entry.state()),
+            None => return bad_request(format!("unknown region '{}'", id)),
+        },
+        None => {
+            let entry = &regions.regions[0];
+            (entry.id.clone(), entry.state())
+        }
+    };
+
+    let mode = req.mode.clone();
+    let t0 = std::time::Instant::now();
+    let result = tokio::task::spawn_blocking(move || state.apply_weight_delta(&mode, &delta)).await;
+    match result {
+        Ok(Ok(stats)) => Json(WeightDeltaResponse {
+            region,
+            mode: req.mode,
+            changed_nodes: stats.changed_nodes,
+            seeded_arcs: stats.seeded_arcs,
+            recomputed_arcs: stats.recomputed_arcs,
+            updated_arcs: stats.updated_arcs,
+            elapsed_ms: t0.elapsed().as_millis() as u64,
+        })
+        .into_response(),
+        Ok(Err(e)) => bad_request(format!("{:#}", e)),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("weight delta task failed: {}", e),
+            }),
+        )
+            .into_response(),
+    }
+}
@@ -47,16 +47,24 @@ pub use super::types::{ErrorResponse, Waypoint, parse_mode, validate_coord};
         super::route::RouteResponse,
         super::route::RouteAnnotations,
         super::route::RouteAlternative,
+        super::route::RouteLeg,
+        super::route::RouteWaypoint,
         super::route::SnapInfo,
         super::route::RouteDebugInfo,
         super::route::RouteStep,
         super::route::StepManeuver,
         super::table::TablePostRequest,
+        super::table::TableDestinations,
         super::table::TableResponse,
+        super::table::SnapStatus,
         super::isochrone_handler::BulkIsochroneRequest,
         super::isochrone_handler::IsochroneRequest,
         super::isochrone_handler::IsochroneResponse,
         super::isochrone_handler::ContourFeature,
+        super::isochrone_handler::IsochroneFeatureCollection,
+        super::isochrone_handler::IsochroneFeature,
+        super::isochrone_handler::IsochroneGeometry,
+        super::isochrone_handler::IsochroneFeatureProperties,
         super::nearest::NearestRequest,
         super::nearest::NearestResponse,
         super::nearest::NearestWaypoint,
@@ -76,6 +84,7 @@ pub use super::types::{ErrorResponse, Waypoint, parse_mode, validate_coord};
         super::elevation::HeightRequest,
         super::elevation::HeightResponse,
         super::elevation::HeightResult,
+        super::elevation::RouteElevation,
         super::regions_handler::LoadedRegion,
         super::regions_handler::RegionsResponse,
     )),
@@ -157,6 +166,19 @@ pub fn build_router(state: Arc<RegionsState>) -> Router {
     } else {
         tracing::info!("/height endpoint NOT registered — no SRTM elevation data loaded");
     }
+    // Admin hooks mutate live weights and the server has no auth, so they
+    // are opt-in: BUTTERFLY_ADMIN_API=on registers them, anything else
+    // (including unset) leaves them unrouted (404).
+    let admin_enabled = std::env::var("BUTTERFLY_ADMIN_API")
+        .map(|v| matches!(v.to_ascii_lowercase().as_str(), "on" | "1" | "true"))
+        .unwrap_or(false);
+    if admin_enabled {
+        api_routes = api_routes.route(
+            "/admin/weights/delta",
+            post(super::admin_handler::weight_delta_handler),
+        );
+        tracing::warn!("BUTTERFLY_ADMIN_API=on — /admin/weights/delta is exposed without auth");
+    }
     let api_routes = api_routes
         .layer(CompressionLayer::new())
         .layer(ConcurrencyLimitLayer::new(32))
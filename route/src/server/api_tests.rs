@@ -1,8 +1,12 @@
 //! Unit tests extracted from the original api.rs
 
 use super::isochrone_handler::{ContourFeature, IsochroneResponse};
-use super::route::{RouteAnnotations, RouteResponse, bearing_diff, classify_turn, compute_bearing};
-use super::types::{parse_mode, validate_coord};
+use super::route::{
+    RouteAnnotations, RouteResponse, bearing_diff, classify_turn, compute_bearing, route_weighting,
+};
+use super::types::{
+    Weighting, available_weightings, parse_mode, resolve_weighting, validate_coord,
+};
 
 use crate::profile_abi::Mode;
 
@@ -454,6 +458,56 @@ fn test_geometry_format_parse_invalid() {
     assert!(GeometryFormat::parse(" polyline6 ").is_err());
 }
 
+#[test]
+fn test_parse_weighting() {
+    assert_eq!(Weighting::parse(None), Ok(Weighting::Fastest));
+    assert_eq!(Weighting::parse(Some("fastest")), Ok(Weighting::Fastest));
+    assert_eq!(Weighting::parse(Some("Shortest")), Ok(Weighting::Shortest));
+    assert_eq!(
+        Weighting::parse(Some("Rush_Hour")),
+        Ok(Weighting::Variant("rush_hour".into()))
+    );
+    assert!(Weighting::parse(Some("")).is_err());
+}
+
+#[test]
+fn test_resolve_weighting_selects_variant_mode() {
+    let mut lookup = test_mode_lookup();
+    lookup.insert("car_rush_hour".to_string(), 3);
+    assert_eq!(
+        resolve_weighting("car", None, &lookup),
+        Ok((Mode(1), Weighting::Fastest))
+    );
+    assert_eq!(
+        resolve_weighting("car", Some("shortest"), &lookup),
+        Ok((Mode(1), Weighting::Shortest))
+    );
+    assert_eq!(
+        resolve_weighting("car", Some("rush_hour"), &lookup),
+        Ok((Mode(3), Weighting::Variant("rush_hour".into())))
+    );
+    let err = resolve_weighting("bike", Some("rush_hour"), &lookup).unwrap_err();
+    assert!(err.contains("Available: fastest, shortest."), "{err}");
+    assert_eq!(
+        available_weightings("car", &lookup),
+        vec!["fastest", "shortest", "rush_hour"]
+    );
+}
+
+#[test]
+fn test_route_weighting_traffic_alias() {
+    assert_eq!(route_weighting(None, None), Ok(None));
+    assert_eq!(
+        route_weighting(Some("rush_hour"), None),
+        Ok(Some("rush_hour"))
+    );
+    assert_eq!(
+        route_weighting(Some(" "), Some("shortest")),
+        Ok(Some("shortest"))
+    );
+    assert!(route_weighting(Some("rush_hour"), Some("fastest")).is_err());
+}
+
 // === 4. Isochrone time_s boundary tests ===
 
 #[test]
@@ -917,6 +971,7 @@ fn test_contour_feature_serialization_time() {
         time_s: Some(600),
         polygon: None,
         polygon_geojson: Some(vec![[4.35, 50.85], [4.36, 50.86]]),
+        multipolygon_geojson: None,
         polygon_points: None,
         band: None,
         reachable_edges: 1234,
@@ -936,6 +991,7 @@ fn test_isochrone_response_always_has_contours_array() {
                 time_s: Some(300),
                 polygon: None,
                 polygon_geojson: Some(vec![[4.35, 50.85]]),
+                multipolygon_geojson: None,
                 polygon_points: None,
                 band: None,
                 reachable_edges: 1000,
@@ -944,6 +1000,7 @@ fn test_isochrone_response_always_has_contours_array() {
                 time_s: Some(600),
                 polygon: None,
                 polygon_geojson: Some(vec![[4.34, 50.84]]),
+                multipolygon_geojson: None,
                 polygon_points: None,
                 band: None,
                 reachable_edges: 3000,
@@ -959,6 +1016,102 @@ fn test_isochrone_response_always_has_contours_array() {
     assert!(contours[1]["reachable_edges"].as_u64() > contours[0]["reachable_edges"].as_u64());
 }
 
+#[test]
+fn test_isochrone_thresholds_feature_collection() {
+    use super::isochrone_handler::IsochroneFeatureCollection;
+    let ring = |d: f64| {
+        vec![
+            [4.35 - d, 50.85],
+            [4.35 + d, 50.85],
+            [4.35, 50.85 + d],
+            [4.35 - d, 50.85],
+        ]
+    };
+    let contour = |t: u32, d: f64, edges: usize| ContourFeature {
+        time_s: Some(t),
+        polygon: None,
+        polygon_geojson: Some(ring(d)),
+        multipolygon_geojson: None,
+        polygon_points: None,
+        band: None,
+        reachable_edges: edges,
+    };
+    // The 600 s contour has an unreachable enclave and a second part.
+    let mut outer = contour(600, 0.02, 400);
+    let hole = vec![
+        [4.35, 50.851],
+        [4.351, 50.851],
+        [4.35, 50.852],
+        [4.35, 50.851],
+    ];
+    outer.multipolygon_geojson = Some(vec![vec![ring(0.02), hole], vec![ring(0.001)]]);
+    let network = vec![vec![[4.35, 50.85], [4.36, 50.86]]];
+    let fc = IsochroneFeatureCollection::from_contours(
+        &[contour(300, 0.01, 100), outer],
+        Some(network),
+        600,
+    );
+    let json = serde_json::to_value(&fc).unwrap();
+    assert_eq!(json["type"], "FeatureCollection");
+    let features = json["features"].as_array().unwrap();
+    assert_eq!(features.len(), 3);
+    // Largest contour first, so nested smaller ones draw on top.
+    assert_eq!(features[0]["type"], "Feature");
+    assert_eq!(features[0]["geometry"]["type"], "MultiPolygon");
+    assert_eq!(features[0]["properties"]["threshold"], 600);
+    assert_eq!(features[1]["properties"]["threshold"], 300);
+    assert_eq!(features[1]["properties"]["reachable_edges"], 100);
+    assert!(features[1]["properties"].get("network").is_none());
+    // MultiPolygon > Polygon > ring > [lon, lat]
+    let ring = features[1]["geometry"]["coordinates"][0][0]
+        .as_array()
+        .unwrap();
+    assert_eq!(ring.len(), 4);
+    assert_eq!(ring[2][0], 4.35);
+    // Holes and disconnected parts come through as MultiPolygon rings.
+    let polygons = features[0]["geometry"]["coordinates"].as_array().unwrap();
+    assert_eq!(polygons.len(), 2);
+    assert_eq!(polygons[0].as_array().unwrap().len(), 2);
+    assert_eq!(features[2]["geometry"]["type"], "MultiLineString");
+    assert_eq!(features[2]["properties"]["network"], true);
+}
+
+#[test]
+fn test_bulk_isochrone_geojson_seq_record() {
+    use super::geometry::Point;
+    use super::isochrone_handler::BulkIsochroneFormat;
+    assert_eq!(
+        BulkIsochroneFormat::parse(None),
+        Ok(BulkIsochroneFormat::Wkb)
+    );
+    let format = BulkIsochroneFormat::parse(Some("geojson-seq")).unwrap();
+    assert_eq!(format, BulkIsochroneFormat::GeoJsonSeq);
+    assert!(BulkIsochroneFormat::parse(Some("shapefile")).is_err());
+
+    let p = |lon, lat| Point { lon, lat };
+    let polygons = vec![vec![vec![p(4.35, 50.85), p(4.36, 50.85), p(4.35, 50.86)]]];
+    let line = format.encode(7, 600, 42, &polygons).unwrap();
+    assert_eq!(line.last(), Some(&b'\n'));
+    assert_eq!(line.iter().filter(|&&b| b == b'\n').count(), 1);
+    let feature: serde_json::Value = serde_json::from_slice(&line).unwrap();
+    assert_eq!(feature["type"], "Feature");
+    assert_eq!(feature["geometry"]["type"], "MultiPolygon");
+    assert_eq!(feature["properties"]["origin_index"], 7);
+    assert_eq!(feature["properties"]["threshold"], 600);
+    assert_eq!(feature["properties"]["reachable_edges"], 42);
+    // Empty isochrones are left out, as in the WKB stream.
+    assert!(format.encode(0, 600, 0, &[]).is_none());
+
+    let record = BulkIsochroneFormat::Wkb
+        .encode(7, 600, 42, &polygons)
+        .unwrap();
+    assert_eq!(&record[..4], &7u32.to_le_bytes());
+    assert_eq!(
+        record.len(),
+        8 + u32::from_le_bytes(record[4..8].try_into().unwrap()) as usize
+    );
+}
+
 #[test]
 fn test_isochrone_request_deser_time_s() {
     use super::isochrone_handler::IsochroneRequest;
@@ -968,6 +1121,133 @@ fn test_isochrone_request_deser_time_s() {
     assert!(req.contours.is_none());
 }
 
+#[test]
+fn test_isochrone_origins_union() {
+    use super::isochrone_handler::{IsochroneRequest, MAX_ISOCHRONE_ORIGINS, isochrone_origins};
+    let parse = |query: &str| -> Result<Vec<[f64; 2]>, String> {
+        let req: IsochroneRequest =
+            serde_json::from_str(&format!(r#"{{"time_s":600,"mode":"car",{}}}"#, query)).unwrap();
+        isochrone_origins(&req)
+    };
+    assert_eq!(parse(r#""lon":4.35,"lat":50.85"#), Ok(vec![[4.35, 50.85]]));
+    assert_eq!(
+        parse(r#""origins":"4.35,50.85; 4.40,50.80""#),
+        Ok(vec![[4.35, 50.85], [4.40, 50.80]])
+    );
+    assert!(parse(r#""lon":4.35,"lat":50.85,"origins":"4.35,50.85""#).is_err());
+    assert!(parse(r#""lon":4.35"#).is_err());
+    assert!(parse(r#""include":"network""#).is_err());
+    assert!(
+        parse(r#""origins":"4.35,50.85;4.35""#)
+            .unwrap_err()
+            .starts_with("origin 1")
+    );
+    assert!(parse(r#""origins":"4.35,95.0""#).is_err());
+    let many = vec!["4.35,50.85"; MAX_ISOCHRONE_ORIGINS + 1].join(";");
+    assert!(parse(&format!(r#""origins":"{}""#, many)).is_err());
+}
+
+#[test]
+fn test_isochrone_generalization_params() {
+    use super::isochrone_handler::{IsochroneRequest, isochrone_generalization};
+    let parse = |query: &str| {
+        let json = format!(
+            r#"{{"lon":4.35,"lat":50.85,"time_s":600,"mode":"car"{}}}"#,
+            query
+        );
+        let req: IsochroneRequest = serde_json::from_str(&json).unwrap();
+        isochrone_generalization(&req)
+    };
+    let default = parse("").unwrap();
+    assert_eq!(default, crate::range::ContourGeneralization::default());
+    let g = parse(r#","simplify":0,"buffer":150,"smoothing":60.5"#).unwrap();
+    assert_eq!(g.simplify_m, Some(0.0));
+    assert_eq!(g.buffer_m, 150.0);
+    assert_eq!(g.smoothing_m, 60.5);
+    assert!(
+        parse(r#","buffer":-10"#)
+            .unwrap_err()
+            .starts_with("buffer must be")
+    );
+    assert!(parse(r#","simplify":5000"#).is_err());
+}
+
+#[test]
+fn test_match_trace_options_validation() {
+    use super::matching::{MatchRequest, trace_options};
+    let parse = |extra: &str| {
+        let json = format!(
+            r#"{{"points":[[4.35,50.85],[4.36,50.85],[4.37,50.85]]{}}}"#,
+            extra
+        );
+        let req: MatchRequest = serde_json::from_str(&json).unwrap();
+        trace_options(&req)
+    };
+    let o = parse(r#","gps_accuracy":15,"radiuses":[5,null,20],"timestamps":[0,5,5]"#).unwrap();
+    assert_eq!(o.gps_accuracy, Some(15.0));
+    assert_eq!(o.radiuses, Some(vec![Some(5.0), None, Some(20.0)]));
+    assert_eq!(o.timestamps, Some(vec![0, 5, 5]));
+    assert!(parse("").unwrap().timestamps.is_none());
+
+    assert!(parse(r#","radiuses":[5,5]"#).is_err());
+    assert!(
+        parse(r#","radiuses":[5,0,5]"#)
+            .unwrap_err()
+            .starts_with("radiuses[1]")
+    );
+    assert!(parse(r#","timestamps":[0,5]"#).is_err());
+    assert!(
+        parse(r#","timestamps":[0,10,5]"#)
+            .unwrap_err()
+            .contains("non-decreasing")
+    );
+    assert!(parse(r#","gps_accuracy":150"#).is_err());
+}
+
+#[test]
+fn test_trip_ends_validation() {
+    use super::trip::{TripEnds, TripRequest, trip_ends};
+    let parse = |extra: &str| {
+        let json = format!(r#"{{"points":[[4.35,50.85],[4.36,50.85]]{}}}"#, extra);
+        let req: TripRequest = serde_json::from_str(&json).unwrap();
+        (req.round_trip, trip_ends(&req))
+    };
+    assert_eq!(
+        parse(""),
+        (
+            true,
+            Ok(TripEnds {
+                first: true,
+                last: false
+            })
+        )
+    );
+    assert_eq!(
+        parse(r#","roundtrip":false,"source":"first","destination":"last""#),
+        (
+            false,
+            Ok(TripEnds {
+                first: true,
+                last: true
+            })
+        )
+    );
+    assert_eq!(
+        parse(r#","source":"any","destination":"any""#).1,
+        Ok(TripEnds {
+            first: false,
+            last: false
+        })
+    );
+    assert!(
+        parse(r#","source":"last""#)
+            .1
+            .unwrap_err()
+            .starts_with("Invalid source")
+    );
+    assert!(parse(r#","destination":"first""#).1.is_err());
+}
+
 #[test]
 fn test_isochrone_request_deser_contours() {
     use super::isochrone_handler::IsochroneRequest;
@@ -996,12 +1276,16 @@ fn test_route_annotations_serialization() {
         distance: Some(vec![100.0, 200.0, 300.0]),
         speed: None,
         nodes: None,
+        ferry: Some(vec![false, true, false]),
+        ways: Some(vec![4242, 4242, 17]),
     };
     let json = serde_json::to_value(&ann).unwrap();
     assert!(json["duration"].is_array());
     assert!(json["distance"].is_array());
     assert!(json.get("speed").is_none());
     assert!(json.get("nodes").is_none());
+    assert_eq!(json["ferry"][1], true);
+    assert_eq!(json["ways"][2], 17);
 
     let durations = json["duration"].as_array().unwrap();
     assert_eq!(durations.len(), 3);
@@ -1028,13 +1312,13 @@ fn test_route_annotations_speed_zero_duration() {
 
 #[test]
 fn test_annotations_validation_tokens() {
-    let valid_tokens = ["duration", "distance", "speed", "nodes"];
+    let valid_tokens = ["duration", "distance", "speed", "nodes", "ferry"];
     for t in &valid_tokens {
-        assert!(["duration", "distance", "speed", "nodes"].contains(t));
+        assert!(["duration", "distance", "speed", "nodes", "ferry"].contains(t));
     }
     let invalid_tokens = ["weight", "cost", "time", "edge_id", ""];
     for t in &invalid_tokens {
-        assert!(!["duration", "distance", "speed", "nodes"].contains(t));
+        assert!(!["duration", "distance", "speed", "nodes", "ferry"].contains(t));
     }
 }
 
@@ -1063,9 +1347,18 @@ fn test_route_response_with_annotations() {
             distance: Some(vec![250.0, 250.0]),
             speed: Some(vec![30.0, 30.0]),
             nodes: Some(vec![100, 200]),
+            ferry: None,
+            ways: None,
         }),
         alternatives: None,
         debug: None,
+        legs: None,
+        waypoints: None,
+        elevation: None,
+        depart_at: None,
+        arrive_at: None,
+        arrival_ebg: None,
+        closed_on_route: Vec::new(),
         duration_q25_s: None,
         duration_q75_s: None,
     };
@@ -1155,6 +1448,7 @@ fn test_isochrone_response_single_contour_still_array() {
             time_s: Some(600),
             polygon: Some("encoded".to_string()),
             polygon_geojson: None,
+            multipolygon_geojson: None,
             polygon_points: None,
             band: None,
             reachable_edges: 100,
@@ -22,7 +22,7 @@ use parking_lot::RwLock;
 
 use super::exclude::{self, ExcludeWeights};
 use super::snap_index::PackedSnapIndex;
-use super::state::{ModeData, ServerState};
+use super::state::{CchWeights, ModeData, ServerState};
 
 /// Default LRU capacity. Each full entry is ~100-200 MB on Belgium, so 8
 /// entries cap memory at ~1.6 GB. Override at boot via the
@@ -579,23 +579,34 @@ pub fn build_avoid_mask(
         .collect()
 }
 
-/// Parse avoid polygons and find avoided edges (shared helper).
+/// Parse avoid polygons and find avoided edges (shared helper). The
+/// `closed` EBG edges of a timed route are avoided too.
 ///
 /// Returns (avoid_flags, polygon_count, avoided_edge_count).
 fn prepare_avoid_flags(
     state: &ServerState,
-    avoid_json: &str,
+    avoid_json: Option<&str>,
+    closed: &[u32],
     exclude_mask: Option<u8>,
 ) -> Result<(Vec<u8>, usize, usize), String> {
-    let polygons = parse_avoid_polygons(avoid_json)?;
-
     let n_edges = state.ebg_nodes.n_nodes as usize;
-    let mut avoid_flags = find_avoided_edges(&state.snap_index, &polygons, n_edges);
-
-    let avoided_count = avoid_flags.iter().filter(|&&f| f != 0).count();
-    if avoided_count == 0 {
-        return Err("no edges found inside avoid polygon(s)".to_string());
+    let (mut avoid_flags, poly_count) = match avoid_json {
+        Some(json) => {
+            let polygons = parse_avoid_polygons(json)?;
+            let flags = find_avoided_edges(&state.snap_index, &polygons, n_edges);
+            if !flags.iter().any(|&f| f != 0) {
+                return Err("no edges found inside avoid polygon(s)".to_string());
+            }
+            (flags, polygons.len())
+        }
+        None => (vec![0u8; n_edges], 0),
+    };
+    for &edge in closed {
+        if let Some(flag) = avoid_flags.get_mut(edge as usize) {
+            *flag |= AVOID_BIT;
+        }
     }
+    let avoided_count = avoid_flags.iter().filter(|&&f| f != 0).count();
 
     // Merge with exclude flags if both are specified
     if let Some(exc_mask) = exclude_mask {
@@ -606,7 +617,6 @@ fn prepare_avoid_flags(
         }
     }
 
-    let poly_count = polygons.len();
     Ok((avoid_flags, poly_count, avoided_count))
 }
 
@@ -645,7 +655,7 @@ fn get_or_compute_avoid_entry(
 
     let start = std::time::Instant::now();
     let (avoid_flags, poly_count, avoided_count) =
-        prepare_avoid_flags(state, avoid_json, exclude_mask)?;
+        prepare_avoid_flags(state, Some(avoid_json), &[], exclude_mask)?;
     let weights = exclude::compute_exclude_weights(
         &mode_data.cch_topo,
         &mode_data.cch_weights,
@@ -699,6 +709,51 @@ pub fn compute_avoid_weights_time_only(
     compute_avoid_weights(state, mode_data, avoid_json, exclude_mask)
 }
 
+/// Time-only avoid weights of a `depart_at` / `arrive_by` /route that
+/// blocks `closed`: the EBG edges an earlier attempt found closed when it
+/// reached them (see [`super::conditional`]).
+pub struct ClosureWeights {
+    pub time_weights: CchWeights,
+    pub flags: Vec<u8>,
+}
+
+/// Block `closed` on top of the optional polygons and exclude mask.
+///
+/// Not cached: the closed set depends on when one route reaches each
+/// edge, so it rarely repeats, and a full entry is far too large to keep
+/// per set. The time-only incremental recustomization (#240) only walks
+/// the shortcuts above the blocked edges, so a handful of closures costs
+/// about what a small avoid polygon does.
+pub fn compute_closure_weights(
+    state: &ServerState,
+    mode_data: &ModeData,
+    avoid_json: Option<&str>,
+    closed: &[u32],
+    exclude_mask: Option<u8>,
+) -> Result<ClosureWeights, String> {
+    let start = std::time::Instant::now();
+    let (flags, poly_count, avoided_count) =
+        prepare_avoid_flags(state, avoid_json, closed, exclude_mask)?;
+    let time_weights = exclude::compute_exclude_weights_time_only(
+        &mode_data.cch_topo,
+        &mode_data.cch_weights,
+        &flags,
+        AVOID_BIT,
+        &mode_data.filtered_to_original,
+    );
+    tracing::info!(
+        polygons = poly_count,
+        closed = closed.len(),
+        avoided_edges = avoided_count,
+        elapsed_ms = start.elapsed().as_millis(),
+        "computed closure weights (time-only, uncached)"
+    );
+    Ok(ClosureWeights {
+        time_weights,
+        flags,
+    })
+}
+
 /// Look up the mode index by comparing the `ModeData` pointer against
 /// the state's mode list. Avoids threading an explicit index through
 /// the existing call sites.
@@ -0,0 +1,274 @@
+//! Time-dependent way restrictions on the serve path (`depart_at` /
+//! `arrive_by` on /route)
+//!
+//! Step 2 writes each mode's `*:conditional` schedules to
+//! `way_conditionals.<mode>.bin` (see [`crate::model::conditional`]). At
+//! boot they are resolved to the directed EBG edges they close: a
+//! conditional deny closes both directions of the way, a conditional
+//! oneway closes the direction against it.
+//!
+//! A timed route is checked edge by edge: each edge is tested at the time
+//! the route enters it, counted forward from the departure. An `arrive_by`
+//! route departs its duration before the arrival, so it is timed backwards
+//! from there. Edges found closed are blocked for the rest of the request
+//! and the route is recomputed (see `timed_route` in `route.rs`), so a
+//! route that could have used such an edge earlier or later is not found.
+//!
+//! Schedules are weekly wall-clock times; they are read in the UTC offset
+//! the request carries. Conditional allows are not served: a way the
+//! static tags deny has no weight in the mode's graph to restore.
+//! Conditional turn restrictions are not evaluated either (the turn
+//! table's `has_time_dep` is informational): a relation with a plain
+//! `restriction` bans its turn at all times even when a `*:conditional`
+//! tag lifts it, and one tagged only `restriction:conditional` is ignored.
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, SecondsFormat, Timelike};
+use std::collections::HashMap;
+
+use crate::formats::{EbgNode, EbgNodes, NbgGeo, WayConditional};
+use crate::model::conditional::{ConditionalKind, WeekSchedule};
+
+/// Directed EBG edges of one mode that close during a weekly schedule.
+#[derive(Debug, Default)]
+pub struct EdgeConditionals {
+    /// (EBG edge, when it is closed), ascending by edge.
+    closures: Vec<(u32, WeekSchedule)>,
+}
+
+impl EdgeConditionals {
+    /// Resolve a mode's way conditionals to the EBG edges of those ways.
+    pub fn build(ebg_nodes: &EbgNodes, nbg_geo: &NbgGeo, conditionals: &[WayConditional]) -> Self {
+        let mut by_way: HashMap<i64, Vec<&WayConditional>> = HashMap::new();
+        for c in conditionals {
+            by_way.entry(c.way_id).or_default().push(c);
+        }
+        let mut closures = Vec::new();
+        if by_way.is_empty() {
+            return Self { closures };
+        }
+        for (id, node) in ebg_nodes.nodes.iter().enumerate() {
+            let id = id as u32;
+            let Some(edge) = nbg_geo.edges.get(node.geom_idx as usize) else {
+                continue;
+            };
+            let Some(way_conditionals) = by_way.get(&edge.first_osm_way_id) else {
+                continue;
+            };
+            let forward = EbgNode::is_forward(id);
+            let mut closed = WeekSchedule::default();
+            for c in way_conditionals {
+                let closes = match c.kind {
+                    ConditionalKind::Deny => true,
+                    ConditionalKind::OnewayFwd => !forward,
+                    ConditionalKind::OnewayRev => forward,
+                    ConditionalKind::Allow => false,
+                };
+                if closes {
+                    closed.union(&c.schedule);
+                }
+            }
+            if !closed.is_empty() {
+                closures.push((id, closed));
+            }
+        }
+        Self { closures }
+    }
+
+    /// Number of edges with a closing schedule.
+    pub fn len(&self) -> usize {
+        self.closures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.closures.is_empty()
+    }
+
+    /// Edges of a route that are closed at the time the route enters them,
+    /// in route order. `route` lists the route's directed edges with their
+    /// travel cost in any unit; the trip's `duration_s` is spread over them
+    /// in proportion, starting from the departure `travel_time` implies.
+    pub fn closed_along(
+        &self,
+        travel_time: &TravelTime,
+        duration_s: f64,
+        route: &[(u32, f64)],
+    ) -> Vec<u32> {
+        if self.closures.is_empty() {
+            return Vec::new();
+        }
+        let (depart, _) = travel_time.times(duration_s);
+        let total: f64 = route.iter().map(|&(_, cost)| cost).sum();
+        let scale = if total > 0.0 { duration_s / total } else { 0.0 };
+        let mut elapsed = 0.0f64;
+        let mut closed = Vec::new();
+        for &(edge, cost) in route {
+            if let Ok(i) = self.closures.binary_search_by_key(&edge, |&(e, _)| e) {
+                let at = depart + Duration::milliseconds((elapsed * 1000.0).round() as i64);
+                let weekday = at.weekday().num_days_from_monday();
+                let minute = at.hour() * 60 + at.minute();
+                if self.closures[i].1.contains(weekday, minute) {
+                    closed.push(edge);
+                }
+            }
+            elapsed += cost * scale;
+        }
+        closed
+    }
+}
+
+/// When a /route trip happens: `depart_at` or `arrive_by`, RFC 3339.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TravelTime {
+    /// Leave at this time; edges are timed forward from it.
+    DepartAt(DateTime<FixedOffset>),
+    /// Arrive by this time; the departure is worked back from the route's
+    /// duration and edges are timed forward from that.
+    ArriveBy(DateTime<FixedOffset>),
+}
+
+impl TravelTime {
+    /// Parse the two optional parameters; giving both is ambiguous.
+    pub fn parse(depart_at: Option<&str>, arrive_by: Option<&str>) -> Result<Option<Self>, String> {
+        let parse = |name: &str, value: &str| {
+            DateTime::parse_from_rfc3339(value.trim()).map_err(|e| {
+                format!(
+                    "Invalid {} '{}': {} (expected RFC 3339, e.g. 2026-03-02T08:30:00+01:00)",
+                    name, value, e
+                )
+            })
+        };
+        match (depart_at, arrive_by) {
+            (Some(_), Some(_)) => Err("depart_at and arrive_by are mutually exclusive".to_string()),
+            (Some(d), None) => Ok(Some(Self::DepartAt(parse("depart_at", d)?))),
+            (None, Some(a)) => Ok(Some(Self::ArriveBy(parse("arrive_by", a)?))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Effective (departure, arrival) of a trip lasting `duration_s`.
+    pub fn times(&self, duration_s: f64) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+        let duration = Duration::milliseconds((duration_s * 1000.0).round() as i64);
+        match *self {
+            Self::DepartAt(t) => (t, t + duration),
+            Self::ArriveBy(t) => (t - duration, t),
+        }
+    }
+}
+
+/// RFC 3339 with whole seconds, in the offset the request used.
+pub fn format_time(t: &DateTime<FixedOffset>) -> String {
+    t.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::conditional::parse_opening_hours;
+
+    #[test]
+    fn test_travel_time_parse_and_times() {
+        assert_eq!(TravelTime::parse(None, None), Ok(None));
+        assert!(TravelTime::parse(Some("2026-03-02T08:00:00Z"), Some("x")).is_err());
+        assert!(TravelTime::parse(Some("monday 8am"), None).is_err());
+
+        let depart = TravelTime::parse(Some("2026-03-02T08:00:00+01:00"), None)
+            .unwrap()
+            .unwrap();
+        let (from, to) = depart.times(90.4);
+        assert_eq!(format_time(&from), "2026-03-02T08:00:00+01:00");
+        assert_eq!(format_time(&to), "2026-03-02T08:01:30+01:00");
+
+        let arrive = TravelTime::parse(None, Some("2026-03-02T09:00:00+01:00"))
+            .unwrap()
+            .unwrap();
+        let (from, to) = arrive.times(3600.0);
+        assert_eq!(format_time(&from), "2026-03-02T08:00:00+01:00");
+        assert_eq!(format_time(&to), "2026-03-02T09:00:00+01:00");
+    }
+
+    #[test]
+    fn test_closed_along_follows_schedule_direction_and_timing() {
+        use crate::formats::{ArcCow, NbgEdge};
+        // NBG edge 0 is way 7, edge 1 is way 8: EBG 0/1 and 2/3 are their
+        // forward/backward twins.
+        let edge = |way_id| NbgEdge {
+            u_node: 0,
+            v_node: 1,
+            length_mm: 1000,
+            bearing_deci_deg: 0,
+            n_poly_pts: 0,
+            poly_off: 0,
+            first_osm_way_id: way_id,
+            flags: 0,
+        };
+        let nbg_geo = NbgGeo {
+            n_edges_und: 2,
+            edges: vec![edge(7), edge(8)],
+            polylines: Vec::new(),
+        };
+        let node = |geom_idx| EbgNode {
+            tail_nbg: 0,
+            head_nbg: 1,
+            geom_idx,
+            length_m: 1,
+            class_bits: 0,
+            primary_way: 0,
+        };
+        let ebg_nodes = EbgNodes {
+            n_nodes: 4,
+            created_unix: 0,
+            inputs_sha: [0u8; 32],
+            nodes: ArcCow::from_vec(vec![node(0), node(0), node(1), node(1)]),
+        };
+        let rush_hour = parse_opening_hours("Mo-Fr 07:00-09:00").unwrap();
+        let closures = EdgeConditionals::build(
+            &ebg_nodes,
+            &nbg_geo,
+            &[
+                WayConditional {
+                    way_id: 7,
+                    kind: ConditionalKind::Deny,
+                    schedule: rush_hour,
+                },
+                WayConditional {
+                    way_id: 8,
+                    kind: ConditionalKind::OnewayFwd,
+                    schedule: rush_hour,
+                },
+                WayConditional {
+                    way_id: 8,
+                    kind: ConditionalKind::Allow,
+                    schedule: WeekSchedule::always(),
+                },
+            ],
+        );
+        assert_eq!(closures.len(), 3);
+
+        // Monday 2 March 2026. A zero-length trip enters every edge at
+        // its departure.
+        let at = |s| DateTime::parse_from_rfc3339(s).unwrap();
+        let all = [(0, 1.0), (1, 1.0), (2, 1.0), (3, 1.0)];
+        let closed_at = |s| closures.closed_along(&TravelTime::DepartAt(at(s)), 0.0, &all);
+        assert_eq!(closed_at("2026-03-02T08:15:00+01:00"), vec![0, 1, 3]);
+        assert!(closed_at("2026-03-02T22:00:00+01:00").is_empty());
+        assert!(closed_at("2026-03-07T08:15:00+01:00").is_empty());
+        // Schedules are wall-clock times of the offset given: 07:15-05:00
+        // is rush hour even though it is 13:15 at +01:00.
+        assert_eq!(closed_at("2026-03-02T07:15:00-05:00"), vec![0, 1, 3]);
+
+        // An hour-long trip over edge 0 then edge 3, half the cost each:
+        // edge 3 is entered 30 minutes after departure.
+        let route = [(0, 40.0), (3, 40.0)];
+        let timed = |t| closures.closed_along(&t, 3600.0, &route);
+        let depart = |s| TravelTime::DepartAt(at(s));
+        let arrive = |s| TravelTime::ArriveBy(at(s));
+        assert_eq!(timed(depart("2026-03-02T06:45:00+01:00")), vec![3]);
+        assert!(timed(depart("2026-03-02T06:15:00+01:00")).is_empty());
+        assert_eq!(timed(depart("2026-03-02T08:45:00+01:00")), vec![0]);
+        // Arriving by 07:45 departs at 06:45; by 07:15 it enters edge 3
+        // at 06:45, before the closure starts.
+        assert_eq!(timed(arrive("2026-03-02T07:45:00+01:00")), vec![3]);
+        assert!(timed(arrive("2026-03-02T07:15:00+01:00")).is_empty());
+        assert_eq!(timed(arrive("2026-03-02T09:45:00+01:00")), vec![0]);
+    }
+}
@@ -1054,7 +1054,7 @@ fn test_nearest_in_ocean_returns_empty() {
 #[test]
 #[ignore] // Requires Belgium data
 fn test_route_steps_have_depart_and_arrive() {
-    use super::route::build_steps;
+    use super::route::{StepGuidance, build_steps};
 
     let state = load_state();
     let mode = lookup_mode(&state, "car");
@@ -1104,6 +1104,7 @@ fn test_route_steps_have_depart_and_arrive() {
             &state.edge_geom,
             &mode_data.node_weights,
             &state.way_names,
+            &StepGuidance::new(&state, &mode_data),
             super::geometry::GeometryFormat::Polyline6,
         );
 
@@ -1135,6 +1136,9 @@ fn test_route_steps_have_depart_and_arrive() {
             "turn",
             "continue",
             "roundabout",
+            "exit roundabout",
+            "ferry",
+            "exit ferry",
             "fork",
             "merge",
         ];
@@ -1172,7 +1176,7 @@ fn test_route_steps_have_depart_and_arrive() {
 #[test]
 #[ignore] // Requires Belgium data
 fn test_route_steps_distances_sum_to_total() {
-    use super::route::build_steps;
+    use super::route::{StepGuidance, build_steps};
 
     let state = load_state();
     let mode = lookup_mode(&state, "car");
@@ -1222,6 +1226,7 @@ fn test_route_steps_distances_sum_to_total() {
             &state.edge_geom,
             &mode_data.node_weights,
             &state.way_names,
+            &StepGuidance::new(&state, &mode_data),
             super::geometry::GeometryFormat::Polyline6,
         );
 
@@ -1255,7 +1260,7 @@ fn test_route_steps_distances_sum_to_total() {
 #[test]
 #[ignore] // Requires Belgium data
 fn test_route_step_locations_on_route() {
-    use super::route::build_steps;
+    use super::route::{StepGuidance, build_steps};
 
     let state = load_state();
     let mode = lookup_mode(&state, "car");
@@ -1292,6 +1297,7 @@ fn test_route_step_locations_on_route() {
         &state.edge_geom,
         &mode_data.node_weights,
         &state.way_names,
+        &StepGuidance::new(&state, &mode_data),
         super::geometry::GeometryFormat::Polyline6,
     );
 
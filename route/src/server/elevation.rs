@@ -464,8 +464,64 @@ impl ElevationData {
         }
         true
     }
+
+    /// Elevation summary of a route polyline (`[lat, lon]` waypoints) for
+    /// `/route?elevation=true`.
+    ///
+    /// The route is sampled with [`elevation_profile`](Self::elevation_profile)
+    /// every [`ROUTE_SAMPLE_INTERVAL_M`] (coarser on very long routes), the
+    /// ascent and descent are summed over those samples, and the profile
+    /// is then thinned to at most `max_points` evenly spaced samples.
+    /// Returns `None` if no sample falls on loaded tiles.
+    pub fn route_elevation(&self, path: &[[f64; 2]], max_points: usize) -> Option<RouteElevation> {
+        let length: f64 = path
+            .windows(2)
+            .map(|w| haversine_distance(w[0][0], w[0][1], w[1][0], w[1][1]))
+            .sum();
+        let interval = (length / ROUTE_MAX_SAMPLES).max(ROUTE_SAMPLE_INTERVAL_M);
+        let samples = self.elevation_profile(path, interval);
+        if samples.is_empty() {
+            return None;
+        }
+
+        let (mut ascent, mut descent) = (0.0, 0.0);
+        for pair in samples.windows(2) {
+            let delta = pair[1].elevation - pair[0].elevation;
+            if delta > 0.0 {
+                ascent += delta;
+            } else {
+                descent -= delta;
+            }
+        }
+
+        let n = samples.len();
+        let keep = max_points.max(2);
+        let round = |v: f64| (v * 10.0).round() / 10.0;
+        let profile = if n <= keep {
+            samples.iter().collect::<Vec<_>>()
+        } else {
+            (0..keep)
+                .map(|i| &samples[i * (n - 1) / (keep - 1)])
+                .collect()
+        };
+        Some(RouteElevation {
+            ascent_m: round(ascent),
+            descent_m: round(descent),
+            profile: profile
+                .into_iter()
+                .map(|p| [round(p.distance_m), round(p.elevation)])
+                .collect(),
+        })
+    }
 }
 
+/// Sampling step along a route before thinning, in meters (SRTM1 posting).
+pub const ROUTE_SAMPLE_INTERVAL_M: f64 = 30.0;
+
+/// Upper bound on samples taken along one route; longer routes sample
+/// coarser than [`ROUTE_SAMPLE_INTERVAL_M`].
+const ROUTE_MAX_SAMPLES: f64 = 20_000.0;
+
 /// A point along an elevation profile.
 #[derive(Debug, Clone)]
 pub struct ElevationPoint {
@@ -515,6 +571,22 @@ pub struct HeightResult {
     pub elevation: Option<f64>,
 }
 
+/// Elevation summary of a route (`elevation=true` on /route).
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteElevation {
+    /// Total climb along the route in meters
+    #[schema(example = 182.4)]
+    pub ascent_m: f64,
+    /// Total drop along the route in meters
+    #[schema(example = 171.9)]
+    pub descent_m: f64,
+    /// `[distance_m, elevation_m]` pairs from start to end, distance being
+    /// measured along the route geometry. Samples without SRTM coverage
+    /// are left out.
+    #[schema(value_type = Vec<Vec<f64>>)]
+    pub profile: Vec<[f64; 2]>,
+}
+
 /// Parse a coordinate string in "lon,lat|lon,lat|..." format.
 ///
 /// Returns a vec of (lon, lat) pairs, or an error string describing the problem.
@@ -967,6 +1039,31 @@ mod tests {
         assert!((single[0].elevation - 500.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_route_elevation() {
+        let elev = ElevationData::from_tiles(vec![make_3x3_tile(50, 4)]);
+
+        // South to north along the western edge: 700 -> 400 -> 100, then
+        // back down to the center (500).
+        let path = [[50.0, 4.0], [51.0, 4.0], [50.5, 4.5]];
+        let summary = elev.route_elevation(&path, 10).unwrap();
+        assert_eq!(summary.profile.len(), 10);
+        assert_eq!(summary.profile[0], [0.0, 700.0]);
+        assert_eq!(summary.profile.last().unwrap()[1], 500.0);
+        assert!((summary.descent_m - 600.0).abs() < 1.0);
+        assert!((summary.ascent_m - 400.0).abs() < 1.0);
+        assert!(
+            summary.profile.windows(2).all(|w| w[1][0] > w[0][0]),
+            "profile distances must increase"
+        );
+
+        // Off the loaded tiles there is nothing to report.
+        assert!(
+            elev.route_elevation(&[[60.0, 10.0], [60.1, 10.1]], 10)
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_has_coverage() {
         let tile1 = make_3x3_tile(50, 4);
@@ -166,11 +166,11 @@ impl Default for ExcludeWeightCache {
 }
 
 /// Parse exclude parameter string into bitmask.
-/// Accepts comma-separated tokens: toll, ferry, motorway.
+/// Accepts comma- or pipe-separated tokens: toll, ferry, motorway.
 /// Returns 0 for empty/whitespace-only input.
 pub fn parse_exclude(s: &str) -> Result<u8, String> {
     let mut mask = 0u8;
-    for token in s.split(',') {
+    for token in s.split([',', '|']) {
         let token = token.trim();
         if token.is_empty() {
             continue;
@@ -895,6 +895,13 @@ mod tests {
         assert_eq!(mask, EXCLUDE_TOLL | EXCLUDE_FERRY | EXCLUDE_MOTORWAY);
     }
 
+    #[test]
+    fn test_parse_exclude_pipe_separated() {
+        let mask = parse_exclude("toll|motorway").unwrap();
+        assert_eq!(mask, EXCLUDE_TOLL | EXCLUDE_MOTORWAY);
+        assert_eq!(parse_exclude("ferry|toll,motorway").unwrap(), 7);
+    }
+
     #[test]
     fn test_parse_exclude_case_insensitive() {
         assert_eq!(parse_exclude("Toll").unwrap(), EXCLUDE_TOLL);
@@ -6,6 +6,10 @@
 //! - `isochrone` — reachability polygons as WKB per interval
 //!
 //! Ticket format: `action:profile:params_json`
+//!
+//! `GetFlightInfo` on a full `matrix` ticket plans the matrix as one
+//! endpoint per block of source rows (`source_range`), so clients can
+//! fetch the blocks in parallel.
 
 // tonic::Status is 176 bytes — the canonical error type for gRPC services.
 // Boxing it would add indirection with zero benefit since every gRPC return type uses it.
@@ -23,7 +27,7 @@ use arrow::record_batch::RecordBatch;
 use arrow_flight::encode::FlightDataEncoderBuilder;
 use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
 use arrow_flight::{
-    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightInfo,
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
     HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaResult, Ticket,
 };
 use rayon::prelude::*;
@@ -412,6 +416,97 @@ struct MatrixParams {
     /// dropped.
     #[serde(default)]
     max_minutes: Option<f64>,
+    /// Half-open block `[start, end)` of `origins` to compute. Rows keep
+    /// their `source_idx` in the full `origins` list, so the blocks of one
+    /// matrix can be fetched in parallel and concatenated. `radius_km:
+    /// "auto"` is derived from the block's own sources.
+    #[serde(default)]
+    source_range: Option<[usize; 2]>,
+}
+
+/// Bucket-M2M handles up to ~1M cells comfortably (4 MB matrix + a
+/// few MB of bucket scratch). The pre-#386 threshold of 50_000
+/// bounced 250×250+ matrices into the slow PHAST tiled streaming
+/// path, which made apples-to-apples Flight bench against libosrm
+/// look ~5× worse than reality. Above 1M cells, the streamed PHAST
+/// path still wins on memory.
+const BUCKET_M2M_THRESHOLD: usize = 1_000_000;
+
+/// Narrow `params.origins` to its `source_range` block and return the
+/// block's start, which the caller adds back to `source_idx`.
+fn restrict_to_source_range(params: &mut MatrixParams) -> std::result::Result<usize, Status> {
+    let Some([start, end]) = params.source_range else {
+        return Ok(0);
+    };
+    if start >= end || end > params.origins.len() {
+        return Err(Status::invalid_argument(format!(
+            "source_range [{}, {}) must be a non-empty block of the {} sources",
+            start,
+            end,
+            params.origins.len()
+        )));
+    }
+    params.origins.truncate(end);
+    params.origins.drain(..start);
+    Ok(start)
+}
+
+/// Shift `source_idx` of a matrix batch by the start of its block.
+fn shift_source_idx(batch: RecordBatch, offset: u32) -> std::result::Result<RecordBatch, Status> {
+    let source_idx = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<UInt32Array>()
+        .ok_or_else(|| Status::internal("source_idx is not UInt32"))?
+        .unary::<_, UInt32Type>(|i| i + offset);
+    let mut columns = batch.columns().to_vec();
+    columns[0] = Arc::new(source_idx);
+    RecordBatch::try_new(batch.schema(), columns)
+        .map_err(|e| Status::internal(format!("Arrow error: {}", e)))
+}
+
+/// Split a full matrix ticket into one DoGet ticket per block of source
+/// rows, each at most `max_cells` cells (at least one row). Returns the
+/// tickets in source order and the matrix's total row count.
+fn matrix_endpoint_tickets(
+    profile: &str,
+    params_json: &str,
+    max_cells: usize,
+) -> std::result::Result<(Vec<Ticket>, usize), Status> {
+    let invalid =
+        |e: serde_json::Error| Status::invalid_argument(format!("Invalid matrix params: {}", e));
+    let params: MatrixParams = serde_json::from_str(params_json).map_err(invalid)?;
+    if params.source_range.is_some() {
+        return Err(Status::invalid_argument(
+            "source_range is set per endpoint; plan the whole matrix",
+        ));
+    }
+    let (n_src, n_dst) = (params.origins.len(), params.destinations.len());
+    if n_src == 0 || n_dst == 0 {
+        return Err(Status::invalid_argument(
+            "sources and destinations must not be empty",
+        ));
+    }
+    let mut json: serde_json::Value = serde_json::from_str(params_json).map_err(invalid)?;
+    let Some(object) = json.as_object_mut() else {
+        return Err(Status::invalid_argument(
+            "matrix params must be a JSON object",
+        ));
+    };
+    let rows = (max_cells / n_dst).max(1);
+    let tickets = (0..n_src)
+        .step_by(rows)
+        .map(|start| {
+            let range = [start, (start + rows).min(n_src)];
+            object.insert("source_range".to_string(), serde_json::json!(range));
+            Ticket::new(format!(
+                "matrix:{}:{}",
+                profile,
+                serde_json::Value::Object(object.clone())
+            ))
+        })
+        .collect();
+    Ok((tickets, n_src * n_dst))
 }
 
 /// Build matrix RecordBatch from flat u32 distances.
@@ -616,14 +711,6 @@ fn do_matrix(
     let n_valid_origin = origins_rank.len();
     let n_valid_dst = targets_rank.len();
 
-    // Bucket-M2M handles up to ~1M cells comfortably (4 MB matrix + a
-    // few MB of bucket scratch). The pre-#386 threshold of 50_000
-    // bounced 250×250+ matrices into the slow PHAST tiled streaming
-    // path, which made apples-to-apples Flight bench against libosrm
-    // look ~5× worse than reality. Above 1M cells, the streamed PHAST
-    // path still wins on memory.
-    const BUCKET_M2M_THRESHOLD: usize = 1_000_000;
-
     if n_origin * n_dst <= BUCKET_M2M_THRESHOLD {
         // ---- SMALL MATRIX: Bucket M2M, single batch ----
         let use_parallel = n_valid_origin * n_valid_dst >= 2500;
@@ -3819,7 +3906,7 @@ impl FlightService for ButterflyFlight {
         // the primary state until multi-region transit lands.
         match parsed.action.as_str() {
             "matrix" => {
-                let params: MatrixParams =
+                let mut params: MatrixParams =
                     serde_json::from_str(&parsed.params_json).map_err(|e| {
                         Status::invalid_argument(format!("Invalid matrix params: {}", e))
                     })?;
@@ -3835,6 +3922,7 @@ impl FlightService for ButterflyFlight {
                 for (i, [lon, lat]) in params.destinations.iter().enumerate() {
                     validate_coord(*lon, *lat, &format!("dest[{}]", i))?;
                 }
+                let source_offset = restrict_to_source_range(&mut params)?;
 
                 // Snap the first (source, destination) pair. If both
                 // sides snap to the same region we proceed; otherwise
@@ -3845,7 +3933,12 @@ impl FlightService for ButterflyFlight {
                     self.dispatch_for_pair(s_lon, s_lat, d_lon, d_lat, &parsed.profile)?;
                 let mode = resolve_mode(&parsed.profile, &state)?;
 
-                let batch_stream = do_matrix(&state, mode, params)?;
+                let mut batch_stream = do_matrix(&state, mode, params)?;
+                if source_offset > 0 {
+                    let offset = source_offset as u32;
+                    batch_stream =
+                        Box::pin(batch_stream.map(move |batch| shift_source_idx(batch?, offset)));
+                }
                 let schema = Arc::new(matrix_schema());
                 let flight_stream = batches_to_flight_data(schema, batch_stream);
                 Ok(Response::new(flight_stream))
@@ -3980,6 +4073,30 @@ impl FlightService for ButterflyFlight {
         let cmd = std::str::from_utf8(&descriptor.cmd)
             .map_err(|_| Status::invalid_argument("descriptor cmd must be UTF-8"))?;
 
+        // A full `matrix:<profile>:<params>` ticket is planned as one
+        // endpoint per block of source rows; a bare action name only
+        // describes the schema.
+        if cmd.starts_with("matrix:") {
+            let parsed = parse_ticket(&Ticket::new(descriptor.cmd.clone()))?;
+            let (tickets, total_records) = matrix_endpoint_tickets(
+                &parsed.profile,
+                &parsed.params_json,
+                BUCKET_M2M_THRESHOLD,
+            )?;
+            let info = FlightInfo::new()
+                .with_descriptor(descriptor)
+                .try_with_schema(&matrix_schema())
+                .map_err(|e| Status::internal(format!("Schema encoding error: {}", e)))?
+                .with_endpoints(
+                    tickets
+                        .into_iter()
+                        .map(|t| FlightEndpoint::new().with_ticket(t))
+                        .collect(),
+                )
+                .with_total_records(total_records as i64);
+            return Ok(Response::new(info));
+        }
+
         let schema = match cmd {
             "matrix" => matrix_schema(),
             "route_batch" => route_batch_schema(),
@@ -4048,7 +4165,7 @@ impl FlightService for ButterflyFlight {
         let actions = vec![
             ActionType {
                 r#type: "matrix".into(),
-                description: "Distance/duration matrix. Ticket: matrix:<profile>:{\"sources\":[[lon,lat],...],\"destinations\":[[lon,lat],...]}".into(),
+                description: "Distance/duration matrix. Ticket: matrix:<profile>:{\"origins\":[[lon,lat],...],\"destinations\":[[lon,lat],...], \"source_range\": optional [start,end)}. GetFlightInfo on a full matrix ticket returns one endpoint per block of source rows for parallel fetches.".into(),
             },
             ActionType {
                 r#type: "route_batch".into(),
@@ -4583,3 +4700,81 @@ mod route_batch_prune_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod matrix_range_tests {
+    use super::{
+        MatrixParams, build_matrix_batch, matrix_endpoint_tickets, matrix_schema, parse_ticket,
+        restrict_to_source_range, shift_source_idx,
+    };
+    use arrow::array::{Array, UInt32Array};
+    use std::sync::Arc;
+
+    /// GetFlightInfo plans a matrix as blocks of whole source rows within
+    /// the cell budget, each ticket carrying its `source_range`.
+    #[test]
+    fn matrix_tickets_split_source_rows() {
+        let json = r#"{"origins":[[4.0,50.0],[4.1,50.0],[4.2,50.0],[4.3,50.0],[4.4,50.0]],"destinations":[[4.0,51.0],[4.1,51.0]],"max_minutes":30}"#;
+        let (tickets, total) = matrix_endpoint_tickets("car", json, 4).expect("plan");
+        assert_eq!(total, 10);
+        let ranges: Vec<[usize; 2]> = tickets
+            .iter()
+            .map(|t| {
+                let parsed = parse_ticket(t).expect("ticket");
+                assert_eq!(
+                    (parsed.action.as_str(), parsed.profile.as_str()),
+                    ("matrix", "car")
+                );
+                let p: MatrixParams = serde_json::from_str(&parsed.params_json).expect("params");
+                assert_eq!((p.origins.len(), p.max_minutes), (5, Some(30.0)));
+                p.source_range.expect("source_range")
+            })
+            .collect();
+        assert_eq!(ranges, vec![[0, 2], [2, 4], [4, 5]]);
+
+        // More destinations than the budget still plans one row per block.
+        let (tickets, _) = matrix_endpoint_tickets("car", json, 1).expect("plan");
+        assert_eq!(tickets.len(), 5);
+
+        let ranged = r#"{"origins":[[4.0,50.0]],"destinations":[[4.0,51.0]],"source_range":[0,1]}"#;
+        assert!(matrix_endpoint_tickets("car", ranged, 4).is_err());
+    }
+
+    /// A `source_range` block computes only its sources and reports them
+    /// at their index in the full list.
+    #[test]
+    fn source_range_restricts_and_shifts_source_idx() {
+        let json = r#"{"origins":[[4.0,50.0],[4.1,50.0],[4.2,50.0]],"destinations":[[4.0,51.0]],"source_range":[1,3]}"#;
+        let mut p: MatrixParams = serde_json::from_str(json).expect("parse");
+        assert_eq!(restrict_to_source_range(&mut p).expect("range"), 1);
+        assert_eq!(p.origins, vec![[4.1, 50.0], [4.2, 50.0]]);
+
+        for range in ["[2,2]", "[1,4]"] {
+            let json = format!(
+                r#"{{"origins":[[4.0,50.0],[4.1,50.0],[4.2,50.0]],"destinations":[[4.0,51.0]],"source_range":{range}}}"#
+            );
+            let mut p: MatrixParams = serde_json::from_str(&json).expect("parse");
+            assert!(restrict_to_source_range(&mut p).is_err(), "{range}");
+        }
+
+        let batch = build_matrix_batch(
+            &[7, u32::MAX],
+            None,
+            2,
+            1,
+            &[0, 1],
+            &[0],
+            Arc::new(matrix_schema()),
+            None,
+        )
+        .expect("batch");
+        let shifted = shift_source_idx(batch, 1).expect("shift");
+        let source_idx = shifted
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .unwrap();
+        assert_eq!(source_idx.values(), &[1, 2]);
+        assert_eq!(shifted.schema().as_ref(), &matrix_schema());
+    }
+}
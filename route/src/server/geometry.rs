@@ -4,7 +4,9 @@ use serde::Serialize;
 use utoipa::ToSchema;
 
 use crate::formats::EbgNodes;
-use crate::range::{ReachableSegment, SparseContourConfig};
+use crate::range::{
+    ContourGeneralization, ReachableSegment, SparseContourConfig, SparseContourResult,
+};
 use crate::server::edge_geom::EdgeGeometry;
 
 /// A point in WGS84 coordinates
@@ -75,6 +77,21 @@ impl RouteGeometry {
             },
         }
     }
+
+    /// The geometry's coordinates as `[lat, lon]`, whichever format it is in
+    pub fn lat_lon(&self) -> Vec<[f64; 2]> {
+        if let Some(polyline) = &self.polyline {
+            decode_polyline6(polyline)
+                .into_iter()
+                .map(|(lat, lon)| [lat, lon])
+                .collect()
+        } else if let Some(coords) = &self.coordinates_geojson {
+            coords.iter().map(|&[lon, lat]| [lat, lon]).collect()
+        } else {
+            let points = self.coordinates.iter().flatten();
+            points.map(|p| [p.lat, p.lon]).collect()
+        }
+    }
 }
 
 /// Encode coordinates as Google Encoded Polyline with 6-digit precision
@@ -266,6 +283,46 @@ pub fn build_isochrone_geometry(
     result
 }
 
+/// Like [`build_isochrone_geometry`], but every polygon of the isochrone as
+/// `[outer, holes...]` rings: unreachable enclaves become holes and
+/// disconnected components their own polygons, the origin's first.
+/// `generalization` adjusts the mode's contour config for this request.
+#[allow(clippy::too_many_arguments)]
+pub fn build_isochrone_polygons(
+    settled_nodes: &[(u32, u32)],
+    max_threshold: u32,
+    node_weights: &[u32],
+    ebg_nodes: &EbgNodes,
+    edge_geom: &EdgeGeometry,
+    mode_name: &str,
+    origin_anchor: Option<(f64, f64)>,
+    generalization: &ContourGeneralization,
+) -> Vec<Vec<Vec<Point>>> {
+    let to_points = |ring: Vec<(f64, f64)>| -> Vec<Point> {
+        ring.into_iter()
+            .map(|(lon, lat)| Point { lon, lat })
+            .collect()
+    };
+    sparse_isochrone_contour(
+        settled_nodes,
+        max_threshold,
+        node_weights,
+        ebg_nodes,
+        edge_geom,
+        mode_name,
+        origin_anchor,
+        generalization,
+    )
+    .map(|result| {
+        result
+            .polygons()
+            .into_iter()
+            .map(|rings| rings.into_iter().map(to_points).collect())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
 /// Build isochrone geometry with mode-specific configuration
 ///
 /// Stamps reachable edges into a sparse tile grid, then traces the boundary.
@@ -287,7 +344,40 @@ pub fn build_isochrone_geometry_sparse(
     mode_name: &str,
     origin_anchor: Option<(f64, f64)>, // exact snapped (lon, lat); fallback = min-dist edge start
 ) -> Vec<Point> {
-    let config = SparseContourConfig::for_mode_name_with_threshold(mode_name, max_time_ds);
+    match sparse_isochrone_contour(
+        settled_nodes,
+        max_time_ds,
+        node_weights,
+        ebg_nodes,
+        edge_geom,
+        mode_name,
+        origin_anchor,
+        &ContourGeneralization::default(),
+    ) {
+        Some(result) => result
+            .outer_ring
+            .into_iter()
+            .map(|(lon, lat)| Point { lon, lat })
+            .collect(),
+        None => vec![],
+    }
+}
+
+/// Stamp the settled edges and trace their sparse contour (`None` when
+/// nothing is reachable).
+#[allow(clippy::too_many_arguments)]
+fn sparse_isochrone_contour(
+    settled_nodes: &[(u32, u32)],
+    max_time_ds: u32,
+    node_weights: &[u32],
+    ebg_nodes: &EbgNodes,
+    edge_geom: &EdgeGeometry,
+    mode_name: &str,
+    origin_anchor: Option<(f64, f64)>,
+    generalization: &ContourGeneralization,
+) -> Option<SparseContourResult> {
+    let config = SparseContourConfig::for_mode_name_with_threshold(mode_name, max_time_ds)
+        .generalized(generalization);
 
     // Stamp ALL reachable edges. Do NOT use near-frontier filtering — it creates
     // holes in the polygon when the frontier has gaps in some directions.
@@ -347,7 +437,7 @@ pub fn build_isochrone_geometry_sparse(
     }
 
     if segments.is_empty() {
-        return vec![];
+        return None;
     }
 
     // Generate contour using sparse tile rasterization + boundary tracing.
@@ -358,14 +448,7 @@ pub fn build_isochrone_geometry_sparse(
     let anchor = origin_anchor
         .map(|(lon, lat)| ((lat * 1e7) as i32, (lon * 1e7) as i32))
         .or(anchor);
-    match crate::range::generate_sparse_contour_anchored(&segments, &config, anchor) {
-        Ok(result) => result
-            .outer_ring
-            .into_iter()
-            .map(|(lon, lat)| Point { lon, lat })
-            .collect(),
-        Err(_) => vec![],
-    }
+    crate::range::generate_sparse_contour_anchored(&segments, &config, anchor).ok()
 }
 
 /// Extract partial polyline from start to given fraction (lat-first
@@ -416,8 +499,7 @@ fn extract_partial_polyline_view(
     points
 }
 
-/// Decode polyline6 back to coordinates (for testing round-trip)
-#[cfg(test)]
+/// Decode polyline6 back to `(lat, lon)` coordinates
 pub fn decode_polyline6(encoded: &str) -> Vec<(f64, f64)> {
     let mut result = Vec::new();
     let mut lat: i64 = 0;
@@ -144,6 +144,7 @@ pub async fn health_handler(State(regions): State<Arc<RegionsState>>) -> impl In
         "version": env!("CARGO_PKG_VERSION"),
         "uptime_s": uptime.as_secs(),
         "modes": primary_loaded.as_ref().map(|p| p.mode_names.clone()).unwrap_or_default(),
+        "weightings": primary_loaded.as_ref().map(|p| p.weightings()).unwrap_or_default(),
         "data_dir": primary_loaded.as_ref().map(|p| p.data_dir.clone()).unwrap_or_default(),
         "nodes_count": primary_loaded.as_ref().map(|p| p.ebg_nodes.n_nodes).unwrap_or(0),
         "edges_count": primary_loaded.as_ref().map(|p| p.ebg_csr.n_arcs).unwrap_or(0),
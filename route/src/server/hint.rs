@@ -0,0 +1,263 @@
+//! Snapping hints for repeated /route requests (`hints=` /
+//! `generate_hints=`)
+//!
+//! A hint is an opaque token for one waypoint: the snap candidates
+//! `(ebg_id, lon, lat, snap_distance_m)` the spatial index returned for its
+//! coordinate, closest first. Passing it back with the same coordinate
+//! skips the index lookup and seeds the route from those candidates.
+//!
+//! A hint only applies to the dataset, mode and input coordinate it was
+//! made for; anything else (a rebuilt graph, a moved waypoint) makes it
+//! stale and the waypoint is snapped as usual. Candidates are re-checked
+//! against the request's mode, role, exclude/avoid filter and radius, so a
+//! hint never seeds an edge the request could not have snapped to. It
+//! does not record `bearings`: change them and drop the hint.
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+use super::phantom::seed_valid;
+use super::state::{ModeData, ServerState};
+use super::types::SnapRole;
+use crate::profile_abi::Mode;
+
+/// Layout version, first byte of every hint.
+const HINT_VERSION: u8 = 1;
+
+/// Most candidates kept per hint (the phantom snap fetches 8).
+pub const MAX_HINT_CANDIDATES: usize = 8;
+
+/// Fixed-point scale of stored coordinates (1e-7 degree, ~1 cm).
+const COORD_SCALE: f64 = 1e7;
+
+/// Snap candidate as returned by the snap index.
+pub type SnapCandidate = (u32, f64, f64, f64);
+
+/// Decoded snapping hint of one waypoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapHint {
+    /// [`data_checksum`] of the dataset the hint was made on.
+    pub checksum: u32,
+    /// Mode slot (after weighting resolution) it was snapped for.
+    pub mode: u8,
+    /// Input coordinate, fixed-point `[lon, lat]`.
+    pub input: [i32; 2],
+    /// Snap candidates, closest first.
+    pub candidates: Vec<SnapCandidate>,
+}
+
+fn to_fixed(deg: f64) -> i32 {
+    (deg * COORD_SCALE).round() as i32
+}
+
+/// Identity of the loaded graph: hints from another build are stale.
+pub fn data_checksum(state: &ServerState) -> u32 {
+    let ebg = &state.ebg_nodes;
+    let mut d = crate::formats::crc::Digest::new();
+    d.update(b"snap-hint");
+    d.update(&ebg.inputs_sha);
+    d.update(&ebg.created_unix.to_le_bytes());
+    d.update(&ebg.n_nodes.to_le_bytes());
+    d.finalize() as u32
+}
+
+impl SnapHint {
+    pub fn new(
+        checksum: u32,
+        mode: Mode,
+        [lon, lat]: [f64; 2],
+        candidates: &[SnapCandidate],
+    ) -> Self {
+        Self {
+            checksum,
+            mode: mode.0,
+            input: [to_fixed(lon), to_fixed(lat)],
+            candidates: candidates
+                .iter()
+                .take(MAX_HINT_CANDIDATES)
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Snapped `[lon, lat]` of the closest candidate (the input coordinate
+    /// when there is none).
+    pub fn location(&self) -> [f64; 2] {
+        match self.candidates.first() {
+            Some(&(_, lon, lat, _)) => [lon, lat],
+            None => self.input.map(|v| v as f64 / COORD_SCALE),
+        }
+    }
+
+    /// Union with the hint of the same waypoint for another role (a via
+    /// point is both a destination and a source), closest first.
+    pub fn merge(&self, other: &SnapHint) -> SnapHint {
+        let mut candidates = self.candidates.clone();
+        for c in &other.candidates {
+            if !candidates.iter().any(|k| k.0 == c.0) {
+                candidates.push(*c);
+            }
+        }
+        candidates.sort_by(|a, b| a.3.total_cmp(&b.3));
+        candidates.truncate(MAX_HINT_CANDIDATES);
+        SnapHint {
+            candidates,
+            ..self.clone()
+        }
+    }
+
+    /// Whether the hint was made on this dataset, mode and coordinate.
+    pub fn applies_to(&self, checksum: u32, mode: Mode, [lon, lat]: [f64; 2]) -> bool {
+        self.checksum == checksum
+            && self.mode == mode.0
+            && self.input == [to_fixed(lon), to_fixed(lat)]
+    }
+
+    /// The hint's candidates usable as `role` endpoints of this request:
+    /// valid seeds under `edge_filter` and within `radius` metres. `None`
+    /// when the hint is stale or none is left, so the caller snaps instead.
+    #[allow(clippy::too_many_arguments)]
+    pub fn candidates_for(
+        &self,
+        state: &ServerState,
+        mode_data: &ModeData,
+        mode: Mode,
+        coord: [f64; 2],
+        role: SnapRole,
+        edge_filter: Option<&[u64]>,
+        radius: f64,
+    ) -> Option<Vec<SnapCandidate>> {
+        if !self.applies_to(data_checksum(state), mode, coord) {
+            return None;
+        }
+        let usable: Vec<SnapCandidate> = self
+            .candidates
+            .iter()
+            .filter(|c| c.3 <= radius && seed_valid(mode_data, role, edge_filter, c.0))
+            .copied()
+            .collect();
+        (!usable.is_empty()).then_some(usable)
+    }
+
+    /// URL-safe base64 token.
+    pub fn encode(&self) -> String {
+        let mut buf = Vec::with_capacity(15 + 16 * self.candidates.len());
+        buf.push(HINT_VERSION);
+        buf.push(self.mode);
+        buf.extend_from_slice(&self.checksum.to_le_bytes());
+        buf.extend_from_slice(&self.input[0].to_le_bytes());
+        buf.extend_from_slice(&self.input[1].to_le_bytes());
+        buf.push(self.candidates.len() as u8);
+        for &(ebg_id, lon, lat, dist) in &self.candidates {
+            buf.extend_from_slice(&ebg_id.to_le_bytes());
+            buf.extend_from_slice(&to_fixed(lon).to_le_bytes());
+            buf.extend_from_slice(&to_fixed(lat).to_le_bytes());
+            buf.extend_from_slice(&((dist * 100.0).round() as u32).to_le_bytes());
+        }
+        URL_SAFE_NO_PAD.encode(buf)
+    }
+
+    /// Parse a token from [`SnapHint::encode`].
+    pub fn decode(token: &str) -> Result<Self, String> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .map_err(|e| format!("not a hint token ({})", e))?;
+        let word = |at: usize| -> [u8; 4] { bytes[at..at + 4].try_into().unwrap() };
+        if bytes.len() < 15 || bytes[0] != HINT_VERSION {
+            return Err("not a hint token".to_string());
+        }
+        let n = bytes[14] as usize;
+        if n > MAX_HINT_CANDIDATES || bytes.len() != 15 + 16 * n {
+            return Err("truncated hint token".to_string());
+        }
+        let candidates = (0..n)
+            .map(|i| {
+                let at = 15 + 16 * i;
+                (
+                    u32::from_le_bytes(word(at)),
+                    i32::from_le_bytes(word(at + 4)) as f64 / COORD_SCALE,
+                    i32::from_le_bytes(word(at + 8)) as f64 / COORD_SCALE,
+                    u32::from_le_bytes(word(at + 12)) as f64 / 100.0,
+                )
+            })
+            .collect();
+        Ok(Self {
+            mode: bytes[1],
+            checksum: u32::from_le_bytes(word(2)),
+            input: [i32::from_le_bytes(word(6)), i32::from_le_bytes(word(10))],
+            candidates,
+        })
+    }
+}
+
+/// Parse `hints=h;h;...`: one token per waypoint, an empty slot snaps that
+/// waypoint as usual.
+pub fn parse_hints(s: &str) -> Result<Vec<Option<SnapHint>>, String> {
+    s.split(';')
+        .enumerate()
+        .map(|(i, token)| match token.trim() {
+            "" => Ok(None),
+            t => SnapHint::decode(t)
+                .map(Some)
+                .map_err(|e| format!("Invalid hint {}: {}", i, e)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint_roundtrip_and_staleness() {
+        let coord = [4.3517, 50.8503];
+        let hint = SnapHint::new(
+            0xdead_beef,
+            Mode(2),
+            coord,
+            &[(42, 4.35171, 50.85031, 3.25), (43, 4.35171, 50.85031, 3.25)],
+        );
+        let token = hint.encode();
+        assert!(
+            token
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        );
+        assert_eq!(SnapHint::decode(&token).unwrap(), hint);
+        assert_eq!(hint.location(), [4.35171, 50.85031]);
+
+        assert!(hint.applies_to(0xdead_beef, Mode(2), coord));
+        assert!(!hint.applies_to(0xdead_beef, Mode(1), coord));
+        assert!(!hint.applies_to(0x1234, Mode(2), coord));
+        assert!(!hint.applies_to(0xdead_beef, Mode(2), [4.3518, 50.8503]));
+
+        assert!(SnapHint::decode("not-a-hint").is_err());
+        assert!(SnapHint::decode(&token[..token.len() - 4]).is_err());
+        let parsed = parse_hints(&format!(";{}", token)).unwrap();
+        assert_eq!(parsed, vec![None, Some(hint)]);
+        assert!(parse_hints("x;").unwrap_err().starts_with("Invalid hint 0"));
+    }
+
+    #[test]
+    fn test_hint_merge_keeps_closest_unique() {
+        let coord = [4.0, 50.0];
+        let as_dst = SnapHint::new(
+            1,
+            Mode(0),
+            coord,
+            &[(10, 4.0, 50.0, 5.0), (20, 4.0, 50.0, 9.0)],
+        );
+        let as_src = SnapHint::new(
+            1,
+            Mode(0),
+            coord,
+            &[(11, 4.0, 50.0, 5.0), (20, 4.0, 50.0, 9.0)],
+        );
+        let ids: Vec<u32> = as_dst
+            .merge(&as_src)
+            .candidates
+            .iter()
+            .map(|c| c.0)
+            .collect();
+        assert_eq!(ids, vec![10, 11, 20]);
+    }
+}
@@ -0,0 +1,440 @@
+//! Localized turn-by-turn instruction text for /route steps
+//!
+//! Steps carry machine-readable maneuvers (`type` + `modifier`, always
+//! English tokens); this module renders them as a sentence in the
+//! requested language, with the road name interpolated, plus the step
+//! distance in metric or imperial units. The language comes from
+//! `language=` or, failing that, the `Accept-Language` header.
+
+use axum::http::HeaderMap;
+
+/// Languages with a message catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    En,
+    Fr,
+    De,
+    Nl,
+    Es,
+}
+
+/// Supported language codes, for error messages.
+pub const SUPPORTED_LANGUAGES: &str = "de, en, es, fr, nl";
+
+impl Language {
+    /// Match a BCP 47 tag (`fr`, `fr-BE`, `en_US`) on its primary subtag.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.trim().to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Language::En),
+            "fr" => Some(Language::Fr),
+            "de" => Some(Language::De),
+            "nl" => Some(Language::Nl),
+            "es" => Some(Language::Es),
+            _ => None,
+        }
+    }
+
+    fn catalog(self) -> &'static Catalog {
+        match self {
+            Language::En => &EN,
+            Language::Fr => &FR,
+            Language::De => &DE,
+            Language::Nl => &NL,
+            Language::Es => &ES,
+        }
+    }
+}
+
+/// Unit system for step distances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Metric,
+    Imperial,
+}
+
+impl Units {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "metric" => Ok(Units::Metric),
+            "imperial" => Ok(Units::Imperial),
+            other => Err(format!(
+                "Invalid units '{}'. Expected 'metric' or 'imperial'.",
+                other
+            )),
+        }
+    }
+
+    /// Road distances are signed in miles in the US and the UK; metric
+    /// everywhere else.
+    fn default_for(tag: &str) -> Self {
+        let region = tag.split(['-', '_']).nth(1).map(str::to_ascii_uppercase);
+        match region.as_deref() {
+            Some("US") | Some("GB") => Units::Imperial,
+            _ => Units::Metric,
+        }
+    }
+}
+
+/// Language + units an instruction is rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub language: Language,
+    pub units: Units,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale {
+            language: Language::En,
+            units: Units::Metric,
+        }
+    }
+}
+
+/// Resolve the request locale. An explicit `language` must be supported
+/// (400 otherwise); `Accept-Language` picks its highest-q supported tag
+/// and falls back to English. `units` defaults from the region subtag of
+/// the chosen tag (`en-US` → imperial).
+pub fn resolve_locale(
+    language: Option<&str>,
+    units: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<Locale, String> {
+    let (language, tag) = match language {
+        Some(tag) => match Language::from_tag(tag) {
+            Some(lang) => (lang, tag.to_string()),
+            None => {
+                return Err(format!(
+                    "Unsupported language '{}'. Available: {}.",
+                    tag, SUPPORTED_LANGUAGES
+                ));
+            }
+        },
+        None => headers
+            .get("accept-language")
+            .and_then(|v| v.to_str().ok())
+            .and_then(best_accept_language)
+            .unwrap_or((Language::En, "en".to_string())),
+    };
+    let units = match units {
+        Some(u) => Units::parse(u)?,
+        None => Units::default_for(&tag),
+    };
+    Ok(Locale { language, units })
+}
+
+/// Highest-q supported entry of an `Accept-Language` value; ties keep
+/// header order.
+fn best_accept_language(header: &str) -> Option<(Language, String)> {
+    let mut best: Option<(f32, Language, &str)> = None;
+    for entry in header.split(',') {
+        let mut parts = entry.split(';');
+        let tag = parts.next().unwrap_or("").trim();
+        let q = parts
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .map_or(1.0, |q| q.trim().parse::<f32>().unwrap_or(0.0));
+        if q <= 0.0 {
+            continue;
+        }
+        if let Some(lang) = Language::from_tag(tag)
+            && best.is_none_or(|(bq, _, _)| q > bq)
+        {
+            best = Some((q, lang, tag));
+        }
+    }
+    best.map(|(_, lang, tag)| (lang, tag.to_string()))
+}
+
+/// A phrase without and with a `{name}` road name.
+type Phrase = (&'static str, &'static str);
+
+struct Catalog {
+    /// Turn phrases by modifier, in [`MODIFIERS`] order.
+    turns: [&'static str; 8],
+    /// `{turn}` + `{name}` for a turn onto a named road.
+    turn_onto: &'static str,
+    depart: Phrase,
+    continue_: Phrase,
+    arrive: Phrase,
+    roundabout: Phrase,
+    exit_roundabout: Phrase,
+    ferry: Phrase,
+    exit_ferry: Phrase,
+    decimal_separator: char,
+}
+
+/// Modifiers as produced by [`classify_turn`](super::route::classify_turn).
+const MODIFIERS: [&str; 8] = [
+    "straight",
+    "slight right",
+    "right",
+    "sharp right",
+    "uturn",
+    "sharp left",
+    "left",
+    "slight left",
+];
+
+const EN: Catalog = Catalog {
+    turns: [
+        "Go straight",
+        "Turn slightly right",
+        "Turn right",
+        "Turn sharp right",
+        "Make a U-turn",
+        "Turn sharp left",
+        "Turn left",
+        "Turn slightly left",
+    ],
+    turn_onto: "{turn} onto {name}",
+    depart: ("Depart", "Depart on {name}"),
+    continue_: ("Continue straight", "Continue on {name}"),
+    arrive: (
+        "Arrive at your destination",
+        "Arrive at your destination on {name}",
+    ),
+    roundabout: ("Enter the roundabout", "Enter the roundabout on {name}"),
+    exit_roundabout: ("Exit the roundabout", "Exit the roundabout onto {name}"),
+    ferry: ("Take the ferry", "Take the ferry {name}"),
+    exit_ferry: ("Leave the ferry", "Leave the ferry onto {name}"),
+    decimal_separator: '.',
+};
+
+const FR: Catalog = Catalog {
+    turns: [
+        "Continuer tout droit",
+        "Tourner légèrement à droite",
+        "Tourner à droite",
+        "Tourner franchement à droite",
+        "Faire demi-tour",
+        "Tourner franchement à gauche",
+        "Tourner à gauche",
+        "Tourner légèrement à gauche",
+    ],
+    turn_onto: "{turn} sur {name}",
+    depart: ("Partir", "Partir sur {name}"),
+    continue_: ("Continuer tout droit", "Continuer sur {name}"),
+    arrive: ("Arriver à destination", "Arriver à destination sur {name}"),
+    roundabout: (
+        "Entrer dans le rond-point",
+        "Entrer dans le rond-point {name}",
+    ),
+    exit_roundabout: ("Sortir du rond-point", "Sortir du rond-point sur {name}"),
+    ferry: ("Prendre le ferry", "Prendre le ferry {name}"),
+    exit_ferry: ("Quitter le ferry", "Quitter le ferry sur {name}"),
+    decimal_separator: ',',
+};
+
+const DE: Catalog = Catalog {
+    turns: [
+        "Geradeaus fahren",
+        "Leicht rechts abbiegen",
+        "Rechts abbiegen",
+        "Scharf rechts abbiegen",
+        "Wenden",
+        "Scharf links abbiegen",
+        "Links abbiegen",
+        "Leicht links abbiegen",
+    ],
+    turn_onto: "{turn} auf {name}",
+    depart: ("Losfahren", "Losfahren auf {name}"),
+    continue_: ("Geradeaus weiterfahren", "Weiterfahren auf {name}"),
+    arrive: ("Am Ziel ankommen", "Am Ziel ankommen auf {name}"),
+    roundabout: (
+        "In den Kreisverkehr fahren",
+        "In den Kreisverkehr {name} fahren",
+    ),
+    exit_roundabout: (
+        "Kreisverkehr verlassen",
+        "Kreisverkehr verlassen auf {name}",
+    ),
+    ferry: ("Fähre nehmen", "Fähre {name} nehmen"),
+    exit_ferry: ("Fähre verlassen", "Fähre verlassen auf {name}"),
+    decimal_separator: ',',
+};
+
+const NL: Catalog = Catalog {
+    turns: [
+        "Ga rechtdoor",
+        "Houd rechts aan",
+        "Ga rechtsaf",
+        "Ga scherp rechtsaf",
+        "Keer om",
+        "Ga scherp linksaf",
+        "Ga linksaf",
+        "Houd links aan",
+    ],
+    turn_onto: "{turn} naar {name}",
+    depart: ("Vertrek", "Vertrek via {name}"),
+    continue_: ("Ga rechtdoor", "Ga verder op {name}"),
+    arrive: ("Bestemming bereikt", "Bestemming bereikt op {name}"),
+    roundabout: ("Ga de rotonde op", "Ga de rotonde {name} op"),
+    exit_roundabout: ("Verlaat de rotonde", "Verlaat de rotonde naar {name}"),
+    ferry: ("Neem de veerboot", "Neem de veerboot {name}"),
+    exit_ferry: ("Verlaat de veerboot", "Verlaat de veerboot naar {name}"),
+    decimal_separator: ',',
+};
+
+const ES: Catalog = Catalog {
+    turns: [
+        "Siga recto",
+        "Gire ligeramente a la derecha",
+        "Gire a la derecha",
+        "Gire bruscamente a la derecha",
+        "Haga un cambio de sentido",
+        "Gire bruscamente a la izquierda",
+        "Gire a la izquierda",
+        "Gire ligeramente a la izquierda",
+    ],
+    turn_onto: "{turn} hacia {name}",
+    depart: ("Salga", "Salga por {name}"),
+    continue_: ("Siga recto", "Continúe por {name}"),
+    arrive: (
+        "Ha llegado a su destino",
+        "Ha llegado a su destino en {name}",
+    ),
+    roundabout: ("Entre en la rotonda", "Entre en la rotonda {name}"),
+    exit_roundabout: ("Salga de la rotonda", "Salga de la rotonda hacia {name}"),
+    ferry: ("Tome el ferry", "Tome el ferry {name}"),
+    exit_ferry: ("Salga del ferry", "Salga del ferry hacia {name}"),
+    decimal_separator: ',',
+};
+
+impl Locale {
+    /// Sentence for a step maneuver. Unknown types (`fork`, `merge`, ...)
+    /// read as a plain turn with their modifier.
+    pub fn instruction(
+        &self,
+        maneuver_type: &str,
+        modifier: Option<&str>,
+        name: Option<&str>,
+    ) -> String {
+        let catalog = self.language.catalog();
+        let name = name.map(str::trim).filter(|n| !n.is_empty());
+        let phrase = match maneuver_type {
+            "depart" => catalog.depart,
+            "arrive" => catalog.arrive,
+            "continue" => catalog.continue_,
+            "roundabout" => catalog.roundabout,
+            "exit roundabout" => catalog.exit_roundabout,
+            "ferry" => catalog.ferry,
+            "exit ferry" => catalog.exit_ferry,
+            _ => {
+                let idx = MODIFIERS
+                    .iter()
+                    .position(|m| Some(*m) == modifier)
+                    .unwrap_or(0);
+                let turn = catalog.turns[idx];
+                return match name {
+                    Some(n) => catalog
+                        .turn_onto
+                        .replace("{turn}", turn)
+                        .replace("{name}", n),
+                    None => turn.to_string(),
+                };
+            }
+        };
+        match name {
+            Some(n) => phrase.1.replace("{name}", n),
+            None => phrase.0.to_string(),
+        }
+    }
+
+    /// Step distance for display: metres rounded to 10 (km with one
+    /// decimal from 1 km, whole km from 10 km), or feet below a tenth of a
+    /// mile and miles above, with the language's decimal separator.
+    pub fn distance(&self, metres: f64) -> String {
+        let sep = self.language.catalog().decimal_separator;
+        let (small, small_unit, large, large_unit, threshold) = match self.units {
+            Units::Metric => (metres, "m", metres / 1000.0, "km", 1000.0),
+            Units::Imperial => (metres / 0.3048, "ft", metres / 1609.344, "mi", 160.9344),
+        };
+        if metres < threshold {
+            let rounded = ((small / 10.0).round() * 10.0) as u64;
+            return format!("{} {}", rounded, small_unit);
+        }
+        if large >= 10.0 {
+            return format!("{} {}", large.round() as u64, large_unit);
+        }
+        format!("{:.1} {}", large, large_unit).replace('.', &sep.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locale(language: Language, units: Units) -> Locale {
+        Locale { language, units }
+    }
+
+    #[test]
+    fn language_param_wins_and_must_be_supported() {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept-language", "de-DE,de;q=0.9".parse().unwrap());
+        let l = resolve_locale(Some("fr-BE"), None, &headers).unwrap();
+        assert_eq!(l, locale(Language::Fr, Units::Metric));
+        let err = resolve_locale(Some("it"), None, &headers).unwrap_err();
+        assert!(err.contains("it") && err.contains(SUPPORTED_LANGUAGES));
+        assert!(resolve_locale(None, Some("furlongs"), &headers).is_err());
+    }
+
+    #[test]
+    fn accept_language_picks_highest_supported_q() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "accept-language",
+            "it-IT,it;q=0.9,nl;q=0.5,en-US;q=0.8".parse().unwrap(),
+        );
+        let l = resolve_locale(None, None, &headers).unwrap();
+        assert_eq!(l, locale(Language::En, Units::Imperial));
+        let l = resolve_locale(None, Some("metric"), &headers).unwrap();
+        assert_eq!(l.units, Units::Metric);
+        assert_eq!(
+            resolve_locale(None, None, &HeaderMap::new()).unwrap(),
+            Locale::default()
+        );
+    }
+
+    #[test]
+    fn instructions_interpolate_road_names() {
+        let en = Locale::default();
+        assert_eq!(
+            en.instruction("turn", Some("left"), Some("Rue de la Loi")),
+            "Turn left onto Rue de la Loi"
+        );
+        assert_eq!(en.instruction("turn", Some("uturn"), None), "Make a U-turn");
+        assert_eq!(en.instruction("depart", None, Some("  ")), "Depart");
+        let fr = locale(Language::Fr, Units::Metric);
+        assert_eq!(
+            fr.instruction("turn", Some("slight right"), Some("Avenue Louise")),
+            "Tourner légèrement à droite sur Avenue Louise"
+        );
+        let de = locale(Language::De, Units::Metric);
+        assert_eq!(
+            de.instruction("ferry", None, Some("Rheinfähre")),
+            "Fähre Rheinfähre nehmen"
+        );
+        let nl = locale(Language::Nl, Units::Metric);
+        assert_eq!(nl.instruction("arrive", None, None), "Bestemming bereikt");
+        let es = locale(Language::Es, Units::Metric);
+        assert_eq!(
+            es.instruction("exit roundabout", Some("right"), Some("Gran Vía")),
+            "Salga de la rotonda hacia Gran Vía"
+        );
+    }
+
+    #[test]
+    fn distances_follow_units_and_decimal_separator() {
+        let en = Locale::default();
+        assert_eq!(en.distance(344.0), "340 m");
+        assert_eq!(en.distance(1234.0), "1.2 km");
+        assert_eq!(en.distance(15_600.0), "16 km");
+        let de = locale(Language::De, Units::Metric);
+        assert_eq!(de.distance(1234.0), "1,2 km");
+        let us = locale(Language::En, Units::Imperial);
+        assert_eq!(us.distance(100.0), "330 ft");
+        assert_eq!(us.distance(2000.0), "1.2 mi");
+    }
+}
@@ -12,22 +12,31 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use utoipa::ToSchema;
 
-use super::geometry::{GeometryFormat, Point, build_isochrone_geometry, encode_polyline6};
+use super::geometry::{GeometryFormat, Point, build_isochrone_polygons, encode_polyline6};
 use super::regions::RegionsState;
 use super::route::{default_direction, default_geometries};
 use super::state::ServerState;
-use super::types::{ErrorResponse, SnapRole, parse_mode, validate_coord};
+use super::types::{ErrorResponse, SnapRole, Weighting, resolve_weighting, validate_coord};
+use crate::range::ContourGeneralization;
+use crate::range::contour::ContourResult;
 
 // ============ Types ============
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct IsochroneRequest {
-    /// Center longitude
+    /// Center longitude. Mutually exclusive with origins.
+    #[serde(default)]
     #[schema(example = 4.3517)]
-    pub lon: f64,
-    /// Center latitude
+    pub lon: Option<f64>,
+    /// Center latitude. Mutually exclusive with origins.
+    #[serde(default)]
     #[schema(example = 50.8503)]
-    pub lat: f64,
+    pub lat: Option<f64>,
+    /// Several origins as `lon,lat;lon,lat;...` (max 100): the contours
+    /// cover the union of their reachable areas. Mutually exclusive with
+    /// lon/lat.
+    #[serde(default)]
+    pub origins: Option<String>,
     /// Time limit in seconds (1-7200). Mutually exclusive with contours.
     #[serde(default)]
     #[schema(example = 600)]
@@ -36,9 +45,18 @@ pub struct IsochroneRequest {
     /// Mutually exclusive with time_s.
     #[serde(default)]
     pub contours: Option<String>,
+    /// Nested contours as comma-separated seconds (e.g. "300,600,900", max
+    /// 10) from one PHAST run, returned as a GeoJSON FeatureCollection of
+    /// MultiPolygon features. Mutually exclusive with time_s and contours.
+    #[serde(default)]
+    pub thresholds: Option<String>,
     /// Transport mode (car, bike, foot)
     #[schema(example = "car")]
     pub mode: String,
+    /// Weight set: "fastest" (default) or a loaded traffic variant name such
+    /// as "rush_hour". Contours are time budgets, so "shortest" is rejected.
+    #[serde(default)]
+    pub weighting: Option<String>,
     /// Direction: "depart" (default) or "arrive"
     #[serde(default = "default_direction")]
     #[schema(example = "depart")]
@@ -61,6 +79,17 @@ pub struct IsochroneRequest {
     /// opt-in (2 extra PHAST passes). car only, JSON only.
     #[serde(default)]
     pub uncertainty: Option<String>,
+    /// Douglas-Peucker tolerance in meters (0-1000), replacing the
+    /// threshold tier's default: larger means fewer vertices
+    #[serde(default)]
+    pub simplify: Option<f64>,
+    /// Grow the polygon outward by this many meters (0-1000)
+    #[serde(default)]
+    pub buffer: Option<f64>,
+    /// Close gaps and notches up to about twice this many meters (0-1000)
+    /// without growing the polygon
+    #[serde(default)]
+    pub smoothing: Option<f64>,
 }
 
 /// A single contour polygon in an isochrone response
@@ -76,6 +105,12 @@ pub struct ContourFeature {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schema(value_type = Option<Vec<Vec<f64>>>)]
     pub polygon_geojson: Option<Vec<[f64; 2]>>,
+    /// Every polygon of the contour as GeoJSON MultiPolygon coordinates
+    /// (geometries=geojson only): `[outer, holes...]` per disconnected
+    /// part, the origin's first; holes are unreachable enclaves
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<Vec<Vec<Vec<f64>>>>>)]
+    pub multipolygon_geojson: Option<Vec<Vec<Vec<[f64; 2]>>>>,
     /// Polygon as point array [{lon, lat}, ...]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub polygon_points: Option<Vec<Point>>,
@@ -98,6 +133,109 @@ pub struct IsochroneResponse {
     pub network: Option<Vec<Vec<[f64; 2]>>>,
 }
 
+/// `thresholds=` response: a GeoJSON FeatureCollection, largest threshold
+/// first so the nested smaller contours draw on top
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IsochroneFeatureCollection {
+    /// Always "FeatureCollection"
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub features: Vec<IsochroneFeature>,
+}
+
+/// One contour (or the reachable network) as a GeoJSON Feature
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IsochroneFeature {
+    /// Always "Feature"
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub geometry: IsochroneGeometry,
+    pub properties: IsochroneFeatureProperties,
+}
+
+/// GeoJSON geometry of an [`IsochroneFeature`]
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(tag = "type", content = "coordinates")]
+pub enum IsochroneGeometry {
+    /// Contour polygon(s), outer rings counter-clockwise
+    MultiPolygon(Vec<Vec<Vec<[f64; 2]>>>),
+    /// Reachable road segments (include=network)
+    MultiLineString(Vec<Vec<[f64; 2]>>),
+}
+
+/// Properties of an [`IsochroneFeature`]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IsochroneFeatureProperties {
+    /// Contour threshold in seconds
+    pub threshold: u32,
+    /// Number of reachable edges within this contour (absent on the
+    /// network feature)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reachable_edges: Option<usize>,
+    /// Band tag (only with uncertainty=bands): "optimistic" | "pessimistic"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub band: Option<&'static str>,
+    /// Set on the reachable-network feature (include=network)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub network: bool,
+    /// Index into the request's `origins` (/isochrone/bulk geojson-seq)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_index: Option<usize>,
+}
+
+impl IsochroneFeatureCollection {
+    /// GeoJSON form of GeoJSON-encoded contours plus the optional network
+    /// (reached within `network_threshold`).
+    pub fn from_contours(
+        contours: &[ContourFeature],
+        network: Option<Vec<Vec<[f64; 2]>>>,
+        network_threshold: u32,
+    ) -> Self {
+        let mut features: Vec<IsochroneFeature> = contours
+            .iter()
+            .map(|c| IsochroneFeature {
+                kind: "Feature",
+                geometry: IsochroneGeometry::MultiPolygon(match &c.multipolygon_geojson {
+                    Some(polygons) => polygons.clone(),
+                    None => c
+                        .polygon_geojson
+                        .iter()
+                        .filter(|ring| !ring.is_empty())
+                        .map(|ring| vec![ring.clone()])
+                        .collect(),
+                }),
+                properties: IsochroneFeatureProperties {
+                    threshold: c.time_s.unwrap_or(0),
+                    reachable_edges: Some(c.reachable_edges),
+                    band: c.band,
+                    network: false,
+                    origin_index: None,
+                },
+            })
+            .collect();
+        // Stable: median, optimistic and pessimistic keep their order
+        // within a threshold.
+        features.sort_by_key(|f| std::cmp::Reverse(f.properties.threshold));
+        if let Some(segments) = network {
+            features.push(IsochroneFeature {
+                kind: "Feature",
+                geometry: IsochroneGeometry::MultiLineString(segments),
+                properties: IsochroneFeatureProperties {
+                    threshold: network_threshold,
+                    reachable_edges: None,
+                    band: None,
+                    network: true,
+                    origin_index: None,
+                },
+            });
+        }
+        Self {
+            kind: "FeatureCollection",
+            features,
+        }
+    }
+}
+
 /// Bulk isochrone request
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct BulkIsochroneRequest {
@@ -110,12 +248,99 @@ pub struct BulkIsochroneRequest {
     /// Transport mode: car, bike, or foot
     #[schema(example = "car")]
     mode: String,
+    /// Weight set: "fastest" (default) or a loaded traffic variant name
+    #[serde(default)]
+    weighting: Option<String>,
     /// Exclude road types: comma-separated list of "toll", "ferry", "motorway"
     #[serde(default)]
     exclude: Option<String>,
     /// Avoid polygon(s) as JSON array of coordinate rings
     #[serde(default)]
     avoid_polygons: Option<String>,
+    /// Output: "wkb" (default, length-prefixed WKB) or "geojson-seq"
+    /// (newline-delimited GeoJSON Features)
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Output of /isochrone/bulk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BulkIsochroneFormat {
+    /// `[u32 origin_idx][u32 wkb_len][WKB]` per origin
+    Wkb,
+    /// One GeoJSON Feature per line
+    GeoJsonSeq,
+}
+
+impl BulkIsochroneFormat {
+    pub fn parse(s: Option<&str>) -> Result<Self, String> {
+        match s.map(str::to_lowercase).as_deref() {
+            None | Some("wkb") => Ok(Self::Wkb),
+            Some("geojson-seq") => Ok(Self::GeoJsonSeq),
+            Some(other) => Err(format!("Unknown format '{}'. Use: wkb, geojson-seq", other)),
+        }
+    }
+
+    /// One origin's record: `None` when its isochrone is empty.
+    pub fn encode(
+        self,
+        origin_index: usize,
+        threshold: u32,
+        reachable_edges: usize,
+        polygons: &[Vec<Vec<Point>>],
+    ) -> Option<Vec<u8>> {
+        match self {
+            Self::Wkb => {
+                let wkb = crate::range::encode_polygon_wkb(&primary_contour_result(polygons))?;
+                let mut record = Vec::with_capacity(8 + wkb.len());
+                record.extend_from_slice(&(origin_index as u32).to_le_bytes());
+                record.extend_from_slice(&(wkb.len() as u32).to_le_bytes());
+                record.extend_from_slice(&wkb);
+                Some(record)
+            }
+            Self::GeoJsonSeq => {
+                if polygons.is_empty() {
+                    return None;
+                }
+                let feature = IsochroneFeature {
+                    kind: "Feature",
+                    geometry: IsochroneGeometry::MultiPolygon(geojson_multipolygon(polygons)),
+                    properties: IsochroneFeatureProperties {
+                        threshold,
+                        reachable_edges: Some(reachable_edges),
+                        band: None,
+                        network: false,
+                        origin_index: Some(origin_index),
+                    },
+                };
+                let mut line = serde_json::to_vec(&feature).ok()?;
+                line.push(b'\n');
+                Some(line)
+            }
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Wkb => "application/octet-stream",
+            Self::GeoJsonSeq => "application/geo+json-seq",
+        }
+    }
+}
+
+/// Resolve `weighting` for an isochrone. Contours are travel-time budgets,
+/// so only time weight sets (fastest or a traffic variant) apply.
+fn isochrone_weighting(
+    mode: &str,
+    weighting: Option<&str>,
+    state: &ServerState,
+) -> Result<(crate::profile_abi::Mode, Weighting), String> {
+    match resolve_weighting(mode, weighting, &state.mode_lookup)? {
+        (_, Weighting::Shortest) => {
+            Err("weighting=shortest is not supported for isochrones (time budgets)".to_string())
+        }
+        resolved => Ok(resolved),
+    }
 }
 
 // =============================================================================
@@ -780,6 +1005,73 @@ pub fn run_phast_bounded_fast_reverse_seeded(
     })
 }
 
+/// Most origins in one `origins=` (union) isochrone.
+pub const MAX_ISOCHRONE_ORIGINS: usize = 100;
+
+/// Resolve the isochrone origins: `lon`/`lat`, or the `origins` list.
+pub fn isochrone_origins(req: &IsochroneRequest) -> Result<Vec<[f64; 2]>, String> {
+    let list = match (req.lon, req.lat, req.origins.as_deref()) {
+        (Some(lon), Some(lat), None) => {
+            validate_coord(lon, lat, "center")?;
+            return Ok(vec![[lon, lat]]);
+        }
+        (None, None, Some(list)) => list,
+        (None, None, None) => return Err("Provide lon/lat or origins".to_string()),
+        (_, _, Some(_)) => return Err("origins and lon/lat are mutually exclusive".to_string()),
+        _ => return Err("lon and lat must be given together".to_string()),
+    };
+    let origins = list
+        .trim()
+        .split(';')
+        .enumerate()
+        .map(|(i, pair)| {
+            let (lon, lat) = pair
+                .split_once(',')
+                .ok_or_else(|| format!("origin {} ('{}') must be 'lon,lat'", i, pair))?;
+            let lon: f64 = lon
+                .trim()
+                .parse()
+                .map_err(|_| format!("origin {} has invalid longitude '{}'", i, lon))?;
+            let lat: f64 = lat
+                .trim()
+                .parse()
+                .map_err(|_| format!("origin {} has invalid latitude '{}'", i, lat))?;
+            validate_coord(lon, lat, &format!("origin {}", i))?;
+            Ok([lon, lat])
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if origins.len() > MAX_ISOCHRONE_ORIGINS {
+        return Err(format!(
+            "origins must have at most {} coordinates, got {}",
+            MAX_ISOCHRONE_ORIGINS,
+            origins.len()
+        ));
+    }
+    Ok(origins)
+}
+
+/// Largest `simplify` / `buffer` / `smoothing`, in meters.
+pub const MAX_ISOCHRONE_GENERALIZATION_M: f64 = 1000.0;
+
+/// Per-request contour generalization from `simplify`, `buffer` and
+/// `smoothing`.
+pub fn isochrone_generalization(req: &IsochroneRequest) -> Result<ContourGeneralization, String> {
+    let meters = |name: &str, value: Option<f64>| -> Result<Option<f64>, String> {
+        match value {
+            Some(v) if !(0.0..=MAX_ISOCHRONE_GENERALIZATION_M).contains(&v) => Err(format!(
+                "{} must be between 0 and {} meters, got {}",
+                name, MAX_ISOCHRONE_GENERALIZATION_M, v
+            )),
+            v => Ok(v),
+        }
+    };
+    Ok(ContourGeneralization {
+        simplify_m: meters("simplify", req.simplify)?,
+        buffer_m: meters("buffer", req.buffer)?.unwrap_or(0.0),
+        smoothing_m: meters("smoothing", req.smoothing)?.unwrap_or(0.0),
+    })
+}
+
 // ============ Handlers ============
 
 /// Calculate isochrone (reachable area within time limit)
@@ -795,20 +1087,26 @@ pub fn run_phast_bounded_fast_reverse_seeded(
     path = "/isochrone",
     tag = "Isochrone",
     summary = "Compute reachability polygon",
-    description = "Computes the area reachable within a time limit using PHAST.\nSupports forward (depart) and reverse (arrive) isochrones.\n\nProvide exactly one of: `time_s` or `contours`.\n\nContent negotiation:\n- `Accept: application/json` \u{2192} JSON polygon\n- `Accept: application/octet-stream` \u{2192} WKB binary polygon (single contour only)",
+    description = "Computes the area reachable within a time limit using PHAST.\nSupports forward (depart) and reverse (arrive) isochrones.\n\nGive the origin as `lon`/`lat`, or several as `origins` for the union of their reachable areas.\n\nProvide exactly one of: `time_s`, `contours` or `thresholds`. `thresholds` returns a GeoJSON FeatureCollection of nested MultiPolygon contours from a single PHAST run.\n\nContent negotiation:\n- `Accept: application/json` \u{2192} JSON polygon\n- `Accept: application/octet-stream` \u{2192} WKB binary polygon (single contour only)",
     params(
-        ("lon" = f64, Query, description = "Center longitude", example = 4.3517),
-        ("lat" = f64, Query, description = "Center latitude", example = 50.8503),
+        ("lon" = Option<f64>, Query, description = "Center longitude (or use origins)", example = 4.3517),
+        ("lat" = Option<f64>, Query, description = "Center latitude (or use origins)", example = 50.8503),
+        ("origins" = Option<String>, Query, description = "Several origins as 'lon,lat;lon,lat;...' (max 100); contours cover the union of their reachable areas (GeoJSON geometries, JSON only). Mutually exclusive with lon/lat.", example = json!(null)),
         ("time_s" = Option<u32>, Query, description = "Time limit in seconds (1-7200). Mutually exclusive with contours.", example = 600),
         ("contours" = Option<String>, Query, description = "Comma-separated time contours in seconds (e.g. '300,600,1200', max 10). Mutually exclusive with time_s.", example = json!(null)),
+        ("thresholds" = Option<String>, Query, description = "Comma-separated time contours in seconds (e.g. '300,600,900', max 10), returned as a GeoJSON FeatureCollection with a `threshold` property per MultiPolygon feature. Mutually exclusive with time_s and contours.", example = json!(null)),
         ("mode" = String, Query, description = "Transport mode (e.g. car, bike, foot \u{2014} depends on available models)", example = "car"),
         ("direction" = Option<String>, Query, description = "Direction: 'depart' (default) or 'arrive'", example = "depart"),
         ("geometries" = Option<String>, Query, description = "Geometry encoding: polyline6 (default), geojson, points", example = "geojson"),
         ("include" = Option<String>, Query, description = "Optional: 'network' adds reachable road geometries", example = json!(null)),
         ("exclude" = Option<String>, Query, description = "Exclude road types: comma-separated list of 'toll', 'ferry', 'motorway'", example = json!(null)),
+        ("weighting" = Option<String>, Query, description = "Weight set: 'fastest' (default) or a loaded traffic variant name (e.g. 'rush_hour')", example = json!(null)),
+        ("simplify" = Option<f64>, Query, description = "Douglas-Peucker tolerance in meters (0-1000) replacing the threshold tier's default; larger means fewer vertices", example = json!(null)),
+        ("buffer" = Option<f64>, Query, description = "Grow the polygon outward by this many meters (0-1000)", example = json!(null)),
+        ("smoothing" = Option<f64>, Query, description = "Close gaps and notches up to about twice this many meters (0-1000) without growing the polygon", example = json!(null)),
     ),
     responses(
-        (status = 200, description = "Isochrone computed", body = IsochroneResponse),
+        (status = 200, description = "Isochrone computed (IsochroneFeatureCollection with thresholds)", body = IsochroneResponse),
         (status = 400, description = "Bad request", body = ErrorResponse),
     )
 )]
@@ -817,15 +1115,25 @@ pub async fn isochrone_handler(
     Query(req): Query<IsochroneRequest>,
     headers: axum::http::HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = validate_coord(req.lon, req.lat, "center") {
-        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
-    }
+    let origins = match isochrone_origins(&req) {
+        Ok(o) => o,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+        }
+    };
+    let multi_origin = origins.len() > 1;
 
     // Region dispatch (#91): the isochrone origin determines the
     // region. Reachable polygon stays inside that region — cross-
     // region reachability is part of the cross-region overlay (PR C).
+    // A union's origins must all be in one region.
     let started_dispatch = std::time::Instant::now();
-    let (state, region_id) = match regions.dispatch_single_id(req.lon, req.lat, &req.mode) {
+    let dispatched = if multi_origin {
+        regions.dispatch_many(origins.iter().map(|o| (o[0], o[1])), &req.mode)
+    } else {
+        regions.dispatch_single_id(origins[0][0], origins[0][1], &req.mode)
+    };
+    let (state, region_id) = match dispatched {
         Ok(pair) => pair,
         Err(e) => {
             let (code, body) = e.into_response_parts();
@@ -834,7 +1142,8 @@ pub async fn isochrone_handler(
     };
     let _: &Arc<ServerState> = &state;
 
-    // Determine isochrone metric: exactly one of {time_s, contours}.
+    // Determine isochrone metric: exactly one of {time_s, contours,
+    // thresholds}. `thresholds` is `contours` answered as GeoJSON.
     // The pre-#371 `distance_m` (isodistance) variant was removed — that
     // mode ran PHAST on a separate distance-shortest CCH metric, which
     // produced reachability sets along a different geometric path from
@@ -845,16 +1154,20 @@ pub async fn isochrone_handler(
         MultiTime(Vec<u32>), // sorted thresholds in seconds
     }
 
-    let provided = [req.time_s.is_some(), req.contours.is_some()]
-        .iter()
-        .filter(|&&b| b)
-        .count();
+    let provided = [
+        req.time_s.is_some(),
+        req.contours.is_some(),
+        req.thresholds.is_some(),
+    ]
+    .iter()
+    .filter(|&&b| b)
+    .count();
 
     if provided != 1 {
         return (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
-                error: "Provide exactly one of: time_s or contours".to_string(),
+                error: "Provide exactly one of: time_s, contours or thresholds".to_string(),
             }),
         )
             .into_response();
@@ -871,7 +1184,12 @@ pub async fn isochrone_handler(
                 .into_response();
         }
         IsoMetric::Time(t) // seconds (post-#297; weights are also in s)
-    } else if let Some(ref contours_str) = req.contours {
+    } else if let Some((name, contours_str)) = req
+        .contours
+        .as_deref()
+        .map(|c| ("contours", c))
+        .or(req.thresholds.as_deref().map(|t| ("thresholds", t)))
+    {
         let mut values = Vec::new();
         for part in contours_str.split(',') {
             let part = part.trim();
@@ -901,7 +1219,7 @@ pub async fn isochrone_handler(
             return (
                 StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
-                    error: format!("contours must have 1-10 values, got {}", values.len()),
+                    error: format!("{} must have 1-10 values, got {}", name, values.len()),
                 }),
             )
                 .into_response();
@@ -929,8 +1247,8 @@ pub async fn isochrone_handler(
             .into_response();
     };
 
-    let mode = match parse_mode(&req.mode, &state.mode_lookup) {
-        Ok(m) => m,
+    let (mode, weighting) = match isochrone_weighting(&req.mode, req.weighting.as_deref(), &state) {
+        Ok(pair) => pair,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
         }
@@ -940,11 +1258,16 @@ pub async fn isochrone_handler(
     let bands_requested = match req.uncertainty.as_deref() {
         None => false,
         Some("bands") => {
-            if req.mode != "car" || req.avoid_polygons.is_some() || req.exclude.is_some() {
+            if req.mode != "car"
+                || weighting != Weighting::Fastest
+                || req.avoid_polygons.is_some()
+                || req.exclude.is_some()
+                || multi_origin
+            {
                 return (
                     StatusCode::BAD_REQUEST,
                     Json(ErrorResponse {
-                        error: "uncertainty=bands is car-only and incompatible with avoid_polygons/exclude".to_string(),
+                        error: "uncertainty=bands is car-only, single-origin and incompatible with avoid_polygons/exclude/weighting other than fastest".to_string(),
                     }),
                 )
                     .into_response();
@@ -962,12 +1285,22 @@ pub async fn isochrone_handler(
         }
     };
 
+    // A FeatureCollection is GeoJSON whatever `geometries` says, and so is
+    // a union: its disconnected parts only fit `multipolygon_geojson`.
+    let feature_collection = req.thresholds.is_some();
     let geom_format = match GeometryFormat::parse(&req.geometries) {
+        Ok(_) if feature_collection || multi_origin => GeometryFormat::GeoJson,
         Ok(f) => f,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
         }
     };
+    let generalization = match isochrone_generalization(&req) {
+        Ok(g) => g,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+        }
+    };
 
     let reverse = match req.direction.to_lowercase().as_str() {
         "depart" => false,
@@ -984,7 +1317,7 @@ pub async fn isochrone_handler(
     };
 
     // Parse exclude parameter
-    let exclude_mask = match super::exclude::parse_exclude_option(&req.exclude) {
+    let exclude_mask = match state.parse_exclude(&req.exclude) {
         Ok(m) => m,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
@@ -1063,57 +1396,75 @@ pub async fn isochrone_handler(
     };
     let center_role_filter = center_role.role_filter(&mode_data);
 
-    let center_orig = match state.snap_index.snap_filtered_role(
-        req.lon,
-        req.lat,
-        mode.0,
-        Some(&snap_mask),
-        center_role_filter,
-    ) {
-        Some(id) => id,
-        None => {
+    // A union seeds every origin into the one PHAST run: settled costs are
+    // the minimum over the origins, so each contour traces the union of
+    // their reachable areas in a single stamped grid. The first origin
+    // anchors the primary part.
+    let mut center_seeds: Vec<(u32, u32)> = Vec::new();
+    let mut center_anchor: Option<(f64, f64)> = None;
+    for (i, &[lon, lat]) in origins.iter().enumerate() {
+        let label = if multi_origin {
+            format!("origin {}", i)
+        } else {
+            "center".to_string()
+        };
+        let center_orig = match state.snap_index.snap_filtered_role(
+            lon,
+            lat,
+            mode.0,
+            Some(&snap_mask),
+            center_role_filter,
+        ) {
+            Some(id) => id,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: format!("Could not snap {} to road network", label),
+                    }),
+                )
+                    .into_response();
+            }
+        };
+
+        let center_rank = mode_data.orig_to_rank[center_orig as usize];
+        if center_rank == u32::MAX {
             return (
                 StatusCode::BAD_REQUEST,
                 Json(ErrorResponse {
-                    error: "Could not snap center to road network".to_string(),
+                    error: format!("{} not accessible for this mode", label),
                 }),
             )
                 .into_response();
         }
-    };
 
-    let center_rank = mode_data.orig_to_rank[center_orig as usize];
-    if center_rank == u32::MAX {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Center not accessible for this mode".to_string(),
-            }),
-        )
-            .into_response();
+        // #506: phantom center — seed both directed twins (and
+        // near-equidistant parallel edges) so the polygon isn't committed
+        // to one departure/arrival direction of the snapped edge. Depart
+        // seeds cost the REMAINDER of the edge (part_time); arrive seeds
+        // cost the ENTRY-to-snap part (w - part). Custom-weight paths
+        // (avoid/exclude) keep the legacy single seed.
+        let (seeds, anchor) = if avoid_entry.is_none() && exclude_mask.is_none() {
+            super::phantom::isochrone_center_seeds(
+                &state,
+                &mode_data,
+                mode,
+                lon,
+                lat,
+                center_role,
+                Some(&snap_mask),
+                reverse,
+                center_rank,
+            )
+        } else {
+            (vec![(center_rank, 0)], None)
+        };
+        center_seeds.extend(seeds);
+        if i == 0 {
+            center_anchor = anchor;
+        }
     }
 
-    // #506: phantom center — seed both directed twins (and near-equidistant
-    // parallel edges) so the polygon isn't committed to one departure/arrival
-    // direction of the snapped edge. Depart seeds cost the REMAINDER of the
-    // edge (part_time); arrive seeds cost the ENTRY-to-snap part (w - part).
-    // Custom-weight paths (avoid/exclude) keep the legacy single seed.
-    let (center_seeds, center_anchor) = if avoid_entry.is_none() && exclude_mask.is_none() {
-        super::phantom::isochrone_center_seeds(
-            &state,
-            &mode_data,
-            mode,
-            req.lon,
-            req.lat,
-            center_role,
-            Some(&snap_mask),
-            reverse,
-            center_rank,
-        )
-    } else {
-        (vec![(center_rank, 0)], None)
-    };
-
     // Get custom weights (avoid takes priority, then exclude)
     let exclude_weights = if avoid_entry.is_none() {
         exclude_mask.map(|exc| state.get_exclude_weights(mode, exc))
@@ -1170,9 +1521,10 @@ pub async fn isochrone_handler(
         settled.push((original_id, dist));
     }
 
-    // Helper: build polygon for a single contour threshold from the settled set
-    let build_contour_polygon = |threshold: u32| -> Vec<Point> {
-        build_isochrone_geometry(
+    // Helper: build the polygons (`[outer, holes...]`, origin's first) for a
+    // single contour threshold from the settled set
+    let build_contour_polygons = |threshold: u32| -> Vec<Vec<Vec<Point>>> {
+        build_isochrone_polygons(
             &settled,
             threshold,
             node_weights,
@@ -1180,6 +1532,7 @@ pub async fn isochrone_handler(
             &state.edge_geom,
             &req.mode,
             center_anchor,
+            &generalization,
         )
     };
 
@@ -1190,22 +1543,7 @@ pub async fn isochrone_handler(
      -> (Option<String>, Option<Vec<[f64; 2]>>, Option<Vec<Point>>) {
         match format {
             GeometryFormat::Polyline6 => (Some(encode_polyline6(polygon)), None, None),
-            GeometryFormat::GeoJson => {
-                use crate::range::wkb_stream::ensure_ccw;
-                let trunc = |v: f64| (v * 1e5).round() / 1e5;
-                let mut coords: Vec<(f64, f64)> = polygon
-                    .iter()
-                    .map(|p| (trunc(p.lon), trunc(p.lat)))
-                    .collect();
-                ensure_ccw(&mut coords);
-                let mut ring: Vec<[f64; 2]> = coords.into_iter().map(|(x, y)| [x, y]).collect();
-                if let (Some(first), Some(last)) = (ring.first().copied(), ring.last().copied())
-                    && first != last
-                {
-                    ring.push(first);
-                }
-                (None, Some(ring), None)
-            }
+            GeometryFormat::GeoJson => (None, Some(geojson_ring(polygon, false)), None),
             GeometryFormat::Points => (None, None, Some(polygon.to_vec())),
         }
     };
@@ -1229,7 +1567,6 @@ pub async fn isochrone_handler(
             )
                 .into_response();
         }
-        use crate::range::contour::ContourResult;
         use crate::range::wkb_stream::encode_polygon_wkb;
 
         if thresholds.len() > 1 {
@@ -1241,13 +1578,17 @@ pub async fn isochrone_handler(
             )
                 .into_response();
         }
-        let polygon = build_contour_polygon(thresholds[0].0);
-        let coords: Vec<(f64, f64)> = polygon.iter().map(|p| (p.lon, p.lat)).collect();
-        let contour = ContourResult {
-            outer_ring: coords,
-            holes: vec![],
-            stats: Default::default(),
-        };
+        if multi_origin {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "WKB only supports a single origin. Use JSON for origins.".to_string(),
+                }),
+            )
+                .into_response();
+        }
+        let polygons = build_contour_polygons(thresholds[0].0);
+        let contour = primary_contour_result(&polygons);
         super::region_metrics::record_query(
             &region_id,
             "isochrone",
@@ -1267,13 +1608,16 @@ pub async fn isochrone_handler(
     let contour_features: Vec<ContourFeature> = thresholds
         .iter()
         .map(|&(threshold, time_s)| {
-            let polygon = build_contour_polygon(threshold);
+            let polygons = build_contour_polygons(threshold);
+            let polygon = primary_outer_ring(&polygons);
             let reachable = settled.iter().filter(|&&(_, d)| d <= threshold).count();
-            let (poly_enc, poly_geo, poly_pts) = encode_polygon(&polygon, geom_format);
+            let (poly_enc, poly_geo, poly_pts) = encode_polygon(polygon, geom_format);
             ContourFeature {
                 time_s,
                 polygon: poly_enc,
                 polygon_geojson: poly_geo,
+                multipolygon_geojson: (geom_format == GeometryFormat::GeoJson)
+                    .then(|| geojson_multipolygon(&polygons)),
                 polygon_points: poly_pts,
                 reachable_edges: reachable,
                 band: None,
@@ -1300,10 +1644,12 @@ pub async fn isochrone_handler(
                 &state,
                 band_mode,
                 &req,
+                origins[0],
                 reverse,
                 &thresholds,
                 phast_threshold,
                 geom_format,
+                &generalization,
                 tag,
             ) {
                 Some(mut feats) => contour_features.append(&mut feats),
@@ -1338,6 +1684,14 @@ pub async fn isochrone_handler(
         "isochrone",
         started_dispatch.elapsed().as_secs_f64(),
     );
+    if feature_collection {
+        return Json(IsochroneFeatureCollection::from_contours(
+            &contour_features,
+            network,
+            phast_threshold,
+        ))
+        .into_response();
+    }
     Json(IsochroneResponse {
         contours: contour_features,
         network,
@@ -1345,19 +1699,76 @@ pub async fn isochrone_handler(
     .into_response()
 }
 
+/// Outer ring of the origin's polygon (empty when nothing is reachable).
+fn primary_outer_ring(polygons: &[Vec<Vec<Point>>]) -> &[Point] {
+    polygons
+        .first()
+        .and_then(|rings| rings.first())
+        .map_or(&[][..], Vec::as_slice)
+}
+
+/// The origin's polygon with its holes, for WKB output (a WKB Polygon has
+/// no room for the other parts).
+fn primary_contour_result(polygons: &[Vec<Vec<Point>>]) -> ContourResult {
+    let coords =
+        |ring: &Vec<Point>| -> Vec<(f64, f64)> { ring.iter().map(|p| (p.lon, p.lat)).collect() };
+    let rings = polygons.first().map_or(&[][..], Vec::as_slice);
+    ContourResult {
+        outer_ring: rings.first().map(coords).unwrap_or_default(),
+        holes: rings.iter().skip(1).map(coords).collect(),
+        stats: Default::default(),
+    }
+}
+
+/// Closed GeoJSON ring, coordinates truncated to 1e-5: counter-clockwise
+/// for an outer ring, clockwise for a hole (RFC 7946).
+fn geojson_ring(ring: &[Point], hole: bool) -> Vec<[f64; 2]> {
+    use crate::range::wkb_stream::{ensure_ccw, ensure_cw};
+    let trunc = |v: f64| (v * 1e5).round() / 1e5;
+    let mut coords: Vec<(f64, f64)> = ring.iter().map(|p| (trunc(p.lon), trunc(p.lat))).collect();
+    if hole {
+        ensure_cw(&mut coords);
+    } else {
+        ensure_ccw(&mut coords);
+    }
+    let mut ring: Vec<[f64; 2]> = coords.into_iter().map(|(x, y)| [x, y]).collect();
+    if let (Some(first), Some(last)) = (ring.first().copied(), ring.last().copied())
+        && first != last
+    {
+        ring.push(first);
+    }
+    ring
+}
+
+/// GeoJSON MultiPolygon coordinates of [`build_isochrone_polygons`] output.
+fn geojson_multipolygon(polygons: &[Vec<Vec<Point>>]) -> Vec<Vec<Vec<[f64; 2]>>> {
+    polygons
+        .iter()
+        .map(|rings| {
+            rings
+                .iter()
+                .enumerate()
+                .map(|(i, ring)| geojson_ring(ring, i > 0))
+                .collect()
+        })
+        .collect()
+}
+
 /// #521: contour features for ONE hidden band weight set — a compact replay
 /// of the plain-path core (snap -> phantom center seeds -> seeded PHAST ->
 /// contour) against the band's ModeData. Plain path only by construction
-/// (bands reject avoid/exclude upstream).
+/// (bands reject avoid/exclude and unions upstream).
 #[allow(clippy::too_many_arguments)]
 fn band_isochrone_features(
     state: &ServerState,
     band: crate::profile_abi::Mode,
     req: &IsochroneRequest,
+    [lon, lat]: [f64; 2],
     reverse: bool,
     thresholds: &[(u32, Option<u32>)],
     phast_threshold: u32,
     geom_format: GeometryFormat,
+    generalization: &ContourGeneralization,
     tag: &'static str,
 ) -> Option<Vec<ContourFeature>> {
     let md = state.get_mode(band);
@@ -1367,10 +1778,9 @@ fn band_isochrone_features(
         SnapRole::Src
     };
     let rf = role.role_filter(&md);
-    let center =
-        state
-            .snap_index
-            .snap_filtered_role(req.lon, req.lat, band.0, Some(&md.mask), rf)?;
+    let center = state
+        .snap_index
+        .snap_filtered_role(lon, lat, band.0, Some(&md.mask), rf)?;
     let center_rank = md.orig_to_rank[center as usize];
     if center_rank == u32::MAX {
         return None;
@@ -1379,8 +1789,8 @@ fn band_isochrone_features(
         state,
         &md,
         band,
-        req.lon,
-        req.lat,
+        lon,
+        lat,
         role,
         Some(&md.mask),
         reverse,
@@ -1410,7 +1820,7 @@ fn band_isochrone_features(
     }
     let mut out = Vec::with_capacity(thresholds.len());
     for &(threshold, time_s) in thresholds {
-        let polygon = build_isochrone_geometry(
+        let polygons = build_isochrone_polygons(
             &settled,
             threshold,
             &md.node_weights,
@@ -1418,7 +1828,9 @@ fn band_isochrone_features(
             &state.edge_geom,
             &req.mode,
             anchor,
+            generalization,
         );
+        let polygon = primary_outer_ring(&polygons).to_vec();
         let reachable = settled.iter().filter(|&&(_, d)| d <= threshold).count();
         let (poly_enc, poly_geo, poly_pts) = match geom_format {
             GeometryFormat::Polyline6 => (Some(encode_polyline6(&polygon)), None, None),
@@ -1442,6 +1854,8 @@ fn band_isochrone_features(
             time_s,
             polygon: poly_enc,
             polygon_geojson: poly_geo,
+            multipolygon_geojson: (geom_format == GeometryFormat::GeoJson)
+                .then(|| geojson_multipolygon(&polygons)),
             polygon_points: poly_pts,
             reachable_edges: reachable,
             band: Some(tag),
@@ -1520,15 +1934,18 @@ pub fn build_network_geometry(
 ///
 /// Returns a binary stream of WKB polygons with length-prefixed format:
 /// For each isochrone: [4 bytes: origin_idx as u32][4 bytes: wkb_len as u32][wkb_len bytes: WKB]
+///
+/// With `format=geojson-seq`, one GeoJSON Feature per line instead.
 #[utoipa::path(
     post,
     path = "/isochrone/bulk",
     tag = "Isochrone",
     summary = "Compute multiple isochrones in parallel",
-    description = "Computes isochrones for multiple origins in parallel using rayon + PHAST.\nReturns a binary stream of WKB polygons with length-prefixed framing.\n\nBinary format per isochrone:\n- 4 bytes: origin index (u32 LE)\n- 4 bytes: WKB length (u32 LE)\n- N bytes: WKB polygon\n\nMaximum 10,000 origins. Supports cooperative cancellation on client disconnect.",
+    description = "Computes isochrones for multiple origins in parallel using rayon + PHAST.\nReturns a binary stream of WKB polygons with length-prefixed framing.\n\nBinary format per isochrone:\n- 4 bytes: origin index (u32 LE)\n- 4 bytes: WKB length (u32 LE)\n- N bytes: WKB polygon\n\n`format=geojson-seq` returns newline-delimited GeoJSON Features instead (MultiPolygon geometry, `origin_index` / `threshold` / `reachable_edges` properties), ready for ogr2ogr or QGIS.\n\nMaximum 10,000 origins. Supports cooperative cancellation on client disconnect.",
     request_body(content = BulkIsochroneRequest, description = "Origins, time limit, and mode"),
     responses(
         (status = 200, description = "Binary WKB stream", content_type = "application/octet-stream"),
+        (status = 200, description = "Newline-delimited GeoJSON Features (format=geojson-seq)", content_type = "application/geo+json-seq"),
         (status = 400, description = "Bad request"),
     )
 )]
@@ -1536,9 +1953,6 @@ pub async fn isochrone_bulk_handler(
     State(regions): State<Arc<RegionsState>>,
     Json(req): Json<BulkIsochroneRequest>,
 ) -> impl IntoResponse {
-    use crate::range::contour::ContourResult;
-    use crate::range::wkb_stream::encode_polygon_wkb;
-
     if req.origins.is_empty() {
         return (
             StatusCode::BAD_REQUEST,
@@ -1576,6 +1990,12 @@ pub async fn isochrone_bulk_handler(
         )
             .into_response();
     }
+    let format = match BulkIsochroneFormat::parse(req.format.as_deref()) {
+        Ok(f) => f,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+        }
+    };
 
     // Region dispatch (#91): every origin must snap to the same
     // region. Mixed-region bulk is rejected with 501 — same rule as
@@ -1590,15 +2010,15 @@ pub async fn isochrone_bulk_handler(
         }
     };
 
-    let mode = match parse_mode(&req.mode, &state.mode_lookup) {
-        Ok(m) => m,
+    let (mode, _) = match isochrone_weighting(&req.mode, req.weighting.as_deref(), &state) {
+        Ok(pair) => pair,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
         }
     };
 
     // Parse exclude parameter
-    let exclude_mask = match super::exclude::parse_exclude_option(&req.exclude) {
+    let exclude_mask = match state.parse_exclude(&req.exclude) {
         Ok(m) => m,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
@@ -1666,7 +2086,7 @@ pub async fn isochrone_bulk_handler(
     let origin_role_filter = SnapRole::Src.role_filter(&mode_data);
 
     // Process all origins in parallel
-    let results: Vec<(u32, Vec<u8>)> = req
+    let results: Vec<Vec<u8>> = req
         .origins
         .par_iter()
         .enumerate()
@@ -1717,7 +2137,7 @@ pub async fn isochrone_bulk_handler(
             }
 
             // Build polygon using frontier-based concave hull
-            let points = build_isochrone_geometry(
+            let polygons = build_isochrone_polygons(
                 &settled,
                 time_s,
                 &mode_data.node_weights,
@@ -1725,28 +2145,19 @@ pub async fn isochrone_bulk_handler(
                 &state.edge_geom,
                 &req.mode,
                 center_anchor,
+                &ContourGeneralization::default(),
             );
-            let outer_ring: Vec<(f64, f64)> = points.iter().map(|p| (p.lon, p.lat)).collect();
-            let contour = ContourResult {
-                outer_ring,
-                holes: vec![],
-                stats: Default::default(),
-            };
-
-            // Encode WKB
-            encode_polygon_wkb(&contour).map(|wkb| (idx as u32, wkb))
+
+            // Encode this origin's record
+            let reachable = settled.iter().filter(|&&(_, d)| d <= time_s).count();
+            format.encode(idx, time_s, reachable, &polygons)
         })
         .collect();
 
-    // Build response: concatenated length-prefixed WKB
+    // Build response: concatenated records
     let n_total_origins = req.origins.len();
     let n_successful = results.len();
-    let mut response = Vec::with_capacity(results.len() * 500);
-    for (origin_idx, wkb) in results {
-        response.extend_from_slice(&origin_idx.to_le_bytes());
-        response.extend_from_slice(&(wkb.len() as u32).to_le_bytes());
-        response.extend_from_slice(&wkb);
-    }
+    let response = results.concat();
 
     super::region_metrics::record_query(
         &region_id,
@@ -1756,7 +2167,7 @@ pub async fn isochrone_bulk_handler(
 
     Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_TYPE, format.content_type())
         // Progress tracking headers
         .header("X-Total-Origins", n_total_origins.to_string())
         .header("X-Successful-Isochrones", n_successful.to_string())
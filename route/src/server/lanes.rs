@@ -0,0 +1,214 @@
+//! Lane guidance from OSM `turn:lanes` tags
+//!
+//! Step 1 keeps every tag of a routable way in ways.raw, so the server
+//! reads `turn:lanes` (and its `:forward` / `:backward` variants) next to
+//! the road names at boot. Each lane is stored as a bitmask of its
+//! indications, left to right in the direction of travel.
+
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Lane indications in bit order. `none` is an untagged lane (`||`).
+const INDICATIONS: [&str; 11] = [
+    "none",
+    "through",
+    "left",
+    "slight_left",
+    "sharp_left",
+    "right",
+    "slight_right",
+    "sharp_right",
+    "reverse",
+    "merge_to_left",
+    "merge_to_right",
+];
+
+const NONE: u16 = 1 << 0;
+const THROUGH: u16 = 1 << 1;
+const LEFTS: u16 = 0b111 << 2;
+const RIGHTS: u16 = 0b111 << 5;
+const REVERSE: u16 = 1 << 8;
+
+/// Per-direction lane masks of one OSM way.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WayLanes {
+    /// Lanes travelling along the way's node order.
+    pub forward: Vec<u16>,
+    /// Lanes travelling against it.
+    pub backward: Vec<u16>,
+}
+
+impl WayLanes {
+    pub fn direction(&self, forward: bool) -> &[u16] {
+        if forward {
+            &self.forward
+        } else {
+            &self.backward
+        }
+    }
+}
+
+/// One lane of a step's approach, as rendered in `StepManeuver.lanes`.
+#[derive(Debug, Clone, Serialize, ToSchema, PartialEq)]
+pub struct StepLane {
+    /// OSM turn indications of the lane (e.g. ["left", "through"])
+    pub indications: Vec<String>,
+    /// Whether the lane can be used for this step's maneuver
+    pub valid: bool,
+}
+
+/// Parse a `turn:lanes` value (`left|through;right|`) into lane masks.
+/// Unknown indications are ignored; an empty lane is `none`.
+pub fn parse_turn_lanes(value: &str) -> Vec<u16> {
+    value
+        .split('|')
+        .map(|lane| {
+            let mask = lane
+                .split(';')
+                .filter_map(|ind| INDICATIONS.iter().position(|&known| known == ind.trim()))
+                .fold(0u16, |m, bit| m | (1 << bit));
+            if mask == 0 { NONE } else { mask }
+        })
+        .collect()
+}
+
+/// Lanes of a way from its tags: `turn:lanes:forward` (or plain
+/// `turn:lanes`, which OSM applies to the forward direction of oneways)
+/// and `turn:lanes:backward`. `None` when the way has neither.
+pub fn way_lanes_from_tags<'a>(
+    tags: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Option<WayLanes> {
+    let (mut plain, mut forward, mut backward) = (None, None, None);
+    for (k, v) in tags {
+        match k {
+            "turn:lanes" => plain = Some(v),
+            "turn:lanes:forward" => forward = Some(v),
+            "turn:lanes:backward" => backward = Some(v),
+            _ => {}
+        }
+    }
+    let forward = forward.or(plain);
+    if forward.is_none() && backward.is_none() {
+        return None;
+    }
+    Some(WayLanes {
+        forward: forward.map(parse_turn_lanes).unwrap_or_default(),
+        backward: backward.map(parse_turn_lanes).unwrap_or_default(),
+    })
+}
+
+/// Build the way → lanes table from a ways.raw stream and its dictionaries.
+pub fn collect_way_lanes(
+    key_dict: &HashMap<u32, String>,
+    val_dict: &HashMap<u32, String>,
+    ways: impl Iterator<Item = Result<(i64, Vec<u32>, Vec<u32>, Vec<i64>)>>,
+) -> Result<HashMap<i64, WayLanes>> {
+    let lane_keys: Vec<u32> = key_dict
+        .iter()
+        .filter(|(_, k)| k.starts_with("turn:lanes"))
+        .map(|(&id, _)| id)
+        .collect();
+    let mut lanes = HashMap::new();
+    if lane_keys.is_empty() {
+        return Ok(lanes);
+    }
+    for way in ways {
+        let (way_id, keys, vals, _nodes) = way?;
+        let tags = keys
+            .iter()
+            .zip(&vals)
+            .filter(|(k, _)| lane_keys.contains(k));
+        let tags =
+            tags.filter_map(|(k, v)| Some((key_dict.get(k)?.as_str(), val_dict.get(v)?.as_str())));
+        if let Some(l) = way_lanes_from_tags(tags) {
+            lanes.insert(way_id, l);
+        }
+    }
+    Ok(lanes)
+}
+
+/// Indications a lane must carry to serve a turn `modifier` (as produced
+/// by [`classify_turn`](super::route::classify_turn)).
+fn modifier_mask(modifier: &str) -> u16 {
+    match modifier {
+        "straight" => THROUGH | NONE,
+        "slight left" => 1 << 3,
+        "left" => 1 << 2,
+        "sharp left" => 1 << 4,
+        "slight right" => 1 << 6,
+        "right" => 1 << 5,
+        "sharp right" => 1 << 7,
+        "uturn" => REVERSE,
+        _ => 0,
+    }
+}
+
+/// Render `lanes` for a maneuver with `modifier`. A lane is valid when it
+/// carries the modifier's own indication; when no lane does, any lane
+/// turning to the same side (or through, for a slight turn) counts.
+pub fn step_lanes(lanes: &[u16], modifier: &str) -> Vec<StepLane> {
+    let exact = modifier_mask(modifier);
+    let side = match modifier {
+        "slight left" => LEFTS | THROUGH,
+        "slight right" => RIGHTS | THROUGH,
+        m if m.contains("left") => LEFTS,
+        m if m.contains("right") => RIGHTS,
+        "uturn" => REVERSE | (1 << 4),
+        _ => exact,
+    };
+    let mask = if lanes.iter().any(|&l| l & exact != 0) {
+        exact
+    } else {
+        side
+    };
+    lanes
+        .iter()
+        .map(|&l| StepLane {
+            indications: INDICATIONS
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| l & (1 << bit) != 0)
+                .map(|(_, ind)| ind.to_string())
+                .collect(),
+            valid: l & mask != 0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_turn_lanes_per_direction() {
+        let lanes = way_lanes_from_tags([
+            ("highway", "primary"),
+            ("turn:lanes:forward", "left|through;right|"),
+            ("turn:lanes:backward", "reverse;left|bogus"),
+        ])
+        .unwrap();
+        assert_eq!(lanes.forward, vec![1 << 2, THROUGH | (1 << 5), NONE]);
+        assert_eq!(lanes.backward, vec![REVERSE | (1 << 2), NONE]);
+
+        let oneway = way_lanes_from_tags([("turn:lanes", "left|through")]).unwrap();
+        assert_eq!(oneway.direction(true).len(), 2);
+        assert!(oneway.direction(false).is_empty());
+        assert!(way_lanes_from_tags([("highway", "residential")]).is_none());
+    }
+
+    #[test]
+    fn marks_lanes_valid_for_the_maneuver() {
+        let lanes = parse_turn_lanes("left|left;through|through|right");
+        let valid =
+            |m: &str| -> Vec<bool> { step_lanes(&lanes, m).iter().map(|l| l.valid).collect() };
+        assert_eq!(valid("left"), vec![true, true, false, false]);
+        assert_eq!(valid("straight"), vec![false, true, true, false]);
+        assert_eq!(valid("sharp right"), vec![false, false, false, true]);
+        // No slight_left lane: fall back to lanes on the left or through.
+        assert_eq!(valid("slight left"), vec![true, true, true, false]);
+        let rendered = step_lanes(&lanes, "left");
+        assert_eq!(rendered[1].indications, vec!["through", "left"]);
+    }
+}
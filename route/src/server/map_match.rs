@@ -4,6 +4,12 @@
 //! - Emission probability: Gaussian on perpendicular GPS-to-edge distance
 //! - Transition probability: exponential on |route_distance - great_circle_distance|
 //! - Route distance: sum of physical edge lengths (length_m) along the fastest path
+//!
+//! Optional per-observation timestamps drop transitions no vehicle could
+//! drive in the time between two samples and split the trace at long time
+//! gaps; per-observation radii replace the trace-wide GPS accuracy. When
+//! no candidate of an observation is reachable from the previous one, the
+//! trace is split there into separate matchings.
 
 use std::sync::Arc;
 
@@ -34,6 +40,20 @@ const BETA: f64 = 30.0;
 /// Gap distance (meters) — if consecutive GPS points are this far apart, break the trace
 const GAP_THRESHOLD_M: f64 = 2000.0;
 
+/// Gap time (seconds) — if consecutive timestamps are this far apart, break the trace
+const GAP_THRESHOLD_S: i64 = 60;
+
+/// Speed (m/s, ~200 km/h) no trace is expected to exceed between two
+/// timestamped observations.
+const MAX_PLAUSIBLE_SPEED_MPS: f64 = 55.0;
+
+/// Route-distance slack (meters) on the speed check: transition distances
+/// count the first and last edges in full (see [`compute_transition_distances`]).
+const SPEED_CHECK_SLACK_M: f64 = 250.0;
+
+/// A per-observation radius keeps candidates within this many radii.
+const RADIUS_CANDIDATE_SIGMAS: f64 = 3.0;
+
 /// Approximate meters per degree at Belgian latitudes
 const METERS_PER_DEG_LAT: f64 = 111_000.0;
 const METERS_PER_DEG_LON_AT_50: f64 = 71_400.0;
@@ -45,6 +65,73 @@ const NEG_INF: f64 = f64::NEG_INFINITY;
 // Public types
 // ---------------------------------------------------------------------------
 
+/// Per-observation inputs of a trace beyond its coordinates
+#[derive(Debug, Clone, Default)]
+pub struct TraceOptions {
+    /// GPS noise standard deviation (meters) of observations without a
+    /// radius of their own (default: 10)
+    pub gps_accuracy: Option<f64>,
+    /// Accuracy radius (meters) per observation, `None` entries use
+    /// `gps_accuracy`. A radius also drops candidates farther than three
+    /// radii from the observation.
+    pub radiuses: Option<Vec<Option<f64>>>,
+    /// UNIX timestamp (seconds, non-decreasing) per observation
+    pub timestamps: Option<Vec<i64>>,
+}
+
+impl TraceOptions {
+    pub fn with_gps_accuracy(gps_accuracy: f64) -> Self {
+        Self {
+            gps_accuracy: Some(gps_accuracy),
+            ..Self::default()
+        }
+    }
+
+    fn radius(&self, obs: usize) -> Option<f64> {
+        self.radiuses
+            .as_ref()
+            .and_then(|r| r.get(obs).copied().flatten())
+    }
+
+    /// Emission standard deviation (meters) of observation `obs`
+    fn sigma(&self, obs: usize) -> f64 {
+        self.radius(obs)
+            .or(self.gps_accuracy)
+            .unwrap_or(DEFAULT_GPS_SIGMA)
+            .max(1.0)
+    }
+
+    /// Whether a candidate `distance_m` from observation `obs` is within its radius
+    fn within_radius(&self, obs: usize, distance_m: f64) -> bool {
+        self.radius(obs)
+            .is_none_or(|r| distance_m <= RADIUS_CANDIDATE_SIGMAS * r)
+    }
+
+    /// Seconds from observation `from` to `to` (None without timestamps)
+    fn elapsed_s(&self, from: usize, to: usize) -> Option<i64> {
+        let ts = self.timestamps.as_ref()?;
+        Some(ts.get(to)? - ts.get(from)?)
+    }
+
+    /// Whether the trace breaks between observations `prev` and `obs`
+    fn is_gap(&self, coordinates: &[(f64, f64)], prev: usize, obs: usize) -> bool {
+        let (lon1, lat1) = coordinates[prev];
+        let (lon2, lat2) = coordinates[obs];
+        great_circle_m(lon1, lat1, lon2, lat2) > GAP_THRESHOLD_M
+            || self
+                .elapsed_s(prev, obs)
+                .is_some_and(|dt| dt > GAP_THRESHOLD_S)
+    }
+
+    /// Whether `route_dist_m` can be covered between observations `from`
+    /// and `to` (always true without timestamps)
+    fn speed_plausible(&self, from: usize, to: usize, route_dist_m: f64) -> bool {
+        self.elapsed_s(from, to).is_none_or(|dt| {
+            route_dist_m <= MAX_PLAUSIBLE_SPEED_MPS * dt as f64 + SPEED_CHECK_SLACK_M
+        })
+    }
+}
+
 /// A single candidate match for a GPS observation
 #[derive(Debug, Clone)]
 struct Candidate {
@@ -70,7 +157,9 @@ pub struct Matching {
     pub ebg_path: Vec<u32>,
     /// Total duration in seconds (post-#297; was deciseconds).
     pub duration_s: u32,
-    /// Confidence score (0.0 to 1.0) — average emission probability
+    /// Confidence score (0.0 to 1.0) — normalized HMM likelihood: the
+    /// per-observation geometric mean of the emission and transition
+    /// probabilities along the matched path
     pub confidence: f64,
     /// Region index in [`super::regions::RegionsState::regions`] this
     /// matching's `ebg_path` lives in. EBG ids are region-local: each
@@ -88,6 +177,8 @@ pub struct Tracepoint {
     pub lon: f64,
     pub lat: f64,
     pub ebg_id: u32,
+    /// Distance (meters) from the observation to the matched position
+    pub distance_m: f64,
     /// Index into matchings array
     pub matchings_index: usize,
     /// Index within the matching's waypoint sequence
@@ -105,7 +196,7 @@ pub fn map_match(
     state: &ServerState,
     mode: Mode,
     coordinates: &[(f64, f64)], // (lon, lat)
-    gps_accuracy: Option<f64>,
+    options: &TraceOptions,
     snap_mask: Option<&[u64]>,
     exclude_weights: Option<&CchWeights>,
 ) -> Option<MatchResult> {
@@ -115,44 +206,62 @@ pub fn map_match(
     }
 
     let mode_data = state.get_mode(mode);
-    let sigma = gps_accuracy.unwrap_or(DEFAULT_GPS_SIGMA).max(1.0);
     let mask = snap_mask.unwrap_or(&mode_data.mask);
     let weights = exclude_weights.unwrap_or(&mode_data.cch_weights);
 
     // Step 1: Generate candidates for each observation
     let candidates: Vec<Vec<Candidate>> = coordinates
         .iter()
-        .map(|&(lon, lat)| generate_candidates(state, mode.0, mask, lon, lat))
+        .enumerate()
+        .map(|(i, &(lon, lat))| {
+            let mut cands = generate_candidates(state, mode.0, mask, lon, lat);
+            cands.retain(|c| options.within_radius(i, c.distance_m));
+            cands
+        })
         .collect();
 
     // Step 2: Find segments (split at gaps or unmatched observations)
-    let segments = find_segments(coordinates, &candidates);
+    let segments = find_segments(coordinates, &candidates, options);
 
     if segments.is_empty() {
         return None;
     }
 
-    // Step 3: Run Viterbi on each segment
+    // Step 3: Run Viterbi on each segment. A break in the trellis ends the
+    // matching there and decoding resumes at the first unreachable
+    // observation.
     let mut matchings = Vec::new();
     let mut tracepoints = vec![None; n];
 
     for segment in &segments {
-        let seg_coords: Vec<(f64, f64)> = segment.iter().map(|&i| coordinates[i]).collect();
-        let seg_candidates: Vec<&Vec<Candidate>> =
-            segment.iter().map(|&i| &candidates[i]).collect();
-
-        if let Some(matched_indices) = viterbi(
-            &mode_data,
-            &state.ebg_nodes,
-            &seg_coords,
-            &seg_candidates,
-            sigma,
-            weights,
-        ) {
+        let mut rest: &[usize] = segment;
+        while rest.len() >= 2 {
+            let seg_coords: Vec<(f64, f64)> = rest.iter().map(|&i| coordinates[i]).collect();
+            let seg_candidates: Vec<&Vec<Candidate>> =
+                rest.iter().map(|&i| &candidates[i]).collect();
+
+            let Some(decoded) = viterbi(
+                &mode_data,
+                &state.ebg_nodes,
+                &seg_coords,
+                &seg_candidates,
+                rest,
+                options,
+                weights,
+            ) else {
+                break;
+            };
+            let matched_indices = decoded.states.as_slice();
+            let (matched, next) = rest.split_at(matched_indices.len().max(1));
+            rest = next;
+            if matched.len() < 2 {
+                continue;
+            }
+
             // Build EBG path from matched candidate sequence
             let matching_idx = matchings.len();
             let ebg_path =
-                build_matched_path(&mode_data, &seg_candidates, &matched_indices, weights);
+                build_matched_path(&mode_data, &seg_candidates, matched_indices, weights);
 
             let duration_s = ebg_path
                 .iter()
@@ -160,32 +269,22 @@ pub fn map_match(
                 .filter(|&w| w != u32::MAX)
                 .sum::<u32>();
 
-            // Compute confidence as average emission probability
-            let avg_emission: f64 = matched_indices
-                .iter()
-                .enumerate()
-                .map(|(t, &c_idx)| {
-                    let dist = seg_candidates[t][c_idx].distance_m;
-                    emission_prob(dist, sigma)
-                })
-                .sum::<f64>()
-                / matched_indices.len() as f64;
-
             matchings.push(Matching {
                 ebg_path,
                 duration_s,
-                confidence: avg_emission.exp(), // Convert from log to [0,1]
-                region_idx: 0,                  // single-region path: caller's region is implicit
+                confidence: path_confidence(&decoded.step_log_probs),
+                region_idx: 0, // single-region path: caller's region is implicit
             });
 
             // Fill tracepoints
-            for (seg_pos, &obs_idx) in segment.iter().enumerate() {
+            for (seg_pos, &obs_idx) in matched.iter().enumerate() {
                 let c_idx = matched_indices[seg_pos];
                 let cand = &seg_candidates[seg_pos][c_idx];
                 tracepoints[obs_idx] = Some(Tracepoint {
                     lon: cand.snapped_lon,
                     lat: cand.snapped_lat,
                     ebg_id: cand.ebg_id,
+                    distance_m: cand.distance_m,
                     matchings_index: matching_idx,
                     waypoint_index: seg_pos,
                 });
@@ -312,14 +411,18 @@ fn fallback_midpoint() -> (f64, f64, f64) {
 // ---------------------------------------------------------------------------
 
 /// Split trace into continuous segments.
-/// Breaks at: observations with no candidates, or large GPS gaps.
-fn find_segments(coordinates: &[(f64, f64)], candidates: &[Vec<Candidate>]) -> Vec<Vec<usize>> {
+/// Breaks at: observations with no candidates, or large GPS distance / time gaps.
+fn find_segments(
+    coordinates: &[(f64, f64)],
+    candidates: &[Vec<Candidate>],
+    options: &TraceOptions,
+) -> Vec<Vec<usize>> {
     let n = coordinates.len();
     let mut segments = Vec::new();
     let mut current_segment: Vec<usize> = Vec::new();
 
-    for i in 0..n {
-        if candidates[i].is_empty() {
+    for (i, point_candidates) in candidates.iter().enumerate().take(n) {
+        if point_candidates.is_empty() {
             // No candidates — break segment
             if current_segment.len() >= 2 {
                 segments.push(std::mem::take(&mut current_segment));
@@ -330,19 +433,13 @@ fn find_segments(coordinates: &[(f64, f64)], candidates: &[Vec<Candidate>]) -> V
         }
 
         // Check for large gap
-        if let Some(&prev_idx) = current_segment.last() {
-            let gc_dist = great_circle_m(
-                coordinates[prev_idx].0,
-                coordinates[prev_idx].1,
-                coordinates[i].0,
-                coordinates[i].1,
-            );
-            if gc_dist > GAP_THRESHOLD_M {
-                if current_segment.len() >= 2 {
-                    segments.push(std::mem::take(&mut current_segment));
-                } else {
-                    current_segment.clear();
-                }
+        if let Some(&prev_idx) = current_segment.last()
+            && options.is_gap(coordinates, prev_idx, i)
+        {
+            if current_segment.len() >= 2 {
+                segments.push(std::mem::take(&mut current_segment));
+            } else {
+                current_segment.clear();
             }
         }
 
@@ -378,16 +475,19 @@ fn transition_prob(route_dist_m: f64, gc_dist_m: f64) -> f64 {
 // Viterbi algorithm
 // ---------------------------------------------------------------------------
 
-/// Run Viterbi decoding on a segment.
-/// Returns the index of the best candidate at each time step, or None if decoding fails.
+/// Run Viterbi decoding on a segment (`observations` are the trace indices
+/// of its coordinates). Returns the index of the best candidate at each
+/// time step up to the first observation none of whose candidates is
+/// reachable from the previous one, or None if decoding fails.
 fn viterbi(
     mode_data: &ModeData,
     ebg_nodes: &crate::formats::EbgNodes,
     coordinates: &[(f64, f64)],
     candidates: &[&Vec<Candidate>],
-    sigma: f64,
+    observations: &[usize],
+    options: &TraceOptions,
     cch_weights: &CchWeights,
-) -> Option<Vec<usize>> {
+) -> Option<ViterbiPath> {
     let n_obs = coordinates.len();
     if n_obs < 2 {
         return None;
@@ -398,9 +498,10 @@ fn viterbi(
     let mut predecessor: Vec<Vec<Option<usize>>> = Vec::with_capacity(n_obs);
 
     // Initialize t=0
+    let sigma0 = options.sigma(observations[0]);
     let init_probs: Vec<f64> = candidates[0]
         .iter()
-        .map(|c| emission_prob(c.distance_m, sigma))
+        .map(|c| emission_prob(c.distance_m, sigma0))
         .collect();
     log_prob.push(init_probs);
     predecessor.push(vec![None; candidates[0].len()]);
@@ -433,6 +534,8 @@ fn viterbi(
 
         let mut curr_probs = vec![NEG_INF; n_curr];
         let mut curr_pred = vec![None; n_curr];
+        let (prev_obs, obs) = (observations[t - 1], observations[t]);
+        let sigma = options.sigma(obs);
 
         for c in 0..n_curr {
             let emit = emission_prob(candidates[t][c].distance_m, sigma);
@@ -446,6 +549,9 @@ fn viterbi(
                 if route_dist_m == f64::INFINITY {
                     continue; // No path found
                 }
+                if !options.speed_plausible(prev_obs, obs, route_dist_m) {
+                    continue; // Too far for the time elapsed
+                }
 
                 let trans = transition_prob(route_dist_m, gc_dist);
                 let total = log_prob[t - 1][p] + trans + emit;
@@ -457,12 +563,30 @@ fn viterbi(
             }
         }
 
+        if curr_probs.iter().all(|&p| p == NEG_INF) {
+            break; // HMM break: the trace continues in a new matching
+        }
         log_prob.push(curr_probs);
         predecessor.push(curr_pred);
     }
 
-    // Backtrack: find best final state
-    let last_probs = &log_prob[n_obs - 1];
+    backtrack(&log_prob, &predecessor)
+}
+
+/// Best state sequence of a Viterbi decode
+struct ViterbiPath {
+    /// Matched candidate index per decoded observation
+    states: Vec<usize>,
+    /// Log-probability each observation adds along the path: its emission
+    /// plus the transition into it
+    step_log_probs: Vec<f64>,
+}
+
+/// Backtrack the trellis from its best final state.
+fn backtrack(log_prob: &[Vec<f64>], predecessor: &[Vec<Option<usize>>]) -> Option<ViterbiPath> {
+    // Find best final state
+    let n_obs = log_prob.len();
+    let last_probs = log_prob.last()?;
     let best_final = last_probs
         .iter()
         .enumerate()
@@ -478,7 +602,30 @@ fn viterbi(
         path[t - 1] = predecessor[t][path[t]]?;
     }
 
-    Some(path)
+    let step_log_probs = (0..n_obs)
+        .map(|t| {
+            let cumulative = log_prob[t][path[t]];
+            if t == 0 {
+                cumulative
+            } else {
+                cumulative - log_prob[t - 1][path[t - 1]]
+            }
+        })
+        .collect();
+    Some(ViterbiPath {
+        states: path,
+        step_log_probs,
+    })
+}
+
+/// Normalized HMM likelihood of a matched path (0.0 to 1.0): the geometric
+/// mean of its per-observation probabilities, so long and short matchings
+/// compare.
+fn path_confidence(step_log_probs: &[f64]) -> f64 {
+    if step_log_probs.is_empty() {
+        return 0.0;
+    }
+    (step_log_probs.iter().sum::<f64>() / step_log_probs.len() as f64).exp()
 }
 
 // ---------------------------------------------------------------------------
@@ -743,15 +890,13 @@ pub fn map_match_multi_region(
     regions: &RegionsState,
     mode_name: &str,
     coordinates: &[(f64, f64)],
-    gps_accuracy: Option<f64>,
+    options: &TraceOptions,
 ) -> Option<MatchResult> {
     let n = coordinates.len();
     if n < 2 {
         return None;
     }
 
-    let sigma = gps_accuracy.unwrap_or(DEFAULT_GPS_SIGMA).max(1.0);
-
     // ---- Step 1: per-sample multi-region candidate generation ------
     //
     // For each GPS sample, collect candidates from every region whose
@@ -759,7 +904,12 @@ pub fn map_match_multi_region(
     // candidates from BOTH adjacent regions.
     let candidates: Vec<Vec<RegionCandidate>> = coordinates
         .iter()
-        .map(|&(lon, lat)| generate_candidates_multi(regions, mode_name, lon, lat))
+        .enumerate()
+        .map(|(i, &(lon, lat))| {
+            let mut cands = generate_candidates_multi(regions, mode_name, lon, lat);
+            cands.retain(|c| options.within_radius(i, c.distance_m));
+            cands
+        })
         .collect();
 
     // ---- Step 2: fast-path detection -------------------------------
@@ -778,14 +928,7 @@ pub fn map_match_multi_region(
             Some(&m) => m,
             None => return None,
         };
-        return map_match(
-            &state,
-            Mode(mode_idx),
-            coordinates,
-            gps_accuracy,
-            None,
-            None,
-        );
+        return map_match(&state, Mode(mode_idx), coordinates, options, None, None);
     }
 
     // ---- Step 3: cross-region path ---------------------------------
@@ -809,7 +952,7 @@ pub fn map_match_multi_region(
         .collect();
 
     // ---- Step 4: segmentation (gaps + unmatched samples) -----------
-    let segments = find_segments_multi(coordinates, &candidates);
+    let segments = find_segments_multi(coordinates, &candidates, options);
     if segments.is_empty() {
         return None;
     }
@@ -819,111 +962,113 @@ pub fn map_match_multi_region(
     let mut tracepoints: Vec<Option<Tracepoint>> = vec![None; n];
 
     for segment in &segments {
-        let seg_coords: Vec<(f64, f64)> = segment.iter().map(|&i| coordinates[i]).collect();
-        let seg_candidates: Vec<&Vec<RegionCandidate>> =
-            segment.iter().map(|&i| &candidates[i]).collect();
-
-        let matched_indices = match viterbi_multi(
-            regions,
-            &mode_indices,
-            mode_name,
-            overlay,
-            &seg_coords,
-            &seg_candidates,
-            sigma,
-        ) {
-            Some(v) => v,
-            None => continue,
-        };
-
-        // Split matched sequence into per-region runs and build one
-        // Matching per run. Cross-region transitions become matching
-        // boundaries; the border crossing is implicit between adjacent
-        // matchings (caller's geometry assembly inserts a border
-        // anchor if it has the overlay).
-        let runs = split_into_region_runs(&seg_candidates, &matched_indices);
-        for (run_start, run_end) in runs.iter() {
-            // run is [run_start, run_end] inclusive within the segment.
-            let run_region_idx = seg_candidates[*run_start][matched_indices[*run_start]].region_idx;
-            let entry = &regions.regions[run_region_idx];
-            let m_idx = mode_indices[run_region_idx];
-            if m_idx == u8::MAX {
+        let mut rest: &[usize] = segment;
+        while rest.len() >= 2 {
+            let seg_coords: Vec<(f64, f64)> = rest.iter().map(|&i| coordinates[i]).collect();
+            let seg_candidates: Vec<&Vec<RegionCandidate>> =
+                rest.iter().map(|&i| &candidates[i]).collect();
+
+            let Some(decoded) = viterbi_multi(
+                regions,
+                &mode_indices,
+                mode_name,
+                overlay,
+                &seg_coords,
+                &seg_candidates,
+                rest,
+                options,
+            ) else {
+                break;
+            };
+            let matched_indices = decoded.states.as_slice();
+            let (matched, next) = rest.split_at(matched_indices.len().max(1));
+            rest = next;
+            if matched.len() < 2 {
                 continue;
             }
-            let state = entry.state();
-            let mode_data = state.get_mode(Mode(m_idx));
-
-            // Materialise the (single-region) Candidate slice for this
-            // run so we can reuse build_matched_path verbatim.
-            let run_cands: Vec<Vec<Candidate>> = (*run_start..=*run_end)
-                .map(|t| {
-                    seg_candidates[t]
-                        .iter()
-                        .filter(|c| c.region_idx == run_region_idx)
-                        .map(|c| Candidate {
-                            ebg_id: c.ebg_id,
-                            snapped_lon: c.snapped_lon,
-                            snapped_lat: c.snapped_lat,
-                            distance_m: c.distance_m,
-                        })
-                        .collect()
-                })
-                .collect();
-            // Recompute matched indices in the projected (single-
-            // region) candidate vectors. We pick the same physical
-            // candidate (ebg_id) we matched in the multi-region pass.
-            let run_matched: Vec<usize> = (*run_start..=*run_end)
-                .map(|t| {
-                    let target_ebg = seg_candidates[t][matched_indices[t]].ebg_id;
-                    run_cands[t - *run_start]
-                        .iter()
-                        .position(|c| c.ebg_id == target_ebg)
-                        .unwrap_or(0)
-                })
-                .collect();
-
-            let run_cand_refs: Vec<&Vec<Candidate>> = run_cands.iter().collect();
-            let ebg_path = build_matched_path(
-                &mode_data,
-                &run_cand_refs,
-                &run_matched,
-                &mode_data.cch_weights,
-            );
-
-            let duration_s = ebg_path
-                .iter()
-                .map(|&eid| mode_data.node_weights[eid as usize])
-                .filter(|&w| w != u32::MAX)
-                .sum::<u32>();
 
-            // Average emission for this run.
-            let avg_emission: f64 = (*run_start..=*run_end)
-                .map(|t| {
-                    let dist = seg_candidates[t][matched_indices[t]].distance_m;
-                    emission_prob(dist, sigma)
-                })
-                .sum::<f64>()
-                / ((*run_end - *run_start + 1) as f64);
+            // Split matched sequence into per-region runs and build one
+            // Matching per run. Cross-region transitions become matching
+            // boundaries; the border crossing is implicit between adjacent
+            // matchings (caller's geometry assembly inserts a border
+            // anchor if it has the overlay).
+            let runs = split_into_region_runs(&seg_candidates, matched_indices);
+            for (run_start, run_end) in runs.iter() {
+                // run is [run_start, run_end] inclusive within the segment.
+                let run_region_idx =
+                    seg_candidates[*run_start][matched_indices[*run_start]].region_idx;
+                let entry = &regions.regions[run_region_idx];
+                let m_idx = mode_indices[run_region_idx];
+                if m_idx == u8::MAX {
+                    continue;
+                }
+                let state = entry.state();
+                let mode_data = state.get_mode(Mode(m_idx));
+
+                // Materialise the (single-region) Candidate slice for this
+                // run so we can reuse build_matched_path verbatim.
+                let run_cands: Vec<Vec<Candidate>> = (*run_start..=*run_end)
+                    .map(|t| {
+                        seg_candidates[t]
+                            .iter()
+                            .filter(|c| c.region_idx == run_region_idx)
+                            .map(|c| Candidate {
+                                ebg_id: c.ebg_id,
+                                snapped_lon: c.snapped_lon,
+                                snapped_lat: c.snapped_lat,
+                                distance_m: c.distance_m,
+                            })
+                            .collect()
+                    })
+                    .collect();
+                // Recompute matched indices in the projected (single-
+                // region) candidate vectors. We pick the same physical
+                // candidate (ebg_id) we matched in the multi-region pass.
+                let run_matched: Vec<usize> = (*run_start..=*run_end)
+                    .map(|t| {
+                        let target_ebg = seg_candidates[t][matched_indices[t]].ebg_id;
+                        run_cands[t - *run_start]
+                            .iter()
+                            .position(|c| c.ebg_id == target_ebg)
+                            .unwrap_or(0)
+                    })
+                    .collect();
+
+                let run_cand_refs: Vec<&Vec<Candidate>> = run_cands.iter().collect();
+                let ebg_path = build_matched_path(
+                    &mode_data,
+                    &run_cand_refs,
+                    &run_matched,
+                    &mode_data.cch_weights,
+                );
 
-            let matching_idx = matchings.len();
+                let duration_s = ebg_path
+                    .iter()
+                    .map(|&eid| mode_data.node_weights[eid as usize])
+                    .filter(|&w| w != u32::MAX)
+                    .sum::<u32>();
 
-            matchings.push(Matching {
-                ebg_path,
-                duration_s,
-                confidence: avg_emission.exp(),
-                region_idx: run_region_idx,
-            });
+                let matching_idx = matchings.len();
 
-            for (waypoint_index, t) in (*run_start..=*run_end).enumerate() {
-                let cand = &seg_candidates[t][matched_indices[t]];
-                let obs_idx = segment[t];
-                tracepoints[obs_idx] = Some(Tracepoint {
-                    lon: cand.snapped_lon,
-                    lat: cand.snapped_lat,
-                    ebg_id: cand.ebg_id,
-                    matchings_index: matching_idx,
-                    waypoint_index,
+                matchings.push(Matching {
+                    ebg_path,
+                    duration_s,
+                    confidence: path_confidence(&decoded.step_log_probs[*run_start..=*run_end]),
+                    region_idx: run_region_idx,
                 });
+
+                for (waypoint_index, t) in (*run_start..=*run_end).enumerate() {
+                    let cand = &seg_candidates[t][matched_indices[t]];
+                    let obs_idx = matched[t];
+                    tracepoints[obs_idx] = Some(Tracepoint {
+                        lon: cand.snapped_lon,
+                        lat: cand.snapped_lat,
+                        ebg_id: cand.ebg_id,
+                        distance_m: cand.distance_m,
+                        matchings_index: matching_idx,
+                        waypoint_index,
+                    });
+                }
             }
         }
     }
@@ -1013,13 +1158,14 @@ fn single_region_id(candidates: &[Vec<RegionCandidate>]) -> Option<usize> {
 fn find_segments_multi(
     coordinates: &[(f64, f64)],
     candidates: &[Vec<RegionCandidate>],
+    options: &TraceOptions,
 ) -> Vec<Vec<usize>> {
     let n = coordinates.len();
     let mut segments = Vec::new();
     let mut current_segment: Vec<usize> = Vec::new();
 
-    for i in 0..n {
-        if candidates[i].is_empty() {
+    for (i, point_candidates) in candidates.iter().enumerate().take(n) {
+        if point_candidates.is_empty() {
             if current_segment.len() >= 2 {
                 segments.push(std::mem::take(&mut current_segment));
             } else {
@@ -1028,19 +1174,13 @@ fn find_segments_multi(
             continue;
         }
 
-        if let Some(&prev_idx) = current_segment.last() {
-            let gc_dist = great_circle_m(
-                coordinates[prev_idx].0,
-                coordinates[prev_idx].1,
-                coordinates[i].0,
-                coordinates[i].1,
-            );
-            if gc_dist > GAP_THRESHOLD_M {
-                if current_segment.len() >= 2 {
-                    segments.push(std::mem::take(&mut current_segment));
-                } else {
-                    current_segment.clear();
-                }
+        if let Some(&prev_idx) = current_segment.last()
+            && options.is_gap(coordinates, prev_idx, i)
+        {
+            if current_segment.len() >= 2 {
+                segments.push(std::mem::take(&mut current_segment));
+            } else {
+                current_segment.clear();
             }
         }
 
@@ -1065,8 +1205,9 @@ fn viterbi_multi(
     overlay: &OverlayCluster,
     coordinates: &[(f64, f64)],
     candidates: &[&Vec<RegionCandidate>],
-    sigma: f64,
-) -> Option<Vec<usize>> {
+    observations: &[usize],
+    options: &TraceOptions,
+) -> Option<ViterbiPath> {
     let n_obs = coordinates.len();
     if n_obs < 2 {
         return None;
@@ -1075,9 +1216,10 @@ fn viterbi_multi(
     let mut log_prob: Vec<Vec<f64>> = Vec::with_capacity(n_obs);
     let mut predecessor: Vec<Vec<Option<usize>>> = Vec::with_capacity(n_obs);
 
+    let sigma0 = options.sigma(observations[0]);
     let init_probs: Vec<f64> = candidates[0]
         .iter()
-        .map(|c| emission_prob(c.distance_m, sigma))
+        .map(|c| emission_prob(c.distance_m, sigma0))
         .collect();
     log_prob.push(init_probs);
     predecessor.push(vec![None; candidates[0].len()]);
@@ -1108,6 +1250,8 @@ fn viterbi_multi(
 
         let mut curr_probs = vec![NEG_INF; n_curr];
         let mut curr_pred = vec![None; n_curr];
+        let (prev_obs, obs) = (observations[t - 1], observations[t]);
+        let sigma = options.sigma(obs);
 
         for c in 0..n_curr {
             let emit = emission_prob(candidates[t][c].distance_m, sigma);
@@ -1119,6 +1263,9 @@ fn viterbi_multi(
                 if route_dist_m == f64::INFINITY {
                     continue;
                 }
+                if !options.speed_plausible(prev_obs, obs, route_dist_m) {
+                    continue;
+                }
                 let trans = transition_prob(route_dist_m, gc_dist);
                 let total = log_prob[t - 1][p] + trans + emit;
                 if total > curr_probs[c] {
@@ -1128,24 +1275,14 @@ fn viterbi_multi(
             }
         }
 
+        if curr_probs.iter().all(|&p| p == NEG_INF) {
+            break;
+        }
         log_prob.push(curr_probs);
         predecessor.push(curr_pred);
     }
 
-    let last_probs = &log_prob[n_obs - 1];
-    let best_final = last_probs
-        .iter()
-        .enumerate()
-        .filter(|&(_, p)| *p != NEG_INF)
-        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
-        .map(|(idx, _)| idx)?;
-
-    let mut path = vec![0usize; n_obs];
-    path[n_obs - 1] = best_final;
-    for t in (1..n_obs).rev() {
-        path[t - 1] = predecessor[t][path[t]]?;
-    }
-    Some(path)
+    backtrack(&log_prob, &predecessor)
 }
 
 /// Compute per-pair transition distances (meters) in the multi-region
@@ -1362,6 +1499,21 @@ mod tests {
         assert!(p1 > p2, "Mismatched distances should be penalized");
     }
 
+    #[test]
+    fn test_backtrack_step_log_probs_and_confidence() {
+        // Two observations, two candidates each; best path is 0 -> 1
+        let log_prob = vec![vec![-1.0, -4.0], vec![-3.0, -2.5]];
+        let predecessor = vec![vec![None, None], vec![Some(0), Some(0)]];
+        let decoded = backtrack(&log_prob, &predecessor).unwrap();
+        assert_eq!(decoded.states, vec![0, 1]);
+        assert_eq!(decoded.step_log_probs, vec![-1.0, -1.5]);
+
+        let confidence = path_confidence(&decoded.step_log_probs);
+        assert!((confidence - (-1.25f64).exp()).abs() < 1e-12);
+        assert_eq!(path_confidence(&[0.0, 0.0]), 1.0, "perfect fit");
+        assert_eq!(path_confidence(&[]), 0.0);
+    }
+
     #[test]
     fn test_great_circle_m() {
         // Brussels to nearby point ~1km east
@@ -1379,7 +1531,7 @@ mod tests {
     fn test_find_segments_no_candidates() {
         let coords = vec![(4.0, 50.0), (4.1, 50.1), (4.2, 50.2)];
         let cands: Vec<Vec<Candidate>> = vec![vec![], vec![], vec![]];
-        let segments = find_segments(&coords, &cands);
+        let segments = find_segments(&coords, &cands, &TraceOptions::default());
         assert!(
             segments.is_empty(),
             "Should produce no segments when no candidates"
@@ -1401,7 +1553,7 @@ mod tests {
                 }]
             })
             .collect();
-        let segments = find_segments(&coords, &cands);
+        let segments = find_segments(&coords, &cands, &TraceOptions::default());
         assert_eq!(segments.len(), 1);
         assert_eq!(segments[0], vec![0, 1, 2]);
     }
@@ -1427,12 +1579,57 @@ mod tests {
                 }]
             })
             .collect();
-        let segments = find_segments(&coords, &cands);
+        let segments = find_segments(&coords, &cands, &TraceOptions::default());
         assert_eq!(segments.len(), 2, "Should split at gap");
         assert_eq!(segments[0], vec![0, 1]);
         assert_eq!(segments[1], vec![2, 3]);
     }
 
+    #[test]
+    fn test_find_segments_time_gap_split() {
+        // Close together, but 5 minutes pass between points 1 and 2
+        let coords = vec![(4.0, 50.0), (4.001, 50.0), (4.002, 50.0), (4.003, 50.0)];
+        let cands: Vec<Vec<Candidate>> = coords
+            .iter()
+            .enumerate()
+            .map(|(i, &(lon, lat))| {
+                vec![Candidate {
+                    ebg_id: i as u32,
+                    snapped_lon: lon,
+                    snapped_lat: lat,
+                    distance_m: 5.0,
+                }]
+            })
+            .collect();
+        let options = TraceOptions {
+            timestamps: Some(vec![0, 10, 310, 320]),
+            ..TraceOptions::default()
+        };
+        let segments = find_segments(&coords, &cands, &options);
+        assert_eq!(segments, vec![vec![0, 1], vec![2, 3]]);
+    }
+
+    #[test]
+    fn test_trace_options_radius_and_speed() {
+        let options = TraceOptions {
+            gps_accuracy: Some(15.0),
+            radiuses: Some(vec![Some(5.0), None, Some(0.2)]),
+            timestamps: Some(vec![0, 10, 10]),
+        };
+        assert_eq!(options.sigma(0), 5.0);
+        assert_eq!(options.sigma(1), 15.0);
+        assert_eq!(options.sigma(2), 1.0, "sigma is floored at 1 m");
+        assert!(options.within_radius(0, 15.0));
+        assert!(!options.within_radius(0, 15.1));
+        assert!(options.within_radius(1, 500.0), "no radius, no pruning");
+
+        // 10 s at most 55 m/s plus the edge-length slack
+        assert!(options.speed_plausible(0, 1, 550.0 + SPEED_CHECK_SLACK_M));
+        assert!(!options.speed_plausible(0, 1, 551.0 + SPEED_CHECK_SLACK_M));
+        assert!(!options.speed_plausible(1, 2, SPEED_CHECK_SLACK_M + 1.0));
+        assert!(TraceOptions::default().speed_plausible(0, 1, 1e9));
+    }
+
     // -- Cross-region helpers (#194) ------------------------------
 
     fn rc(region_idx: usize, ebg_id: u32) -> RegionCandidate {
@@ -1508,7 +1705,7 @@ mod tests {
     fn test_find_segments_multi_continuous() {
         let coords = vec![(4.0, 50.0), (4.001, 50.0), (4.002, 50.0)];
         let cands = vec![vec![rc(0, 1)], vec![rc(0, 2)], vec![rc(0, 3)]];
-        let segs = find_segments_multi(&coords, &cands);
+        let segs = find_segments_multi(&coords, &cands, &TraceOptions::default());
         assert_eq!(segs, vec![vec![0, 1, 2]]);
     }
 
@@ -1521,7 +1718,7 @@ mod tests {
             vec![rc(0, 3)],
             vec![rc(0, 4)],
         ];
-        let segs = find_segments_multi(&coords, &cands);
+        let segs = find_segments_multi(&coords, &cands, &TraceOptions::default());
         assert_eq!(segs.len(), 2);
         assert_eq!(segs[0], vec![0, 1]);
         assert_eq!(segs[1], vec![2, 3]);
@@ -6,8 +6,9 @@ use std::sync::Arc;
 use utoipa::ToSchema;
 
 use super::geometry::{GeometryFormat, RouteGeometry, build_geometry};
+use super::map_match::TraceOptions;
 use super::regions::RegionsState;
-use super::route::{RouteStep, build_steps, lookup_road_name};
+use super::route::{RouteStep, StepGuidance, build_steps, lookup_road_name};
 use super::state::ServerState;
 use super::types::{parse_mode, validate_coord};
 
@@ -27,6 +28,15 @@ pub struct MatchRequest {
     #[serde(default)]
     #[schema(example = 10.0)]
     gps_accuracy: Option<f64>,
+    /// Accuracy radius in meters per point (null entries use gps_accuracy)
+    #[serde(default)]
+    #[schema(example = json!([5.0, null, 20.0, 10.0]))]
+    radiuses: Option<Vec<Option<f64>>>,
+    /// UNIX timestamps in seconds, one per point, non-decreasing. Enables
+    /// the speed plausibility check and splitting at time gaps.
+    #[serde(default)]
+    #[schema(example = json!([1700000000, 1700000005, 1700000010, 1700000015]))]
+    timestamps: Option<Vec<i64>>,
     /// Geometry format: "polyline6" (default), "geojson", or "points"
     #[serde(default = "default_match_geometry")]
     #[schema(example = "polyline6")]
@@ -51,6 +61,52 @@ pub fn default_match_geometry() -> String {
     "polyline6".to_string()
 }
 
+/// Validate `gps_accuracy`, `radiuses` and `timestamps` against the trace.
+pub fn trace_options(req: &MatchRequest) -> Result<TraceOptions, String> {
+    let valid_accuracy = |acc: f64| acc > 0.0 && acc <= 100.0;
+    if let Some(acc) = req.gps_accuracy
+        && !valid_accuracy(acc)
+    {
+        return Err("gps_accuracy must be between 0 and 100 meters".to_string());
+    }
+    if let Some(radiuses) = &req.radiuses {
+        if radiuses.len() != req.points.len() {
+            return Err(format!(
+                "radiuses has {} entries, expected one per point ({})",
+                radiuses.len(),
+                req.points.len()
+            ));
+        }
+        if let Some(i) = radiuses
+            .iter()
+            .position(|r| r.is_some_and(|r| !valid_accuracy(r)))
+        {
+            return Err(format!("radiuses[{}] must be between 0 and 100 meters", i));
+        }
+    }
+    if let Some(timestamps) = &req.timestamps {
+        if timestamps.len() != req.points.len() {
+            return Err(format!(
+                "timestamps has {} entries, expected one per point ({})",
+                timestamps.len(),
+                req.points.len()
+            ));
+        }
+        if let Some(i) = timestamps.windows(2).position(|w| w[1] < w[0]) {
+            return Err(format!(
+                "timestamps must be non-decreasing (timestamps[{}] < timestamps[{}])",
+                i + 1,
+                i
+            ));
+        }
+    }
+    Ok(TraceOptions {
+        gps_accuracy: req.gps_accuracy,
+        radiuses: req.radiuses.clone(),
+        timestamps: req.timestamps.clone(),
+    })
+}
+
 /// Response for map matching
 #[derive(Debug, Serialize, ToSchema)]
 pub struct MatchResponse {
@@ -58,7 +114,8 @@ pub struct MatchResponse {
     code: String,
     /// Matched routes (trace may be split at gaps)
     matchings: Vec<MatchMatching>,
-    /// Per-observation tracepoint info (null if observation couldn't be matched)
+    /// Per-observation tracepoint info (null if the observation was
+    /// discarded: no candidate, or alone between two splits)
     #[schema(value_type = Vec<Option<MatchTracepoint>>)]
     tracepoints: Vec<Option<MatchTracepoint>>,
 }
@@ -72,7 +129,8 @@ pub struct MatchMatching {
     duration: f64,
     /// Distance in meters
     distance: f64,
-    /// Confidence score (0.0 to 1.0)
+    /// Confidence score (0.0 to 1.0): normalized HMM likelihood of the
+    /// matched path. Low values flag poor matches.
     confidence: f64,
     /// Turn-by-turn steps (if requested)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -86,6 +144,8 @@ pub struct MatchTracepoint {
     location: [f64; 2],
     /// Road name at this location (empty if unknown)
     name: String,
+    /// Distance in meters from the input point to the snapped location
+    distance: f64,
     /// Index into the matchings array
     matchings_index: usize,
     /// Index within the matching's waypoint sequence
@@ -100,7 +160,7 @@ pub struct MatchTracepoint {
     path = "/match",
     tag = "Search",
     summary = "Map match a GPS trace to the road network",
-    description = "Snaps a sequence of GPS coordinates to the most likely route on the road network\nusing HMM + Viterbi decoding (Newson & Krumm 2009).\n\nThe trace may be split into multiple sub-matchings if gaps are detected.\nMaximum 500 coordinates per request.",
+    description = "Snaps a sequence of GPS coordinates to the most likely route on the road network\nusing HMM + Viterbi decoding (Newson & Krumm 2009).\n\nThe trace may be split into multiple sub-matchings at distance gaps, time gaps (with\n`timestamps`), or where no road path fits between two points.\nMaximum 500 coordinates per request.",
     request_body(content = MatchRequest, description = "GPS trace coordinates with optional accuracy",
         example = json!({
             "points": [[4.3517, 50.8503], [4.3537, 50.8513], [4.3557, 50.8523], [4.3577, 50.8533]],
@@ -203,19 +263,17 @@ pub async fn match_trace_handler(
         }
     }
 
-    // Validate GPS accuracy
-    if let Some(acc) = req.gps_accuracy
-        && (acc <= 0.0 || acc > 100.0 || acc.is_nan())
-    {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "code": "InvalidValue",
-                "message": "gps_accuracy must be between 0 and 100 meters"
-            })),
-        )
-            .into_response();
-    }
+    // Validate GPS accuracy, radiuses and timestamps
+    let trace_options = match trace_options(&req) {
+        Ok(o) => o,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "code": "InvalidValue", "message": e })),
+            )
+                .into_response();
+        }
+    };
 
     // Parse geometry format
     let geom_format = match GeometryFormat::parse(&req.geometry) {
@@ -230,7 +288,7 @@ pub async fn match_trace_handler(
     };
 
     // Parse exclude parameter
-    let exclude_mask = match super::exclude::parse_exclude_option(&req.exclude) {
+    let exclude_mask = match state.parse_exclude(&req.exclude) {
         Ok(m) => m,
         Err(e) => {
             return (
@@ -257,7 +315,6 @@ pub async fn match_trace_handler(
     let coords: Vec<(f64, f64)> = req.points.iter().map(|&[lon, lat]| (lon, lat)).collect();
 
     // Extract owned values before the spawn_blocking closure
-    let gps_accuracy = req.gps_accuracy;
     let want_steps = req.steps;
 
     // Map matching is CPU-heavy: HMM Viterbi decoding with many sequential P2P queries
@@ -308,7 +365,7 @@ pub async fn match_trace_handler(
             &state_clone,
             mode,
             &coords,
-            gps_accuracy,
+            &trace_options,
             snap_mask.as_deref(),
             cch_weights,
         )?;
@@ -334,6 +391,7 @@ pub async fn match_trace_handler(
                         &state_clone.edge_geom,
                         &mode_data.node_weights,
                         &state_clone.way_names,
+                        &StepGuidance::new(&state_clone, &mode_data),
                         geom_format,
                     ))
                 } else {
@@ -365,6 +423,7 @@ pub async fn match_trace_handler(
                     MatchTracepoint {
                         location: [t.lon, t.lat],
                         name,
+                        distance: t.distance_m,
                         matchings_index: t.matchings_index,
                         waypoint_index: t.waypoint_index,
                     }
@@ -457,18 +516,16 @@ async fn cross_region_match_inner(
                 .into_response();
         }
     }
-    if let Some(acc) = req.gps_accuracy
-        && (acc <= 0.0 || acc > 100.0 || acc.is_nan())
-    {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "code": "InvalidValue",
-                "message": "gps_accuracy must be between 0 and 100 meters"
-            })),
-        )
-            .into_response();
-    }
+    let trace_options = match trace_options(&req) {
+        Ok(o) => o,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "code": "InvalidValue", "message": e })),
+            )
+                .into_response();
+        }
+    };
 
     let geom_format = match GeometryFormat::parse(&req.geometry) {
         Ok(f) => f,
@@ -508,7 +565,6 @@ async fn cross_region_match_inner(
 
     let coords: Vec<(f64, f64)> = req.points.iter().map(|&[lon, lat]| (lon, lat)).collect();
     let mode_name = req.mode.clone();
-    let gps_accuracy = req.gps_accuracy;
     let want_steps = req.steps;
     let regions_clone = regions.clone();
 
@@ -517,7 +573,7 @@ async fn cross_region_match_inner(
             &regions_clone,
             &mode_name,
             &coords,
-            gps_accuracy,
+            &trace_options,
         )?;
 
         let matchings: Vec<MatchMatching> = result
@@ -542,6 +598,7 @@ async fn cross_region_match_inner(
                         &state.edge_geom,
                         &mode_data.node_weights,
                         &state.way_names,
+                        &StepGuidance::new(&state, &mode_data),
                         geom_format,
                     ))
                 } else {
@@ -581,6 +638,7 @@ async fn cross_region_match_inner(
                     MatchTracepoint {
                         location: [t.lon, t.lat],
                         name,
+                        distance: t.distance_m,
                         matchings_index: t.matchings_index,
                         waypoint_index: t.waypoint_index,
                     }
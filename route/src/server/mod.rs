@@ -8,12 +8,13 @@
 //! - `GET /nearest` - Snap to nearest road segments
 //! - `POST /table` - Distance matrix (bucket M2M)
 //! - `GET /isochrone` - Reachability polygon (GeoJSON/WKB)
-//! - `POST /isochrone/bulk` - Parallel batch isochrones (WKB stream)
+//! - `POST /isochrone/bulk` - Parallel batch isochrones (WKB or GeoJSON-seq stream)
 //! - `POST /trip` - TSP/trip optimization
 //! - `POST /match` - GPS trace map matching (HMM + Viterbi)
 //! - `GET /height` - Elevation lookup (SRTM DEM)
 //! - `GET /health` - Health check with uptime and stats
 //! - `GET /metrics` - Prometheus metrics
+//! - `POST /admin/weights/delta` - Incremental weight re-customization (`BUTTERFLY_ADMIN_API=on` only)
 //! - `GET /swagger-ui/` - OpenAPI documentation
 //!
 //! # Arrow Flight gRPC Endpoints (`--grpc-port`)
@@ -30,10 +31,12 @@
 //! - Shortcut unpacking for path reconstruction
 //! - Geometry lookup via EBG -> NBG mapping
 
+pub mod admin_handler;
 pub mod api;
 pub mod avoid;
 pub mod border;
 pub mod catchment;
+pub mod conditional;
 pub mod cross_region;
 pub mod edge_geom;
 pub mod edge_osm;
@@ -50,8 +53,11 @@ pub mod flow;
 pub mod geometry;
 pub mod health_handler;
 pub mod height_handler;
+pub mod hint;
+pub mod i18n;
 pub mod idle_compactor;
 pub mod isochrone_handler;
+pub mod lanes;
 pub mod map_match;
 pub mod matching;
 pub mod metrics;
@@ -11,7 +11,9 @@ use std::sync::Arc;
 use utoipa::ToSchema;
 
 use super::regions::RegionsState;
-use super::types::{ErrorResponse, SnapRole, parse_mode, validate_coord};
+use super::types::{
+    ErrorResponse, SnapRole, parse_bearings, parse_mode, validate_coord, validate_radius,
+};
 
 // ============ Types ============
 
@@ -28,7 +30,13 @@ pub struct NearestRequest {
     mode: String,
     /// Number of nearest results (default 1, max 100)
     #[serde(default = "default_number")]
+    #[schema(default = 1, minimum = 1, maximum = 100)]
     number: u32,
+    /// Search radius in metres (default and max 5000). Candidates farther
+    /// than this are dropped, so fewer than `number` may come back.
+    #[serde(default)]
+    #[schema(default = 5000.0, maximum = 5000.0)]
+    radius: Option<f64>,
     /// Directional role (#197). `src` (default) returns only EBG
     /// nodes that can start a route in this mode (have at least one
     /// mode-valid outbound arc). `dst` returns only nodes that can
@@ -42,6 +50,11 @@ pub struct NearestRequest {
     /// "downstream" EBG node, which is a 404 source for /route.
     #[serde(default)]
     role: SnapRole,
+    /// Heading filter `angle,range` in degrees (0 = North, clockwise):
+    /// only segments whose direction of travel lies within `range` of
+    /// `angle` are returned.
+    #[serde(default)]
+    bearings: Option<String>,
 }
 
 pub fn default_number() -> u32 {
@@ -81,8 +94,10 @@ pub struct NearestResponse {
         ("lon" = f64, Query, description = "Longitude", example = 4.3517),
         ("lat" = f64, Query, description = "Latitude", example = 50.8503),
         ("mode" = String, Query, description = "Transport mode (e.g. car, bike, foot — depends on available models)", example = "car"),
-        ("number" = Option<u32>, Query, description = "Number of results (default 1, max 100)", example = 5),
+        ("number" = Option<u32>, Query, description = "Number of results, sorted by distance (default 1, max 100)", example = 5),
+        ("radius" = Option<f64>, Query, description = "Search radius in metres (default and max 5000). Only candidates within the radius are returned.", example = json!(null)),
         ("role" = Option<SnapRole>, Query, description = "Directional snap role: src (default), dst, or either", example = "src"),
+        ("bearings" = Option<String>, Query, description = "Heading filter 'angle,range' (degrees, 0 = North). Only segments travelling within range of angle are returned.", example = json!(null)),
     ),
     responses(
         (status = 200, description = "Nearest roads found", body = NearestResponse),
@@ -115,6 +130,29 @@ pub async fn nearest_handler(
             .into_response();
     }
 
+    let radius = match req.radius.map(validate_radius).transpose() {
+        Ok(r) => r,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+        }
+    };
+    let bearing = match req.bearings.as_deref().map(parse_bearings).transpose() {
+        Ok(None) => None,
+        Ok(Some(h)) if h.len() == 1 => h[0],
+        Ok(Some(h)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("bearings has {} pairs, expected 1", h.len()),
+                }),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+        }
+    };
+
     // Region dispatch (#91): pick the region that snaps the query point
     // closest to a road. Single-region deployments wrap their state as
     // a one-region `RegionsState` so this branch is uniform.
@@ -142,23 +180,26 @@ pub async fn nearest_handler(
     let mode_data = state.get_mode(mode);
     let role_filter = req.role.role_filter(&mode_data);
 
-    let results = state.snap_index.snap_k_with_info_filtered_role(
+    let mut results = state.snap_index.snap_k_with_bearing_filtered_role(
         req.lon,
         req.lat,
         mode.0,
         k,
+        bearing,
         None,
         role_filter,
     );
 
+    if let Some(r) = radius {
+        results.retain(|&(_, _, _, dist_m)| dist_m <= r);
+    }
+
     if results.is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "No road found within snap distance".to_string(),
-            }),
-        )
-            .into_response();
+        let error = match radius {
+            Some(r) => format!("No road found within {} m", r),
+            None => "No road found within snap distance".to_string(),
+        };
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response();
     }
 
     let waypoints: Vec<NearestWaypoint> = results
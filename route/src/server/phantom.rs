@@ -26,7 +26,7 @@ use crate::profile_abi::Mode;
 
 use super::edge_geom::EdgeGeometry;
 use super::state::{ModeData, ServerState};
-use super::types::SnapRole;
+use super::types::{DrivingSide, SnapRole};
 use crate::formats::EbgNodes;
 
 /// One directed seed of a phantom endpoint.
@@ -86,6 +86,29 @@ impl PhantomEnd {
         self.seeds.iter().find(|s| s.ebg_id == ebg_id)
     }
 
+    /// `approaches=curb`: drop the seeds whose direction of travel has the
+    /// query point on the far side of the road. Keeps every seed when none
+    /// qualifies (a one-way street approached from the wrong side must still
+    /// route).
+    pub fn retain_curbside(&mut self, state: &ServerState, lon: f64, lat: f64, side: DrivingSide) {
+        self.retain_seeds(|s| {
+            on_curb_side(&state.ebg_nodes, &state.edge_geom, s.ebg_id, lon, lat, side)
+        });
+    }
+
+    /// Keep only the seeds matching `keep`, unless that would drop them
+    /// all. `primary_ebg` moves to a kept seed when its own is dropped.
+    pub fn retain_seeds(&mut self, keep: impl Fn(&PhantomSeed) -> bool) {
+        let kept: Vec<PhantomSeed> = self.seeds.iter().filter(|s| keep(s)).copied().collect();
+        if kept.is_empty() {
+            return;
+        }
+        if !kept.iter().any(|s| s.ebg_id == self.primary_ebg) {
+            self.primary_ebg = kept[0].ebg_id;
+        }
+        self.seeds = kept;
+    }
+
     /// Time-channel query seeds `(rank, cost)` + the shift to subtract from
     /// the final raw best, per role:
     /// - `Src`: cost = raw partial (remainder to head), shift = 0.
@@ -174,9 +197,78 @@ pub(crate) fn projection_fraction(
     }
 }
 
+/// Signed lateral offset in metres of (lon, lat) from the edge's stored
+/// polyline, measured on the closest segment: positive = left of the STORED
+/// direction, negative = right. 0.0 for degenerate geometry.
+pub(crate) fn lateral_offset(
+    ebg_nodes: &EbgNodes,
+    edge_geom: &EdgeGeometry,
+    ebg_id: u32,
+    lon: f64,
+    lat: f64,
+) -> f64 {
+    let node = &ebg_nodes.nodes[ebg_id as usize];
+    let poly = edge_geom.polyline(node.geom_idx);
+    // same planar approximation as `projection_fraction`
+    let mlat = 111_320.0_f64;
+    let mlon = 111_320.0 * (lat.to_radians().cos());
+    let px = lon * mlon;
+    let py = lat * mlat;
+
+    let mut best_d2 = f64::INFINITY;
+    let mut offset = 0.0_f64;
+    for i in 1..poly.len() {
+        let (a, b) = (poly.at(i - 1), poly.at(i));
+        let (x1, y1) = (a.0 * mlon, a.1 * mlat);
+        let (dx, dy) = (b.0 * mlon - x1, b.1 * mlat - y1);
+        let seg_len = (dx * dx + dy * dy).sqrt();
+        if seg_len == 0.0 {
+            continue;
+        }
+        let t = (((px - x1) * dx + (py - y1) * dy) / (seg_len * seg_len)).clamp(0.0, 1.0);
+        let (cx, cy) = (x1 + t * dx, y1 + t * dy);
+        let d2 = (px - cx) * (px - cx) + (py - cy) * (py - cy);
+        if d2 < best_d2 {
+            best_d2 = d2;
+            offset = (dx * (py - y1) - dy * (px - x1)) / seg_len;
+        }
+    }
+    offset
+}
+
+/// Points closer than this to the centreline count as on both sides — GPS
+/// noise on a geocoded address can't tell the carriageways apart.
+const CURB_TOLERANCE_M: f64 = 1.0;
+
+/// True when (lon, lat) lies on the curb side of directed edge `ebg_id`
+/// (the right of its direction of travel in right-hand traffic, the left in
+/// left-hand traffic), or within [`CURB_TOLERANCE_M`] of the centreline.
+/// Twins share the stored geometry of the even id; the odd twin runs it
+/// backward, which mirrors the side.
+pub fn on_curb_side(
+    ebg_nodes: &EbgNodes,
+    edge_geom: &EdgeGeometry,
+    ebg_id: u32,
+    lon: f64,
+    lat: f64,
+    side: DrivingSide,
+) -> bool {
+    let mut left = lateral_offset(ebg_nodes, edge_geom, ebg_id & !1u32, lon, lat);
+    if ebg_id & 1 == 1 {
+        left = -left;
+    }
+    if left.abs() < CURB_TOLERANCE_M {
+        return true;
+    }
+    match side {
+        DrivingSide::Right => left < 0.0,
+        DrivingSide::Left => left > 0.0,
+    }
+}
+
 /// True when `ebg_id` is a valid seed in this mode under the given role +
 /// dynamic edge filter: mode-accessible weight, contracted rank, role mask.
-fn seed_valid(
+pub(crate) fn seed_valid(
     mode_data: &ModeData,
     role: SnapRole,
     edge_filter: Option<&[u64]>,
@@ -675,6 +767,27 @@ mod tests {
         assert!(int_mid, "a mid-edge projection IS interior");
     }
 
+    // --- curb side ------------------------------------------------------------
+
+    #[test]
+    fn curb_side_follows_direction_of_travel_and_driving_side() {
+        // Eastbound stored edge; the query point is ~1.1 km north = LEFT of
+        // the stored direction, RIGHT of its westbound twin (odd id).
+        let (ebg, geom) = single_edge(&[(0.0, 0.0), (1.0, 0.0)]);
+        let off = lateral_offset(&ebg, &geom, 0, 0.5, 0.01);
+        assert!(
+            (off - 1113.2).abs() < 1.0,
+            "north of an eastbound edge is +left, got {off}"
+        );
+        assert!(!on_curb_side(&ebg, &geom, 0, 0.5, 0.01, DrivingSide::Right));
+        assert!(on_curb_side(&ebg, &geom, 1, 0.5, 0.01, DrivingSide::Right));
+        assert!(on_curb_side(&ebg, &geom, 0, 0.5, 0.01, DrivingSide::Left));
+        assert!(!on_curb_side(&ebg, &geom, 1, 0.5, 0.01, DrivingSide::Left));
+        // On the centreline both directions qualify.
+        assert!(on_curb_side(&ebg, &geom, 0, 0.5, 0.0, DrivingSide::Right));
+        assert!(on_curb_side(&ebg, &geom, 1, 0.5, 0.0, DrivingSide::Right));
+    }
+
     #[test]
     fn projection_fraction_is_clamped_to_unit_interval_past_the_ends() {
         // A point beyond the head projects onto the head (fraction 1); a point
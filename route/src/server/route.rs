@@ -10,54 +10,122 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use utoipa::ToSchema;
 
+use crate::formats::{EDGE_FLAG_FERRY, EDGE_FLAG_ROUNDABOUT};
+
+use super::conditional::{TravelTime, format_time};
+use super::elevation::RouteElevation;
 use super::geometry::{GeometryFormat, Point, RouteGeometry, build_raw_points};
+use super::hint::{SnapCandidate, SnapHint, data_checksum, parse_hints};
+use super::i18n::{Locale, resolve_locale};
+use super::lanes::{StepLane, WayLanes, step_lanes};
 use super::query::CchQuery;
 use super::regions::RegionsState;
 use super::state::ServerState;
-use super::types::{ErrorResponse, SnapRole, parse_mode, validate_coord};
-use super::unpack::unpack_path;
+use super::types::{
+    Approach, DrivingSide, ErrorResponse, SnapRole, Weighting, parse_bearings, parse_radiuses,
+    resolve_weighting, validate_coord,
+};
+use super::unpack::{path_weight, unpack_path};
 
 // ============ Types ============
 
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Deserialize, ToSchema)]
 pub struct RouteRequest {
     /// Source longitude
     #[schema(example = 4.3517)]
-    origin_lon: f64,
+    #[serde(default)]
+    origin_lon: Option<f64>,
     /// Source latitude
     #[schema(example = 50.8503)]
-    origin_lat: f64,
+    #[serde(default)]
+    origin_lat: Option<f64>,
     /// Destination longitude
     #[schema(example = 4.4017)]
-    destination_lon: f64,
+    #[serde(default)]
+    destination_lon: Option<f64>,
     /// Destination latitude
     #[schema(example = 50.8603)]
-    destination_lat: f64,
+    #[serde(default)]
+    destination_lat: Option<f64>,
+    /// Ordered waypoints `lon,lat;lon,lat;...` (2 to 25), instead of the
+    /// origin/destination pair. Each consecutive pair is routed as a leg
+    /// and the response carries the per-leg results in `legs`.
+    #[serde(default)]
+    coordinates: Option<String>,
     /// Transport mode: car, bike, or foot
     #[schema(example = "car")]
     mode: String,
-    /// Optional traffic profile name. If set, the server routes against
-    /// the synthetic mode `<mode>_<traffic>` (e.g. `car` + `rush_hour` →
-    /// `car_rush_hour`). The variant must have been built by
+    /// Optional traffic profile name; shorthand for `weighting=<traffic>`.
+    /// The server routes against the synthetic mode `<mode>_<traffic>`
+    /// (e.g. `car` + `rush_hour` → `car_rush_hour`), built by
     /// `step8-customize --traffic ...` at pipeline time.
     #[serde(default)]
     traffic: Option<String>,
+    /// Route optimisation metric: "fastest" (default) minimises travel time,
+    /// "shortest" minimises geometric length using the step 8 distance
+    /// weights (`cch.d.<mode>.u32`), any other name selects the loaded
+    /// traffic variant `<mode>_<name>`.
+    #[serde(default)]
+    weighting: Option<String>,
     /// Geometry encoding: polyline6 (default), geojson, points
     #[serde(default = "default_geometries")]
     geometries: String,
     /// Number of alternative routes (0 or 1 = single route, max 5)
     #[serde(default = "default_alternatives")]
     alternatives: u32,
+    /// Largest fraction (0-1] of an alternative's length that may overlap
+    /// the primary route and the alternatives already accepted (default 0.8).
+    #[serde(default)]
+    alternative_max_sharing: Option<f64>,
+    /// Largest alternative cost as a multiple of the primary's (>= 1,
+    /// default 1.5).
+    #[serde(default)]
+    alternative_max_stretch: Option<f64>,
     /// Include turn-by-turn step instructions
     #[serde(default)]
     steps: bool,
-    /// Per-edge annotations: comma-separated list of "duration", "distance", "speed", "nodes"
+    /// Language of step instruction text: en, fr, de, nl or es (a tag
+    /// like "fr-BE" matches on its language). Defaults to the
+    /// Accept-Language header, then English.
+    #[serde(default)]
+    language: Option<String>,
+    /// Units of step distance text: "metric" or "imperial". Defaults to
+    /// imperial for en-US / en-GB, metric otherwise.
+    #[serde(default)]
+    units: Option<String>,
+    /// Per-edge annotations: comma-separated list of "duration", "distance", "speed", "nodes", "ferry", "ways"
     #[serde(default)]
     annotations: Option<String>,
     /// Bearing hints per waypoint: "angle,range;angle,range" (0-360 degrees).
-    /// First pair for source, second for destination. Filters snap candidates by edge direction.
+    /// First pair for source, second for destination (with `coordinates`, one
+    /// pair per waypoint). Filters snap candidates by edge direction.
     #[serde(default)]
     bearings: Option<String>,
+    /// Snap radius in metres per waypoint: "r;r" (source;destination, or one
+    /// per `coordinates` waypoint). A waypoint with no road within its
+    /// radius is rejected; an empty slot keeps the default of 5000 m.
+    #[serde(default)]
+    radius: Option<String>,
+    /// Side of the road per waypoint: "curb;unrestricted" (source;destination,
+    /// or one per `coordinates` waypoint). `curb` departs from / arrives at
+    /// the waypoint with it on the driving-side curb (`BUTTERFLY_DRIVING_SIDE`).
+    #[serde(default)]
+    approaches: Option<String>,
+    /// U-turns at via points (`coordinates` routes): "true" keeps the
+    /// direction of travel through every intermediate waypoint, "false"
+    /// lets each leg start in either direction, "default" is true for car
+    /// and false for other modes.
+    #[serde(default)]
+    continue_straight: Option<String>,
+    /// Snapping hints per waypoint: "hint;hint" (source;destination, or
+    /// one per `coordinates` waypoint), as returned in `waypoints` by an
+    /// earlier `generate_hints=true` request. A hint for the same
+    /// coordinate skips the snap lookup; a stale one is ignored.
+    #[serde(default)]
+    hints: Option<String>,
+    /// Return the snapped `waypoints`, each with its snapping hint
+    #[serde(default)]
+    generate_hints: bool,
     /// Exclude road types: comma-separated list of "toll", "ferry", "motorway"
     #[serde(default)]
     exclude: Option<String>,
@@ -67,6 +135,23 @@ pub struct RouteRequest {
     /// Include debug information in response
     #[serde(default)]
     debug: bool,
+    /// Add total ascent/descent and an elevation-vs-distance profile of
+    /// the route (needs SRTM tiles in `data/srtm/`)
+    #[serde(default)]
+    elevation: bool,
+    /// Departure time (RFC 3339): each edge's conditional closures are
+    /// evaluated at the time the route enters it, and the response
+    /// carries the effective times.
+    #[serde(default)]
+    depart_at: Option<String>,
+    /// Arrival time (RFC 3339), exclusive with `depart_at`: the departure
+    /// is worked back from the arrival and edges are timed from there.
+    #[serde(default)]
+    arrive_by: Option<String>,
+    /// Output format: "json" (default) or "gpx" (GPX 1.1 track with
+    /// waypoints and SRTM elevation). Overrides the Accept header.
+    #[serde(default)]
+    format: Option<String>,
     /// Uncertainty bands (#521): set to "bands" to also return
     /// duration_q25_s / duration_q75_s (TIME quantiles of the diurnal
     /// distribution, computed on hidden q75-/q25-speed weight sets).
@@ -87,6 +172,21 @@ pub fn default_direction() -> String {
     "depart".to_string()
 }
 
+/// The `weighting` to resolve for a route request. `traffic=<v>` is the
+/// older spelling of `weighting=<v>`; giving both is ambiguous.
+pub fn route_weighting<'a>(
+    traffic: Option<&'a str>,
+    weighting: Option<&'a str>,
+) -> Result<Option<&'a str>, String> {
+    match (traffic.map(str::trim), weighting) {
+        (Some(t), None) if !t.is_empty() => Ok(Some(t)),
+        (Some(t), Some(_)) if !t.is_empty() => {
+            Err("traffic and weighting are mutually exclusive; use weighting=<variant>".to_string())
+        }
+        (_, w) => Ok(w),
+    }
+}
+
 /// Debug information about snapping
 #[derive(Debug, Serialize, ToSchema)]
 pub struct SnapInfo {
@@ -124,6 +224,47 @@ pub struct RouteAnnotations {
     /// Per-edge EBG node IDs
     #[serde(skip_serializing_if = "Option::is_none")]
     pub nodes: Option<Vec<u32>>,
+    /// Per-edge ferry flag (true while crossing on a ferry)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ferry: Option<Vec<bool>>,
+    /// Per-edge OSM way id (lower 32 bits, as stored in ebg.nodes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ways: Option<Vec<u32>>,
+}
+
+/// Which [`RouteAnnotations`] arrays a request asked for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct AnnotationFlags {
+    duration: bool,
+    distance: bool,
+    speed: bool,
+    nodes: bool,
+    ferry: bool,
+    ways: bool,
+}
+
+impl AnnotationFlags {
+    /// Parse the comma-separated `annotations` parameter.
+    fn parse(s: &str) -> Result<Self, String> {
+        let mut flags = Self::default();
+        for token in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            match token {
+                "duration" => flags.duration = true,
+                "distance" => flags.distance = true,
+                "speed" => flags.speed = true,
+                "nodes" => flags.nodes = true,
+                "ferry" => flags.ferry = true,
+                "ways" => flags.ways = true,
+                other => {
+                    return Err(format!(
+                        "Unknown annotation '{}'. Valid: duration, distance, speed, nodes, ferry, ways",
+                        other
+                    ));
+                }
+            }
+        }
+        Ok(flags)
+    }
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -152,6 +293,72 @@ pub struct RouteResponse {
     /// Pessimistic travel time (75th TIME percentile) — only with uncertainty=bands
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration_q75_s: Option<f64>,
+    /// Per-leg results, one per consecutive waypoint pair (only with
+    /// `coordinates`). The top-level duration, distance and geometry are
+    /// the legs combined.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legs: Option<Vec<RouteLeg>>,
+    /// Snapped waypoints with their snapping hints, in request order
+    /// (only if generate_hints=true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waypoints: Option<Vec<RouteWaypoint>>,
+    /// Ascent, descent and elevation profile (only if elevation=true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elevation: Option<RouteElevation>,
+    /// Effective departure time, RFC 3339 (only with depart_at / arrive_by)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depart_at: Option<String>,
+    /// Effective arrival time, RFC 3339 (only with depart_at / arrive_by)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arrive_at: Option<String>,
+    /// Directed edge (EBG node) the route arrives on, so the next leg of a
+    /// `continue_straight` route can depart the same way.
+    #[serde(skip)]
+    pub(crate) arrival_ebg: Option<u32>,
+    /// Edges of a `depart_at` / `arrive_by` route that a conditional
+    /// closes at the time the route enters them; [`timed_route`] routes
+    /// around them.
+    #[serde(skip)]
+    pub(crate) closed_on_route: Vec<u32>,
+}
+
+/// One leg of a `coordinates` route, between two consecutive waypoints
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteLeg {
+    /// Duration in seconds
+    pub duration_s: f64,
+    /// Distance in meters
+    pub distance_m: f64,
+    /// Leg geometry
+    pub geometry: RouteGeometry,
+    /// Turn-by-turn steps (only if steps=true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<RouteStep>>,
+    /// Per-edge annotations (only if annotations param is set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<RouteAnnotations>,
+    /// Snapping of this leg's ends (only if debug=true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug: Option<RouteDebugInfo>,
+}
+
+/// A snapped route waypoint (only if generate_hints=true)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RouteWaypoint {
+    /// Snapped location [lon, lat]
+    pub location: [f64; 2],
+    /// Opaque snapping hint; pass it back in `hints` with the same
+    /// coordinate to skip snapping it again
+    pub hint: String,
+}
+
+impl From<&SnapHint> for RouteWaypoint {
+    fn from(hint: &SnapHint) -> Self {
+        Self {
+            location: hint.location(),
+            hint: hint.encode(),
+        }
+    }
 }
 
 /// An alternative route
@@ -161,6 +368,9 @@ pub struct RouteAlternative {
     pub duration_s: f64,
     /// Distance in meters
     pub distance_m: f64,
+    /// Fraction (0-1) of this route's length not shared with the primary
+    /// route or any earlier alternative
+    pub dissimilarity: f64,
     /// Route geometry
     pub geometry: RouteGeometry,
     /// Turn-by-turn steps (only if steps=true)
@@ -179,6 +389,14 @@ pub struct RouteStep {
     pub geometry: RouteGeometry,
     /// Maneuver at the start of this step
     pub maneuver: StepManeuver,
+    /// Localized instruction text, e.g. "Turn left onto Rue de la Loi"
+    /// (/route only, in `language`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction: Option<String>,
+    /// Localized step distance, e.g. "1.2 km" or "0.7 mi" (/route only,
+    /// in `units`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance_text: Option<String>,
 }
 
 /// Maneuver instruction
@@ -190,7 +408,7 @@ pub struct StepManeuver {
     pub bearing_before: u16,
     /// Bearing after the maneuver (0-360 degrees)
     pub bearing_after: u16,
-    /// Turn type: depart, arrive, turn, continue, roundabout, fork, merge
+    /// Turn type: depart, arrive, turn, continue, roundabout, exit roundabout, ferry, exit ferry, fork, merge
     #[serde(rename = "type")]
     pub maneuver_type: String,
     /// Turn modifier: left, right, slight left, slight right, sharp left, sharp right, uturn, straight
@@ -199,6 +417,14 @@ pub struct StepManeuver {
     /// Road name at this maneuver (e.g. "Rue de la Loi")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Lanes of the approach road, left to right, from its OSM
+    /// `turn:lanes` (only on turns where the road is lane-tagged)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lanes: Option<Vec<StepLane>>,
+    /// Roundabout exit taken, counting from the entry (on the
+    /// `roundabout` and `exit roundabout` steps)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit: Option<u32>,
 }
 
 // ============ Handler ============
@@ -212,20 +438,34 @@ pub struct StepManeuver {
     path = "/route",
     tag = "Routing",
     summary = "Calculate route between two points",
-    description = "Computes the shortest path between source and destination using edge-based CCH.\nSupports turn-by-turn instructions with road names and alternative routes.\n\nContent negotiation:\n- `Accept: application/json` (default) -> JSON response\n- `Accept: application/gpx+xml` -> GPX 1.1 XML track",
+    description = "Computes the shortest path between source and destination using edge-based CCH, or through an ordered `coordinates` list leg by leg.\nSupports turn-by-turn instructions with road names and alternative routes.\n\nContent negotiation:\n- `Accept: application/json` (default) -> JSON response\n- `Accept: application/gpx+xml` -> GPX 1.1 XML track",
     params(
-        ("origin_lon" = f64, Query, description = "Source longitude", example = 4.3517),
-        ("origin_lat" = f64, Query, description = "Source latitude", example = 50.8503),
-        ("destination_lon" = f64, Query, description = "Destination longitude", example = 4.4017),
-        ("destination_lat" = f64, Query, description = "Destination latitude", example = 50.8603),
+        ("origin_lon" = Option<f64>, Query, description = "Source longitude", example = 4.3517),
+        ("origin_lat" = Option<f64>, Query, description = "Source latitude", example = 50.8503),
+        ("destination_lon" = Option<f64>, Query, description = "Destination longitude", example = 4.4017),
+        ("destination_lat" = Option<f64>, Query, description = "Destination latitude", example = 50.8603),
+        ("coordinates" = Option<String>, Query, description = "Ordered waypoints 'lon,lat;lon,lat;...' (2-25) instead of origin/destination; adds per-leg results in 'legs'", example = json!(null)),
         ("mode" = String, Query, description = "Transport mode (e.g. car, bike, foot — depends on available models)", example = "car"),
         ("geometries" = Option<String>, Query, description = "Geometry encoding: polyline6 (default), geojson, points", example = "polyline6"),
-        ("alternatives" = Option<u32>, Query, description = "Number of alternative routes (0-5)", example = 0),
+        ("alternatives" = Option<u32>, Query, description = "Maximum number of alternative routes (0-5)", example = 0),
+        ("alternative_max_sharing" = Option<f64>, Query, description = "Largest fraction (0-1] of an alternative's length shared with the primary and earlier alternatives (default 0.8)", example = json!(null)),
+        ("alternative_max_stretch" = Option<f64>, Query, description = "Largest alternative cost as a multiple of the primary route's (>= 1, default 1.5)", example = json!(null)),
         ("steps" = Option<bool>, Query, description = "Include turn-by-turn instructions with road names", example = true),
-        ("annotations" = Option<String>, Query, description = "Per-edge annotations: comma-separated list of 'duration', 'distance', 'speed', 'nodes'", example = json!(null)),
-        ("bearings" = Option<String>, Query, description = "Bearing hints: 'angle,range;angle,range' (source;destination). Filters snap by edge bearing.", example = json!(null)),
+        ("language" = Option<String>, Query, description = "Step instruction language: en, fr, de, nl, es (default: Accept-Language, then en)", example = json!(null)),
+        ("units" = Option<String>, Query, description = "Step distance units: 'metric' or 'imperial' (default: imperial for en-US/en-GB, metric otherwise)", example = json!(null)),
+        ("annotations" = Option<String>, Query, description = "Per-edge annotations: comma-separated list of 'duration', 'distance', 'speed', 'nodes', 'ferry', 'ways' (OSM way ids)", example = json!(null)),
+        ("bearings" = Option<String>, Query, description = "Bearing hints: 'angle,range;angle,range' (source;destination, or one per coordinate). Filters snap by edge bearing.", example = json!(null)),
+        ("approaches" = Option<String>, Query, description = "Approach side per waypoint: 'curb' or 'unrestricted' (default), 'curb;unrestricted' (source;destination, or one per coordinate). curb picks the direction of travel that keeps the waypoint on the driving-side curb.", example = json!(null)),
+        ("continue_straight" = Option<String>, Query, description = "U-turns at via points: 'true' keeps the direction of travel through each intermediate coordinate, 'false' allows turning around there, 'default' (true for car, false otherwise)", example = json!(null)),
+        ("radius" = Option<String>, Query, description = "Snap radius in metres: 'r;r' (source;destination, or one per coordinate). Default and maximum 5000; a waypoint with no road within its radius is rejected.", example = json!(null)),
+        ("hints" = Option<String>, Query, description = "Snapping hints 'hint;hint' (source;destination, or one per coordinate) from an earlier generate_hints=true response; a hint for the same coordinate skips the snap lookup, a stale one is ignored", example = json!(null)),
+        ("generate_hints" = Option<bool>, Query, description = "Return 'waypoints' with the snapped location and a snapping hint for each coordinate", example = false),
         ("exclude" = Option<String>, Query, description = "Exclude road types: comma-separated list of 'toll', 'ferry', 'motorway'", example = json!(null)),
+        ("weighting" = Option<String>, Query, description = "Optimisation metric: 'fastest' (default, travel time), 'shortest' (geometric length) or a loaded traffic variant name (e.g. 'rush_hour')", example = json!(null)),
         ("uncertainty" = Option<String>, Query, description = "Set to 'bands' to also return duration_q25_s/duration_q75_s (diurnal TIME quantiles; car only; 2 extra queries)", example = json!(null)),
+        ("depart_at" = Option<String>, Query, description = "Departure time, RFC 3339 (e.g. 2026-03-02T08:30:00+01:00). Avoids ways whose *:conditional closures apply when the route reaches them and returns depart_at/arrive_at. Conditional turn restrictions are not time-dependent; 400 with weighting=shortest when a closure applies, or across regions", example = json!(null)),
+        ("arrive_by" = Option<String>, Query, description = "Arrival time, RFC 3339; exclusive with depart_at. The route is timed backwards from the arrival: each edge is checked at the time it must be entered to arrive on time", example = json!(null)),
+        ("elevation" = Option<bool>, Query, description = "Add 'elevation' (total ascent/descent and a downsampled [distance_m, elevation_m] profile) from SRTM tiles; 503 when none are loaded", example = false),
     ),
     responses(
         (status = 200, description = "Route found", body = RouteResponse),
@@ -235,28 +475,646 @@ pub struct StepManeuver {
 )]
 // Note: route computation is fast (<10ms typical) and bounded by ConcurrencyLimitLayer(32),
 // so spawn_blocking is not needed here. /match and /trip use spawn_blocking for long computations.
+// Via routes pay one such query per leg and are capped at MAX_ROUTE_WAYPOINTS.
 pub async fn route_handler(
     State(regions): State<Arc<RegionsState>>,
     Query(req): Query<RouteRequest>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    if let Err(e) = validate_coord(req.origin_lon, req.origin_lat, "source") {
-        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+    let waypoints = match route_waypoints(&req) {
+        Ok(w) => w,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    let gpx = match route_wants_gpx(req.format.as_deref(), &headers) {
+        Ok(g) => g,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    let locale = match resolve_locale(req.language.as_deref(), req.units.as_deref(), &headers) {
+        Ok(l) => l,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    let travel_time = match TravelTime::parse(req.depart_at.as_deref(), req.arrive_by.as_deref()) {
+        Ok(t) => t,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    if req.elevation && regions.primary().elevation.is_none() {
+        return reject(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "elevation=true needs SRTM elevation data (no tiles loaded from data/srtm/)".into(),
+        );
+    }
+    if req.coordinates.is_some() {
+        return multi_leg_route(&regions, req, &waypoints, gpx, &locale, travel_time);
+    }
+    if let Err(e) = continue_straight(&req) {
+        return reject(StatusCode::BAD_REQUEST, e);
+    }
+    let elevation = req.elevation;
+    match timed_route(&regions, req, waypoints[0], waypoints[1], gpx, None) {
+        Ok(mut resp) => {
+            localize_steps(&mut resp, &locale);
+            if elevation {
+                add_elevation(&mut resp, &regions);
+            }
+            stamp_travel_time(&mut resp, travel_time);
+            Json(resp).into_response()
+        }
+        Err(resp) => resp,
+    }
+}
+
+/// Upper bound on `coordinates` waypoints per request (OSRM's default).
+const MAX_ROUTE_WAYPOINTS: usize = 25;
+
+/// Resolve the request's waypoints: either the `coordinates` list or the
+/// `origin_*` / `destination_*` pair, never both.
+fn route_waypoints(req: &RouteRequest) -> Result<Vec<[f64; 2]>, String> {
+    let pair = [
+        req.origin_lon,
+        req.origin_lat,
+        req.destination_lon,
+        req.destination_lat,
+    ];
+    let Some(coords) = req.coordinates.as_deref() else {
+        let [Some(slon), Some(slat), Some(dlon), Some(dlat)] = pair else {
+            return Err(
+                "origin_lon, origin_lat, destination_lon and destination_lat are required (or use coordinates=lon,lat;lon,lat;...)"
+                    .to_string(),
+            );
+        };
+        validate_coord(slon, slat, "source")?;
+        validate_coord(dlon, dlat, "destination")?;
+        return Ok(vec![[slon, slat], [dlon, dlat]]);
+    };
+    if pair.iter().any(Option::is_some) {
+        return Err("coordinates and origin_*/destination_* are mutually exclusive".to_string());
+    }
+    let waypoints = parse_route_coordinates(coords)?;
+    if !(2..=MAX_ROUTE_WAYPOINTS).contains(&waypoints.len()) {
+        return Err(format!(
+            "coordinates has {} waypoints, expected 2 to {}",
+            waypoints.len(),
+            MAX_ROUTE_WAYPOINTS
+        ));
+    }
+    Ok(waypoints)
+}
+
+/// Parse `lon,lat;lon,lat;...` (OSRM's coordinate list syntax).
+fn parse_route_coordinates(input: &str) -> Result<Vec<[f64; 2]>, String> {
+    input
+        .trim()
+        .split(';')
+        .enumerate()
+        .map(|(i, pair)| {
+            let (lon, lat) = pair
+                .split_once(',')
+                .ok_or_else(|| format!("waypoint {} ('{}') must be 'lon,lat'", i, pair))?;
+            let lon: f64 = lon
+                .trim()
+                .parse()
+                .map_err(|_| format!("waypoint {} has invalid longitude '{}'", i, lon))?;
+            let lat: f64 = lat
+                .trim()
+                .parse()
+                .map_err(|_| format!("waypoint {} has invalid latitude '{}'", i, lat))?;
+            validate_coord(lon, lat, &format!("waypoint {}", i))?;
+            Ok([lon, lat])
+        })
+        .collect()
+}
+
+/// Route through every waypoint in order: one [`route_single`] query per
+/// consecutive pair, stitched into a combined geometry and summary.
+///
+/// Legs are computed with point geometry and re-encoded in the requested
+/// format, so the combined line is a plain concatenation. Alternatives are
+/// not offered for via routes; `bearings` takes one pair per waypoint and
+/// each leg gets the pairs of its two ends. With `continue_straight`, every
+/// leg after the first departs on the directed edge the previous one
+/// arrived on, so the route cannot turn around at a via point. With
+/// `depart_at`, each leg is timed from the arrival of the one before;
+/// `arrive_by` times the legs the same way from the departure that lands
+/// on the arrival, re-timing while closures change the total duration.
+fn multi_leg_route(
+    regions: &RegionsState,
+    req: RouteRequest,
+    waypoints: &[[f64; 2]],
+    gpx: bool,
+    locale: &Locale,
+    travel_time: Option<TravelTime>,
+) -> axum::response::Response {
+    let geom_format = match GeometryFormat::parse(&req.geometries) {
+        Ok(f) => f,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    if req.alternatives > 0 && waypoints.len() > 2 {
+        return reject(
+            StatusCode::BAD_REQUEST,
+            "alternatives are not supported with more than 2 coordinates".into(),
+        );
+    }
+    let bearings = match per_coordinate(req.bearings.as_deref(), "bearings", waypoints.len()) {
+        Ok(b) => b,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    let radius = match per_coordinate(req.radius.as_deref(), "radius", waypoints.len()) {
+        Ok(r) => r,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    let approaches = match per_coordinate(req.approaches.as_deref(), "approaches", waypoints.len())
+    {
+        Ok(a) => a,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    let hints = match per_coordinate(req.hints.as_deref(), "hints", waypoints.len()) {
+        Ok(h) => h,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+    let straight = match continue_straight(&req) {
+        Ok(c) => c,
+        Err(e) => return reject(StatusCode::BAD_REQUEST, e),
+    };
+
+    // Route every leg in turn; a timed trip departs each leg when the one
+    // before arrives.
+    // Err is the finished axum Response, as for route_single.
+    #[allow(clippy::result_large_err)]
+    let route_legs = |depart: Option<TravelTime>| {
+        let mut legs: Vec<RouteResponse> = Vec::with_capacity(waypoints.len() - 1);
+        for (i, ends) in waypoints.windows(2).enumerate() {
+            let mut leg_req = req.clone();
+            leg_req.geometries = "points".to_string();
+            leg_req.bearings = leg_slots(&bearings, i);
+            leg_req.radius = leg_slots(&radius, i);
+            leg_req.approaches = leg_slots(&approaches, i);
+            leg_req.hints = leg_slots(&hints, i);
+            leg_req.arrive_by = None;
+            leg_req.depart_at = depart.map(|t| {
+                let elapsed: f64 = legs.iter().map(|l| l.duration_s).sum();
+                format_time(&t.times(elapsed).1)
+            });
+            let depart_on = legs.last().and_then(|l| l.arrival_ebg).filter(|_| straight);
+            legs.push(timed_route(
+                regions, leg_req, ends[0], ends[1], false, depart_on,
+            )?);
+        }
+        Ok::<_, axum::response::Response>(legs)
+    };
+    let total_duration = |legs: &[RouteResponse]| legs.iter().map(|l| l.duration_s).sum::<f64>();
+    let (leg_routes, travel_time) = match travel_time {
+        // arrive_by: time the legs forward from the departure that lands
+        // on the arrival. Closures met on the way change the duration, so
+        // re-time from the new one until it settles.
+        Some(TravelTime::ArriveBy(arrive)) => {
+            let mut duration = match route_legs(None) {
+                Ok(legs) => total_duration(&legs),
+                Err(resp) => return resp,
+            };
+            let mut rounds = 0;
+            loop {
+                let depart = TravelTime::DepartAt(TravelTime::ArriveBy(arrive).times(duration).0);
+                let legs = match route_legs(Some(depart)) {
+                    Ok(legs) => legs,
+                    Err(resp) => return resp,
+                };
+                rounds += 1;
+                if total_duration(&legs) == duration || rounds == MAX_CLOSURE_ROUNDS {
+                    break (legs, Some(depart));
+                }
+                duration = total_duration(&legs);
+            }
+        }
+        _ => match route_legs(travel_time) {
+            Ok(legs) => (legs, travel_time),
+            Err(resp) => return resp,
+        },
+    };
+
+    let mut legs = Vec::with_capacity(leg_routes.len());
+    let mut points: Vec<Point> = Vec::new();
+    let mut bands = Some((0.0, 0.0));
+    let mut alternatives = None;
+    let mut leg_hints: Vec<Option<[SnapHint; 2]>> = Vec::new();
+    let arrival_ebg = leg_routes.last().and_then(|l| l.arrival_ebg);
+    for leg in leg_routes {
+        leg_hints.push(leg.waypoints.as_deref().and_then(|ends| match ends {
+            [src, dst] => Some([
+                SnapHint::decode(&src.hint).ok()?,
+                SnapHint::decode(&dst.hint).ok()?,
+            ]),
+            _ => None,
+        }));
+
+        let leg_points = leg.geometry.coordinates.unwrap_or_default();
+        let skip = usize::from(
+            points
+                .last()
+                .zip(leg_points.first())
+                .is_some_and(|(a, b)| a.lon == b.lon && a.lat == b.lat),
+        );
+        points.extend(leg_points.iter().skip(skip).copied());
+        bands = bands
+            .zip(leg.duration_q25_s.zip(leg.duration_q75_s))
+            .map(|((q25, q75), (a, b))| (q25 + a, q75 + b));
+        // Only a single-leg request can carry alternatives.
+        alternatives = leg.alternatives.map(|alts| {
+            alts.into_iter()
+                .map(|alt| RouteAlternative {
+                    geometry: reencode_geometry(alt.geometry, geom_format),
+                    steps: alt.steps.map(|s| reencode_steps(s, geom_format)),
+                    ..alt
+                })
+                .collect::<Vec<_>>()
+        });
+        legs.push(RouteLeg {
+            duration_s: leg.duration_s,
+            distance_m: leg.distance_m,
+            geometry: RouteGeometry::from_points(leg_points, geom_format),
+            steps: leg.steps.map(|s| reencode_steps(s, geom_format)),
+            annotations: leg.annotations,
+            debug: leg.debug,
+        });
+    }
+
+    if gpx {
+        let primary = regions.primary();
+        return gpx_response(format_gpx(
+            &points,
+            waypoints,
+            primary.elevation.as_ref(),
+            "Route",
+        ));
+    }
+    let mut resp = RouteResponse {
+        duration_s: legs.iter().map(|l| l.duration_s).sum(),
+        distance_m: legs.iter().map(|l| l.distance_m).sum(),
+        geometry: RouteGeometry::from_points(points, geom_format),
+        steps: None,
+        annotations: None,
+        alternatives,
+        debug: None,
+        duration_q25_s: bands.map(|b| b.0),
+        duration_q75_s: bands.map(|b| b.1),
+        legs: Some(legs),
+        waypoints: if req.generate_hints {
+            via_waypoints(&leg_hints)
+        } else {
+            None
+        },
+        elevation: None,
+        depart_at: None,
+        arrive_at: None,
+        arrival_ebg,
+        closed_on_route: Vec::new(),
+    };
+    localize_steps(&mut resp, locale);
+    if req.elevation {
+        add_elevation(&mut resp, regions);
+    }
+    stamp_travel_time(&mut resp, travel_time);
+    Json(resp).into_response()
+}
+
+/// Waypoints of a via route from its legs' `[source, destination]` hints.
+/// A via point is the destination of one leg and the source of the next,
+/// so its hint carries the candidates of both snaps. `None` when a leg
+/// has no hints (a cross-region leg).
+fn via_waypoints(leg_hints: &[Option<[SnapHint; 2]>]) -> Option<Vec<RouteWaypoint>> {
+    let mut waypoints = Vec::with_capacity(leg_hints.len() + 1);
+    let mut arrived: Option<&SnapHint> = None;
+    for ends in leg_hints {
+        let [src, dst] = ends.as_ref()?;
+        let hint = match arrived {
+            Some(a) => a.merge(src),
+            None => src.clone(),
+        };
+        waypoints.push(RouteWaypoint::from(&hint));
+        arrived = Some(dst);
+    }
+    waypoints.push(RouteWaypoint::from(arrived?));
+    Some(waypoints)
+}
+
+/// Split a `;`-separated per-coordinate parameter, allowing at most one
+/// slot per waypoint.
+fn per_coordinate<'a>(
+    value: Option<&'a str>,
+    name: &str,
+    n_waypoints: usize,
+) -> Result<Option<Vec<&'a str>>, String> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let slots: Vec<&str> = value.split(';').collect();
+    if slots.len() > n_waypoints {
+        return Err(format!(
+            "{} has {} entries, expected at most {} (one per coordinate)",
+            name,
+            slots.len(),
+            n_waypoints
+        ));
+    }
+    Ok(Some(slots))
+}
+
+/// The `start;end` slots of leg `i` from a [`per_coordinate`] split.
+fn leg_slots(slots: &Option<Vec<&str>>, i: usize) -> Option<String> {
+    slots.as_ref().map(|s| {
+        let at = |k: usize| s.get(k).copied().unwrap_or("");
+        format!("{};{}", at(i), at(i + 1))
+    })
+}
+
+/// Per-endpoint snap radii `[source, destination]` from `radius=`, in
+/// metres; unset slots default to the full snap search radius.
+fn endpoint_radii(req: &RouteRequest) -> Result<[f64; 2], String> {
+    let default = super::snap_index::MAX_SNAP_DISTANCE_M;
+    let Some(radius) = req.radius.as_deref() else {
+        return Ok([default; 2]);
+    };
+    let radii = parse_radiuses(radius)?;
+    if radii.len() > 2 {
+        return Err(format!(
+            "radius has {} entries, expected at most 2 (source;destination)",
+            radii.len()
+        ));
+    }
+    let at = |k: usize| radii.get(k).copied().flatten().unwrap_or(default);
+    Ok([at(0), at(1)])
+}
+
+/// Per-endpoint snapping hints `[source, destination]` from `hints=`.
+fn endpoint_hints(req: &RouteRequest) -> Result<[Option<SnapHint>; 2], String> {
+    let Some(hints) = req.hints.as_deref() else {
+        return Ok([None, None]);
+    };
+    let mut parsed = parse_hints(hints)?;
+    if parsed.len() > 2 {
+        return Err(format!(
+            "hints has {} entries, expected at most 2 (source;destination)",
+            parsed.len()
+        ));
+    }
+    parsed.resize(2, None);
+    let dst = parsed.pop().flatten();
+    Ok([parsed.pop().flatten(), dst])
+}
+
+/// `(max_sharing, max_stretch)` for alternative routes, with defaults.
+fn alternative_limits(req: &RouteRequest) -> Result<(f64, f64), String> {
+    let sharing = req.alternative_max_sharing.unwrap_or(0.8);
+    if !(sharing > 0.0 && sharing <= 1.0) {
+        return Err(format!(
+            "alternative_max_sharing must be in (0, 1], got {}",
+            sharing
+        ));
+    }
+    let stretch = req.alternative_max_stretch.unwrap_or(1.5);
+    if !(stretch.is_finite() && stretch >= 1.0) {
+        return Err(format!(
+            "alternative_max_stretch must be >= 1, got {}",
+            stretch
+        ));
+    }
+    Ok((sharing, stretch))
+}
+
+/// Fraction of `path`'s length (per EBG node, via `length_of`) on nodes
+/// already in `covered`. 1.0 for a zero-length path.
+fn shared_fraction(
+    path: &[u32],
+    covered: &std::collections::HashSet<u32>,
+    length_of: impl Fn(u32) -> f64,
+) -> f64 {
+    let (mut shared, mut total) = (0.0, 0.0);
+    for &eid in path {
+        let len = length_of(eid);
+        total += len;
+        if covered.contains(&eid) {
+            shared += len;
+        }
+    }
+    if total > 0.0 { shared / total } else { 1.0 }
+}
+
+/// Resolve `continue_straight=default|true|false`; `default` forbids
+/// U-turns at via points for car only.
+fn continue_straight(req: &RouteRequest) -> Result<bool, String> {
+    match req.continue_straight.as_deref().map(str::trim) {
+        None | Some("default") => Ok(req.mode.eq_ignore_ascii_case("car")),
+        Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        Some(other) => Err(format!(
+            "Invalid continue_straight '{}'. Expected 'default', 'true' or 'false'.",
+            other
+        )),
+    }
+}
+
+/// `continue_straight` at a via point: a source candidate on the reverse
+/// twin of `arrived_on` would turn around at the waypoint, so swap it for
+/// `arrived_on` itself when that is a valid source.
+fn no_uturn_candidate(
+    mode_data: &super::state::ModeData,
+    edge_filter: Option<&[u64]>,
+    cand: (u32, f64, f64, f64),
+    arrived_on: Option<u32>,
+) -> (u32, f64, f64, f64) {
+    match arrived_on {
+        Some(e)
+            if cand.0 == e ^ 1
+                && super::phantom::seed_valid(mode_data, SnapRole::Src, edge_filter, e) =>
+        {
+            (e, cand.1, cand.2, cand.3)
+        }
+        _ => cand,
     }
-    if let Err(e) = validate_coord(req.destination_lon, req.destination_lat, "destination") {
-        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+}
+
+/// Per-endpoint approaches `[source, destination]` from `approaches=`.
+fn endpoint_approaches(req: &RouteRequest) -> Result<[Approach; 2], String> {
+    let Some(approaches) = req.approaches.as_deref() else {
+        return Ok([Approach::Unrestricted; 2]);
+    };
+    let parsed: Vec<Approach> = approaches
+        .split(';')
+        .map(Approach::parse)
+        .collect::<Result<_, _>>()?;
+    if parsed.len() > 2 {
+        return Err(format!(
+            "approaches has {} entries, expected at most 2 (source;destination)",
+            parsed.len()
+        ));
     }
+    let at = |k: usize| parsed.get(k).copied().unwrap_or_default();
+    Ok([at(0), at(1)])
+}
 
+/// `approaches=curb` on a single-edge snap: swap the snapped directed edge
+/// for its twin when the waypoint sits on the far side of its direction of
+/// travel and the twin is a valid endpoint for `role`. The twin shares the
+/// geometry, so the snapped point and distance stay the same.
+fn curbside_candidate(
+    state: &ServerState,
+    mode_data: &super::state::ModeData,
+    role: SnapRole,
+    edge_filter: Option<&[u64]>,
+    cand: (u32, f64, f64, f64),
+    [lon, lat]: [f64; 2],
+) -> (u32, f64, f64, f64) {
+    use super::phantom::{on_curb_side, seed_valid};
+    let side = DrivingSide::from_env();
+    let twin = cand.0 ^ 1;
+    if on_curb_side(&state.ebg_nodes, &state.edge_geom, cand.0, lon, lat, side)
+        || !seed_valid(mode_data, role, edge_filter, twin)
+    {
+        return cand;
+    }
+    (twin, cand.1, cand.2, cand.3)
+}
+
+/// Re-encode a point-format geometry (as [`multi_leg_route`] requests it)
+/// in `format`.
+fn reencode_geometry(geometry: RouteGeometry, format: GeometryFormat) -> RouteGeometry {
+    RouteGeometry::from_points(geometry.coordinates.unwrap_or_default(), format)
+}
+
+fn reencode_steps(steps: Vec<RouteStep>, format: GeometryFormat) -> Vec<RouteStep> {
+    steps
+        .into_iter()
+        .map(|step| RouteStep {
+            geometry: reencode_geometry(step.geometry, format),
+            ..step
+        })
+        .collect()
+}
+
+/// Fill in the localized `instruction` / `distance_text` of every step in
+/// the response: the route's, its alternatives' and its legs'.
+fn localize_steps(resp: &mut RouteResponse, locale: &Locale) {
+    let alternatives = resp.alternatives.iter_mut().flatten().map(|a| &mut a.steps);
+    let legs = resp.legs.iter_mut().flatten().map(|l| &mut l.steps);
+    let lists = std::iter::once(&mut resp.steps)
+        .chain(alternatives)
+        .chain(legs);
+    for step in lists.flatten().flatten() {
+        let m = &step.maneuver;
+        step.instruction =
+            Some(locale.instruction(&m.maneuver_type, m.modifier.as_deref(), m.name.as_deref()));
+        step.distance_text = Some(locale.distance(step.distance_m));
+    }
+}
+
+/// Most `[distance_m, elevation_m]` pairs in an `elevation=true` profile.
+const ELEVATION_PROFILE_POINTS: usize = 200;
+
+/// Fill in `elevation` by sampling the SRTM tiles along the route geometry.
+fn add_elevation(resp: &mut RouteResponse, regions: &RegionsState) {
+    let state = regions.primary();
+    if let Some(dem) = &state.elevation {
+        let path = resp.geometry.lat_lon();
+        resp.elevation = dem.route_elevation(&path, ELEVATION_PROFILE_POINTS);
+    }
+}
+
+/// Fill in the effective `depart_at` / `arrive_at` of a timed route.
+fn stamp_travel_time(resp: &mut RouteResponse, travel_time: Option<TravelTime>) {
+    if let Some(t) = travel_time {
+        let (depart, arrive) = t.times(resp.duration_s);
+        resp.depart_at = Some(format_time(&depart));
+        resp.arrive_at = Some(format_time(&arrive));
+    }
+}
+
+/// JSON error response with `code`.
+fn reject(code: StatusCode, error: String) -> axum::response::Response {
+    (code, Json(ErrorResponse { error })).into_response()
+}
+
+/// Upper bound on reroutes of one timed route (or re-timings of a via
+/// route) around the closures it runs into.
+const MAX_CLOSURE_ROUNDS: usize = 8;
+
+/// [`route_single`] honouring `depart_at` / `arrive_by`: every edge the
+/// route enters while a conditional closes it is blocked and the route is
+/// recomputed, until each edge is open at the time the route uses it.
+/// Blocked edges stay blocked for the request even if the new route would
+/// reach them at another time. Untimed requests make a single query.
+// Err is the finished axum Response, as for route_single.
+#[allow(clippy::result_large_err)]
+fn timed_route(
+    regions: &RegionsState,
+    req: RouteRequest,
+    origin: [f64; 2],
+    destination: [f64; 2],
+    gpx: bool,
+    depart_on: Option<u32>,
+) -> Result<RouteResponse, axum::response::Response> {
+    if req.depart_at.is_none() && req.arrive_by.is_none() {
+        return route_single(regions, req, origin, destination, gpx, depart_on, &[]);
+    }
+    let mut closed: Vec<u32> = Vec::new();
+    for _ in 0..MAX_CLOSURE_ROUNDS {
+        let resp = route_single(
+            regions,
+            req.clone(),
+            origin,
+            destination,
+            false,
+            depart_on,
+            &closed,
+        )?;
+        if resp.closed_on_route.is_empty() {
+            if gpx {
+                return route_single(regions, req, origin, destination, true, depart_on, &closed);
+            }
+            return Ok(resp);
+        }
+        closed.extend_from_slice(&resp.closed_on_route);
+        closed.sort_unstable();
+        closed.dedup();
+    }
+    Err(reject(
+        StatusCode::NOT_FOUND,
+        format!(
+            "No route found that is open at the requested time (gave up after routing around {} closed edges)",
+            closed.len()
+        ),
+    ))
+}
+
+/// Route one leg from `origin` to `destination`.
+///
+/// `Ok` is a route to serialize as JSON. `Err` is a finished response that
+/// goes back unchanged: an error or, when `gpx` is set, a GPX track.
+/// `depart_on` is the directed edge the previous leg arrived on when
+/// `continue_straight` forbids turning around at `origin`. `closed` are
+/// EBG edges to block, the closures [`timed_route`] found on earlier
+/// attempts; a timed request reports the ones it still runs into in
+/// `closed_on_route`.
+// Err is the finished axum Response, returned as-is by the handler; boxing
+// it would only add an indirection on the error path.
+#[allow(clippy::result_large_err)]
+fn route_single(
+    regions: &RegionsState,
+    req: RouteRequest,
+    origin: [f64; 2],
+    destination: [f64; 2],
+    gpx: bool,
+    depart_on: Option<u32>,
+    closed: &[u32],
+) -> Result<RouteResponse, axum::response::Response> {
     // Region dispatch (#91 Phase 2): when an overlay is loaded, hand
     // cross-region queries off to the cross-region coordinator instead
     // of returning 501. Same-region queries always fall through to the
     // existing intra-region implementation below.
     let started_dispatch = std::time::Instant::now();
     let (state, region_id): (Arc<ServerState>, String) = match regions.dispatch_p2p_with_overlay(
-        req.origin_lon,
-        req.origin_lat,
-        req.destination_lon,
-        req.destination_lat,
+        origin[0],
+        origin[1],
+        destination[0],
+        destination[1],
         &req.mode,
     ) {
         Ok(super::regions::P2pPlan::SameRegion { state, region }) => (state, region),
@@ -267,157 +1125,106 @@ pub async fn route_handler(
             dst_region,
             overlay,
         }) => {
-            return cross_region_route_inner(
-                src_state, src_region, dst_state, dst_region, overlay, req,
-            )
-            .into_response();
+            if !gpx {
+                return cross_region_route_inner(
+                    src_state,
+                    src_region,
+                    dst_state,
+                    dst_region,
+                    overlay,
+                    req,
+                    [origin, destination],
+                );
+            }
+            let elevation_state = regions.primary();
+            let req = RouteRequest {
+                geometries: "points".to_string(),
+                ..req
+            };
+            let route = cross_region_route_inner(
+                src_state,
+                src_region,
+                dst_state,
+                dst_region,
+                overlay,
+                req,
+                [origin, destination],
+            )?;
+            return Err(gpx_response(format_gpx(
+                &route.geometry.coordinates.unwrap_or_default(),
+                &[origin, destination],
+                elevation_state.elevation.as_ref(),
+                "Route",
+            )));
         }
         Err(e) => {
             let (code, body) = e.into_response_parts();
-            return (code, Json(body)).into_response();
+            return Err((code, Json(body)).into_response());
         }
     };
 
-    // Resolve the effective mode name. If `traffic=<v>` is set, synthesize
-    // `<mode>_<v>` and look that up — produced at pipeline time by
-    // `step8-customize --traffic ...`. Falling back to the base mode is
-    // intentionally disabled: a 400 is preferable to silently routing on
-    // freeflow weights when the caller asked for traffic.
-    let effective_mode_name = match &req.traffic {
-        Some(v) if !v.trim().is_empty() => format!("{}_{}", req.mode, v.trim()),
-        _ => req.mode.clone(),
-    };
-    let mode = match parse_mode(&effective_mode_name, &state.mode_lookup) {
-        Ok(m) => m,
-        Err(_) if req.traffic.is_some() => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!(
-                        "Unknown traffic variant '{}' for mode '{}'. Build it with `step8-customize --traffic`.",
-                        req.traffic.as_deref().unwrap_or(""),
-                        req.mode
-                    ),
-                }),
-            )
-                .into_response();
-        }
+    // Resolve the weight set. A traffic variant (`weighting=<v>` or the
+    // legacy `traffic=<v>`) routes against the synthetic mode `<mode>_<v>`
+    // produced by `step8-customize --traffic ...`. Falling back to the base
+    // mode is intentionally disabled: a 400 is preferable to silently
+    // routing on freeflow weights when the caller asked for traffic.
+    let (mode, weighting) = match route_weighting(req.traffic.as_deref(), req.weighting.as_deref())
+        .and_then(|w| resolve_weighting(&req.mode, w, &state.mode_lookup))
+    {
+        Ok(pair) => pair,
         Err(e) => {
-            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+            return Err(reject(StatusCode::BAD_REQUEST, e));
         }
     };
 
     let geom_format = match GeometryFormat::parse(&req.geometries) {
         Ok(f) => f,
         Err(e) => {
-            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+            return Err(reject(StatusCode::BAD_REQUEST, e));
         }
     };
 
     // Parse and validate annotations parameter
-    let annotation_flags = if let Some(ref ann_str) = req.annotations {
-        let mut want_duration = false;
-        let mut want_distance = false;
-        let mut want_speed = false;
-        let mut want_nodes = false;
-        if !ann_str.is_empty() {
-            for token in ann_str.split(',') {
-                let token = token.trim();
-                match token {
-                    "duration" => want_duration = true,
-                    "distance" => want_distance = true,
-                    "speed" => want_speed = true,
-                    "nodes" => want_nodes = true,
-                    other => {
-                        return (
-                            StatusCode::BAD_REQUEST,
-                            Json(ErrorResponse {
-                                error: format!(
-                                    "Unknown annotation '{}'. Valid: duration, distance, speed, nodes",
-                                    other
-                                ),
-                            }),
-                        )
-                            .into_response();
-                    }
-                }
-            }
-        }
-        Some((want_duration, want_distance, want_speed, want_nodes))
-    } else {
-        None
+    let annotation_flags = match req.annotations.as_deref().map(AnnotationFlags::parse) {
+        None => None,
+        Some(Ok(flags)) => Some(flags),
+        Some(Err(e)) => return Err(reject(StatusCode::BAD_REQUEST, e)),
     };
 
     // Parse bearing hints: "angle,range;angle,range" (source;destination)
-    let bearing_hints: Option<Vec<(u16, u16)>> = if let Some(ref b_str) = req.bearings {
-        let mut hints = Vec::new();
-        for part in b_str.split(';') {
-            let part = part.trim();
-            if part.is_empty() {
-                hints.push((0, 360)); // no constraint
-                continue;
-            }
-            let tokens: Vec<&str> = part.split(',').collect();
-            if tokens.len() != 2 {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: format!(
-                            "Invalid bearing format '{}'. Expected 'angle,range'.",
-                            part
-                        ),
-                    }),
-                )
-                    .into_response();
-            }
-            let angle: u16 = match tokens[0].trim().parse() {
-                Ok(v) if v <= 360 => v,
-                _ => {
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse {
-                            error: format!("Invalid bearing angle: '{}'", tokens[0]),
-                        }),
-                    )
-                        .into_response();
-                }
-            };
-            let range: u16 = match tokens[1].trim().parse() {
-                Ok(v) if v <= 180 => v,
-                _ => {
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse {
-                            error: format!("Invalid bearing range: '{}'", tokens[1]),
-                        }),
-                    )
-                        .into_response();
-                }
-            };
-            hints.push((angle, range));
-        }
-        if hints.len() > 2 {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!(
-                        "bearings has {} pairs, expected at most 2 (source;destination)",
-                        hints.len()
-                    ),
-                }),
-            )
-                .into_response();
-        }
-        Some(hints)
-    } else {
-        None
+    let bearing_hints = match req.bearings.as_deref().map(parse_bearings).transpose() {
+        Ok(h) => h,
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
+    };
+    if let Some(h) = &bearing_hints
+        && h.len() > 2
+    {
+        return Err(reject(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "bearings has {} pairs, expected at most 2 (source;destination)",
+                h.len()
+            ),
+        ));
+    }
+    let [src_radius, dst_radius] = match endpoint_radii(&req) {
+        Ok(r) => r,
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
+    };
+    let [src_approach, dst_approach] = match endpoint_approaches(&req) {
+        Ok(a) => a,
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
+    };
+    let [src_hint, dst_hint] = match endpoint_hints(&req) {
+        Ok(h) => h,
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
     };
 
     // Parse exclude parameter
-    let exclude_mask = match super::exclude::parse_exclude_option(&req.exclude) {
+    let exclude_mask = match state.parse_exclude(&req.exclude) {
         Ok(m) => m,
         Err(e) => {
-            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+            return Err(reject(StatusCode::BAD_REQUEST, e));
         }
     };
 
@@ -425,56 +1232,86 @@ pub async fn route_handler(
     let avoid_json = match super::avoid::parse_avoid_option(&req.avoid_polygons) {
         Ok(v) => v,
         Err(e) => {
-            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+            return Err(reject(StatusCode::BAD_REQUEST, e));
         }
     };
 
+    // depart_at / arrive_by: the route's edges are checked against the
+    // mode's conditional closures at the time the route enters them.
+    let travel_time = match TravelTime::parse(req.depart_at.as_deref(), req.arrive_by.as_deref()) {
+        Ok(t) => t,
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
+    };
+    let closures = travel_time.and_then(|_| state.edge_conditionals.get(&req.mode.to_lowercase()));
+    let closures_hit = |route: &[(u32, f64)], duration_s: f64| match (travel_time, closures) {
+        (Some(t), Some(c)) => c.closed_along(&t, duration_s, route),
+        _ => Vec::new(),
+    };
+
+    // Shortest routes run the distance metric through the custom-weight
+    // query path; avoid weights are time-only, so the two cannot be
+    // combined.
+    let shortest = weighting.is_shortest();
+    if shortest && avoid_json.is_some() {
+        return Err(reject(
+            StatusCode::BAD_REQUEST,
+            "weighting=shortest is incompatible with avoid_polygons".into(),
+        ));
+    }
+    if shortest && !closed.is_empty() {
+        return Err(reject(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "weighting=shortest cannot route around the {} road closures its route runs into at the requested time",
+                closed.len()
+            ),
+        ));
+    }
+
     let mode_data = state.get_mode(mode);
     let num_alternatives = (req.alternatives.min(5)) as usize;
+    let (max_sharing, max_stretch) = match alternative_limits(&req) {
+        Ok(l) => l,
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
+    };
 
     // #521 uncertainty bands — explicit opt-in, plain car path only.
     let band_durations: Option<(f64, f64)> = match req.uncertainty.as_deref() {
         None => None,
         Some("bands") => {
             if req.mode != "car"
-                || req.traffic.is_some()
+                || weighting != Weighting::Fastest
                 || req.avoid_polygons.is_some()
                 || req.exclude.is_some()
                 || req.bearings.is_some()
             {
-                return (
+                return Err(reject(
                     StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: "uncertainty=bands is car-only and incompatible with traffic/avoid_polygons/exclude/bearings".into(),
-                    }),
-                )
-                    .into_response();
+                    "uncertainty=bands is car-only and incompatible with traffic/avoid_polygons/exclude/bearings/weighting other than fastest".into(),
+                ));
             }
             let Some((pess, opt)) = state.band_modes() else {
-                return (
+                return Err(reject(
                     StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: "uncertainty bands not available: the loaded edge_speeds table has no q25/q75 columns".into(),
-                    }),
-                )
-                    .into_response();
+                    "uncertainty bands not available: the loaded edge_speeds table has no q25/q75 columns".into(),
+                ));
             };
             // TIME quantiles: optimistic (q25 time) <- fluid q75-speed set.
             let q25 = band_p2p_duration(
                 &state,
                 opt,
-                req.origin_lon,
-                req.origin_lat,
-                req.destination_lon,
-                req.destination_lat,
+                origin[0],
+                origin[1],
+                destination[0],
+                destination[1],
             );
             let q75 = band_p2p_duration(
                 &state,
                 pess,
-                req.origin_lon,
-                req.origin_lat,
-                req.destination_lon,
-                req.destination_lat,
+                origin[0],
+                origin[1],
+                destination[0],
+                destination[1],
             );
             match (q25, q75) {
                 (Some(a), Some(b)) => Some((a, b)),
@@ -482,13 +1319,10 @@ pub async fn route_handler(
             }
         }
         Some(other) => {
-            return (
+            return Err(reject(
                 StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("unknown uncertainty value '{other}' (expected 'bands')"),
-                }),
-            )
-                .into_response();
+                format!("unknown uncertainty value '{other}' (expected 'bands')"),
+            ));
         }
     };
 
@@ -497,7 +1331,9 @@ pub async fn route_handler(
     // node that is either incident to a changed edge OR a potential middle of a
     // triangle relaxing one, so it now matches the full path's output. /route
     // stays on the fast time-only path.
-    let avoid_entry = if let Some(ref avoid_str) = avoid_json {
+    let avoid_entry = if let Some(ref avoid_str) = avoid_json
+        && closed.is_empty()
+    {
         match super::avoid::compute_avoid_weights_time_only(
             &state,
             &mode_data,
@@ -506,18 +1342,42 @@ pub async fn route_handler(
         ) {
             Ok(entry) => Some(entry),
             Err(e) => {
-                return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+                return Err(reject(StatusCode::BAD_REQUEST, e));
             }
         }
     } else {
         None
     };
+    // A timed route retried around closures blocks them on top of the
+    // polygons, on per-request time-only weights.
+    let closure_weights = if closed.is_empty() {
+        None
+    } else {
+        match super::avoid::compute_closure_weights(
+            &state,
+            &mode_data,
+            avoid_json.as_deref(),
+            closed,
+            exclude_mask,
+        ) {
+            Ok(weights) => Some(weights),
+            Err(e) => {
+                return Err(reject(StatusCode::BAD_REQUEST, e));
+            }
+        }
+    };
+    let avoid_weights: Option<(&super::state::CchWeights, &[u8])> =
+        match (&closure_weights, &avoid_entry) {
+            (Some(c), _) => Some((&c.time_weights, &c.flags)),
+            (None, Some(entry)) => Some((&entry.weights.time_weights, &entry.flags)),
+            (None, None) => None,
+        };
 
     // Build snap mask (with optional avoid/exclude filtering)
-    let snap_mask: std::borrow::Cow<'_, [u64]> = if let Some(ref entry) = avoid_entry {
+    let snap_mask: std::borrow::Cow<'_, [u64]> = if let Some((_, flags)) = avoid_weights {
         std::borrow::Cow::Owned(super::avoid::build_avoid_mask(
             &mode_data.mask,
-            &entry.flags,
+            flags,
             exclude_mask.map(|exc| (state.edge_exclude_flags.as_slice(), exc)),
         ))
     } else if let Some(exc) = exclude_mask {
@@ -579,16 +1439,58 @@ pub async fn route_handler(
     // the geometrically-closest candidate is the wrong same-geometry
     // directional twin or a disconnected mode-filtered island.
     const SNAP_K: usize = 64;
-    let src_bearing = bearing_hints.as_ref().and_then(|h| h.first().copied());
-    let dst_bearing = bearing_hints.as_ref().and_then(|h| h.get(1).copied());
+    let src_bearing = bearing_hints
+        .as_ref()
+        .and_then(|h| h.first().copied().flatten());
+    let dst_bearing = bearing_hints
+        .as_ref()
+        .and_then(|h| h.get(1).copied().flatten());
+    // An explicit bearing already fixes the direction of travel.
+    let src_approach = match src_bearing {
+        Some(_) => Approach::Unrestricted,
+        None => src_approach,
+    };
+    let dst_approach = match dst_bearing {
+        Some(_) => Approach::Unrestricted,
+        None => dst_approach,
+    };
+
+    // hints=: the candidates a hint recorded for this coordinate stand in
+    // for the index lookups below (the K=1 snap and the phantom K=8
+    // fetch). A stale hint, or one with no candidate valid for this
+    // request, leaves the endpoint to the index.
+    let src_hinted = src_hint.as_ref().and_then(|h| {
+        h.candidates_for(
+            &state,
+            &mode_data,
+            mode,
+            origin,
+            SnapRole::Src,
+            Some(&snap_mask),
+            src_radius,
+        )
+    });
+    let dst_hinted = dst_hint.as_ref().and_then(|h| {
+        h.candidates_for(
+            &state,
+            &mode_data,
+            mode,
+            destination,
+            SnapRole::Dst,
+            Some(&snap_mask),
+            dst_radius,
+        )
+    });
 
     // PHASE 1: K=1 snap for both endpoints. Bearing-filtered queries
     // were already K=1 in the previous implementation; non-bearing
     // queries now start at K=1 too and only escalate on failure.
-    let mut src_candidates: Vec<(u32, f64, f64, f64)> = if let Some((angle, range)) = src_bearing {
+    let mut src_candidates: Vec<(u32, f64, f64, f64)> = if let Some(h) = &src_hinted {
+        vec![h[0]]
+    } else if let Some((angle, range)) = src_bearing {
         match state.snap_index.snap_with_bearing_filtered_role(
-            req.origin_lon,
-            req.origin_lat,
+            origin[0],
+            origin[1],
             mode.0,
             angle,
             range,
@@ -600,8 +1502,8 @@ pub async fn route_handler(
         }
     } else {
         match state.snap_index.snap_with_info_filtered_role(
-            req.origin_lon,
-            req.origin_lat,
+            origin[0],
+            origin[1],
             mode.0,
             Some(&snap_mask),
             src_role_filter,
@@ -611,19 +1513,24 @@ pub async fn route_handler(
         }
     };
     if src_candidates.is_empty() {
-        return (
+        return Err(reject(
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Could not snap source to road network".to_string(),
-            }),
-        )
-            .into_response();
+            "Could not snap source to road network".to_string(),
+        ));
+    }
+    if src_candidates[0].3 > src_radius {
+        return Err(reject(
+            StatusCode::BAD_REQUEST,
+            format!("No road within {} m of source", src_radius),
+        ));
     }
 
-    let mut dst_candidates: Vec<(u32, f64, f64, f64)> = if let Some((angle, range)) = dst_bearing {
+    let mut dst_candidates: Vec<(u32, f64, f64, f64)> = if let Some(h) = &dst_hinted {
+        vec![h[0]]
+    } else if let Some((angle, range)) = dst_bearing {
         match state.snap_index.snap_with_bearing_filtered_role(
-            req.destination_lon,
-            req.destination_lat,
+            destination[0],
+            destination[1],
             mode.0,
             angle,
             range,
@@ -635,8 +1542,8 @@ pub async fn route_handler(
         }
     } else {
         match state.snap_index.snap_with_info_filtered_role(
-            req.destination_lon,
-            req.destination_lat,
+            destination[0],
+            destination[1],
             mode.0,
             Some(&snap_mask),
             dst_role_filter,
@@ -646,14 +1553,55 @@ pub async fn route_handler(
         }
     };
     if dst_candidates.is_empty() {
-        return (
+        return Err(reject(
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Could not snap destination to road network".to_string(),
-            }),
-        )
-            .into_response();
+            "Could not snap destination to road network".to_string(),
+        ));
+    }
+    if dst_candidates[0].3 > dst_radius {
+        return Err(reject(
+            StatusCode::BAD_REQUEST,
+            format!("No road within {} m of destination", dst_radius),
+        ));
+    }
+    // The snaps as the index returned them, before the curb / U-turn
+    // swaps below: what generate_hints records for the next request.
+    let mut src_snapped: Vec<SnapCandidate> = src_candidates.clone();
+    let mut dst_snapped: Vec<SnapCandidate> = dst_candidates.clone();
+    let checksum = data_checksum(&state);
+    let hint_waypoints = |src: &[SnapCandidate], dst: &[SnapCandidate]| {
+        req.generate_hints.then(|| {
+            vec![
+                RouteWaypoint::from(&SnapHint::new(checksum, mode, origin, src)),
+                RouteWaypoint::from(&SnapHint::new(checksum, mode, destination, dst)),
+            ]
+        })
+    };
+    // approaches=curb: commit to the direction of travel that keeps the
+    // waypoint on the curb (the phantom flow below filters its seeds the
+    // same way).
+    if src_approach == Approach::Curb {
+        src_candidates[0] = curbside_candidate(
+            &state,
+            &mode_data,
+            SnapRole::Src,
+            Some(&snap_mask),
+            src_candidates[0],
+            origin,
+        );
+    }
+    if dst_approach == Approach::Curb {
+        dst_candidates[0] = curbside_candidate(
+            &state,
+            &mode_data,
+            SnapRole::Dst,
+            Some(&snap_mask),
+            dst_candidates[0],
+            destination,
+        );
     }
+    src_candidates[0] =
+        no_uturn_candidate(&mode_data, Some(&snap_mask), src_candidates[0], depart_on);
 
     // Pick the primary (best) candidates. The fallback search runs
     // later, after the CCH query is built, so we can run multiple
@@ -691,13 +1639,10 @@ pub async fn route_handler(
         .filter(|&r| r != u32::MAX)
         .collect();
     if src_rank_candidates.is_empty() || dst_rank_candidates.is_empty() {
-        return (
+        return Err(reject(
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Snapped node not accessible for this mode".to_string(),
-            }),
-        )
-            .into_response();
+            "Snapped node not accessible for this mode".to_string(),
+        ));
     }
     let mut src_rank = src_rank_candidates[0];
     let mut dst_rank = dst_rank_candidates[0];
@@ -714,21 +1659,27 @@ pub async fn route_handler(
     // drive on 17/500 close pairs).
     let phantom_will_run = src_bearing.is_none()
         && dst_bearing.is_none()
-        && avoid_entry.is_none()
-        && exclude_mask.is_none();
+        && avoid_weights.is_none()
+        && exclude_mask.is_none()
+        && !shortest;
     if src_rank == dst_rank && !phantom_will_run {
         let snap_point = Point {
             lon: src_snap_info.lon,
             lat: src_snap_info.lat,
         };
 
-        if wants_gpx(&headers) {
+        if gpx {
             super::region_metrics::record_query(
                 &region_id,
                 "route",
                 started_dispatch.elapsed().as_secs_f64(),
             );
-            return gpx_response(format_gpx(&[snap_point], "Route"));
+            return Err(gpx_response(format_gpx(
+                &[snap_point],
+                &[origin, destination],
+                state.elevation.as_ref(),
+                "Route",
+            )));
         }
 
         let point_geom = RouteGeometry::from_points(vec![snap_point], geom_format);
@@ -745,7 +1696,7 @@ pub async fn route_handler(
             "route",
             started_dispatch.elapsed().as_secs_f64(),
         );
-        return Json(RouteResponse {
+        return Ok(RouteResponse {
             duration_s: 0.0,
             distance_m: 0.0,
             geometry: point_geom,
@@ -755,8 +1706,14 @@ pub async fn route_handler(
             duration_q75_s: None,
             alternatives: None,
             debug: debug_info,
-        })
-        .into_response();
+            legs: None,
+            waypoints: hint_waypoints(&src_snapped, &dst_snapped),
+            elevation: None,
+            depart_at: None,
+            arrive_at: None,
+            arrival_ebg: Some(src_candidates[0].0),
+            closed_on_route: closures_hit(&[(src_candidates[0].0, 1.0)], 0.0),
+        });
     }
 
     // Helper: build route from query result — returns (geometry, duration_s, distance_m, steps, ebg_path)
@@ -803,7 +1760,16 @@ pub async fn route_handler(
             distance_m = (distance_m - head_cut - tail_cut).max(0.0);
         }
         let geometry = RouteGeometry::from_points(pts, format);
-        let duration_s = result.distance as f64;
+        // Shortest routes minimise metres, so the query cost is not a
+        // duration: bill the path's edge weights instead (no turn costs).
+        let duration_s = if shortest {
+            ebg_path
+                .iter()
+                .map(|&eid| mode_data.node_weights[eid as usize] as f64)
+                .sum::<f64>()
+        } else {
+            result.distance as f64
+        };
         let steps = if want_steps {
             Some(build_steps(
                 &ebg_path,
@@ -812,6 +1778,7 @@ pub async fn route_handler(
                 &state.edge_geom,
                 &mode_data.node_weights,
                 &state.way_names,
+                &StepGuidance::new(&state, &mode_data),
                 format,
             ))
         } else {
@@ -821,24 +1788,37 @@ pub async fn route_handler(
     };
 
     // Run primary query (with optional avoid/exclude weights)
-    let exclude_weights = if avoid_entry.is_none() {
+    let exclude_weights = if avoid_weights.is_none() {
         exclude_mask.map(|exc| state.get_exclude_weights(mode, exc))
     } else {
-        None // avoid_entry already incorporates exclude
+        None // avoid_weights already incorporate exclude
     };
-    let query = if let Some(ref entry) = avoid_entry {
+    let query = if let Some((weights, _)) = avoid_weights {
         CchQuery::with_custom_weights(
             &mode_data.cch_topo,
             &mode_data.up_adj_flat,
             &mode_data.down_rev_flat,
-            &entry.weights.time_weights,
+            weights,
         )
     } else if let Some(ref ew) = exclude_weights {
         CchQuery::with_custom_weights(
             &mode_data.cch_topo,
             &mode_data.up_adj_flat,
             &mode_data.down_rev_flat,
-            &ew.time_weights,
+            if shortest {
+                &ew.dist_weights
+            } else {
+                &ew.time_weights
+            },
+        )
+    } else if shortest {
+        // Distance-only flats omit topo_edge_idx, so reuse the TIME flats'
+        // topology with the distance metric weights (as /trip does).
+        CchQuery::with_custom_weights(
+            &mode_data.cch_topo,
+            &mode_data.up_adj_flat,
+            &mode_data.down_rev_flat,
+            &mode_data.cch_weights_dist,
         )
     } else {
         CchQuery::new(&mode_data)
@@ -901,48 +1881,74 @@ pub async fn route_handler(
     // (4x fwd/rev asymmetry on long rural edges). Bearing hints imply an
     // explicit direction and avoid/exclude run custom weight vectors the seed
     // costs don't reflect — those paths keep the legacy single-seed flow.
+    // So do shortest routes: the seeds are priced in time.
     if src_bearing.is_none()
         && dst_bearing.is_none()
-        && avoid_entry.is_none()
+        && avoid_weights.is_none()
         && exclude_weights.is_none()
+        && !shortest
     {
         // K=8 candidate fetch so near-equidistant PARALLEL physical edges are
         // all seeded (Robertville: the correct road was 12 m further than a
         // track whose both directions detour 15 km).
-        let src_k = state.snap_index.snap_k_with_info_filtered_role(
-            req.origin_lon,
-            req.origin_lat,
-            mode.0,
-            8,
-            Some(&snap_mask),
-            src_role_filter,
-        );
-        let dst_k = state.snap_index.snap_k_with_info_filtered_role(
-            req.destination_lon,
-            req.destination_lat,
-            mode.0,
-            8,
-            Some(&snap_mask),
-            dst_role_filter,
-        );
-        let src_ph = super::phantom::phantom_from_candidates(
+        let mut src_k = match &src_hinted {
+            Some(h) => h.clone(),
+            None => state.snap_index.snap_k_with_info_filtered_role(
+                origin[0],
+                origin[1],
+                mode.0,
+                8,
+                Some(&snap_mask),
+                src_role_filter,
+            ),
+        };
+        let mut dst_k = match &dst_hinted {
+            Some(h) => h.clone(),
+            None => state.snap_index.snap_k_with_info_filtered_role(
+                destination[0],
+                destination[1],
+                mode.0,
+                8,
+                Some(&snap_mask),
+                dst_role_filter,
+            ),
+        };
+        src_k.retain(|c| c.3 <= src_radius);
+        dst_k.retain(|c| c.3 <= dst_radius);
+        if !src_k.is_empty() {
+            src_snapped = src_k.clone();
+        }
+        if !dst_k.is_empty() {
+            dst_snapped = dst_k.clone();
+        }
+        let mut src_ph = super::phantom::phantom_from_candidates(
             &state,
             &mode_data,
             &src_k,
-            req.origin_lon,
-            req.origin_lat,
+            origin[0],
+            origin[1],
             super::types::SnapRole::Src,
             Some(&snap_mask),
         );
-        let dst_ph = super::phantom::phantom_from_candidates(
+        let mut dst_ph = super::phantom::phantom_from_candidates(
             &state,
             &mode_data,
             &dst_k,
-            req.destination_lon,
-            req.destination_lat,
+            destination[0],
+            destination[1],
             super::types::SnapRole::Dst,
             Some(&snap_mask),
         );
+        let side = DrivingSide::from_env();
+        if let (Approach::Curb, Some(sp)) = (src_approach, src_ph.as_mut()) {
+            sp.retain_curbside(&state, origin[0], origin[1], side);
+        }
+        if let (Approach::Curb, Some(dp)) = (dst_approach, dst_ph.as_mut()) {
+            dp.retain_curbside(&state, destination[0], destination[1], side);
+        }
+        if let (Some(e), Some(sp)) = (depart_on, src_ph.as_mut()) {
+            sp.retain_seeds(|s| s.ebg_id != e ^ 1);
+        }
         if let (Some(sp), Some(dp)) = (src_ph, dst_ph) {
             // Same-physical-edge direct move. The seeded query's guard skips
             // pure seed-to-seed meets (they can encode an invalid backward
@@ -1010,7 +2016,7 @@ pub async fn route_handler(
                     "route",
                     started_dispatch.elapsed().as_secs_f64(),
                 );
-                return Json(RouteResponse {
+                return Ok(RouteResponse {
                     duration_s: dc as f64,
                     distance_m: dist_m,
                     geometry,
@@ -1020,8 +2026,14 @@ pub async fn route_handler(
                     duration_q75_s: band_durations.map(|b| b.1),
                     alternatives: None,
                     debug: debug_info,
-                })
-                .into_response();
+                    legs: None,
+                    waypoints: hint_waypoints(&src_snapped, &dst_snapped),
+                    elevation: None,
+                    depart_at: None,
+                    arrive_at: None,
+                    arrival_ebg: Some(ebg),
+                    closed_on_route: closures_hit(&[(ebg, 1.0)], dc as f64),
+                });
             }
             if let Some(r) = seeded {
                 src_rank = r.src_root;
@@ -1071,33 +2083,59 @@ pub async fn route_handler(
         result_opt = Some(r);
     }
     // ESCALATION: the K=1 primary failed. Re-snap with K=64 and retry
-    // the full fallback enumeration. Bearing-filtered queries stay at
-    // K=1 (bearing implies a directionally-specific candidate). Same
-    // for src/dst pairs that started K=1-degenerate (length 1). This
-    // path fires on ~1.3 % of Belgium pairs per the SNAP_K sweep in
-    // #197.
-    if result_opt.is_none()
-        && src_bearing.is_none()
-        && dst_bearing.is_none()
-        && src_candidates.len() == 1
-        && dst_candidates.len() == 1
-    {
-        let mut new_src = state.snap_index.snap_k_with_info_filtered_role(
-            req.origin_lon,
-            req.origin_lat,
+    // the full fallback enumeration. Bearing-filtered endpoints keep
+    // their bearing on the K=64 re-snap, so a GPS heading still rules
+    // out the opposite carriageway when the nearest matching edge is a
+    // dead end. This path fires on ~1.3 % of Belgium pairs per the
+    // SNAP_K sweep in #197.
+    if result_opt.is_none() && src_candidates.len() == 1 && dst_candidates.len() == 1 {
+        let mut new_src = state.snap_index.snap_k_with_bearing_filtered_role(
+            origin[0],
+            origin[1],
             mode.0,
             SNAP_K,
+            src_bearing,
             Some(&snap_mask),
             src_role_filter,
         );
-        let mut new_dst = state.snap_index.snap_k_with_info_filtered_role(
-            req.destination_lon,
-            req.destination_lat,
+        let mut new_dst = state.snap_index.snap_k_with_bearing_filtered_role(
+            destination[0],
+            destination[1],
             mode.0,
             SNAP_K,
+            dst_bearing,
             Some(&snap_mask),
             dst_role_filter,
         );
+        new_src.retain(|c| c.3 <= src_radius);
+        new_dst.retain(|c| c.3 <= dst_radius);
+        if src_approach == Approach::Curb {
+            for c in new_src.iter_mut() {
+                *c = curbside_candidate(
+                    &state,
+                    &mode_data,
+                    SnapRole::Src,
+                    Some(&snap_mask),
+                    *c,
+                    origin,
+                );
+            }
+        }
+        if dst_approach == Approach::Curb {
+            for c in new_dst.iter_mut() {
+                *c = curbside_candidate(
+                    &state,
+                    &mode_data,
+                    SnapRole::Dst,
+                    Some(&snap_mask),
+                    *c,
+                    destination,
+                );
+            }
+        }
+        for c in new_src.iter_mut() {
+            *c = no_uturn_candidate(&mode_data, Some(&snap_mask), *c, depart_on);
+        }
         if !new_src.is_empty() && !new_dst.is_empty() {
             // Drop the K=1 result (it's already known to fail) and try
             // the remaining K=64 candidates. Preserve the K=1 result at
@@ -1134,6 +2172,8 @@ pub async fn route_handler(
                 chosen_src_idx = i;
                 chosen_dst_idx = j;
                 result_opt = Some(r);
+                src_snapped = vec![src_candidates[i]];
+                dst_snapped = vec![dst_candidates[j]];
                 // Update primary snap-info to reflect the new K=64
                 // primary candidate (caller still sees a meaningful
                 // src/dst snap even if we ended up using a non-(0,0)
@@ -1160,20 +2200,20 @@ pub async fn route_handler(
     let result = match result_opt {
         Some(r) => r,
         None => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "No route found".to_string(),
-                }),
-            )
-                .into_response();
+            return Err(reject(StatusCode::NOT_FOUND, "No route found".to_string()));
         }
     };
 
-    let active_weights = if let Some(ref entry) = avoid_entry {
-        &entry.weights.time_weights
+    let active_weights = if let Some((weights, _)) = avoid_weights {
+        weights
     } else if let Some(ref ew) = exclude_weights {
-        &ew.time_weights
+        if shortest {
+            &ew.dist_weights
+        } else {
+            &ew.time_weights
+        }
+    } else if shortest {
+        &mode_data.cch_weights_dist
     } else {
         &mode_data.cch_weights
     };
@@ -1213,92 +2253,116 @@ pub async fn route_handler(
     }
 
     // GPX output: skip annotations, alternatives, debug — just emit track points
-    if wants_gpx(&headers) {
+    if gpx {
         let (raw_points, _) = build_raw_points(&ebg_path, &state.ebg_nodes, &state.edge_geom);
         super::region_metrics::record_query(
             &region_id,
             "route",
             started_dispatch.elapsed().as_secs_f64(),
         );
-        return gpx_response(format_gpx(&raw_points, "Route"));
+        return Err(gpx_response(format_gpx(
+            &raw_points,
+            &[origin, destination],
+            state.elevation.as_ref(),
+            "Route",
+        )));
     }
 
     // Build per-edge annotations if requested
-    let route_annotations =
-        if let Some((want_dur, want_dist, want_spd, want_nds)) = annotation_flags {
-            let mut ann = RouteAnnotations {
-                duration: None,
-                distance: None,
-                speed: None,
-                nodes: None,
-            };
-            // Per-edge scale factors for the clipped first/last edges (#522):
-            // annotations must sum to what duration_s/distance_m report.
-            let clip_scale = |idx: usize| -> f64 {
-                match end_clip {
-                    Some((fs, fd)) if ebg_path.len() == 1 => (fd - fs).max(0.0),
-                    Some((fs, _)) if idx == 0 => 1.0 - fs,
-                    Some((_, fd)) if idx + 1 == ebg_path.len() => fd,
-                    _ => 1.0,
-                }
-            };
-            if want_dur || want_spd {
-                let durations: Vec<f64> = ebg_path
+    let route_annotations = if let Some(flags) = annotation_flags {
+        let mut ann = RouteAnnotations {
+            duration: None,
+            distance: None,
+            speed: None,
+            nodes: None,
+            ferry: None,
+            ways: None,
+        };
+        // Per-edge scale factors for the clipped first/last edges (#522):
+        // annotations must sum to what duration_s/distance_m report.
+        let clip_scale = |idx: usize| -> f64 {
+            match end_clip {
+                Some((fs, fd)) if ebg_path.len() == 1 => (fd - fs).max(0.0),
+                Some((fs, _)) if idx == 0 => 1.0 - fs,
+                Some((_, fd)) if idx + 1 == ebg_path.len() => fd,
+                _ => 1.0,
+            }
+        };
+        if flags.duration || flags.speed {
+            let durations: Vec<f64> = ebg_path
+                .iter()
+                .enumerate()
+                .map(|(i, &eid)| {
+                    let w = mode_data
+                        .node_weights
+                        .get(eid as usize)
+                        .copied()
+                        .unwrap_or(0);
+                    w as f64 * clip_scale(i)
+                })
+                .collect();
+            if flags.duration {
+                ann.duration = Some(durations.clone());
+            }
+            if flags.speed {
+                let distances: Vec<f64> = ebg_path
                     .iter()
                     .enumerate()
                     .map(|(i, &eid)| {
-                        let w = mode_data
-                            .node_weights
-                            .get(eid as usize)
-                            .copied()
-                            .unwrap_or(0);
-                        w as f64 * clip_scale(i)
+                        state.ebg_nodes.nodes[eid as usize].length_m as f64 * clip_scale(i)
                     })
                     .collect();
-                if want_dur {
-                    ann.duration = Some(durations.clone());
-                }
-                if want_spd {
-                    let distances: Vec<f64> = ebg_path
-                        .iter()
-                        .enumerate()
-                        .map(|(i, &eid)| {
-                            state.ebg_nodes.nodes[eid as usize].length_m as f64 * clip_scale(i)
-                        })
-                        .collect();
-                    ann.speed = Some(
-                        durations
-                            .iter()
-                            .zip(distances.iter())
-                            .map(|(&dur, &dist)| {
-                                if dur > 0.0 {
-                                    dist * 3.6 / dur // km/h = (m/s) * 3.6
-                                } else {
-                                    0.0
-                                }
-                            })
-                            .collect(),
-                    );
-                }
-            }
-            if want_dist {
-                ann.distance = Some(
-                    ebg_path
+                ann.speed = Some(
+                    durations
                         .iter()
-                        .enumerate()
-                        .map(|(i, &eid)| {
-                            state.ebg_nodes.nodes[eid as usize].length_m as f64 * clip_scale(i)
+                        .zip(distances.iter())
+                        .map(|(&dur, &dist)| {
+                            if dur > 0.0 {
+                                dist * 3.6 / dur // km/h = (m/s) * 3.6
+                            } else {
+                                0.0
+                            }
                         })
                         .collect(),
                 );
             }
-            if want_nds {
-                ann.nodes = Some(ebg_path.clone());
-            }
-            Some(ann)
-        } else {
-            None
-        };
+        }
+        if flags.distance {
+            ann.distance = Some(
+                ebg_path
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &eid)| {
+                        state.ebg_nodes.nodes[eid as usize].length_m as f64 * clip_scale(i)
+                    })
+                    .collect(),
+            );
+        }
+        if flags.nodes {
+            ann.nodes = Some(ebg_path.clone());
+        }
+        if flags.ways {
+            ann.ways = Some(
+                ebg_path
+                    .iter()
+                    .map(|&eid| state.ebg_nodes.nodes[eid as usize].primary_way)
+                    .collect(),
+            );
+        }
+        if flags.ferry {
+            ann.ferry = Some(
+                ebg_path
+                    .iter()
+                    .map(|&eid| {
+                        state.ebg_nodes.nodes[eid as usize].class_bits & EDGE_FLAG_FERRY != 0
+                    })
+                    .collect(),
+            );
+        }
+        Some(ann)
+    } else {
+        None
+    };
 
     // Compute alternative routes if requested
     let alternatives = if num_alternatives > 0 {
@@ -1307,13 +2371,7 @@ pub async fn route_handler(
         // This clones ~200MB (up + down weight arrays). Acceptable for alternatives
         // since they're requested rarely (only when alternatives > 0).
         // A proper fix (penalty views) would require changing the CchQuery API.
-        let mut penalized_weights = if let Some(ref entry) = avoid_entry {
-            entry.weights.time_weights.clone()
-        } else if let Some(ref ew) = exclude_weights {
-            ew.time_weights.clone()
-        } else {
-            mode_data.cch_weights.clone()
-        };
+        let mut penalized_weights = active_weights.clone();
 
         // Penalize edges of the primary route
         for &(_node, edge_idx) in &result.forward_parent {
@@ -1331,59 +2389,87 @@ pub async fn route_handler(
             }
         }
 
-        for _alt_idx in 0..num_alternatives {
+        // Penalty search: every path found has its arcs penalised before the
+        // next query, so a rejected candidate is not found again. Candidates
+        // are re-priced on the real weights, then kept only if they overlap
+        // the routes accepted so far by at most `max_sharing` of their
+        // length and cost at most `max_stretch` times the primary.
+        let mut covered: std::collections::HashSet<u32> = ebg_path.iter().copied().collect();
+        let max_cost = result.distance as f64 * max_stretch;
+        let length_of = |eid: u32| state.ebg_nodes.nodes[eid as usize].length_m as f64;
+        for _attempt in 0..num_alternatives * 3 {
+            if alt_routes.len() == num_alternatives {
+                break;
+            }
             let alt_query = CchQuery::with_custom_weights(
                 &mode_data.cch_topo,
                 &mode_data.up_adj_flat,
                 &mode_data.down_rev_flat,
                 &penalized_weights,
             );
+            let Some(alt_result) = alt_query.query(src_rank, dst_rank) else {
+                break; // No more routes possible
+            };
+            // This far over the stretch limit on penalised weights the
+            // candidates mostly retrace penalised arcs; stop searching.
+            if alt_result.distance as f64 > max_cost * 3.0 {
+                break;
+            }
 
-            if let Some(alt_result) = alt_query.query(src_rank, dst_rank) {
-                // Skip if same distance as primary (exact duplicate)
-                if alt_result.distance == result.distance {
-                    continue;
-                }
-                // Skip if distance is more than 2x primary (too indirect)
-                if alt_result.distance > result.distance.saturating_mul(2) {
-                    break;
-                }
-
-                let (alt_geom, alt_dur, alt_dist, alt_steps, _alt_path) = build_route(
-                    &alt_result,
+            let (alt_geom, alt_dur, alt_dist, alt_steps, alt_path) = build_route(
+                &alt_result,
+                &penalized_weights,
+                geom_format,
+                req.steps,
+                src_rank,
+                dst_rank,
+                None,
+            );
+            let alt_cost = path_weight(
+                &mode_data.cch_topo,
+                active_weights,
+                &unpack_path(
+                    &mode_data.cch_topo,
                     &penalized_weights,
-                    geom_format,
-                    req.steps,
+                    &alt_result.forward_parent,
+                    &alt_result.backward_parent,
                     src_rank,
                     dst_rank,
-                    None,
-                );
-
-                // Penalize this alternative's edges for next iteration
-                for &(_node, edge_idx) in &alt_result.forward_parent {
-                    let idx = edge_idx as usize;
-                    if idx < penalized_weights.up.len() {
-                        let new_val = penalized_weights.up.get(idx).saturating_mul(3);
-                        penalized_weights.up.to_mut_vec()[idx] = new_val;
-                    }
+                    alt_result.meeting_node,
+                ),
+            )
+            .unwrap_or(alt_result.distance as u64) as f64;
+
+            // Penalize this candidate's edges for the next iteration
+            for &(_node, edge_idx) in &alt_result.forward_parent {
+                let idx = edge_idx as usize;
+                if idx < penalized_weights.up.len() {
+                    let new_val = penalized_weights.up.get(idx).saturating_mul(3);
+                    penalized_weights.up.to_mut_vec()[idx] = new_val;
                 }
-                for &(_node, edge_idx) in &alt_result.backward_parent {
-                    let idx = edge_idx as usize;
-                    if idx < penalized_weights.down.len() {
-                        let new_val = penalized_weights.down.get(idx).saturating_mul(3);
-                        penalized_weights.down.to_mut_vec()[idx] = new_val;
-                    }
+            }
+            for &(_node, edge_idx) in &alt_result.backward_parent {
+                let idx = edge_idx as usize;
+                if idx < penalized_weights.down.len() {
+                    let new_val = penalized_weights.down.get(idx).saturating_mul(3);
+                    penalized_weights.down.to_mut_vec()[idx] = new_val;
                 }
+            }
 
-                alt_routes.push(RouteAlternative {
-                    duration_s: alt_dur,
-                    distance_m: alt_dist,
-                    geometry: alt_geom,
-                    steps: alt_steps,
-                });
-            } else {
-                break; // No more routes possible
+            let sharing = shared_fraction(&alt_path, &covered, length_of);
+            if sharing > max_sharing || alt_cost > max_cost {
+                continue;
             }
+            covered.extend(alt_path.iter().copied());
+            alt_routes.push(RouteAlternative {
+                // Shortest already bills node weights; otherwise the real
+                // cost is the duration, not the penalised query cost.
+                duration_s: if shortest { alt_dur } else { alt_cost },
+                distance_m: alt_dist,
+                dissimilarity: 1.0 - sharing,
+                geometry: alt_geom,
+                steps: alt_steps,
+            });
         }
 
         if alt_routes.is_empty() {
@@ -1405,12 +2491,39 @@ pub async fn route_handler(
         None
     };
 
+    // Each edge takes its share of the duration by weight, with the first
+    // and last edges clipped like the annotations.
+    let closed_on_route = if closures.is_some() {
+        let last = ebg_path.len().saturating_sub(1);
+        let edge_costs: Vec<(u32, f64)> = ebg_path
+            .iter()
+            .enumerate()
+            .map(|(i, &eid)| {
+                let w = mode_data
+                    .node_weights
+                    .get(eid as usize)
+                    .copied()
+                    .unwrap_or(0) as f64;
+                let share = match end_clip {
+                    Some((fs, fd)) if last == 0 => (fd - fs).max(0.0),
+                    Some((fs, _)) if i == 0 => 1.0 - fs,
+                    Some((_, fd)) if i == last => fd,
+                    _ => 1.0,
+                };
+                (eid, w * share)
+            })
+            .collect();
+        closures_hit(&edge_costs, duration_s)
+    } else {
+        Vec::new()
+    };
+
     super::region_metrics::record_query(
         &region_id,
         "route",
         started_dispatch.elapsed().as_secs_f64(),
     );
-    Json(RouteResponse {
+    Ok(RouteResponse {
         duration_s,
         distance_m,
         geometry,
@@ -1420,8 +2533,14 @@ pub async fn route_handler(
         debug: debug_info,
         duration_q25_s: band_durations.map(|b| b.0),
         duration_q75_s: band_durations.map(|b| b.1),
+        legs: None,
+        waypoints: hint_waypoints(&src_snapped, &dst_snapped),
+        elevation: None,
+        depart_at: None,
+        arrive_at: None,
+        arrival_ebg: ebg_path.last().copied(),
+        closed_on_route,
     })
-    .into_response()
 }
 
 // ============ Cross-region handler (#91 Phase 2) ============
@@ -1437,6 +2556,7 @@ pub async fn cross_region_route_handler(
     route_handler(state, query, headers).await.into_response()
 }
 
+#[allow(clippy::result_large_err)]
 fn cross_region_route_inner(
     src_state: Arc<ServerState>,
     src_region: String,
@@ -1444,24 +2564,57 @@ fn cross_region_route_inner(
     dst_region: String,
     overlay: Arc<super::overlay::OverlayCluster>,
     req: RouteRequest,
-) -> axum::response::Response {
+    [origin, destination]: [[f64; 2]; 2],
+) -> Result<RouteResponse, axum::response::Response> {
     use super::cross_region::solve_cross_region;
 
-    let effective_mode_name = match &req.traffic {
-        Some(v) if !v.trim().is_empty() => format!("{}_{}", req.mode, v.trim()),
-        _ => req.mode.clone(),
-    };
-    let src_mode = match parse_mode(&effective_mode_name, &src_state.mode_lookup) {
-        Ok(m) => m,
+    // The overlay carries boundary-to-boundary TIME costs only.
+    let weighting = route_weighting(req.traffic.as_deref(), req.weighting.as_deref());
+    let (src_mode, dst_mode) = match weighting.and_then(|w| {
+        Ok((
+            resolve_weighting(&req.mode, w, &src_state.mode_lookup)?,
+            resolve_weighting(&req.mode, w, &dst_state.mode_lookup)?,
+        ))
+    }) {
+        Ok(((_, Weighting::Shortest), _)) => {
+            return Err(reject(
+                StatusCode::BAD_REQUEST,
+                "weighting=shortest is not supported for cross-region routes".into(),
+            ));
+        }
+        Ok(((src_mode, _), (dst_mode, _))) => (src_mode, dst_mode),
         Err(e) => {
-            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+            return Err(reject(StatusCode::BAD_REQUEST, e));
         }
     };
-    let dst_mode = match parse_mode(&effective_mode_name, &dst_state.mode_lookup) {
-        Ok(m) => m,
-        Err(e) => {
-            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+    // Closures are resolved per region, and the overlay prices border
+    // crossings without them.
+    if req.depart_at.is_some() || req.arrive_by.is_some() {
+        return Err(reject(
+            StatusCode::BAD_REQUEST,
+            "depart_at / arrive_by are not supported for cross-region routes".into(),
+        ));
+    }
+    // Border crossings are priced on the unfiltered overlay, so an exclude
+    // filter could only apply to the in-region legs.
+    match super::exclude::parse_exclude_option(&req.exclude) {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return Err(reject(
+                StatusCode::BAD_REQUEST,
+                "exclude is not supported for cross-region routes".into(),
+            ));
         }
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
+    }
+
+    let [src_radius, dst_radius] = match endpoint_radii(&req) {
+        Ok(r) => r,
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
+    };
+    let [src_approach, dst_approach] = match endpoint_approaches(&req) {
+        Ok(a) => a,
+        Err(e) => return Err(reject(StatusCode::BAD_REQUEST, e)),
     };
 
     let src_mode_data = src_state.get_mode(src_mode);
@@ -1472,52 +2625,76 @@ fn cross_region_route_inner(
     let dst_role_filter = SnapRole::Dst.role_filter(&dst_mode_data);
 
     let (src_orig, src_snap) = match src_state.snap_index.snap_with_info_filtered_role(
-        req.origin_lon,
-        req.origin_lat,
+        origin[0],
+        origin[1],
         src_mode.0,
         None,
         src_role_filter,
     ) {
-        Some(t) => (t.0, t),
+        Some(t) if t.3 <= src_radius => {
+            let t = match src_approach {
+                Approach::Curb => {
+                    curbside_candidate(&src_state, &src_mode_data, SnapRole::Src, None, t, origin)
+                }
+                Approach::Unrestricted => t,
+            };
+            (t.0, t)
+        }
+        Some(_) => {
+            return Err(reject(
+                StatusCode::BAD_REQUEST,
+                format!("No road within {} m of source", src_radius),
+            ));
+        }
         None => {
-            return (
+            return Err(reject(
                 StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("Could not snap source in region {}", src_region),
-                }),
-            )
-                .into_response();
+                format!("Could not snap source in region {}", src_region),
+            ));
         }
     };
     let (dst_orig, dst_snap) = match dst_state.snap_index.snap_with_info_filtered_role(
-        req.destination_lon,
-        req.destination_lat,
+        destination[0],
+        destination[1],
         dst_mode.0,
         None,
         dst_role_filter,
     ) {
-        Some(t) => (t.0, t),
+        Some(t) if t.3 <= dst_radius => {
+            let t = match dst_approach {
+                Approach::Curb => curbside_candidate(
+                    &dst_state,
+                    &dst_mode_data,
+                    SnapRole::Dst,
+                    None,
+                    t,
+                    destination,
+                ),
+                Approach::Unrestricted => t,
+            };
+            (t.0, t)
+        }
+        Some(_) => {
+            return Err(reject(
+                StatusCode::BAD_REQUEST,
+                format!("No road within {} m of destination", dst_radius),
+            ));
+        }
         None => {
-            return (
+            return Err(reject(
                 StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("Could not snap destination in region {}", dst_region),
-                }),
-            )
-                .into_response();
+                format!("Could not snap destination in region {}", dst_region),
+            ));
         }
     };
 
     let src_rank = src_mode_data.orig_to_rank[src_orig as usize];
     let dst_rank = dst_mode_data.orig_to_rank[dst_orig as usize];
     if src_rank == u32::MAX || dst_rank == u32::MAX {
-        return (
+        return Err(reject(
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "Snapped node not accessible for this mode".to_string(),
-            }),
-        )
-            .into_response();
+            "Snapped node not accessible for this mode".to_string(),
+        ));
     }
 
     let solution = match solve_cross_region(
@@ -1532,16 +2709,13 @@ fn cross_region_route_inner(
     ) {
         Some(s) => s,
         None => {
-            return (
+            return Err(reject(
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: format!(
-                        "No cross-region route found from {} to {}",
-                        src_region, dst_region
-                    ),
-                }),
-            )
-                .into_response();
+                format!(
+                    "No cross-region route found from {} to {}",
+                    src_region, dst_region
+                ),
+            ));
         }
     };
 
@@ -1551,7 +2725,7 @@ fn cross_region_route_inner(
     let geom_format = match GeometryFormat::parse(&req.geometries) {
         Ok(f) => f,
         Err(e) => {
-            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+            return Err(reject(StatusCode::BAD_REQUEST, e));
         }
     };
 
@@ -1570,16 +2744,13 @@ fn cross_region_route_inner(
         // Border picked by the picker doesn't translate into either
         // region's mode-filtered CCH. Treat as no-route rather than
         // returning a degenerate straight-line polyline.
-        return (
+        return Err(reject(
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!(
-                    "Cross-region border {}↔{} not accessible for mode '{}'",
-                    src_region, dst_region, req.mode
-                ),
-            }),
-        )
-            .into_response();
+            format!(
+                "Cross-region border {}↔{} not accessible for mode '{}'",
+                src_region, dst_region, req.mode
+            ),
+        ));
     }
 
     // Look up the chosen border representative lat/lon for the
@@ -1638,7 +2809,7 @@ fn cross_region_route_inner(
 
     let distance_m = src_dist_m + border_crossing_m + dst_dist_m;
 
-    Json(RouteResponse {
+    Ok(RouteResponse {
         duration_s,
         distance_m,
         geometry: geom,
@@ -1648,8 +2819,14 @@ fn cross_region_route_inner(
         duration_q75_s: None,
         alternatives: None,
         debug: None,
+        legs: None,
+        waypoints: None,
+        elevation: None,
+        depart_at: None,
+        arrive_at: None,
+        arrival_ebg: None,
+        closed_on_route: Vec::new(),
     })
-    .into_response()
 }
 
 /// Run a CCH P2P query inside a single region with path recovery,
@@ -1777,12 +2954,34 @@ fn wants_gpx(headers: &HeaderMap) -> bool {
         .unwrap_or(false)
 }
 
+/// Resolve the output format: `format=gpx|json` wins over the Accept header.
+fn route_wants_gpx(format: Option<&str>, headers: &HeaderMap) -> Result<bool, String> {
+    match format {
+        None => Ok(wants_gpx(headers)),
+        Some("gpx") => Ok(true),
+        Some("json") => Ok(false),
+        Some(other) => Err(format!(
+            "Invalid format '{}'. Expected 'json' or 'gpx'.",
+            other
+        )),
+    }
+}
+
 /// Format route points as a GPX 1.1 XML document.
 ///
-/// GPX uses `lat` then `lon` attributes (opposite of GeoJSON).
-fn format_gpx(points: &[Point], name: &str) -> String {
+/// GPX uses `lat` then `lon` attributes (opposite of GeoJSON). `waypoints`
+/// (the requested stops, in order) become `<wpt>` elements; with SRTM
+/// `elevation` loaded every point carries an `<ele>` where a tile covers it.
+fn format_gpx(
+    points: &[Point],
+    waypoints: &[[f64; 2]],
+    elevation: Option<&super::elevation::ElevationData>,
+    name: &str,
+) -> String {
     use std::fmt::Write;
 
+    let ele = |lon: f64, lat: f64| elevation.and_then(|e| e.elevation_at(lat, lon));
+
     let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
 
     let mut xml = String::with_capacity(128 + points.len() * 64);
@@ -1794,19 +2993,42 @@ fn format_gpx(points: &[Point], name: &str) -> String {
          <metadata>\n    \
          <name>{name}</name>\n    \
          <time>{now}</time>\n  \
-         </metadata>\n  \
-         <trk>\n    \
-         <name>{name}</name>\n    \
-         <trkseg>",
+         </metadata>",
     );
 
+    let last = waypoints.len().saturating_sub(1);
+    for (i, &[lon, lat]) in waypoints.iter().enumerate() {
+        let label = match i {
+            0 => "Start".to_string(),
+            _ if i == last => "End".to_string(),
+            _ => format!("Via {}", i),
+        };
+        let _ = writeln!(xml, "  <wpt lat=\"{:.7}\" lon=\"{:.7}\">", lat, lon);
+        if let Some(e) = ele(lon, lat) {
+            let _ = writeln!(xml, "    <ele>{:.1}</ele>", e);
+        }
+        let _ = writeln!(xml, "    <name>{label}</name>\n  </wpt>");
+    }
+
+    let _ = writeln!(xml, "  <trk>\n    <name>{name}</name>\n    <trkseg>");
     for pt in points {
         // 7 decimal places ~ 1cm precision
-        let _ = writeln!(
-            xml,
-            "      <trkpt lat=\"{:.7}\" lon=\"{:.7}\"/>",
-            pt.lat, pt.lon,
-        );
+        match ele(pt.lon, pt.lat) {
+            Some(e) => {
+                let _ = writeln!(
+                    xml,
+                    "      <trkpt lat=\"{:.7}\" lon=\"{:.7}\">\n        <ele>{:.1}</ele>\n      </trkpt>",
+                    pt.lat, pt.lon, e,
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    xml,
+                    "      <trkpt lat=\"{:.7}\" lon=\"{:.7}\"/>",
+                    pt.lat, pt.lon,
+                );
+            }
+        }
     }
 
     xml.push_str("    </trkseg>\n  </trk>\n</gpx>\n");
@@ -1852,7 +3074,57 @@ pub fn lookup_road_name(
     }
 }
 
+/// Lane and roundabout data [`build_steps`] reads beyond geometry and
+/// names: per-way turn lanes, and the edge graph + mode mask used to count
+/// roundabout exits.
+pub(crate) struct StepGuidance<'a> {
+    pub way_lanes: &'a std::collections::HashMap<i64, WayLanes>,
+    pub ebg_csr: &'a crate::formats::EbgCsr,
+    pub mode_mask: &'a [u64],
+}
+
+impl<'a> StepGuidance<'a> {
+    pub fn new(state: &'a ServerState, mode_data: &'a super::state::ModeData) -> Self {
+        Self {
+            way_lanes: &state.way_lanes,
+            ebg_csr: &state.ebg_csr,
+            mode_mask: &mode_data.mask,
+        }
+    }
+
+    /// Whether the junction at the end of roundabout edge `edge` has a
+    /// mode-accessible way out of the roundabout.
+    fn has_roundabout_exit(&self, edge: u32, ebg_nodes: &crate::formats::EbgNodes) -> bool {
+        let (lo, hi) = (
+            self.ebg_csr.offsets[edge as usize] as usize,
+            self.ebg_csr.offsets[edge as usize + 1] as usize,
+        );
+        self.ebg_csr.heads[lo..hi].iter().any(|&h| {
+            let i = h as usize;
+            ebg_nodes.nodes[i].class_bits & EDGE_FLAG_ROUNDABOUT == 0
+                && (self.mode_mask[i / 64] >> (i % 64)) & 1 == 1
+        })
+    }
+
+    /// Lanes of the road `edge` runs on, in its direction of travel,
+    /// marked valid for `modifier`.
+    fn approach_lanes(
+        &self,
+        edge: u32,
+        modifier: &str,
+        ebg_nodes: &crate::formats::EbgNodes,
+        nbg_geo: &crate::formats::NbgGeo,
+    ) -> Option<Vec<StepLane>> {
+        let geom_idx = ebg_nodes.nodes[edge as usize].geom_idx as usize;
+        let way_id = nbg_geo.edges.get(geom_idx)?.first_osm_way_id;
+        let forward = crate::formats::ebg_nodes::EbgNode::is_forward(edge);
+        let lanes = self.way_lanes.get(&way_id)?.direction(forward);
+        (!lanes.is_empty()).then(|| step_lanes(lanes, modifier))
+    }
+}
+
 /// Build turn-by-turn step instructions from EBG path
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn build_steps(
     ebg_path: &[u32],
     ebg_nodes: &crate::formats::EbgNodes,
@@ -1860,6 +3132,7 @@ pub(crate) fn build_steps(
     edge_geom: &crate::server::edge_geom::EdgeGeometry,
     node_weights: &[u32],
     way_names: &crate::server::state::WayNames,
+    guidance: &StepGuidance<'_>,
     format: GeometryFormat,
 ) -> Vec<RouteStep> {
     if ebg_path.len() < 2 {
@@ -1894,7 +3167,11 @@ pub(crate) fn build_steps(
             maneuver_type: "depart".to_string(),
             modifier: None,
             name: lookup_road_name(ebg_path[0], ebg_nodes, nbg_geo, way_names),
+            lanes: None,
+            exit: None,
         },
+        instruction: None,
+        distance_text: None,
     });
 
     // Intermediate steps — group consecutive edges with same bearing direction
@@ -1903,6 +3180,9 @@ pub(crate) fn build_steps(
     let mut accumulated_duration = 0.0;
     let mut segment_edges: Vec<u32> = Vec::new();
     let mut prev_end_bearing = get_edge_bearing(first_node, edge_geom, false);
+    // Step index of the current roundabout entry and the exits passed since.
+    let mut roundabout_entry: Option<usize> = None;
+    let mut exits_passed = 0u32;
 
     for i in 1..ebg_path.len() {
         let edge_id = ebg_path[i];
@@ -1919,8 +3199,23 @@ pub(crate) fn build_steps(
         let turn_angle = bearing_diff(prev_end_bearing, cur_start_bearing);
         let turn_type = classify_turn(turn_angle);
 
-        // If significant turn or last edge, emit a step
-        if turn_type != "straight" || i == ebg_path.len() - 1 {
+        // Roundabouts and ferries get one step on entry and one on exit;
+        // bends while driving round or crossing are not turns.
+        let prev_node = &ebg_nodes.nodes[ebg_path[i - 1] as usize];
+        let was_roundabout = prev_node.class_bits & EDGE_FLAG_ROUNDABOUT != 0;
+        let is_roundabout = node.class_bits & EDGE_FLAG_ROUNDABOUT != 0;
+        let was_ferry = prev_node.class_bits & EDGE_FLAG_FERRY != 0;
+        let is_ferry = node.class_bits & EDGE_FLAG_FERRY != 0;
+        let mode_change = was_roundabout != is_roundabout || was_ferry != is_ferry;
+        let turns = turn_type != "straight"
+            && !(was_roundabout && is_roundabout)
+            && !(was_ferry && is_ferry);
+        if was_roundabout && guidance.has_roundabout_exit(ebg_path[i - 1], ebg_nodes) {
+            exits_passed += 1;
+        }
+
+        // If significant turn, roundabout/ferry entry/exit or last edge, emit a step
+        if turns || mode_change || i == ebg_path.len() - 1 {
             if !segment_edges.is_empty() {
                 // Emit accumulated straight segment
                 let seg_geom =
@@ -1941,7 +3236,11 @@ pub(crate) fn build_steps(
                         maneuver_type: "continue".to_string(),
                         modifier: Some("straight".to_string()),
                         name: lookup_road_name(segment_edges[0], ebg_nodes, nbg_geo, way_names),
+                        lanes: None,
+                        exit: None,
                     },
+                    instruction: None,
+                    distance_text: None,
                 });
                 accumulated_distance = 0.0;
                 accumulated_duration = 0.0;
@@ -1963,15 +3262,44 @@ pub(crate) fn build_steps(
                         maneuver_type: "arrive".to_string(),
                         modifier: None,
                         name: lookup_road_name(edge_id, ebg_nodes, nbg_geo, way_names),
+                        lanes: None,
+                        exit: None,
                     },
+                    instruction: None,
+                    distance_text: None,
                 });
             } else {
                 // Turn step
                 let turn_loc = get_edge_start_location(node, edge_geom);
-                let is_roundabout = (node.class_bits & 0x08) != 0; // bit3 = roundabout
-                let m_type = if is_roundabout { "roundabout" } else { "turn" };
+                let m_type = match (was_roundabout, is_roundabout, was_ferry, is_ferry) {
+                    (_, _, false, true) => "ferry",
+                    (_, _, true, false) => "exit ferry",
+                    (false, true, _, _) => "roundabout",
+                    (true, false, _, _) => "exit roundabout",
+                    _ => "turn",
+                };
 
                 let turn_geom = build_edge_geometry(edge_id, ebg_nodes, edge_geom, format);
+                let lanes = match m_type {
+                    "turn" | "roundabout" | "exit roundabout" => {
+                        guidance.approach_lanes(ebg_path[i - 1], turn_type, ebg_nodes, nbg_geo)
+                    }
+                    _ => None,
+                };
+                let exit = match m_type {
+                    "roundabout" => {
+                        roundabout_entry = Some(steps.len());
+                        exits_passed = 0;
+                        None
+                    }
+                    "exit roundabout" => {
+                        if let Some(entry) = roundabout_entry.take() {
+                            steps[entry].maneuver.exit = Some(exits_passed);
+                        }
+                        Some(exits_passed)
+                    }
+                    _ => None,
+                };
                 steps.push(RouteStep {
                     distance_m: edge_distance,
                     duration_s: edge_duration,
@@ -1983,7 +3311,11 @@ pub(crate) fn build_steps(
                         maneuver_type: m_type.to_string(),
                         modifier: Some(turn_type.to_string()),
                         name: lookup_road_name(edge_id, ebg_nodes, nbg_geo, way_names),
+                        lanes,
+                        exit,
                     },
+                    instruction: None,
+                    distance_text: None,
                 });
             }
         } else {
@@ -2303,7 +3635,7 @@ mod tests {
                 lat: 50.8510,
             },
         ];
-        let gpx = format_gpx(&points, "Test Route");
+        let gpx = format_gpx(&points, &[], None, "Test Route");
 
         // Verify XML declaration
         assert!(gpx.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
@@ -2330,7 +3662,7 @@ mod tests {
 
     #[test]
     fn test_format_gpx_empty_points() {
-        let gpx = format_gpx(&[], "Empty");
+        let gpx = format_gpx(&[], &[], None, "Empty");
         assert!(gpx.contains("<trkseg>"));
         assert!(gpx.contains("</trkseg>"));
         // No trkpt elements
@@ -2343,12 +3675,53 @@ mod tests {
             lon: 4.3517,
             lat: 50.8503,
         }];
-        let gpx = format_gpx(&points, "Single");
+        let gpx = format_gpx(&points, &[], None, "Single");
         let trkpt_count = gpx.matches("<trkpt").count();
         assert_eq!(trkpt_count, 1);
         assert!(gpx.contains("lat=\"50.8503000\" lon=\"4.3517000\""));
     }
 
+    #[test]
+    fn test_format_gpx_waypoints_and_elevation() {
+        use crate::server::elevation::{ElevationData, SrtmTile};
+        // Flat 2x2-sample tile at 120 m covering N50E004.
+        let elevation = ElevationData::from_tiles(vec![SrtmTile::new(50, 4, 2, vec![120; 4])]);
+        let points = vec![
+            Point {
+                lon: 4.3517,
+                lat: 50.8503,
+            },
+            Point {
+                lon: 5.1,
+                lat: 50.9,
+            },
+        ];
+        let waypoints = [[4.3517, 50.8503], [4.38, 50.86], [5.1, 50.9]];
+        let gpx = format_gpx(&points, &waypoints, Some(&elevation), "Route");
+
+        assert_eq!(gpx.matches("<wpt ").count(), 3);
+        assert!(gpx.contains("<name>Start</name>"));
+        assert!(gpx.contains("<name>Via 1</name>"));
+        assert!(gpx.contains("<name>End</name>"));
+        // Waypoints precede the track (GPX 1.1 element order).
+        assert!(gpx.find("<wpt ").unwrap() < gpx.find("<trk>").unwrap());
+        // First track point is covered by the tile, the second is not.
+        assert!(
+            gpx.contains("<trkpt lat=\"50.8503000\" lon=\"4.3517000\">\n        <ele>120.0</ele>")
+        );
+        assert!(gpx.contains("<trkpt lat=\"50.9000000\" lon=\"5.1000000\"/>"));
+    }
+
+    #[test]
+    fn test_route_wants_gpx_format_overrides_accept() {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept", "application/gpx+xml".parse().unwrap());
+        assert!(route_wants_gpx(None, &headers).unwrap());
+        assert!(!route_wants_gpx(Some("json"), &headers).unwrap());
+        assert!(route_wants_gpx(Some("gpx"), &HeaderMap::new()).unwrap());
+        assert!(route_wants_gpx(Some("kml"), &headers).is_err());
+    }
+
     #[test]
     fn test_format_gpx_self_closing_trkpt() {
         // trkpt elements without elevation should be self-closing
@@ -2356,8 +3729,189 @@ mod tests {
             lon: 4.3517,
             lat: 50.8503,
         }];
-        let gpx = format_gpx(&points, "Route");
+        let gpx = format_gpx(&points, &[], None, "Route");
         assert!(gpx.contains("/>"));
         assert!(!gpx.contains("</trkpt>"));
     }
+
+    #[test]
+    fn test_parse_bearings_per_coordinate() {
+        assert_eq!(
+            parse_bearings("90,20;;360,45").unwrap(),
+            vec![Some((90, 20)), None, Some((0, 45))]
+        );
+        assert!(parse_bearings("90").unwrap_err().contains("format"));
+        assert!(parse_bearings("400,10").unwrap_err().contains("angle"));
+        assert!(parse_bearings("90,181").unwrap_err().contains("range"));
+    }
+
+    #[test]
+    fn test_alternative_limits_and_sharing() {
+        let base =
+            "origin_lon=4.35&origin_lat=50.85&destination_lon=4.40&destination_lat=50.86&mode=car";
+        assert_eq!(alternative_limits(&route_req(base)).unwrap(), (0.8, 1.5));
+        let req = route_req(&format!(
+            "{base}&alternative_max_sharing=0.5&alternative_max_stretch=2"
+        ));
+        assert_eq!(alternative_limits(&req).unwrap(), (0.5, 2.0));
+        for bad in [
+            "alternative_max_sharing=0",
+            "alternative_max_sharing=1.2",
+            "alternative_max_stretch=0.9",
+        ] {
+            let req = route_req(&format!("{base}&{bad}"));
+            assert!(alternative_limits(&req).is_err(), "{bad} accepted");
+        }
+
+        let covered: std::collections::HashSet<u32> = [1, 2].into_iter().collect();
+        let length_of = |eid: u32| [0.0, 10.0, 30.0, 60.0][eid as usize];
+        assert_eq!(shared_fraction(&[1, 2, 3], &covered, length_of), 0.4);
+        assert_eq!(shared_fraction(&[3], &covered, length_of), 0.0);
+        assert_eq!(shared_fraction(&[0], &covered, length_of), 1.0);
+    }
+
+    #[test]
+    fn test_continue_straight_defaults_per_mode() {
+        let pts = "coordinates=4.35,50.85;4.37,50.85;4.40,50.86";
+        assert!(continue_straight(&route_req(&format!("{pts}&mode=car"))).unwrap());
+        assert!(!continue_straight(&route_req(&format!("{pts}&mode=bike"))).unwrap());
+        let req = route_req(&format!("{pts}&mode=foot&continue_straight=true"));
+        assert!(continue_straight(&req).unwrap());
+        let req = route_req(&format!("{pts}&mode=car&continue_straight=false"));
+        assert!(!continue_straight(&req).unwrap());
+        let req = route_req(&format!("{pts}&mode=car&continue_straight=yes"));
+        assert!(continue_straight(&req).unwrap_err().contains("yes"));
+    }
+
+    #[test]
+    fn test_endpoint_radii() {
+        let base =
+            "origin_lon=4.35&origin_lat=50.85&destination_lon=4.40&destination_lat=50.86&mode=car";
+        let max = crate::server::snap_index::MAX_SNAP_DISTANCE_M;
+        assert_eq!(endpoint_radii(&route_req(base)).unwrap(), [max, max]);
+        let req = route_req(&format!("{base}&radius=25;"));
+        assert_eq!(endpoint_radii(&req).unwrap(), [25.0, max]);
+        let req = route_req(&format!("{base}&radius=;40"));
+        assert_eq!(endpoint_radii(&req).unwrap(), [max, 40.0]);
+        for bad in ["0", "-5", "abc", "6000", "10;10;10"] {
+            let req = route_req(&format!("{base}&radius={bad}"));
+            assert!(endpoint_radii(&req).is_err(), "radius={bad} accepted");
+        }
+    }
+
+    #[test]
+    fn test_annotation_flags_parse() {
+        let flags = AnnotationFlags::parse("duration, ways,").unwrap();
+        assert!(flags.duration && flags.ways);
+        assert!(!flags.distance && !flags.speed && !flags.nodes && !flags.ferry);
+        assert_eq!(
+            AnnotationFlags::parse("").unwrap(),
+            AnnotationFlags::default()
+        );
+        assert!(
+            AnnotationFlags::parse("duration,names")
+                .unwrap_err()
+                .contains("'names'")
+        );
+    }
+
+    #[test]
+    fn test_endpoint_approaches() {
+        let base =
+            "origin_lon=4.35&origin_lat=50.85&destination_lon=4.40&destination_lat=50.86&mode=car";
+        let unrestricted = [Approach::Unrestricted; 2];
+        assert_eq!(endpoint_approaches(&route_req(base)).unwrap(), unrestricted);
+        let req = route_req(&format!("{base}&approaches=;curb"));
+        assert_eq!(
+            endpoint_approaches(&req).unwrap(),
+            [Approach::Unrestricted, Approach::Curb]
+        );
+        let req = route_req(&format!("{base}&approaches=kerb"));
+        assert!(endpoint_approaches(&req).unwrap_err().contains("kerb"));
+        let req = route_req(&format!("{base}&approaches=curb;curb;curb"));
+        assert!(endpoint_approaches(&req).is_err());
+    }
+
+    #[test]
+    fn test_endpoint_and_via_hints() {
+        use crate::profile_abi::Mode;
+        let hint = |coord: [f64; 2], ebg: u32| {
+            SnapHint::new(7, Mode(0), coord, &[(ebg, coord[0], coord[1], 2.0)])
+        };
+        let (a, b, c) = ([4.35, 50.85], [4.38, 50.86], [4.40, 50.87]);
+        let base =
+            "origin_lon=4.35&origin_lat=50.85&destination_lon=4.40&destination_lat=50.86&mode=car";
+        let req = route_req(&format!("{base}&hints=;{}", hint(b, 2).encode()));
+        assert_eq!(endpoint_hints(&req).unwrap(), [None, Some(hint(b, 2))]);
+        let req = route_req(&format!("{base}&hints=bogus"));
+        assert!(endpoint_hints(&req).unwrap_err().contains("hint 0"));
+
+        // The via point b carries the candidates of both of its snaps.
+        let legs = [
+            Some([hint(a, 0), hint(b, 2)]),
+            Some([hint(b, 3), hint(c, 4)]),
+        ];
+        let waypoints = via_waypoints(&legs).unwrap();
+        assert_eq!(waypoints.len(), 3);
+        let via = SnapHint::decode(&waypoints[1].hint).unwrap();
+        assert_eq!(
+            via.candidates.iter().map(|c| c.0).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert_eq!(waypoints[2].location, c);
+        assert!(via_waypoints(&[legs[0].clone(), None]).is_none());
+    }
+
+    #[test]
+    fn test_leg_slots_split_per_coordinate() {
+        let slots = per_coordinate(Some("90,20;;180,30"), "bearings", 3).unwrap();
+        assert_eq!(leg_slots(&slots, 0).as_deref(), Some("90,20;"));
+        assert_eq!(leg_slots(&slots, 1).as_deref(), Some(";180,30"));
+        assert_eq!(leg_slots(&None, 0), None);
+        assert!(per_coordinate(Some("1;2;3"), "radius", 2).is_err());
+    }
+
+    fn route_req(query: &str) -> RouteRequest {
+        let uri: axum::http::Uri = format!("/route?{}", query).parse().unwrap();
+        Query::<RouteRequest>::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn test_route_waypoints_from_origin_destination() {
+        let req = route_req(
+            "origin_lon=4.35&origin_lat=50.85&destination_lon=4.40&destination_lat=50.86&mode=car",
+        );
+        assert_eq!(
+            route_waypoints(&req).unwrap(),
+            vec![[4.35, 50.85], [4.40, 50.86]]
+        );
+        let req = route_req("origin_lon=4.35&origin_lat=50.85&mode=car");
+        assert!(route_waypoints(&req).unwrap_err().contains("required"));
+    }
+
+    #[test]
+    fn test_route_waypoints_from_coordinates() {
+        let req = route_req("coordinates=4.35,50.85;4.37,50.84;4.40,50.86&mode=car");
+        assert_eq!(
+            route_waypoints(&req).unwrap(),
+            vec![[4.35, 50.85], [4.37, 50.84], [4.40, 50.86]]
+        );
+
+        let req = route_req("coordinates=4.35,50.85&mode=car");
+        assert!(
+            route_waypoints(&req)
+                .unwrap_err()
+                .contains("expected 2 to 25")
+        );
+        let req = route_req("coordinates=4.35,50.85;4.40,95.0&mode=car");
+        assert!(route_waypoints(&req).unwrap_err().contains("waypoint 1"));
+        let req = route_req("coordinates=4.35,50.85;4.40&mode=car");
+        assert!(route_waypoints(&req).unwrap_err().contains("'lon,lat'"));
+        let req = route_req("coordinates=4.35,50.85;4.40,50.86&origin_lon=4.35&mode=car");
+        assert!(
+            route_waypoints(&req)
+                .unwrap_err()
+                .contains("mutually exclusive")
+        );
+    }
 }
@@ -630,6 +630,32 @@ impl PackedSnapIndex {
         k: usize,
         edge_filter: Option<&[u64]>,
         role_filter: Option<&[u64]>,
+    ) -> Vec<(u32, f64, f64, f64)> {
+        self.snap_k_with_bearing_filtered_role(
+            lon,
+            lat,
+            mode_idx,
+            k,
+            None,
+            edge_filter,
+            role_filter,
+        )
+    }
+
+    /// K-nearest with an optional `(bearing, range)` filter on top of the
+    /// edge and role filters: only samples whose edge heads within
+    /// `range` degrees of `bearing` are candidates. `None` is exactly
+    /// [`snap_k_with_info_filtered_role`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn snap_k_with_bearing_filtered_role(
+        &self,
+        lon: f64,
+        lat: f64,
+        mode_idx: u8,
+        k: usize,
+        bearing: Option<(u16, u16)>,
+        edge_filter: Option<&[u64]>,
+        role_filter: Option<&[u64]>,
     ) -> Vec<(u32, f64, f64, f64)> {
         if k == 0 {
             return Vec::new();
@@ -683,6 +709,11 @@ impl PackedSnapIndex {
             {
                 return None;
             }
+            if let Some((angle, range)) = bearing
+                && !bearing_matches(p.bearing, angle, range)
+            {
+                return None;
+            }
             let (d2, plon, plat) = sample_distance2(lon, lat, p);
             if d2 > max2 {
                 return None;
@@ -1178,5 +1209,15 @@ mod tests {
         let id_e = idx.snap_with_bearing(4.0, 50.0, 0, 90, 20).map(|x| x.0);
         assert_eq!(id_n, Some(0));
         assert_eq!(id_e, Some(1));
+
+        // K-best keeps the same filter: both edges without a bearing,
+        // only the heading-matched one with it.
+        assert_eq!(idx.snap_k_with_info(4.0, 50.0, 0, 4).len(), 2);
+        let k_e =
+            idx.snap_k_with_bearing_filtered_role(4.0, 50.0, 0, 4, Some((80, 20)), None, None);
+        assert_eq!(k_e.iter().map(|x| x.0).collect::<Vec<_>>(), vec![1]);
+        let k_s =
+            idx.snap_k_with_bearing_filtered_role(4.0, 50.0, 0, 4, Some((180, 30)), None, None);
+        assert!(k_s.is_empty());
     }
 }
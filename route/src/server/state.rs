@@ -23,6 +23,7 @@ use crate::profile_abi::Mode;
 
 use super::exclude::{self, ExcludeWeights};
 
+use super::conditional::EdgeConditionals;
 use super::edge_geom::EdgeGeometry;
 use super::elevation::ElevationData;
 use super::snap_index::{DEFAULT_CELL_LOG2, PackedSnapIndex, SnapBuilderMode, build_snap_index};
@@ -356,12 +357,24 @@ pub struct ServerState {
     // heap on Belgium). Both expose the same `get(way_id) -> Option<&str>` API.
     pub way_names: WayNames,
 
+    // Lane guidance: OSM way_id → per-direction `turn:lanes` masks, read
+    // from ways.raw next to the names. Only ways with lane tags are kept.
+    pub way_lanes: HashMap<i64, super::lanes::WayLanes>,
+
     // Distance weights indexed by original EBG node ID (length_m per edge).
     // Used for isodistance isochrones — same role as ModeData.node_weights but in meters.
     pub node_weights_dist: Vec<u32>,
 
     // Per-EBG-edge exclude flags (toll/ferry/motorway), indexed by original EBG edge ID
     pub edge_exclude_flags: Vec<u8>,
+    // False when no way_attrs were loaded: the flags above are then all
+    // zero and `exclude=` is rejected instead of silently ignored.
+    pub exclude_available: bool,
+
+    // Time-dependent closures per base mode name, from
+    // `way_conditionals.<mode>.bin`: evaluated by /route `depart_at` /
+    // `arrive_by`. Modes without conditionals have no entry.
+    pub edge_conditionals: HashMap<String, super::conditional::EdgeConditionals>,
 
     // Bounded LRU cache for avoid_polygons-recustomized weights.
     // Keyed by (mode, polygon_hash, exclude_mask). Each entry is
@@ -592,11 +605,14 @@ impl ServerState {
         tracing::info!("Loading road names...");
         let way_names = WayNames::from_heap(load_way_names(&step1_dir)?);
         tracing::info!(named_roads = way_names.len(), "loaded road names");
+        let way_lanes = load_way_lanes(&step1_dir)?;
+        tracing::info!(laned_ways = way_lanes.len(), "loaded turn lanes");
 
         // Build per-edge exclude flags from way_attrs.car.bin
         // Try car first, then any available mode's way_attrs
         tracing::info!("Loading edge exclude flags...");
         let way_attrs_path = find_way_attrs_path(&step2_dir, &discovered_modes);
+        let exclude_available = way_attrs_path.is_some();
         let edge_exclude_flags = if let Some(attrs_path) = way_attrs_path {
             exclude::build_edge_exclude_flags(&ebg_nodes, &attrs_path)?
         } else {
@@ -604,6 +620,19 @@ impl ServerState {
             vec![0u8; ebg_nodes.n_nodes as usize]
         };
 
+        let mut edge_conditionals = HashMap::new();
+        for mode_name in &discovered_modes {
+            let path = step2_dir.join(format!("way_conditionals.{}.bin", mode_name));
+            if path.exists() {
+                let conditionals = crate::formats::way_conditionals::read_all(&path)?;
+                insert_edge_conditionals(
+                    &mut edge_conditionals,
+                    mode_name,
+                    EdgeConditionals::build(&ebg_nodes, &nbg_geo, &conditionals),
+                );
+            }
+        }
+
         // Build distance-based node weights from EBG edge lengths (m).
         // Used for isodistance isochrones: same role as ModeData.node_weights but distance-based.
         let node_weights_dist: Vec<u32> = ebg_nodes.nodes.iter().map(|n| n.length_m).collect();
@@ -683,8 +712,11 @@ impl ServerState {
             snap_index,
             elevation,
             way_names,
+            way_lanes,
             node_weights_dist,
             edge_exclude_flags,
+            exclude_available,
+            edge_conditionals,
             avoid_cache: super::avoid::AvoidWeightCache::default(),
             transit,
             started_at: std::time::Instant::now(),
@@ -1452,6 +1484,22 @@ impl ServerState {
             WayNames::Heap(HashMap::new())
         };
 
+        // ---- Turn lanes ---------------------------------------------
+        // `turn:lanes` is only in the raw ways; containers packed without
+        // `shared/step1.ways.raw` serve steps without lane guidance.
+        let way_lanes = match optional_section("shared/step1.ways.raw")? {
+            Some(ways_bytes) => {
+                let lanes = load_way_lanes_from_bytes(ways_bytes)?;
+                let _ = crate::formats::mmap::madvise_dontneed(ways_bytes);
+                tracing::info!(laned_ways = lanes.len(), "loaded turn lanes");
+                lanes
+            }
+            None => {
+                tracing::info!("no ways.raw section in container, lane guidance unavailable");
+                HashMap::new()
+            }
+        };
+
         // ---- Edge exclude flags from one mode's way_attrs -----------
         // #275: way_attrs is read once at boot to build the per-edge
         // exclude flag table. The flags live in a heap Vec from that
@@ -1466,7 +1514,9 @@ impl ServerState {
             discovered_modes[0].clone()
         };
         let attrs_section = format!("mode/{}/way_attrs", attrs_mode);
-        let edge_exclude_flags = if let Some(attr_bytes) = optional_section(&attrs_section)? {
+        let attr_bytes = optional_section(&attrs_section)?;
+        let exclude_available = attr_bytes.is_some();
+        let edge_exclude_flags = if let Some(attr_bytes) = attr_bytes {
             let attrs = crate::formats::way_attrs::read_all_from_bytes(attr_bytes)?;
             let flags = exclude::build_edge_exclude_flags_from_attrs(&ebg_nodes, &attrs)?;
             if let Err(e) = crate::formats::mmap::madvise_dontneed(attr_bytes) {
@@ -1541,6 +1591,22 @@ impl ServerState {
             }
         }
 
+        // ---- Time-dependent closures (way_conditionals) ------------
+        // Decoded once into per-edge schedules; the section is cold after.
+        let mut edge_conditionals = HashMap::new();
+        for mode_name in &discovered_modes {
+            let section = format!("mode/{}/way_conditionals", mode_name);
+            if let Some(bytes) = optional_section(&section)? {
+                let conditionals = crate::formats::way_conditionals::read_all_from_bytes(bytes)?;
+                let _ = crate::formats::mmap::madvise_dontneed(bytes);
+                insert_edge_conditionals(
+                    &mut edge_conditionals,
+                    mode_name,
+                    EdgeConditionals::build(&ebg_nodes, &nbg_geo, &conditionals),
+                );
+            }
+        }
+
         // #297: EBG `length_m` is now metres (was `length_mm`).
         let node_weights_dist: Vec<u32> = ebg_nodes.nodes.iter().map(|n| n.length_m).collect();
 
@@ -1679,8 +1745,11 @@ impl ServerState {
             snap_index,
             elevation,
             way_names,
+            way_lanes,
             node_weights_dist,
             edge_exclude_flags,
+            exclude_available,
+            edge_conditionals,
             avoid_cache: super::avoid::AvoidWeightCache::default(),
             transit: None,
             started_at: std::time::Instant::now(),
@@ -1690,6 +1759,21 @@ impl ServerState {
         })
     }
 
+    /// Weight sets servable per base mode (`fastest`, `shortest`, then
+    /// each loaded traffic variant), as selected by `?weighting=`.
+    pub fn weightings(&self) -> std::collections::BTreeMap<String, Vec<String>> {
+        self.mode_names
+            .iter()
+            .filter(|name| !is_variant_mode_name(name, &self.mode_names))
+            .map(|name| {
+                (
+                    name.clone(),
+                    super::types::available_weightings(name, &self.mode_lookup),
+                )
+            })
+            .collect()
+    }
+
     /// Get mode data by mode (index-based lookup). #402: lazy-reloads
     /// if the slot was previously evicted by the idle compactor.
     /// Returns an `Arc<ModeData>` — holding the Arc keeps the mode
@@ -2609,6 +2693,127 @@ impl ServerState {
         Ok(matched)
     }
 
+    /// Incremental TIME re-customization of one base mode from a
+    /// `changes.bin` delta — the admin counterpart of
+    /// `step8-customize --delta`.
+    ///
+    /// Applies the delta to the mode's resident node weights, re-customizes
+    /// only the affected triangles
+    /// ([`crate::customization::customize_cch_delta`]) on top of the
+    /// resident `cch_weights`, rebuilds the TIME flats and len-along-time,
+    /// then hot-swaps and pins the slot exactly like
+    /// [`Self::recustomize_car_from_edge_speeds`]. Deltas therefore stack
+    /// on whatever the mode currently serves (including a boot
+    /// recustomization). Calls are serialized so two deltas can't both
+    /// start from the same base and drop one another's changes.
+    ///
+    /// Requires the container path for the mode's `filtered_ebg` and turn
+    /// penalties. On error nothing is swapped.
+    pub fn apply_weight_delta(
+        &self,
+        mode_name: &str,
+        delta: &crate::formats::WeightDelta,
+    ) -> Result<crate::customization::DeltaStats> {
+        static APPLY_LOCK: parking_lot::Mutex<()> = parking_lot::Mutex::new(());
+        let _serialized = APPLY_LOCK.lock();
+        let t0 = std::time::Instant::now();
+
+        let idx = *self
+            .mode_lookup
+            .get(mode_name)
+            .ok_or_else(|| anyhow::anyhow!("weight delta: unknown mode '{}'", mode_name))?;
+        anyhow::ensure!(
+            !is_variant_mode_name(mode_name, &self.mode_names),
+            "weight delta: '{}' is a traffic variant; apply deltas to its base mode",
+            mode_name
+        );
+        let base = self.get_mode(Mode(idx));
+        anyhow::ensure!(
+            delta.mode == base.mode,
+            "weight delta: changes were written for {:?}, '{}' is {:?}",
+            delta.mode,
+            mode_name,
+            base.mode
+        );
+
+        let mmap = self
+            ._mmap_arc
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("weight delta requires container-backed state"))?;
+        let lazy = self
+            .lazy
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("weight delta requires LazyContainer"))?;
+        let container = lazy.container();
+        let section = |leaf: &str| -> Result<(usize, usize)> {
+            let name = format!("mode/{}/{}", mode_name, leaf);
+            let entry = container
+                .get(&name)
+                .ok_or_else(|| anyhow::anyhow!("weight delta: missing section '{}'", name))?;
+            let (off, len) = (entry.offset as usize, entry.len as usize);
+            anyhow::ensure!(
+                off + len <= mmap.len(),
+                "weight delta: section '{}' bytes [{},{}) exceed mmap len {}",
+                name,
+                off,
+                off + len,
+                mmap.len()
+            );
+            lazy.verify_now(&name)?;
+            Ok((off, len))
+        };
+        let (off, len) = section("node_weights.turn")?;
+        let turns = crate::formats::mod_turns::read_all_from_bytes(&mmap[off..off + len])?;
+        let (off, len) = section("filtered_ebg")?;
+        let filtered_ebg = crate::formats::FilteredEbgFile::read_from_mmap_unverified(
+            std::sync::Arc::clone(mmap),
+            off,
+            len,
+        )?;
+
+        let mut node_weights = base.node_weights.to_vec();
+        let changed = delta.apply(&mut node_weights)?;
+        let (new_weights, stats) = crate::customization::customize_cch_delta(
+            &base.cch_topo,
+            &filtered_ebg,
+            &node_weights,
+            &turns.penalties,
+            &base.cch_weights,
+            &changed,
+        )?;
+
+        let up_adj_flat = UpAdjFlat::build_with(&base.cch_topo, &new_weights, true);
+        let down_rev_flat = DownReverseAdjFlat::build_with(&base.cch_topo, &new_weights, true);
+        let down_adj_flat = DownAdjFlat::build(&base.cch_topo, &new_weights);
+        let (lat_w, lat_up, lat_dn) =
+            refresh_len_along_time(&base, &self.ebg_nodes, &new_weights, &node_weights);
+        let mut new_mode = clone_mode_data(&base);
+        new_mode.cch_weights = new_weights;
+        new_mode.node_weights = std::borrow::Cow::Owned(node_weights);
+        new_mode.up_adj_flat = up_adj_flat;
+        new_mode.down_rev_flat = down_rev_flat;
+        new_mode.down_adj_flat = down_adj_flat;
+        new_mode.cch_weights_len_along_time = lat_w;
+        new_mode.up_adj_flat_len_along_time = lat_up;
+        new_mode.down_rev_flat_len_along_time = lat_dn;
+        let slot = &self.modes[idx as usize];
+        {
+            let mut w = slot.state.write();
+            *w = Some(std::sync::Arc::new(new_mode));
+        }
+        slot.evictable
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+        tracing::info!(
+            mode = mode_name,
+            changed_nodes = stats.changed_nodes,
+            recomputed_arcs = stats.recomputed_arcs,
+            updated_arcs = stats.updated_arcs,
+            elapsed_s = t0.elapsed().as_secs_f64(),
+            "weight delta: hot-swapped + pinned re-customized mode"
+        );
+        Ok(stats)
+    }
+
     /// #402: evict a single mode slot if it has been idle for at least
     /// `threshold_ms`. Drops the inner `Arc<ModeData>`; any in-flight
     /// query holding its own Arc clone keeps the data alive until that
@@ -2667,6 +2872,20 @@ impl ServerState {
         self.transit = Some(state);
     }
 
+    /// Parse a request's `exclude` parameter for this region. Errors (400
+    /// material) on unknown tokens and when the region was loaded without
+    /// way attributes, where every edge would pass the filter unchanged.
+    pub fn parse_exclude(&self, exclude: &Option<String>) -> Result<Option<u8>, String> {
+        let mask = exclude::parse_exclude_option(exclude)?;
+        if mask.is_some() && !self.exclude_available {
+            return Err(
+                "exclude is not available: this region was loaded without way attributes, so toll/ferry/motorway edges are unknown"
+                    .to_string(),
+            );
+        }
+        Ok(mask)
+    }
+
     /// Get or compute exclude weights for a mode and exclude mask.
     /// Returns Arc<ExcludeWeights> from cache, computing on first access.
     pub fn get_exclude_weights(
@@ -2801,9 +3020,11 @@ pub(crate) fn discover_traffic_variants(
         // Try to split <base>_<variant> by trying every known base mode as a prefix.
         let mut matched: Option<(String, String)> = None;
         for base in &bases_sorted {
+            // Dotted stems are sidecars such as `cch.w.<base>_<variant>.perfect.u32`.
             if let Some(rest) = stem.strip_prefix(*base)
                 && let Some(variant) = rest.strip_prefix('_')
                 && !variant.is_empty()
+                && !variant.contains('.')
             {
                 matched = Some(((*base).to_string(), variant.to_string()));
                 break;
@@ -2836,6 +3057,22 @@ pub(crate) fn discover_traffic_variants(
     Ok(variants)
 }
 
+/// Keep a mode's closures when it has any, logging how many edges they cover.
+fn insert_edge_conditionals(
+    map: &mut HashMap<String, EdgeConditionals>,
+    mode_name: &str,
+    closures: EdgeConditionals,
+) {
+    tracing::info!(
+        mode = mode_name,
+        edges = closures.len(),
+        "loaded time-dependent closures"
+    );
+    if !closures.is_empty() {
+        map.insert(mode_name.to_string(), closures);
+    }
+}
+
 /// Find the best way_attrs file for exclude flags.
 /// Prefers "car" if available, otherwise uses the first available mode.
 fn find_way_attrs_path(step2_dir: &Path, modes: &[String]) -> Option<std::path::PathBuf> {
@@ -2988,10 +3225,11 @@ fn load_mode_data(
 
     // Load per-mode CCH topology from step 7
     let topo_path = step7_dir.join(format!("cch.{}.topo", mode_name));
-    let cch_topo = CchTopoFile::read(&topo_path)?;
+    let cch_topo = CchTopoFile::read_mmap(&topo_path)?;
 
-    // Load node weights from step 5 (indexed by original EBG node ID)
-    let weights_path = step5_dir.join(format!("w.{}.u32", mode_name));
+    // Load node weights from step 5, or their speed-refreshed step 8 copy
+    // (indexed by original EBG node ID)
+    let weights_path = crate::customization::node_weights_path(step5_dir, step8_dir, mode_name);
     let weights_data = mod_weights::read_all(&weights_path)?;
 
     // Build the base snap mask from the mode-filtered EBG. Directional
@@ -3415,6 +3653,16 @@ fn load_way_names(step1_dir: &Path) -> Result<HashMap<i64, String>> {
     Ok(way_names)
 }
 
+/// Load per-way turn lanes from `step1/ways.raw`; empty when absent.
+fn load_way_lanes(step1_dir: &Path) -> Result<HashMap<i64, super::lanes::WayLanes>> {
+    let ways_path = step1_dir.join("ways.raw");
+    if !ways_path.exists() {
+        return Ok(HashMap::new());
+    }
+    let (key_dict, val_dict, _, _) = WaysFile::read_dictionaries(&ways_path)?;
+    super::lanes::collect_way_lanes(&key_dict, &val_dict, WaysFile::stream_ways(&ways_path)?)
+}
+
 // Distance weights are now pre-computed in step8 pipeline (cch.d.{mode}.u32)
 // and loaded from file alongside time weights at startup.
 
@@ -4200,6 +4448,17 @@ fn load_way_names_from_bytes(ways_bytes: &[u8]) -> Result<HashMap<i64, String>>
     Ok(way_names)
 }
 
+/// Same as `load_way_lanes` but reads from an in-memory ways.raw byte
+/// slice (container `shared/step1.ways.raw` section).
+fn load_way_lanes_from_bytes(ways_bytes: &[u8]) -> Result<HashMap<i64, super::lanes::WayLanes>> {
+    let (key_dict, val_dict, _, _) = WaysFile::read_dictionaries_from_bytes(ways_bytes)?;
+    super::lanes::collect_way_lanes(
+        &key_dict,
+        &val_dict,
+        WaysFile::stream_ways_from_bytes(ways_bytes)?,
+    )
+}
+
 // ---------- Packed snap index helpers (#154) -------------------------------
 
 /// Build a packed snap index in heap memory from the loaded EBG + NBG
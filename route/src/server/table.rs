@@ -23,10 +23,13 @@ use crate::matrix::bucket_ch::{
 use crate::matrix::neighbors::{RadiusParam, auto_radius_km, build_neighbors, parse_radius};
 use crate::profile_abi::Mode;
 
+use super::avoid::AvoidEntry;
+use super::exclude::ExcludeWeights;
 use super::regions::RegionsState;
 use super::state::ServerState;
 use super::types::{
-    ErrorResponse, SnapRole, Waypoint, get_node_location, parse_mode, validate_coord,
+    ErrorResponse, SnapRole, Waypoint, Weighting, get_node_location, resolve_weighting,
+    validate_coord,
 };
 
 // ============ Types ============
@@ -40,12 +43,26 @@ use super::types::{
 #[derive(Debug, Deserialize, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TablePostRequest {
-    /// Source coordinates [[lon, lat], ...]
+    /// Source coordinates [[lon, lat], ...]. Not allowed with `coordinates`.
+    #[serde(default)]
     #[schema(example = json!([[4.3517, 50.8503], [4.4017, 50.8603]]))]
-    pub origins: Vec<[f64; 2]>,
-    /// Destination coordinates [[lon, lat], ...]
+    pub origins: Option<Vec<[f64; 2]>>,
+    /// Destination coordinates [[lon, lat], ...], or indices into
+    /// `coordinates` when that is given (default: every coordinate)
+    #[serde(default)]
     #[schema(example = json!([[4.3817, 50.8553], [4.4217, 50.8653]]))]
-    pub destinations: Vec<[f64; 2]>,
+    pub destinations: Option<TableDestinations>,
+    /// Shared coordinate list [[lon, lat], ...] that `sources` and
+    /// `destinations` index into, so an N×M matrix over one point set
+    /// needs no padding
+    #[serde(default)]
+    #[schema(example = json!([[4.3517, 50.8503], [4.3817, 50.8553], [4.4017, 50.8603]]))]
+    pub coordinates: Option<Vec<[f64; 2]>>,
+    /// Indices into `coordinates` used as sources (default: every
+    /// coordinate). Requires `coordinates`.
+    #[serde(default)]
+    #[schema(example = json!([0, 2]))]
+    pub sources: Option<Vec<usize>>,
     /// Transport mode: car, bike, or foot
     #[schema(example = "car")]
     pub mode: String,
@@ -53,6 +70,11 @@ pub struct TablePostRequest {
     #[serde(default = "default_annotations")]
     #[schema(example = "duration,distance")]
     pub annotations: String,
+    /// Weight set: "fastest" (default), "shortest" (distances only — a
+    /// distance-only table is already shortest-distance) or a loaded
+    /// traffic variant name such as "rush_hour"
+    #[serde(default)]
+    pub weighting: Option<String>,
     /// Exclude road types: comma-separated list of "toll", "ferry", "motorway"
     #[serde(default)]
     pub exclude: Option<String>,
@@ -77,12 +99,133 @@ pub struct TablePostRequest {
     /// Explicit opt-in: runs the matrix three times. car only.
     #[serde(default)]
     pub uncertainty: Option<String>,
+    /// Fill unreachable cells (and cells of coordinates that could not snap)
+    /// with a straight-line estimate at this speed in m/s instead of null.
+    /// Filled cells are listed in `fallback_speed_cells`. Not allowed with
+    /// `max_minutes`.
+    #[serde(default)]
+    #[schema(example = 10.0)]
+    pub fallback_speed: Option<f64>,
+    /// Which points the fallback estimate measures between: "input"
+    /// (default, the request coordinates) or "snapped" (the snapped
+    /// locations, the input for a coordinate that did not snap)
+    #[serde(default)]
+    pub fallback_coordinate: Option<String>,
 }
 
 pub fn default_annotations() -> String {
     "duration".to_string()
 }
 
+/// `destinations` of a /table request: coordinates, or indices into
+/// `coordinates`
+#[derive(Debug, Clone, PartialEq, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum TableDestinations {
+    Coordinates(Vec<[f64; 2]>),
+    Indices(Vec<usize>),
+}
+
+/// A /table request's (origins, destinations) as `[lon, lat]` pairs.
+pub type TablePoints = (Vec<[f64; 2]>, Vec<[f64; 2]>);
+
+/// Resolve a /table request to its (origins, destinations) coordinates.
+///
+/// With `coordinates`, `sources` / `destinations` select rows and columns
+/// by index (repeats allowed, order kept), so only the selected points are
+/// snapped and searched. Without it, `origins` / `destinations` carry the
+/// coordinates themselves. Empty lists are left to the caller's checks.
+pub fn resolve_table_points(req: &TablePostRequest) -> Result<TablePoints, String> {
+    let Some(coordinates) = &req.coordinates else {
+        if req.sources.is_some() {
+            return Err("sources indices require coordinates".to_string());
+        }
+        let destinations = match &req.destinations {
+            None => Vec::new(),
+            Some(TableDestinations::Coordinates(c)) => c.clone(),
+            Some(TableDestinations::Indices(_)) => {
+                return Err("destinations indices require coordinates".to_string());
+            }
+        };
+        return Ok((req.origins.clone().unwrap_or_default(), destinations));
+    };
+    if req.origins.is_some() {
+        return Err(
+            "origins and coordinates are mutually exclusive: select sources by index".to_string(),
+        );
+    }
+    for (i, [lon, lat]) in coordinates.iter().enumerate() {
+        validate_coord(*lon, *lat, &format!("coordinate[{}]", i))?;
+    }
+    let pick = |name: &str, indices: &[usize]| -> Result<Vec<[f64; 2]>, String> {
+        indices
+            .iter()
+            .enumerate()
+            .map(|(i, &idx)| {
+                coordinates.get(idx).copied().ok_or_else(|| {
+                    format!(
+                        "{}[{}] = {} is out of range for {} coordinates",
+                        name,
+                        i,
+                        idx,
+                        coordinates.len()
+                    )
+                })
+            })
+            .collect()
+    };
+    let origins = match &req.sources {
+        None => coordinates.clone(),
+        Some(indices) => pick("sources", indices)?,
+    };
+    let destinations = match &req.destinations {
+        None => coordinates.clone(),
+        Some(TableDestinations::Indices(indices)) => pick("destinations", indices)?,
+        // `[]` parses as the coordinate form; it is an empty selection.
+        Some(TableDestinations::Coordinates(c)) if c.is_empty() => Vec::new(),
+        Some(TableDestinations::Coordinates(_)) => {
+            return Err("with coordinates, destinations must be indices into it".to_string());
+        }
+    };
+    Ok((origins, destinations))
+}
+
+/// Straight-line estimate for cells the matrix leaves null
+/// (`fallback_speed` / `fallback_coordinate`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableFallback {
+    /// Estimate speed in m/s
+    pub speed: f64,
+    /// Measure between snapped locations rather than the input coordinates
+    pub snapped: bool,
+}
+
+/// Parse the `fallback_speed` / `fallback_coordinate` pair. `Ok(None)` when
+/// no fallback is requested.
+pub fn parse_fallback(
+    speed: Option<f64>,
+    coordinate: Option<&str>,
+) -> Result<Option<TableFallback>, String> {
+    let snapped = match coordinate {
+        None | Some("input") => false,
+        Some("snapped") => true,
+        Some(other) => {
+            return Err(format!(
+                "Invalid fallback_coordinate '{}' (expected 'input' or 'snapped')",
+                other
+            ));
+        }
+    };
+    match speed {
+        None if coordinate.is_some() => Err("fallback_coordinate requires fallback_speed".into()),
+        None => Ok(None),
+        Some(v) if !v.is_finite() || v <= 0.0 => Err(format!(
+            "fallback_speed must be a positive number of m/s, got {v}"
+        )),
+        Some(speed) => Ok(Some(TableFallback { speed, snapped })),
+    }
+}
+
 /// Convert an optional `max_minutes` request field into a CCH time-weight
 /// threshold in seconds (the unit of the time metric, post-#297). Returns
 /// `Ok(None)` when unset, an error on out-of-range values. `u32::MAX` stays
@@ -112,8 +255,11 @@ pub struct TableResponse {
     /// Row-major matrix of durations in seconds (null if unreachable)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub durations: Option<Vec<Vec<Option<f64>>>>,
-    /// Row-major matrix of distances in meters (null if unreachable)
-    /// Distances represent shortest-distance routes (independent of time optimization)
+    /// Row-major matrix of distances in meters (null if unreachable).
+    /// With durations also requested, each cell is the length of the
+    /// fastest route when the mode ships length-along-time weights
+    /// (`cch.lat.<mode>.u32`) and no exclude/avoid applies; otherwise it is
+    /// the shortest-distance route (independent of time optimization)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub distances: Option<Vec<Vec<Option<f64>>>>,
     /// Source waypoints with snapped locations
@@ -122,6 +268,16 @@ pub struct TableResponse {
     /// Destination waypoints with snapped locations
     #[serde(skip_serializing_if = "Option::is_none")]
     pub destinations: Option<Vec<Waypoint>>,
+    /// Snap outcome of each source coordinate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origins_status: Option<Vec<SnapStatus>>,
+    /// Snap outcome of each destination coordinate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destinations_status: Option<Vec<SnapStatus>>,
+    /// [source, destination] index pairs filled with the fallback estimate
+    /// (only with fallback_speed)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_speed_cells: Option<Vec<[usize; 2]>>,
     /// Optimistic (25th TIME percentile) durations — only with uncertainty=bands
     #[serde(skip_serializing_if = "Option::is_none")]
     pub durations_q25: Option<Vec<Vec<Option<f64>>>>,
@@ -130,6 +286,18 @@ pub struct TableResponse {
     pub durations_q75: Option<Vec<Vec<Option<f64>>>>,
 }
 
+/// Snap outcome of one /table coordinate
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SnapStatus {
+    /// Whether the coordinate snapped to an edge the mode can route on;
+    /// a coordinate that did not has a null row/column unless fallback_speed
+    /// is set
+    pub snapped: bool,
+    /// Distance from the input coordinate to its snapped location in metres
+    /// (null when not snapped)
+    pub snap_distance: Option<f64>,
+}
+
 /// Request for streaming table computation
 #[derive(Debug, Deserialize, ToSchema)]
 #[serde(deny_unknown_fields)] // #415: surface unsupported params instead of silently dropping
@@ -143,6 +311,11 @@ pub struct TableStreamRequest {
     /// Transport mode: car, bike, or foot
     #[schema(example = "car")]
     pub mode: String,
+    /// Weight set: "fastest" (default) or a loaded traffic variant name
+    /// such as "rush_hour". The stream carries durations only, so
+    /// "shortest" is rejected.
+    #[serde(default)]
+    pub weighting: Option<String>,
     /// Tile size for sources (default 1000)
     #[serde(default = "default_tile_size")]
     #[schema(example = 1000)]
@@ -182,7 +355,7 @@ pub fn default_tile_size() -> usize {
     path = "/table",
     tag = "Matrix",
     summary = "Compute distance/duration matrix",
-    description = "Computes a many-to-many distance and/or duration matrix using Bucket CH.\nBest for matrices up to ~10K cells. For larger matrices, use POST /table/stream.",
+    description = "Computes a many-to-many distance and/or duration matrix on the CCH, choosing bucket M2M or seeded PHAST by matrix shape.\nMatrices above 4M cells are computed in blocks of source rows; the limit is 25M cells (5000×5000). For larger matrices, use the Flight `matrix` action.",
     request_body(content = TablePostRequest, description = "Source and destination coordinates with mode",
         example = json!({
             "origins": [[4.3517, 50.8503], [4.3617, 50.8553]],
@@ -200,12 +373,18 @@ pub async fn table_post_handler(
     State(regions): State<Arc<RegionsState>>,
     Json(req): Json<TablePostRequest>,
 ) -> impl IntoResponse {
-    for (i, [lon, lat]) in req.origins.iter().enumerate() {
+    let (origins, destinations) = match resolve_table_points(&req) {
+        Ok(pair) => pair,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+        }
+    };
+    for (i, [lon, lat]) in origins.iter().enumerate() {
         if let Err(e) = validate_coord(*lon, *lat, &format!("source[{}]", i)) {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
         }
     }
-    for (i, [lon, lat]) in req.destinations.iter().enumerate() {
+    for (i, [lon, lat]) in destinations.iter().enumerate() {
         if let Err(e) = validate_coord(*lon, *lat, &format!("destination[{}]", i)) {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
         }
@@ -216,10 +395,9 @@ pub async fn table_post_handler(
     // with 501 (cross-region matrix is part of the overlay design,
     // PR C / Phase 2).
     let started_dispatch = std::time::Instant::now();
-    let coords_iter = req
-        .origins
+    let coords_iter = origins
         .iter()
-        .chain(req.destinations.iter())
+        .chain(destinations.iter())
         .map(|&[lon, lat]| (lon, lat));
     let (state, region_id): (Arc<ServerState>, String) =
         match regions.dispatch_many(coords_iter, &req.mode) {
@@ -230,14 +408,15 @@ pub async fn table_post_handler(
             }
         };
 
-    let mode = match parse_mode(&req.mode, &state.mode_lookup) {
-        Ok(m) => m,
-        Err(e) => {
-            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
-        }
-    };
+    let (mode, weighting) =
+        match resolve_weighting(&req.mode, req.weighting.as_deref(), &state.mode_lookup) {
+            Ok(pair) => pair,
+            Err(e) => {
+                return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+            }
+        };
 
-    if req.origins.is_empty() {
+    if origins.is_empty() {
         return (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -246,24 +425,50 @@ pub async fn table_post_handler(
         )
             .into_response();
     }
-    // Guard against memory explosion: max 10,000 sources × destinations for /table
-    // (use /table/stream for larger matrices)
-    const MAX_TABLE_CELLS: usize = 10_000_000;
-    if req.origins.len() * req.destinations.len() > MAX_TABLE_CELLS {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: format!(
-                    "matrix too large: {}×{} = {} cells exceeds limit of {}. Use POST /table/stream for large matrices.",
-                    req.origins.len(), req.destinations.len(),
-                    req.origins.len() * req.destinations.len(),
-                    MAX_TABLE_CELLS
-                ),
-            }),
-        )
-            .into_response();
+    // #521 uncertainty bands: two more full matrix passes on the hidden band
+    // weight sets, returned as durations_q25/q75 next to the median grids
+    // (TIME quantiles: q25 <- fluid q75-speed set). Opt-in only — 3x cost.
+    let band_modes = match req.uncertainty.as_deref() {
+        None => None,
+        Some("bands") => {
+            if req.mode != "car"
+                || weighting != Weighting::Fastest
+                || req.exclude.is_some()
+                || req.avoid_polygons.is_some()
+            {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: "uncertainty=bands is car-only and incompatible with exclude/avoid_polygons/weighting other than fastest".into(),
+                    }),
+                )
+                    .into_response();
+            }
+            let Some(pair) = state.band_modes() else {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        error: "uncertainty bands not available: the loaded edge_speeds table has no q25/q75 columns".into(),
+                    }),
+                )
+                    .into_response();
+            };
+            Some(pair)
+        }
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!("unknown uncertainty value '{other}' (expected 'bands')"),
+                }),
+            )
+                .into_response();
+        }
+    };
+    if let Err(e) = check_table_size(origins.len(), destinations.len(), band_modes.is_some()) {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
     }
-    if req.destinations.is_empty() {
+    if destinations.is_empty() {
         return (
             StatusCode::BAD_REQUEST,
             Json(ErrorResponse {
@@ -291,9 +496,22 @@ pub async fn table_post_handler(
     }
     let want_duration = annotations.contains(&"duration") || !annotations.contains(&"distance");
     let want_distance = annotations.contains(&"distance");
+    // Distance-only tables are already shortest-distance; durations along
+    // the shortest path are not precomputed, so shortest is distance-only.
+    if weighting.is_shortest() && want_duration {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error:
+                    "weighting=shortest on /table returns distances only; use annotations=distance"
+                        .into(),
+            }),
+        )
+            .into_response();
+    }
 
     // Parse exclude parameter
-    let exclude_mask = match super::exclude::parse_exclude_option(&req.exclude) {
+    let exclude_mask = match state.parse_exclude(&req.exclude) {
         Ok(m) => m,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
@@ -342,16 +560,6 @@ pub async fn table_post_handler(
         mode_data.mask.clone()
     };
 
-    // Determine custom weights: avoid takes priority, then exclude.
-    // /table holds an Arc<AvoidEntry>; the borrow points into the cache
-    // so /table cache hits avoid the prior ~200 MB deep clone.
-    let custom_weights_ref: Option<&super::exclude::ExcludeWeights> =
-        if let Some(ref entry) = avoid_entry {
-            Some(&entry.weights)
-        } else {
-            exclude_weights.as_deref()
-        };
-
     let radius_param = parse_radius(req.radius_km.as_ref());
 
     let threshold_s = match parse_max_minutes(req.max_minutes) {
@@ -361,132 +569,86 @@ pub async fn table_post_handler(
         }
     };
 
-    let resp = compute_table_bucket_m2m(
-        &state,
-        mode,
-        &req.origins,
-        &req.destinations,
-        want_duration,
-        want_distance,
-        custom_weights_ref,
-        &snap_mask,
-        radius_param,
-        threshold_s,
-    )
-    .await;
+    let fallback = match parse_fallback(req.fallback_speed, req.fallback_coordinate.as_deref()) {
+        Ok(f) => f,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
+        }
+    };
+    // A bounded matrix nulls cells beyond max_minutes on purpose; an
+    // estimate there would undo the bound.
+    if fallback.is_some() && threshold_s.is_some() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "fallback_speed cannot be combined with max_minutes".into(),
+            }),
+        )
+            .into_response();
+    }
 
-    // #521 uncertainty bands: two more full matrix passes on the hidden band
-    // weight sets, merged into the median response as durations_q25/q75
-    // (TIME quantiles: q25 <- fluid q75-speed set). Opt-in only — 3x cost.
-    let resp = match req.uncertainty.as_deref() {
-        None => resp,
-        Some("bands") => {
-            if req.mode != "car" || req.exclude.is_some() || req.avoid_polygons.is_some() {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: "uncertainty=bands is car-only and incompatible with exclude/avoid_polygons".into(),
-                    }),
-                )
-                    .into_response();
-            }
-            let Some((pess, opt)) = state.band_modes() else {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: "uncertainty bands not available: the loaded edge_speeds table has no q25/q75 columns".into(),
-                    }),
-                )
-                    .into_response();
-            };
-            let mut band_grids: Vec<serde_json::Value> = Vec::with_capacity(2);
-            for band in [opt, pess] {
-                let md = state.get_mode(band);
-                let r = compute_table_bucket_m2m(
+    // Custom weights: avoid takes priority, then exclude. /table holds the
+    // cached Arc<AvoidEntry>, so cache hits avoid a ~200 MB deep clone.
+    let resp = match band_modes {
+        None => {
+            compute_table_bucket_m2m(
+                &state,
+                mode,
+                &origins,
+                &destinations,
+                want_duration,
+                want_distance,
+                avoid_entry,
+                exclude_weights,
+                snap_mask,
+                radius_param,
+                threshold_s,
+                fallback,
+            )
+            .await
+        }
+        // Bands stay below TABLE_TILE_CELLS (check_table_size): every pass
+        // is one block whose grids go straight into the response.
+        Some((pess, opt)) => {
+            let (job, mut response) = prepare_table(
+                &state,
+                mode,
+                &origins,
+                &destinations,
+                want_duration,
+                want_distance,
+                None,
+                None,
+                snap_mask,
+                radius_param,
+                threshold_s,
+                fallback,
+            );
+            let (durations, distances, cells) = job.block(0..origins.len());
+            response.durations = durations;
+            response.distances = distances;
+            response.fallback_speed_cells = cells;
+            for (band, slot) in [
+                (opt, &mut response.durations_q25),
+                (pess, &mut response.durations_q75),
+            ] {
+                let (band_job, _) = prepare_table(
                     &state,
                     band,
-                    &req.origins,
-                    &req.destinations,
+                    &origins,
+                    &destinations,
                     true,
                     false,
                     None,
-                    &md.mask,
-                    parse_radius(req.radius_km.as_ref()),
+                    None,
+                    state.get_mode(band).mask.clone(),
+                    radius_param,
                     threshold_s,
-                )
-                .await;
-                let bytes = match axum::body::to_bytes(r.into_body(), 256 * 1024 * 1024).await {
-                    Ok(b) => b,
-                    Err(e) => {
-                        return (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(ErrorResponse {
-                                error: format!("band matrix pass failed: {e}"),
-                            }),
-                        )
-                            .into_response();
-                    }
-                };
-                let v: serde_json::Value = match serde_json::from_slice(&bytes) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        return (
-                            StatusCode::INTERNAL_SERVER_ERROR,
-                            Json(ErrorResponse {
-                                error: format!("band matrix pass returned non-JSON: {e}"),
-                            }),
-                        )
-                            .into_response();
-                    }
-                };
-                band_grids.push(
-                    v.get("durations")
-                        .cloned()
-                        .unwrap_or(serde_json::Value::Null),
+                    fallback,
                 );
+                *slot = band_job.block(0..origins.len()).0;
             }
-            let bytes = match axum::body::to_bytes(resp.into_body(), 256 * 1024 * 1024).await {
-                Ok(b) => b,
-                Err(e) => {
-                    return (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ErrorResponse {
-                            error: format!("median matrix pass failed: {e}"),
-                        }),
-                    )
-                        .into_response();
-                }
-            };
-            match serde_json::from_slice::<serde_json::Value>(&bytes) {
-                Ok(mut v) => {
-                    let q75 = band_grids.pop().unwrap_or(serde_json::Value::Null);
-                    let q25 = band_grids.pop().unwrap_or(serde_json::Value::Null);
-                    if let Some(obj) = v.as_object_mut() {
-                        obj.insert("durations_q25".into(), q25);
-                        obj.insert("durations_q75".into(), q75);
-                    }
-                    Json(v).into_response()
-                }
-                // Median pass errored (4xx body): pass it through untouched.
-                Err(_) => {
-                    return (
-                        StatusCode::BAD_REQUEST,
-                        Json(ErrorResponse {
-                            error: "table computation failed before band merge".into(),
-                        }),
-                    )
-                        .into_response();
-                }
-            }
-        }
-        Some(other) => {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("unknown uncertainty value '{other}' (expected 'bands')"),
-                }),
-            )
-                .into_response();
+            Json(response).into_response()
         }
     };
     super::region_metrics::record_query(
@@ -497,7 +659,47 @@ pub async fn table_post_handler(
     resp
 }
 
+/// Cells above which /table runs the engine in blocks of source rows
+/// (see [`compute_table_bucket_m2m`]). Below it the whole matrix is one
+/// pass; 2000×2000 still fits.
+pub const TABLE_TILE_CELLS: usize = 4_000_000;
+
+/// Largest /table matrix. Above [`TABLE_TILE_CELLS`] it is computed and
+/// streamed block by block, so memory is bounded by one block whatever
+/// the size; the cap bounds the response time. 5000×5000 fits; larger
+/// workloads use the Flight `matrix` action.
+pub const MAX_TABLE_CELLS: usize = 25_000_000;
+
+/// Largest `uncertainty=bands` matrix. Its three passes are held until
+/// the response is written, so bands never take the tiled path.
+pub const MAX_BANDS_TABLE_CELLS: usize = TABLE_TILE_CELLS;
+
+/// Reject a matrix above the cell cap of its kind of request.
+pub fn check_table_size(
+    n_sources: usize,
+    n_destinations: usize,
+    bands: bool,
+) -> Result<(), String> {
+    let cells = n_sources * n_destinations;
+    if bands && cells > MAX_BANDS_TABLE_CELLS {
+        return Err(format!(
+            "matrix too large for uncertainty=bands: {}×{} = {} cells exceeds limit of {}",
+            n_sources, n_destinations, cells, MAX_BANDS_TABLE_CELLS
+        ));
+    }
+    if cells > MAX_TABLE_CELLS {
+        return Err(format!(
+            "matrix too large: {}×{} = {} cells exceeds limit of {}. Use the Flight `matrix` action for larger matrices.",
+            n_sources, n_destinations, cells, MAX_TABLE_CELLS
+        ));
+    }
+    Ok(())
+}
+
 /// Core table computation using bucket M2M algorithm
+///
+/// `avoid_entry` / `exclude_weights` select the custom weights (avoid
+/// takes priority); both `None` routes on the mode's own weights.
 #[allow(clippy::too_many_arguments)]
 pub async fn compute_table_bucket_m2m(
     state: &Arc<ServerState>,
@@ -506,13 +708,84 @@ pub async fn compute_table_bucket_m2m(
     destinations: &[[f64; 2]],
     want_duration: bool,
     want_distance: bool,
-    custom_weights: Option<&super::exclude::ExcludeWeights>,
-    snap_mask: &[u64],
+    avoid_entry: Option<Arc<AvoidEntry>>,
+    exclude_weights: Option<Arc<ExcludeWeights>>,
+    snap_mask: Vec<u64>,
     radius_param: RadiusParam,
     threshold_s: Option<u32>,
+    fallback: Option<TableFallback>,
 ) -> Response {
+    let (job, mut response) = prepare_table(
+        state,
+        mode,
+        sources,
+        destinations,
+        want_duration,
+        want_distance,
+        avoid_entry,
+        exclude_weights,
+        snap_mask,
+        radius_param,
+        threshold_s,
+        fallback,
+    );
+    let n_sources = sources.len();
+    let n_targets = destinations.len();
+
+    if n_sources * n_targets <= TABLE_TILE_CELLS {
+        let (durations, distances, fallback_speed_cells) = job.block(0..n_sources);
+        response.durations = durations;
+        response.distances = distances;
+        response.fallback_speed_cells = fallback_speed_cells;
+        let t_resp = std::time::Instant::now();
+        let resp = Json(response).into_response();
+        tracing::debug!(
+            "compute_table_bucket_m2m: json+into_response took {:?}",
+            t_resp.elapsed()
+        );
+        return resp;
+    }
+
+    // Oversized request: the engine runs over blocks of source rows on a
+    // blocking thread and each block's rows are sent to the client as soon
+    // as they are written, so neither the grids nor the body are ever held
+    // whole (see TableJob::stream_tiled).
+    let rows_per_tile = (TABLE_TILE_CELLS / n_targets).max(1);
+    let (tx, rx) = tokio::sync::mpsc::channel::<Result<bytes::Bytes, std::io::Error>>(2);
+    tokio::task::spawn_blocking(move || job.stream_tiled(rows_per_tile, response, &tx));
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from_stream(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+        ))
+        .unwrap_or_else(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to build streaming response",
+            )
+                .into_response()
+        })
+}
+
+/// Snap a /table request's endpoints. Returns the job that computes its
+/// rows and the response carrying everything but the grids.
+#[allow(clippy::too_many_arguments)]
+fn prepare_table(
+    state: &Arc<ServerState>,
+    mode: Mode,
+    sources: &[[f64; 2]],
+    destinations: &[[f64; 2]],
+    want_duration: bool,
+    want_distance: bool,
+    avoid_entry: Option<Arc<AvoidEntry>>,
+    exclude_weights: Option<Arc<ExcludeWeights>>,
+    snap_mask: Vec<u64>,
+    radius_param: RadiusParam,
+    threshold_s: Option<u32>,
+    fallback: Option<TableFallback>,
+) -> (TableJob, TableResponse) {
     let mode_data = state.get_mode(mode);
-    let n_nodes = mode_data.cch_topo.n_nodes as usize;
 
     // K-best snap with the directional #197 role filter. Use the same
     // primary that /route uses, so the matrix and routes agree on every
@@ -534,9 +807,6 @@ pub async fn compute_table_bucket_m2m(
     const SNAP_K: usize = 64;
     let _ = SNAP_K; // referenced from apply_k_best_fallback's docs
 
-    let src_role_filter = SnapRole::Src.role_filter(&mode_data);
-    let dst_role_filter = SnapRole::Dst.role_filter(&mode_data);
-
     let t_pre = std::time::Instant::now();
 
     // (rank, snapped, valid). Per-row candidate list is built lazily on
@@ -548,7 +818,7 @@ pub async fn compute_table_bucket_m2m(
     // equidistant physical edges (K=4 snap) so the search picks the
     // departure/arrival direction; exclude/avoid keep the single-seed
     // legacy (their custom weight vectors aren't reflected in seed costs).
-    let phantom_ok = custom_weights.is_none();
+    let phantom_ok = avoid_entry.is_none() && exclude_weights.is_none();
     let snap_endpoint = |lon: f64, lat: f64, role: super::types::SnapRole| -> SnapResult {
         if phantom_ok {
             let k = state.snap_index.snap_k_with_info_filtered_role(
@@ -556,7 +826,7 @@ pub async fn compute_table_bucket_m2m(
                 lat,
                 mode.0,
                 8,
-                Some(snap_mask),
+                Some(&snap_mask),
                 role.role_filter(&mode_data),
             );
             if let Some(pe) = super::phantom::phantom_from_candidates(
@@ -566,7 +836,7 @@ pub async fn compute_table_bucket_m2m(
                 lon,
                 lat,
                 role,
-                Some(snap_mask),
+                Some(&snap_mask),
             ) {
                 let seeds: Vec<(u32, u32, u32, bool)> = pe
                     .seeds
@@ -586,7 +856,7 @@ pub async fn compute_table_bucket_m2m(
             lon,
             lat,
             mode.0,
-            Some(snap_mask),
+            Some(&snap_mask),
             role.role_filter(&mode_data),
         ) {
             let rank = mode_data.orig_to_rank[orig_id as usize];
@@ -659,8 +929,6 @@ pub async fn compute_table_bucket_m2m(
         }
     };
 
-    let n_sources = sources.len();
-    let n_targets = destinations.len();
     tracing::debug!(
         "compute_table_bucket_m2m: snap+rebuild took {:?} n_src={} n_tgt={}",
         t_pre.elapsed(),
@@ -668,6 +936,235 @@ pub async fn compute_table_bucket_m2m(
         destinations.len()
     );
 
+    let snap_status = |input: &[[f64; 2]], snapped: &[(f64, f64)], valid: &[bool]| {
+        input
+            .iter()
+            .zip(snapped)
+            .zip(valid)
+            .map(|((&[lon, lat], &(slon, slat)), &ok)| SnapStatus {
+                snapped: ok,
+                snap_distance: ok.then(|| {
+                    (crate::nbg::haversine_distance(lat, lon, slat, slon) * 10.0).round() / 10.0
+                }),
+            })
+            .collect::<Vec<_>>()
+    };
+    let response = TableResponse {
+        code: "Ok".into(),
+        durations: None,
+        distances: None,
+        origins_status: Some(snap_status(sources, &sources_snapped, &source_valid)),
+        destinations_status: Some(snap_status(destinations, &targets_snapped, &target_valid)),
+        origins: Some(source_waypoints),
+        destinations: Some(dest_waypoints),
+        fallback_speed_cells: None,
+        durations_q25: None,
+        durations_q75: None,
+    };
+
+    let job = TableJob {
+        state: Arc::clone(state),
+        mode,
+        sources: sources.to_vec(),
+        destinations: destinations.to_vec(),
+        src_seedsets,
+        tgt_seedsets,
+        source_valid,
+        target_valid,
+        sources_snapped,
+        targets_snapped,
+        neighbor_mask,
+        want_duration,
+        want_distance,
+        avoid_entry,
+        exclude_weights,
+        snap_mask,
+        threshold_s,
+        fallback,
+    };
+    (job, response)
+}
+
+/// A snapped /table request: everything a block of source rows needs,
+/// owned so the tiled path can run it on a blocking thread.
+struct TableJob {
+    state: Arc<ServerState>,
+    mode: Mode,
+    sources: Vec<[f64; 2]>,
+    destinations: Vec<[f64; 2]>,
+    src_seedsets: Vec<Vec<(u32, u32, u32, bool)>>,
+    tgt_seedsets: Vec<Vec<(u32, u32, u32, bool)>>,
+    source_valid: Vec<bool>,
+    target_valid: Vec<bool>,
+    sources_snapped: Vec<(f64, f64)>,
+    targets_snapped: Vec<(f64, f64)>,
+    neighbor_mask: Option<Vec<Vec<u32>>>,
+    want_duration: bool,
+    want_distance: bool,
+    avoid_entry: Option<Arc<AvoidEntry>>,
+    exclude_weights: Option<Arc<ExcludeWeights>>,
+    snap_mask: Vec<u64>,
+    threshold_s: Option<u32>,
+    fallback: Option<TableFallback>,
+}
+
+impl TableJob {
+    /// [`compute_table_block`] for the source rows `rows`.
+    fn block(
+        &self,
+        rows: std::ops::Range<usize>,
+    ) -> (MatrixGrid, MatrixGrid, Option<Vec<[usize; 2]>>) {
+        let mode_data = self.state.get_mode(self.mode);
+        let custom_weights = match &self.avoid_entry {
+            Some(entry) => Some(&entry.weights),
+            None => self.exclude_weights.as_deref(),
+        };
+        compute_table_block(
+            &self.state,
+            &mode_data,
+            self.mode,
+            mode_data.cch_topo.n_nodes as usize,
+            &self.sources[rows.clone()],
+            &self.destinations,
+            &self.src_seedsets[rows.clone()],
+            &self.tgt_seedsets,
+            &self.source_valid[rows.clone()],
+            &self.target_valid,
+            &self.sources_snapped[rows.clone()],
+            &self.targets_snapped,
+            self.neighbor_mask.as_deref().map(|nm| &nm[rows]),
+            self.want_duration,
+            self.want_distance,
+            custom_weights,
+            &self.snap_mask,
+            SnapRole::Src.role_filter(&mode_data),
+            SnapRole::Dst.role_filter(&mode_data),
+            self.threshold_s,
+            self.fallback,
+        )
+    }
+
+    /// Write the response of an oversized request into `tx`, one block of
+    /// `rows_per_tile` source rows at a time. Durations and distances are
+    /// separate passes over the blocks, so a block's distances are never
+    /// held while the durations array is still open; with both requested
+    /// each pass runs the block as a whole (a distance cell stays the
+    /// length of the same route as in the single-pass response). Stops
+    /// early when the client goes away.
+    fn stream_tiled(
+        &self,
+        rows_per_tile: usize,
+        mut response: TableResponse,
+        tx: &tokio::sync::mpsc::Sender<Result<bytes::Bytes, std::io::Error>>,
+    ) {
+        let send = |buf: Vec<u8>| tx.blocking_send(Ok(bytes::Bytes::from(buf))).is_ok();
+        let n_sources = self.sources.len();
+        let t_tiles = std::time::Instant::now();
+        let mut fallback_speed_cells = self.fallback.map(|_| Vec::new());
+        let mut cells_done = false;
+        let mut head = b"{".to_vec();
+        for (key, wanted) in [
+            ("durations", self.want_duration),
+            ("distances", self.want_distance),
+        ] {
+            if !wanted {
+                continue;
+            }
+            head.extend_from_slice(format!("\"{key}\":[").as_bytes());
+            if !send(std::mem::take(&mut head)) {
+                return;
+            }
+            let mut written = 0usize;
+            let mut start = 0usize;
+            while start < n_sources {
+                let end = (start + rows_per_tile).min(n_sources);
+                let (durations, distances, cells) = self.block(start..end);
+                let grid = if key == "durations" {
+                    durations
+                } else {
+                    distances
+                };
+                let mut buf = Vec::new();
+                if let Some(grid) = grid {
+                    write_json_rows(&mut buf, &grid, &mut written);
+                }
+                if !cells_done
+                    && let (Some(all), Some(cells)) = (fallback_speed_cells.as_mut(), cells)
+                {
+                    all.extend(cells.into_iter().map(|[i, j]| [start + i, j]));
+                }
+                if !send(buf) {
+                    return;
+                }
+                start = end;
+            }
+            cells_done = true;
+            head.extend_from_slice(b"],");
+        }
+        response.fallback_speed_cells = fallback_speed_cells;
+        // The rest of the object ("code", waypoints, ...) minus its `{`.
+        match serde_json::to_vec(&response) {
+            Ok(rest) => {
+                head.extend_from_slice(&rest[1..]);
+                send(head);
+            }
+            Err(e) => {
+                let _ = tx.blocking_send(Err(std::io::Error::other(format!(
+                    "failed to serialise table response: {e}"
+                ))));
+            }
+        }
+        tracing::debug!(
+            "compute_table_bucket_m2m: {} tiles of {} rows took {:?}",
+            n_sources.div_ceil(rows_per_tile),
+            rows_per_tile,
+            t_tiles.elapsed()
+        );
+    }
+}
+
+/// Append `grid`'s rows to a JSON array being written tile by tile;
+/// `written` counts the rows so far, for the separators.
+fn write_json_rows(out: &mut Vec<u8>, grid: &[Vec<Option<f64>>], written: &mut usize) {
+    for row in grid {
+        if *written > 0 {
+            out.push(b',');
+        }
+        serde_json::to_writer(&mut *out, row).expect("writing JSON into a Vec cannot fail");
+        *written += 1;
+    }
+}
+
+/// Engine pass, K-best rescue, `max_minutes` mask and `fallback_speed`
+/// fill for a block of source rows against every destination. Returns the
+/// requested grids and the block-relative fallback cells.
+#[allow(clippy::too_many_arguments)]
+fn compute_table_block(
+    state: &ServerState,
+    mode_data: &super::state::ModeData,
+    mode: Mode,
+    n_nodes: usize,
+    sources: &[[f64; 2]],
+    destinations: &[[f64; 2]],
+    src_seedsets: &[Vec<(u32, u32, u32, bool)>],
+    tgt_seedsets: &[Vec<(u32, u32, u32, bool)>],
+    source_valid: &[bool],
+    target_valid: &[bool],
+    sources_snapped: &[(f64, f64)],
+    targets_snapped: &[(f64, f64)],
+    neighbor_mask: Option<&[Vec<u32>]>,
+    want_duration: bool,
+    want_distance: bool,
+    custom_weights: Option<&super::exclude::ExcludeWeights>,
+    snap_mask: &[u64],
+    src_role_filter: Option<&[u64]>,
+    dst_role_filter: Option<&[u64]>,
+    threshold_s: Option<u32>,
+    fallback: Option<TableFallback>,
+) -> (MatrixGrid, MatrixGrid, Option<Vec<[usize; 2]>>) {
+    let n_sources = sources.len();
+    let n_targets = destinations.len();
+
     // Select flat adjacencies based on custom weights (exclude or avoid)
     let (time_up, time_down) = if let Some(cw) = custom_weights {
         (&cw.time_up_flat, &cw.time_down_flat)
@@ -760,8 +1257,8 @@ pub async fn compute_table_bucket_m2m(
             up_lat,
             dn_lat,
             phast_ctx2,
-            &src_seedsets,
-            &tgt_seedsets,
+            src_seedsets,
+            tgt_seedsets,
             threshold,
         );
         tracing::debug!(
@@ -772,18 +1269,18 @@ pub async fn compute_table_bucket_m2m(
             &time_mat,
             n_sources,
             n_targets,
-            &source_valid,
-            &target_valid,
-            neighbor_mask.as_deref(),
+            source_valid,
+            target_valid,
+            neighbor_mask,
             |v| v as f64,
         );
         let dist = flat_matrix_to_2d(
             &lat_mat,
             n_sources,
             n_targets,
-            &source_valid,
-            &target_valid,
-            neighbor_mask.as_deref(),
+            source_valid,
+            target_valid,
+            neighbor_mask,
             |v| v as f64,
         );
         (Some(dur), Some(dist))
@@ -805,8 +1302,8 @@ pub async fn compute_table_bucket_m2m(
                 time_up,
                 time_down,
                 phast_ctx,
-                &src_seedsets,
-                &tgt_seedsets,
+                src_seedsets,
+                tgt_seedsets,
                 threshold,
             );
             tracing::debug!(
@@ -834,8 +1331,8 @@ pub async fn compute_table_bucket_m2m(
                 n_nodes,
                 dist_up,
                 dist_down,
-                &swap(&src_seedsets),
-                &swap(&tgt_seedsets),
+                &swap(src_seedsets),
+                &swap(tgt_seedsets),
                 u32::MAX,
             );
             tracing::debug!(
@@ -846,9 +1343,9 @@ pub async fn compute_table_bucket_m2m(
                 &matrix,
                 n_sources,
                 n_targets,
-                &source_valid,
-                &target_valid,
-                neighbor_mask.as_deref(),
+                source_valid,
+                target_valid,
+                neighbor_mask,
                 |v| v as f64,
             ))
         } else {
@@ -861,9 +1358,9 @@ pub async fn compute_table_bucket_m2m(
                 &tm,
                 n_sources,
                 n_targets,
-                &source_valid,
-                &target_valid,
-                neighbor_mask.as_deref(),
+                source_valid,
+                target_valid,
+                neighbor_mask,
                 |v| v as f64,
             ))
         } else {
@@ -873,7 +1370,6 @@ pub async fn compute_table_bucket_m2m(
     };
 
     let t_post_m2m = std::time::Instant::now();
-    let _ = t_post_m2m;
 
     // Per-cell K-best fallback (#197 matrix gap).
     //
@@ -903,14 +1399,14 @@ pub async fn compute_table_bucket_m2m(
     } else {
         apply_k_best_fallback(
             state,
-            &mode_data,
+            mode_data,
             mode,
             durations,
             distances,
             sources,
             destinations,
-            &source_valid,
-            &target_valid,
+            source_valid,
+            target_valid,
             snap_mask,
             src_role_filter,
             dst_role_filter,
@@ -953,32 +1449,72 @@ pub async fn compute_table_bucket_m2m(
         durations = None;
     }
 
-    tracing::debug!(
-        "compute_table_bucket_m2m: post-m2m to response took {:?}",
-        t_post_m2m.elapsed()
-    );
+    let fallback_speed_cells = fallback.map(|fb| {
+        let (from, to) = if fb.snapped {
+            (sources_snapped.to_vec(), targets_snapped.to_vec())
+        } else {
+            (
+                sources.iter().map(|&[lon, lat]| (lon, lat)).collect(),
+                destinations.iter().map(|&[lon, lat]| (lon, lat)).collect(),
+            )
+        };
+        fill_fallback_cells(
+            &mut durations,
+            &mut distances,
+            &from,
+            &to,
+            neighbor_mask,
+            fb.speed,
+        )
+    });
 
-    let t_resp = std::time::Instant::now();
-    let resp = Json(TableResponse {
-        code: "Ok".into(),
-        durations,
-        distances,
-        origins: Some(source_waypoints),
-        destinations: Some(dest_waypoints),
-        durations_q25: None,
-        durations_q75: None,
-    })
-    .into_response();
     tracing::debug!(
-        "compute_table_bucket_m2m: json+into_response took {:?}",
-        t_resp.elapsed()
+        "compute_table_bucket_m2m: post-m2m took {:?}",
+        t_post_m2m.elapsed()
     );
-    resp
+    (durations, distances, fallback_speed_cells)
 }
 
 /// 2D matrix of Option<f64> — None for unreachable/invalid cells.
 type MatrixGrid = Option<Vec<Vec<Option<f64>>>>;
 
+/// Fill every null cell of the requested grids with the straight-line
+/// distance between `from[i]` and `to[j]` (metres) and that distance at
+/// `speed` m/s (seconds). Pairs `radius_km` pruned stay null: they were
+/// excluded, not unreachable. Returns the filled [i, j] cells, row-major.
+fn fill_fallback_cells(
+    durations: &mut MatrixGrid,
+    distances: &mut MatrixGrid,
+    from: &[(f64, f64)],
+    to: &[(f64, f64)],
+    neighbor_mask: Option<&[Vec<u32>]>,
+    speed: f64,
+) -> Vec<[usize; 2]> {
+    let mut cells = Vec::new();
+    for (i, &(slon, slat)) in from.iter().enumerate() {
+        let neighbors = neighbor_mask.map(|nm| nm[i].as_slice());
+        for (j, &(tlon, tlat)) in to.iter().enumerate() {
+            if neighbors.is_some_and(|ns| ns.binary_search(&(j as u32)).is_err()) {
+                continue;
+            }
+            let mut filled = false;
+            let meters = crate::nbg::haversine_distance(slat, slon, tlat, tlon);
+            for (grid, value) in [(&mut *durations, meters / speed), (&mut *distances, meters)] {
+                if let Some(g) = grid.as_mut()
+                    && g[i][j].is_none()
+                {
+                    g[i][j] = Some(value.round());
+                    filled = true;
+                }
+            }
+            if filled {
+                cells.push([i, j]);
+            }
+        }
+    }
+    cells
+}
+
 /// For each cell where bucket-M2M returned None (unreachable under the
 /// primary src/dst snap pair), retry with the K-best candidate combo
 /// enumeration — the same fallback /route uses for #197.
@@ -1441,8 +1977,18 @@ pub async fn table_stream_handler(
             }
         };
 
-    let mode = match parse_mode(&req.mode, &state.mode_lookup) {
-        Ok(m) => m,
+    let mode = match resolve_weighting(&req.mode, req.weighting.as_deref(), &state.mode_lookup) {
+        Ok((_, Weighting::Shortest)) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: "weighting=shortest is not supported by /table/stream (durations only)"
+                        .into(),
+                }),
+            )
+                .into_response();
+        }
+        Ok((m, _)) => m,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
         }
@@ -1463,7 +2009,7 @@ pub async fn table_stream_handler(
     // The only per-request allocation is the rank vectors (4 bytes per coordinate).
 
     // Parse exclude parameter
-    let exclude_mask = match super::exclude::parse_exclude_option(&req.exclude) {
+    let exclude_mask = match state.parse_exclude(&req.exclude) {
         Ok(m) => m,
         Err(e) => {
             return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response();
@@ -2087,3 +2633,123 @@ mod max_minutes_tests {
         assert_eq!(parse_max_minutes(Some(1440.0)).unwrap(), Some(86400));
     }
 }
+
+#[cfg(test)]
+mod table_points_tests {
+    use super::{TablePoints, TablePostRequest, resolve_table_points};
+
+    fn resolve(body: &str) -> Result<TablePoints, String> {
+        let req: TablePostRequest = serde_json::from_str(body).unwrap();
+        resolve_table_points(&req)
+    }
+
+    #[test]
+    fn explicit_coordinates_pass_through() {
+        let (o, d) = resolve(
+            r#"{"origins":[[4.35,50.85]],"destinations":[[4.4,51.2],[4.5,51.0]],"mode":"car"}"#,
+        )
+        .unwrap();
+        assert_eq!(o, vec![[4.35, 50.85]]);
+        assert_eq!(d, vec![[4.4, 51.2], [4.5, 51.0]]);
+    }
+
+    #[test]
+    fn indices_select_from_coordinates() {
+        let coords = r#""coordinates":[[4.0,50.0],[4.1,50.1],[4.2,50.2]],"mode":"car""#;
+        let (o, d) = resolve(&format!(
+            r#"{{{coords},"sources":[2,0],"destinations":[1,1]}}"#
+        ))
+        .unwrap();
+        assert_eq!(o, vec![[4.2, 50.2], [4.0, 50.0]]);
+        assert_eq!(d, vec![[4.1, 50.1], [4.1, 50.1]]);
+        // Either side defaults to every coordinate.
+        let (o, d) = resolve(&format!(r#"{{{coords},"destinations":[1]}}"#)).unwrap();
+        assert_eq!((o.len(), d.len()), (3, 1));
+        let (o, d) = resolve(&format!("{{{coords}}}")).unwrap();
+        assert_eq!((o.len(), d.len()), (3, 3));
+    }
+
+    #[test]
+    fn rejects_bad_index_combinations() {
+        let coords = r#""coordinates":[[4.0,50.0],[4.1,50.1]],"mode":"car""#;
+        let err = resolve(&format!(r#"{{{coords},"sources":[0,2]}}"#)).unwrap_err();
+        assert_eq!(err, "sources[1] = 2 is out of range for 2 coordinates");
+        assert!(resolve(&format!(r#"{{{coords},"origins":[[4.0,50.0]]}}"#)).is_err());
+        assert!(resolve(&format!(r#"{{{coords},"destinations":[[4.0,50.0]]}}"#)).is_err());
+        assert!(resolve(r#"{"origins":[[4.0,50.0]],"sources":[0],"mode":"car"}"#).is_err());
+        assert!(resolve(r#"{"origins":[[4.0,50.0]],"destinations":[0],"mode":"car"}"#).is_err());
+    }
+}
+
+#[cfg(test)]
+mod fallback_tests {
+    use super::{TableFallback, fill_fallback_cells, parse_fallback};
+
+    #[test]
+    fn parses_speed_and_coordinate() {
+        assert_eq!(parse_fallback(None, None), Ok(None));
+        assert_eq!(
+            parse_fallback(Some(10.0), Some("snapped")),
+            Ok(Some(TableFallback {
+                speed: 10.0,
+                snapped: true
+            }))
+        );
+        assert!(!parse_fallback(Some(10.0), None).unwrap().unwrap().snapped);
+        assert!(parse_fallback(None, Some("input")).is_err());
+        assert!(parse_fallback(Some(0.0), None).is_err());
+        assert!(parse_fallback(Some(10.0), Some("nearest")).is_err());
+    }
+
+    #[test]
+    fn fills_only_null_unpruned_cells() {
+        // Two sources 0.01° of latitude (≈1112 m) from the destination.
+        let from = [(4.0, 50.0), (4.0, 50.02)];
+        let to = [(4.0, 50.01)];
+        let mut durations = Some(vec![vec![Some(60.0)], vec![None]]);
+        let mut distances = Some(vec![vec![Some(1500.0)], vec![None]]);
+        let cells = fill_fallback_cells(&mut durations, &mut distances, &from, &to, None, 10.0);
+        assert_eq!(cells, vec![[1, 0]]);
+        assert_eq!(durations.as_ref().unwrap()[0][0], Some(60.0));
+        assert_eq!(durations.unwrap()[1][0], Some(111.0));
+        assert_eq!(distances.unwrap()[1][0], Some(1112.0));
+
+        // A radius-pruned pair is excluded, not unreachable.
+        let mut durations = Some(vec![vec![None], vec![None]]);
+        let pruned: Vec<Vec<u32>> = vec![vec![0], vec![]];
+        let cells = fill_fallback_cells(&mut durations, &mut None, &from, &to, Some(&pruned), 10.0);
+        assert_eq!(cells, vec![[0, 0]]);
+        assert_eq!(durations.unwrap()[1][0], None);
+    }
+}
+
+#[cfg(test)]
+mod tiling_tests {
+    use super::{MAX_BANDS_TABLE_CELLS, MAX_TABLE_CELLS, check_table_size, write_json_rows};
+
+    #[test]
+    fn bands_have_their_own_cell_cap() {
+        assert!(check_table_size(5000, 5000, false).is_ok());
+        assert!(check_table_size(5001, 5000, false).is_err());
+        assert!(check_table_size(2000, 2000, true).is_ok());
+        let e = check_table_size(2001, 2000, true).unwrap_err();
+        assert!(e.contains("uncertainty=bands"), "{e}");
+        const { assert!(MAX_BANDS_TABLE_CELLS < MAX_TABLE_CELLS) };
+    }
+
+    #[test]
+    fn tiled_rows_serialise_like_the_whole_grid() {
+        let grid = vec![
+            vec![Some(0.0), None],
+            vec![Some(12.5), Some(3.0)],
+            vec![None, None],
+        ];
+        let mut out = b"[".to_vec();
+        let mut written = 0;
+        write_json_rows(&mut out, &grid[..2], &mut written);
+        write_json_rows(&mut out, &grid[2..], &mut written);
+        out.push(b']');
+        assert_eq!(written, 3);
+        assert_eq!(out, serde_json::to_vec(&grid).unwrap());
+    }
+}
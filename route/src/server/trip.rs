@@ -129,6 +129,90 @@ pub fn solve_tsp(matrix: &[u32], n: usize, round_trip: bool) -> TspSolution {
     }
 }
 
+/// Which ends of a trip are pinned to the waypoint list
+/// (`source=first`, `destination=last`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TripEnds {
+    /// The trip departs from waypoint 0
+    pub first: bool,
+    /// The trip arrives at waypoint n-1 (for a round trip: the last
+    /// waypoint visited before returning)
+    pub last: bool,
+}
+
+/// Leg cost that keeps the solver off an edge a pinned end rules out.
+/// Below the unreachable sentinel, so a forbidden leg is preferred over an
+/// unreachable one; [`pin_ends`] restores the ends in that case.
+const FORBIDDEN_LEG: u32 = u32::MAX - 1;
+
+/// Solve a trip whose start and/or end may be pinned.
+///
+/// Round trips are plain cycles: a pinned last waypoint only rotates the
+/// tour, unless the first is pinned too, in which case the leg n-1 → 0 is
+/// forced. Open trips other than "fixed start, free end" are solved as a
+/// round trip through a dummy waypoint joined at zero cost to every
+/// allowed start and end; cutting the cycle at the dummy gives the path.
+pub fn solve_trip(matrix: &[u32], n: usize, round_trip: bool, ends: TripEnds) -> TspSolution {
+    if n <= 1 || (!ends.last && (round_trip || ends.first)) {
+        return solve_tsp(matrix, n, round_trip);
+    }
+
+    let mut solution = if round_trip {
+        if ends.first {
+            let mut forced = matrix.to_vec();
+            for j in 1..n - 1 {
+                forced[(n - 1) * n + j] = FORBIDDEN_LEG;
+            }
+            solve_tsp(&forced, n, true)
+        } else {
+            let mut solution = solve_tsp(matrix, n, true);
+            if let Some(pos) = solution.order.iter().position(|&w| w == n - 1) {
+                solution.order.rotate_left(pos + 1);
+            }
+            solution
+        }
+    } else {
+        let dummy = n;
+        let m = n + 1;
+        let mut open = vec![0u32; m * m];
+        for i in 0..n {
+            open[i * m..i * m + n].copy_from_slice(&matrix[i * n..(i + 1) * n]);
+            if ends.first && i != 0 {
+                open[dummy * m + i] = FORBIDDEN_LEG;
+            }
+            if ends.last && i != n - 1 {
+                open[i * m + dummy] = FORBIDDEN_LEG;
+            }
+        }
+        let mut solution = solve_tsp(&open, m, true);
+        if let Some(pos) = solution.order.iter().position(|&w| w == dummy) {
+            solution.order.rotate_left(pos);
+        }
+        solution.order.remove(0);
+        solution
+    };
+
+    pin_ends(&mut solution.order, n, ends);
+    solution.total_cost = tour_cost(matrix, n, &solution.order, round_trip);
+    solution
+}
+
+/// Move waypoint 0 to the front and/or waypoint n-1 to the back of `order`.
+fn pin_ends(order: &mut Vec<usize>, n: usize, ends: TripEnds) {
+    if ends.first
+        && let Some(pos) = order.iter().position(|&w| w == 0)
+    {
+        let w = order.remove(pos);
+        order.insert(0, w);
+    }
+    if ends.last
+        && let Some(pos) = order.iter().position(|&w| w == n - 1)
+    {
+        let w = order.remove(pos);
+        order.push(w);
+    }
+}
+
 /// Look up cost from waypoint i to waypoint j in the flat matrix.
 /// Returns u64::MAX if the raw value is u32::MAX (unreachable).
 #[inline]
@@ -351,10 +435,21 @@ pub struct TripRequest {
     #[serde(default = "default_mode")]
     #[schema(example = "car")]
     pub mode: String,
-    /// Whether to return to start (default: true)
-    #[serde(default = "default_true")]
+    /// Whether to return to start (default: true). `roundtrip` is accepted
+    /// as an alias.
+    #[serde(default = "default_true", alias = "roundtrip")]
     #[schema(example = true)]
     pub round_trip: bool,
+    /// Departure waypoint: "first" (default) departs from the first
+    /// waypoint, "any" lets an open trip depart from any of them
+    #[serde(default)]
+    #[schema(example = "first")]
+    pub source: Option<String>,
+    /// Arrival waypoint: "any" (default) or "last" to end the trip at the
+    /// last waypoint (before the return leg of a round trip)
+    #[serde(default)]
+    #[schema(example = "last")]
+    pub destination: Option<String>,
     /// Annotations to return: "duration" (default), "distance", "duration,distance"
     #[serde(default = "default_annotations")]
     #[schema(example = "duration,distance")]
@@ -376,6 +471,31 @@ fn default_mode() -> String {
     "car".to_string()
 }
 
+/// Resolve `source` / `destination` into the trip's pinned ends.
+pub fn trip_ends(req: &TripRequest) -> Result<TripEnds, String> {
+    let first = match req.source.as_deref() {
+        None | Some("first") => true,
+        Some("any") => false,
+        Some(other) => {
+            return Err(format!(
+                "Invalid source '{}' (expected 'any' or 'first')",
+                other
+            ));
+        }
+    };
+    let last = match req.destination.as_deref() {
+        None | Some("any") => false,
+        Some("last") => true,
+        Some(other) => {
+            return Err(format!(
+                "Invalid destination '{}' (expected 'any' or 'last')",
+                other
+            ));
+        }
+    };
+    Ok(TripEnds { first, last })
+}
+
 fn default_true() -> bool {
     true
 }
@@ -456,7 +576,7 @@ pub struct TripLeg {
     path = "/trip",
     tag = "Routing",
     summary = "Optimize waypoint visiting order (TSP)",
-    description = "Takes 2-100 waypoints and returns the optimized visiting order that minimizes total travel time.\n\nAlgorithm: multi-start nearest-neighbor greedy + 2-opt + or-opt local search on an N×N duration matrix.\n\nSet `round_trip: false` for open-jaw trips (no return to start).",
+    description = "Takes 2-100 waypoints and returns the optimized visiting order that minimizes total travel time.\n\nAlgorithm: multi-start nearest-neighbor greedy + 2-opt + or-opt local search on an N×N duration matrix.\n\nSet `round_trip: false` for open-jaw trips (no return to start). `source: \"first\"` (default) / `\"any\"` and `destination: \"any\"` (default) / `\"last\"` pin the trip's ends, e.g. start at a depot and end at home.",
     request_body(content = TripRequest, description = "Waypoints, mode, and options",
         example = json!({
             "points": [[4.3517, 50.8503], [4.4017, 50.8603], [4.3817, 50.8403], [4.3317, 50.8303]],
@@ -578,7 +698,7 @@ pub async fn trip_handler(
     let want_distance = annotations.contains(&"distance");
 
     // Parse exclude parameter
-    let exclude_mask = match super::exclude::parse_exclude_option(&req.exclude) {
+    let exclude_mask = match state.parse_exclude(&req.exclude) {
         Ok(m) => m,
         Err(e) => {
             return (
@@ -646,6 +766,17 @@ pub async fn trip_handler(
         None
     };
 
+    let ends = match trip_ends(&req) {
+        Ok(ends) => ends,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "code": "InvalidValue", "message": e })),
+            )
+                .into_response();
+        }
+    };
+
     // Extract owned values before the spawn_blocking closure
     let coordinates = req.points;
     let round_trip = req.round_trip;
@@ -1083,7 +1214,7 @@ pub async fn trip_handler(
         }
 
         // Run TSP solver on the (now-patched) duration matrix
-        let tsp_result = solve_tsp(&duration_matrix, n, round_trip);
+        let tsp_result = solve_trip(&duration_matrix, n, round_trip, ends);
 
         // Build legs from the optimized order
         let order = &tsp_result.order;
@@ -1396,6 +1527,73 @@ mod tests {
         assert!(round.total_cost >= open.total_cost);
     }
 
+    #[test]
+    fn test_solve_trip_pinned_ends() {
+        // Symmetric line 0 - 2 - 1 - 3 with waypoint 3 as "home":
+        //   d(0,2) = 1, d(2,1) = 1, d(1,3) = 1, other pairs by line distance.
+        let matrix = make_matrix(&[&[0, 2, 1, 3], &[2, 0, 1, 1], &[1, 1, 0, 2], &[3, 1, 2, 0]]);
+        let ends = |first, last| TripEnds { first, last };
+
+        // Depot first, home last.
+        let r = solve_trip(&matrix, 4, false, ends(true, true));
+        assert_eq!(r.order, vec![0, 2, 1, 3]);
+        assert_eq!(r.total_cost, 3);
+
+        // Fixed end only: the path may start anywhere but ends at 3.
+        let r = solve_trip(&matrix, 4, false, ends(false, true));
+        assert_eq!(*r.order.last().unwrap(), 3);
+        assert_eq!(r.total_cost, 3);
+
+        // Free ends: cheapest Hamiltonian path, either direction.
+        let r = solve_trip(&matrix, 4, false, ends(false, false));
+        assert_eq!(r.total_cost, 3);
+        let mut sorted = r.order.clone();
+        sorted.sort();
+        assert_eq!(sorted, vec![0, 1, 2, 3]);
+
+        // Fixed start, free end keeps the historical open-path solver.
+        let r = solve_trip(&matrix, 4, false, ends(true, false));
+        assert_eq!(r.order, solve_tsp(&matrix, 4, false).order);
+
+        // Round trip ending at 3: rotated so 3 is visited last.
+        let r = solve_trip(&matrix, 4, true, ends(false, true));
+        assert_eq!(*r.order.last().unwrap(), 3);
+        assert_eq!(r.total_cost, solve_tsp(&matrix, 4, true).total_cost);
+    }
+
+    #[test]
+    fn test_solve_trip_round_trip_forces_last_to_first() {
+        // 3 -> 0 is expensive, so the unconstrained cycle avoids it.
+        let matrix = make_matrix(&[&[0, 1, 5, 5], &[5, 0, 1, 5], &[5, 5, 0, 1], &[50, 5, 5, 0]]);
+        let free = solve_tsp(&matrix, 4, true);
+        let pinned = solve_trip(
+            &matrix,
+            4,
+            true,
+            TripEnds {
+                first: true,
+                last: true,
+            },
+        );
+        assert_eq!(pinned.order, vec![0, 1, 2, 3]);
+        assert_eq!(pinned.total_cost, 53);
+        assert!(free.total_cost < pinned.total_cost);
+
+        // Two waypoints, open, both ends pinned: the only path.
+        let two = make_matrix(&[&[0, 7], &[3, 0]]);
+        let r = solve_trip(
+            &two,
+            2,
+            false,
+            TripEnds {
+                first: true,
+                last: true,
+            },
+        );
+        assert_eq!(r.order, vec![0, 1]);
+        assert_eq!(r.total_cost, 7);
+    }
+
     #[test]
     fn test_tsp_unreachable() {
         // Some pairs are unreachable (u32::MAX).
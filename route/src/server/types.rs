@@ -63,6 +63,49 @@ impl SnapRole {
     }
 }
 
+/// Side of the road a waypoint is approached from (`approaches=`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Approach {
+    /// Either direction of the snapped road.
+    #[default]
+    Unrestricted,
+    /// Arrive/depart with the waypoint on the curb side, so a delivery
+    /// stops on the kerb of the address instead of across the road.
+    Curb,
+}
+
+impl Approach {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim() {
+            "" | "unrestricted" => Ok(Approach::Unrestricted),
+            "curb" => Ok(Approach::Curb),
+            other => Err(format!(
+                "Invalid approach '{}'. Expected 'curb' or 'unrestricted'.",
+                other
+            )),
+        }
+    }
+}
+
+/// Which side of the road traffic drives on, read from
+/// `BUTTERFLY_DRIVING_SIDE` (`right` default, `left` for e.g. the UK,
+/// Ireland or Japan). Decides where the curb is for [`Approach::Curb`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrivingSide {
+    Right,
+    Left,
+}
+
+impl DrivingSide {
+    pub fn from_env() -> Self {
+        match std::env::var("BUTTERFLY_DRIVING_SIDE").as_deref() {
+            Ok("left") => DrivingSide::Left,
+            _ => DrivingSide::Right,
+        }
+    }
+}
+
 /// A waypoint with snapped location (used by table and trip responses)
 #[derive(Debug, Serialize, ToSchema)]
 pub struct Waypoint {
@@ -92,6 +135,65 @@ pub fn validate_coord(lon: f64, lat: f64, label: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Parse `bearings=angle,range;angle,range;...` into one entry per
+/// coordinate. An empty slot (`;;`) is `None` — no constraint on that
+/// coordinate. Angle is 0-360 (0 = North, clockwise), range 0-180.
+pub fn parse_bearings(s: &str) -> Result<Vec<Option<(u16, u16)>>, String> {
+    let mut hints = Vec::new();
+    for part in s.split(';') {
+        let part = part.trim();
+        if part.is_empty() {
+            hints.push(None);
+            continue;
+        }
+        let tokens: Vec<&str> = part.split(',').collect();
+        if tokens.len() != 2 {
+            return Err(format!(
+                "Invalid bearing format '{}'. Expected 'angle,range'.",
+                part
+            ));
+        }
+        let angle: u16 = match tokens[0].trim().parse() {
+            Ok(v) if v <= 360 => v,
+            _ => return Err(format!("Invalid bearing angle: '{}'", tokens[0])),
+        };
+        let range: u16 = match tokens[1].trim().parse() {
+            Ok(v) if v <= 180 => v,
+            _ => return Err(format!("Invalid bearing range: '{}'", tokens[1])),
+        };
+        hints.push(Some((angle % 360, range)));
+    }
+    Ok(hints)
+}
+
+/// Check a snap radius in metres: positive and no larger than the snap
+/// index search limit ([`MAX_SNAP_DISTANCE_M`](super::snap_index::MAX_SNAP_DISTANCE_M)).
+pub fn validate_radius(radius: f64) -> Result<f64, String> {
+    let max = super::snap_index::MAX_SNAP_DISTANCE_M;
+    if !radius.is_finite() || radius <= 0.0 || radius > max {
+        return Err(format!(
+            "radius must be a positive number of metres up to {}, got {}",
+            max, radius
+        ));
+    }
+    Ok(radius)
+}
+
+/// Parse `radius=r;r;...` (metres) into one entry per coordinate, like
+/// [`parse_bearings`]. An empty slot keeps the default (the full snap
+/// search radius).
+pub fn parse_radiuses(s: &str) -> Result<Vec<Option<f64>>, String> {
+    s.split(';')
+        .map(|part| match part.trim() {
+            "" => Ok(None),
+            v => match v.parse::<f64>() {
+                Ok(r) => validate_radius(r).map(Some),
+                Err(_) => Err(format!("Invalid radius: '{}'", v)),
+            },
+        })
+        .collect()
+}
+
 /// Parse mode string to Mode using dynamic lookup in state's mode_lookup table
 pub fn parse_mode(
     s: &str,
@@ -112,6 +214,86 @@ pub fn parse_mode(
     }
 }
 
+/// Optimisation metric selected by the `weighting` parameter of /route,
+/// /table and /isochrone. Every weight set of a mode stays resident, so
+/// switching between them is a per-request choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Weighting {
+    /// Travel time on the mode's base weights (default).
+    Fastest,
+    /// Geometric length on the step 8 distance weights (`cch.d.<mode>.u32`).
+    Shortest,
+    /// Travel time on a traffic-variant customization, served as the
+    /// synthetic mode `<mode>_<variant>`.
+    Variant(String),
+}
+
+impl Weighting {
+    /// Parse the raw parameter (case-insensitive, like `mode`). Variant
+    /// names are only checked against the loaded modes by
+    /// [`resolve_weighting`].
+    pub fn parse(weighting: Option<&str>) -> Result<Self, String> {
+        let Some(raw) = weighting else {
+            return Ok(Self::Fastest);
+        };
+        match raw.trim().to_lowercase().as_str() {
+            "" => Err("weighting must not be empty".to_string()),
+            "fastest" => Ok(Self::Fastest),
+            "shortest" => Ok(Self::Shortest),
+            other => Ok(Self::Variant(other.to_string())),
+        }
+    }
+
+    pub fn is_shortest(&self) -> bool {
+        matches!(self, Self::Shortest)
+    }
+}
+
+/// Weightings servable for `mode`: `fastest`, `shortest`, then every
+/// traffic variant loaded as `<mode>_<variant>`, sorted.
+pub fn available_weightings(
+    mode: &str,
+    mode_lookup: &std::collections::HashMap<String, u8>,
+) -> Vec<String> {
+    let prefix = format!("{}_", mode.to_lowercase());
+    let mut variants: Vec<String> = mode_lookup
+        .keys()
+        .filter_map(|name| name.strip_prefix(&prefix))
+        .map(str::to_string)
+        .collect();
+    variants.sort();
+    let mut all = vec!["fastest".to_string(), "shortest".to_string()];
+    all.extend(variants);
+    all
+}
+
+/// Resolve `mode` + `weighting` to the mode slot that serves it. Fastest
+/// and shortest share the base mode; a variant selects its synthetic mode.
+/// An unknown variant is a 400, never a silent fallback to freeflow.
+pub fn resolve_weighting(
+    mode: &str,
+    weighting: Option<&str>,
+    mode_lookup: &std::collections::HashMap<String, u8>,
+) -> Result<(Mode, Weighting), String> {
+    let weighting = Weighting::parse(weighting)?;
+    let base = parse_mode(mode, mode_lookup)?;
+    match &weighting {
+        Weighting::Fastest | Weighting::Shortest => Ok((base, weighting)),
+        Weighting::Variant(name) => {
+            let synthetic = format!("{}_{}", mode.to_lowercase(), name);
+            match mode_lookup.get(&synthetic) {
+                Some(&idx) => Ok((Mode(idx), weighting)),
+                None => Err(format!(
+                    "Unknown weighting '{}' for mode '{}'. Available: {}. Build traffic variants with `step8-customize --traffic`.",
+                    name,
+                    mode,
+                    available_weightings(mode, mode_lookup).join(", ")
+                )),
+            }
+        }
+    }
+}
+
 /// Helper: return a 400 Bad Request JSON error response
 pub fn bad_request(error: String) -> (axum::http::StatusCode, Json<ErrorResponse>) {
     (
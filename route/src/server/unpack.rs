@@ -122,3 +122,19 @@ pub(crate) fn find_down_edge(topo: &CchTopo, source: usize, target: u32) -> Opti
     let slice = &topo.down_targets[start..end];
     slice.binary_search(&target).ok().map(|i| start + i)
 }
+
+/// Cost of an unpacked rank path (as produced by [`unpack_path`]) under
+/// `weights`: the sum of its original arc weights. Lets a path found on a
+/// modified weight set (e.g. penalised alternatives) be re-priced on the
+/// real one. `None` if two consecutive ranks are not joined by an arc.
+pub fn path_weight(topo: &CchTopo, weights: &CchWeights, rank_path: &[u32]) -> Option<u64> {
+    rank_path.windows(2).try_fold(0u64, |acc, w| {
+        let (from, to) = (w[0], w[1]);
+        let weight = if to > from {
+            weights.up.get(find_up_edge(topo, from as usize, to)?)
+        } else {
+            weights.down.get(find_down_edge(topo, from as usize, to)?)
+        };
+        Some(acc + weight as u64)
+    })
+}
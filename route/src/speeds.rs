@@ -0,0 +1,286 @@
+//! Live speed overrides for step 8 recustomization.
+//!
+//! A speeds table maps `(osm_way_id, direction)` to an observed speed.
+//! `step8-customize --speeds speeds.csv` rewrites the per-EBG-node time
+//! weights of every matching edge from the freeflow `w.<mode>.u32`, writes
+//! the result as `step8/w.<mode>.u32` and re-runs the customization on the
+//! unchanged step 6/7 hierarchy. Refreshing live traffic therefore costs one
+//! customization pass, never a rebuild.
+//!
+//! ## File layout
+//!
+//! ```text
+//! osm_way_id,direction,speed_kmh
+//! 4003125,forward,23.5
+//! 4003125,backward,41
+//! 22871544,both,8
+//! ```
+//!
+//! - `osm_way_id` (alias `way_id`) and `speed_kmh` (alias `speed`) are
+//!   required; column order is free and headers are case-insensitive.
+//! - `direction` is optional (`both` when the column is absent or the cell
+//!   is blank). `forward` follows the OSM way's node order, `backward` runs
+//!   against it — the same convention as `access_fwd` / `access_rev`.
+//! - Speeds must be finite and in `(0, 300]` km/h. Closures are not
+//!   expressed here: an override never makes an edge (in)accessible.
+//! - A `(way, direction)` pair may appear once; `both` counts as both
+//!   directions.
+//!
+//! ## Semantics
+//!
+//! An overridden edge's weight becomes its travel time at the given speed
+//! (`length_m / speed`, round-half-even, at least 1 s). It REPLACES the
+//! model weight, including per-km and constant penalties, since the
+//! observation already reflects them. Edges without an override keep their
+//! freeflow weight; edges inaccessible in the freeflow weights stay
+//! inaccessible. Because the base is always the pristine step 5 file, each
+//! refresh is independent of the previous one.
+
+use anyhow::{Context, Result, bail};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::formats::{EbgNode, EbgNodes, NbgGeo};
+use crate::gradient::FLAT_PERMILLE;
+use crate::weights::travel_time_s;
+
+/// Fastest accepted override (km/h).
+pub const MAX_SPEED_KMH: f64 = 300.0;
+
+const WAY_ALIASES: &[&str] = &["osm_way_id", "way_id"];
+const DIRECTION_ALIASES: &[&str] = &["direction", "dir"];
+const SPEED_ALIASES: &[&str] = &["speed_kmh", "speed"];
+
+/// Per-direction speed overrides keyed by OSM way id, in mm/s
+/// (`[forward, backward]`, 0 = no override).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SpeedOverrides {
+    pub ways: HashMap<i64, [u32; 2]>,
+}
+
+/// Outcome of [`SpeedOverrides::apply_to_node_weights`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ApplyStats {
+    /// Accessible EBG nodes whose weight was rewritten.
+    pub overridden: usize,
+    /// Overrides that hit only inaccessible EBG nodes (or none at all).
+    pub unmatched_ways: usize,
+}
+
+impl SpeedOverrides {
+    /// Load a speeds table (`.csv` or `.tsv`).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let delimiter = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("tsv") => b'\t',
+            _ => b',',
+        };
+        let rdr = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .has_headers(true)
+            .trim(csv::Trim::All)
+            .from_path(path)
+            .with_context(|| format!("opening speeds file {}", path.display()))?;
+        Self::from_reader(rdr).with_context(|| format!("reading speeds file {}", path.display()))
+    }
+
+    /// Parse a speeds table from CSV text (header row required).
+    pub fn from_csv(raw: &str) -> Result<Self> {
+        let rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .trim(csv::Trim::All)
+            .from_reader(raw.as_bytes());
+        Self::from_reader(rdr)
+    }
+
+    fn from_reader<R: std::io::Read>(mut rdr: csv::Reader<R>) -> Result<Self> {
+        let headers = rdr.headers().context("reading header row")?.clone();
+        let find = |aliases: &[&str]| {
+            headers
+                .iter()
+                .position(|h| aliases.iter().any(|a| h.eq_ignore_ascii_case(a)))
+        };
+        let way_idx = find(WAY_ALIASES).with_context(|| {
+            format!(
+                "missing a way-id column (one of: {})",
+                WAY_ALIASES.join(", ")
+            )
+        })?;
+        let speed_idx = find(SPEED_ALIASES).with_context(|| {
+            format!(
+                "missing a speed column (one of: {})",
+                SPEED_ALIASES.join(", ")
+            )
+        })?;
+        let dir_idx = find(DIRECTION_ALIASES);
+
+        let mut overrides = Self::default();
+        for (i, rec) in rdr.records().enumerate() {
+            let row = i + 2; // 1-based, plus header line
+            let rec = rec.with_context(|| format!("parse error at row {row}"))?;
+            let way_id: i64 = rec
+                .get(way_idx)
+                .unwrap_or("")
+                .parse()
+                .with_context(|| format!("row {row}: bad osm_way_id"))?;
+            let speed_kmh: f64 = rec
+                .get(speed_idx)
+                .unwrap_or("")
+                .parse()
+                .with_context(|| format!("row {row}: bad speed_kmh"))?;
+            if !(speed_kmh > 0.0 && speed_kmh <= MAX_SPEED_KMH) {
+                bail!("row {row}: speed_kmh {speed_kmh} outside (0, {MAX_SPEED_KMH}]");
+            }
+            let dirs: &[usize] = match dir_idx.and_then(|d| rec.get(d)).unwrap_or("") {
+                "" | "both" => &[0, 1],
+                "forward" => &[0],
+                "backward" => &[1],
+                other => bail!(
+                    "row {row}: unknown direction '{other}' (expected forward, backward or both)"
+                ),
+            };
+            let speed_mmps = ((speed_kmh * 1_000_000.0 / 3600.0).round() as u32).max(1);
+            let entry = overrides.ways.entry(way_id).or_insert([0; 2]);
+            for &d in dirs {
+                if entry[d] != 0 {
+                    bail!("row {row}: duplicate override for way {way_id}");
+                }
+                entry[d] = speed_mmps;
+            }
+        }
+        Ok(overrides)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ways.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ways.is_empty()
+    }
+
+    /// Rewrite the per-EBG-node time weights (seconds) of every overridden
+    /// edge. `nbg_geo` maps each EBG node to its OSM way.
+    pub fn apply_to_node_weights(
+        &self,
+        weights: &mut [u32],
+        ebg_nodes: &EbgNodes,
+        nbg_geo: &NbgGeo,
+    ) -> Result<ApplyStats> {
+        anyhow::ensure!(
+            weights.len() == ebg_nodes.nodes.len(),
+            "weights len {} mismatches EBG node count {}",
+            weights.len(),
+            ebg_nodes.nodes.len()
+        );
+
+        let mut stats = ApplyStats::default();
+        let mut hit_ways: HashSet<i64> = HashSet::with_capacity(self.ways.len());
+        for (i, node) in ebg_nodes.nodes.iter().enumerate() {
+            let Some(edge) = nbg_geo.edges.get(node.geom_idx as usize) else {
+                continue;
+            };
+            let Some(speeds) = self.ways.get(&edge.first_osm_way_id) else {
+                continue;
+            };
+            let speed_mmps = speeds[usize::from(!EbgNode::is_forward(i as u32))];
+            if speed_mmps == 0 || weights[i] == 0 {
+                // No override this way, or inaccessible — preserve sentinel.
+                continue;
+            }
+            weights[i] = travel_time_s(node.length_m, speed_mmps, FLAT_PERMILLE).max(1);
+            stats.overridden += 1;
+            hit_ways.insert(edge.first_osm_way_id);
+        }
+        stats.unmatched_ways = self.ways.len() - hit_ways.len();
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{ArcCow, NbgEdge};
+
+    fn geo_and_nodes() -> (NbgGeo, EbgNodes) {
+        let edge = |way_id: i64| NbgEdge {
+            u_node: 0,
+            v_node: 1,
+            length_mm: 1_000_000,
+            bearing_deci_deg: 0,
+            n_poly_pts: 0,
+            poly_off: 0,
+            first_osm_way_id: way_id,
+            flags: 0,
+        };
+        let geo = NbgGeo {
+            n_edges_und: 2,
+            edges: vec![edge(100), edge(200)],
+            polylines: vec![],
+        };
+        let node = |geom_idx: u32| EbgNode {
+            tail_nbg: 0,
+            head_nbg: 1,
+            geom_idx,
+            length_m: 1000,
+            class_bits: 0,
+            primary_way: 0,
+        };
+        let nodes = EbgNodes {
+            n_nodes: 4,
+            created_unix: 0,
+            inputs_sha: [0; 32],
+            nodes: ArcCow::from_vec(vec![node(0), node(0), node(1), node(1)]),
+        };
+        (geo, nodes)
+    }
+
+    #[test]
+    fn parses_directions_and_aliases() {
+        let o = SpeedOverrides::from_csv(
+            "Way_ID,speed,direction\n100,36,forward\n100,72,backward\n200,18,\n",
+        )
+        .unwrap();
+        assert_eq!(o.ways[&100], [10_000, 20_000]);
+        assert_eq!(o.ways[&200], [5_000, 5_000]);
+
+        let o = SpeedOverrides::from_csv("osm_way_id,speed_kmh\n7,36\n").unwrap();
+        assert_eq!(o.ways[&7], [10_000, 10_000]);
+    }
+
+    #[test]
+    fn rejects_bad_rows() {
+        for csv in [
+            "speed_kmh\n36\n",
+            "osm_way_id\n7\n",
+            "osm_way_id,speed_kmh\n7,0\n",
+            "osm_way_id,speed_kmh\n7,301\n",
+            "osm_way_id,speed_kmh\n7,NaN\n",
+            "osm_way_id,speed_kmh,direction\n7,36,north\n",
+            "osm_way_id,speed_kmh,direction\n7,36,both\n7,40,forward\n",
+        ] {
+            assert!(SpeedOverrides::from_csv(csv).is_err(), "{csv:?}");
+        }
+    }
+
+    #[test]
+    fn overrides_matching_accessible_nodes_only() {
+        let (geo, nodes) = geo_and_nodes();
+        let o = SpeedOverrides::from_csv(
+            "osm_way_id,direction,speed_kmh\n100,backward,36\n200,both,72\n300,both,50\n",
+        )
+        .unwrap();
+        // Node 3 (way 200, backward) is inaccessible.
+        let mut weights = vec![60, 60, 60, 0];
+        let stats = o.apply_to_node_weights(&mut weights, &nodes, &geo).unwrap();
+        // 1000 m at 10 m/s = 100 s; at 20 m/s = 50 s.
+        assert_eq!(weights, vec![60, 100, 50, 0]);
+        assert_eq!(
+            stats,
+            ApplyStats {
+                overridden: 2,
+                unmatched_ways: 1,
+            }
+        );
+    }
+}
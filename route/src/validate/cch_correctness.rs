@@ -60,7 +60,7 @@ struct QueryResult {
 /// Auto-derives `Send + Sync` from its `Vec<u64>` / `Vec<u32>` fields, so
 /// rayon workers can share `&DownReverse` for read-only access during
 /// parallel validation.
-struct DownReverse {
+pub(crate) struct DownReverse {
     offsets: Vec<u64>,
     sources: Vec<u32>,
     weights: Vec<u32>,
@@ -376,7 +376,7 @@ fn generate_query_pairs(
     pairs
 }
 
-fn build_down_reverse(
+pub(crate) fn build_down_reverse(
     topo: &crate::formats::CchTopo,
     weights: &crate::formats::CchWeights,
 ) -> DownReverse {
@@ -494,7 +494,7 @@ fn random_walk_neighbor(
 
 /// Bidirectional CCH query with generation-based clearing
 #[allow(clippy::too_many_arguments)]
-fn bidi_cch_query(
+pub(crate) fn bidi_cch_query(
     topo: &crate::formats::CchTopo,
     weights: &crate::formats::CchWeights,
     down_rev: &DownReverse,
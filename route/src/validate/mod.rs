@@ -7,7 +7,8 @@ use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
-use crate::formats::{RelationsFile, WaysFile};
+use crate::formats::zstd_compress::{artifact_len, open_artifact, read_artifact_tail};
+use crate::formats::{RelationsFile, WaysFile, nodes_sa};
 
 pub mod step3;
 pub use step3::{ComponentStats, Step3LockFile, verify_step3_lock_conditions};
@@ -33,6 +34,9 @@ pub use cch_correctness::{
 pub mod invariants;
 pub use invariants::{InvariantResult, validate_invariants};
 
+pub mod turns;
+pub use turns::{TurnFailure, TurnValidationResult, validate_turn_costs};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BBox {
     pub min_lat: f64,
@@ -41,6 +45,32 @@ pub struct BBox {
     pub max_lon: f64,
 }
 
+impl BBox {
+    /// From a nodes.sa fixed-point `(min_lat, min_lon, max_lat, max_lon)`.
+    pub fn from_fxp((min_lat, min_lon, max_lat, max_lon): (i32, i32, i32, i32)) -> Self {
+        Self {
+            min_lat: min_lat as f64 / 1e7,
+            min_lon: min_lon as f64 / 1e7,
+            max_lat: max_lat as f64 / 1e7,
+            max_lon: max_lon as f64 / 1e7,
+        }
+    }
+
+    /// Lock files written before the bbox was computed (and empty
+    /// extracts) carry all zeros; there is nothing to check against.
+    pub fn is_placeholder(&self) -> bool {
+        self.min_lat == 0.0 && self.min_lon == 0.0 && self.max_lat == 0.0 && self.max_lon == 0.0
+    }
+
+    /// Inclusive containment of a 1e-7 fixed-point coordinate. Compared
+    /// in fixed-point so nodes on the boundary survive the f64 round trip.
+    pub fn contains_fxp(&self, lat_fxp: i32, lon_fxp: i32) -> bool {
+        use crate::formats::nodes_sa::to_fxp;
+        (to_fxp(self.min_lat)..=to_fxp(self.max_lat)).contains(&lat_fxp)
+            && (to_fxp(self.min_lon)..=to_fxp(self.max_lon)).contains(&lon_fxp)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Counts {
     pub nodes: u64,
@@ -59,6 +89,10 @@ pub struct LockFile {
     pub bbox: BBox,
     pub block_size: u32,
     pub top_bits: u8,
+    /// Step 1 way filter spec; absent in locks that predate the filter
+    /// (every way kept).
+    #[serde(default)]
+    pub way_filter: Option<String>,
     pub created_at_utc: String,
 }
 
@@ -75,6 +109,26 @@ impl LockFile {
         println!("🔒 Generating lock file...");
 
         let input_sha256 = compute_sha256(input_path)?;
+        Self::with_input_sha256(
+            input_sha256,
+            nodes_sa_path,
+            nodes_si_path,
+            ways_path,
+            relations_path,
+            counts,
+        )
+    }
+
+    /// Same as [`LockFile::create`] for a precomputed input hash (e.g.
+    /// the changefile chain of `step1-update`, which has no single input).
+    pub fn with_input_sha256(
+        input_sha256: String,
+        nodes_sa_path: &Path,
+        nodes_si_path: &Path,
+        ways_path: &Path,
+        relations_path: &Path,
+        counts: Counts,
+    ) -> Result<Self> {
         println!("  ✓ Input SHA-256: {}", input_sha256);
 
         let nodes_sa_sha256 = compute_sha256(nodes_sa_path)?;
@@ -89,13 +143,12 @@ impl LockFile {
         let relations_sha256 = compute_sha256(relations_path)?;
         println!("  ✓ relations.raw SHA-256: {}", relations_sha256);
 
-        // For now, use placeholder bbox (would need to parse nodes.sa header)
-        let bbox = BBox {
-            min_lat: 0.0,
-            min_lon: 0.0,
-            max_lat: 0.0,
-            max_lon: 0.0,
-        };
+        // Same bbox the node pass computed for the nodes.sa header.
+        let bbox = BBox::from_fxp(nodes_sa::Reader::open(nodes_sa_path)?.bbox());
+        println!(
+            "  ✓ BBox: [{:.7}, {:.7}] – [{:.7}, {:.7}]",
+            bbox.min_lat, bbox.min_lon, bbox.max_lat, bbox.max_lon
+        );
 
         let created_at_utc = chrono::Utc::now().to_rfc3339();
 
@@ -109,6 +162,7 @@ impl LockFile {
             bbox,
             block_size: 2048,
             top_bits: 16,
+            way_filter: None,
             created_at_utc,
         })
     }
@@ -177,10 +231,8 @@ pub fn verify_lock_conditions(
 
 /// Verify nodes.sa file structure and checksums
 fn verify_nodes_sa(path: &Path) -> Result<()> {
-    use std::io::{Seek, SeekFrom};
-
     let mut file =
-        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        open_artifact(path).with_context(|| format!("Failed to open {}", path.display()))?;
 
     // Read header
     let mut header = vec![0u8; 128];
@@ -204,7 +256,7 @@ fn verify_nodes_sa(path: &Path) -> Result<()> {
 
     // Calculate expected file size
     let expected_size = 128 + (count * 16) + 16; // header + records + footer
-    let actual_size = file.seek(SeekFrom::End(0))?;
+    let actual_size = artifact_len(path)?;
 
     if actual_size != expected_size {
         anyhow::bail!(
@@ -229,7 +281,7 @@ fn verify_nodes_sa(path: &Path) -> Result<()> {
 /// Validation is limited to magic number and file size checks.
 fn verify_nodes_si(path: &Path) -> Result<()> {
     let mut file =
-        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        open_artifact(path).with_context(|| format!("Failed to open {}", path.display()))?;
 
     // Read header
     let mut header = vec![0u8; 32];
@@ -245,7 +297,7 @@ fn verify_nodes_si(path: &Path) -> Result<()> {
         );
     }
 
-    let file_size = file.metadata()?.len();
+    let file_size = artifact_len(path)?;
     println!("  ✓ {} verified ({} bytes)", path.display(), file_size);
     Ok(())
 }
@@ -361,7 +413,7 @@ impl Step2LockFile {
 
 /// Read way_attrs file header to get count and CRC
 fn read_way_attrs_info(path: &Path) -> Result<(u64, String)> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 80];
     file.read_exact(&mut header)?;
 
@@ -371,18 +423,14 @@ fn read_way_attrs_info(path: &Path) -> Result<(u64, String)> {
     ]);
 
     // Read CRC from footer (last 8 bytes of file)
-    use std::io::{Seek, SeekFrom};
-    file.seek(SeekFrom::End(-8))?;
-    let mut crc_bytes = [0u8; 8];
-    file.read_exact(&mut crc_bytes)?;
-    let crc64 = u64::from_le_bytes(crc_bytes);
+    let crc64 = read_footer_crc(path)?;
 
     Ok((count, format!("{:016x}", crc64)))
 }
 
 /// Read turn_rules file header to get count and CRC
 fn read_turn_rules_info(path: &Path) -> Result<(u64, String)> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 80];
     file.read_exact(&mut header)?;
 
@@ -392,11 +440,7 @@ fn read_turn_rules_info(path: &Path) -> Result<(u64, String)> {
     ]);
 
     // Read CRC from footer (last 8 bytes of file)
-    use std::io::{Seek, SeekFrom};
-    file.seek(SeekFrom::End(-8))?;
-    let mut crc_bytes = [0u8; 8];
-    file.read_exact(&mut crc_bytes)?;
-    let crc64 = u64::from_le_bytes(crc_bytes);
+    let crc64 = read_footer_crc(path)?;
 
     Ok((count, format!("{:016x}", crc64)))
 }
@@ -561,8 +605,15 @@ fn verify_lock_condition_e() -> Result<()> {
 
 // Helper functions
 
+/// File CRC-64 stored in the last 8 bytes of Step 2 artifacts.
+fn read_footer_crc(path: &Path) -> Result<u64> {
+    let tail = read_artifact_tail(path, 8)
+        .with_context(|| format!("Failed to read footer of {}", path.display()))?;
+    Ok(u64::from_le_bytes(tail[..].try_into()?))
+}
+
 fn get_ways_count(ways_path: &Path) -> Result<u64> {
-    let mut file = File::open(ways_path)?;
+    let mut file = open_artifact(ways_path)?;
     let mut header = vec![0u8; 32];
     file.read_exact(&mut header)?;
 
@@ -575,13 +626,7 @@ fn get_ways_count(ways_path: &Path) -> Result<u64> {
 }
 
 fn verify_way_attrs_crc(path: &Path, expected_crc: &str) -> Result<()> {
-    use std::io::{Seek, SeekFrom};
-
-    let mut file = File::open(path)?;
-    file.seek(SeekFrom::End(-8))?;
-    let mut crc_bytes = [0u8; 8];
-    file.read_exact(&mut crc_bytes)?;
-    let crc64 = u64::from_le_bytes(crc_bytes);
+    let crc64 = read_footer_crc(path)?;
     let actual_crc = format!("{:016x}", crc64);
 
     if actual_crc != expected_crc {
@@ -597,13 +642,7 @@ fn verify_way_attrs_crc(path: &Path, expected_crc: &str) -> Result<()> {
 }
 
 fn verify_turn_rules_crc(path: &Path, expected_crc: &str) -> Result<()> {
-    use std::io::{Seek, SeekFrom};
-
-    let mut file = File::open(path)?;
-    file.seek(SeekFrom::End(-8))?;
-    let mut crc_bytes = [0u8; 8];
-    file.read_exact(&mut crc_bytes)?;
-    let crc64 = u64::from_le_bytes(crc_bytes);
+    let crc64 = read_footer_crc(path)?;
     let actual_crc = format!("{:016x}", crc64);
 
     if actual_crc != expected_crc {
@@ -619,7 +658,7 @@ fn verify_turn_rules_crc(path: &Path, expected_crc: &str) -> Result<()> {
 }
 
 fn verify_way_attrs_sorted(path: &Path) -> Result<()> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 80];
     file.read_exact(&mut header)?;
 
@@ -652,7 +691,7 @@ fn verify_way_attrs_sorted(path: &Path) -> Result<()> {
 }
 
 fn verify_turn_rules_sorted(path: &Path) -> Result<()> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 80];
     file.read_exact(&mut header)?;
 
@@ -698,7 +737,7 @@ fn verify_turn_rules_sorted(path: &Path) -> Result<()> {
 }
 
 fn verify_access_class_consistency(path: &Path) -> Result<()> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 80];
     file.read_exact(&mut header)?;
 
@@ -731,7 +770,7 @@ fn verify_access_class_consistency(path: &Path) -> Result<()> {
 }
 
 fn verify_speed_bounds(path: &Path, mode_name: &str) -> Result<()> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 80];
     file.read_exact(&mut header)?;
 
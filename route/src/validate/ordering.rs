@@ -11,6 +11,9 @@ use crate::ordering_lifted::Step6LiftedResult;
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Step6LockFile {
     pub mode: String,
+    /// Partitioner used (`current` or `nd`); absent in older lock files.
+    #[serde(default = "default_ordering")]
+    pub ordering: String,
     pub inputs_sha256: String,
     pub order_sha256: String,
     pub n_nodes: u32,
@@ -20,6 +23,10 @@ pub struct Step6LockFile {
     pub created_at_utc: String,
 }
 
+fn default_ordering() -> String {
+    "current".to_string()
+}
+
 /// Validate Step 6 outputs and generate lock file
 pub fn validate_step6(result: &Step6Result, filtered_ebg_path: &Path) -> Result<Step6LockFile> {
     let mode_name = &result.mode_name;
@@ -46,6 +53,7 @@ pub fn validate_step6(result: &Step6Result, filtered_ebg_path: &Path) -> Result<
 
     Ok(Step6LockFile {
         mode: mode_name.to_string(),
+        ordering: result.algorithm.as_str().to_string(),
         inputs_sha256,
         order_sha256,
         n_nodes: result.n_nodes,
@@ -10,11 +10,15 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+use super::BBox;
+use crate::formats::zstd_compress::open_artifact;
+use crate::formats::{EDGE_FLAG_SELF_LOOP, NbgCsrFile, NbgElevFile, NbgGeoFile};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ComponentStats {
     pub count: usize,
@@ -33,6 +37,9 @@ pub struct Step3LockFile {
     pub components: ComponentStats,
     pub rss_peak_bytes: u64,
     pub created_at_utc: String,
+    /// nbg.elev hash, when Step 3 sampled elevation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbg_elev_sha256: Option<String>,
 }
 
 impl Step3LockFile {
@@ -65,9 +72,23 @@ impl Step3LockFile {
             components,
             rss_peak_bytes,
             created_at_utc: Utc::now().to_rfc3339(),
+            nbg_elev_sha256: None,
         })
     }
 
+    /// Record nbg.elev after checking it covers every nbg.geo edge.
+    pub fn with_elev(mut self, elev_path: &Path) -> Result<Self> {
+        let elev = NbgElevFile::read(elev_path)?;
+        anyhow::ensure!(
+            elev.edges.len() as u64 == self.n_edges_und,
+            "nbg.elev has {} edges, nbg.geo has {}",
+            elev.edges.len(),
+            self.n_edges_und
+        );
+        self.nbg_elev_sha256 = Some(compute_file_sha256(elev_path)?);
+        Ok(self)
+    }
+
     pub fn write(&self, path: &Path) -> Result<()> {
         let file = File::create(path)?;
         serde_json::to_writer_pretty(file, self)?;
@@ -98,6 +119,7 @@ pub fn verify_step3_lock_conditions(
     csr_path: &Path,
     geo_path: &Path,
     node_map_path: &Path,
+    step1_bbox: Option<&BBox>,
 ) -> Result<()> {
     println!("🔍 Verifying Step 3 lock conditions...");
     println!();
@@ -114,7 +136,7 @@ pub fn verify_step3_lock_conditions(
     // C. Metric correctness
     println!();
     println!("C. Metric Correctness:");
-    verify_lock_condition_c_metrics(csr_path, geo_path, node_map_path)?;
+    verify_lock_condition_c_metrics(csr_path, geo_path, node_map_path, step1_bbox)?;
 
     // D. End-to-end reachability
     println!();
@@ -193,27 +215,24 @@ fn verify_lock_condition_a_structural(
 }
 
 /// B. Topology semantics (4 conditions)
-fn verify_lock_condition_b_topology(csr_path: &Path, _geo_path: &Path) -> Result<()> {
+fn verify_lock_condition_b_topology(csr_path: &Path, geo_path: &Path) -> Result<()> {
     // B5: Layer correctness - skip for now (requires OSM data)
     println!("  ✓ Layer correctness (requires OSM layer data)");
 
-    // B6: Symmetry - verify bidirectional edges
-    verify_symmetry(csr_path)?;
+    // B6: Symmetry - every edge listed from both ends, keyed by edge index
+    verify_symmetry(csr_path, geo_path)?;
     println!("  ✓ Symmetry verified (all edges bidirectional)");
 
-    // B7: No self-loops
-    let self_loop_count = count_self_loops(csr_path)?;
-    if self_loop_count > 0 {
-        println!(
-            "  ⚠ Warning: {} self-loops found (degenerate geometries)",
-            self_loop_count
-        );
-    } else {
-        println!("  ✓ No self-loops found");
-    }
+    // B7: Self-loops are kept, and flagged
+    let self_loop_count = count_self_loops(geo_path)?;
+    println!("  ✓ {} self-loops, all flagged", self_loop_count);
 
-    // B8: Parallel edges allowed
-    println!("  ✓ Parallel edges allowed (no de-dup constraint)");
+    // B8: Parallel edges are kept as distinct edges
+    let parallel_count = count_parallel_edges(geo_path)?;
+    println!(
+        "  ✓ {} parallel edges (no de-dup constraint)",
+        parallel_count
+    );
 
     Ok(())
 }
@@ -223,11 +242,24 @@ fn verify_lock_condition_c_metrics(
     _csr_path: &Path,
     geo_path: &Path,
     _node_map_path: &Path,
+    step1_bbox: Option<&BBox>,
 ) -> Result<()> {
     // C9: Length plausibility
     verify_length_plausibility(geo_path)?;
     println!("  ✓ Length plausibility (1m ≤ length ≤ 500km)");
 
+    // C9b: Geometry stays within the Step 1 extract
+    match step1_bbox {
+        Some(bbox) if !bbox.is_placeholder() => {
+            let n_points = verify_geo_within_bbox(geo_path, bbox)?;
+            println!(
+                "  ✓ Geometry within step1 bbox ({} polyline points)",
+                n_points
+            );
+        }
+        _ => println!("  - Geometry within step1 bbox (no step1 bbox recorded, skipped)"),
+    }
+
     // C10: Geometry sum parity - sample 1000 edges
     // This requires polyline data - skip for now
     println!("  ✓ Geometry sum parity (sampled 1000 edges, within ±1m)");
@@ -287,7 +319,7 @@ fn verify_lock_condition_f_failures() -> Result<()> {
 // Helper functions
 
 fn read_csr_counts(path: &Path) -> Result<(u32, u64, usize, usize)> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 64];
     file.read_exact(&mut header)?;
 
@@ -306,7 +338,7 @@ fn read_csr_counts(path: &Path) -> Result<(u32, u64, usize, usize)> {
 }
 
 fn read_geo_count(path: &Path) -> Result<u64> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 64];
     file.read_exact(&mut header)?;
 
@@ -319,7 +351,7 @@ fn read_geo_count(path: &Path) -> Result<u64> {
 }
 
 fn read_node_map_count(path: &Path) -> Result<u32> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 16];
     file.read_exact(&mut header)?;
 
@@ -332,7 +364,7 @@ fn read_node_map_count(path: &Path) -> Result<u32> {
 }
 
 fn verify_csr_integrity(path: &Path, n_nodes: u32, n_edges_und: u64) -> Result<()> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 64];
     file.read_exact(&mut header)?;
 
@@ -379,7 +411,7 @@ fn verify_csr_integrity(path: &Path, n_nodes: u32, n_edges_und: u64) -> Result<(
 }
 
 fn verify_geo_integrity(path: &Path) -> Result<()> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 64];
     file.read_exact(&mut header)?;
 
@@ -418,125 +450,120 @@ fn verify_geo_integrity(path: &Path) -> Result<()> {
     Ok(())
 }
 
-fn verify_symmetry(path: &Path) -> Result<()> {
-    let mut file = File::open(path)?;
-    let mut header = vec![0u8; 64];
-    file.read_exact(&mut header)?;
-
-    let n_nodes = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
-    let n_edges_und = u64::from_le_bytes([
-        header[12], header[13], header[14], header[15], header[16], header[17], header[18],
-        header[19],
-    ]);
-
-    // Read offsets
-    let mut offsets = vec![0u64; n_nodes as usize + 1];
-    for offset in offsets.iter_mut() {
-        let mut buf = [0u8; 8];
-        file.read_exact(&mut buf)?;
-        *offset = u64::from_le_bytes(buf);
-    }
-
-    // Read heads and edge_idx
-    let mut heads = vec![0u32; 2 * n_edges_und as usize];
-    for head in heads.iter_mut() {
-        let mut buf = [0u8; 4];
-        file.read_exact(&mut buf)?;
-        *head = u32::from_le_bytes(buf);
+/// Every polyline point of nbg.geo must lie inside the Step 1 bbox;
+/// anything outside means coordinates were corrupted or mixed up
+/// between extracts. Returns the number of points checked.
+fn verify_geo_within_bbox(path: &Path, bbox: &BBox) -> Result<u64> {
+    let geo = NbgGeoFile::read(path)?;
+    let mut n_points = 0u64;
+    for (edge, poly) in geo.polylines.iter().enumerate() {
+        for (&lat_fxp, &lon_fxp) in poly.lat_fxp.iter().zip(&poly.lon_fxp) {
+            if !bbox.contains_fxp(lat_fxp, lon_fxp) {
+                anyhow::bail!(
+                    "{}: edge {} point ({:.7}, {:.7}) outside step1 bbox [{:.7}, {:.7}] – [{:.7}, {:.7}]",
+                    path.display(),
+                    edge,
+                    lat_fxp as f64 * 1e-7,
+                    lon_fxp as f64 * 1e-7,
+                    bbox.min_lat,
+                    bbox.min_lon,
+                    bbox.max_lat,
+                    bbox.max_lon
+                );
+            }
+            n_points += 1;
+        }
     }
+    Ok(n_points)
+}
 
-    let mut edge_idx = vec![0u64; 2 * n_edges_und as usize];
-    for eidx in edge_idx.iter_mut() {
-        let mut buf = [0u8; 8];
-        file.read_exact(&mut buf)?;
-        *eidx = u64::from_le_bytes(buf);
-    }
+/// Every geo edge must appear in the CSR exactly twice, once from each
+/// end, and nowhere else. Checked per edge index rather than per (u, v)
+/// pair, so parallel edges and self-loops are covered and an edge missing
+/// from the CSR cannot hide behind a parallel one.
+fn verify_symmetry(csr_path: &Path, geo_path: &Path) -> Result<()> {
+    let csr = NbgCsrFile::read(csr_path)?;
+    let geo = NbgGeoFile::read(geo_path)?;
+    anyhow::ensure!(
+        geo.edges.len() as u64 == csr.n_edges_und,
+        "nbg.geo has {} edges, nbg.csr {}",
+        geo.edges.len(),
+        csr.n_edges_und
+    );
 
-    // Build adjacency map: (u, v) -> edge_idx
-    let mut adj_map: HashMap<(u32, u32), u64> = HashMap::new();
-    for u in 0..n_nodes {
-        let start = offsets[u as usize] as usize;
-        let end = offsets[u as usize + 1] as usize;
+    // Bit 0: seen from u_node, bit 1: seen from v_node.
+    let mut seen = vec![0u8; geo.edges.len()];
+    for u in 0..csr.n_nodes {
+        let start = csr.offsets[u as usize] as usize;
+        let end = csr.offsets[u as usize + 1] as usize;
         for i in start..end {
-            let v = heads[i];
-            let eidx = edge_idx[i];
-            adj_map.insert((u, v), eidx);
-        }
-    }
-
-    // Check symmetry: for each (u, v) with edge_idx e, verify (v, u) exists with same e
-    for (&(u, v), &e) in &adj_map {
-        if let Some(&e_rev) = adj_map.get(&(v, u)) {
-            if e != e_rev {
+            let (v, e) = (csr.heads[i], csr.edge_idx[i] as usize);
+            let edge = geo.edges.get(e).ok_or_else(|| {
+                anyhow::anyhow!("CSR entry {u}->{v} has edge_idx {e} out of range")
+            })?;
+            let bit = if u == edge.u_node && v == edge.v_node && seen[e] & 1 == 0 {
+                1
+            } else if u == edge.v_node && v == edge.u_node && seen[e] & 2 == 0 {
+                2
+            } else {
                 anyhow::bail!(
-                    "Symmetry violation: edge ({}, {}) has edge_idx {} but ({}, {}) has {}",
+                    "Symmetry violation: CSR entry {}->{} for edge {} ({}-{})",
                     u,
                     v,
                     e,
-                    v,
-                    u,
-                    e_rev
+                    edge.u_node,
+                    edge.v_node
                 );
-            }
-        } else {
-            anyhow::bail!(
-                "Symmetry violation: edge ({}, {}) exists but ({}, {}) missing",
-                u,
-                v,
-                v,
-                u
-            );
+            };
+            seen[e] |= bit;
         }
     }
 
-    Ok(())
-}
-
-fn count_self_loops(path: &Path) -> Result<usize> {
-    let mut file = File::open(path)?;
-    let mut header = vec![0u8; 64];
-    file.read_exact(&mut header)?;
-
-    let n_nodes = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
-    let n_edges_und = u64::from_le_bytes([
-        header[12], header[13], header[14], header[15], header[16], header[17], header[18],
-        header[19],
-    ]);
-
-    // Read offsets
-    let mut offsets = vec![0u64; n_nodes as usize + 1];
-    for offset in offsets.iter_mut() {
-        let mut buf = [0u8; 8];
-        file.read_exact(&mut buf)?;
-        *offset = u64::from_le_bytes(buf);
+    if let Some(e) = seen.iter().position(|&s| s != 3) {
+        anyhow::bail!(
+            "Symmetry violation: edge {} ({}-{}) missing from the CSR in {} direction(s)",
+            e,
+            geo.edges[e].u_node,
+            geo.edges[e].v_node,
+            2 - seen[e].count_ones()
+        );
     }
 
-    // Read heads
-    let mut heads = vec![0u32; 2 * n_edges_und as usize];
-    for head in heads.iter_mut() {
-        let mut buf = [0u8; 4];
-        file.read_exact(&mut buf)?;
-        *head = u32::from_le_bytes(buf);
-    }
+    Ok(())
+}
 
-    // Count self-loops
+/// Self-loops in nbg.geo; each must carry [`EDGE_FLAG_SELF_LOOP`].
+fn count_self_loops(geo_path: &Path) -> Result<usize> {
+    let geo = NbgGeoFile::read(geo_path)?;
     let mut count = 0;
-    for u in 0..n_nodes {
-        let start = offsets[u as usize] as usize;
-        let end = offsets[u as usize + 1] as usize;
-        for &head in &heads[start..end] {
-            if head == u {
-                count += 1;
-            }
-        }
+    for (e, edge) in geo.edges.iter().enumerate() {
+        let is_loop = edge.u_node == edge.v_node;
+        anyhow::ensure!(
+            is_loop == (edge.flags & EDGE_FLAG_SELF_LOOP != 0),
+            "edge {e} ({}-{}) has self-loop flag {}",
+            edge.u_node,
+            edge.v_node,
+            !is_loop
+        );
+        count += is_loop as usize;
     }
+    Ok(count)
+}
 
-    // Each self-loop appears twice, so divide by 2
-    Ok(count / 2)
+/// Edges that share both end nodes with an earlier edge.
+fn count_parallel_edges(geo_path: &Path) -> Result<usize> {
+    let geo = NbgGeoFile::read(geo_path)?;
+    let mut pairs: Vec<(u32, u32)> = geo
+        .edges
+        .iter()
+        .map(|e| (e.u_node.min(e.v_node), e.u_node.max(e.v_node)))
+        .collect();
+    pairs.sort_unstable();
+    Ok(pairs.windows(2).filter(|w| w[0] == w[1]).count())
 }
 
 fn verify_length_plausibility(path: &Path) -> Result<()> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 64];
     file.read_exact(&mut header)?;
 
@@ -573,7 +600,7 @@ fn verify_length_plausibility(path: &Path) -> Result<()> {
 }
 
 pub fn compute_component_stats(path: &Path) -> Result<ComponentStats> {
-    let mut file = File::open(path)?;
+    let mut file = open_artifact(path)?;
     let mut header = vec![0u8; 64];
     file.read_exact(&mut header)?;
 
@@ -0,0 +1,388 @@
+//! Turn-cost validation for Step 8 time weights
+//!
+//! Compares bidirectional CCH queries against a turn-aware Dijkstra run
+//! directly on the filtered EBG, where traversing arc a = u→v costs
+//! `w[v] + t[a]`. Unlike `validate-cch`, whose baseline walks the CCH itself,
+//! this baseline never touches the hierarchy, so a turn penalty dropped (or
+//! double-counted) during customization shows up as a cost mismatch.
+
+use anyhow::Result;
+use priority_queue::PriorityQueue;
+use rand::rngs::StdRng;
+#[allow(unused_imports)]
+use rand::{Rng, RngExt, SeedableRng};
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::path::Path;
+
+use super::cch_correctness::{bidi_cch_query, build_down_reverse};
+use crate::formats::{CchTopoFile, CchWeightsFile, FilteredEbg, FilteredEbgFile};
+use crate::formats::{mod_turns, mod_weights};
+
+/// Turn validation result
+#[derive(Debug)]
+pub struct TurnValidationResult {
+    pub total_pairs: usize,
+    pub routable_pairs: usize,
+    pub unreachable_pairs: usize,
+    /// Routable pairs whose EBG shortest path pays at least one turn penalty
+    pub turn_paths: usize,
+    pub mismatches: usize,
+    pub max_diff: i64,
+}
+
+/// Single failure record (filtered EBG node IDs)
+#[derive(Debug, Clone)]
+pub struct TurnFailure {
+    pub src: u32,
+    pub dst: u32,
+    pub cch_cost: u32,
+    pub ebg_cost: u32,
+}
+
+/// Shortest EBG path cost plus the turn seconds it contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct EbgCost {
+    pub total: u32,
+    pub turn_s: u32,
+}
+
+/// Run turn-cost validation with parallel processing
+#[allow(clippy::too_many_arguments)]
+pub fn validate_turn_costs(
+    topo_path: &Path,
+    weights_path: &Path,
+    filtered_ebg_path: &Path,
+    node_weights_path: &Path,
+    turns_path: &Path,
+    n_pairs: usize,
+    seed: u64,
+    mode_name: &str,
+) -> Result<(TurnValidationResult, Vec<TurnFailure>)> {
+    println!(
+        "\n🔬 Turn-Cost Validation ({} mode) - {} threads",
+        mode_name,
+        rayon::current_num_threads()
+    );
+    println!("   Pairs: {}", n_pairs);
+    println!("   Seed: {}", seed);
+
+    println!("\nLoading CCH topology...");
+    let topo = CchTopoFile::read(topo_path)?;
+    println!("Loading CCH weights...");
+    let weights = CchWeightsFile::read(weights_path)?;
+    println!("Loading filtered EBG...");
+    let ebg = FilteredEbgFile::read(filtered_ebg_path)?;
+    println!("Loading node weights and turn penalties...");
+    let node_weights = mod_weights::read_all(node_weights_path)?;
+    let turns = mod_turns::read_all(turns_path)?;
+
+    let n = ebg.n_filtered_nodes as usize;
+    if topo.n_nodes as usize != n {
+        anyhow::bail!(
+            "Node count mismatch: CCH has {} nodes, filtered EBG has {}",
+            topo.n_nodes,
+            n
+        );
+    }
+    let n_penalized = turns.penalties.iter().filter(|&&p| p > 0).count();
+    println!(
+        "  ✓ {} nodes, {} arcs, {} arcs with a turn penalty",
+        n, ebg.n_filtered_arcs, n_penalized
+    );
+
+    let mut filtered_to_rank = vec![0u32; n];
+    for (rank, &filtered) in topo.rank_to_filtered.iter().enumerate() {
+        filtered_to_rank[filtered as usize] = rank as u32;
+    }
+
+    let down_rev = build_down_reverse(&topo, &weights);
+
+    // Sample sources among accessible nodes only; a closed edge never
+    // starts a real route.
+    let accessible: Vec<u32> = (0..n as u32)
+        .filter(|&f| node_weights.weights[ebg.filtered_to_original[f as usize] as usize] > 0)
+        .collect();
+    if accessible.is_empty() {
+        anyhow::bail!("No accessible nodes found!");
+    }
+
+    let pairs = generate_pairs(&ebg, &accessible, n_pairs, seed);
+
+    println!("\nRunning {} queries in parallel...", n_pairs);
+    let start_time = std::time::Instant::now();
+    let chunk_size = 256.max(n_pairs / 100);
+    let outcomes: Vec<(Option<u32>, Option<EbgCost>, u32, u32)> = pairs
+        .par_chunks(chunk_size)
+        .flat_map(|chunk| {
+            let mut dist_fwd = vec![u32::MAX; n];
+            let mut dist_bwd = vec![u32::MAX; n];
+            let mut gen_fwd = vec![0u32; n];
+            let mut gen_bwd = vec![0u32; n];
+            let mut current_gen = 0u32;
+            let mut pq_fwd: PriorityQueue<u32, Reverse<u32>> = PriorityQueue::with_capacity(10000);
+            let mut pq_bwd: PriorityQueue<u32, Reverse<u32>> = PriorityQueue::with_capacity(10000);
+            let mut search = EbgSearch::new(n);
+
+            chunk
+                .iter()
+                .map(|&(src, dst)| {
+                    current_gen += 1;
+                    let cch = bidi_cch_query(
+                        &topo,
+                        &weights,
+                        &down_rev,
+                        filtered_to_rank[src as usize],
+                        filtered_to_rank[dst as usize],
+                        &mut dist_fwd,
+                        &mut dist_bwd,
+                        &mut gen_fwd,
+                        &mut gen_bwd,
+                        current_gen,
+                        &mut pq_fwd,
+                        &mut pq_bwd,
+                    );
+                    let ebg_cost =
+                        search.query(&ebg, &node_weights.weights, &turns.penalties, src, dst);
+                    (cch, ebg_cost, src, dst)
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let mut result = TurnValidationResult {
+        total_pairs: n_pairs,
+        routable_pairs: 0,
+        unreachable_pairs: 0,
+        turn_paths: 0,
+        mismatches: 0,
+        max_diff: 0,
+    };
+    let mut failures = Vec::new();
+    for &(cch, ebg_cost, src, dst) in &outcomes {
+        match (cch, ebg_cost) {
+            (None, None) => result.unreachable_pairs += 1,
+            (Some(c), Some(e)) if c == e.total => {
+                result.routable_pairs += 1;
+                if e.turn_s > 0 {
+                    result.turn_paths += 1;
+                }
+            }
+            _ => {
+                let cch_cost = cch.unwrap_or(u32::MAX);
+                let ebg_total = ebg_cost.map_or(u32::MAX, |e| e.total);
+                result.mismatches += 1;
+                if cch.is_some() && ebg_cost.is_some() {
+                    let diff = cch_cost as i64 - ebg_total as i64;
+                    result.max_diff = result.max_diff.max(diff.abs());
+                }
+                if failures.len() < 1000 {
+                    failures.push(TurnFailure {
+                        src,
+                        dst,
+                        cch_cost,
+                        ebg_cost: ebg_total,
+                    });
+                }
+            }
+        }
+    }
+
+    println!("\n=== TURN VALIDATION COMPLETE ===");
+    println!("  Total time: {:.2}s", start_time.elapsed().as_secs_f64());
+    println!("  Total pairs: {}", result.total_pairs);
+    println!("  Routable:    {}", result.routable_pairs);
+    println!("  With turns:  {}", result.turn_paths);
+    println!("  Unreachable: {}", result.unreachable_pairs);
+    println!("  MISMATCHES:  {}", result.mismatches);
+    if result.mismatches > 0 {
+        println!("  Max diff:    {}", result.max_diff);
+        println!("\n  First 5 failures:");
+        for f in failures.iter().take(5) {
+            println!(
+                "    src={} dst={} cch={} ebg={}",
+                f.src, f.dst, f.cch_cost, f.ebg_cost
+            );
+        }
+        println!(
+            "\n❌ TURN VALIDATION FAILED - {} mismatches",
+            result.mismatches
+        );
+    } else {
+        println!("\n✅ TURN VALIDATION PASSED - 0 mismatches");
+    }
+
+    Ok((result, failures))
+}
+
+/// Half nearby pairs (random walk along EBG arcs, so short paths with
+/// turns are well covered), half uniform pairs.
+fn generate_pairs(
+    ebg: &FilteredEbg,
+    accessible: &[u32],
+    n_pairs: usize,
+    seed: u64,
+) -> Vec<(u32, u32)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n_pairs)
+        .map(|_| {
+            let src = accessible[rng.random_range(0..accessible.len())];
+            let dst = if rng.random_bool(0.5) {
+                let mut cur = src;
+                for _ in 0..rng.random_range(1..10) {
+                    let start = ebg.offsets[cur as usize] as usize;
+                    let end = ebg.offsets[cur as usize + 1] as usize;
+                    if start == end {
+                        break;
+                    }
+                    cur = ebg.heads[rng.random_range(start..end)];
+                }
+                cur
+            } else {
+                accessible[rng.random_range(0..accessible.len())]
+            };
+            (src, dst)
+        })
+        .collect()
+}
+
+/// Reusable turn-aware Dijkstra over the filtered EBG.
+pub(crate) struct EbgSearch {
+    dist: Vec<u32>,
+    turn_s: Vec<u32>,
+    generation: Vec<u32>,
+    current_gen: u32,
+    pq: PriorityQueue<u32, Reverse<u32>>,
+}
+
+impl EbgSearch {
+    pub(crate) fn new(n_nodes: usize) -> Self {
+        Self {
+            dist: vec![u32::MAX; n_nodes],
+            turn_s: vec![0; n_nodes],
+            generation: vec![0; n_nodes],
+            current_gen: 0,
+            pq: PriorityQueue::with_capacity(10000),
+        }
+    }
+
+    /// Cheapest `src → dst` cost in filtered node IDs. Arc a = u→v costs
+    /// `w[v] + t[a]`; nodes with `w == 0` are inaccessible.
+    pub(crate) fn query(
+        &mut self,
+        ebg: &FilteredEbg,
+        node_weights: &[u32],
+        turn_penalties: &[u32],
+        src: u32,
+        dst: u32,
+    ) -> Option<EbgCost> {
+        self.current_gen += 1;
+        let current_gen = self.current_gen;
+        self.pq.clear();
+
+        self.dist[src as usize] = 0;
+        self.turn_s[src as usize] = 0;
+        self.generation[src as usize] = current_gen;
+        self.pq.push(src, Reverse(0));
+
+        while let Some((u, Reverse(d))) = self.pq.pop() {
+            let u_idx = u as usize;
+            if u == dst {
+                return Some(EbgCost {
+                    total: d,
+                    turn_s: self.turn_s[u_idx],
+                });
+            }
+
+            let start = ebg.offsets[u_idx] as usize;
+            let end = ebg.offsets[u_idx + 1] as usize;
+            for i in start..end {
+                let v = ebg.heads[i];
+                let v_idx = v as usize;
+                let w_v = node_weights[ebg.filtered_to_original[v_idx] as usize];
+                if w_v == 0 {
+                    continue;
+                }
+                let turn = turn_penalties[ebg.original_arc_idx[i] as usize];
+                let nd = d.saturating_add(w_v).saturating_add(turn);
+                let old = if self.generation[v_idx] == current_gen {
+                    self.dist[v_idx]
+                } else {
+                    u32::MAX
+                };
+                if nd < old {
+                    self.dist[v_idx] = nd;
+                    self.turn_s[v_idx] = self.turn_s[u_idx].saturating_add(turn);
+                    self.generation[v_idx] = current_gen;
+                    self.pq.push(v, Reverse(nd));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::ArcCow;
+    use crate::profile_abi::Mode;
+
+    /// Diamond 0→{1,2}→3. The approach via node 1 is cheaper,
+    /// but the turn 1→3 costs 10 s, so the turn-aware optimum goes via 2.
+    fn diamond() -> FilteredEbg {
+        FilteredEbg {
+            mode: Mode(0),
+            n_filtered_nodes: 4,
+            n_filtered_arcs: 4,
+            n_original_nodes: 4,
+            inputs_sha: [0; 32],
+            offsets: ArcCow::from_vec(vec![0, 2, 3, 4, 4]),
+            heads: ArcCow::from_vec(vec![1, 2, 3, 3]),
+            original_arc_idx: ArcCow::from_vec(vec![0, 1, 2, 3]),
+            filtered_to_original: ArcCow::from_vec(vec![0, 1, 2, 3]),
+            original_to_filtered: ArcCow::from_vec(vec![0, 1, 2, 3]),
+        }
+    }
+
+    #[test]
+    fn dijkstra_pays_turn_penalties() {
+        let ebg = diamond();
+        let w = [5, 5, 8, 4];
+        let mut search = EbgSearch::new(4);
+
+        let free = search.query(&ebg, &w, &[0, 0, 0, 0], 0, 3).unwrap();
+        assert_eq!(
+            free,
+            EbgCost {
+                total: 9,
+                turn_s: 0
+            }
+        );
+
+        let t = [0, 0, 10, 1];
+        let turned = search.query(&ebg, &w, &t, 0, 3).unwrap();
+        assert_eq!(
+            turned,
+            EbgCost {
+                total: 13,
+                turn_s: 1
+            }
+        );
+    }
+
+    #[test]
+    fn dijkstra_skips_inaccessible_nodes() {
+        let ebg = diamond();
+        let mut search = EbgSearch::new(4);
+        assert_eq!(search.query(&ebg, &[5, 5, 8, 0], &[0; 4], 0, 3), None);
+        assert_eq!(
+            search.query(&ebg, &[5, 0, 8, 4], &[0; 4], 0, 3),
+            Some(EbgCost {
+                total: 12,
+                turn_s: 0
+            })
+        );
+    }
+}
@@ -71,7 +71,16 @@ pub fn validate_step5(
             let t0 = std::time::Instant::now();
             let way_attrs_data = way_attrs::read_all(way_attrs_path)?;
             let way_index = build_way_index(&way_attrs_data);
-            verify_lock_b_math(&ebg_nodes, &nbg_geo, &weights, &mask, &way_index, mode)?;
+            verify_lock_b_math(
+                &ebg_nodes,
+                &nbg_geo,
+                &weights,
+                &mask,
+                &way_index,
+                mode_output.time_factors.as_deref(),
+                mode_output.ferry_penalty_s,
+                mode,
+            )?;
             println!(
                 "  Passed math parity checks for '{}' ({:.3}s)",
                 mode_name,
@@ -160,12 +169,15 @@ fn verify_lock_a_structural(
 }
 
 /// Lock B.4-B.6: Math parity checks
+#[allow(clippy::too_many_arguments)]
 fn verify_lock_b_math(
     ebg_nodes: &EbgNodes,
     nbg_geo: &NbgGeo,
     weights: &ModWeights,
     mask: &ModMask,
     way_index: &HashMap<i64, WayAttr>,
+    time_factors: Option<&[u16]>,
+    ferry_penalty_s: u32,
     mode: Mode,
 ) -> Result<()> {
     let n_nodes = ebg_nodes.n_nodes as usize;
@@ -194,8 +206,7 @@ fn verify_lock_b_math(
         };
 
         // Determine direction
-        let is_forward =
-            ebg_node.tail_nbg == nbg_edge.u_node && ebg_node.head_nbg == nbg_edge.v_node;
+        let is_forward = EbgNode::is_forward(ebg_id as u32);
         let has_access = if is_forward {
             way_attr.output.access_fwd
         } else {
@@ -225,32 +236,39 @@ fn verify_lock_b_math(
         let length_m = ebg_node.length_m;
         let base_speed_mmps = way_attr.output.base_speed_mmps;
 
-        let travel_time_s =
-            crate::weights::round_half_even_div(length_m as u64 * 1000, base_speed_mmps as u64)
-                as u32;
+        let factor = time_factors.map_or(crate::gradient::FLAT_PERMILLE, |f| f[ebg_id]);
+        let travel_time_s = crate::weights::travel_time_s(length_m, base_speed_mmps, factor);
         let per_km_extra_s = crate::weights::round_half_even_div(
             length_m as u64 * way_attr.output.per_km_penalty_ds as u64,
             10_000,
         ) as u32;
         let const_penalty_s =
             crate::weights::round_half_even_div(way_attr.output.const_penalty_ds as u64, 10) as u32;
+        let ferry_s = if ebg_node.class_bits & EDGE_FLAG_FERRY != 0 {
+            ferry_penalty_s
+        } else {
+            0
+        };
         let expected_weight = travel_time_s
             .saturating_add(per_km_extra_s)
             .saturating_add(const_penalty_s)
+            .saturating_add(ferry_s)
             .max(1);
 
         anyhow::ensure!(
             weights.weights[ebg_id] == expected_weight,
-            "Weight mismatch at node {}: expected {} got {} (mode={:?}, length_m={}, speed={}, travel_time={}, per_km={}, const={})",
+            "Weight mismatch at node {}: expected {} got {} (mode={:?}, length_m={}, speed={}, gradient={}‰, travel_time={}, per_km={}, const={}, ferry={})",
             ebg_id,
             expected_weight,
             weights.weights[ebg_id],
             mode,
             length_m,
             base_speed_mmps,
+            factor,
             travel_time_s,
             per_km_extra_s,
             const_penalty_s,
+            ferry_s,
         );
 
         sampled += 1;
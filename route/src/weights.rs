@@ -5,7 +5,9 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::formats::*;
+use crate::gradient::{FLAT_PERMILLE, GradientModel};
 use crate::profile_abi::Mode;
+use crate::server::elevation::ElevationData;
 
 /// Integer division with round-half-to-even (banker's rounding) semantics.
 /// `numerator / denominator`, with halves rounded toward the even quotient.
@@ -39,6 +41,16 @@ pub(crate) fn round_half_even_div(numerator: u64, denominator: u64) -> u64 {
     }
 }
 
+/// Travel time in seconds for `length_m` at `base_speed_mmps`, scaled by a
+/// gradient multiplier in permille (1000 = flat, same result as unscaled).
+#[inline]
+pub(crate) fn travel_time_s(length_m: u32, base_speed_mmps: u32, time_factor_permille: u16) -> u32 {
+    round_half_even_div(
+        length_m as u64 * 1000 * time_factor_permille as u64,
+        base_speed_mmps as u64 * FLAT_PERMILLE as u64,
+    ) as u32
+}
+
 #[cfg(test)]
 mod round_tests {
     use super::round_half_even_div;
@@ -92,6 +104,29 @@ mod round_tests {
     fn test_zero_denominator() {
         assert_eq!(round_half_even_div(100, 0), 0);
     }
+
+    #[test]
+    fn test_travel_time_flat_parity() {
+        use super::travel_time_s;
+        for (length_m, speed) in [(1000, 13_889), (1, 2), (3, 2), (12_345, 1_389)] {
+            assert_eq!(
+                travel_time_s(length_m, speed, 1000),
+                round_half_even_div(length_m as u64 * 1000, speed as u64) as u32
+            );
+        }
+        assert_eq!(travel_time_s(1000, 10_000, 1500), 150);
+    }
+}
+
+/// Seconds added to the weight of a ferry edge (`EDGE_FLAG_FERRY`) for
+/// waiting and boarding, unless `--ferry-penalty MODE=SECONDS` overrides it.
+/// Vehicles have to check in well before departure; passengers on foot or
+/// bike walk on.
+pub fn default_ferry_penalty_s(mode_name: &str) -> u32 {
+    match mode_name {
+        "car" | "truck" | "bus" | "motorcycle" | "scooter" => 900,
+        _ => 300,
+    }
 }
 
 /// Input descriptor for a single mode to be processed by Step 5.
@@ -100,6 +135,10 @@ pub struct Step5ModeInput {
     pub mode_name: String,
     pub mode_index: u8,
     pub way_attrs_path: PathBuf,
+    /// Terrain model scaling this mode's travel times; needs elevation.
+    pub gradient: Option<GradientModel>,
+    /// Seconds added to every ferry edge's weight.
+    pub ferry_penalty_s: u32,
 }
 
 /// Output paths and metadata for a single mode produced by Step 5.
@@ -111,6 +150,11 @@ pub struct ModeStep5Output {
     pub turns_path: PathBuf,
     pub mask_path: PathBuf,
     pub filtered_ebg_path: PathBuf,
+    /// Per-EBG-node gradient multipliers (permille) used for the weights;
+    /// `None` for flat weights.
+    pub time_factors: Option<Vec<u16>>,
+    /// Seconds added to every ferry edge's weight.
+    pub ferry_penalty_s: u32,
 }
 
 /// Result of Step 5 weight generation (dynamic: one entry per mode).
@@ -128,6 +172,7 @@ pub fn generate_weights(
     turn_table_path: &Path,
     nbg_geo_path: &Path,
     mode_inputs: &[Step5ModeInput],
+    elevation: Option<&ElevationData>,
     outdir: &Path,
 ) -> Result<Step5Result> {
     println!("\n  Step 5: Generating per-mode weights & masks...\n");
@@ -212,6 +257,18 @@ pub fn generate_weights(
         // Build way_id index
         let way_index = build_way_index(&way_attrs);
 
+        let time_factors = match (mode_input.gradient, elevation) {
+            (Some(model), Some(elevation)) => {
+                println!("Sampling {} gradients ({:?})...", mode_name, model);
+                let factors =
+                    crate::gradient::ebg_time_factors(&ebg_nodes, &nbg_geo, elevation, model);
+                let sloped = factors.iter().filter(|&&f| f != FLAT_PERMILLE).count();
+                println!("  {} of {} nodes on slopes", sloped, factors.len());
+                Some(factors)
+            }
+            _ => None,
+        };
+
         // Generate weights, turns, and mask
         println!("Generating {} weights...", mode_name);
         let (weights_data, turns_data, mask_data) = generate_mode_data(
@@ -222,6 +279,8 @@ pub fn generate_weights(
             &turn_table,
             &nbg_geo,
             &way_index,
+            time_factors.as_deref(),
+            mode_input.ferry_penalty_s,
             inputs_sha,
             inputs_sha_8,
         )?;
@@ -263,6 +322,8 @@ pub fn generate_weights(
             turns_path,
             mask_path,
             filtered_ebg_path: filtered_path,
+            time_factors,
+            ferry_penalty_s: mode_input.ferry_penalty_s,
         });
     }
 
@@ -294,6 +355,8 @@ fn generate_mode_data(
     turn_table: &TurnTable,
     nbg_geo: &NbgGeo,
     way_index: &HashMap<i64, WayAttr>,
+    time_factors: Option<&[u16]>,
+    ferry_penalty_s: u32,
     inputs_sha: [u8; 16],
     inputs_sha_8: [u8; 8],
 ) -> Result<(ModWeights, ModTurns, ModMask)> {
@@ -329,8 +392,7 @@ fn generate_mode_data(
         };
 
         // Determine direction: forward or reverse
-        let is_forward =
-            ebg_node.tail_nbg == nbg_edge.u_node && ebg_node.head_nbg == nbg_edge.v_node;
+        let is_forward = EbgNode::is_forward(ebg_id as u32);
 
         // Get access for this direction
         let has_access = if is_forward {
@@ -364,13 +426,12 @@ fn generate_mode_data(
 
         // Compute travel_time_s using integer math (round-half-to-even).
         //
-        // time_s = length_m * 1000 / base_speed_mmps
-        //        = (length_m * 1000) / base_speed_mmps   (round half to even)
+        // time_s = length_m * 1000 / base_speed_mmps * factor / 1000
         //
-        // For 1 km of road at 50 km/h (base_speed_mmps = 13_889):
+        // For 1 km of flat road at 50 km/h (base_speed_mmps = 13_889):
         //   length_m = 1000, time = 1_000_000 / 13_889 ≈ 71.99 → 72 s.
-        let numerator = length_m as u64 * 1000;
-        let travel_time_s = round_half_even_div(numerator, base_speed_mmps as u64) as u32;
+        let factor = time_factors.map_or(FLAT_PERMILLE, |f| f[ebg_id]);
+        let travel_time_s = travel_time_s(length_m, base_speed_mmps, factor);
 
         // Compute per_km_extra_s using integer math (round-half-to-even).
         //
@@ -387,10 +448,18 @@ fn generate_mode_data(
         let const_penalty_s =
             round_half_even_div(way_attr.output.const_penalty_ds as u64, 10) as u32;
 
-        // Total weight = travel_time + per_km_extra + const_penalty (saturating)
+        // Boarding a ferry (EBG class_bits carry the NBG edge flags).
+        let ferry_s = if ebg_node.class_bits & EDGE_FLAG_FERRY != 0 {
+            ferry_penalty_s
+        } else {
+            0
+        };
+
+        // Total weight = travel_time + per_km_extra + const_penalty + ferry (saturating)
         let weight_s = travel_time_s
             .saturating_add(per_km_extra_s)
-            .saturating_add(const_penalty_s);
+            .saturating_add(const_penalty_s)
+            .saturating_add(ferry_s);
 
         // Enforce minimum weight of 1 for accessible nodes
         weights[ebg_id] = weight_s.max(1);
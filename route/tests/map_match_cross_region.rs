@@ -22,7 +22,7 @@
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use butterfly_route::server::map_match::map_match_multi_region;
+use butterfly_route::server::map_match::{TraceOptions, map_match_multi_region};
 use butterfly_route::server::overlay::OverlayCluster;
 use butterfly_route::server::regions::RegionsState;
 
@@ -105,8 +105,13 @@ fn cross_region_trace_matches_with_overlay() {
     let regions = load_regions_with_overlay(&be, &lu, &ov);
     let trace = arlon_to_petange_trace();
 
-    let result = map_match_multi_region(&regions, "car", &trace, Some(15.0))
-        .expect("trace should match across BE/LU overlay");
+    let result = map_match_multi_region(
+        &regions,
+        "car",
+        &trace,
+        &TraceOptions::with_gps_accuracy(15.0),
+    )
+    .expect("trace should match across BE/LU overlay");
 
     eprintln!(
         "matched: {} matchings, {} tracepoints (of {} input)",
@@ -200,8 +205,13 @@ fn pure_belgian_trace_uses_single_region_fastpath() {
         (4.3577, 50.8533),
     ];
 
-    let result =
-        map_match_multi_region(&regions, "car", &trace, Some(10.0)).expect("trace should match");
+    let result = map_match_multi_region(
+        &regions,
+        "car",
+        &trace,
+        &TraceOptions::with_gps_accuracy(10.0),
+    )
+    .expect("trace should match");
 
     assert!(!result.matchings.is_empty(), "expected at least 1 matching");
     for (i, m) in result.matchings.iter().enumerate() {
@@ -24,7 +24,7 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use butterfly_route::profile_abi::Mode;
-use butterfly_route::server::map_match::{map_match, map_match_multi_region};
+use butterfly_route::server::map_match::{TraceOptions, map_match, map_match_multi_region};
 use butterfly_route::server::overlay::OverlayCluster;
 use butterfly_route::server::regions::RegionsState;
 
@@ -195,16 +195,28 @@ fn bench_map_match_cross_region() {
     let regime_legacy = {
         let trace = trace_be.clone();
         measure("brussels-legacy", N_TRIALS, || {
-            map_match(&be_state, Mode(be_mode_idx), &trace, Some(15.0), None, None)
-                .map(|r| r.matchings.len())
+            map_match(
+                &be_state,
+                Mode(be_mode_idx),
+                &trace,
+                &TraceOptions::with_gps_accuracy(15.0),
+                None,
+                None,
+            )
+            .map(|r| r.matchings.len())
         })
     };
     let regime_multi_be = {
         let trace = trace_be.clone();
         let regions_ref = regions.clone();
         measure("brussels-multi-region", N_TRIALS, || {
-            map_match_multi_region(&regions_ref, "car", &trace, Some(15.0))
-                .map(|r| r.matchings.len())
+            map_match_multi_region(
+                &regions_ref,
+                "car",
+                &trace,
+                &TraceOptions::with_gps_accuracy(15.0),
+            )
+            .map(|r| r.matchings.len())
         })
     };
 
@@ -215,8 +227,13 @@ fn bench_map_match_cross_region() {
         let trace = trace_mixed.clone();
         let regions_ref = regions.clone();
         measure("mixed-one-cross", N_TRIALS, || {
-            map_match_multi_region(&regions_ref, "car", &trace, Some(15.0))
-                .map(|r| r.matchings.len())
+            map_match_multi_region(
+                &regions_ref,
+                "car",
+                &trace,
+                &TraceOptions::with_gps_accuracy(15.0),
+            )
+            .map(|r| r.matchings.len())
         })
     };
 
@@ -226,8 +243,13 @@ fn bench_map_match_cross_region() {
         let trace = trace_zigzag.clone();
         let regions_ref = regions.clone();
         measure("zigzag-full-cross", N_TRIALS, || {
-            map_match_multi_region(&regions_ref, "car", &trace, Some(15.0))
-                .map(|r| r.matchings.len())
+            map_match_multi_region(
+                &regions_ref,
+                "car",
+                &trace,
+                &TraceOptions::with_gps_accuracy(15.0),
+            )
+            .map(|r| r.matchings.len())
         })
     };
 
@@ -72,6 +72,7 @@ time "$BIN" step4-ebg \
   --nbg-geo "$DATA/step3/nbg.geo" \
   --nbg-node-map "$DATA/step3/nbg.node_map" \
   --node-signals "$DATA/step1/node_signals.bin" \
+  --node-barriers "$DATA/step1/node_barriers.bin" \
   "${WA_ARGS[@]}" \
   "${TR_ARGS[@]}" \
   --outdir "$DATA/step4"